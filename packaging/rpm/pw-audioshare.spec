@@ -44,6 +44,9 @@ cargo build --release
 %install
 install -Dm755 target/release/%{name} %{buildroot}%{_bindir}/%{name}
 install -Dm644 data/%{name}.desktop %{buildroot}%{_datadir}/applications/%{name}.desktop
+install -Dm644 data/%{name}-search-provider.ini %{buildroot}%{_datadir}/gnome-shell/search-providers/%{name}-search-provider.ini
+install -Dm644 data/io.github.destructatron.PwAudioshare.service %{buildroot}%{_datadir}/dbus-1/services/io.github.destructatron.PwAudioshare.service
+install -Dm644 data/io.github.destructatron.PwAudioshare.gschema.xml %{buildroot}%{_datadir}/glib-2.0/schemas/io.github.destructatron.PwAudioshare.gschema.xml
 
 %check
 desktop-file-validate %{buildroot}%{_datadir}/applications/%{name}.desktop
@@ -53,6 +56,9 @@ desktop-file-validate %{buildroot}%{_datadir}/applications/%{name}.desktop
 %doc README.md
 %{_bindir}/%{name}
 %{_datadir}/applications/%{name}.desktop
+%{_datadir}/gnome-shell/search-providers/%{name}-search-provider.ini
+%{_datadir}/dbus-1/services/io.github.destructatron.PwAudioshare.service
+%{_datadir}/glib-2.0/schemas/io.github.destructatron.PwAudioshare.gschema.xml
 
 %changelog
 * Tue Dec 09 2025 Harley Richardson <destructatron2018@gmail.com> - 1.0.6-1
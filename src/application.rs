@@ -4,32 +4,78 @@ use async_channel::Receiver;
 use gtk::{gio, glib};
 use std::sync::mpsc;
 
-use crate::config::APP_ID;
-use crate::pipewire::{PipeWireThread, PwEvent};
+use crate::config::{self, DBUS_NAME};
+#[cfg(feature = "replay")]
+use crate::pipewire::mock::{self, MockBackend};
+#[cfg(feature = "replay")]
+use crate::pipewire::recording;
+use crate::pipewire::{GraphBackend, PipeWireThread, PwEvent};
 use crate::presets::PresetStore;
+use crate::search_provider::{self, SearchRequest};
+use crate::session_snapshot::SessionSnapshot;
 use crate::settings::Settings;
 use crate::tray::{self, TrayCommand, TrayHandle};
 use crate::ui::Window;
 
+/// Signals asking the app to stop, from Ctrl+C in a terminal and from
+/// logout or `systemctl --user stop`
+const SIGINT: i32 = 2;
+const SIGTERM: i32 = 15;
+
 mod imp {
     use super::*;
     use std::cell::{Cell, RefCell};
 
     pub struct Application {
-        pub pw_thread: RefCell<Option<PipeWireThread>>,
+        /// Live PipeWire connection, or a mock when replaying
+        pub backend: RefCell<Option<Box<dyn GraphBackend>>>,
         pub tray_handle: RefCell<Option<TrayHandle>>,
         pub tray_rx: RefCell<Option<mpsc::Receiver<TrayCommand>>>,
+        /// Whether the tray icon is shown; false if disabled or it failed
+        pub tray_available: Cell<bool>,
+        /// Skip starting the tray for this session (--no-tray)
+        pub no_tray: Cell<bool>,
+        /// Run without showing the window until launched again (--daemon)
+        pub daemon: Cell<bool>,
+        /// Start hidden in the tray this time, whatever the setting (--hidden)
+        pub launch_hidden: Cell<bool>,
+        /// Show the window minimized this time, whatever the setting (--minimized)
+        pub launch_minimized: Cell<bool>,
+        /// Keeps a daemon running while its window is hidden
+        pub daemon_hold: RefCell<Option<gio::ApplicationHoldGuard>>,
         /// Track if this is the first activation (startup)
         pub first_activation: Cell<bool>,
+        /// Record PipeWire events for this session (--debug-events)
+        pub debug_events: Cell<bool>,
+        /// GNOME Shell search provider exported on the session bus
+        pub search_provider: RefCell<Option<gio::RegistrationId>>,
+        /// Recording to replay instead of connecting to PipeWire (--replay-events)
+        #[cfg(feature = "replay")]
+        pub replay_path: RefCell<Option<std::path::PathBuf>>,
+        /// Ports of a generated graph to show instead (--synthetic-graph)
+        #[cfg(feature = "replay")]
+        pub synthetic_ports: Cell<u32>,
     }
 
     impl Default for Application {
         fn default() -> Self {
             Self {
-                pw_thread: RefCell::new(None),
+                backend: RefCell::new(None),
                 tray_handle: RefCell::new(None),
                 tray_rx: RefCell::new(None),
+                tray_available: Cell::new(false),
+                no_tray: Cell::new(false),
+                daemon: Cell::new(false),
+                launch_hidden: Cell::new(false),
+                launch_minimized: Cell::new(false),
+                daemon_hold: RefCell::new(None),
                 first_activation: Cell::new(true),
+                debug_events: Cell::new(false),
+                search_provider: RefCell::new(None),
+                #[cfg(feature = "replay")]
+                replay_path: RefCell::new(None),
+                #[cfg(feature = "replay")]
+                synthetic_ports: Cell::new(0),
             }
         }
     }
@@ -44,6 +90,105 @@ mod imp {
     impl ObjectImpl for Application {}
 
     impl ApplicationImpl for Application {
+        fn dbus_register(
+            &self,
+            connection: &gio::DBusConnection,
+            object_path: &str,
+        ) -> Result<(), glib::Error> {
+            self.parent_dbus_register(connection, object_path)?;
+
+            let app = self.obj().downgrade();
+            let registration = search_provider::register(connection, move |request| {
+                if let Some(app) = app.upgrade() {
+                    app.search_requested(request);
+                }
+            });
+            // Searching from the Shell is optional, the app works without it
+            match registration {
+                Ok(id) => {
+                    self.search_provider.replace(Some(id));
+                }
+                Err(e) => log::warn!("Failed to export the search provider: {}", e),
+            }
+            Ok(())
+        }
+
+        fn dbus_unregister(&self, connection: &gio::DBusConnection, object_path: &str) {
+            if let Some(id) = self.search_provider.take() {
+                if let Err(e) = connection.unregister_object(id) {
+                    log::warn!("Failed to remove the search provider: {}", e);
+                }
+            }
+            self.parent_dbus_unregister(connection, object_path);
+        }
+
+        fn handle_local_options(&self, options: &glib::VariantDict) -> glib::ExitCode {
+            if options.contains("debug-events") {
+                self.debug_events.set(true);
+            }
+            if options.contains("no-tray") {
+                self.no_tray.set(true);
+            }
+            if options.contains("daemon") {
+                // A daemon started at login must not show the window of an
+                // instance that is already running
+                let app = self.obj();
+                if let Err(e) = app.register(gio::Cancellable::NONE) {
+                    log::error!("Failed to register the application: {}", e);
+                    return glib::ExitCode::FAILURE;
+                }
+                if app.is_remote() {
+                    log::info!("PW Audioshare is already running");
+                    return glib::ExitCode::SUCCESS;
+                }
+                self.daemon.set(true);
+                self.no_tray.set(true);
+            }
+            #[cfg(feature = "replay")]
+            if let Ok(Some(path)) = options.lookup::<std::path::PathBuf>("replay-events") {
+                self.replay_path.replace(Some(path));
+            }
+            #[cfg(feature = "replay")]
+            if let Ok(Some(ports)) = options.lookup::<i32>("synthetic-graph") {
+                self.synthetic_ports.set(ports.max(0) as u32);
+            }
+
+            // Requests such as --activate-preset go to the running instance,
+            // or are run here first if this is the only one
+            let requests = super::command_line_requests(options);
+            if !requests.is_empty() {
+                let app = self.obj();
+                if let Err(e) = app.register(gio::Cancellable::NONE) {
+                    log::error!("Failed to register the application: {}", e);
+                    return glib::ExitCode::FAILURE;
+                }
+                for (action, param) in &requests {
+                    app.activate_action(action, param.as_ref());
+                }
+                if app.is_remote() {
+                    return glib::ExitCode::SUCCESS;
+                }
+            }
+
+            // Only a new instance starts hidden or minimized; a running one
+            // is left as it is rather than shown
+            if options.contains("hidden") || options.contains("minimized") {
+                let app = self.obj();
+                if let Err(e) = app.register(gio::Cancellable::NONE) {
+                    log::error!("Failed to register the application: {}", e);
+                    return glib::ExitCode::FAILURE;
+                }
+                if app.is_remote() {
+                    log::info!("PW Audioshare is already running");
+                    return glib::ExitCode::SUCCESS;
+                }
+                self.launch_hidden.set(options.contains("hidden"));
+                self.launch_minimized.set(options.contains("minimized"));
+            }
+
+            self.parent_handle_local_options(options)
+        }
+
         fn activate(&self) {
             let app = self.obj();
 
@@ -52,25 +197,43 @@ mod imp {
             if is_first {
                 self.first_activation.set(false);
 
-                // Check if we should start minimized
+                // The hidden window keeps presets connected; launching the
+                // app again shows it
+                if self.daemon.get() {
+                    log::info!("Running in the background");
+                    let _window = app.main_window_or_hidden();
+                    self.daemon_hold.replace(Some(app.hold()));
+                    return;
+                }
+
+                // Check if we should start minimized; without a tray the
+                // window could never be shown. --hidden and --minimized
+                // override the setting for this launch.
                 let settings = Settings::load();
-                if settings.start_minimized {
+                let hidden = self.launch_hidden.get()
+                    || (settings.start_minimized && !self.launch_minimized.get());
+                if hidden && self.tray_available.get() {
                     log::info!("Starting minimized to tray");
-                    // Create window but don't show it
-                    let _window = app.create_window();
+                    // Create window but don't show it; a command-line request
+                    // may have created it already
+                    let _window = app.main_window_or_hidden();
                     // Window is created but not presented - will be shown via tray
                     return;
                 }
+                if self.launch_hidden.get() {
+                    log::warn!("No tray icon to start hidden in, showing the window");
+                }
+
+                if self.launch_minimized.get() {
+                    let window = app.main_window_or_hidden();
+                    window.set_visible(true);
+                    window.minimize();
+                    return;
+                }
             }
 
             // Normal activation: show the window
-            if let Some(window) = app.active_window() {
-                window.set_visible(true);
-                window.present();
-            } else {
-                let window = app.create_window();
-                window.present();
-            }
+            app.show_main_window();
         }
 
         fn startup(&self) {
@@ -80,18 +243,32 @@ mod imp {
 
             // Set up application actions
             app.setup_actions();
+            crate::ui::load_css();
 
             // Start PipeWire thread
             app.start_pipewire();
 
-            // Start system tray
-            app.start_tray();
+            // Quit normally when killed, so the session is saved as ended
+            app.quit_on_signals();
+
+            // Start system tray, unless disabled
+            if self.no_tray.get() || !Settings::load().show_tray {
+                log::info!("System tray disabled");
+            } else {
+                app.start_tray();
+            }
         }
 
         fn shutdown(&self) {
+            // Mark the session as ended normally while its graph is still known
+            match self.obj().main_window() {
+                Some(window) => window.save_session_on_quit(),
+                None => SessionSnapshot::mark_clean_exit(),
+            }
+
             // Stop PipeWire thread
-            if let Some(mut thread) = self.pw_thread.take() {
-                thread.shutdown();
+            if let Some(mut backend) = self.backend.take() {
+                backend.shutdown();
             }
 
             self.parent_shutdown();
@@ -110,10 +287,124 @@ glib::wrapper! {
 
 impl Application {
     pub fn new() -> Self {
-        glib::Object::builder()
-            .property("application-id", APP_ID)
-            .property("flags", gio::ApplicationFlags::FLAGS_NONE)
-            .build()
+        // A separate profile runs beside the usual instance instead of
+        // handing its options to it
+        let flags = if config::is_separate_profile() {
+            gio::ApplicationFlags::NON_UNIQUE
+        } else {
+            gio::ApplicationFlags::FLAGS_NONE
+        };
+        let app: Self = glib::Object::builder()
+            .property("application-id", DBUS_NAME)
+            .property("flags", flags)
+            .build();
+
+        // Applied in main before settings load; listed here for --help
+        app.add_main_option(
+            "config",
+            glib::Char(0),
+            glib::OptionFlags::NONE,
+            glib::OptionArg::Filename,
+            "Keep settings, presets and scripts in DIR instead of ~/.config/pw-audioshare",
+            Some("DIR"),
+        );
+
+        app.add_main_option(
+            "debug-events",
+            glib::Char(0),
+            glib::OptionFlags::NONE,
+            glib::OptionArg::None,
+            "Record every PipeWire event for the event debugger",
+            None,
+        );
+        app.add_main_option(
+            "show",
+            glib::Char(0),
+            glib::OptionFlags::NONE,
+            glib::OptionArg::None,
+            "Show the window of the running instance",
+            None,
+        );
+        app.add_main_option(
+            "activate-preset",
+            glib::Char(0),
+            glib::OptionFlags::NONE,
+            glib::OptionArg::String,
+            "Activate a preset for auto-connecting, in the running instance if there is one",
+            Some("NAME"),
+        );
+        app.add_main_option(
+            "preset",
+            glib::Char(0),
+            glib::OptionFlags::NONE,
+            glib::OptionArg::String,
+            "Same as --activate-preset",
+            Some("NAME"),
+        );
+        app.add_main_option(
+            "deactivate-preset",
+            glib::Char(0),
+            glib::OptionFlags::NONE,
+            glib::OptionArg::None,
+            "Deactivate the active preset",
+            None,
+        );
+        app.add_main_option(
+            "minimized",
+            glib::Char(0),
+            glib::OptionFlags::NONE,
+            glib::OptionArg::None,
+            "Start with the window minimized, whatever the Start Minimized to Tray setting",
+            None,
+        );
+        app.add_main_option(
+            "hidden",
+            glib::Char(0),
+            glib::OptionFlags::NONE,
+            glib::OptionArg::None,
+            "Start hidden in the system tray, whatever the Start Minimized to Tray setting",
+            None,
+        );
+        app.add_main_option(
+            "daemon",
+            glib::Char(0),
+            glib::OptionFlags::NONE,
+            glib::OptionArg::None,
+            "Run in the background without a window, keeping the active preset connected",
+            None,
+        );
+        app.add_main_option(
+            "no-tray",
+            glib::Char(0),
+            glib::OptionFlags::NONE,
+            glib::OptionArg::None,
+            "Run without a system tray icon; closing the window quits",
+            None,
+        );
+        #[cfg(feature = "replay")]
+        app.add_main_option(
+            "replay-events",
+            glib::Char(0),
+            glib::OptionFlags::NONE,
+            glib::OptionArg::Filename,
+            "Replay events exported by the event debugger instead of connecting to PipeWire",
+            Some("FILE"),
+        );
+        #[cfg(feature = "replay")]
+        app.add_main_option(
+            "synthetic-graph",
+            glib::Char(0),
+            glib::OptionFlags::NONE,
+            glib::OptionArg::Int,
+            "Show a generated graph with this many ports instead of connecting to PipeWire",
+            Some("PORTS"),
+        );
+
+        // When started by D-Bus for the search provider, stay long enough to
+        // receive the call that started it
+        app.set_inactivity_timeout(10_000);
+
+        app
     }
 
     /// Create the main window
@@ -121,17 +412,22 @@ impl Application {
         let window = Window::new(self.upcast_ref());
 
         // Give the window the command sender
-        if let Some(thread) = self.imp().pw_thread.borrow().as_ref() {
-            window.set_command_sender(thread.command_sender());
+        if let Some(backend) = self.imp().backend.borrow().as_ref() {
+            window.set_command_sender(backend.command_sender());
+        }
+
+        if self.imp().debug_events.get() {
+            window.set_event_recording(true);
+        }
+
+        window.set_tray_available(self.imp().tray_available.get());
+        window.set_daemon_mode(self.imp().daemon.get());
+        if let Some(tray_handle) = self.imp().tray_handle.borrow().as_ref() {
+            window.set_tray_updater(tray_handle.updater());
         }
 
-        // Override close-request to minimize to tray instead of quitting
-        window.connect_close_request(|window| {
-            // Hide the window instead of closing
-            window.set_visible(false);
-            // Stop the event from propagating (prevents actual close)
-            glib::Propagation::Stop
-        });
+        // Minimize to tray, quit or ask, as configured in settings
+        window.connect_close_request(|window| window.close_requested());
 
         window
     }
@@ -149,24 +445,141 @@ impl Application {
         ));
         self.add_action(&action_quit);
 
+        // Actions for command-line requests forwarded from another instance
+        let action_show = gio::SimpleAction::new("show-window", None);
+        action_show.connect_activate(glib::clone!(
+            #[weak(rename_to = app)]
+            self,
+            move |_, _| {
+                // Shown now, so starting minimized no longer applies
+                app.imp().first_activation.set(false);
+                app.show_main_window();
+            }
+        ));
+        self.add_action(&action_show);
+
+        let action_activate_preset =
+            gio::SimpleAction::new("activate-preset", Some(glib::VariantTy::STRING));
+        action_activate_preset.connect_activate(glib::clone!(
+            #[weak(rename_to = app)]
+            self,
+            move |_, param| {
+                if let Some(name) = param.and_then(|v| v.get::<String>()) {
+                    app.main_window_or_hidden().activate_preset(&name);
+                }
+            }
+        ));
+        self.add_action(&action_activate_preset);
+
+        let action_deactivate_preset = gio::SimpleAction::new("deactivate-preset", None);
+        action_deactivate_preset.connect_activate(glib::clone!(
+            #[weak(rename_to = app)]
+            self,
+            move |_, _| {
+                app.main_window_or_hidden().deactivate_preset();
+            }
+        ));
+        self.add_action(&action_deactivate_preset);
+
+        let action_open_server =
+            gio::SimpleAction::new("open-server", Some(glib::VariantTy::STRING));
+        action_open_server.connect_activate(glib::clone!(
+            #[weak(rename_to = app)]
+            self,
+            move |_, param| {
+                if let Some(server) = param.and_then(|v| v.get::<String>()) {
+                    app.open_server_tab(&server);
+                }
+            }
+        ));
+        self.add_action(&action_open_server);
+
+        // Connect again after PipeWire was unreachable; the parameter is the
+        // server of a server window, or empty for the main connection
+        let action_reconnect = gio::SimpleAction::new("reconnect", Some(glib::VariantTy::STRING));
+        action_reconnect.connect_activate(glib::clone!(
+            #[weak(rename_to = app)]
+            self,
+            move |_, param| {
+                let server = param.and_then(|v| v.get::<String>()).unwrap_or_default();
+                app.reconnect(&server);
+            }
+        ));
+        self.add_action(&action_reconnect);
+
         // Set up keyboard shortcuts
-        self.set_accels_for_action("app.quit", &["<Ctrl>q"]);
-        self.set_accels_for_action("win.connect-selected", &["<Ctrl>Return"]);
+        crate::ui::register_accels(self);
+    }
+
+    /// Quit through shutdown on SIGINT and SIGTERM rather than dying,
+    /// which would leave the session snapshot looking like a crash
+    fn quit_on_signals(&self) {
+        for signal in [SIGINT, SIGTERM] {
+            glib::unix_signal_add_local(
+                signal,
+                glib::clone!(
+                    #[weak(rename_to = app)]
+                    self,
+                    #[upgrade_or]
+                    glib::ControlFlow::Break,
+                    move || {
+                        log::info!("Quitting on signal {}", signal);
+                        app.quit();
+                        glib::ControlFlow::Break
+                    }
+                ),
+            );
+        }
     }
 
     /// Start the PipeWire thread and set up event handling
     fn start_pipewire(&self) {
         let (event_tx, event_rx) = async_channel::unbounded::<PwEvent>();
 
-        // Start the PipeWire thread
-        match PipeWireThread::spawn(event_tx) {
-            Ok(thread) => {
-                self.imp().pw_thread.replace(Some(thread));
-                log::info!("PipeWire thread started");
+        #[cfg(feature = "replay")]
+        if let Some(path) = self.imp().replay_path.take() {
+            // Replay a recording into an in-memory graph instead of PipeWire
+            let backend = recording::load_recording(&path)
+                .and_then(|script| MockBackend::spawn_with_script(script, event_tx.clone()));
+            match backend {
+                Ok(backend) => {
+                    log::info!("Replaying events from {}", path.display());
+                    self.imp().backend.replace(Some(Box::new(backend)));
+                }
+                Err(e) => {
+                    log::error!("Failed to replay {}: {}", path.display(), e);
+                    return;
+                }
             }
-            Err(e) => {
-                log::error!("Failed to start PipeWire thread: {}", e);
-                return;
+        }
+
+        #[cfg(feature = "replay")]
+        if self.imp().backend.borrow().is_none() && self.imp().synthetic_ports.get() > 0 {
+            // A large generated graph, to test filtering and scrolling
+            let ports = self.imp().synthetic_ports.get();
+            match MockBackend::spawn_with_script(mock::synthetic_graph(ports), event_tx.clone()) {
+                Ok(backend) => {
+                    log::info!("Showing a synthetic graph of {} ports", ports);
+                    self.imp().backend.replace(Some(Box::new(backend)));
+                }
+                Err(e) => {
+                    log::error!("Failed to start the synthetic graph: {}", e);
+                    return;
+                }
+            }
+        }
+
+        // Start the PipeWire thread
+        if self.imp().backend.borrow().is_none() {
+            match PipeWireThread::spawn(event_tx) {
+                Ok(thread) => {
+                    self.imp().backend.replace(Some(Box::new(thread)));
+                    log::info!("PipeWire thread started");
+                }
+                Err(e) => {
+                    log::error!("Failed to start PipeWire thread: {}", e);
+                    return;
+                }
             }
         }
 
@@ -183,17 +596,88 @@ impl Application {
     /// Process events from PipeWire thread
     async fn process_pw_events(&self, rx: Receiver<PwEvent>) {
         while let Ok(event) = rx.recv().await {
-            // Get any window, not just the "active" one.
-            // active_window() returns None when the window is hidden (e.g., minimized to tray),
-            // but windows() returns all toplevel windows regardless of visibility.
-            if let Some(window) = self.windows().into_iter().next() {
-                if let Some(window) = window.downcast_ref::<Window>() {
+            // Found even while hidden (e.g., minimized to tray), unlike active_window()
+            if let Some(window) = self.main_window() {
+                window.handle_pw_event(event);
+            }
+        }
+
+        log::debug!("PipeWire event channel closed");
+    }
+
+    /// Open another PipeWire server, such as `pipewire-1` for a second
+    /// realtime instance, in a tab of the main window. The tab's window
+    /// keeps its own connection and stays hidden.
+    fn open_server_tab(&self, server: &str) {
+        let main_window = self.main_window_or_hidden();
+        if main_window.select_server_tab(server) {
+            main_window.present();
+            return;
+        }
+
+        let window = Window::new_for_server(self.upcast_ref(), server);
+        if !Self::connect_server_window(&window, server) {
+            window.destroy();
+            return;
+        }
+        main_window.add_server_tab(&window);
+        main_window.set_visible(true);
+        main_window.present();
+    }
+
+    /// Start a PipeWire thread for a server window and feed it its events
+    fn connect_server_window(window: &Window, server: &str) -> bool {
+        let (event_tx, event_rx) = async_channel::unbounded::<PwEvent>();
+        let thread = match PipeWireThread::spawn_for_server(event_tx, Some(server.to_string())) {
+            Ok(thread) => thread,
+            Err(e) => {
+                log::error!("Failed to start PipeWire thread for {}: {}", server, e);
+                return false;
+            }
+        };
+        log::info!("PipeWire thread for {} started", server);
+
+        window.set_command_sender(thread.command_sender());
+        window.set_server_backend(thread);
+
+        glib::spawn_future_local(glib::clone!(
+            #[weak]
+            window,
+            async move {
+                while let Ok(event) = event_rx.recv().await {
                     window.handle_pw_event(event);
                 }
             }
+        ));
+        true
+    }
+
+    /// Replace a stopped PipeWire thread with a new one, for the main
+    /// window or the window of `server`
+    fn reconnect(&self, server: &str) {
+        if !server.is_empty() {
+            let window = self
+                .windows()
+                .into_iter()
+                .filter_map(|window| window.downcast::<Window>().ok())
+                .find(|window| window.server().as_deref() == Some(server));
+            if let Some(window) = window {
+                window.close_server_connection();
+                Self::connect_server_window(&window, server);
+            }
+            return;
         }
 
-        log::debug!("PipeWire event channel closed");
+        log::info!("Reconnecting to PipeWire");
+        if let Some(mut backend) = self.imp().backend.take() {
+            backend.shutdown();
+        }
+        self.start_pipewire();
+        if let (Some(window), Some(backend)) =
+            (self.main_window(), self.imp().backend.borrow().as_ref())
+        {
+            window.set_command_sender(backend.command_sender());
+        }
     }
 
     /// Start the system tray
@@ -206,6 +690,8 @@ impl Application {
 
         self.imp().tray_handle.replace(Some(tray_handle));
         self.imp().tray_rx.replace(Some(tray_rx));
+        // Until the tray reports otherwise
+        self.imp().tray_available.set(true);
 
         log::info!("System tray started");
 
@@ -225,6 +711,39 @@ impl Application {
         );
     }
 
+    /// The main window, even while it is hidden in the tray
+    fn main_window(&self) -> Option<Window> {
+        self.windows()
+            .into_iter()
+            .filter_map(|window| window.downcast::<Window>().ok())
+            .find(|window| !window.is_server_window())
+    }
+
+    /// The main window, created without showing it if there is none yet
+    fn main_window_or_hidden(&self) -> Window {
+        self.main_window().unwrap_or_else(|| self.create_window())
+    }
+
+    /// Show and raise the main window, creating it if needed
+    fn show_main_window(&self) {
+        let window = self.main_window_or_hidden();
+        window.set_visible(true);
+        window.present();
+    }
+
+    /// Act on a result chosen in the GNOME Shell overview. A window is
+    /// created, hidden, if the app was started just for the search.
+    fn search_requested(&self, request: SearchRequest) {
+        match request {
+            SearchRequest::ActivatePreset(name) => {
+                // A later launch shows this window rather than starting minimized again
+                self.imp().first_activation.set(false);
+                self.main_window_or_hidden().activate_preset(&name);
+            }
+            SearchRequest::ShowWindow => self.activate(),
+        }
+    }
+
     /// Process pending tray commands
     fn process_tray_commands(&self) {
         let rx = self.imp().tray_rx.borrow();
@@ -234,25 +753,56 @@ impl Application {
                 match cmd {
                     TrayCommand::Show => {
                         log::debug!("Tray: Show window");
-                        if let Some(window) = self.active_window() {
-                            window.set_visible(true);
-                            window.present();
-                        } else {
-                            // No window exists, create one
-                            let window = self.create_window();
-                            window.present();
-                        }
+                        self.show_main_window();
                     }
                     TrayCommand::Quit => {
                         log::debug!("Tray: Quit application");
                         self.quit();
                     }
+                    TrayCommand::CyclePreset(step) => {
+                        if let Some(window) = self.main_window() {
+                            window.tray_scrolled(step);
+                        }
+                    }
+                    TrayCommand::ToggleWindow => {
+                        if let Some(window) = self.main_window() {
+                            window.tray_middle_clicked();
+                        }
+                    }
+                    TrayCommand::ToggleConnection(key) => {
+                        if let Some(window) = self.main_window() {
+                            window.toggle_recent_connection(&key);
+                        }
+                    }
+                    TrayCommand::Unavailable(reason) => {
+                        self.imp().tray_available.set(false);
+                        self.main_window_or_hidden().show_tray_unavailable(&reason);
+                    }
                 }
             }
         }
     }
 }
 
+/// Application actions requested by command-line options, with their parameters
+fn command_line_requests(
+    options: &glib::VariantDict,
+) -> Vec<(&'static str, Option<glib::Variant>)> {
+    let mut requests = Vec::new();
+    for option in ["activate-preset", "preset"] {
+        if let Ok(Some(name)) = options.lookup::<String>(option) {
+            requests.push(("activate-preset", Some(name.to_variant())));
+        }
+    }
+    if options.contains("deactivate-preset") {
+        requests.push(("deactivate-preset", None));
+    }
+    if options.contains("show") {
+        requests.push(("show-window", None));
+    }
+    requests
+}
+
 impl Default for Application {
     fn default() -> Self {
         Self::new()
@@ -1,8 +1,59 @@
-/// Application ID
+use std::path::PathBuf;
+use std::sync::OnceLock;
+
+/// Application ID, also the name of the config and state directories
 pub const APP_ID: &str = "pw-audioshare";
 
+/// GApplication ID and D-Bus name, which make a second launch reach the
+/// running instance
+pub const DBUS_NAME: &str = "io.github.destructatron.PwAudioshare";
+
 /// Application name for display
 pub const APP_NAME: &str = "PW Audioshare";
 
 /// Application version
 pub const VERSION: &str = env!("CARGO_PKG_VERSION");
+
+/// Environment variable naming another config directory, like --config
+pub const CONFIG_DIR_ENV: &str = "PW_AUDIOSHARE_CONFIG_DIR";
+
+/// Config directory given with --config, set once before anything is loaded
+static CONFIG_DIR_OVERRIDE: OnceLock<PathBuf> = OnceLock::new();
+
+/// Use `dir` for settings, presets and scripts for the rest of this run
+pub fn set_config_dir(dir: PathBuf) {
+    if CONFIG_DIR_OVERRIDE.set(dir).is_err() {
+        log::warn!("Config directory already set");
+    }
+}
+
+/// The directory another location was chosen for with --config or the
+/// environment, if any
+fn custom_config_dir() -> Option<PathBuf> {
+    if let Some(dir) = CONFIG_DIR_OVERRIDE.get() {
+        return Some(dir.clone());
+    }
+    std::env::var_os(CONFIG_DIR_ENV)
+        .filter(|dir| !dir.is_empty())
+        .map(PathBuf::from)
+}
+
+/// Directory of settings.json, presets.json and scripts: the one given
+/// with --config, then $PW_AUDIOSHARE_CONFIG_DIR, then
+/// ~/.config/pw-audioshare
+pub fn config_dir() -> Option<PathBuf> {
+    custom_config_dir().or_else(|| Some(dirs::config_dir()?.join(APP_ID)))
+}
+
+/// Directory of state kept between runs, such as the session snapshot:
+/// the custom config directory if one was chosen, so profiles don't share
+/// it, else ~/.local/state/pw-audioshare
+pub fn state_dir() -> Option<PathBuf> {
+    custom_config_dir().or_else(|| Some(dirs::state_dir()?.join(APP_ID)))
+}
+
+/// Whether this run uses its own config directory, so it runs beside the
+/// usual instance instead of handing over to it
+pub fn is_separate_profile() -> bool {
+    custom_config_dir().is_some()
+}
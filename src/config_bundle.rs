@@ -0,0 +1,234 @@
+use serde::{Deserialize, Serialize};
+use std::collections::BTreeMap;
+use std::fs;
+use std::path::Path;
+
+use crate::presets::PresetStore;
+use crate::settings::Settings;
+
+/// Version of the bundle format, raised on incompatible changes
+const FORMAT_VERSION: u32 = 1;
+
+/// Settings, presets (with their plug triggers) and user scripts in one
+/// file, for moving to another machine or keeping a backup
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ConfigBundle {
+    pub format: u32,
+    /// Version of PW Audioshare that wrote the bundle
+    #[serde(default)]
+    pub app_version: String,
+    pub settings: Settings,
+    pub presets: PresetStore,
+    /// Lua scripts by file name
+    #[serde(default)]
+    pub scripts: BTreeMap<String, String>,
+}
+
+/// How an imported bundle combines with the current configuration
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ImportMode {
+    /// Add presets, triggers, saved filters, pinned connections, tunnels
+    /// and scripts that don't exist yet; everything present is kept
+    Merge,
+    /// Replace the settings, presets and same-named scripts
+    Overwrite,
+}
+
+/// What an import changed, for the announcement
+#[derive(Debug, Default)]
+pub struct ImportSummary {
+    pub presets: usize,
+    pub scripts: usize,
+}
+
+impl ConfigBundle {
+    /// Collect the current configuration
+    pub fn collect(settings: &Settings, presets: &PresetStore) -> Self {
+        Self {
+            format: FORMAT_VERSION,
+            app_version: crate::config::VERSION.to_string(),
+            settings: settings.clone(),
+            presets: presets.clone(),
+            scripts: read_scripts(),
+        }
+    }
+
+    pub fn write(&self, path: &Path) -> Result<(), String> {
+        let content =
+            serde_json::to_string_pretty(self).map_err(|e| format!("Failed to serialize: {}", e))?;
+        fs::write(path, content).map_err(|e| format!("Failed to write {}: {}", path.display(), e))
+    }
+
+    pub fn read(path: &Path) -> Result<Self, String> {
+        let content = fs::read_to_string(path)
+            .map_err(|e| format!("Failed to read {}: {}", path.display(), e))?;
+        let bundle: Self = serde_json::from_str(&content)
+            .map_err(|_| format!("{} is not a PW Audioshare configuration", path.display()))?;
+        if bundle.format > FORMAT_VERSION {
+            return Err(format!(
+                "The configuration was exported by a newer version ({}); update PW Audioshare first",
+                bundle.app_version
+            ));
+        }
+        Ok(bundle)
+    }
+
+    /// Combine the bundle with the current configuration, returning what
+    /// was added or replaced. Scripts run on the next start with the user's
+    /// permissions, so they are only written with `with_scripts`, at once;
+    /// the caller saves the settings and presets.
+    pub fn apply(
+        self,
+        mode: ImportMode,
+        with_scripts: bool,
+        settings: &mut Settings,
+        presets: &mut PresetStore,
+    ) -> Result<ImportSummary, String> {
+        let mut summary = ImportSummary::default();
+        match mode {
+            ImportMode::Overwrite => {
+                summary.presets = self.presets.presets.len();
+                *settings = self.settings;
+                *presets = self.presets;
+            }
+            ImportMode::Merge => {
+                for (name, mut preset) in self.presets.presets {
+                    if !presets.presets.contains_key(&name) {
+                        let taken = preset
+                            .accelerator
+                            .as_deref()
+                            .is_some_and(|accel| presets.accelerator_preset(accel).is_some());
+                        if taken {
+                            preset.accelerator = None;
+                        }
+                        presets.add_preset(preset);
+                        summary.presets += 1;
+                    }
+                }
+                for trigger in self.presets.route_triggers {
+                    let taken = presets
+                        .route_trigger(&trigger.device, &trigger.route, trigger.plugged)
+                        .is_some();
+                    if !taken && presets.presets.contains_key(&trigger.preset) {
+                        presets.route_triggers.push(trigger);
+                    }
+                }
+
+                let incoming = self.settings;
+                for saved in incoming.saved_filters {
+                    if !settings.saved_filters.iter().any(|f| f.name == saved.name) {
+                        settings.saved_filters.push(saved);
+                    }
+                }
+                for pinned in incoming.pinned_connections {
+                    let exists = settings.pinned_connections.iter().any(|p| {
+                        p.output_node == pinned.output_node
+                            && p.output_port == pinned.output_port
+                            && p.input_node == pinned.input_node
+                            && p.input_port == pinned.input_port
+                    });
+                    if !exists {
+                        settings.pinned_connections.push(pinned);
+                    }
+                }
+                for tunnel in incoming.tunnels {
+                    if !settings.tunnels.iter().any(|t| t.name == tunnel.name) {
+                        settings.tunnels.push(tunnel);
+                    }
+                }
+            }
+        }
+
+        if with_scripts {
+            summary.scripts = write_scripts(&self.scripts, mode == ImportMode::Overwrite)?;
+        }
+        Ok(summary)
+    }
+}
+
+/// The user's Lua scripts by file name
+#[cfg(feature = "scripting")]
+fn read_scripts() -> BTreeMap<String, String> {
+    let Some(dir) = crate::scripting::scripts_dir() else {
+        return BTreeMap::new();
+    };
+    let Ok(entries) = fs::read_dir(&dir) else {
+        return BTreeMap::new();
+    };
+    entries
+        .flatten()
+        .map(|entry| entry.path())
+        .filter(|path| path.extension().is_some_and(|ext| ext == "lua"))
+        .filter_map(|path| {
+            let name = path.file_name()?.to_str()?.to_string();
+            Some((name, fs::read_to_string(&path).ok()?))
+        })
+        .collect()
+}
+
+#[cfg(not(feature = "scripting"))]
+fn read_scripts() -> BTreeMap<String, String> {
+    BTreeMap::new()
+}
+
+/// Write imported scripts, keeping existing files unless `replace`.
+/// Returns how many were written.
+#[cfg(feature = "scripting")]
+fn write_scripts(scripts: &BTreeMap<String, String>, replace: bool) -> Result<usize, String> {
+    if scripts.is_empty() {
+        return Ok(0);
+    }
+    let dir = crate::scripting::scripts_dir().ok_or("Could not determine config directory")?;
+    fs::create_dir_all(&dir).map_err(|e| format!("Failed to create scripts dir: {}", e))?;
+
+    let mut written = 0;
+    for (name, content) in scripts {
+        // Only plain file names, never a path out of the directory
+        if Path::new(name).file_name().and_then(|n| n.to_str()) != Some(name.as_str()) {
+            log::warn!("Skipping script with invalid name {:?}", name);
+            continue;
+        }
+        let path = dir.join(name);
+        if path.exists() && !replace {
+            continue;
+        }
+        fs::write(&path, content).map_err(|e| format!("Failed to write {}: {}", name, e))?;
+        written += 1;
+    }
+    Ok(written)
+}
+
+#[cfg(not(feature = "scripting"))]
+fn write_scripts(scripts: &BTreeMap<String, String>, _replace: bool) -> Result<usize, String> {
+    if !scripts.is_empty() {
+        log::info!("Skipping {} scripts; built without scripting", scripts.len());
+    }
+    Ok(0)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn bundle_with_script() -> ConfigBundle {
+        ConfigBundle {
+            format: FORMAT_VERSION,
+            app_version: String::new(),
+            settings: Settings::default(),
+            presets: PresetStore::default(),
+            scripts: BTreeMap::from([("evil.lua".to_string(), "os.execute('true')".to_string())]),
+        }
+    }
+
+    #[test]
+    fn scripts_are_left_out_unless_asked_for() {
+        for mode in [ImportMode::Merge, ImportMode::Overwrite] {
+            let mut settings = Settings::default();
+            let mut presets = PresetStore::default();
+            let summary = bundle_with_script()
+                .apply(mode, false, &mut settings, &mut presets)
+                .unwrap();
+            assert_eq!(summary.scripts, 0);
+        }
+    }
+}
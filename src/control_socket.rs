@@ -0,0 +1,320 @@
+use std::io::{BufRead, BufReader, ErrorKind, Write};
+use std::os::unix::net::{UnixListener, UnixStream};
+use std::path::{Path, PathBuf};
+use std::sync::atomic::{AtomicBool, AtomicU64, Ordering};
+use std::sync::{mpsc, Arc};
+use std::thread;
+
+use serde_json::{json, Value};
+
+/// File name of the socket in `$XDG_RUNTIME_DIR`
+const SOCKET_NAME: &str = "pw-audioshare.sock";
+
+/// A JSON-RPC error returned to the client
+#[derive(Debug, Clone)]
+pub struct RpcError {
+    pub code: i64,
+    pub message: String,
+}
+
+impl RpcError {
+    pub fn method_not_found(method: &str) -> Self {
+        Self {
+            code: -32601,
+            message: format!("Unknown method {}", method),
+        }
+    }
+
+    pub fn invalid_params(message: impl Into<String>) -> Self {
+        Self {
+            code: -32602,
+            message: message.into(),
+        }
+    }
+
+    /// The request was understood but could not be carried out
+    pub fn failed(message: impl Into<String>) -> Self {
+        Self {
+            code: -32000,
+            message: message.into(),
+        }
+    }
+}
+
+/// A connected client; messages sent to it are written by its own thread so
+/// a slow reader never blocks the UI
+#[derive(Debug, Clone)]
+pub struct ControlClient {
+    id: u64,
+    tx: mpsc::Sender<String>,
+}
+
+impl ControlClient {
+    pub fn id(&self) -> u64 {
+        self.id
+    }
+
+    /// Send a notification. Returns false once the client has disconnected.
+    pub fn notify(&self, method: &str, params: Value) -> bool {
+        let message = json!({ "jsonrpc": "2.0", "method": method, "params": params });
+        self.tx.send(message.to_string()).is_ok()
+    }
+}
+
+/// A method call from a client, answered on the GTK main thread
+#[derive(Debug)]
+pub struct ControlRequest {
+    pub method: String,
+    pub params: Value,
+    pub client: ControlClient,
+    /// None for notifications, which get no reply
+    id: Option<Value>,
+}
+
+impl ControlRequest {
+    /// Answer the request
+    pub fn reply(self, result: Result<Value, RpcError>) {
+        let Some(id) = self.id else {
+            return;
+        };
+        let message = match result {
+            Ok(result) => json!({ "jsonrpc": "2.0", "id": id, "result": result }),
+            Err(e) => json!({
+                "jsonrpc": "2.0",
+                "id": id,
+                "error": { "code": e.code, "message": e.message },
+            }),
+        };
+        let _ = self.client.tx.send(message.to_string());
+    }
+}
+
+/// The listening socket; removed when dropped
+pub struct ControlServer {
+    path: PathBuf,
+    stop: Arc<AtomicBool>,
+}
+
+impl ControlServer {
+    pub fn path(&self) -> &Path {
+        &self.path
+    }
+}
+
+impl Drop for ControlServer {
+    fn drop(&mut self) {
+        self.stop.store(true, Ordering::SeqCst);
+        // Wake the accept loop so it sees the stop flag
+        let _ = UnixStream::connect(&self.path);
+        let _ = std::fs::remove_file(&self.path);
+    }
+}
+
+/// Where the control socket is created
+pub fn socket_path() -> Option<PathBuf> {
+    Some(dirs::runtime_dir()?.join(SOCKET_NAME))
+}
+
+/// Start listening on the control socket. Requests are passed to `request_tx`
+/// one JSON-RPC message per line.
+pub fn spawn(
+    request_tx: async_channel::Sender<ControlRequest>,
+) -> Result<ControlServer, anyhow::Error> {
+    let path = socket_path().ok_or_else(|| anyhow::anyhow!("XDG_RUNTIME_DIR is not set"))?;
+
+    // A socket left behind by a crash can be replaced, one in use can't
+    if path.exists() {
+        if UnixStream::connect(&path).is_ok() {
+            anyhow::bail!("{} is in use by another program", path.display());
+        }
+        std::fs::remove_file(&path)?;
+    }
+
+    let listener = UnixListener::bind(&path)?;
+    let stop = Arc::new(AtomicBool::new(false));
+    let next_client_id = AtomicU64::new(1);
+
+    let thread_stop = stop.clone();
+    thread::spawn(move || {
+        for stream in listener.incoming() {
+            if thread_stop.load(Ordering::SeqCst) {
+                break;
+            }
+            match stream {
+                Ok(stream) => {
+                    let id = next_client_id.fetch_add(1, Ordering::SeqCst);
+                    serve_client(id, stream, request_tx.clone());
+                }
+                Err(e) => log::warn!("Control socket accept failed: {}", e),
+            }
+        }
+        log::debug!("Control socket closed");
+    });
+
+    log::info!("Control socket listening on {}", path.display());
+    Ok(ControlServer { path, stop })
+}
+
+/// Read requests from one client and write its replies, each on its own thread
+fn serve_client(
+    id: u64,
+    stream: UnixStream,
+    request_tx: async_channel::Sender<ControlRequest>,
+) {
+    let mut writer = match stream.try_clone() {
+        Ok(writer) => writer,
+        Err(e) => {
+            log::warn!("Control socket client failed: {}", e);
+            return;
+        }
+    };
+    let (tx, rx) = mpsc::channel::<String>();
+
+    thread::spawn(move || {
+        for message in rx {
+            if writer
+                .write_all(message.as_bytes())
+                .and_then(|_| writer.write_all(b"\n"))
+                .is_err()
+            {
+                break;
+            }
+        }
+    });
+
+    let client = ControlClient { id, tx };
+    thread::spawn(move || {
+        let reader = BufReader::new(stream);
+        for line in reader.lines() {
+            let line = match line {
+                Ok(line) => line,
+                Err(e) if e.kind() == ErrorKind::Interrupted => continue,
+                Err(_) => break,
+            };
+            if line.trim().is_empty() {
+                continue;
+            }
+
+            let request = match parse_request(&line, &client) {
+                Ok(request) => request,
+                Err(reply) => {
+                    let _ = client.tx.send(reply.to_string());
+                    continue;
+                }
+            };
+            if request_tx.send_blocking(request).is_err() {
+                break;
+            }
+        }
+    });
+}
+
+/// Parse one line into a request, or the error reply to send back
+fn parse_request(line: &str, client: &ControlClient) -> Result<ControlRequest, Value> {
+    let error = |code: i64, message: &str| {
+        json!({
+            "jsonrpc": "2.0",
+            "id": Value::Null,
+            "error": { "code": code, "message": message },
+        })
+    };
+
+    let message: Value = serde_json::from_str(line).map_err(|_| error(-32700, "Parse error"))?;
+    let method = message
+        .get("method")
+        .and_then(Value::as_str)
+        .ok_or_else(|| error(-32600, "Invalid request"))?;
+
+    Ok(ControlRequest {
+        method: method.to_string(),
+        params: message.get("params").cloned().unwrap_or(Value::Null),
+        client: client.clone(),
+        id: message.get("id").cloned(),
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn client() -> (ControlClient, mpsc::Receiver<String>) {
+        let (tx, rx) = mpsc::channel();
+        (ControlClient { id: 1, tx }, rx)
+    }
+
+    fn error_code(reply: &Value) -> Option<i64> {
+        reply["error"]["code"].as_i64()
+    }
+
+    #[test]
+    fn parses_a_method_call() {
+        let (client, _rx) = client();
+        let request = parse_request(
+            r#"{"jsonrpc":"2.0","id":7,"method":"connect","params":{"output_port_id":3}}"#,
+            &client,
+        )
+        .unwrap();
+
+        assert_eq!(request.method, "connect");
+        assert_eq!(request.params, json!({ "output_port_id": 3 }));
+        assert_eq!(request.id, Some(json!(7)));
+        assert_eq!(request.client.id(), 1);
+    }
+
+    #[test]
+    fn missing_params_are_null() {
+        let (client, _rx) = client();
+        let request = parse_request(r#"{"id":"a","method":"list_ports"}"#, &client).unwrap();
+
+        assert_eq!(request.params, Value::Null);
+        assert_eq!(request.id, Some(json!("a")));
+    }
+
+    #[test]
+    fn notification_has_no_id_and_gets_no_reply() {
+        let (client, rx) = client();
+        let request = parse_request(r#"{"method":"subscribe"}"#, &client).unwrap();
+        assert_eq!(request.id, None);
+
+        request.reply(Ok(json!(true)));
+        assert!(rx.try_recv().is_err());
+    }
+
+    #[test]
+    fn invalid_json_is_a_parse_error() {
+        let (client, _rx) = client();
+        for line in ["{", "not json", r#"{"method":"connect""#] {
+            let reply = parse_request(line, &client).unwrap_err();
+            assert_eq!(error_code(&reply), Some(-32700), "{}", line);
+            assert_eq!(reply["id"], Value::Null);
+        }
+    }
+
+    #[test]
+    fn missing_or_non_string_method_is_an_invalid_request() {
+        let (client, _rx) = client();
+        for line in ["{}", "[]", "42", r#"{"id":1}"#, r#"{"id":1,"method":5}"#] {
+            let reply = parse_request(line, &client).unwrap_err();
+            assert_eq!(error_code(&reply), Some(-32600), "{}", line);
+        }
+    }
+
+    #[test]
+    fn replies_carry_the_request_id() {
+        let (client, rx) = client();
+        parse_request(r#"{"id":3,"method":"a"}"#, &client)
+            .unwrap()
+            .reply(Ok(json!({ "link_id": 9 })));
+        parse_request(r#"{"id":4,"method":"b"}"#, &client)
+            .unwrap()
+            .reply(Err(RpcError::method_not_found("b")));
+
+        let ok: Value = serde_json::from_str(&rx.try_recv().unwrap()).unwrap();
+        assert_eq!(ok["id"], json!(3));
+        assert_eq!(ok["result"], json!({ "link_id": 9 }));
+
+        let err: Value = serde_json::from_str(&rx.try_recv().unwrap()).unwrap();
+        assert_eq!(err["id"], json!(4));
+        assert_eq!(error_code(&err), Some(-32601));
+    }
+}
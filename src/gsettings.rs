@@ -0,0 +1,134 @@
+use gio::prelude::*;
+use serde_json::{Map, Value};
+
+use crate::config::{self, DBUS_NAME};
+use crate::settings::Settings;
+
+/// Key choosing GSettings over settings.json
+const ENABLED_KEY: &str = "use-gsettings";
+
+/// The schema key for a settings field, e.g. `show-tray` for `show_tray`
+fn key_name(field: &str) -> String {
+    field.replace('_', "-")
+}
+
+/// Our GSettings, if the schema is installed. A separate profile (--config)
+/// keeps its own settings.json, as GSettings is shared by every profile.
+fn open() -> Option<(gio::Settings, gio::SettingsSchema)> {
+    if config::is_separate_profile() {
+        return None;
+    }
+    let schema = gio::SettingsSchemaSource::default()?.lookup(DBUS_NAME, true)?;
+    let settings = gio::Settings::new_full(&schema, None::<&gio::SettingsBackend>, None);
+    Some((settings, schema))
+}
+
+/// Whether settings can be stored in GSettings at all
+pub fn available() -> bool {
+    open().is_some()
+}
+
+/// Whether settings are stored in GSettings rather than settings.json
+pub fn enabled() -> bool {
+    open().is_some_and(|(settings, _)| settings.boolean(ENABLED_KEY))
+}
+
+/// Choose where settings are stored from now on
+pub fn set_enabled(enabled: bool) -> Result<(), String> {
+    let (settings, _) = open().ok_or("The GSettings schema is not installed")?;
+    settings
+        .set_boolean(ENABLED_KEY, enabled)
+        .map_err(|_| "Where settings are stored is set by the administrator".to_string())?;
+    gio::Settings::sync();
+    Ok(())
+}
+
+/// Call `changed` whenever a key changes, from this or another process.
+/// The returned object must be kept for the signal to keep arriving.
+pub fn watch(changed: impl Fn() + 'static) -> Option<gio::Settings> {
+    let (settings, _) = open()?;
+    settings.connect_changed(None, move |_, _| changed());
+    Some(settings)
+}
+
+/// Read settings from GSettings. Keys are read by the type of the field's
+/// default; lists and nested settings are JSON strings.
+pub fn load() -> Result<Settings, String> {
+    let (settings, schema) = open().ok_or("The GSettings schema is not installed")?;
+    let Value::Object(defaults) = serde_json::to_value(Settings::default())
+        .map_err(|e| format!("Failed to serialize: {}", e))?
+    else {
+        return Err("Settings are not an object".to_string());
+    };
+
+    let mut fields = Map::new();
+    for (field, default) in defaults {
+        let key = key_name(&field);
+        if !schema.has_key(&key) {
+            continue;
+        }
+        let value = match default {
+            Value::Bool(_) => Value::Bool(settings.boolean(&key)),
+            Value::Number(_) => Value::from(settings.int(&key)),
+            Value::String(_) => Value::String(settings.string(&key).to_string()),
+            Value::Null => {
+                let text = settings.string(&key);
+                if text.is_empty() {
+                    Value::Null
+                } else {
+                    Value::String(text.to_string())
+                }
+            }
+            Value::Array(_) | Value::Object(_) => {
+                match serde_json::from_str(&settings.string(&key)) {
+                    Ok(value) => value,
+                    Err(e) => {
+                        log::warn!("Ignoring invalid GSettings key {}: {}", key, e);
+                        continue;
+                    }
+                }
+            }
+        };
+        fields.insert(field, value);
+    }
+
+    serde_json::from_value(Value::Object(fields)).map_err(|e| format!("Invalid settings: {}", e))
+}
+
+/// Write settings to GSettings in one change. Keys locked by the
+/// administrator are left as they are.
+pub fn save(values: &Settings) -> Result<(), String> {
+    let (settings, schema) = open().ok_or("The GSettings schema is not installed")?;
+    let Value::Object(fields) =
+        serde_json::to_value(values).map_err(|e| format!("Failed to serialize: {}", e))?
+    else {
+        return Err("Settings are not an object".to_string());
+    };
+
+    settings.delay();
+    for (field, value) in fields {
+        let key = key_name(&field);
+        if !schema.has_key(&key) || !settings.is_writable(&key) {
+            continue;
+        }
+        let result = match &value {
+            Value::Bool(b) => settings.set_boolean(&key, *b),
+            Value::Number(n) => {
+                let n = n
+                    .as_i64()
+                    .and_then(|n| i32::try_from(n).ok())
+                    .unwrap_or_default();
+                settings.set_int(&key, n)
+            }
+            Value::String(s) => settings.set_string(&key, s),
+            Value::Null => settings.set_string(&key, ""),
+            Value::Array(_) | Value::Object(_) => settings.set_string(&key, &value.to_string()),
+        };
+        if let Err(e) = result {
+            log::warn!("Failed to set GSettings key {}: {}", key, e);
+        }
+    }
+    settings.apply();
+    gio::Settings::sync();
+    Ok(())
+}
@@ -0,0 +1,228 @@
+use std::collections::VecDeque;
+use std::fs::{self, File, OpenOptions};
+use std::io::Write;
+use std::path::PathBuf;
+use std::sync::Mutex;
+
+use log::{Level, LevelFilter, Log, Metadata, Record};
+use once_cell::sync::OnceCell;
+
+use crate::config::APP_ID;
+
+/// Number of recent entries kept in memory for the log viewer
+const RECENT_CAPACITY: usize = 2000;
+
+/// Size at which the log file is rotated
+const MAX_FILE_SIZE: u64 = 1024 * 1024;
+
+/// Number of rotated files kept next to the current one (log.1, log.2, ...)
+const ROTATED_FILES: usize = 3;
+
+/// Log target of this crate; its debug messages are always kept
+const OWN_TARGET: &str = "pw_audioshare";
+
+static LOGGER: OnceCell<AppLogger> = OnceCell::new();
+
+/// A single log record as shown in the log viewer
+#[derive(Debug, Clone)]
+pub struct LogEntry {
+    pub timestamp: String,
+    pub level: Level,
+    pub target: String,
+    pub message: String,
+}
+
+impl LogEntry {
+    /// The entry as one line of text, as written to the log file
+    pub fn line(&self) -> String {
+        format!(
+            "{} {:<5} {}: {}",
+            self.timestamp, self.level, self.target, self.message
+        )
+    }
+}
+
+/// Log file that is rotated once it grows past `MAX_FILE_SIZE`
+struct RotatingFile {
+    path: PathBuf,
+    file: File,
+    size: u64,
+}
+
+impl RotatingFile {
+    fn open(path: PathBuf) -> std::io::Result<Self> {
+        if let Some(parent) = path.parent() {
+            fs::create_dir_all(parent)?;
+        }
+        let file = OpenOptions::new().create(true).append(true).open(&path)?;
+        let size = file.metadata()?.len();
+        Ok(Self { path, file, size })
+    }
+
+    fn write_line(&mut self, line: &str) -> std::io::Result<()> {
+        if self.size >= MAX_FILE_SIZE {
+            self.rotate()?;
+        }
+        writeln!(self.file, "{}", line)?;
+        self.size += line.len() as u64 + 1;
+        Ok(())
+    }
+
+    /// Shift log -> log.1 -> log.2 ..., dropping the oldest
+    fn rotate(&mut self) -> std::io::Result<()> {
+        for i in (1..ROTATED_FILES).rev() {
+            let from = rotated_path(&self.path, i);
+            if from.exists() {
+                fs::rename(&from, rotated_path(&self.path, i + 1))?;
+            }
+        }
+        fs::rename(&self.path, rotated_path(&self.path, 1))?;
+
+        self.file = OpenOptions::new()
+            .create(true)
+            .append(true)
+            .open(&self.path)?;
+        self.size = 0;
+        Ok(())
+    }
+}
+
+fn rotated_path(path: &std::path::Path, index: usize) -> PathBuf {
+    let mut name = path.as_os_str().to_owned();
+    name.push(format!(".{}", index));
+    PathBuf::from(name)
+}
+
+/// Logger writing to stderr (filtered by RUST_LOG), to an in-memory buffer
+/// for the log viewer, and optionally to a rotating file
+struct AppLogger {
+    console: env_logger::Logger,
+    file: Mutex<Option<RotatingFile>>,
+    recent: Mutex<VecDeque<LogEntry>>,
+}
+
+impl AppLogger {
+    /// Whether a record is kept for the viewer and the log file
+    fn keeps(metadata: &Metadata) -> bool {
+        match metadata.level() {
+            Level::Error | Level::Warn | Level::Info => true,
+            Level::Debug => metadata.target().starts_with(OWN_TARGET),
+            Level::Trace => false,
+        }
+    }
+}
+
+impl Log for AppLogger {
+    fn enabled(&self, metadata: &Metadata) -> bool {
+        self.console.enabled(metadata) || Self::keeps(metadata)
+    }
+
+    fn log(&self, record: &Record) {
+        if self.console.matches(record) {
+            self.console.log(record);
+        }
+
+        if !Self::keeps(record.metadata()) {
+            return;
+        }
+
+        let entry = LogEntry {
+            timestamp: glib::DateTime::now_local()
+                .and_then(|now| now.format("%F %T.%f"))
+                .map(|s| s.to_string())
+                .unwrap_or_default(),
+            level: record.level(),
+            target: record.target().to_string(),
+            message: record.args().to_string(),
+        };
+
+        if let Ok(mut file) = self.file.lock() {
+            if let Some(f) = file.as_mut() {
+                if let Err(e) = f.write_line(&entry.line()) {
+                    // Stop writing rather than failing on every record
+                    eprintln!("Failed to write log file: {}", e);
+                    *file = None;
+                }
+            }
+        }
+
+        if let Ok(mut recent) = self.recent.lock() {
+            if recent.len() >= RECENT_CAPACITY {
+                recent.pop_front();
+            }
+            recent.push_back(entry);
+        }
+    }
+
+    fn flush(&self) {
+        self.console.flush();
+        if let Ok(mut file) = self.file.lock() {
+            if let Some(f) = file.as_mut() {
+                let _ = f.file.flush();
+            }
+        }
+    }
+}
+
+/// Path of the log file, `$XDG_STATE_HOME/pw-audioshare/log`
+pub fn log_file_path() -> Option<PathBuf> {
+    Some(dirs::state_dir()?.join(APP_ID).join("log"))
+}
+
+/// Install the application logger. Call once at startup.
+pub fn init(log_to_file: bool) {
+    let console =
+        env_logger::Builder::from_env(env_logger::Env::default().default_filter_or("info")).build();
+    let max_level = console.filter().max(LevelFilter::Debug);
+
+    let logger = LOGGER.get_or_init(|| AppLogger {
+        console,
+        file: Mutex::new(None),
+        recent: Mutex::new(VecDeque::new()),
+    });
+
+    if log::set_logger(logger).is_ok() {
+        log::set_max_level(max_level);
+    }
+
+    if log_to_file {
+        if let Err(e) = set_file_logging(true) {
+            log::warn!("{}", e);
+        }
+    }
+}
+
+/// Start or stop mirroring log output to the log file
+pub fn set_file_logging(enabled: bool) -> Result<(), String> {
+    let logger = LOGGER.get().ok_or("Logging is not initialized")?;
+    let mut file = logger.file.lock().map_err(|_| "Log file lock poisoned")?;
+
+    if !enabled {
+        *file = None;
+        return Ok(());
+    }
+
+    if file.is_none() {
+        let path = log_file_path().ok_or("Could not determine state directory")?;
+        let opened =
+            RotatingFile::open(path).map_err(|e| format!("Failed to open log file: {}", e))?;
+        *file = Some(opened);
+    }
+
+    Ok(())
+}
+
+/// Recent entries at `level` or more severe, oldest first
+pub fn recent_entries(level: LevelFilter) -> Vec<LogEntry> {
+    LOGGER
+        .get()
+        .and_then(|logger| logger.recent.lock().ok())
+        .map(|recent| {
+            recent
+                .iter()
+                .filter(|entry| entry.level <= level)
+                .cloned()
+                .collect()
+        })
+        .unwrap_or_default()
+}
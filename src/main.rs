@@ -1,23 +1,68 @@
 mod application;
 mod config;
+mod config_bundle;
+mod control_socket;
+mod gsettings;
+mod logging;
 mod model;
+mod osc;
 mod pipewire;
 mod presets;
+mod sandbox;
+#[cfg(feature = "scripting")]
+mod scripting;
+mod search_provider;
+mod session_snapshot;
 mod settings;
 mod tray;
 mod ui;
 
+use std::ffi::OsString;
+use std::path::PathBuf;
+
 use gtk::prelude::*;
 
+/// The directory given with `--config DIR` or `--config=DIR`. Read before
+/// GApplication parses the options, since settings are loaded first.
+fn config_dir_from_args(args: impl Iterator<Item = OsString>) -> Option<PathBuf> {
+    let mut args = args.skip(1);
+    while let Some(arg) = args.next() {
+        if arg == "--" {
+            break;
+        }
+        if arg == "--config" {
+            return args.next().map(PathBuf::from);
+        }
+        if let Some(dir) = arg.to_str().and_then(|a| a.strip_prefix("--config=")) {
+            return Some(PathBuf::from(dir));
+        }
+    }
+    None
+}
+
 fn main() -> glib::ExitCode {
-    // Initialize logging
-    env_logger::Builder::from_env(env_logger::Env::default().default_filter_or("info")).init();
+    // Use another config directory for this run, before anything reads it
+    if let Some(dir) = config_dir_from_args(std::env::args_os()) {
+        config::set_config_dir(dir);
+    }
+
+    // Initialize logging, mirrored to a file if enabled in settings
+    let settings = settings::Settings::load();
+    logging::init(settings.log_to_file);
+
+    // Before any thread starts, so every PipeWire client uses the same server
+    pipewire::remote::apply_setting(settings.pipewire_remote.as_deref());
 
     log::info!(
         "Starting {} v{}",
         config::APP_NAME,
         config::VERSION
     );
+    if config::is_separate_profile() {
+        if let Some(dir) = config::config_dir() {
+            log::info!("Using config directory {}", dir.display());
+        }
+    }
 
     // Create and run the application
     let app = application::Application::new();
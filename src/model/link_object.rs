@@ -4,6 +4,35 @@ use gtk::prelude::*;
 use gtk::subclass::prelude::*;
 use std::cell::{Cell, RefCell};
 
+/// How a link state is shown: a symbolic icon, so the state never rests on
+/// colour alone, a style class colouring it and a name for the legend and
+/// screen readers
+#[derive(Debug, Clone, Copy)]
+pub struct LinkStateStyle {
+    pub icon_name: &'static str,
+    pub css_class: &'static str,
+    pub name: &'static str,
+}
+
+impl LinkStateStyle {
+    /// The states a link can be in, for the legend
+    pub const STATES: [&'static str; 3] = ["active", "paused", "error"];
+
+    pub fn of(state: &str) -> Self {
+        let (icon_name, css_class, name) = match state {
+            "active" => ("media-playback-start-symbolic", "link-active", "Active"),
+            "paused" => ("media-playback-pause-symbolic", "link-paused", "Paused"),
+            "error" => ("dialog-error-symbolic", "link-error", "Error"),
+            _ => ("dialog-question-symbolic", "link-unknown", "Unknown state"),
+        };
+        Self {
+            icon_name,
+            css_class,
+            name,
+        }
+    }
+}
+
 mod imp {
     use super::*;
 
@@ -33,6 +62,15 @@ mod imp {
 
         #[property(get, set)]
         media_type: RefCell<String>,
+
+        /// Links between the same two ports shown by this row, more than
+        /// one when other tools made duplicates
+        #[property(get, set)]
+        link_count: Cell<u32>,
+
+        /// Who made the link: "app", "session-manager" or "external"
+        #[property(get, set)]
+        origin: RefCell<String>,
     }
 
     #[glib::object_subclass]
@@ -60,7 +98,7 @@ impl LinkObject {
         state: &str,
         media_type: &str,
     ) -> Self {
-        let display_label = format!("{} -> {}", output_label, input_label);
+        let display_label = display_label(output_label, input_label, 1);
 
         Object::builder()
             .property("id", id)
@@ -71,9 +109,32 @@ impl LinkObject {
             .property("state", state)
             .property("display-label", &display_label)
             .property("media-type", media_type)
+            .property("link-count", 1u32)
             .build()
     }
 
+    /// Replace the endpoint labels, e.g. after a node was renamed
+    pub fn update_labels(&self, output_label: &str, input_label: &str) {
+        self.set_output_label(output_label);
+        self.set_input_label(input_label);
+        self.set_display_label(display_label(output_label, input_label, self.link_count()));
+    }
+
+    /// Show the row as standing for `count` links between its ports
+    pub fn update_link_count(&self, count: u32) {
+        self.set_link_count(count);
+        self.set_display_label(display_label(
+            &self.output_label(),
+            &self.input_label(),
+            count,
+        ));
+    }
+
+    /// Symbolic icon, style class and name of the link's state
+    pub fn state_style(&self) -> LinkStateStyle {
+        LinkStateStyle::of(&self.state())
+    }
+
     /// Check if the link is active
     pub fn is_active(&self) -> bool {
         self.state() == "active"
@@ -88,16 +149,38 @@ impl LinkObject {
             _ => "unknown state",
         };
 
+        let origin = match self.origin().as_str() {
+            "app" => ", made by this app",
+            "session-manager" => ", made by the session manager",
+            "external" => ", made by another application",
+            _ => "",
+        };
+        let duplicates = match self.link_count() {
+            0 | 1 => String::new(),
+            count => format!(", {} duplicate links", count),
+        };
+
         format!(
-            "{} connection from {} to {}, {}",
+            "{} connection from {} to {}, {}{}{}",
             self.media_type(),
             self.output_label(),
             self.input_label(),
-            state_desc
+            state_desc,
+            origin,
+            duplicates
         )
     }
 }
 
+/// Label of a link row, with the number of links when there are duplicates
+fn display_label(output_label: &str, input_label: &str, link_count: u32) -> String {
+    if link_count > 1 {
+        format!("{} -> {} ({} links)", output_label, input_label, link_count)
+    } else {
+        format!("{} -> {}", output_label, input_label)
+    }
+}
+
 impl Default for LinkObject {
     fn default() -> Self {
         Object::builder().build()
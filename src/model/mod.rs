@@ -1,5 +1,5 @@
 mod link_object;
 mod port_object;
 
-pub use link_object::LinkObject;
+pub use link_object::{LinkObject, LinkStateStyle};
 pub use port_object::PortObject;
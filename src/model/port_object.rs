@@ -36,6 +36,33 @@ mod imp {
 
         #[property(get, set)]
         display_label: RefCell<String>,
+
+        /// Application owning the node, or the node name for devices
+        #[property(get, set)]
+        app_name: RefCell<String>,
+
+        /// Negotiated or preferred format of a video port, such as
+        /// "1920×1080 BGRx 30 fps", empty for other ports
+        #[property(get, set)]
+        video_format: RefCell<String>,
+
+        /// Monotonic time the port appeared, for sorting by most recent
+        #[property(get, set)]
+        added_time: Cell<i64>,
+
+        /// Lowercase copies of the fields searched, so filtering and
+        /// type-ahead don't lowercase them again for every port at every
+        /// keystroke. Set when the port is created and its node renamed.
+        #[property(get)]
+        pub(super) search_label: RefCell<String>,
+        #[property(get)]
+        pub(super) search_node_name: RefCell<String>,
+        #[property(get)]
+        pub(super) search_name: RefCell<String>,
+        #[property(get)]
+        pub(super) search_alias: RefCell<String>,
+        #[property(get)]
+        pub(super) search_channel: RefCell<String>,
     }
 
     #[glib::object_subclass]
@@ -64,17 +91,9 @@ impl PortObject {
         media_type: &str,
         channel: Option<&str>,
     ) -> Self {
-        let port_display = alias.unwrap_or(name);
-        let channel_str = channel.unwrap_or("");
-
-        // Create a descriptive label for screen readers
-        let display_label = if channel_str.is_empty() {
-            format!("{} - {}", node_name, port_display)
-        } else {
-            format!("{} - {} ({})", node_name, port_display, channel_str)
-        };
+        let display_label = display_label(node_name, alias.unwrap_or(name), channel.unwrap_or(""));
 
-        Object::builder()
+        let port: Self = Object::builder()
             .property("id", id)
             .property("node-id", node_id)
             .property("name", name)
@@ -84,7 +103,61 @@ impl PortObject {
             .property("media-type", media_type)
             .property("channel", channel.unwrap_or(""))
             .property("display-label", &display_label)
-            .build()
+            .property("app-name", node_name)
+            .property("added-time", glib::monotonic_time())
+            .build();
+        port.update_search_keys();
+        port
+    }
+
+    /// Update the owning node's name (and the label built from it) in place
+    pub fn update_node_name(&self, node_name: &str) {
+        let alias = self.alias();
+        let port_display = if alias.is_empty() { self.name() } else { alias };
+        self.set_node_name(node_name);
+        self.set_display_label(display_label(node_name, &port_display, &self.channel()));
+        self.update_search_keys();
+    }
+
+    fn update_search_keys(&self) {
+        let imp = self.imp();
+        for (key, value, name) in [
+            (&imp.search_label, self.display_label(), "search-label"),
+            (&imp.search_node_name, self.node_name(), "search-node-name"),
+            (&imp.search_name, self.name(), "search-name"),
+            (&imp.search_alias, self.alias(), "search-alias"),
+            (&imp.search_channel, self.channel(), "search-channel"),
+        ] {
+            let value = value.to_lowercase();
+            if *key.borrow() != value {
+                key.replace(value);
+                self.notify(name);
+            }
+        }
+    }
+
+    /// Whether the label contains `text`, which must be lowercase
+    pub fn label_contains(&self, text: &str) -> bool {
+        self.imp().search_label.borrow().contains(text)
+    }
+
+    /// Whether the node name contains `text`, which must be lowercase
+    pub fn node_name_contains(&self, text: &str) -> bool {
+        self.imp().search_node_name.borrow().contains(text)
+    }
+
+    /// Whether the channel is `channel`, which must be lowercase
+    pub fn channel_is(&self, channel: &str) -> bool {
+        *self.imp().search_channel.borrow() == channel
+    }
+
+    /// Whether the label, name or alias starts with `prefix`, which must be
+    /// lowercase
+    pub fn starts_with(&self, prefix: &str) -> bool {
+        let imp = self.imp();
+        imp.search_label.borrow().starts_with(prefix)
+            || imp.search_name.borrow().starts_with(prefix)
+            || imp.search_alias.borrow().starts_with(prefix)
     }
 
     /// Check if this is an output port
@@ -103,7 +176,7 @@ impl PortObject {
         let dir = if self.is_output() { "output" } else { "input" };
         let channel = self.channel();
 
-        if channel.is_empty() {
+        let description = if channel.is_empty() {
             format!("{} {} port on {}", media, dir, self.node_name())
         } else {
             format!(
@@ -113,8 +186,33 @@ impl PortObject {
                 channel,
                 self.node_name()
             )
+        };
+        let video_format = self.video_format();
+        if video_format.is_empty() {
+            description
+        } else {
+            format!("{}, {}", description, video_format)
         }
     }
+
+    /// Text of the port's row: its label, then the format of a video port
+    pub fn row_label(&self) -> String {
+        let video_format = self.video_format();
+        if video_format.is_empty() {
+            self.display_label()
+        } else {
+            format!("{} [{}]", self.display_label(), video_format)
+        }
+    }
+}
+
+/// Create a descriptive label for screen readers
+fn display_label(node_name: &str, port_display: &str, channel: &str) -> String {
+    if channel.is_empty() {
+        format!("{} - {}", node_name, port_display)
+    } else {
+        format!("{} - {} ({})", node_name, port_display, channel)
+    }
 }
 
 impl Default for PortObject {
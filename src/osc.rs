@@ -0,0 +1,369 @@
+use std::net::{Ipv4Addr, SocketAddr, UdpSocket};
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::Arc;
+use std::thread;
+use std::time::Duration;
+
+/// Port the OSC server listens on unless changed in settings
+pub const DEFAULT_PORT: u16 = 9000;
+
+/// How often the receive loop checks whether it should stop
+const POLL_INTERVAL: Duration = Duration::from_millis(500);
+
+/// Bundles nested deeper than this are dropped
+const MAX_BUNDLE_DEPTH: usize = 8;
+
+/// An argument of an OSC message
+#[derive(Debug, Clone, PartialEq)]
+pub enum OscArg {
+    Int(i32),
+    Float(f32),
+    String(String),
+}
+
+impl OscArg {
+    /// The argument as a whole number, for port ids sent as floats by faders
+    pub fn as_u32(&self) -> Option<u32> {
+        match self {
+            OscArg::Int(i) => u32::try_from(*i).ok(),
+            OscArg::Float(f) if *f >= 0.0 => Some(f.round() as u32),
+            _ => None,
+        }
+    }
+
+    pub fn as_str(&self) -> Option<&str> {
+        match self {
+            OscArg::String(s) => Some(s),
+            _ => None,
+        }
+    }
+
+    /// Whether the argument is a button release (0), which controls send
+    /// after the press
+    pub fn is_release(&self) -> bool {
+        match self {
+            OscArg::Int(i) => *i == 0,
+            OscArg::Float(f) => *f == 0.0,
+            OscArg::String(_) => false,
+        }
+    }
+}
+
+/// A received OSC message
+#[derive(Debug, Clone, PartialEq)]
+pub struct OscMessage {
+    pub address: String,
+    pub args: Vec<OscArg>,
+}
+
+/// Read a null-terminated string padded to four bytes. The padding must be
+/// in the packet too, so a truncated string is rejected.
+fn read_string(data: &[u8], pos: &mut usize) -> Option<String> {
+    let rest = data.get(*pos..)?;
+    let len = rest.iter().position(|&b| b == 0)?;
+    let padded = (len + 4) & !3;
+    if padded > rest.len() {
+        return None;
+    }
+    let s = std::str::from_utf8(&rest[..len]).ok()?.to_string();
+    *pos += padded;
+    Some(s)
+}
+
+fn read_u32(data: &[u8], pos: &mut usize) -> Option<u32> {
+    let bytes = data.get(*pos..*pos + 4)?;
+    *pos += 4;
+    Some(u32::from_be_bytes(bytes.try_into().ok()?))
+}
+
+/// Parse a packet into its messages; bundles are flattened and their time
+/// tags ignored
+pub fn parse_packet(data: &[u8]) -> Vec<OscMessage> {
+    let mut messages = Vec::new();
+    parse_into(data, 0, &mut messages);
+    messages
+}
+
+fn parse_into(data: &[u8], depth: usize, messages: &mut Vec<OscMessage>) {
+    if data.starts_with(b"#bundle\0") {
+        if depth >= MAX_BUNDLE_DEPTH {
+            return;
+        }
+        // Skip "#bundle" and the 8-byte time tag
+        let mut pos = 16;
+        while let Some(size) = read_u32(data, &mut pos) {
+            let size = size as usize;
+            // Elements are a multiple of four bytes; anything else means
+            // the rest of the bundle can't be trusted
+            if size % 4 != 0 {
+                return;
+            }
+            let Some(element) = pos.checked_add(size).and_then(|end| data.get(pos..end)) else {
+                return;
+            };
+            parse_into(element, depth + 1, messages);
+            pos += size;
+        }
+    } else if let Some(message) = parse_message(data) {
+        messages.push(message);
+    }
+}
+
+fn parse_message(data: &[u8]) -> Option<OscMessage> {
+    let mut pos = 0;
+    let address = read_string(data, &mut pos)?;
+    if !address.starts_with('/') {
+        return None;
+    }
+
+    let mut args = Vec::new();
+    // Very old senders leave out the type tags
+    let Some(tags) = read_string(data, &mut pos) else {
+        return Some(OscMessage { address, args });
+    };
+    for tag in tags.strip_prefix(',')?.chars() {
+        let arg = match tag {
+            'i' => OscArg::Int(read_u32(data, &mut pos)? as i32),
+            'f' => OscArg::Float(f32::from_bits(read_u32(data, &mut pos)?)),
+            's' => OscArg::String(read_string(data, &mut pos)?),
+            'T' => OscArg::Int(1),
+            'F' => OscArg::Int(0),
+            // Types without a useful mapping end the argument list
+            _ => break,
+        };
+        args.push(arg);
+    }
+    Some(OscMessage { address, args })
+}
+
+/// The UDP listener; stops when dropped
+pub struct OscServer {
+    port: u16,
+    stop: Arc<AtomicBool>,
+}
+
+impl OscServer {
+    pub fn port(&self) -> u16 {
+        self.port
+    }
+}
+
+impl Drop for OscServer {
+    fn drop(&mut self) {
+        self.stop.store(true, Ordering::SeqCst);
+    }
+}
+
+/// Listen for OSC messages, from this computer only unless `all_interfaces`
+/// lets touch surfaces on other devices reach it. Messages are passed to
+/// `message_tx` with the sender.
+pub fn spawn(
+    port: u16,
+    all_interfaces: bool,
+    message_tx: async_channel::Sender<(OscMessage, SocketAddr)>,
+) -> Result<OscServer, anyhow::Error> {
+    let address = if all_interfaces {
+        Ipv4Addr::UNSPECIFIED
+    } else {
+        Ipv4Addr::LOCALHOST
+    };
+    let socket = UdpSocket::bind((address, port))?;
+    socket.set_read_timeout(Some(POLL_INTERVAL))?;
+    let stop = Arc::new(AtomicBool::new(false));
+
+    let thread_stop = stop.clone();
+    thread::spawn(move || {
+        let mut buf = [0u8; 65536];
+        while !thread_stop.load(Ordering::SeqCst) {
+            let (len, sender) = match socket.recv_from(&mut buf) {
+                Ok(received) => received,
+                Err(e)
+                    if matches!(
+                        e.kind(),
+                        std::io::ErrorKind::WouldBlock | std::io::ErrorKind::TimedOut
+                    ) =>
+                {
+                    continue
+                }
+                Err(e) => {
+                    log::warn!("OSC receive failed: {}", e);
+                    continue;
+                }
+            };
+            for message in parse_packet(&buf[..len]) {
+                if message_tx.send_blocking((message, sender)).is_err() {
+                    return;
+                }
+            }
+        }
+        log::debug!("OSC server on port {} stopped", port);
+    });
+
+    log::info!("OSC server listening on {}:{}", address, port);
+    Ok(OscServer { port, stop })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// A string as OSC sends it: null-terminated and padded to four bytes
+    fn osc_string(s: &str) -> Vec<u8> {
+        let mut bytes = s.as_bytes().to_vec();
+        bytes.push(0);
+        while bytes.len() % 4 != 0 {
+            bytes.push(0);
+        }
+        bytes
+    }
+
+    fn message(address: &str, tags: &str, args: &[&[u8]]) -> Vec<u8> {
+        let mut bytes = osc_string(address);
+        bytes.extend(osc_string(tags));
+        for arg in args {
+            bytes.extend_from_slice(arg);
+        }
+        bytes
+    }
+
+    fn bundle(elements: &[Vec<u8>]) -> Vec<u8> {
+        let mut bytes = osc_string("#bundle");
+        bytes.extend_from_slice(&1u64.to_be_bytes());
+        for element in elements {
+            bytes.extend_from_slice(&(element.len() as u32).to_be_bytes());
+            bytes.extend_from_slice(element);
+        }
+        bytes
+    }
+
+    #[test]
+    fn parses_message_arguments() {
+        let packet = message(
+            "/connect",
+            ",ifsTF",
+            &[
+                &12i32.to_be_bytes(),
+                &1.0f32.to_bits().to_be_bytes(),
+                &osc_string("speakers:playback_FL"),
+            ],
+        );
+
+        assert_eq!(
+            parse_packet(&packet),
+            vec![OscMessage {
+                address: "/connect".into(),
+                args: vec![
+                    OscArg::Int(12),
+                    OscArg::Float(1.0),
+                    OscArg::String("speakers:playback_FL".into()),
+                    OscArg::Int(1),
+                    OscArg::Int(0),
+                ],
+            }]
+        );
+    }
+
+    #[test]
+    fn message_without_type_tags_has_no_arguments() {
+        let messages = parse_packet(&osc_string("/preset/deactivate"));
+        assert_eq!(messages.len(), 1);
+        assert!(messages[0].args.is_empty());
+    }
+
+    #[test]
+    fn unknown_type_ends_the_arguments() {
+        let packet = message("/link", ",ibi", &[&1i32.to_be_bytes()]);
+        assert_eq!(parse_packet(&packet)[0].args, vec![OscArg::Int(1)]);
+    }
+
+    #[test]
+    fn rejects_bad_addresses_and_tags() {
+        assert!(parse_packet(&osc_string("connect")).is_empty());
+        assert!(parse_packet(&message("/connect", "if", &[])).is_empty());
+        assert!(parse_packet(&[0xff, 0xfe, 0, 0]).is_empty());
+        assert!(parse_packet(&[]).is_empty());
+    }
+
+    #[test]
+    fn rejects_truncated_messages() {
+        let packet = message(
+            "/connect",
+            ",ii",
+            &[&1i32.to_be_bytes(), &2i32.to_be_bytes()],
+        );
+        // Cut inside an argument: the message is dropped rather than read
+        // with made up values
+        for len in 17..packet.len() {
+            assert!(parse_packet(&packet[..len]).is_empty(), "length {}", len);
+        }
+        assert_eq!(parse_packet(&packet)[0].args.len(), 2);
+    }
+
+    #[test]
+    fn rejects_strings_missing_their_padding() {
+        // "/ab" and its null fill exactly four bytes; "/abc" needs eight
+        assert_eq!(parse_packet(b"/ab\0").len(), 1);
+        assert!(parse_packet(b"/abc\0").is_empty());
+        assert!(parse_packet(b"/abc").is_empty());
+    }
+
+    #[test]
+    fn flattens_bundles() {
+        let packet = bundle(&[
+            message("/preset/deactivate", ",", &[]),
+            bundle(&[message("/preset/activate", ",s", &[&osc_string("Live")])]),
+        ]);
+
+        let addresses: Vec<_> = parse_packet(&packet)
+            .into_iter()
+            .map(|m| m.address)
+            .collect();
+        assert_eq!(addresses, ["/preset/deactivate", "/preset/activate"]);
+    }
+
+    #[test]
+    fn stops_at_truncated_bundle_element() {
+        let mut packet = bundle(&[message("/a", ",", &[]), message("/b", ",", &[])]);
+        packet.truncate(packet.len() - 4);
+
+        let messages = parse_packet(&packet);
+        assert_eq!(messages.len(), 1);
+        assert_eq!(messages[0].address, "/a");
+    }
+
+    #[test]
+    fn stops_at_misaligned_bundle_element() {
+        let mut packet = bundle(&[message("/a", ",", &[])]);
+        packet.extend_from_slice(&5u32.to_be_bytes());
+        packet.extend(message("/b", ",", &[]));
+
+        let messages = parse_packet(&packet);
+        assert_eq!(messages.len(), 1);
+        assert_eq!(messages[0].address, "/a");
+    }
+
+    #[test]
+    fn ignores_oversized_bundle_element() {
+        let mut packet = osc_string("#bundle");
+        packet.extend_from_slice(&0u64.to_be_bytes());
+        packet.extend_from_slice(&(u32::MAX & !3).to_be_bytes());
+        packet.extend(message("/a", ",", &[]));
+
+        assert!(parse_packet(&packet).is_empty());
+    }
+
+    #[test]
+    fn drops_deeply_nested_bundles() {
+        let mut packet = message("/deep", ",", &[]);
+        for _ in 0..MAX_BUNDLE_DEPTH + 1 {
+            packet = bundle(&[packet]);
+        }
+        assert!(parse_packet(&packet).is_empty());
+
+        let mut packet = message("/shallow", ",", &[]);
+        for _ in 0..MAX_BUNDLE_DEPTH {
+            packet = bundle(&[packet]);
+        }
+        assert_eq!(parse_packet(&packet).len(), 1);
+    }
+}
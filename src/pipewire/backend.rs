@@ -0,0 +1,20 @@
+use async_channel::Sender;
+
+use super::messages::{PwEvent, UiCommand};
+
+/// Source of graph events and receiver of commands for the UI.
+///
+/// Implemented by `PipeWireThread` for a live daemon and by `MockBackend`
+/// for an in-memory graph, so UI flows can run without PipeWire.
+pub trait GraphBackend {
+    /// Start the backend, sending graph events to `event_tx`
+    fn spawn(event_tx: Sender<PwEvent>) -> Result<Self, anyhow::Error>
+    where
+        Self: Sized;
+
+    /// Get a sender to send commands to the backend
+    fn command_sender(&self) -> Sender<UiCommand>;
+
+    /// Request shutdown and wait for the backend to finish
+    fn shutdown(&mut self);
+}
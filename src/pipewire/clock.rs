@@ -0,0 +1,83 @@
+use std::collections::HashMap;
+
+/// Name of the metadata object holding the server's runtime settings, as
+/// changed by `pw-metadata -n settings`
+pub const SETTINGS_METADATA: &str = "settings";
+
+/// Current graph rate and quantum, set by the server
+pub const RATE: &str = "clock.rate";
+pub const QUANTUM: &str = "clock.quantum";
+/// Rates the graph may switch to
+pub const ALLOWED_RATES: &str = "clock.allowed-rates";
+/// Overrides; 0 removes a forced rate or quantum
+pub const FORCE_RATE: &str = "clock.force-rate";
+pub const FORCE_QUANTUM: &str = "clock.force-quantum";
+pub const MIN_QUANTUM: &str = "clock.min-quantum";
+pub const MAX_QUANTUM: &str = "clock.max-quantum";
+
+/// Rates offered when the server doesn't list its allowed rates
+pub const COMMON_RATES: &[u32] = &[44100, 48000, 88200, 96000, 176400, 192000];
+
+/// Quantum sizes offered for forcing, in samples
+pub const COMMON_QUANTUMS: &[u32] = &[16, 32, 64, 128, 256, 512, 1024, 2048, 4096, 8192];
+
+/// Whether a settings key concerns the graph clock
+pub fn is_clock_key(key: &str) -> bool {
+    key.starts_with("clock.")
+}
+
+/// Clock values of the `settings` metadata, as last reported by the server
+#[derive(Debug, Clone, Default)]
+pub struct ClockSettings {
+    values: HashMap<String, String>,
+}
+
+impl ClockSettings {
+    /// Record a changed value, `None` when the key was removed
+    pub fn update(&mut self, key: String, value: Option<String>) {
+        match value {
+            Some(value) => {
+                self.values.insert(key, value);
+            }
+            None => {
+                self.values.remove(&key);
+            }
+        }
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.values.is_empty()
+    }
+
+    /// A numeric value, `None` if unset or 0
+    pub fn get(&self, key: &str) -> Option<u32> {
+        self.values
+            .get(key)
+            .and_then(|v| v.trim().parse().ok())
+            .filter(|v| *v != 0)
+    }
+
+    /// Rates the graph may run at, from `clock.allowed-rates` (a SPA JSON
+    /// array such as `[ 44100 48000 ]`) or common rates if it is unset
+    pub fn allowed_rates(&self) -> Vec<u32> {
+        let mut rates: Vec<u32> = self
+            .values
+            .get(ALLOWED_RATES)
+            .map(|list| {
+                list.split(|c: char| !c.is_ascii_digit())
+                    .filter_map(|n| n.parse().ok())
+                    .collect()
+            })
+            .unwrap_or_default();
+        if rates.len() < 2 {
+            rates = COMMON_RATES.to_vec();
+        }
+        if let Some(rate) = self.get(RATE) {
+            if !rates.contains(&rate) {
+                rates.push(rate);
+            }
+        }
+        rates.sort_unstable();
+        rates
+    }
+}
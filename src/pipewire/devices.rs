@@ -0,0 +1,355 @@
+use std::cell::RefCell;
+use std::collections::BTreeMap;
+use std::io::Cursor;
+use std::rc::Rc;
+
+use async_channel::Sender;
+use pipewire::core::{Core, Listener, PW_ID_CORE};
+use pipewire::device::{Device, DeviceListener};
+use pipewire::registry::{GlobalObject, Registry};
+use pipewire::spa::param::ParamType;
+use pipewire::spa::pod::deserialize::PodDeserializer;
+use pipewire::spa::pod::serialize::PodSerializer;
+use pipewire::spa::pod::{Object, Pod, Property, Value, ValueArray};
+use pipewire::spa::sys as spa_sys;
+use pipewire::spa::utils::dict::DictRef;
+use pipewire::spa::utils::result::AsyncSeq;
+use pipewire::spa::utils::Id;
+
+use super::messages::{DeviceProfile, DeviceRoute, PwEvent};
+
+/// Profiles and routes of a device as the server last enumerated them
+#[derive(Default)]
+struct DeviceParams {
+    profiles: BTreeMap<u32, DeviceProfile>,
+    active_profile: Option<u32>,
+    routes: BTreeMap<u32, DeviceRoute>,
+    /// Selected route index keyed by card device
+    active_routes: BTreeMap<u32, u32>,
+    /// Routes of an enumeration still arriving
+    pending_routes: Option<BTreeMap<u32, DeviceRoute>>,
+    /// Selected routes of an enumeration still arriving, with the
+    /// availability each reported
+    pending_active: Option<BTreeMap<u32, (u32, bool)>>,
+    /// The sync answered once the server has sent every route param of
+    /// the enumerations in progress
+    routes_sync: Option<AsyncSeq>,
+}
+
+impl DeviceParams {
+    /// The routes with the devices each is selected on
+    fn routes(&self) -> Vec<DeviceRoute> {
+        self.routes
+            .values()
+            .map(|route| {
+                let mut route = route.clone();
+                route.active_on = self
+                    .active_routes
+                    .iter()
+                    .filter(|(_, index)| **index == route.index)
+                    .map(|(device, _)| *device)
+                    .collect();
+                route
+            })
+            .collect()
+    }
+
+    /// Replace the routes with the enumerations that just completed
+    fn finish_routes(&mut self) {
+        if let Some(routes) = self.pending_routes.take() {
+            self.routes = routes;
+        }
+        if let Some(active) = self.pending_active.take() {
+            self.active_routes = active
+                .iter()
+                .map(|(device, (index, _))| (*device, *index))
+                .collect();
+            // The selected routes report their availability as it is now,
+            // which may have changed since they were enumerated
+            for (index, available) in active.into_values() {
+                if let Some(route) = self.routes.get_mut(&index) {
+                    route.available = available;
+                }
+            }
+        }
+    }
+}
+
+/// A bound device proxy reporting its profiles and routes
+pub struct DeviceWatcher {
+    pub device: Device,
+    _listener: DeviceListener,
+    _core_listener: Listener,
+}
+
+/// The properties of a param object, keyed by SPA key
+pub(super) fn object_properties(pod: &Pod) -> Option<Vec<Property>> {
+    match PodDeserializer::deserialize_any_from(pod.as_bytes()) {
+        Ok((_, Value::Object(object))) => Some(object.properties),
+        _ => None,
+    }
+}
+
+fn find_int(properties: &[Property], key: u32) -> Option<i32> {
+    properties.iter().find(|p| p.key == key).and_then(|p| match p.value {
+        Value::Int(v) => Some(v),
+        _ => None,
+    })
+}
+
+fn find_string(properties: &[Property], key: u32) -> Option<String> {
+    properties.iter().find(|p| p.key == key).and_then(|p| match &p.value {
+        Value::String(s) => Some(s.clone()),
+        _ => None,
+    })
+}
+
+fn find_ints(properties: &[Property], key: u32) -> Vec<u32> {
+    properties
+        .iter()
+        .find(|p| p.key == key)
+        .map(|p| match &p.value {
+            Value::ValueArray(ValueArray::Int(values)) => {
+                values.iter().map(|v| *v as u32).collect()
+            }
+            _ => Vec::new(),
+        })
+        .unwrap_or_default()
+}
+
+/// Availability is "no" only when the device says so; unknown counts as available
+fn find_available(properties: &[Property], key: u32) -> bool {
+    !properties.iter().any(|p| {
+        p.key == key && p.value == Value::Id(Id(spa_sys::SPA_PARAM_AVAILABILITY_no))
+    })
+}
+
+/// Read a profile from an `EnumProfile` or `Profile` param
+fn parse_profile(pod: &Pod) -> Option<DeviceProfile> {
+    let properties = object_properties(pod)?;
+    let index = find_int(&properties, spa_sys::SPA_PARAM_PROFILE_index)?;
+    let name = find_string(&properties, spa_sys::SPA_PARAM_PROFILE_name).unwrap_or_default();
+    Some(DeviceProfile {
+        index: index as u32,
+        description: find_string(&properties, spa_sys::SPA_PARAM_PROFILE_description)
+            .unwrap_or_else(|| name.clone()),
+        name,
+        available: find_available(&properties, spa_sys::SPA_PARAM_PROFILE_available),
+    })
+}
+
+/// Read a route from an `EnumRoute` param
+fn parse_route(properties: &[Property]) -> Option<DeviceRoute> {
+    let index = find_int(properties, spa_sys::SPA_PARAM_ROUTE_index)?;
+    let name = find_string(properties, spa_sys::SPA_PARAM_ROUTE_name).unwrap_or_default();
+    let playback = properties.iter().any(|p| {
+        p.key == spa_sys::SPA_PARAM_ROUTE_direction
+            && p.value == Value::Id(Id(spa_sys::SPA_DIRECTION_OUTPUT))
+    });
+    Some(DeviceRoute {
+        index: index as u32,
+        description: find_string(properties, spa_sys::SPA_PARAM_ROUTE_description)
+            .unwrap_or_else(|| name.clone()),
+        name,
+        playback,
+        available: find_available(properties, spa_sys::SPA_PARAM_ROUTE_available),
+        profiles: find_ints(properties, spa_sys::SPA_PARAM_ROUTE_profiles),
+        devices: find_ints(properties, spa_sys::SPA_PARAM_ROUTE_devices),
+        active_on: Vec::new(),
+    })
+}
+
+/// Serialize a param object to set on a device or node
+pub(super) fn serialize_param(type_: u32, id: ParamType, properties: Vec<Property>) -> Result<Vec<u8>, anyhow::Error> {
+    let object = Value::Object(Object {
+        type_,
+        id: id.as_raw(),
+        properties,
+    });
+    let (cursor, _) = PodSerializer::serialize(Cursor::new(Vec::new()), &object)
+        .map_err(|e| anyhow::anyhow!("Failed to build the param: {:?}", e))?;
+    Ok(cursor.into_inner())
+}
+
+/// Switch a device to the profile with `index`, saving it as the device's
+/// preferred profile like a desktop sound panel does
+pub fn set_profile(device: &Device, index: u32) -> Result<(), anyhow::Error> {
+    let bytes = serialize_param(
+        spa_sys::SPA_TYPE_OBJECT_ParamProfile,
+        ParamType::Profile,
+        vec![
+            Property::new(spa_sys::SPA_PARAM_PROFILE_index, Value::Int(index as i32)),
+            Property::new(spa_sys::SPA_PARAM_PROFILE_save, Value::Bool(true)),
+        ],
+    )?;
+    let pod = Pod::from_bytes(&bytes).ok_or_else(|| anyhow::anyhow!("Invalid profile param"))?;
+    device.set_param(ParamType::Profile, 0, pod);
+    Ok(())
+}
+
+/// Select the route with `index` on device `card_device` of the card,
+/// saving it like a desktop sound panel does
+pub fn set_route(device: &Device, index: u32, card_device: u32) -> Result<(), anyhow::Error> {
+    let bytes = serialize_param(
+        spa_sys::SPA_TYPE_OBJECT_ParamRoute,
+        ParamType::Route,
+        vec![
+            Property::new(spa_sys::SPA_PARAM_ROUTE_index, Value::Int(index as i32)),
+            Property::new(spa_sys::SPA_PARAM_ROUTE_device, Value::Int(card_device as i32)),
+            Property::new(spa_sys::SPA_PARAM_ROUTE_save, Value::Bool(true)),
+        ],
+    )?;
+    let pod = Pod::from_bytes(&bytes).ok_or_else(|| anyhow::anyhow!("Invalid route param"))?;
+    device.set_param(ParamType::Route, 0, pod);
+    Ok(())
+}
+
+/// The `DeviceAdded` event for a device global
+pub fn device_added_event(id: u32, props: &DictRef) -> PwEvent {
+    PwEvent::DeviceAdded {
+        id,
+        name: props.get("device.name").unwrap_or("Unknown").to_string(),
+        description: props
+            .get("device.description")
+            .or_else(|| props.get("device.nick"))
+            .map(String::from),
+        api: props.get("device.api").map(String::from),
+    }
+}
+
+/// Bind a device and report its profiles and routes whenever they change
+pub fn watch_device<T>(
+    event_tx: &Sender<PwEvent>,
+    core: &Core,
+    registry: &Registry,
+    global: &GlobalObject<T>,
+) -> Option<DeviceWatcher>
+where
+    T: AsRef<DictRef>,
+{
+    let device: Device = match registry.bind(global) {
+        Ok(device) => device,
+        Err(e) => {
+            log::warn!("Could not watch device {}: {}", global.id, e);
+            return None;
+        }
+    };
+
+    let id = global.id;
+    let params = Rc::new(RefCell::new(DeviceParams::default()));
+    let core_for_params = core.clone();
+    let listener = device
+        .add_listener_local()
+        .param({
+            let params = params.clone();
+            let event_tx = event_tx.clone();
+            move |_seq, param_type, index, _next, pod| {
+                let Some(pod) = pod else {
+                    return;
+                };
+                let mut params = params.borrow_mut();
+                match param_type {
+                    ParamType::EnumProfile => {
+                        // A new enumeration starts over at index 0
+                        if index == 0 {
+                            params.profiles.clear();
+                        }
+                        if let Some(profile) = parse_profile(pod) {
+                            params.profiles.insert(profile.index, profile);
+                        }
+                    }
+                    ParamType::Profile => {
+                        params.active_profile = parse_profile(pod).map(|p| p.index);
+                    }
+                    ParamType::EnumRoute | ParamType::Route => {
+                        // Routes are collected until the server has sent all
+                        // of them and reported once, so a re-enumeration
+                        // never shows a route as briefly gone or switched
+                        if index == 0 {
+                            if param_type == ParamType::EnumRoute {
+                                params.pending_routes = Some(BTreeMap::new());
+                            } else {
+                                params.pending_active = Some(BTreeMap::new());
+                            }
+                            match core_for_params.sync(0) {
+                                Ok(seq) => params.routes_sync = Some(seq),
+                                Err(e) => log::warn!("Could not sync device {}: {}", id, e),
+                            }
+                        }
+                        let Some(properties) = object_properties(pod) else {
+                            return;
+                        };
+                        if param_type == ParamType::EnumRoute {
+                            if let Some(route) = parse_route(&properties) {
+                                params
+                                    .pending_routes
+                                    .get_or_insert_with(BTreeMap::new)
+                                    .insert(route.index, route);
+                            }
+                        } else {
+                            // The active route of one device of the card
+                            let route_index = find_int(&properties, spa_sys::SPA_PARAM_ROUTE_index);
+                            let card_device =
+                                find_int(&properties, spa_sys::SPA_PARAM_ROUTE_device);
+                            if let (Some(route_index), Some(card_device)) =
+                                (route_index, card_device)
+                            {
+                                let available =
+                                    find_available(&properties, spa_sys::SPA_PARAM_ROUTE_available);
+                                params
+                                    .pending_active
+                                    .get_or_insert_with(BTreeMap::new)
+                                    .insert(card_device as u32, (route_index as u32, available));
+                            }
+                        }
+                        if params.routes_sync.is_none() {
+                            params.finish_routes();
+                            let _ = event_tx.send_blocking(PwEvent::DeviceRoutesChanged {
+                                id,
+                                routes: params.routes(),
+                            });
+                        }
+                        return;
+                    }
+                    _ => return,
+                }
+                let _ = event_tx.send_blocking(PwEvent::DeviceProfilesChanged {
+                    id,
+                    profiles: params.profiles.values().cloned().collect(),
+                    active_profile: params.active_profile,
+                });
+            }
+        })
+        .register();
+    device.subscribe_params(&[
+        ParamType::EnumProfile,
+        ParamType::Profile,
+        ParamType::EnumRoute,
+        ParamType::Route,
+    ]);
+
+    // The server answers the sync after the params sent before it, so the
+    // enumerations of routes are complete
+    let event_tx = event_tx.clone();
+    let core_listener = core
+        .add_listener_local()
+        .done(move |core_id, seq| {
+            let mut params = params.borrow_mut();
+            if core_id != PW_ID_CORE || params.routes_sync != Some(seq) {
+                return;
+            }
+            params.routes_sync = None;
+            params.finish_routes();
+            let _ = event_tx.send_blocking(PwEvent::DeviceRoutesChanged {
+                id,
+                routes: params.routes(),
+            });
+        })
+        .register();
+
+    Some(DeviceWatcher {
+        device,
+        _listener: listener,
+        _core_listener: core_listener,
+    })
+}
@@ -0,0 +1,146 @@
+use super::modules::{sanitize_description, OWNED_NAME_PREFIX};
+
+/// Module hosting inserted filters
+pub const FILTER_CHAIN_MODULE: &str = "libpipewire-module-filter-chain";
+
+/// Effects that can be inserted into a connection
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum FilterPreset {
+    /// High-pass plus presence boost for speech
+    VoiceEq,
+    /// Low-shelf boost
+    BassBoost,
+    /// High-shelf cut for harsh sources
+    TrebleCut,
+    /// Dynamic range compressor (requires the SWH LADSPA plugins)
+    Compressor,
+    /// Passes audio unchanged; its output node's volume sets the gain
+    Volume,
+}
+
+impl FilterPreset {
+    pub const ALL: [FilterPreset; 5] = [
+        FilterPreset::VoiceEq,
+        FilterPreset::BassBoost,
+        FilterPreset::TrebleCut,
+        FilterPreset::Compressor,
+        FilterPreset::Volume,
+    ];
+
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            FilterPreset::VoiceEq => "voice-eq",
+            FilterPreset::BassBoost => "bass-boost",
+            FilterPreset::TrebleCut => "treble-cut",
+            FilterPreset::Compressor => "compressor",
+            FilterPreset::Volume => "volume",
+        }
+    }
+
+    pub fn label(&self) -> &'static str {
+        match self {
+            FilterPreset::VoiceEq => "Voice EQ",
+            FilterPreset::BassBoost => "Bass Boost",
+            FilterPreset::TrebleCut => "Treble Cut",
+            FilterPreset::Compressor => "Compressor",
+            FilterPreset::Volume => "Volume",
+        }
+    }
+
+    /// The `filter.graph` nodes and internal links for this preset
+    fn graph(&self) -> &'static str {
+        match self {
+            FilterPreset::VoiceEq => {
+                r#"nodes = [
+                { type = builtin name = highpass label = bq_highpass control = { "Freq" = 80.0 "Q" = 0.707 } }
+                { type = builtin name = presence label = bq_peaking control = { "Freq" = 3000.0 "Q" = 1.0 "Gain" = 3.0 } }
+            ]
+            links = [
+                { output = "highpass:Out" input = "presence:In" }
+            ]"#
+            }
+            FilterPreset::BassBoost => {
+                r#"nodes = [
+                { type = builtin name = lowshelf label = bq_lowshelf control = { "Freq" = 100.0 "Q" = 0.707 "Gain" = 6.0 } }
+            ]"#
+            }
+            FilterPreset::TrebleCut => {
+                r#"nodes = [
+                { type = builtin name = highshelf label = bq_highshelf control = { "Freq" = 6000.0 "Q" = 0.707 "Gain" = -6.0 } }
+            ]"#
+            }
+            FilterPreset::Compressor => {
+                r#"nodes = [
+                { type = ladspa name = compressor plugin = sc4m_1916 label = sc4m
+                  control = {
+                      "Attack time (ms)" = 10.0
+                      "Release time (ms)" = 100.0
+                      "Threshold level (dB)" = -20.0
+                      "Ratio (1:n)" = 4.0
+                      "Makeup gain (dB)" = 6.0
+                  }
+                }
+            ]"#
+            }
+            FilterPreset::Volume => {
+                r#"nodes = [
+                { type = builtin name = volume label = copy }
+            ]"#
+            }
+        }
+    }
+}
+
+/// Node name prefix for an inserted filter instance
+pub fn filter_key(instance: u32) -> String {
+    format!("{}filter_{}", OWNED_NAME_PREFIX, instance)
+}
+
+/// Name of the filter's capture (input) node
+pub fn filter_input_node(key: &str) -> String {
+    format!("{}_in", key)
+}
+
+/// Name of the filter's playback (output) node
+pub fn filter_output_node(key: &str) -> String {
+    format!("{}_out", key)
+}
+
+/// Linear gain for a volume in percent, on the cubic scale desktop volume
+/// controls use so that 50% sounds about half as loud
+pub fn volume_gain(percent: f64) -> f32 {
+    (percent / 100.0).max(0.0).powi(3) as f32
+}
+
+/// Build the filter-chain module arguments for a mono insert.
+///
+/// Both ends are created with autoconnect disabled so the session manager
+/// leaves the wiring to us.
+pub fn filter_chain_args(key: &str, description: &str, preset: FilterPreset) -> String {
+    let description = sanitize_description(description);
+    format!(
+        r#"{{
+        node.description = "{description}"
+        media.name = "{description}"
+        filter.graph = {{
+            {graph}
+        }}
+        audio.channels = 1
+        audio.position = [ MONO ]
+        capture.props = {{
+            node.name = "{input}"
+            node.description = "{description} Input"
+            node.autoconnect = false
+        }}
+        playback.props = {{
+            node.name = "{output}"
+            node.description = "{description} Output"
+            node.autoconnect = false
+        }}
+    }}"#,
+        description = description,
+        graph = preset.graph(),
+        input = filter_input_node(key),
+        output = filter_output_node(key),
+    )
+}
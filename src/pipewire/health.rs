@@ -0,0 +1,180 @@
+use std::io::ErrorKind;
+use std::os::unix::net::UnixStream;
+
+use pipewire::registry::GlobalObject;
+use serde::{Deserialize, Serialize};
+use pipewire::spa::utils::dict::DictRef;
+use pipewire::types::ObjectType;
+
+/// Oldest PipeWire version the application is known to work with
+pub const MIN_PIPEWIRE_VERSION: (u32, u32, u32) = (0, 3, 48);
+
+/// Factory used to create links
+const LINK_FACTORY: &str = "link-factory";
+
+/// What the startup health check found out about the PipeWire server
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct HealthReport {
+    /// Version reported by the server
+    pub server_version: Option<String>,
+    /// Name of the running session manager, if any
+    pub session_manager: Option<String>,
+    /// Whether the server can create links
+    pub has_link_factory: bool,
+}
+
+/// A problem found by the health check, with advice for fixing it
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct HealthProblem {
+    pub summary: String,
+    pub hint: String,
+}
+
+impl HealthProblem {
+    fn new(summary: impl Into<String>, hint: impl Into<String>) -> Self {
+        Self {
+            summary: summary.into(),
+            hint: hint.into(),
+        }
+    }
+}
+
+impl HealthReport {
+    /// Record anything relevant about a global from the initial registry scan
+    pub fn observe<T>(&mut self, global: &GlobalObject<T>)
+    where
+        T: AsRef<DictRef>,
+    {
+        let Some(props) = global.props.as_ref().map(|p| p.as_ref()) else {
+            return;
+        };
+
+        match global.type_ {
+            ObjectType::Factory => {
+                if props.get("factory.name") == Some(LINK_FACTORY) {
+                    self.has_link_factory = true;
+                }
+            }
+            ObjectType::Client => {
+                if let Some(name) = props.get("application.name") {
+                    if self.session_manager.is_none() && is_session_manager(name) {
+                        self.session_manager = Some(name.to_string());
+                    }
+                }
+            }
+            _ => {}
+        }
+    }
+
+    /// Problems found, most serious first
+    pub fn problems(&self) -> Vec<HealthProblem> {
+        let mut problems = Vec::new();
+
+        if !self.has_link_factory {
+            problems.push(HealthProblem::new(
+                "PipeWire cannot create connections",
+                "The link-factory module is not loaded. Check that your PipeWire \
+                 configuration has not removed libpipewire-module-link-factory.",
+            ));
+        }
+
+        if self.session_manager.is_none() {
+            problems.push(HealthProblem::new(
+                "No session manager is running",
+                "Devices may be missing and new applications will not be connected. \
+                 Start WirePlumber with: systemctl --user enable --now wireplumber",
+            ));
+        }
+
+        if let Some(version) = &self.server_version {
+            if parse_version(version).is_some_and(|v| v < MIN_PIPEWIRE_VERSION) {
+                let (major, minor, micro) = MIN_PIPEWIRE_VERSION;
+                problems.push(HealthProblem::new(
+                    format!("PipeWire {} is too old", version),
+                    format!(
+                        "Some features need PipeWire {}.{}.{} or newer. \
+                         Update PipeWire through your distribution.",
+                        major, minor, micro
+                    ),
+                ));
+            }
+        }
+
+        problems
+    }
+}
+
+/// Whether a client's application name belongs to a session manager
+fn is_session_manager(application_name: &str) -> bool {
+    let name = application_name.to_lowercase();
+    name.contains("wireplumber") || name.contains("media-session")
+}
+
+/// Parse a version string like "1.0.5" into its numeric parts
+pub fn parse_version(version: &str) -> Option<(u32, u32, u32)> {
+    let mut parts = version
+        .split(|c: char| !c.is_ascii_digit())
+        .filter(|p| !p.is_empty())
+        .map(|p| p.parse::<u32>().ok());
+
+    let major = parts.next()??;
+    let minor = parts.next().flatten().unwrap_or(0);
+    let micro = parts.next().flatten().unwrap_or(0);
+    Some((major, minor, micro))
+}
+
+/// Work out why connecting to PipeWire failed
+pub fn diagnose_connection_failure() -> Vec<HealthProblem> {
+    let Some(path) = super::remote::socket_path() else {
+        return vec![HealthProblem::new(
+            "XDG_RUNTIME_DIR is not set",
+            "The PipeWire socket cannot be found. Start the application from your \
+             desktop session rather than with sudo or from another user's shell.",
+        )];
+    };
+
+    // Flatpak only exposes the socket to apps allowed to use it
+    if !path.exists() && crate::sandbox::is_flatpak() {
+        return vec![HealthProblem::new(
+            "The sandbox has no access to PipeWire",
+            format!(
+                "No socket at {}.{}",
+                path.display(),
+                crate::sandbox::permission_hint("--filesystem=xdg-run/pipewire-0")
+            ),
+        )];
+    }
+
+    if !path.exists() {
+        return vec![HealthProblem::new(
+            "PipeWire is not running",
+            format!(
+                "No socket at {}. Start it with: \
+                 systemctl --user enable --now pipewire pipewire-pulse wireplumber",
+                path.display()
+            ),
+        )];
+    }
+
+    match UnixStream::connect(&path) {
+        Err(e) if e.kind() == ErrorKind::PermissionDenied => vec![HealthProblem::new(
+            "Permission denied on the PipeWire socket",
+            format!(
+                "{} is not accessible. If running in a sandbox, allow access to \
+                 the PipeWire socket; otherwise run as the user who owns the session.{}",
+                path.display(),
+                crate::sandbox::permission_hint("--filesystem=xdg-run/pipewire-0")
+            ),
+        )],
+        Err(e) => vec![HealthProblem::new(
+            "The PipeWire socket is not responding",
+            format!(
+                "Connecting to {} failed: {}. Try restarting PipeWire with: \
+                 systemctl --user restart pipewire",
+                path.display(),
+                e
+            ),
+        )],
+        Ok(_) => Vec::new(),
+    }
+}
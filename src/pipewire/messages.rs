@@ -1,5 +1,11 @@
+use std::collections::BTreeMap;
+
+use serde::{Deserialize, Serialize};
+
+use super::health::HealthReport;
+
 /// Direction of a port (input receives data, output sends data)
-#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Serialize, Deserialize)]
 pub enum PortDirection {
     Input,
     Output,
@@ -15,7 +21,7 @@ impl PortDirection {
 }
 
 /// Type of media carried by a port
-#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Default)]
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Default, Serialize, Deserialize)]
 pub enum MediaType {
     #[default]
     Audio,
@@ -45,7 +51,7 @@ impl MediaType {
 }
 
 /// State of a link between ports
-#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Default)]
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Default, Serialize, Deserialize)]
 pub enum LinkState {
     #[default]
     Active,
@@ -63,8 +69,149 @@ impl LinkState {
     }
 }
 
+/// Properties applied to a newly created link
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub struct LinkOptions {
+    /// `link.passive`: the link does not keep its nodes running on its own
+    pub passive: bool,
+    /// `object.linger`: the link outlives the application that created it
+    pub linger: bool,
+}
+
+impl Default for LinkOptions {
+    fn default() -> Self {
+        Self {
+            passive: false,
+            linger: true,
+        }
+    }
+}
+
+/// Signal generated by the test tone utility
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Default, Serialize, Deserialize)]
+pub enum TestSignal {
+    #[default]
+    Sine,
+    PinkNoise,
+}
+
+impl TestSignal {
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            TestSignal::Sine => "sine",
+            TestSignal::PinkNoise => "pink",
+        }
+    }
+
+    pub fn from_name(s: &str) -> Option<Self> {
+        match s {
+            "sine" => Some(TestSignal::Sine),
+            "pink" => Some(TestSignal::PinkNoise),
+            _ => None,
+        }
+    }
+
+    /// Human readable name for announcements and the stream's media.name
+    pub fn label(&self) -> &'static str {
+        match self {
+            TestSignal::Sine => "440 Hz sine tone",
+            TestSignal::PinkNoise => "Pink noise",
+        }
+    }
+}
+
+/// A profile of a device, such as a Bluetooth headset's A2DP profile with
+/// one codec or its HFP profile
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct DeviceProfile {
+    pub index: u32,
+    pub name: String,
+    pub description: String,
+    pub available: bool,
+}
+
+/// A route of a device, the physical output or input a node plays to or
+/// records from, such as "Speakers" or "Headphones" on one sound card
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct DeviceRoute {
+    pub index: u32,
+    pub name: String,
+    pub description: String,
+    /// Whether the route plays audio rather than recording it
+    pub playback: bool,
+    /// False when jack detection says nothing is plugged in
+    pub available: bool,
+    /// Profiles the route can be used with
+    pub profiles: Vec<u32>,
+    /// Devices of the card (`card.profile.device` of a node) it applies to
+    pub devices: Vec<u32>,
+    /// Devices of the card the route is currently selected on
+    pub active_on: Vec<u32>,
+}
+
+/// A video format a port offers or has negotiated, such as raw BGRx at
+/// 1920×1080 and 30 fps from a screencast, or MJPG from a camera
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct VideoFormat {
+    /// Media subtype, such as "raw", "mjpg" or "h264"
+    pub subtype: String,
+    /// Pixel formats of raw video, the preferred one first, such as "BGRx"
+    pub pixel_formats: Vec<String>,
+    /// Width and height, the preferred ones when a range is offered
+    pub size: Option<(u32, u32)>,
+    /// Frames per second as numerator and denominator, 0/1 when variable
+    pub framerate: Option<(u32, u32)>,
+}
+
+impl VideoFormat {
+    /// Name of the encoding, the pixel format for raw video
+    pub fn encoding(&self) -> String {
+        match self.pixel_formats.first() {
+            Some(pixel_format) if self.subtype == "raw" => pixel_format.clone(),
+            _ => self.subtype.to_uppercase(),
+        }
+    }
+
+    /// Whether video in this format can be sent to a port accepting `other`
+    pub fn matches(&self, other: &VideoFormat) -> bool {
+        if self.subtype != other.subtype {
+            return false;
+        }
+        // An empty list leaves the pixel format open
+        self.subtype != "raw"
+            || self.pixel_formats.is_empty()
+            || other.pixel_formats.is_empty()
+            || self
+                .pixel_formats
+                .iter()
+                .any(|format| other.pixel_formats.contains(format))
+    }
+
+    /// Short description, such as "1920×1080 BGRx 30 fps"
+    pub fn label(&self) -> String {
+        let mut parts = Vec::new();
+        if let Some((width, height)) = self.size {
+            parts.push(format!("{}×{}", width, height));
+        }
+        parts.push(self.encoding());
+        match self.framerate {
+            Some((num, denom)) if num > 0 && denom > 0 => {
+                if num % denom == 0 {
+                    parts.push(format!("{} fps", num / denom));
+                } else {
+                    parts.push(format!("{:.2} fps", num as f64 / denom as f64));
+                }
+            }
+            Some(_) => parts.push("variable fps".to_string()),
+            None => {}
+        }
+        parts.join(" ")
+    }
+}
+
 /// Events sent from the PipeWire thread to the UI thread
-#[derive(Debug, Clone)]
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(tag = "type")]
 pub enum PwEvent {
     /// A new node appeared in the registry
     NodeAdded {
@@ -73,6 +220,30 @@ pub enum PwEvent {
         media_class: Option<String>,
         description: Option<String>,
         application_name: Option<String>,
+        /// `object.serial`, unique for the lifetime of the server
+        serial: Option<u64>,
+        nick: Option<String>,
+        device_name: Option<String>,
+        /// `client.id`, the client that created the node
+        #[serde(default)]
+        client_id: Option<u32>,
+    },
+
+    /// A node's properties changed after it was added, e.g. a Bluetooth
+    /// device renaming itself once its codec is negotiated
+    NodeChanged {
+        id: u32,
+        name: String,
+        media_class: Option<String>,
+        description: Option<String>,
+        application_name: Option<String>,
+        /// `object.serial`, unique for the lifetime of the server
+        serial: Option<u64>,
+        nick: Option<String>,
+        device_name: Option<String>,
+        /// `client.id`, the client that created the node
+        #[serde(default)]
+        client_id: Option<u32>,
     },
 
     /// A node was removed from the registry
@@ -105,12 +276,102 @@ pub enum PwEvent {
     /// A link was removed
     LinkRemoved { id: u32 },
 
+    /// A device (sound card, Bluetooth headset, ...) appeared
+    DeviceAdded {
+        id: u32,
+        name: String,
+        description: Option<String>,
+        /// `device.api`, e.g. "alsa" or "bluez5"
+        api: Option<String>,
+    },
+
+    /// A device's profiles were enumerated or its active profile changed
+    DeviceProfilesChanged {
+        id: u32,
+        profiles: Vec<DeviceProfile>,
+        active_profile: Option<u32>,
+    },
+
+    /// A device's routes were enumerated or their selection or
+    /// availability changed
+    DeviceRoutesChanged { id: u32, routes: Vec<DeviceRoute> },
+
+    /// A client connected, such as an application or the session manager
+    ClientAdded {
+        id: u32,
+        application_name: Option<String>,
+        /// `pipewire.sec.pid`, the process id of the client
+        pid: Option<u32>,
+        /// `application.process.binary`, e.g. "firefox"
+        #[serde(default)]
+        process_binary: Option<String>,
+    },
+
+    /// A client's properties changed, or were first read from the bound
+    /// client, which has more of them than the registry announces
+    ClientChanged {
+        id: u32,
+        application_name: Option<String>,
+        pid: Option<u32>,
+        process_binary: Option<String>,
+    },
+
+    /// The formats a video port offers were enumerated, or the format it
+    /// negotiated changed. `current` is `None` while the port is idle.
+    VideoFormatsChanged {
+        id: u32,
+        offered: Vec<VideoFormat>,
+        current: Option<VideoFormat>,
+    },
+
+    /// The full property list of a node, port or link, sent after it was
+    /// added and whenever a node's properties change
+    PropertiesChanged {
+        id: u32,
+        properties: BTreeMap<String, String>,
+    },
+
     /// The state of a link changed
     LinkStateChanged { id: u32, state: LinkState },
 
+    /// A link requested with `CreateLink` was created
+    LinkCreated { request_id: u64, link_id: u32 },
+
+    /// A link requested with `CreateLink` could not be created
+    LinkFailed { request_id: u64, message: String },
+
+    /// A command sent with a request id, other than `CreateLink`, was
+    /// carried out, or failed with `error`
+    CommandResult {
+        request_id: u64,
+        error: Option<String>,
+    },
+
+    /// The test signal stream was started or stopped
+    TestToneChanged { signal: Option<TestSignal> },
+
+    /// A module created by the application was loaded (or found at startup)
+    ModuleLoaded { id: u32, name: String, args: String },
+
+    /// A module was unloaded
+    ModuleUnloaded { id: u32 },
+
+    /// All modules left over from a previous session have been reported
+    ModuleScanComplete,
+
+    /// A helper process hosting a native module exited unexpectedly
+    ModuleHostExited { key: String },
+
+    /// A `clock.*` value of the server's settings metadata changed, `None`
+    /// when it was removed
+    ClockSettingChanged { key: String, value: Option<String> },
+
     /// PipeWire connection established
     Connected,
 
+    /// The startup health check finished
+    HealthChecked { report: HealthReport },
+
     /// PipeWire connection lost or failed
     Disconnected { reason: String },
 
@@ -121,15 +382,102 @@ pub enum PwEvent {
 /// Commands sent from the UI thread to the PipeWire thread
 #[derive(Debug, Clone)]
 pub enum UiCommand {
-    /// Create a link between two ports
+    /// Create a link between two ports. The request id is echoed back in
+    /// `LinkCreated` or `LinkFailed`.
     CreateLink {
+        request_id: u64,
         output_port_id: u32,
         input_port_id: u32,
+        options: LinkOptions,
+    },
+
+    /// Delete an existing link. The request id is echoed back in
+    /// `CommandResult`.
+    DeleteLink { request_id: u64, link_id: u32 },
+
+    /// Start playing a test signal, replacing any signal already playing
+    StartTestTone { signal: TestSignal },
+
+    /// Stop the test signal
+    StopTestTone,
+
+    /// Load a Pulse-compatible module (e.g. a combine sink)
+    LoadModule { name: String, args: String },
+
+    /// Unload a module by index
+    UnloadModule { id: u32 },
+
+    /// Run a native module (e.g. filter-chain) in a helper process identified by `key`
+    StartModuleHost {
+        key: String,
+        module: String,
+        args: String,
     },
 
-    /// Delete an existing link
-    DeleteLink { link_id: u32 },
+    /// Stop a helper process started with `StartModuleHost`
+    StopModuleHost { key: String },
+
+    /// Switch a device to another profile, e.g. a Bluetooth codec
+    SetDeviceProfile { device_id: u32, index: u32 },
+
+    /// Select a route on one device of a card, e.g. headphones instead of
+    /// speakers
+    SetDeviceRoute {
+        device_id: u32,
+        index: u32,
+        card_device: u32,
+    },
+
+    /// Set the volume of a node, a linear factor where 1.0 leaves the
+    /// signal unchanged
+    SetNodeVolume { node_id: u32, volume: f32 },
+
+    /// Write a `clock.*` value to the server's settings metadata, like
+    /// `pw-metadata -n settings 0 <key> <value>`
+    SetClockSetting { key: String, value: String },
 
     /// Shutdown the PipeWire thread
     Quit,
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn video(subtype: &str, pixel_formats: &[&str]) -> VideoFormat {
+        VideoFormat {
+            subtype: subtype.to_string(),
+            pixel_formats: pixel_formats.iter().map(|f| f.to_string()).collect(),
+            size: None,
+            framerate: None,
+        }
+    }
+
+    #[test]
+    fn raw_video_matches_a_shared_pixel_format() {
+        let screencast = video("raw", &["BGRx", "RGBx"]);
+        assert!(screencast.matches(&video("raw", &["I420", "RGBx"])));
+        assert!(!screencast.matches(&video("raw", &["I420", "NV12"])));
+    }
+
+    #[test]
+    fn open_pixel_formats_match_any() {
+        assert!(video("raw", &[]).matches(&video("raw", &["NV12"])));
+        assert!(video("raw", &["NV12"]).matches(&video("raw", &[])));
+    }
+
+    #[test]
+    fn subtypes_must_agree() {
+        assert!(!video("mjpg", &[]).matches(&video("raw", &[])));
+        assert!(video("mjpg", &[]).matches(&video("mjpg", &[])));
+        // Only raw video has pixel formats to compare
+        assert!(video("h264", &["I420"]).matches(&video("h264", &["NV12"])));
+    }
+
+    #[test]
+    fn encoding_is_the_pixel_format_of_raw_video() {
+        assert_eq!(video("raw", &["BGRx", "RGBx"]).encoding(), "BGRx");
+        assert_eq!(video("raw", &[]).encoding(), "RAW");
+        assert_eq!(video("mjpg", &[]).encoding(), "MJPG");
+    }
+}
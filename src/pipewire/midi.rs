@@ -0,0 +1,106 @@
+use std::io::{BufRead, BufReader};
+use std::process::{Child, Stdio};
+use std::thread;
+use std::time::{Duration, Instant};
+
+use async_channel::Sender;
+
+use crate::sandbox::host_command;
+
+/// Start of the node names of the streams watching MIDI ports, followed by
+/// the id of the port watched
+const MONITOR_NODE_PREFIX: &str = "pw-audioshare-midi-monitor-";
+
+/// Report a port's activity at most this often, as a busy controller sends
+/// hundreds of events a second
+const ACTIVITY_INTERVAL: Duration = Duration::from_millis(100);
+
+/// Node name of the stream watching `port_id`
+fn monitor_node_name(port_id: u32) -> String {
+    format!("{}{}", MONITOR_NODE_PREFIX, port_id)
+}
+
+/// The port a MIDI monitor node watches, from the node's name
+pub fn monitored_port(node_name: &str) -> Option<u32> {
+    node_name.strip_prefix(MONITOR_NODE_PREFIX)?.parse().ok()
+}
+
+/// The ALSA sequencer client and port of a port of PipeWire's ALSA MIDI
+/// bridge, from an `object.path` such as "alsa:seq:default:client_14:capture_0"
+pub fn alsa_seq_address(object_path: &str) -> Option<(u32, u32)> {
+    let mut parts = object_path.strip_prefix("alsa:seq:")?.split(':').skip(1);
+    let client = parts.next()?.strip_prefix("client_")?.parse().ok()?;
+    let port = parts.next()?.rsplit('_').next()?.parse().ok()?;
+    Some((client, port))
+}
+
+/// Watches one MIDI port for events with `pw-mididump`, sending the port's
+/// id whenever it has some, until dropped.
+///
+/// `pw-mididump` makes a node with one MIDI input named after the port, which
+/// the UI links to the port once it appears.
+pub struct MidiMonitor {
+    child: Child,
+}
+
+impl MidiMonitor {
+    /// Start watching `port_id` on the server with socket `remote`, or the
+    /// default one
+    pub fn spawn(
+        remote: Option<&str>,
+        port_id: u32,
+        tx: Sender<u32>,
+    ) -> Result<Self, anyhow::Error> {
+        // pw-mididump takes no node properties, but PipeWire reads them
+        // from the environment
+        let properties = format!(
+            "{{ node.name = \"{}\" node.description = \"PW Audioshare MIDI Monitor\" }}",
+            monitor_node_name(port_id)
+        );
+        let mut command = host_command("env");
+        command
+            .arg(format!("PIPEWIRE_PROPS={}", properties))
+            .arg("pw-mididump");
+        if let Some(remote) = remote {
+            command.args(["--remote", remote]);
+        }
+        let mut child = command
+            .stdout(Stdio::piped())
+            .stderr(Stdio::null())
+            .spawn()
+            .map_err(|e| anyhow::anyhow!("Failed to run pw-mididump: {}", e))?;
+        let stdout = child
+            .stdout
+            .take()
+            .ok_or_else(|| anyhow::anyhow!("pw-mididump has no output"))?;
+
+        thread::Builder::new()
+            .name("pw-mididump".into())
+            .spawn(move || {
+                // Each line is one event
+                let mut last_sent: Option<Instant> = None;
+                for line in BufReader::new(stdout).lines() {
+                    if line.is_err() {
+                        break;
+                    }
+                    if last_sent.is_some_and(|at| at.elapsed() < ACTIVITY_INTERVAL) {
+                        continue;
+                    }
+                    if tx.send_blocking(port_id).is_err() {
+                        break;
+                    }
+                    last_sent = Some(Instant::now());
+                }
+                log::debug!("pw-mididump for port {} stopped", port_id);
+            })?;
+
+        Ok(Self { child })
+    }
+}
+
+impl Drop for MidiMonitor {
+    fn drop(&mut self) {
+        let _ = self.child.kill();
+        let _ = self.child.wait();
+    }
+}
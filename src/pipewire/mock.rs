@@ -0,0 +1,510 @@
+use std::collections::HashMap;
+use std::thread::{self, JoinHandle};
+use std::time::{Duration, Instant};
+
+use async_channel::{Receiver, Sender, TryRecvError};
+
+use super::backend::GraphBackend;
+use super::messages::{LinkState, MediaType, PortDirection, PwEvent, UiCommand};
+use super::recording::RecordedEvent;
+
+/// How often the mock thread checks for commands while waiting
+const POLL_INTERVAL: Duration = Duration::from_millis(10);
+
+/// In-memory graph standing in for PipeWire.
+///
+/// The graph is built from a script of events, such as a recording from the
+/// event debugger, sent with their original timing. Link commands are then
+/// applied to the graph and answered the way PipeWire would.
+pub struct MockBackend {
+    handle: Option<JoinHandle<()>>,
+    command_tx: Sender<UiCommand>,
+}
+
+impl MockBackend {
+    /// Start a mock that plays `script`, then keeps answering commands
+    pub fn spawn_with_script(
+        script: Vec<RecordedEvent>,
+        event_tx: Sender<PwEvent>,
+    ) -> Result<Self, anyhow::Error> {
+        let (command_tx, command_rx) = async_channel::bounded::<UiCommand>(64);
+
+        let handle = thread::Builder::new()
+            .name("mock-graph".into())
+            .spawn(move || {
+                let mut graph = MockGraph::new(event_tx);
+                graph.run(script, &command_rx);
+            })?;
+
+        Ok(Self {
+            handle: Some(handle),
+            command_tx,
+        })
+    }
+}
+
+impl GraphBackend for MockBackend {
+    /// Start a mock with an empty graph
+    fn spawn(event_tx: Sender<PwEvent>) -> Result<Self, anyhow::Error> {
+        Self::spawn_with_script(Vec::new(), event_tx)
+    }
+
+    fn command_sender(&self) -> Sender<UiCommand> {
+        self.command_tx.clone()
+    }
+
+    fn shutdown(&mut self) {
+        let _ = self.command_tx.send_blocking(UiCommand::Quit);
+        if let Some(handle) = self.handle.take() {
+            let _ = handle.join();
+        }
+    }
+}
+
+impl Drop for MockBackend {
+    fn drop(&mut self) {
+        self.shutdown();
+    }
+}
+
+/// Ports on each node of a synthetic graph, half outputs and half inputs
+const SYNTHETIC_PORTS_PER_NODE: u32 = 8;
+
+/// A script that adds a graph of `port_count` ports at once, shaped like a
+/// large Ardour session: tracks with a few audio channels each, and every
+/// tenth a MIDI track. For testing filtering and scrolling on big graphs
+/// with `--synthetic-graph`.
+pub fn synthetic_graph(port_count: u32) -> Vec<RecordedEvent> {
+    let event = |event| RecordedEvent {
+        time: String::new(),
+        elapsed_us: 0,
+        event,
+    };
+    let mut script = Vec::new();
+    let mut next_id = 100;
+    let node_count = port_count.div_ceil(SYNTHETIC_PORTS_PER_NODE);
+
+    for track in 1..=node_count {
+        let node_id = next_id;
+        next_id += 1;
+        let media_type = if track % 10 == 0 {
+            MediaType::Midi
+        } else {
+            MediaType::Audio
+        };
+        script.push(event(PwEvent::NodeAdded {
+            id: node_id,
+            name: format!("ardour.Track {}", track),
+            media_class: Some("Audio/Duplex".into()),
+            description: Some(format!("Track {}", track)),
+            application_name: Some("Ardour".into()),
+            serial: Some(node_id as u64),
+            nick: None,
+            device_name: None,
+            client_id: None,
+        }));
+
+        let ports =
+            SYNTHETIC_PORTS_PER_NODE.min(port_count - (track - 1) * SYNTHETIC_PORTS_PER_NODE);
+        for port in 0..ports {
+            let (direction, suffix) = if port % 2 == 0 {
+                (PortDirection::Output, "out")
+            } else {
+                (PortDirection::Input, "in")
+            };
+            let channel = port / 2 + 1;
+            script.push(event(PwEvent::PortAdded {
+                id: next_id,
+                node_id,
+                name: format!("{}_{}_{}", media_type.as_str(), suffix, channel),
+                alias: None,
+                direction,
+                media_type,
+                channel: Some(format!("AUX{}", channel - 1)),
+            }));
+            next_id += 1;
+        }
+    }
+    script
+}
+
+/// A port known to the mock
+struct MockPort {
+    node_id: u32,
+    direction: PortDirection,
+}
+
+/// A link known to the mock: (output port, input port)
+type MockLink = (u32, u32);
+
+/// Graph state owned by the mock thread
+struct MockGraph {
+    event_tx: Sender<PwEvent>,
+    ports: HashMap<u32, MockPort>,
+    links: HashMap<u32, MockLink>,
+    /// Next id handed out to a created link
+    next_id: u32,
+}
+
+impl MockGraph {
+    fn new(event_tx: Sender<PwEvent>) -> Self {
+        Self {
+            event_tx,
+            ports: HashMap::new(),
+            links: HashMap::new(),
+            next_id: 1,
+        }
+    }
+
+    /// Play the script, then answer commands until the UI quits
+    fn run(&mut self, script: Vec<RecordedEvent>, command_rx: &Receiver<UiCommand>) {
+        let start = Instant::now();
+        for recorded in script {
+            let due = Duration::from_micros(recorded.elapsed_us.max(0) as u64);
+            while start.elapsed() < due {
+                if !self.drain_commands(command_rx) {
+                    return;
+                }
+                thread::sleep(POLL_INTERVAL.min(due.saturating_sub(start.elapsed())));
+            }
+
+            self.observe(&recorded.event);
+            if self.event_tx.send_blocking(recorded.event).is_err() {
+                return;
+            }
+        }
+
+        while let Ok(cmd) = command_rx.recv_blocking() {
+            if !self.handle_command(cmd) {
+                return;
+            }
+        }
+    }
+
+    /// Handle pending commands. Returns false once the UI has quit.
+    fn drain_commands(&mut self, command_rx: &Receiver<UiCommand>) -> bool {
+        loop {
+            match command_rx.try_recv() {
+                Ok(cmd) => {
+                    if !self.handle_command(cmd) {
+                        return false;
+                    }
+                }
+                Err(TryRecvError::Empty) => return true,
+                Err(TryRecvError::Closed) => return false,
+            }
+        }
+    }
+
+    /// Keep the graph in step with an event sent to the UI
+    fn observe(&mut self, event: &PwEvent) {
+        match event {
+            PwEvent::NodeAdded { id, .. } => self.reserve_id(*id),
+            PwEvent::PortAdded {
+                id,
+                node_id,
+                direction,
+                ..
+            } => {
+                self.reserve_id(*id);
+                self.ports.insert(
+                    *id,
+                    MockPort {
+                        node_id: *node_id,
+                        direction: *direction,
+                    },
+                );
+            }
+            PwEvent::PortRemoved { id } => {
+                self.ports.remove(id);
+            }
+            PwEvent::LinkAdded {
+                id,
+                output_port_id,
+                input_port_id,
+                ..
+            } => {
+                self.reserve_id(*id);
+                self.links.insert(*id, (*output_port_id, *input_port_id));
+            }
+            PwEvent::LinkRemoved { id } => {
+                self.links.remove(id);
+            }
+            _ => {}
+        }
+    }
+
+    /// Make sure created objects never reuse an id from the script
+    fn reserve_id(&mut self, id: u32) {
+        self.next_id = self.next_id.max(id + 1);
+    }
+
+    fn send(&mut self, event: PwEvent) {
+        self.observe(&event);
+        let _ = self.event_tx.send_blocking(event);
+    }
+
+    /// Apply a command to the graph. Returns false for `Quit`.
+    fn handle_command(&mut self, cmd: UiCommand) -> bool {
+        match cmd {
+            UiCommand::CreateLink {
+                request_id,
+                output_port_id,
+                input_port_id,
+                ..
+            } => match self.link_endpoints(output_port_id, input_port_id) {
+                Ok((output_node_id, input_node_id)) => {
+                    let id = self.next_id;
+                    self.send(PwEvent::LinkAdded {
+                        id,
+                        output_node_id,
+                        output_port_id,
+                        input_node_id,
+                        input_port_id,
+                        state: LinkState::Active,
+                    });
+                    self.send(PwEvent::LinkCreated {
+                        request_id,
+                        link_id: id,
+                    });
+                }
+                Err(message) => self.send(PwEvent::LinkFailed {
+                    request_id,
+                    message,
+                }),
+            },
+            UiCommand::DeleteLink {
+                request_id,
+                link_id,
+            } => {
+                let error = if self.links.contains_key(&link_id) {
+                    self.send(PwEvent::LinkRemoved { id: link_id });
+                    None
+                } else {
+                    Some(format!("Failed to delete connection: no link {}", link_id))
+                };
+                self.send(PwEvent::CommandResult { request_id, error });
+            }
+            UiCommand::Quit => return false,
+            other => log::info!("Mock backend: ignoring {:?}", other),
+        }
+        true
+    }
+
+    /// Check a requested link and return the (output, input) node ids
+    fn link_endpoints(
+        &self,
+        output_port_id: u32,
+        input_port_id: u32,
+    ) -> Result<(u32, u32), String> {
+        let output = self
+            .ports
+            .get(&output_port_id)
+            .filter(|p| p.direction == PortDirection::Output)
+            .ok_or_else(|| {
+                format!(
+                    "Failed to create connection: no output port {}",
+                    output_port_id
+                )
+            })?;
+        let input = self
+            .ports
+            .get(&input_port_id)
+            .filter(|p| p.direction == PortDirection::Input)
+            .ok_or_else(|| {
+                format!(
+                    "Failed to create connection: no input port {}",
+                    input_port_id
+                )
+            })?;
+
+        if self
+            .links
+            .values()
+            .any(|&link| link == (output_port_id, input_port_id))
+        {
+            return Err("Failed to create connection: ports are already connected".into());
+        }
+
+        Ok((output.node_id, input.node_id))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::pipewire::messages::LinkOptions;
+
+    /// A graph with one output port (11) and one input port (21)
+    fn graph() -> (MockGraph, Receiver<PwEvent>) {
+        let (event_tx, event_rx) = async_channel::unbounded();
+        let mut graph = MockGraph::new(event_tx);
+        for (id, node_id, direction) in [
+            (11, 10, PortDirection::Output),
+            (21, 20, PortDirection::Input),
+        ] {
+            graph.observe(&PwEvent::PortAdded {
+                id,
+                node_id,
+                name: "playback_FL".into(),
+                alias: None,
+                direction,
+                media_type: MediaType::Audio,
+                channel: Some("FL".into()),
+            });
+        }
+        (graph, event_rx)
+    }
+
+    fn create_link(request_id: u64, output_port_id: u32, input_port_id: u32) -> UiCommand {
+        UiCommand::CreateLink {
+            request_id,
+            output_port_id,
+            input_port_id,
+            options: LinkOptions::default(),
+        }
+    }
+
+    fn events(event_rx: &Receiver<PwEvent>) -> Vec<PwEvent> {
+        std::iter::from_fn(|| event_rx.try_recv().ok()).collect()
+    }
+
+    /// The request id and message of a `LinkFailed` event
+    fn failure(event: &PwEvent) -> (u64, &str) {
+        match event {
+            PwEvent::LinkFailed {
+                request_id,
+                message,
+            } => (*request_id, message),
+            other => panic!("expected LinkFailed, got {:?}", other),
+        }
+    }
+
+    #[test]
+    fn create_link_adds_link_and_reports_it() {
+        let (mut graph, event_rx) = graph();
+        assert!(graph.handle_command(create_link(1, 11, 21)));
+
+        let events = events(&event_rx);
+        let link_id = match events.as_slice() {
+            [PwEvent::LinkAdded {
+                id,
+                output_node_id: 10,
+                output_port_id: 11,
+                input_node_id: 20,
+                input_port_id: 21,
+                ..
+            }, PwEvent::LinkCreated {
+                request_id: 1,
+                link_id,
+            }] if id == link_id => *link_id,
+            other => panic!("unexpected events {:?}", other),
+        };
+        assert_eq!(graph.links.get(&link_id), Some(&(11, 21)));
+    }
+
+    #[test]
+    fn create_link_never_reuses_an_id_from_the_script() {
+        let (mut graph, event_rx) = graph();
+        graph.observe(&PwEvent::LinkAdded {
+            id: 50,
+            output_node_id: 10,
+            output_port_id: 11,
+            input_node_id: 30,
+            input_port_id: 31,
+            state: LinkState::Active,
+        });
+        graph.handle_command(create_link(1, 11, 21));
+
+        assert!(events(&event_rx)
+            .iter()
+            .any(|event| matches!(event, PwEvent::LinkCreated { link_id: 51, .. })));
+    }
+
+    #[test]
+    fn create_link_fails_for_missing_or_reversed_ports() {
+        let (mut graph, event_rx) = graph();
+        graph.handle_command(create_link(1, 99, 21));
+        graph.handle_command(create_link(2, 21, 11));
+
+        let events = events(&event_rx);
+        assert_eq!(events.len(), 2);
+        let (request_id, message) = failure(&events[0]);
+        assert_eq!(request_id, 1);
+        assert!(message.contains("no output port 99"));
+        let (request_id, message) = failure(&events[1]);
+        assert_eq!(request_id, 2);
+        assert!(message.contains("no output port 21"));
+        assert!(graph.links.is_empty());
+    }
+
+    #[test]
+    fn create_link_fails_when_already_connected() {
+        let (mut graph, event_rx) = graph();
+        graph.handle_command(create_link(1, 11, 21));
+        events(&event_rx);
+        graph.handle_command(create_link(2, 11, 21));
+
+        let events = events(&event_rx);
+        assert_eq!(events.len(), 1);
+        let (request_id, message) = failure(&events[0]);
+        assert_eq!(request_id, 2);
+        assert!(message.contains("already connected"));
+        assert_eq!(graph.links.len(), 1);
+    }
+
+    #[test]
+    fn delete_link_removes_link_and_reports_result() {
+        let (mut graph, event_rx) = graph();
+        graph.handle_command(create_link(1, 11, 21));
+        let link_id = *graph.links.keys().next().unwrap();
+        events(&event_rx);
+
+        graph.handle_command(UiCommand::DeleteLink {
+            request_id: 2,
+            link_id,
+        });
+        match events(&event_rx).as_slice() {
+            [PwEvent::LinkRemoved { id }, PwEvent::CommandResult {
+                request_id: 2,
+                error: None,
+            }] => assert_eq!(*id, link_id),
+            other => panic!("unexpected events {:?}", other),
+        }
+        assert!(graph.links.is_empty());
+    }
+
+    #[test]
+    fn delete_unknown_link_reports_error() {
+        let (mut graph, event_rx) = graph();
+        graph.handle_command(UiCommand::DeleteLink {
+            request_id: 3,
+            link_id: 42,
+        });
+
+        match events(&event_rx).as_slice() {
+            [PwEvent::CommandResult {
+                request_id: 3,
+                error: Some(error),
+            }] => assert!(error.contains("no link 42")),
+            other => panic!("unexpected events {:?}", other),
+        }
+    }
+
+    #[test]
+    fn removed_port_can_no_longer_be_linked() {
+        let (mut graph, event_rx) = graph();
+        graph.observe(&PwEvent::PortRemoved { id: 21 });
+        graph.handle_command(create_link(1, 11, 21));
+
+        let events = events(&event_rx);
+        assert_eq!(events.len(), 1);
+        assert_eq!(failure(&events[0]).0, 1);
+    }
+
+    #[test]
+    fn quit_stops_the_graph() {
+        let (mut graph, _event_rx) = graph();
+        assert!(!graph.handle_command(UiCommand::Quit));
+    }
+}
@@ -1,7 +1,23 @@
+pub mod backend;
+pub mod clock;
+pub mod devices;
+pub mod filters;
+pub mod health;
 pub mod messages;
+pub mod midi;
+#[cfg(feature = "replay")]
+pub mod mock;
+pub mod modules;
+pub mod network;
+pub mod profiler;
+pub mod recording;
+pub mod remote;
 pub mod state;
+pub mod test_tone;
 pub mod thread;
+pub mod video;
 
-pub use messages::{PortDirection, PwEvent, UiCommand};
+pub use backend::GraphBackend;
+pub use messages::{LinkOptions, PortDirection, PwEvent, TestSignal, UiCommand};
 pub use state::PwState;
 pub use thread::PipeWireThread;
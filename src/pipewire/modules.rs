@@ -0,0 +1,380 @@
+use crate::sandbox::{host_command, host_runtime_dir};
+
+/// Prefix for the names of every sink/source the application creates.
+/// Modules whose arguments contain this prefix are treated as owned by us.
+pub const OWNED_NAME_PREFIX: &str = "pw_audioshare_";
+
+/// A module loaded into the PipeWire Pulse server
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct LoadedModule {
+    pub id: u32,
+    pub name: String,
+    pub args: String,
+}
+
+impl LoadedModule {
+    /// Look up a single argument value
+    pub fn arg(&self, key: &str) -> Option<String> {
+        module_arg(&self.args, key)
+    }
+
+    /// The user visible description from the source or sink properties.
+    /// The source comes first: a module with both, like an echo canceller,
+    /// is used through its source, its sink being only the reference.
+    pub fn description(&self) -> Option<String> {
+        ["source_properties", "sink_properties"]
+            .iter()
+            .filter_map(|key| self.arg(key))
+            .find_map(|props| description_from_properties(&props))
+    }
+
+    /// Whether this module was created by the application
+    pub fn is_owned(&self) -> bool {
+        self.args.contains(OWNED_NAME_PREFIX)
+    }
+}
+
+/// Load a module through `pactl` and return its index.
+///
+/// Modules are loaded into pipewire-pulse rather than a native context so that
+/// they outlive this process, like links created with `object.linger`.
+pub fn load_module(name: &str, args: &str) -> Result<u32, anyhow::Error> {
+    let mut command = host_command("pactl");
+    command.arg("load-module").arg(name);
+    if !args.is_empty() {
+        command.arg(args);
+    }
+
+    let output = command.output()?;
+    if !output.status.success() {
+        let stderr = String::from_utf8_lossy(&output.stderr);
+        anyhow::bail!("Failed to load {}: {}", name, stderr.trim());
+    }
+
+    let stdout = String::from_utf8_lossy(&output.stdout);
+    stdout
+        .trim()
+        .parse()
+        .map_err(|_| anyhow::anyhow!("Unexpected pactl output: {}", stdout.trim()))
+}
+
+/// Unload a module by index
+pub fn unload_module(id: u32) -> Result<(), anyhow::Error> {
+    let output = host_command("pactl")
+        .args(["unload-module", &id.to_string()])
+        .output()?;
+
+    if !output.status.success() {
+        let stderr = String::from_utf8_lossy(&output.stderr);
+        anyhow::bail!("Failed to unload module {}: {}", id, stderr.trim());
+    }
+
+    Ok(())
+}
+
+/// List all loaded modules
+pub fn list_modules() -> Result<Vec<LoadedModule>, anyhow::Error> {
+    let output = host_command("pactl")
+        .args(["list", "short", "modules"])
+        .output()?;
+
+    if !output.status.success() {
+        let stderr = String::from_utf8_lossy(&output.stderr);
+        anyhow::bail!("Failed to list modules: {}", stderr.trim());
+    }
+
+    // Each line is "<index>\t<name>\t<arguments>\t<users>" with trailing fields optional
+    let stdout = String::from_utf8_lossy(&output.stdout);
+    Ok(stdout
+        .lines()
+        .filter_map(|line| {
+            let mut fields = line.split('\t');
+            let id = fields.next()?.trim().parse().ok()?;
+            let name = fields.next()?.trim().to_string();
+            let args = fields.next().unwrap_or("").trim().to_string();
+            Some(LoadedModule { id, name, args })
+        })
+        .collect())
+}
+
+/// Split a module argument string into `(key, raw value)` pairs.
+///
+/// Handles the `key=value`, `key="quoted value"` and `key='quoted value'` forms
+/// used by Pulse module arguments. Raw values keep their surrounding quotes.
+fn split_args(args: &str) -> Vec<(&str, &str)> {
+    let mut pairs = Vec::new();
+    let mut rest = args.trim_start();
+
+    while let Some(eq) = rest.find('=') {
+        let key = &rest[..eq];
+        let after = &rest[eq + 1..];
+
+        let end = match after.chars().next() {
+            Some(quote @ ('"' | '\'')) => after[1..]
+                .find(quote)
+                .map(|i| i + 2)
+                .unwrap_or(after.len()),
+            _ => after.find(char::is_whitespace).unwrap_or(after.len()),
+        };
+
+        pairs.push((key, &after[..end]));
+        rest = after[end..].trim_start();
+    }
+
+    pairs
+}
+
+/// Strip one level of matching quotes from a raw argument value
+fn unquote(raw: &str) -> &str {
+    for quote in ['"', '\''] {
+        if raw.len() >= 2 && raw.starts_with(quote) && raw.ends_with(quote) {
+            return &raw[1..raw.len() - 1];
+        }
+    }
+    raw
+}
+
+/// Extract the value of `key` from a module argument string
+pub fn module_arg(args: &str, key: &str) -> Option<String> {
+    split_args(args)
+        .into_iter()
+        .find(|(k, _)| *k == key)
+        .map(|(_, v)| unquote(v).to_string())
+}
+
+/// Return `args` with the value of `key` replaced (or appended if missing).
+/// `raw_value` is inserted verbatim, so it must already be quoted if needed.
+pub fn replace_module_arg(args: &str, key: &str, raw_value: &str) -> String {
+    let mut found = false;
+    let mut parts: Vec<String> = split_args(args)
+        .into_iter()
+        .map(|(k, v)| {
+            if k == key {
+                found = true;
+                format!("{}={}", k, raw_value)
+            } else {
+                format!("{}={}", k, v)
+            }
+        })
+        .collect();
+
+    if !found {
+        parts.push(format!("{}={}", key, raw_value));
+    }
+
+    parts.join(" ")
+}
+
+/// Extract the `device.description` from a `*_properties` argument value
+pub fn description_from_properties(properties: &str) -> Option<String> {
+    split_args(properties)
+        .into_iter()
+        .find(|(k, _)| *k == "device.description")
+        .map(|(_, v)| unquote(v).to_string())
+}
+
+/// Turn a user supplied name into a safe node name suffix
+pub fn slugify(name: &str) -> String {
+    let slug: String = name
+        .trim()
+        .chars()
+        .map(|c| {
+            if c.is_ascii_alphanumeric() {
+                c.to_ascii_lowercase()
+            } else {
+                '_'
+            }
+        })
+        .collect();
+
+    if slug.is_empty() {
+        "device".into()
+    } else {
+        slug
+    }
+}
+
+/// Strip whitespace and quoting characters from a node name given by the user
+pub fn sanitize_node_name(name: &str) -> String {
+    name.chars()
+        .filter(|c| !c.is_whitespace() && !matches!(c, '"' | '\'' | '\\'))
+        .collect()
+}
+
+/// Strip characters that would break Pulse argument and property list quoting
+pub fn sanitize_description(description: &str) -> String {
+    description
+        .trim()
+        .chars()
+        .filter(|c| !matches!(c, '"' | '\'' | '\\'))
+        .collect()
+}
+
+/// Build a `*_properties` argument value setting the device description
+pub fn description_properties(description: &str) -> String {
+    format!(
+        "'device.description=\"{}\"'",
+        sanitize_description(description)
+    )
+}
+
+/// A native PipeWire module hosted in a helper `pipewire -c <config>` process.
+///
+/// Some modules (filter-chain, VBAN) have no Pulse equivalent, so they run in
+/// a minimal PipeWire client instance owned by this process. Dropping the
+/// host stops the helper and removes the nodes it created.
+pub struct ModuleHost {
+    child: std::process::Child,
+    config_path: std::path::PathBuf,
+}
+
+impl ModuleHost {
+    /// Start a helper hosting `module` with the given SPA-JSON `args`,
+    /// connected to the server with socket `remote`, or the default one
+    pub fn spawn(
+        key: &str,
+        module: &str,
+        args: &str,
+        remote: Option<&str>,
+    ) -> Result<Self, anyhow::Error> {
+        // Read by the helper on the host when running inside Flatpak
+        let dir = host_runtime_dir();
+        std::fs::create_dir_all(&dir)?;
+
+        let config_path = dir.join(format!("{}.conf", key));
+        let config = format!(
+            r#"context.properties = {{
+    log.level = 0
+}}
+context.spa-libs = {{
+    audio.convert.* = audioconvert/libspa-audioconvert
+    support.*       = support/libspa-support
+}}
+context.modules = [
+    {{ name = libpipewire-module-rt flags = [ ifexists nofail ] }}
+    {{ name = libpipewire-module-protocol-native }}
+    {{ name = libpipewire-module-client-node }}
+    {{ name = libpipewire-module-adapter }}
+    {{ name = {}
+        args = {}
+    }}
+]
+"#,
+            module, args
+        );
+        std::fs::write(&config_path, config)?;
+
+        // The hosted module connects wherever PIPEWIRE_REMOTE points. Set
+        // through `env`, as the environment of the sandbox doesn't reach
+        // the host.
+        let mut command = host_command("env");
+        if let Some(remote) = remote {
+            command.arg(format!("PIPEWIRE_REMOTE={}", remote));
+        }
+        command.arg("pipewire").arg("-c").arg(&config_path);
+        let child = command
+            .stdout(std::process::Stdio::null())
+            .stderr(std::process::Stdio::null())
+            .spawn()?;
+
+        Ok(Self { child, config_path })
+    }
+
+    /// Check whether the helper exited (e.g. because the module failed to load)
+    pub fn has_exited(&mut self) -> bool {
+        !matches!(self.child.try_wait(), Ok(None))
+    }
+
+    /// Stop the helper process
+    pub fn stop(&mut self) {
+        let _ = self.child.kill();
+        let _ = self.child.wait();
+        let _ = std::fs::remove_file(&self.config_path);
+    }
+}
+
+impl Drop for ModuleHost {
+    fn drop(&mut self) {
+        self.stop();
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn split_args_keeps_quoted_values_together() {
+        let args = r#"sink_name=combined sink_properties="device.description=Out" slaves=a,b"#;
+        assert_eq!(
+            split_args(args),
+            vec![
+                ("sink_name", "combined"),
+                ("sink_properties", r#""device.description=Out""#),
+                ("slaves", "a,b"),
+            ]
+        );
+    }
+
+    #[test]
+    fn module_arg_unquotes_double_and_single_quotes() {
+        let args = r#"sink_name="My Sink" source_name='My Source' rate=48000"#;
+        assert_eq!(module_arg(args, "sink_name").as_deref(), Some("My Sink"));
+        assert_eq!(
+            module_arg(args, "source_name").as_deref(),
+            Some("My Source")
+        );
+        assert_eq!(module_arg(args, "rate").as_deref(), Some("48000"));
+        assert_eq!(module_arg(args, "channels"), None);
+    }
+
+    #[test]
+    fn nested_properties_keep_their_inner_quotes() {
+        let args = r#"source_properties='device.description="Echo Cancel"' aec_method=webrtc"#;
+        let properties = module_arg(args, "source_properties").unwrap();
+        assert_eq!(properties, r#"device.description="Echo Cancel""#);
+        assert_eq!(
+            description_from_properties(&properties).as_deref(),
+            Some("Echo Cancel")
+        );
+        assert_eq!(description_from_properties("node.nick=x"), None);
+    }
+
+    #[test]
+    fn replace_module_arg_replaces_in_place() {
+        let args = r#"sink_name=x sink_properties='device.description="Old"' rate=48000"#;
+        let renamed = replace_module_arg(args, "sink_properties", &description_properties("New"));
+        assert_eq!(
+            renamed,
+            r#"sink_name=x sink_properties='device.description="New"' rate=48000"#
+        );
+        let properties = module_arg(&renamed, "sink_properties").unwrap();
+        assert_eq!(
+            description_from_properties(&properties).as_deref(),
+            Some("New")
+        );
+    }
+
+    #[test]
+    fn replace_module_arg_appends_a_missing_key() {
+        assert_eq!(
+            replace_module_arg("sink_name=x", "sink_properties", "'a=b'"),
+            "sink_name=x sink_properties='a=b'"
+        );
+    }
+
+    #[test]
+    fn description_properties_drops_quotes() {
+        assert_eq!(
+            description_properties(r#" My "Mic" "#),
+            r#"'device.description="My Mic"'"#
+        );
+    }
+
+    #[test]
+    fn slugify_falls_back_when_nothing_is_left() {
+        assert_eq!(slugify("Living Room!"), "living_room_");
+        assert_eq!(slugify("   "), "device");
+        assert_eq!(slugify(""), "device");
+    }
+}
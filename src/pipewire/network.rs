@@ -0,0 +1,161 @@
+use super::modules::{sanitize_description, OWNED_NAME_PREFIX};
+
+/// Module that listens for SAP announcements and receives announced streams
+pub const RTP_SAP_MODULE: &str = "libpipewire-module-rtp-sap";
+
+/// Module that receives a single RTP stream from a known address
+pub const RTP_SOURCE_MODULE: &str = "libpipewire-module-rtp-source";
+
+/// Module that discovers AirPlay (RAOP) speakers and creates a sink for each
+pub const RAOP_DISCOVER_MODULE: &str = "libpipewire-module-raop-discover";
+
+/// Node name prefix of sinks created by `module-raop-discover`
+pub const RAOP_SINK_PREFIX: &str = "raop_sink.";
+
+/// Modules sending and receiving VBAN streams
+pub const VBAN_SEND_MODULE: &str = "libpipewire-module-vban-send";
+pub const VBAN_RECV_MODULE: &str = "libpipewire-module-vban-recv";
+
+/// Default VBAN UDP port
+pub const VBAN_DEFAULT_PORT: u16 = 6980;
+
+/// Default multicast address and port for SAP announcements
+pub const SAP_DEFAULT_ADDRESS: &str = "224.0.0.56";
+pub const SAP_DEFAULT_PORT: u16 = 9875;
+
+/// Default RTP port used by `module-rtp-sink`
+pub const RTP_DEFAULT_PORT: u16 = 46000;
+
+/// Prefix shared by the helper keys of one kind of network module
+pub fn network_key_prefix(kind: &str) -> String {
+    format!("{}{}_", OWNED_NAME_PREFIX, kind)
+}
+
+/// Helper key for a network module instance
+pub fn network_key(kind: &str, instance: u32) -> String {
+    format!("{}{}", network_key_prefix(kind), instance)
+}
+
+/// Arguments for `module-rtp-sap` receiving every announced session.
+///
+/// Announced streams become sources with autoconnect disabled so they show
+/// up in the port lists for patching instead of playing immediately.
+pub fn rtp_sap_args(address: &str, port: u16) -> String {
+    format!(
+        r#"{{
+        sap.ip = "{address}"
+        sap.port = {port}
+        stream.rules = [
+            {{
+                matches = [ {{ rtp.session = "~.*" }} ]
+                actions = {{
+                    create-stream = {{
+                        node.autoconnect = false
+                        media.class = "Audio/Source"
+                    }}
+                }}
+            }}
+        ]
+    }}"#,
+        address = sanitize_description(address),
+        port = port,
+    )
+}
+
+/// Arguments for `module-rtp-source` receiving one stream on `address:port`
+pub fn rtp_source_args(key: &str, description: &str, address: &str, port: u16) -> String {
+    format!(
+        r#"{{
+        source.ip = "{address}"
+        source.port = {port}
+        sess.latency.msec = 100
+        sess.media = "audio"
+        audio.format = "S16BE"
+        audio.rate = 48000
+        audio.channels = 2
+        audio.position = [ FL FR ]
+        stream.props = {{
+            node.name = "{key}"
+            node.description = "{description}"
+            media.class = "Audio/Source"
+            node.autoconnect = false
+        }}
+    }}"#,
+        address = sanitize_description(address),
+        port = port,
+        key = key,
+        description = sanitize_description(description),
+    )
+}
+
+/// Arguments for `module-raop-discover` creating a sink for every speaker found
+pub fn raop_discover_args() -> String {
+    r#"{
+        stream.rules = [
+            {
+                matches = [ { raop.ip = "~.*" } ]
+                actions = {
+                    create-stream = { }
+                }
+            }
+        ]
+    }"#
+    .to_string()
+}
+
+/// Whether a node is an AirPlay sink created by RAOP discovery
+pub fn is_raop_sink(node_name: &str) -> bool {
+    node_name.starts_with(RAOP_SINK_PREFIX)
+}
+
+/// Arguments for `module-vban-send`, creating a sink that streams to `address:port`
+pub fn vban_send_args(
+    key: &str,
+    description: &str,
+    stream_name: &str,
+    address: &str,
+    port: u16,
+) -> String {
+    format!(
+        r#"{{
+        destination.ip = "{address}"
+        destination.port = {port}
+        sess.name = "{stream_name}"
+        audio.format = "S16LE"
+        audio.rate = 48000
+        audio.channels = 2
+        audio.position = [ FL FR ]
+        stream.props = {{
+            node.name = "{key}"
+            node.description = "{description}"
+            media.class = "Audio/Sink"
+            node.autoconnect = false
+        }}
+    }}"#,
+        address = sanitize_description(address),
+        port = port,
+        stream_name = sanitize_description(stream_name),
+        key = key,
+        description = sanitize_description(description),
+    )
+}
+
+/// Arguments for `module-vban-recv`, creating a source for streams arriving on `port`
+pub fn vban_recv_args(key: &str, description: &str, port: u16) -> String {
+    format!(
+        r#"{{
+        source.ip = "0.0.0.0"
+        source.port = {port}
+        sess.latency.msec = 100
+        stream.props = {{
+            node.name = "{key}"
+            node.description = "{description}"
+            media.class = "Audio/Source"
+            node.autoconnect = false
+        }}
+    }}"#,
+        port = port,
+        key = key,
+        description = sanitize_description(description),
+    )
+}
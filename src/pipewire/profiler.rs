@@ -0,0 +1,339 @@
+use std::io::{BufRead, BufReader};
+use std::process::{Child, Stdio};
+use std::thread;
+
+use async_channel::Sender;
+
+/// Load of one node in a graph cycle, as `pw-top` reports it from the
+/// PipeWire profiler
+#[derive(Debug, Clone, PartialEq)]
+pub struct NodeLoad {
+    pub id: u32,
+    /// R running, I idle, S suspended, C creating or E error
+    pub state: char,
+    pub quantum: Option<u32>,
+    pub rate: Option<u32>,
+    /// Time from being woken to starting to process, in microseconds
+    pub wait_us: Option<f64>,
+    /// Time spent processing, in microseconds
+    pub busy_us: Option<f64>,
+    /// Busy time as a fraction of the quantum; near 1.0 means xruns
+    pub busy_quantum: Option<f64>,
+    /// Xruns and other errors since the node was created
+    pub errors: u64,
+    pub name: String,
+    /// Driver the node follows, `None` for a driver itself
+    pub driver_id: Option<u32>,
+}
+
+impl NodeLoad {
+    pub fn is_driver(&self) -> bool {
+        self.driver_id.is_none()
+    }
+}
+
+/// A duration as `pw-top` prints it ("11.5us", "2.1ms", "---")
+fn parse_duration_us(s: &str) -> Option<f64> {
+    if let Some(v) = s.strip_suffix("us") {
+        v.parse().ok()
+    } else if let Some(v) = s.strip_suffix("ms") {
+        v.parse::<f64>().ok().map(|v| v * 1000.0)
+    } else if let Some(v) = s.strip_suffix('s') {
+        v.parse::<f64>().ok().map(|v| v * 1_000_000.0)
+    } else {
+        None
+    }
+}
+
+/// Where the values of a node line are, by position among its
+/// whitespace-separated fields, read from the column header of `pw-top -b`.
+/// Columns are added between PipeWire versions, so they are found by name
+/// and unknown ones are skipped.
+#[derive(Debug, Clone, PartialEq)]
+pub struct Columns {
+    state: usize,
+    id: usize,
+    quantum: Option<usize>,
+    rate: Option<usize>,
+    wait: Option<usize>,
+    busy: Option<usize>,
+    busy_quantum: Option<usize>,
+    errors: Option<usize>,
+    /// Fields before the format and name, which take a varying number
+    fixed: usize,
+}
+
+impl Columns {
+    /// Read the header line, "S   ID  QUANT   RATE    WAIT ...". `None` if
+    /// the line is no header or lacks the state and id columns.
+    pub fn from_header(line: &str) -> Option<Self> {
+        let names: Vec<&str> = line.split_whitespace().collect();
+        let find = |name: &str| names.iter().position(|n| *n == name);
+        if names.first() != Some(&"S") {
+            return None;
+        }
+        let fixed = names
+            .iter()
+            .position(|n| *n == "FORMAT" || *n == "NAME")
+            .unwrap_or(names.len());
+        let columns = Self {
+            state: find("S")?,
+            id: find("ID")?,
+            quantum: find("QUANT"),
+            rate: find("RATE"),
+            wait: find("WAIT"),
+            busy: find("BUSY"),
+            busy_quantum: find("B/Q"),
+            errors: find("ERR"),
+            fixed,
+        };
+        (columns.id < fixed).then_some(columns)
+    }
+}
+
+impl Default for Columns {
+    /// The columns of PipeWire 1.x, until a header is read
+    fn default() -> Self {
+        Self {
+            state: 0,
+            id: 1,
+            quantum: Some(2),
+            rate: Some(3),
+            wait: Some(4),
+            busy: Some(5),
+            busy_quantum: Some(7),
+            errors: Some(8),
+            fixed: 9,
+        }
+    }
+}
+
+/// Parse one node line of `pw-top -b`. Followers' names start with "+ "
+/// and follow the last driver listed before them.
+fn parse_line(line: &str, columns: &Columns, driver_id: Option<u32>) -> Option<NodeLoad> {
+    let fields: Vec<&str> = line.split_whitespace().collect();
+    if fields.len() < columns.fixed {
+        return None;
+    }
+    let field = |index: Option<usize>| index.and_then(|i| fields.get(i).copied());
+
+    let state = fields[columns.state].chars().next()?;
+    let id = fields[columns.id].parse().ok()?;
+    let quantum = field(columns.quantum)
+        .and_then(|q| q.parse().ok())
+        .filter(|q| *q != 0);
+    let rate = field(columns.rate)
+        .and_then(|r| r.parse().ok())
+        .filter(|r| *r != 0);
+    let wait_us = field(columns.wait).and_then(parse_duration_us);
+    let busy_us = field(columns.busy).and_then(parse_duration_us);
+    let busy_quantum = field(columns.busy_quantum).and_then(|b| b.parse().ok());
+    let errors = field(columns.errors)
+        .and_then(|e| e.parse().ok())
+        .unwrap_or(0);
+
+    // The format takes none to three fields; the name is whatever follows,
+    // which for a follower starts at its "+" marker
+    let rest = &fields[columns.fixed..];
+    let (name, follower) = match rest.iter().position(|f| *f == "+") {
+        Some(i) => (rest[i + 1..].join(" "), true),
+        None => (rest.last().map(|s| s.to_string()).unwrap_or_default(), false),
+    };
+
+    Some(NodeLoad {
+        id,
+        state,
+        quantum,
+        rate,
+        wait_us,
+        busy_us,
+        busy_quantum,
+        errors,
+        name,
+        driver_id: if follower { driver_id } else { None },
+    })
+}
+
+/// Parse one refresh of `pw-top -b`, without its header line
+pub fn parse_batch(lines: &[String], columns: &Columns) -> Vec<NodeLoad> {
+    let mut loads = Vec::new();
+    let mut driver_id = None;
+    for line in lines {
+        if let Some(load) = parse_line(line, columns, driver_id) {
+            if load.is_driver() {
+                driver_id = Some(load.id);
+            }
+            loads.push(load);
+        }
+    }
+    loads
+}
+
+/// Runs `pw-top` in batch mode and sends each refresh, about once a
+/// second, until dropped
+pub struct ProfilerMonitor {
+    child: Child,
+}
+
+impl ProfilerMonitor {
+    /// Start monitoring the server with socket `remote`, or the default one
+    pub fn spawn(remote: Option<&str>, tx: Sender<Vec<NodeLoad>>) -> Result<Self, anyhow::Error> {
+        let mut command = crate::sandbox::host_command("pw-top");
+        command.arg("--batch-mode");
+        if let Some(remote) = remote {
+            command.args(["--remote", remote]);
+        }
+        let mut child = command
+            .stdout(Stdio::piped())
+            .stderr(Stdio::null())
+            .spawn()
+            .map_err(|e| match e.kind() {
+                std::io::ErrorKind::NotFound => {
+                    anyhow::anyhow!("pw-top is not installed; it comes with the PipeWire tools")
+                }
+                _ => anyhow::anyhow!("Failed to run pw-top: {}", e),
+            })?;
+        let stdout = child
+            .stdout
+            .take()
+            .ok_or_else(|| anyhow::anyhow!("pw-top has no output"))?;
+
+        thread::Builder::new()
+            .name("pw-top".into())
+            .spawn(move || {
+                // Each refresh starts with the column header
+                let mut columns = Columns::default();
+                let mut batch: Vec<String> = Vec::new();
+                for line in BufReader::new(stdout).lines() {
+                    let Ok(line) = line else {
+                        break;
+                    };
+                    if let Some(header) = Columns::from_header(&line) {
+                        if !batch.is_empty()
+                            && tx.send_blocking(parse_batch(&batch, &columns)).is_err()
+                        {
+                            break;
+                        }
+                        batch.clear();
+                        columns = header;
+                    } else if !line.trim().is_empty() {
+                        batch.push(line);
+                    }
+                }
+                log::debug!("pw-top stopped");
+            })?;
+
+        Ok(Self { child })
+    }
+}
+
+impl Drop for ProfilerMonitor {
+    fn drop(&mut self) {
+        let _ = self.child.kill();
+        let _ = self.child.wait();
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// A refresh of `pw-top -b` from PipeWire 1.0, with a format column
+    const PIPEWIRE_1_0: &str = "\
+S   ID  QUANT   RATE    WAIT    BUSY   W/Q   B/Q  ERR FORMAT           NAME
+R   30   1024  48000  23.4us  12.3us  0.02  0.01    0    S32LE 2 48000 alsa_output.pci-0000_00_1f.3.analog-stereo
+R   78   1024  48000  10.1us   2.1ms  0.01  0.98    3    F32LE 2 48000  + Firefox
+R   81   1024  48000   9.8us   5.2us  0.01  0.00    0                   + pw-audioshare-test-signal
+S   31      0      0    ---     ---   ---   ---     0                  alsa_input.pci-0000_00_1f.3.analog-stereo";
+
+    /// The same from PipeWire 0.3, before the format column was added
+    const PIPEWIRE_0_3: &str = "\
+S   ID  QUANT   RATE    WAIT    BUSY   W/Q   B/Q  ERR  NAME
+R   30   1024  48000  23.4us  12.3us  0.02  0.01    0  alsa_output.pci-0000_00_1f.3.analog-stereo
+R   78   1024  48000  10.1us   2.1ms  0.01  0.98    3   + Firefox";
+
+    fn parse(output: &str) -> Vec<NodeLoad> {
+        let mut lines = output.lines();
+        let columns = Columns::from_header(lines.next().unwrap()).unwrap();
+        let lines: Vec<String> = lines.map(String::from).collect();
+        parse_batch(&lines, &columns)
+    }
+
+    #[test]
+    fn durations_in_any_unit_are_microseconds() {
+        assert_eq!(parse_duration_us("11.5us"), Some(11.5));
+        assert_eq!(parse_duration_us("2.1ms"), Some(2100.0));
+        assert_eq!(parse_duration_us("1s"), Some(1_000_000.0));
+        assert_eq!(parse_duration_us("---"), None);
+    }
+
+    #[test]
+    fn reads_drivers_and_their_followers() {
+        let loads = parse(PIPEWIRE_1_0);
+        assert_eq!(loads.len(), 4);
+
+        let driver = &loads[0];
+        assert_eq!(driver.id, 30);
+        assert_eq!(driver.state, 'R');
+        assert_eq!(driver.quantum, Some(1024));
+        assert_eq!(driver.rate, Some(48000));
+        assert_eq!(driver.busy_us, Some(12.3));
+        assert_eq!(driver.name, "alsa_output.pci-0000_00_1f.3.analog-stereo");
+        assert!(driver.is_driver());
+
+        let firefox = &loads[1];
+        assert_eq!(firefox.name, "Firefox");
+        assert_eq!(firefox.driver_id, Some(30));
+        assert_eq!(firefox.busy_us, Some(2100.0));
+        assert_eq!(firefox.busy_quantum, Some(0.98));
+        assert_eq!(firefox.errors, 3);
+
+        // No format, as for a node that hasn't negotiated one
+        assert_eq!(loads[2].name, "pw-audioshare-test-signal");
+        assert_eq!(loads[2].driver_id, Some(30));
+    }
+
+    #[test]
+    fn suspended_nodes_have_no_timing() {
+        let suspended = &parse(PIPEWIRE_1_0)[3];
+        assert_eq!(suspended.state, 'S');
+        assert_eq!(suspended.quantum, None);
+        assert_eq!(suspended.rate, None);
+        assert_eq!(suspended.wait_us, None);
+        assert_eq!(suspended.busy_quantum, None);
+        assert!(suspended.is_driver());
+    }
+
+    #[test]
+    fn reads_output_without_a_format_column() {
+        let loads = parse(PIPEWIRE_0_3);
+        assert_eq!(loads.len(), 2);
+        assert_eq!(loads[0].name, "alsa_output.pci-0000_00_1f.3.analog-stereo");
+        assert_eq!(loads[1].name, "Firefox");
+        assert_eq!(loads[1].errors, 3);
+        assert_eq!(loads[1].driver_id, Some(30));
+    }
+
+    #[test]
+    fn skips_unknown_columns() {
+        let output = "\
+S   ID  QUANT   RATE    WAIT    BUSY   W/Q   B/Q   NEW  ERR FORMAT           NAME
+R   30   1024  48000  23.4us  12.3us  0.02  0.01    7    2    S32LE 2 48000 alsa_output.usb";
+        let loads = parse(output);
+        assert_eq!(loads[0].errors, 2);
+        assert_eq!(loads[0].name, "alsa_output.usb");
+    }
+
+    #[test]
+    fn header_needs_state_and_id() {
+        assert!(Columns::from_header("S   ID  QUANT   RATE  NAME").is_some());
+        assert!(Columns::from_header("R   30   1024  48000  alsa_output").is_none());
+        assert!(Columns::from_header("S   QUANT   RATE  NAME").is_none());
+    }
+
+    #[test]
+    fn short_lines_are_skipped() {
+        let columns = Columns::default();
+        assert_eq!(parse_line("R   30   1024", &columns, None), None);
+    }
+}
@@ -0,0 +1,40 @@
+use std::path::Path;
+
+use serde::{Deserialize, Serialize};
+
+use super::messages::PwEvent;
+
+/// A PipeWire event captured by the event debugger
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct RecordedEvent {
+    /// Local time the event reached the UI
+    pub time: String,
+    /// Microseconds since recording started
+    pub elapsed_us: i64,
+    pub event: PwEvent,
+}
+
+impl RecordedEvent {
+    /// The event as one line of text for the debugger view
+    pub fn line(&self) -> String {
+        format!(
+            "{} +{:.3}s {:?}",
+            self.time,
+            self.elapsed_us as f64 / 1_000_000.0,
+            self.event
+        )
+    }
+}
+
+/// Write recorded events to a JSON file
+pub fn save_recording(path: &Path, events: &[RecordedEvent]) -> Result<(), anyhow::Error> {
+    let content = serde_json::to_string_pretty(events)?;
+    std::fs::write(path, content)?;
+    Ok(())
+}
+
+/// Read events written by `save_recording`
+pub fn load_recording(path: &Path) -> Result<Vec<RecordedEvent>, anyhow::Error> {
+    let content = std::fs::read_to_string(path)?;
+    Ok(serde_json::from_str(&content)?)
+}
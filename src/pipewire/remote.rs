@@ -0,0 +1,60 @@
+use std::path::PathBuf;
+
+/// Socket the PipeWire client library connects to unless told otherwise
+pub const DEFAULT_REMOTE: &str = "pipewire-0";
+
+/// Point the PipeWire library, and the `pw-*` tools started by the app, at
+/// the socket chosen in settings. `PIPEWIRE_REMOTE` set in the environment
+/// takes precedence. Must run before any thread is started.
+pub fn apply_setting(remote: Option<&str>) {
+    let Some(remote) = remote.filter(|r| !r.is_empty()) else {
+        return;
+    };
+    if std::env::var_os("PIPEWIRE_REMOTE").is_some() {
+        log::info!("PIPEWIRE_REMOTE is set, ignoring the configured socket {}", remote);
+        return;
+    }
+    std::env::set_var("PIPEWIRE_REMOTE", remote);
+}
+
+/// Name or path of the socket in use, as `PIPEWIRE_REMOTE` takes it
+pub fn remote_name() -> String {
+    std::env::var("PIPEWIRE_REMOTE").unwrap_or_else(|_| DEFAULT_REMOTE.into())
+}
+
+/// Directory relative socket names are looked up in
+fn runtime_dir() -> Option<PathBuf> {
+    std::env::var_os("PIPEWIRE_RUNTIME_DIR")
+        .or_else(|| std::env::var_os("XDG_RUNTIME_DIR"))
+        .map(PathBuf::from)
+}
+
+/// Path of the socket the PipeWire client library connects to
+pub fn socket_path() -> Option<PathBuf> {
+    let remote = remote_name();
+    if remote.starts_with('/') {
+        return Some(PathBuf::from(remote));
+    }
+    Some(runtime_dir()?.join(remote))
+}
+
+/// Names of the PipeWire sockets in the runtime directory, such as
+/// `pipewire-0` and a second instance's `pipewire-1`
+pub fn available_remotes() -> Vec<String> {
+    use std::os::unix::fs::FileTypeExt;
+
+    let Some(dir) = runtime_dir() else {
+        return Vec::new();
+    };
+    let Ok(entries) = std::fs::read_dir(dir) else {
+        return Vec::new();
+    };
+    let mut names: Vec<String> = entries
+        .filter_map(|entry| entry.ok())
+        .filter(|entry| entry.file_type().is_ok_and(|t| t.is_socket()))
+        .filter_map(|entry| entry.file_name().into_string().ok())
+        .filter(|name| name.starts_with("pipewire-"))
+        .collect();
+    names.sort();
+    names
+}
@@ -1,6 +1,8 @@
-use std::collections::HashMap;
+use std::collections::{BTreeMap, HashMap};
 
-use super::messages::{LinkState, MediaType, PortDirection};
+use super::messages::{
+    DeviceProfile, DeviceRoute, LinkState, MediaType, PortDirection, VideoFormat,
+};
 
 /// Represents a PipeWire node (audio device, application, etc.)
 #[derive(Debug, Clone)]
@@ -10,6 +12,12 @@ pub struct PwNode {
     pub media_class: Option<String>,
     pub description: Option<String>,
     pub application_name: Option<String>,
+    /// `object.serial`, unlike `id` never reused while the server runs
+    pub serial: Option<u64>,
+    pub nick: Option<String>,
+    pub device_name: Option<String>,
+    /// `client.id`, the client that created the node
+    pub client_id: Option<u32>,
 }
 
 impl PwNode {
@@ -20,6 +28,41 @@ impl PwNode {
             .or(self.application_name.as_deref())
             .unwrap_or(&self.name)
     }
+
+    /// Application the node belongs to, or its display name for devices
+    pub fn app_name(&self) -> &str {
+        self.application_name
+            .as_deref()
+            .unwrap_or_else(|| self.display_name())
+    }
+
+    /// Whether the node is an application's playback or recording stream
+    pub fn is_stream(&self) -> bool {
+        self.media_class
+            .as_deref()
+            .is_some_and(|class| class.starts_with("Stream/"))
+    }
+
+    /// Media type for one of this node's ports, using media.class when the
+    /// port itself didn't say
+    pub fn infer_media_type(&self, reported: MediaType) -> MediaType {
+        if reported != MediaType::Unknown {
+            return reported;
+        }
+        let Some(ref mc) = self.media_class else {
+            return reported;
+        };
+        let mc_lower = mc.to_lowercase();
+        if mc_lower.contains("video") {
+            MediaType::Video
+        } else if mc_lower.contains("midi") {
+            MediaType::Midi
+        } else if mc_lower.contains("audio") || mc_lower.contains("stream") {
+            MediaType::Audio
+        } else {
+            reported
+        }
+    }
 }
 
 /// Represents a port on a node
@@ -41,6 +84,33 @@ impl PwPort {
     }
 }
 
+/// Formats of a video port as last reported
+#[derive(Debug, Clone, Default)]
+pub struct PortVideoFormats {
+    pub offered: Vec<VideoFormat>,
+    /// The negotiated format, while the port streams
+    pub current: Option<VideoFormat>,
+}
+
+impl PortVideoFormats {
+    /// Formats the port can use now: the negotiated one once streaming,
+    /// as it won't change for a new link, or else any it offers
+    pub fn usable(&self) -> &[VideoFormat] {
+        match &self.current {
+            Some(current) => std::slice::from_ref(current),
+            None => &self.offered,
+        }
+    }
+
+    /// The negotiated format, or the preferred one offered
+    pub fn label(&self) -> Option<String> {
+        self.current
+            .as_ref()
+            .or(self.offered.first())
+            .map(VideoFormat::label)
+    }
+}
+
 /// Represents a link between two ports
 #[derive(Debug, Clone)]
 pub struct PwLink {
@@ -52,12 +122,130 @@ pub struct PwLink {
     pub state: LinkState,
 }
 
+/// Represents a device owning nodes, such as a sound card or Bluetooth headset
+#[derive(Debug, Clone)]
+pub struct PwDevice {
+    pub id: u32,
+    pub name: String,
+    pub description: Option<String>,
+    pub api: Option<String>,
+    pub profiles: Vec<DeviceProfile>,
+    pub active_profile: Option<u32>,
+    pub routes: Vec<DeviceRoute>,
+}
+
+impl PwDevice {
+    pub fn display_name(&self) -> &str {
+        self.description.as_deref().unwrap_or(&self.name)
+    }
+
+    pub fn is_bluetooth(&self) -> bool {
+        self.api.as_deref() == Some("bluez5")
+    }
+
+    pub fn active_profile(&self) -> Option<&DeviceProfile> {
+        let index = self.active_profile?;
+        self.profiles.iter().find(|p| p.index == index)
+    }
+
+    /// Routes that can be selected on one device of the card with the
+    /// current profile
+    pub fn routes_for(&self, card_device: u32) -> Vec<&DeviceRoute> {
+        self.routes
+            .iter()
+            .filter(|r| r.devices.contains(&card_device))
+            .filter(|r| {
+                r.profiles.is_empty()
+                    || self.active_profile.is_some_and(|p| r.profiles.contains(&p))
+            })
+            .collect()
+    }
+
+    /// The route selected on one device of the card
+    pub fn active_route(&self, card_device: u32) -> Option<&DeviceRoute> {
+        self.routes.iter().find(|r| r.active_on.contains(&card_device))
+    }
+}
+
+/// Represents a client connected to PipeWire
+#[derive(Debug, Clone)]
+pub struct PwClient {
+    pub id: u32,
+    pub application_name: Option<String>,
+    pub pid: Option<u32>,
+    /// `application.process.binary`, e.g. "firefox"
+    pub process_binary: Option<String>,
+}
+
+impl PwClient {
+    /// The application, such as "Firefox (PID 4242)"
+    pub fn label(&self) -> Option<String> {
+        let name = self
+            .application_name
+            .as_deref()
+            .or(self.process_binary.as_deref())?;
+        Some(match self.pid {
+            Some(pid) => format!("{} (PID {})", name, pid),
+            None => name.to_string(),
+        })
+    }
+
+    /// Whether the client is a session manager, which makes links by policy
+    pub fn is_session_manager(&self) -> bool {
+        self.application_name.as_deref().is_some_and(|name| {
+            name.starts_with("WirePlumber") || name == "pipewire-media-session"
+        })
+    }
+}
+
+/// Who made a link
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum LinkOrigin {
+    /// This application, e.g. by hand, a preset or auto-connect
+    App,
+    SessionManager,
+    /// Another application, or a client that has gone
+    External,
+}
+
+impl LinkOrigin {
+    pub const ALL: [LinkOrigin; 3] = [
+        LinkOrigin::App,
+        LinkOrigin::SessionManager,
+        LinkOrigin::External,
+    ];
+
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            LinkOrigin::App => "app",
+            LinkOrigin::SessionManager => "session-manager",
+            LinkOrigin::External => "external",
+        }
+    }
+
+    pub fn label(&self) -> &'static str {
+        match self {
+            LinkOrigin::App => "This App",
+            LinkOrigin::SessionManager => "Session Manager",
+            LinkOrigin::External => "Other Applications",
+        }
+    }
+}
+
 /// Holds the complete PipeWire state as seen by the application
 #[derive(Debug, Default)]
 pub struct PwState {
     pub nodes: HashMap<u32, PwNode>,
     pub ports: HashMap<u32, PwPort>,
     pub links: HashMap<u32, PwLink>,
+    pub devices: HashMap<u32, PwDevice>,
+    pub clients: HashMap<u32, PwClient>,
+    /// Ports that arrived before their node, keyed by node id
+    pub orphan_ports: HashMap<u32, Vec<u32>>,
+    /// Every PipeWire property of nodes, ports and links, for the inspector
+    pub properties: HashMap<u32, BTreeMap<String, String>>,
+    /// Formats of video ports, keyed by port id
+    pub video_formats: HashMap<u32, PortVideoFormats>,
 }
 
 impl PwState {
@@ -72,6 +260,69 @@ impl PwState {
             .and_then(|port| self.nodes.get(&port.node_id))
     }
 
+    /// Remember a port whose node hasn't been announced yet
+    pub fn add_orphan_port(&mut self, node_id: u32, port_id: u32) {
+        self.orphan_ports.entry(node_id).or_default().push(port_id);
+    }
+
+    /// Attach ports buffered for a node that has just arrived, filling in
+    /// their media type from the node. Returns the ports still present.
+    pub fn adopt_orphan_ports(&mut self, node_id: u32) -> Vec<PwPort> {
+        let Some(port_ids) = self.orphan_ports.remove(&node_id) else {
+            return Vec::new();
+        };
+        let Some(node) = self.nodes.get(&node_id) else {
+            return Vec::new();
+        };
+
+        let mut adopted = Vec::new();
+        for port_id in port_ids {
+            if let Some(port) = self.ports.get_mut(&port_id) {
+                port.media_type = node.infer_media_type(port.media_type);
+                adopted.push(port.clone());
+            }
+        }
+        adopted
+    }
+
+    /// "Node - Port" label for a port, if both are known
+    pub fn port_label(&self, port_id: u32) -> Option<String> {
+        let port = self.ports.get(&port_id)?;
+        let node = self.nodes.get(&port.node_id)?;
+        Some(format!("{} - {}", self.node_label(node), port.display_name()))
+    }
+
+    /// "node.name:port.name", the form `pw-link` accepts for a port
+    pub fn port_path(&self, port_id: u32) -> Option<String> {
+        let port = self.ports.get(&port_id)?;
+        let node = self.nodes.get(&port.node_id)?;
+        Some(format!("{}:{}", node.name, port.name))
+    }
+
+    /// The port with a "node.name:port.name" path, as `pw-link` shows them
+    pub fn find_port_by_path(&self, path: &str) -> Option<u32> {
+        self.ports
+            .keys()
+            .copied()
+            .find(|&id| self.port_path(id).as_deref() == Some(path))
+    }
+
+    /// The device a node belongs to, from its `device.id` property
+    pub fn get_node_device(&self, node_id: u32) -> Option<&PwDevice> {
+        let device_id: u32 = self.properties.get(&node_id)?.get("device.id")?.parse().ok()?;
+        self.devices.get(&device_id)
+    }
+
+    /// Which device of its card a node plays to or records from, the
+    /// `card.profile.device` that routes are selected on
+    pub fn get_node_card_device(&self, node_id: u32) -> Option<u32> {
+        self.properties
+            .get(&node_id)?
+            .get("card.profile.device")?
+            .parse()
+            .ok()
+    }
+
     /// Get all ports for a node
     pub fn get_node_ports(&self, node_id: u32) -> impl Iterator<Item = &PwPort> {
         self.ports.values().filter(move |p| p.node_id == node_id)
@@ -104,4 +355,163 @@ impl PwState {
             link.output_port_id == output_port_id && link.input_port_id == input_port_id
         })
     }
+
+    /// The client that created a node
+    pub fn node_client(&self, node_id: u32) -> Option<&PwClient> {
+        let client_id = self.nodes.get(&node_id)?.client_id?;
+        self.clients.get(&client_id)
+    }
+
+    /// Name shown for a node. Streams often have a generic name such as
+    /// "Playback" or "audio stream", so one without a description is named
+    /// after the application owning it, e.g. "Firefox (PID 4242)".
+    pub fn node_label(&self, node: &PwNode) -> String {
+        if node.is_stream() && node.description.is_none() {
+            if let Some(label) = self.node_client(node.id).and_then(|c| c.label()) {
+                return label;
+            }
+        }
+        node.display_name().to_string()
+    }
+
+    /// Who made a link, from the client its `client.id` property names.
+    /// Links made in this process are recognised by its process id; the
+    /// caller also knows the links it requested.
+    pub fn link_origin(&self, link_id: u32) -> LinkOrigin {
+        let client = self
+            .properties
+            .get(&link_id)
+            .and_then(|props| props.get("client.id"))
+            .and_then(|id| id.parse().ok())
+            .and_then(|id: u32| self.clients.get(&id));
+        match client {
+            Some(client) if client.pid == Some(std::process::id()) => LinkOrigin::App,
+            Some(client) if client.is_session_manager() => LinkOrigin::SessionManager,
+            _ => LinkOrigin::External,
+        }
+    }
+
+    /// Ids of the links whose `client.id` property names `client_id`
+    pub fn links_of_client(&self, client_id: u32) -> Vec<u32> {
+        let client_id = client_id.to_string();
+        self.links
+            .keys()
+            .copied()
+            .filter(|id| {
+                self.properties
+                    .get(id)
+                    .and_then(|props| props.get("client.id"))
+                    .is_some_and(|id| *id == client_id)
+            })
+            .collect()
+    }
+
+    /// Ids of every link between two ports, lowest first. PipeWire lets
+    /// other tools create more than one between the same ports.
+    pub fn links_between(&self, output_port_id: u32, input_port_id: u32) -> Vec<u32> {
+        let mut ids: Vec<u32> = self
+            .links
+            .values()
+            .filter(|link| {
+                link.output_port_id == output_port_id && link.input_port_id == input_port_id
+            })
+            .map(|link| link.id)
+            .collect();
+        ids.sort_unstable();
+        ids
+    }
+
+    /// Links that repeat another link between the same ports. The lowest
+    /// id of each pair of ports is left out, as the one to keep.
+    pub fn duplicate_links(&self) -> Vec<u32> {
+        let mut by_ports: HashMap<(u32, u32), Vec<u32>> = HashMap::new();
+        for link in self.links.values() {
+            by_ports
+                .entry((link.output_port_id, link.input_port_id))
+                .or_default()
+                .push(link.id);
+        }
+        let mut duplicates = Vec::new();
+        for mut ids in by_ports.into_values() {
+            ids.sort_unstable();
+            duplicates.extend(ids.into_iter().skip(1));
+        }
+        duplicates.sort_unstable();
+        duplicates
+    }
+
+    /// Why video from an output port can't be sent to an input port, or
+    /// `None` if it can or the formats of either aren't known
+    pub fn video_incompatibility(&self, output_port_id: u32, input_port_id: u32) -> Option<String> {
+        let output = self.video_formats.get(&output_port_id)?.usable();
+        let input = self.video_formats.get(&input_port_id)?.usable();
+        if output.is_empty()
+            || input.is_empty()
+            || output.iter().any(|o| input.iter().any(|i| o.matches(i)))
+        {
+            return None;
+        }
+        Some(format!(
+            "{} sends {} video, but {} only accepts {}",
+            self.port_label(output_port_id)
+                .unwrap_or_else(|| format!("Port {}", output_port_id)),
+            encodings(output),
+            self.port_label(input_port_id)
+                .unwrap_or_else(|| format!("port {}", input_port_id)),
+            encodings(input)
+        ))
+    }
+}
+
+/// The distinct encodings of some video formats, such as "BGRx, RGBx or MJPG"
+fn encodings(formats: &[VideoFormat]) -> String {
+    let mut names: Vec<String> = Vec::new();
+    for format in formats {
+        let format_names = if format.subtype == "raw" && !format.pixel_formats.is_empty() {
+            format.pixel_formats.clone()
+        } else {
+            vec![format.encoding()]
+        };
+        for name in format_names {
+            if !names.contains(&name) {
+                names.push(name);
+            }
+        }
+    }
+    match names.split_last() {
+        Some((last, [])) => last.clone(),
+        Some((last, rest)) => format!("{} or {}", rest.join(", "), last),
+        None => String::new(),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn video(subtype: &str, pixel_formats: &[&str]) -> VideoFormat {
+        VideoFormat {
+            subtype: subtype.to_string(),
+            pixel_formats: pixel_formats.iter().map(|f| f.to_string()).collect(),
+            size: None,
+            framerate: None,
+        }
+    }
+
+    #[test]
+    fn encodings_lists_each_name_once() {
+        let formats = [
+            video("raw", &["BGRx", "RGBx"]),
+            video("raw", &["RGBx", "I420"]),
+            video("mjpg", &[]),
+        ];
+        assert_eq!(encodings(&formats), "BGRx, RGBx, I420 or MJPG");
+    }
+
+    #[test]
+    fn encodings_of_one_or_none() {
+        assert_eq!(encodings(&[video("h264", &[])]), "H264");
+        assert_eq!(encodings(&[video("raw", &[])]), "RAW");
+        assert_eq!(encodings(&[]), "");
+    }
 }
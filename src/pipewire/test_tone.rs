@@ -0,0 +1,178 @@
+use std::f32::consts::TAU;
+use std::io::Write;
+use std::process::{Child, ChildStdin, Stdio};
+use std::thread::{self, JoinHandle};
+
+use super::messages::TestSignal;
+use crate::sandbox::host_command;
+
+/// Node name used for the test signal stream
+pub const TEST_TONE_NODE_NAME: &str = "pw-audioshare-test-signal";
+
+/// Human readable description shown in the port lists
+pub const TEST_TONE_DESCRIPTION: &str = "PW Audioshare Test Signal";
+
+const SAMPLE_RATE: u32 = 48000;
+const CHANNELS: usize = 2;
+const FRAMES_PER_CHUNK: usize = 1024;
+const SINE_FREQUENCY: f32 = 440.0;
+/// Roughly -18 dBFS so the signal is clearly audible without being harsh
+const AMPLITUDE: f32 = 0.125;
+
+/// A running test signal playback stream.
+///
+/// The stream is played through `pw-cat` with `--target 0`, so PipeWire creates
+/// an unlinked node whose output ports can be patched like any other source.
+/// Samples are generated on a writer thread and piped into `pw-cat`'s stdin;
+/// the blocking pipe write paces generation to real time.
+pub struct TestTone {
+    child: Child,
+    writer: Option<JoinHandle<()>>,
+}
+
+impl TestTone {
+    /// Start playing the given signal on the server with socket `remote`, or
+    /// the default one
+    pub fn start(signal: TestSignal, remote: Option<&str>) -> Result<Self, anyhow::Error> {
+        let properties = format!(
+            "{{ node.name = \"{}\" node.description = \"{}\" media.name = \"{}\" }}",
+            TEST_TONE_NODE_NAME,
+            TEST_TONE_DESCRIPTION,
+            signal.label()
+        );
+
+        let mut command = host_command("pw-cat");
+        if let Some(remote) = remote {
+            command.args(["--remote", remote]);
+        }
+        let mut child = command
+            .args([
+                "--playback",
+                "--raw",
+                "--format",
+                "f32",
+                "--rate",
+                &SAMPLE_RATE.to_string(),
+                "--channels",
+                &CHANNELS.to_string(),
+                "--target",
+                "0",
+                "--properties",
+                &properties,
+                "-",
+            ])
+            .stdin(Stdio::piped())
+            .stdout(Stdio::null())
+            .stderr(Stdio::null())
+            .spawn()?;
+
+        let stdin = match child.stdin.take() {
+            Some(stdin) => stdin,
+            None => {
+                let _ = child.kill();
+                let _ = child.wait();
+                anyhow::bail!("pw-cat did not provide a stdin pipe");
+            }
+        };
+
+        let writer = thread::Builder::new()
+            .name("test-signal".into())
+            .spawn(move || write_samples(stdin, signal))?;
+
+        Ok(Self {
+            child,
+            writer: Some(writer),
+        })
+    }
+
+    /// Stop playback and wait for the helper process to exit
+    pub fn stop(&mut self) {
+        let _ = self.child.kill();
+        let _ = self.child.wait();
+        // The writer thread exits once its pipe is closed
+        if let Some(writer) = self.writer.take() {
+            let _ = writer.join();
+        }
+    }
+}
+
+impl Drop for TestTone {
+    fn drop(&mut self) {
+        self.stop();
+    }
+}
+
+/// Generate samples until the pipe to `pw-cat` is closed
+fn write_samples(mut stdin: ChildStdin, signal: TestSignal) {
+    let mut generator = SignalGenerator::new(signal);
+    let mut bytes = Vec::with_capacity(FRAMES_PER_CHUNK * CHANNELS * 4);
+
+    loop {
+        bytes.clear();
+        for _ in 0..FRAMES_PER_CHUNK {
+            let sample = generator.next_sample().to_le_bytes();
+            for _ in 0..CHANNELS {
+                bytes.extend_from_slice(&sample);
+            }
+        }
+
+        if stdin.write_all(&bytes).is_err() {
+            log::debug!("Test signal pipe closed");
+            return;
+        }
+    }
+}
+
+/// Mono sample generator for the supported test signals
+struct SignalGenerator {
+    signal: TestSignal,
+    phase: f32,
+    rng: u32,
+    /// Paul Kellet's pink noise filter state
+    pink: [f32; 7],
+}
+
+impl SignalGenerator {
+    fn new(signal: TestSignal) -> Self {
+        Self {
+            signal,
+            phase: 0.0,
+            rng: 0x1234_5678,
+            pink: [0.0; 7],
+        }
+    }
+
+    fn next_sample(&mut self) -> f32 {
+        match self.signal {
+            TestSignal::Sine => {
+                let sample = (self.phase * TAU).sin() * AMPLITUDE;
+                self.phase = (self.phase + SINE_FREQUENCY / SAMPLE_RATE as f32).fract();
+                sample
+            }
+            TestSignal::PinkNoise => {
+                let white = self.white_noise();
+                let b = &mut self.pink;
+                b[0] = 0.99886 * b[0] + white * 0.0555179;
+                b[1] = 0.99332 * b[1] + white * 0.0750759;
+                b[2] = 0.96900 * b[2] + white * 0.1538520;
+                b[3] = 0.86650 * b[3] + white * 0.3104856;
+                b[4] = 0.55000 * b[4] + white * 0.5329522;
+                b[5] = -0.7616 * b[5] - white * 0.0168980;
+                let pink = b[0] + b[1] + b[2] + b[3] + b[4] + b[5] + b[6] + white * 0.5362;
+                b[6] = white * 0.115926;
+                // The filter has a gain of roughly 5, scale back to the target level
+                (pink * 0.2 * AMPLITUDE).clamp(-1.0, 1.0)
+            }
+        }
+    }
+
+    /// Uniform white noise in [-1, 1) from a xorshift generator
+    fn white_noise(&mut self) -> f32 {
+        let mut x = self.rng;
+        x ^= x << 13;
+        x ^= x >> 17;
+        x ^= x << 5;
+        self.rng = x;
+        (x as f32 / u32::MAX as f32) * 2.0 - 1.0
+    }
+}
@@ -1,17 +1,37 @@
-use std::cell::RefCell;
+use std::cell::{Cell, RefCell};
+use std::collections::{BTreeMap, HashMap, HashSet};
 use std::rc::Rc;
 use std::thread::{self, JoinHandle};
 
 use async_channel::{Receiver, Sender};
+use pipewire::client::{Client, ClientChangeMask, ClientListener};
 use pipewire::context::Context;
-use pipewire::core::Core;
+use pipewire::core::{Core, PW_ID_CORE};
 use pipewire::link::Link;
 use pipewire::main_loop::MainLoop;
-use pipewire::registry::GlobalObject;
+use pipewire::metadata::{Metadata, MetadataListener};
+use pipewire::node::{Node, NodeChangeMask, NodeListener};
+use pipewire::proxy::{ProxyListener, ProxyT};
+use pipewire::registry::{GlobalObject, Registry};
+use pipewire::spa::param::ParamType;
+use pipewire::spa::pod::{Pod, Property, Value};
+use pipewire::spa::sys as spa_sys;
 use pipewire::spa::utils::dict::DictRef;
 use pipewire::types::ObjectType;
 
-use super::messages::{LinkState, MediaType, PortDirection, PwEvent, UiCommand};
+use super::backend::GraphBackend;
+use super::clock;
+use super::devices::{self, DeviceWatcher};
+use super::health::HealthReport;
+use super::messages::{
+    LinkOptions, LinkState, MediaType, PortDirection, PwEvent, TestSignal, UiCommand,
+};
+use super::modules::{self, ModuleHost};
+use super::test_tone::TestTone;
+use super::video::{self, PortWatcher};
+
+/// Error the core reports when the server closes the connection
+const EPIPE: i32 = 32;
 
 /// Manages the PipeWire connection running in a separate thread
 pub struct PipeWireThread {
@@ -20,14 +40,18 @@ pub struct PipeWireThread {
 }
 
 impl PipeWireThread {
-    /// Spawn a new PipeWire thread that sends events to the given sender
-    pub fn spawn(event_tx: Sender<PwEvent>) -> Result<Self, anyhow::Error> {
+    /// Spawn a PipeWire thread connected to the socket `server`, such as
+    /// `pipewire-1` for a second instance, or the default one if `None`
+    pub fn spawn_for_server(
+        event_tx: Sender<PwEvent>,
+        server: Option<String>,
+    ) -> Result<Self, anyhow::Error> {
         let (command_tx, command_rx) = async_channel::bounded::<UiCommand>(64);
 
         let handle = thread::Builder::new()
             .name("pipewire".into())
             .spawn(move || {
-                if let Err(e) = run_pipewire_loop(event_tx.clone(), command_rx) {
+                if let Err(e) = run_pipewire_loop(event_tx.clone(), command_rx, server) {
                     log::error!("PipeWire thread error: {}", e);
                     let _ = event_tx.send_blocking(PwEvent::Disconnected {
                         reason: e.to_string(),
@@ -40,14 +64,21 @@ impl PipeWireThread {
             command_tx,
         })
     }
+}
+
+impl GraphBackend for PipeWireThread {
+    /// Spawn a new PipeWire thread that sends events to the given sender
+    fn spawn(event_tx: Sender<PwEvent>) -> Result<Self, anyhow::Error> {
+        Self::spawn_for_server(event_tx, None)
+    }
 
     /// Get a sender to send commands to the PipeWire thread
-    pub fn command_sender(&self) -> Sender<UiCommand> {
+    fn command_sender(&self) -> Sender<UiCommand> {
         self.command_tx.clone()
     }
 
     /// Request shutdown and wait for the thread to finish
-    pub fn shutdown(&mut self) {
+    fn shutdown(&mut self) {
         let _ = self.command_tx.send_blocking(UiCommand::Quit);
         if let Some(handle) = self.handle.take() {
             let _ = handle.join();
@@ -61,48 +92,218 @@ impl Drop for PipeWireThread {
     }
 }
 
+/// A link created by this app
+struct CreatedLink {
+    /// Proxy kept alive while the app is running
+    _link: Link,
+    _listener: ProxyListener,
+    /// Global id of the link, known once the server has bound it
+    id: Rc<Cell<Option<u32>>>,
+    /// Whether the link was created with `object.linger` and outlives the app
+    linger: bool,
+    /// Set when the server rejected the link, the proxy is then dropped
+    failed: Rc<Cell<bool>>,
+}
+
+/// Node details the UI shows, as announced in NodeAdded/NodeChanged
+#[derive(Debug, Clone, PartialEq)]
+struct NodeDetails {
+    name: String,
+    media_class: Option<String>,
+    description: Option<String>,
+    application_name: Option<String>,
+    serial: Option<u64>,
+    nick: Option<String>,
+    device_name: Option<String>,
+    client_id: Option<u32>,
+}
+
+impl NodeDetails {
+    fn from_props(props: &DictRef) -> Self {
+        Self {
+            name: props.get("node.name").unwrap_or("Unknown").to_string(),
+            media_class: props.get("media.class").map(String::from),
+            description: props.get("node.description").map(String::from),
+            application_name: props.get("application.name").map(String::from),
+            serial: props.get("object.serial").and_then(|s| s.parse().ok()),
+            nick: props.get("node.nick").map(String::from),
+            device_name: props.get("device.name").map(String::from),
+            client_id: props.get("client.id").and_then(|s| s.parse().ok()),
+        }
+    }
+}
+
+/// Client details the UI shows, as announced in ClientAdded/ClientChanged
+#[derive(Debug, Clone, PartialEq)]
+struct ClientDetails {
+    application_name: Option<String>,
+    pid: Option<u32>,
+    process_binary: Option<String>,
+}
+
+impl ClientDetails {
+    fn from_props(props: &DictRef) -> Self {
+        Self {
+            application_name: props.get("application.name").map(String::from),
+            // The id the server saw is the one other tools show outside a
+            // sandbox, so prefer it to the one the client reports
+            pid: props
+                .get("pipewire.sec.pid")
+                .or_else(|| props.get("application.process.id"))
+                .and_then(|s| s.parse().ok()),
+            process_binary: props.get("application.process.binary").map(String::from),
+        }
+    }
+}
+
+/// A bound client proxy whose info listener reports property changes
+struct ClientWatcher {
+    _client: Client,
+    _listener: ClientListener,
+}
+
+/// A bound node proxy whose info listener reports property changes
+struct NodeWatcher {
+    node: Node,
+    _listener: NodeListener,
+}
+
+/// The bound `settings` metadata, reporting clock changes
+struct SettingsMetadata {
+    id: u32,
+    metadata: Metadata,
+    _listener: MetadataListener,
+}
+
 /// State shared within the PipeWire thread
 struct ThreadState {
     event_tx: Sender<PwEvent>,
     core: Core,
-    /// Store created links to keep them alive without leaking memory.
-    /// The `object.linger = true` property ensures PipeWire keeps the connection
-    /// even after the proxy is dropped, but we need to keep the proxy alive
-    /// while the app is running.
-    created_links: Vec<Link>,
+    /// Registry of the server this thread is connected to, to destroy objects
+    registry: Rc<Registry>,
+    /// Socket of the server when it isn't the default one, for the tools
+    /// and helpers started on its behalf
+    server: Option<String>,
+    /// Store created links to keep them alive while they exist. Entries are
+    /// dropped when their link is removed from the graph. Lingering links stay
+    /// in the graph after the proxy is dropped; temporary ones are removed
+    /// when the app quits.
+    created_links: Vec<CreatedLink>,
+    /// Currently playing test signal, stopped when replaced or on shutdown
+    test_tone: Option<TestTone>,
+    /// Helper processes hosting native modules, keyed by the name given by the UI
+    module_hosts: HashMap<String, ModuleHost>,
+    /// Info listeners on every node, keyed by node id
+    node_watchers: HashMap<u32, NodeWatcher>,
+    /// Proxies of every device, to follow and switch their profiles
+    device_watchers: HashMap<u32, DeviceWatcher>,
+    /// Info listeners on every client, keyed by client id
+    client_watchers: HashMap<u32, ClientWatcher>,
+    /// Nodes whose media class is video, to tell which ports to watch
+    video_nodes: HashSet<u32>,
+    /// Param listeners on video ports, to follow their formats
+    port_watchers: HashMap<u32, PortWatcher>,
+    /// Runtime settings of the server, once announced
+    settings_metadata: Option<SettingsMetadata>,
 }
 
 /// Run the PipeWire main loop
 fn run_pipewire_loop(
     event_tx: Sender<PwEvent>,
     command_rx: Receiver<UiCommand>,
+    server: Option<String>,
 ) -> Result<(), anyhow::Error> {
     // Initialize PipeWire
     pipewire::init();
 
     let mainloop = MainLoop::new(None)?;
     let context = Context::new(&mainloop)?;
-    let core = context.connect(None)?;
-    let registry = core.get_registry()?;
+    let properties = server.clone().map(|server| {
+        pipewire::properties::properties! {
+            "remote.name" => server,
+        }
+    });
+    let core = context.connect(properties)?;
+    let registry = Rc::new(core.get_registry()?);
 
     // Shared state for callbacks
     let state = Rc::new(RefCell::new(ThreadState {
         event_tx: event_tx.clone(),
         core: core.clone(),
+        registry: registry.clone(),
+        server: server.clone(),
         created_links: Vec::new(),
+        test_tone: None,
+        module_hosts: HashMap::new(),
+        node_watchers: HashMap::new(),
+        device_watchers: HashMap::new(),
+        client_watchers: HashMap::new(),
+        video_nodes: HashSet::new(),
+        port_watchers: HashMap::new(),
+        settings_metadata: None,
     }));
 
+    // Facts gathered for the startup health check
+    let health = Rc::new(RefCell::new(HealthReport::default()));
+
     // Set up registry listener for global object events
     let state_clone = state.clone();
+    let health_clone = health.clone();
+    let registry_weak = Rc::downgrade(&registry);
     let _registry_listener = registry
         .add_listener_local()
         .global(move |global| {
+            health_clone.borrow_mut().observe(global);
             handle_global_added(&state_clone.borrow().event_tx, global);
+            if let Some(registry) = registry_weak.upgrade() {
+                match global.type_ {
+                    ObjectType::Node => {
+                        watch_node(&mut state_clone.borrow_mut(), &registry, global)
+                    }
+                    ObjectType::Client => {
+                        watch_client(&mut state_clone.borrow_mut(), &registry, global)
+                    }
+                    ObjectType::Port => {
+                        watch_video_port(&mut state_clone.borrow_mut(), &registry, global)
+                    }
+                    ObjectType::Metadata => {
+                        watch_settings(&mut state_clone.borrow_mut(), &registry, global)
+                    }
+                    ObjectType::Device => {
+                        let mut state = state_clone.borrow_mut();
+                        if let Some(watcher) =
+                            devices::watch_device(&state.event_tx, &state.core, &registry, global)
+                        {
+                            state.device_watchers.insert(global.id, watcher);
+                        }
+                    }
+                    _ => {}
+                }
+            }
         })
         .global_remove({
-            let event_tx = event_tx.clone();
+            let state = state.clone();
             move |id| {
-                handle_global_removed(&event_tx, id);
+                // Release the proxy of a link we created once the link is gone,
+                // so long sessions don't accumulate dead proxies
+                state
+                    .borrow_mut()
+                    .created_links
+                    .retain(|link| link.id.get() != Some(id));
+                state.borrow_mut().node_watchers.remove(&id);
+                state.borrow_mut().device_watchers.remove(&id);
+                state.borrow_mut().client_watchers.remove(&id);
+                state.borrow_mut().video_nodes.remove(&id);
+                state.borrow_mut().port_watchers.remove(&id);
+                let removed_settings = state
+                    .borrow()
+                    .settings_metadata
+                    .as_ref()
+                    .is_some_and(|settings| settings.id == id);
+                if removed_settings {
+                    state.borrow_mut().settings_metadata = None;
+                }
+                handle_global_removed(&state.borrow().event_tx, id);
             }
         })
         .register();
@@ -110,6 +311,63 @@ fn run_pipewire_loop(
     // Notify that we're connected
     let _ = event_tx.send_blocking(PwEvent::Connected);
 
+    // Once the server has answered this sync, every existing global has been
+    // seen and the health report is complete
+    let health_seq = core.sync(0)?;
+    let _core_listener = core
+        .add_listener_local()
+        .info({
+            let health = health.clone();
+            move |info| {
+                health.borrow_mut().server_version = Some(info.version().to_string());
+            }
+        })
+        .done({
+            let event_tx = event_tx.clone();
+            move |id, seq| {
+                if id == PW_ID_CORE && seq == health_seq {
+                    let report = health.borrow().clone();
+                    let _ = event_tx.send_blocking(PwEvent::HealthChecked { report });
+                }
+            }
+        })
+        .error({
+            let event_tx = event_tx.clone();
+            let mainloop_weak = mainloop.downgrade();
+            move |id, _seq, res, message| {
+                // The server going away is reported as EPIPE on the core;
+                // stop so the UI can offer to reconnect
+                if id == PW_ID_CORE && res == -EPIPE {
+                    log::error!("Lost the connection to PipeWire: {}", message);
+                    let _ = event_tx.send_blocking(PwEvent::Disconnected {
+                        reason: "PipeWire stopped".into(),
+                    });
+                    if let Some(mainloop) = mainloop_weak.upgrade() {
+                        mainloop.quit();
+                    }
+                }
+            }
+        })
+        .register();
+
+    // Report modules created by a previous session so they can be managed.
+    // pactl reaches the pipewire-pulse of the default server only.
+    if server.is_none() {
+        match modules::list_modules() {
+            Ok(loaded) => {
+                for module in loaded.into_iter().filter(|m| m.is_owned()) {
+                    let _ = event_tx.send_blocking(PwEvent::ModuleLoaded {
+                        id: module.id,
+                        name: module.name,
+                        args: module.args,
+                    });
+                }
+            }
+            Err(e) => log::warn!("Could not list loaded modules: {}", e),
+        }
+    }
+    let _ = event_tx.send_blocking(PwEvent::ModuleScanComplete);
+
     // Set up a receiver for UI commands using the main loop
     let mainloop_weak = mainloop.downgrade();
     let state_for_commands = state.clone();
@@ -117,33 +375,195 @@ fn run_pipewire_loop(
 
     // Use a timer to poll for commands (pipewire-rs doesn't have direct channel integration)
     let _timer = mainloop.loop_().add_timer(move |_| {
+        // Reap module helpers that died on their own
+        let exited: Vec<String> = state_for_commands
+            .borrow_mut()
+            .module_hosts
+            .iter_mut()
+            .filter_map(|(key, host)| host.has_exited().then(|| key.clone()))
+            .collect();
+        for key in exited {
+            state_for_commands.borrow_mut().module_hosts.remove(&key);
+            log::warn!("Module helper {} exited", key);
+            let _ = event_tx_for_commands.send_blocking(PwEvent::ModuleHostExited { key });
+        }
+
+        // Drop links the server refused to create
+        state_for_commands
+            .borrow_mut()
+            .created_links
+            .retain(|link| !link.failed.get());
+
         // Process all pending commands
         while let Ok(cmd) = command_rx.try_recv() {
             match cmd {
                 UiCommand::CreateLink {
+                    request_id,
                     output_port_id,
                     input_port_id,
+                    options,
                 } => {
                     if let Err(e) = handle_create_link(
                         &mut state_for_commands.borrow_mut(),
+                        request_id,
                         output_port_id,
                         input_port_id,
+                        options,
                     ) {
                         log::error!("Failed to create link: {}", e);
-                        let _ = event_tx_for_commands.send_blocking(PwEvent::Error {
+                        let _ = event_tx_for_commands.send_blocking(PwEvent::LinkFailed {
+                            request_id,
                             message: format!("Failed to create connection: {}", e),
                         });
                     }
                 }
-                UiCommand::DeleteLink { link_id } => {
-                    if let Err(e) = handle_delete_link(&state_for_commands.borrow(), link_id) {
-                        log::error!("Failed to delete link: {}", e);
+                UiCommand::DeleteLink {
+                    request_id,
+                    link_id,
+                } => {
+                    let error = handle_delete_link(&state_for_commands.borrow(), link_id)
+                        .err()
+                        .map(|e| {
+                            log::error!("Failed to delete link: {}", e);
+                            format!("Failed to delete connection: {}", e)
+                        });
+                    let _ = event_tx_for_commands
+                        .send_blocking(PwEvent::CommandResult { request_id, error });
+                }
+                UiCommand::StartTestTone { signal } => {
+                    let mut state = state_for_commands.borrow_mut();
+                    if let Err(e) = handle_start_test_tone(&mut state, signal) {
+                        log::error!("Failed to start test signal: {}", e);
+                        let _ = event_tx_for_commands.send_blocking(PwEvent::Error {
+                            message: format!("Failed to start test signal: {}", e),
+                        });
+                        let _ = event_tx_for_commands
+                            .send_blocking(PwEvent::TestToneChanged { signal: None });
+                    }
+                }
+                UiCommand::StopTestTone => {
+                    if let Some(mut tone) = state_for_commands.borrow_mut().test_tone.take() {
+                        tone.stop();
+                    }
+                    let _ = event_tx_for_commands
+                        .send_blocking(PwEvent::TestToneChanged { signal: None });
+                }
+                UiCommand::LoadModule { .. } | UiCommand::UnloadModule { .. }
+                    if server.is_some() =>
+                {
+                    let _ = event_tx_for_commands.send_blocking(PwEvent::Error {
+                        message: "Modules can only be loaded on the default PipeWire server"
+                            .to_string(),
+                    });
+                }
+                UiCommand::LoadModule { name, args } => match modules::load_module(&name, &args) {
+                    Ok(id) => {
+                        log::info!("Loaded module {} ({}) with id {}", name, args, id);
+                        let _ = event_tx_for_commands.send_blocking(PwEvent::ModuleLoaded {
+                            id,
+                            name,
+                            args,
+                        });
+                    }
+                    Err(e) => {
+                        log::error!("Failed to load module: {}", e);
+                        let _ = event_tx_for_commands.send_blocking(PwEvent::Error {
+                            message: e.to_string(),
+                        });
+                    }
+                },
+                UiCommand::UnloadModule { id } => match modules::unload_module(id) {
+                    Ok(()) => {
+                        let _ = event_tx_for_commands.send_blocking(PwEvent::ModuleUnloaded { id });
+                    }
+                    Err(e) => {
+                        log::error!("Failed to unload module: {}", e);
+                        let _ = event_tx_for_commands.send_blocking(PwEvent::Error {
+                            message: e.to_string(),
+                        });
+                    }
+                },
+                UiCommand::StartModuleHost { key, module, args } => {
+                    match ModuleHost::spawn(&key, &module, &args, server.as_deref()) {
+                        Ok(host) => {
+                            log::info!("Started {} helper {}", module, key);
+                            state_for_commands
+                                .borrow_mut()
+                                .module_hosts
+                                .insert(key, host);
+                        }
+                        Err(e) => {
+                            log::error!("Failed to start {}: {}", module, e);
+                            let _ = event_tx_for_commands.send_blocking(PwEvent::Error {
+                                message: format!("Failed to start {}: {}", module, e),
+                            });
+                            let _ = event_tx_for_commands
+                                .send_blocking(PwEvent::ModuleHostExited { key });
+                        }
+                    }
+                }
+                UiCommand::StopModuleHost { key } => {
+                    // Dropping the host stops the helper process
+                    state_for_commands.borrow_mut().module_hosts.remove(&key);
+                }
+                UiCommand::SetDeviceProfile { device_id, index } => {
+                    let result = match state_for_commands.borrow().device_watchers.get(&device_id)
+                    {
+                        Some(watcher) => devices::set_profile(&watcher.device, index),
+                        None => Err(anyhow::anyhow!("no device {}", device_id)),
+                    };
+                    if let Err(e) = result {
+                        log::error!("Failed to switch profile: {}", e);
+                        let _ = event_tx_for_commands.send_blocking(PwEvent::Error {
+                            message: format!("Failed to switch the device profile: {}", e),
+                        });
+                    }
+                }
+                UiCommand::SetNodeVolume { node_id, volume } => {
+                    let result = match state_for_commands.borrow().node_watchers.get(&node_id) {
+                        Some(watcher) => set_node_volume(&watcher.node, volume),
+                        None => Err(anyhow::anyhow!("no node {}", node_id)),
+                    };
+                    if let Err(e) = result {
+                        log::error!("Failed to set volume: {}", e);
+                        let _ = event_tx_for_commands.send_blocking(PwEvent::Error {
+                            message: format!("Failed to set the volume: {}", e),
+                        });
+                    }
+                }
+                UiCommand::SetDeviceRoute {
+                    device_id,
+                    index,
+                    card_device,
+                } => {
+                    let result = match state_for_commands.borrow().device_watchers.get(&device_id)
+                    {
+                        Some(watcher) => devices::set_route(&watcher.device, index, card_device),
+                        None => Err(anyhow::anyhow!("no device {}", device_id)),
+                    };
+                    if let Err(e) = result {
+                        log::error!("Failed to select route: {}", e);
                         let _ = event_tx_for_commands.send_blocking(PwEvent::Error {
-                            message: format!("Failed to delete connection: {}", e),
+                            message: format!("Failed to select the device output: {}", e),
                         });
                     }
                 }
+                UiCommand::SetClockSetting { key, value } => {
+                    match state_for_commands.borrow().settings_metadata.as_ref() {
+                        Some(settings) => {
+                            log::info!("Setting {} to {}", key, value);
+                            settings.metadata.set_property(0, &key, None, Some(&value));
+                        }
+                        None => {
+                            let _ = event_tx_for_commands.send_blocking(PwEvent::Error {
+                                message: "PipeWire's settings metadata is not available"
+                                    .to_string(),
+                            });
+                        }
+                    }
+                }
                 UiCommand::Quit => {
+                    remove_temporary_links(&state_for_commands.borrow());
                     if let Some(mainloop) = mainloop_weak.upgrade() {
                         mainloop.quit();
                     }
@@ -177,12 +597,17 @@ where
 
     match global.type_ {
         ObjectType::Node => {
+            let details = NodeDetails::from_props(props);
             let event = PwEvent::NodeAdded {
                 id: global.id,
-                name: props.get("node.name").unwrap_or("Unknown").to_string(),
-                media_class: props.get("media.class").map(String::from),
-                description: props.get("node.description").map(String::from),
-                application_name: props.get("application.name").map(String::from),
+                name: details.name,
+                media_class: details.media_class,
+                description: details.description,
+                application_name: details.application_name,
+                serial: details.serial,
+                nick: details.nick,
+                device_name: details.device_name,
+                client_id: details.client_id,
             };
             let _ = tx.send_blocking(event);
         }
@@ -209,6 +634,20 @@ where
             };
             let _ = tx.send_blocking(event);
         }
+        ObjectType::Device => {
+            let _ = tx.send_blocking(devices::device_added_event(global.id, props));
+            return;
+        }
+        ObjectType::Client => {
+            let details = ClientDetails::from_props(props);
+            let event = PwEvent::ClientAdded {
+                id: global.id,
+                application_name: details.application_name,
+                pid: details.pid,
+                process_binary: details.process_binary,
+            };
+            let _ = tx.send_blocking(event);
+        }
         ObjectType::Link => {
             let event = PwEvent::LinkAdded {
                 id: global.id,
@@ -232,8 +671,227 @@ where
             };
             let _ = tx.send_blocking(event);
         }
-        _ => {}
+        _ => return,
     }
+
+    let _ = tx.send_blocking(PwEvent::PropertiesChanged {
+        id: global.id,
+        properties: property_map(props),
+    });
+}
+
+/// Every property of an object, sorted by key
+fn property_map(props: &DictRef) -> BTreeMap<String, String> {
+    props
+        .iter()
+        .map(|(key, value)| (key.to_string(), value.to_string()))
+        .collect()
+}
+
+/// Bind a client to read the properties the registry leaves out, such as
+/// the process binary, and to follow changes to them
+fn watch_client<T>(state: &mut ThreadState, registry: &Registry, global: &GlobalObject<T>)
+where
+    T: AsRef<DictRef>,
+{
+    let client: Client = match registry.bind(global) {
+        Ok(client) => client,
+        Err(e) => {
+            log::warn!("Could not watch client {}: {}", global.id, e);
+            return;
+        }
+    };
+
+    let announced = RefCell::new(
+        global
+            .props
+            .as_ref()
+            .map(|props| ClientDetails::from_props(props.as_ref())),
+    );
+    let id = global.id;
+    let event_tx = state.event_tx.clone();
+    let listener = client
+        .add_listener_local()
+        .info(move |info| {
+            if !info.change_mask().contains(ClientChangeMask::PROPS) {
+                return;
+            }
+            let Some(props) = info.props() else {
+                return;
+            };
+            let details = ClientDetails::from_props(props);
+            if announced.borrow().as_ref() == Some(&details) {
+                return;
+            }
+            announced.replace(Some(details.clone()));
+            let _ = event_tx.send_blocking(PwEvent::ClientChanged {
+                id,
+                application_name: details.application_name,
+                pid: details.pid,
+                process_binary: details.process_binary,
+            });
+        })
+        .register();
+
+    state.client_watchers.insert(
+        id,
+        ClientWatcher {
+            _client: client,
+            _listener: listener,
+        },
+    );
+}
+
+/// Bind a video port to follow the formats it offers and negotiates,
+/// which the registry doesn't announce
+fn watch_video_port<T>(state: &mut ThreadState, registry: &Registry, global: &GlobalObject<T>)
+where
+    T: AsRef<DictRef>,
+{
+    let Some(props) = global.props.as_ref().map(|props| props.as_ref()) else {
+        return;
+    };
+    let node_is_video = props
+        .get("node.id")
+        .and_then(|s| s.parse().ok())
+        .is_some_and(|node_id| state.video_nodes.contains(&node_id));
+    if !video::is_video_port(props, node_is_video) {
+        return;
+    }
+    if let Some(watcher) = video::watch_port(&state.event_tx, registry, global) {
+        state.port_watchers.insert(global.id, watcher);
+    }
+}
+
+/// Bind a node and report changes to the properties shown in the UI
+fn watch_node<T>(state: &mut ThreadState, registry: &Registry, global: &GlobalObject<T>)
+where
+    T: AsRef<DictRef>,
+{
+    let media_class = global
+        .props
+        .as_ref()
+        .and_then(|props| props.as_ref().get("media.class"));
+    if media_class.is_some_and(|class| class.contains("Video")) {
+        state.video_nodes.insert(global.id);
+    }
+
+    let node: Node = match registry.bind(global) {
+        Ok(node) => node,
+        Err(e) => {
+            log::warn!("Could not watch node {}: {}", global.id, e);
+            return;
+        }
+    };
+
+    // What the UI was last told, so the initial info and unrelated prop
+    // updates (volume, latency, ...) don't produce events
+    let announced = RefCell::new(
+        global
+            .props
+            .as_ref()
+            .map(|props| NodeDetails::from_props(props.as_ref())),
+    );
+    let id = global.id;
+    let event_tx = state.event_tx.clone();
+    let listener = node
+        .add_listener_local()
+        .info(move |info| {
+            if !info.change_mask().contains(NodeChangeMask::PROPS) {
+                return;
+            }
+            let Some(props) = info.props() else {
+                return;
+            };
+            let _ = event_tx.send_blocking(PwEvent::PropertiesChanged {
+                id,
+                properties: property_map(props),
+            });
+            let details = NodeDetails::from_props(props);
+            if announced.borrow().as_ref() == Some(&details) {
+                return;
+            }
+            announced.replace(Some(details.clone()));
+            let _ = event_tx.send_blocking(PwEvent::NodeChanged {
+                id,
+                name: details.name,
+                media_class: details.media_class,
+                description: details.description,
+                application_name: details.application_name,
+                serial: details.serial,
+                nick: details.nick,
+                device_name: details.device_name,
+                client_id: details.client_id,
+            });
+        })
+        .register();
+
+    state.node_watchers.insert(
+        id,
+        NodeWatcher {
+            node,
+            _listener: listener,
+        },
+    );
+}
+
+/// Set a node's master volume through its `Props` param, as `pw-cli
+/// set-param <id> Props '{ volume = 0.5 }'` does
+fn set_node_volume(node: &Node, volume: f32) -> Result<(), anyhow::Error> {
+    let bytes = devices::serialize_param(
+        spa_sys::SPA_TYPE_OBJECT_Props,
+        ParamType::Props,
+        vec![Property::new(spa_sys::SPA_PROP_volume, Value::Float(volume))],
+    )?;
+    let pod = Pod::from_bytes(&bytes).ok_or_else(|| anyhow::anyhow!("Invalid volume param"))?;
+    node.set_param(ParamType::Props, 0, pod);
+    Ok(())
+}
+
+/// Bind the server's `settings` metadata and report its clock values
+fn watch_settings<T>(state: &mut ThreadState, registry: &Registry, global: &GlobalObject<T>)
+where
+    T: AsRef<DictRef>,
+{
+    let is_settings = global
+        .props
+        .as_ref()
+        .and_then(|props| props.as_ref().get("metadata.name"))
+        == Some(clock::SETTINGS_METADATA);
+    if !is_settings {
+        return;
+    }
+
+    let metadata: Metadata = match registry.bind(global) {
+        Ok(metadata) => metadata,
+        Err(e) => {
+            log::warn!("Could not watch the settings metadata: {}", e);
+            return;
+        }
+    };
+
+    let event_tx = state.event_tx.clone();
+    let listener = metadata
+        .add_listener_local()
+        .property(move |subject, key, _type, value| {
+            if subject != 0 {
+                return 0;
+            }
+            if let Some(key) = key.filter(|key| clock::is_clock_key(key)) {
+                let _ = event_tx.send_blocking(PwEvent::ClockSettingChanged {
+                    key: key.to_string(),
+                    value: value.map(String::from),
+                });
+            }
+            0
+        })
+        .register();
+
+    state.settings_metadata = Some(SettingsMetadata {
+        id: global.id,
+        metadata,
+        _listener: listener,
+    });
 }
 
 /// Handle a global object being removed from the registry
@@ -248,40 +906,108 @@ fn handle_global_removed(tx: &Sender<PwEvent>, id: u32) {
 /// Create a link between two ports
 fn handle_create_link(
     state: &mut ThreadState,
+    request_id: u64,
     output_port_id: u32,
     input_port_id: u32,
+    options: LinkOptions,
 ) -> Result<(), anyhow::Error> {
     // Create properties for the link
     let props = pipewire::properties::properties! {
         "link.output.port" => output_port_id.to_string(),
         "link.input.port" => input_port_id.to_string(),
-        "object.linger" => "true",
+        "link.passive" => options.passive.to_string(),
+        "object.linger" => options.linger.to_string(),
     };
 
     // Create the link using the core
     let link: Link = state.core.create_object("link-factory", &props)?;
 
+    // Remember the global id once bound so the link can be removed later,
+    // and report the outcome of the request to the UI
+    let id = Rc::new(Cell::new(None));
+    let failed = Rc::new(Cell::new(false));
+    let listener = link
+        .upcast_ref()
+        .add_listener_local()
+        .bound({
+            let id = id.clone();
+            let event_tx = state.event_tx.clone();
+            move |global_id| {
+                id.set(Some(global_id));
+                let _ = event_tx.send_blocking(PwEvent::LinkCreated {
+                    request_id,
+                    link_id: global_id,
+                });
+            }
+        })
+        .error({
+            let failed = failed.clone();
+            let event_tx = state.event_tx.clone();
+            move |_seq, _res, message| {
+                log::error!(
+                    "Link {} -> {} failed: {}",
+                    output_port_id,
+                    input_port_id,
+                    message
+                );
+                failed.set(true);
+                let _ = event_tx.send_blocking(PwEvent::LinkFailed {
+                    request_id,
+                    message: format!("Failed to create connection: {}", message),
+                });
+            }
+        })
+        .register();
+
     // Store the link to keep it alive. When ThreadState is dropped during
     // shutdown, links will be properly cleaned up.
-    state.created_links.push(link);
+    state.created_links.push(CreatedLink {
+        _link: link,
+        _listener: listener,
+        id,
+        linger: options.linger,
+        failed,
+    });
 
     Ok(())
 }
 
-/// Delete an existing link by ID
-/// Note: This is a simplified implementation. In a production app, you'd want to
-/// keep track of link proxies or use pw-link command as a fallback.
-fn handle_delete_link(_state: &ThreadState, link_id: u32) -> Result<(), anyhow::Error> {
-    // Use pw-link command to delete the link as a workaround
-    // The pipewire-rs API requires a GlobalObject to bind, which we don't have here
-    let output = std::process::Command::new("pw-link")
-        .args(["-d", &link_id.to_string()])
-        .output()?;
-
-    if !output.status.success() {
-        let stderr = String::from_utf8_lossy(&output.stderr);
-        anyhow::bail!("Failed to delete link {}: {}", link_id, stderr);
+/// Delete the temporary links created by this app so quitting leaves the
+/// graph as it was found
+fn remove_temporary_links(state: &ThreadState) {
+    for link in state.created_links.iter().filter(|l| !l.linger) {
+        if let Some(id) = link.id.get() {
+            if let Err(e) = handle_delete_link(state, id) {
+                log::warn!("Failed to remove temporary link {}: {}", id, e);
+            }
+        }
+    }
+}
+
+/// Start a test signal, replacing any signal that is already playing
+fn handle_start_test_tone(
+    state: &mut ThreadState,
+    signal: TestSignal,
+) -> Result<(), anyhow::Error> {
+    if let Some(mut tone) = state.test_tone.take() {
+        tone.stop();
     }
 
+    state.test_tone = Some(TestTone::start(signal, state.server.as_deref())?);
+    let _ = state.event_tx.send_blocking(PwEvent::TestToneChanged {
+        signal: Some(signal),
+    });
+
+    Ok(())
+}
+
+/// Delete an existing link by ID, on this thread's own server rather than
+/// the one `PIPEWIRE_REMOTE` names
+fn handle_delete_link(state: &ThreadState, link_id: u32) -> Result<(), anyhow::Error> {
+    state
+        .registry
+        .destroy_global(link_id)
+        .into_result()
+        .map_err(|e| anyhow::anyhow!("Failed to delete link {}: {}", link_id, e))?;
     Ok(())
 }
@@ -0,0 +1,323 @@
+use std::cell::RefCell;
+use std::rc::Rc;
+
+use async_channel::Sender;
+use pipewire::port::{Port, PortListener};
+use pipewire::registry::{GlobalObject, Registry};
+use pipewire::spa::param::ParamType;
+use pipewire::spa::pod::{CanonicalFixedSizedPod, ChoiceValue, Pod, Property, Value};
+use pipewire::spa::sys as spa_sys;
+use pipewire::spa::utils::dict::DictRef;
+use pipewire::spa::utils::{ChoiceEnum, Id};
+
+use super::devices::object_properties;
+use super::messages::{PwEvent, VideoFormat};
+
+/// Formats of a video port as the server last enumerated them
+#[derive(Default)]
+struct PortFormats {
+    offered: Vec<VideoFormat>,
+    current: Option<VideoFormat>,
+}
+
+/// A bound video port reporting the formats it offers and negotiated
+pub struct PortWatcher {
+    _port: Port,
+    _listener: PortListener,
+}
+
+/// Whether a port carries video, from its own `format.dsp` or, for the
+/// raw ports of cameras and screencasts that have none, its node
+pub fn is_video_port(props: &DictRef, node_is_video: bool) -> bool {
+    match props.get("format.dsp") {
+        Some(format) => format.contains("video"),
+        None => node_is_video,
+    }
+}
+
+/// The values a choice allows, the preferred one first
+fn choice_values<T: CanonicalFixedSizedPod + Copy + PartialEq>(choice: &ChoiceEnum<T>) -> Vec<T> {
+    match choice {
+        ChoiceEnum::None(value) => vec![*value],
+        ChoiceEnum::Range { default, .. } | ChoiceEnum::Step { default, .. } => vec![*default],
+        ChoiceEnum::Enum {
+            default,
+            alternatives,
+        } => {
+            let mut values = vec![*default];
+            for value in alternatives {
+                if !values.contains(value) {
+                    values.push(*value);
+                }
+            }
+            values
+        }
+        ChoiceEnum::Flags { default, .. } => vec![*default],
+    }
+}
+
+fn find_ids(properties: &[Property], key: u32) -> Vec<u32> {
+    match properties.iter().find(|p| p.key == key).map(|p| &p.value) {
+        Some(Value::Id(Id(id))) => vec![*id],
+        Some(Value::Choice(ChoiceValue::Id(choice))) => choice_values(&choice.1)
+            .into_iter()
+            .map(|id| id.0)
+            .collect(),
+        _ => Vec::new(),
+    }
+}
+
+fn find_size(properties: &[Property]) -> Option<(u32, u32)> {
+    let size = match &properties
+        .iter()
+        .find(|p| p.key == spa_sys::SPA_FORMAT_VIDEO_size)?
+        .value
+    {
+        Value::Rectangle(size) => *size,
+        Value::Choice(ChoiceValue::Rectangle(choice)) => *choice_values(&choice.1).first()?,
+        _ => return None,
+    };
+    Some((size.width, size.height))
+}
+
+fn find_framerate(properties: &[Property]) -> Option<(u32, u32)> {
+    let rate = match &properties
+        .iter()
+        .find(|p| p.key == spa_sys::SPA_FORMAT_VIDEO_framerate)?
+        .value
+    {
+        Value::Fraction(rate) => *rate,
+        Value::Choice(ChoiceValue::Fraction(choice)) => *choice_values(&choice.1).first()?,
+        _ => return None,
+    };
+    Some((rate.num, rate.denom))
+}
+
+/// Name of a video media subtype, such as "mjpg", or `None` for one
+/// this app doesn't know
+fn subtype_name(id: u32) -> Option<&'static str> {
+    Some(match id {
+        spa_sys::SPA_MEDIA_SUBTYPE_raw => "raw",
+        spa_sys::SPA_MEDIA_SUBTYPE_h264 => "h264",
+        spa_sys::SPA_MEDIA_SUBTYPE_mjpg => "mjpg",
+        spa_sys::SPA_MEDIA_SUBTYPE_dv => "dv",
+        spa_sys::SPA_MEDIA_SUBTYPE_mpegts => "mpegts",
+        spa_sys::SPA_MEDIA_SUBTYPE_h263 => "h263",
+        spa_sys::SPA_MEDIA_SUBTYPE_mpeg1 => "mpeg1",
+        spa_sys::SPA_MEDIA_SUBTYPE_mpeg2 => "mpeg2",
+        spa_sys::SPA_MEDIA_SUBTYPE_mpeg4 => "mpeg4",
+        spa_sys::SPA_MEDIA_SUBTYPE_xvid => "xvid",
+        spa_sys::SPA_MEDIA_SUBTYPE_vc1 => "vc1",
+        spa_sys::SPA_MEDIA_SUBTYPE_vp8 => "vp8",
+        spa_sys::SPA_MEDIA_SUBTYPE_vp9 => "vp9",
+        spa_sys::SPA_MEDIA_SUBTYPE_bayer => "bayer",
+        _ => return None,
+    })
+}
+
+/// SPA's name of a raw video pixel format, such as "BGRx", or `None` for
+/// one this app doesn't know
+fn pixel_format_name(id: u32) -> Option<&'static str> {
+    Some(match id {
+        spa_sys::SPA_VIDEO_FORMAT_I420 => "I420",
+        spa_sys::SPA_VIDEO_FORMAT_YV12 => "YV12",
+        spa_sys::SPA_VIDEO_FORMAT_YUY2 => "YUY2",
+        spa_sys::SPA_VIDEO_FORMAT_UYVY => "UYVY",
+        spa_sys::SPA_VIDEO_FORMAT_AYUV => "AYUV",
+        spa_sys::SPA_VIDEO_FORMAT_RGBx => "RGBx",
+        spa_sys::SPA_VIDEO_FORMAT_BGRx => "BGRx",
+        spa_sys::SPA_VIDEO_FORMAT_xRGB => "xRGB",
+        spa_sys::SPA_VIDEO_FORMAT_xBGR => "xBGR",
+        spa_sys::SPA_VIDEO_FORMAT_RGBA => "RGBA",
+        spa_sys::SPA_VIDEO_FORMAT_BGRA => "BGRA",
+        spa_sys::SPA_VIDEO_FORMAT_ARGB => "ARGB",
+        spa_sys::SPA_VIDEO_FORMAT_ABGR => "ABGR",
+        spa_sys::SPA_VIDEO_FORMAT_RGB => "RGB",
+        spa_sys::SPA_VIDEO_FORMAT_BGR => "BGR",
+        spa_sys::SPA_VIDEO_FORMAT_Y41B => "Y41B",
+        spa_sys::SPA_VIDEO_FORMAT_Y42B => "Y42B",
+        spa_sys::SPA_VIDEO_FORMAT_YVYU => "YVYU",
+        spa_sys::SPA_VIDEO_FORMAT_Y444 => "Y444",
+        spa_sys::SPA_VIDEO_FORMAT_v210 => "v210",
+        spa_sys::SPA_VIDEO_FORMAT_v216 => "v216",
+        spa_sys::SPA_VIDEO_FORMAT_NV12 => "NV12",
+        spa_sys::SPA_VIDEO_FORMAT_NV21 => "NV21",
+        spa_sys::SPA_VIDEO_FORMAT_GRAY8 => "GRAY8",
+        spa_sys::SPA_VIDEO_FORMAT_GRAY16_BE => "GRAY16_BE",
+        spa_sys::SPA_VIDEO_FORMAT_GRAY16_LE => "GRAY16_LE",
+        spa_sys::SPA_VIDEO_FORMAT_v308 => "v308",
+        spa_sys::SPA_VIDEO_FORMAT_RGB16 => "RGB16",
+        spa_sys::SPA_VIDEO_FORMAT_BGR16 => "BGR16",
+        spa_sys::SPA_VIDEO_FORMAT_RGB15 => "RGB15",
+        spa_sys::SPA_VIDEO_FORMAT_BGR15 => "BGR15",
+        spa_sys::SPA_VIDEO_FORMAT_UYVP => "UYVP",
+        spa_sys::SPA_VIDEO_FORMAT_A420 => "A420",
+        spa_sys::SPA_VIDEO_FORMAT_RGB8P => "RGB8P",
+        spa_sys::SPA_VIDEO_FORMAT_YUV9 => "YUV9",
+        spa_sys::SPA_VIDEO_FORMAT_YVU9 => "YVU9",
+        spa_sys::SPA_VIDEO_FORMAT_IYU1 => "IYU1",
+        spa_sys::SPA_VIDEO_FORMAT_ARGB64 => "ARGB64",
+        spa_sys::SPA_VIDEO_FORMAT_AYUV64 => "AYUV64",
+        spa_sys::SPA_VIDEO_FORMAT_r210 => "r210",
+        spa_sys::SPA_VIDEO_FORMAT_I420_10BE => "I420_10BE",
+        spa_sys::SPA_VIDEO_FORMAT_I420_10LE => "I420_10LE",
+        spa_sys::SPA_VIDEO_FORMAT_I422_10BE => "I422_10BE",
+        spa_sys::SPA_VIDEO_FORMAT_I422_10LE => "I422_10LE",
+        spa_sys::SPA_VIDEO_FORMAT_Y444_10BE => "Y444_10BE",
+        spa_sys::SPA_VIDEO_FORMAT_Y444_10LE => "Y444_10LE",
+        spa_sys::SPA_VIDEO_FORMAT_GBR => "GBR",
+        spa_sys::SPA_VIDEO_FORMAT_GBR_10BE => "GBR_10BE",
+        spa_sys::SPA_VIDEO_FORMAT_GBR_10LE => "GBR_10LE",
+        spa_sys::SPA_VIDEO_FORMAT_NV16 => "NV16",
+        spa_sys::SPA_VIDEO_FORMAT_NV24 => "NV24",
+        spa_sys::SPA_VIDEO_FORMAT_NV12_64Z32 => "NV12_64Z32",
+        spa_sys::SPA_VIDEO_FORMAT_A420_10BE => "A420_10BE",
+        spa_sys::SPA_VIDEO_FORMAT_A420_10LE => "A420_10LE",
+        spa_sys::SPA_VIDEO_FORMAT_A422_10BE => "A422_10BE",
+        spa_sys::SPA_VIDEO_FORMAT_A422_10LE => "A422_10LE",
+        spa_sys::SPA_VIDEO_FORMAT_A444_10BE => "A444_10BE",
+        spa_sys::SPA_VIDEO_FORMAT_A444_10LE => "A444_10LE",
+        spa_sys::SPA_VIDEO_FORMAT_NV61 => "NV61",
+        spa_sys::SPA_VIDEO_FORMAT_P010_10BE => "P010_10BE",
+        spa_sys::SPA_VIDEO_FORMAT_P010_10LE => "P010_10LE",
+        spa_sys::SPA_VIDEO_FORMAT_IYU2 => "IYU2",
+        spa_sys::SPA_VIDEO_FORMAT_VYUY => "VYUY",
+        spa_sys::SPA_VIDEO_FORMAT_GBRA => "GBRA",
+        spa_sys::SPA_VIDEO_FORMAT_GBRA_10BE => "GBRA_10BE",
+        spa_sys::SPA_VIDEO_FORMAT_GBRA_10LE => "GBRA_10LE",
+        spa_sys::SPA_VIDEO_FORMAT_GBR_12BE => "GBR_12BE",
+        spa_sys::SPA_VIDEO_FORMAT_GBR_12LE => "GBR_12LE",
+        spa_sys::SPA_VIDEO_FORMAT_GBRA_12BE => "GBRA_12BE",
+        spa_sys::SPA_VIDEO_FORMAT_GBRA_12LE => "GBRA_12LE",
+        spa_sys::SPA_VIDEO_FORMAT_I420_12BE => "I420_12BE",
+        spa_sys::SPA_VIDEO_FORMAT_I420_12LE => "I420_12LE",
+        spa_sys::SPA_VIDEO_FORMAT_I422_12BE => "I422_12BE",
+        spa_sys::SPA_VIDEO_FORMAT_I422_12LE => "I422_12LE",
+        spa_sys::SPA_VIDEO_FORMAT_Y444_12BE => "Y444_12BE",
+        spa_sys::SPA_VIDEO_FORMAT_Y444_12LE => "Y444_12LE",
+        spa_sys::SPA_VIDEO_FORMAT_RGBA_F16 => "RGBA_F16",
+        spa_sys::SPA_VIDEO_FORMAT_RGBA_F32 => "RGBA_F32",
+        spa_sys::SPA_VIDEO_FORMAT_xRGB_210LE => "xRGB_210LE",
+        spa_sys::SPA_VIDEO_FORMAT_xBGR_210LE => "xBGR_210LE",
+        spa_sys::SPA_VIDEO_FORMAT_RGBx_102LE => "RGBx_102LE",
+        spa_sys::SPA_VIDEO_FORMAT_BGRx_102LE => "BGRx_102LE",
+        spa_sys::SPA_VIDEO_FORMAT_ARGB_210LE => "ARGB_210LE",
+        spa_sys::SPA_VIDEO_FORMAT_ABGR_210LE => "ABGR_210LE",
+        spa_sys::SPA_VIDEO_FORMAT_RGBA_102LE => "RGBA_102LE",
+        spa_sys::SPA_VIDEO_FORMAT_BGRA_102LE => "BGRA_102LE",
+        _ => return None,
+    })
+}
+
+/// Read a video format from an `EnumFormat` or `Format` param, or `None`
+/// for other media
+fn parse_video_format(pod: &Pod) -> Option<VideoFormat> {
+    let properties = object_properties(pod)?;
+    if !find_ids(&properties, spa_sys::SPA_FORMAT_mediaType)
+        .contains(&spa_sys::SPA_MEDIA_TYPE_video)
+    {
+        return None;
+    }
+    let subtype = *find_ids(&properties, spa_sys::SPA_FORMAT_mediaSubtype).first()?;
+    Some(VideoFormat {
+        subtype: subtype_name(subtype)
+            .map(str::to_string)
+            .unwrap_or_else(|| format!("subtype {}", subtype)),
+        pixel_formats: find_ids(&properties, spa_sys::SPA_FORMAT_VIDEO_format)
+            .into_iter()
+            .map(|format| {
+                pixel_format_name(format)
+                    .map(str::to_string)
+                    .unwrap_or_else(|| format!("format {}", format))
+            })
+            .collect(),
+        size: find_size(&properties),
+        framerate: find_framerate(&properties),
+    })
+}
+
+/// Bind a video port and report the formats it offers and negotiates
+pub fn watch_port<T>(
+    event_tx: &Sender<PwEvent>,
+    registry: &Registry,
+    global: &GlobalObject<T>,
+) -> Option<PortWatcher>
+where
+    T: AsRef<DictRef>,
+{
+    let port: Port = match registry.bind(global) {
+        Ok(port) => port,
+        Err(e) => {
+            log::warn!("Could not watch video port {}: {}", global.id, e);
+            return None;
+        }
+    };
+
+    let id = global.id;
+    let formats = Rc::new(RefCell::new(PortFormats::default()));
+    let event_tx = event_tx.clone();
+    let listener = port
+        .add_listener_local()
+        .param(move |_seq, param_type, index, _next, pod| {
+            let mut formats = formats.borrow_mut();
+            match param_type {
+                ParamType::EnumFormat => {
+                    // A new enumeration starts over at index 0
+                    if index == 0 {
+                        formats.offered.clear();
+                    }
+                    if let Some(format) = pod.and_then(parse_video_format) {
+                        formats.offered.push(format);
+                    }
+                }
+                ParamType::Format => {
+                    // Without a pod the port has no format
+                    formats.current = pod.and_then(parse_video_format);
+                }
+                _ => return,
+            }
+            let _ = event_tx.send_blocking(PwEvent::VideoFormatsChanged {
+                id,
+                offered: formats.offered.clone(),
+                current: formats.current.clone(),
+            });
+        })
+        .register();
+    port.subscribe_params(&[ParamType::EnumFormat, ParamType::Format]);
+
+    Some(PortWatcher {
+        _port: port,
+        _listener: listener,
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn names_known_subtypes() {
+        assert_eq!(subtype_name(spa_sys::SPA_MEDIA_SUBTYPE_raw), Some("raw"));
+        assert_eq!(subtype_name(spa_sys::SPA_MEDIA_SUBTYPE_mjpg), Some("mjpg"));
+        assert_eq!(subtype_name(spa_sys::SPA_MEDIA_SUBTYPE_h264), Some("h264"));
+        // Audio subtypes don't belong on a video port
+        assert_eq!(subtype_name(spa_sys::SPA_MEDIA_SUBTYPE_opus), None);
+    }
+
+    #[test]
+    fn names_known_pixel_formats() {
+        assert_eq!(
+            pixel_format_name(spa_sys::SPA_VIDEO_FORMAT_BGRx),
+            Some("BGRx")
+        );
+        assert_eq!(
+            pixel_format_name(spa_sys::SPA_VIDEO_FORMAT_NV12),
+            Some("NV12")
+        );
+        assert_eq!(
+            pixel_format_name(spa_sys::SPA_VIDEO_FORMAT_GRAY16_LE),
+            Some("GRAY16_LE")
+        );
+        assert_eq!(pixel_format_name(spa_sys::SPA_VIDEO_FORMAT_UNKNOWN), None);
+    }
+}
@@ -2,8 +2,83 @@ use serde::{Deserialize, Serialize};
 use std::collections::HashMap;
 use std::fs;
 use std::path::PathBuf;
+use std::time::{SystemTime, UNIX_EPOCH};
 
-use crate::config::APP_ID;
+use crate::config;
+use crate::pipewire::state::{PwLink, PwNode, PwPort, PwState};
+use crate::pipewire::PortDirection;
+
+/// Days a deleted preset stays in the trash unless set otherwise
+pub const DEFAULT_TRASH_DAYS: u32 = 30;
+
+/// Seconds since the Unix epoch
+fn now() -> u64 {
+    SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .map(|d| d.as_secs())
+        .unwrap_or_default()
+}
+
+/// Node identifiers besides `node.name`, used to tell apart nodes that
+/// share a name or whose name changes between sessions
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+pub struct NodeIdentity {
+    /// `object.serial`; only matches while the same server keeps running,
+    /// and only together with the name or device name
+    #[serde(default)]
+    pub serial: Option<u64>,
+    #[serde(default)]
+    pub device_name: Option<String>,
+    #[serde(default)]
+    pub nick: Option<String>,
+}
+
+impl NodeIdentity {
+    /// The identifiers of a node currently in the graph
+    pub fn of(node: &PwNode) -> Self {
+        Self {
+            serial: node.serial,
+            device_name: node.device_name.clone(),
+            nick: node.nick.clone(),
+        }
+    }
+}
+
+/// How well a node matches a saved endpoint, higher is better. `None` means
+/// it doesn't match at all.
+///
+/// `node.name` is the usual stable name and the serial identifies the exact
+/// node, but serials start over whenever PipeWire restarts, so a saved one
+/// can name an unrelated node after a reboot. It only counts when the name
+/// or device name matches too. The device name and nick only break ties,
+/// since every node of a card shares the device name and often the nick, so
+/// alone they could pick HDMI for a connection saved on the analog output.
+fn match_score(name: &str, identity: &NodeIdentity, node: &PwNode) -> Option<u32> {
+    let same =
+        |saved: &Option<String>, current: &Option<String>| saved.is_some() && saved == current;
+
+    let same_name = node.name == name;
+    let same_device = same(&identity.device_name, &node.device_name);
+    let same_serial = identity.serial.is_some() && identity.serial == node.serial;
+    if !same_name && !(same_serial && same_device) {
+        return None;
+    }
+
+    let mut score = 0;
+    if same_serial {
+        score += 100;
+    }
+    if same_name {
+        score += 40;
+    }
+    if same_device {
+        score += 20;
+    }
+    if same(&identity.nick, &node.nick) {
+        score += 10;
+    }
+    Some(score)
+}
 
 /// A single connection in a preset (stored by port names, not IDs)
 #[derive(Debug, Clone, Serialize, Deserialize)]
@@ -12,6 +87,162 @@ pub struct PresetConnection {
     pub output_port: String,
     pub input_node: String,
     pub input_port: String,
+    #[serde(default)]
+    pub output_identity: NodeIdentity,
+    #[serde(default)]
+    pub input_identity: NodeIdentity,
+}
+
+impl PresetConnection {
+    /// Describe a link in the graph by its node and port names
+    pub fn from_link(state: &PwState, link: &PwLink) -> Option<Self> {
+        Self::from_ports(state, link.output_port_id, link.input_port_id)
+    }
+
+    /// Describe a connection between two ports by their node and port names
+    pub fn from_ports(state: &PwState, output_port_id: u32, input_port_id: u32) -> Option<Self> {
+        let output_port = state.ports.get(&output_port_id)?;
+        let input_port = state.ports.get(&input_port_id)?;
+        let output_node = state.nodes.get(&output_port.node_id)?;
+        let input_node = state.nodes.get(&input_port.node_id)?;
+
+        Some(Self {
+            output_node: output_node.name.clone(),
+            output_port: output_port.name.clone(),
+            input_node: input_node.name.clone(),
+            input_port: input_port.name.clone(),
+            output_identity: NodeIdentity::of(output_node),
+            input_identity: NodeIdentity::of(input_node),
+        })
+    }
+
+    /// Find the output and input ports this connection refers to, preferring
+    /// the best-matching node when several have a port of the right name
+    pub fn find_ports<'a>(&self, state: &'a PwState) -> (Option<&'a PwPort>, Option<&'a PwPort>) {
+        let find = |direction, node_name: &str, identity: &NodeIdentity, port_name: &str| {
+            state
+                .ports
+                .values()
+                .filter(|p| p.direction == direction && p.name == port_name)
+                .filter_map(|p| {
+                    let node = state.nodes.get(&p.node_id)?;
+                    Some((match_score(node_name, identity, node)?, p))
+                })
+                // Lowest id wins a tie so the choice is stable
+                .max_by_key(|(score, p)| (*score, std::cmp::Reverse(p.id)))
+                .map(|(_, p)| p)
+        };
+
+        (
+            find(
+                PortDirection::Output,
+                &self.output_node,
+                &self.output_identity,
+                &self.output_port,
+            ),
+            find(
+                PortDirection::Input,
+                &self.input_node,
+                &self.input_identity,
+                &self.input_port,
+            ),
+        )
+    }
+}
+
+/// Connections copied to the clipboard as JSON, to paste on this or another
+/// machine
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ConnectionSnippet {
+    pub connections: Vec<PresetConnection>,
+}
+
+impl ConnectionSnippet {
+    /// Serials are only meaningful on the server they came from, so they are
+    /// left out rather than risk matching an unrelated node elsewhere
+    pub fn new(connections: Vec<PresetConnection>) -> Self {
+        let connections = connections
+            .into_iter()
+            .map(|mut conn| {
+                conn.output_identity.serial = None;
+                conn.input_identity.serial = None;
+                conn
+            })
+            .collect();
+        Self { connections }
+    }
+
+    pub fn to_json(&self) -> Result<String, String> {
+        serde_json::to_string_pretty(self).map_err(|e| format!("Failed to serialize: {}", e))
+    }
+
+    pub fn from_json(text: &str) -> Result<Self, String> {
+        serde_json::from_str(text.trim())
+            .map_err(|_| "The clipboard does not hold copied connections".to_string())
+    }
+}
+
+/// A device route saved with a preset, such as the headphones of a sound
+/// card, by device and route names so it survives restarts
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct PresetRoute {
+    /// `device.name` of the card
+    pub device: String,
+    /// Device of the card (`card.profile.device`) the route is selected on
+    pub card_device: u32,
+    /// Name of the route, e.g. "analog-output-headphones"
+    pub route: String,
+}
+
+/// Activates a preset when a route of a device is plugged in or unplugged,
+/// as jack detection reports it
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct RouteTrigger {
+    /// `device.name` of the card
+    pub device: String,
+    /// Name of the route, e.g. "analog-output-headphones"
+    pub route: String,
+    /// Fire on plugging in, or on unplugging when false
+    pub plugged: bool,
+    pub preset: String,
+}
+
+/// How a preset has fared, to spot one that no longer matches the graph,
+/// for example after a device was renamed
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub struct PresetStats {
+    /// Times the preset was activated
+    #[serde(default)]
+    pub activations: u32,
+    /// Connections made by auto-connecting while it was active
+    #[serde(default)]
+    pub connections_made: u32,
+    /// Connections PipeWire failed to make
+    #[serde(default)]
+    pub failures: u32,
+    #[serde(default)]
+    pub last_failure: Option<String>,
+}
+
+impl PresetStats {
+    fn is_empty(&self) -> bool {
+        *self == Self::default()
+    }
+
+    /// "Activated 3 times, 12 connections made, 1 failed: reason"
+    pub fn summary(&self) -> String {
+        let mut summary = format!(
+            "Activated {} times, {} connections made",
+            self.activations, self.connections_made
+        );
+        if self.failures > 0 {
+            summary.push_str(&format!(", {} failed", self.failures));
+            if let Some(reason) = &self.last_failure {
+                summary.push_str(&format!(", last: {}", reason));
+            }
+        }
+        summary
+    }
 }
 
 /// A named preset containing a list of connections
@@ -19,6 +250,154 @@ pub struct PresetConnection {
 pub struct Preset {
     pub name: String,
     pub connections: Vec<PresetConnection>,
+    /// Routes to select when the preset loads; empty unless the user chose
+    /// to include them
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub routes: Vec<PresetRoute>,
+    /// Key that activates the preset while a window is focused, in
+    /// `gtk::accelerator_parse` syntax
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub accelerator: Option<String>,
+    #[serde(default, skip_serializing_if = "PresetStats::is_empty")]
+    pub stats: PresetStats,
+}
+
+/// A preset written as WirePlumber rules
+#[derive(Debug, Clone)]
+pub struct WirePlumberExport {
+    /// Contents of the `.conf` fragment
+    pub text: String,
+    /// Number of streams given a target
+    pub rules: usize,
+    /// Connections that could not be expressed, listed as comments
+    pub skipped: usize,
+}
+
+/// Quote a string for SPA-JSON, the format of PipeWire and WirePlumber config
+fn spa_json_string(value: &str) -> String {
+    format!("\"{}\"", value.replace('\\', "\\\\").replace('"', "\\\""))
+}
+
+impl Preset {
+    /// Turn the preset into `stream.rules` that make WirePlumber 0.5 route
+    /// each application stream to the node it is connected to.
+    ///
+    /// WirePlumber routes whole streams, so a stream connected to several
+    /// nodes gets the one with most connections. Connections between two
+    /// devices, and nodes missing from `state` whose kind is unknown, can't
+    /// be expressed and are listed as comments instead.
+    pub fn to_wireplumber_rules(&self, state: &PwState) -> WirePlumberExport {
+        let media_class = |name: &str| {
+            state
+                .nodes
+                .values()
+                .find(|n| n.name == name)
+                .map(|n| n.media_class.clone().unwrap_or_default())
+        };
+
+        // Stream node name -> target node name -> number of connections
+        let mut targets: Vec<(String, Vec<(String, usize)>)> = Vec::new();
+        let mut skipped = Vec::new();
+        for conn in &self.connections {
+            let output_class = media_class(&conn.output_node);
+            let input_class = media_class(&conn.input_node);
+            let (stream, target) = if output_class
+                .as_deref()
+                .is_some_and(|c| c.starts_with("Stream/Output"))
+            {
+                (&conn.output_node, &conn.input_node)
+            } else if input_class
+                .as_deref()
+                .is_some_and(|c| c.starts_with("Stream/Input"))
+            {
+                (&conn.input_node, &conn.output_node)
+            } else {
+                let reason = if output_class.is_none() || input_class.is_none() {
+                    "not running when exported"
+                } else {
+                    "neither end is an application stream"
+                };
+                skipped.push(format!(
+                    "{}:{} -> {}:{} ({})",
+                    conn.output_node, conn.output_port, conn.input_node, conn.input_port, reason
+                ));
+                continue;
+            };
+
+            let index = match targets.iter().position(|(s, _)| s == stream) {
+                Some(index) => index,
+                None => {
+                    targets.push((stream.clone(), Vec::new()));
+                    targets.len() - 1
+                }
+            };
+            let counts = &mut targets[index].1;
+            match counts.iter_mut().find(|(t, _)| t == target) {
+                Some((_, count)) => *count += 1,
+                None => counts.push((target.clone(), 1)),
+            }
+        }
+
+        let mut text = format!(
+            "# WirePlumber rules generated by PW Audioshare from the preset {}.\n\
+             # Copy to ~/.config/wireplumber/wireplumber.conf.d/ and restart WirePlumber\n\
+             # (systemctl --user restart wireplumber). Needs WirePlumber 0.5 or newer.\n\n\
+             stream.rules = [\n",
+            spa_json_string(&self.name)
+        );
+        for (stream, counts) in &targets {
+            // The first of equally used targets wins, as saved in the preset
+            let mut best = &counts[0];
+            for count in counts {
+                if count.1 > best.1 {
+                    best = count;
+                }
+            }
+            text.push_str(&format!(
+                "  {{\n    matches = [\n      {{ node.name = {} }}\n    ]\n    \
+                 actions = {{\n      update-props = {{\n        target.object = {}\n      }}\n    }}\n  }}\n",
+                spa_json_string(stream),
+                spa_json_string(&best.0)
+            ));
+            for (other, _) in counts.iter().filter(|(t, _)| t != &best.0) {
+                skipped.push(format!(
+                    "{} -> {} (the stream can only have one target)",
+                    stream, other
+                ));
+            }
+        }
+        text.push_str("]\n");
+
+        if !skipped.is_empty() {
+            text.push_str("\n# Connections these rules can't express, kept by PW Audioshare only:\n");
+            for line in &skipped {
+                text.push_str(&format!("#   {}\n", line));
+            }
+        }
+
+        WirePlumberExport {
+            text,
+            rules: targets.len(),
+            skipped: skipped.len(),
+        }
+    }
+}
+
+/// A deleted preset kept for restoring, with the triggers that activated it
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct DeletedPreset {
+    pub preset: Preset,
+    #[serde(default)]
+    pub route_triggers: Vec<RouteTrigger>,
+    /// When it was deleted, in seconds since the Unix epoch
+    pub deleted_at: u64,
+}
+
+impl DeletedPreset {
+    /// Whole days since the preset was deleted
+    pub fn days_ago(&self) -> u64 {
+        now().saturating_sub(self.deleted_at) / 86_400
+    }
 }
 
 /// Collection of all saved presets
@@ -28,14 +407,18 @@ pub struct PresetStore {
     /// Name of the currently active (auto-connecting) preset, if any
     #[serde(default)]
     pub active_preset: Option<String>,
+    /// Presets to activate on jack detection events
+    #[serde(default)]
+    pub route_triggers: Vec<RouteTrigger>,
+    /// Deleted presets, most recently deleted last
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub trash: Vec<DeletedPreset>,
 }
 
 impl PresetStore {
     /// Get the path to the presets file
-    fn presets_path() -> Option<PathBuf> {
-        let config_dir = dirs::config_dir()?;
-        let app_dir = config_dir.join(APP_ID);
-        Some(app_dir.join("presets.json"))
+    pub fn presets_path() -> Option<PathBuf> {
+        Some(config::config_dir()?.join("presets.json"))
     }
 
     /// Load presets from disk
@@ -58,6 +441,15 @@ impl PresetStore {
         }
     }
 
+    /// Read presets from disk, with an error for a missing or unparsable
+    /// file rather than an empty store
+    pub fn read() -> Result<Self, String> {
+        let path = Self::presets_path().ok_or("Could not determine config directory")?;
+        let content =
+            fs::read_to_string(&path).map_err(|e| format!("Failed to read presets: {}", e))?;
+        serde_json::from_str(&content).map_err(|e| format!("Invalid presets: {}", e))
+    }
+
     /// Save presets to disk
     pub fn save(&self) -> Result<(), String> {
         let path = Self::presets_path().ok_or("Could not determine config directory")?;
@@ -80,9 +472,177 @@ impl PresetStore {
         self.presets.insert(preset.name.clone(), preset);
     }
 
-    /// Remove a preset by name
-    pub fn remove_preset(&mut self, name: &str) {
-        self.presets.remove(name);
+    /// Give a preset a new name, keeping it active and its triggers
+    pub fn rename_preset(&mut self, name: &str, new_name: &str) -> Result<(), String> {
+        if new_name.is_empty() {
+            return Err("Preset name cannot be empty".to_string());
+        }
+        if new_name != name && self.presets.contains_key(new_name) {
+            return Err(format!("A preset named \"{}\" already exists", new_name));
+        }
+        let mut preset = self
+            .presets
+            .remove(name)
+            .ok_or_else(|| format!("Preset \"{}\" not found", name))?;
+        preset.name = new_name.to_string();
+        self.add_preset(preset);
+
+        if self.is_active(name) {
+            self.active_preset = Some(new_name.to_string());
+        }
+        for trigger in self.route_triggers.iter_mut().filter(|t| t.preset == name) {
+            trigger.preset = new_name.to_string();
+        }
+        Ok(())
+    }
+
+    /// Save a copy of a preset under a new name. Triggers stay with the
+    /// original.
+    pub fn duplicate_preset(&mut self, name: &str, new_name: &str) -> Result<(), String> {
+        if new_name.is_empty() {
+            return Err("Preset name cannot be empty".to_string());
+        }
+        if self.presets.contains_key(new_name) {
+            return Err(format!("A preset named \"{}\" already exists", new_name));
+        }
+        let mut preset = self
+            .get_preset(name)
+            .cloned()
+            .ok_or_else(|| format!("Preset \"{}\" not found", name))?;
+        preset.name = new_name.to_string();
+        // A key activates one preset only
+        preset.accelerator = None;
+        preset.stats = PresetStats::default();
+        self.add_preset(preset);
+        Ok(())
+    }
+
+    /// A free name for a copy of a preset: "Name (copy)", then
+    /// "Name (copy 2)" and so on
+    pub fn unused_copy_name(&self, name: &str) -> String {
+        let mut candidate = format!("{} (copy)", name);
+        let mut n = 2;
+        while self.presets.contains_key(&candidate) {
+            candidate = format!("{} (copy {})", name, n);
+            n += 1;
+        }
+        candidate
+    }
+
+    /// Move a preset and its triggers to the trash, deactivating it.
+    /// Returns false if there is no such preset.
+    pub fn trash_preset(&mut self, name: &str) -> bool {
+        let Some(preset) = self.presets.remove(name) else {
+            return false;
+        };
+        if self.is_active(name) {
+            self.deactivate_preset();
+        }
+        let (route_triggers, kept) = self
+            .route_triggers
+            .drain(..)
+            .partition(|t| t.preset == name);
+        self.route_triggers = kept;
+        // A preset deleted again replaces its older copy
+        self.trash.retain(|d| d.preset.name != name);
+        self.trash.push(DeletedPreset {
+            preset,
+            route_triggers,
+            deleted_at: now(),
+        });
+        true
+    }
+
+    /// Put a deleted preset back, with the triggers not taken by another
+    /// preset since. Fails if a preset of the same name was saved meanwhile.
+    pub fn restore_preset(&mut self, name: &str) -> Result<(), String> {
+        if self.presets.contains_key(name) {
+            return Err(format!("A preset named \"{}\" already exists", name));
+        }
+        let index = self
+            .trash
+            .iter()
+            .rposition(|d| d.preset.name == name)
+            .ok_or_else(|| format!("Preset \"{}\" is not in the trash", name))?;
+        let mut deleted = self.trash.remove(index);
+        if let Some(accel) = &deleted.preset.accelerator {
+            if self.accelerator_preset(accel).is_some() {
+                deleted.preset.accelerator = None;
+            }
+        }
+        for trigger in deleted.route_triggers {
+            if self
+                .route_trigger(&trigger.device, &trigger.route, trigger.plugged)
+                .is_none()
+            {
+                self.route_triggers.push(trigger);
+            }
+        }
+        self.add_preset(deleted.preset);
+        Ok(())
+    }
+
+    /// Permanently remove presets deleted more than `days` days ago, or all
+    /// of them for `None`. Returns how many were removed.
+    pub fn purge_trash(&mut self, days: Option<u32>) -> usize {
+        let before = self.trash.len();
+        match days {
+            Some(days) => self.trash.retain(|d| d.days_ago() < u64::from(days)),
+            None => self.trash.clear(),
+        }
+        before - self.trash.len()
+    }
+
+    /// Give a preset a key, or none, taking the key from any other preset
+    pub fn set_accelerator(&mut self, name: &str, accel: Option<&str>) {
+        if let Some(accel) = accel {
+            for preset in self.presets.values_mut() {
+                if preset.accelerator.as_deref() == Some(accel) {
+                    preset.accelerator = None;
+                }
+            }
+        }
+        if let Some(preset) = self.presets.get_mut(name) {
+            preset.accelerator = accel.map(str::to_string);
+        }
+    }
+
+    /// The preset a key activates
+    pub fn accelerator_preset(&self, accel: &str) -> Option<&str> {
+        self.presets
+            .values()
+            .find(|p| p.accelerator.as_deref() == Some(accel))
+            .map(|p| p.name.as_str())
+    }
+
+    /// Keys and the presets they activate, ordered by preset name
+    pub fn accelerators(&self) -> Vec<(String, String)> {
+        self.preset_names()
+            .into_iter()
+            .filter_map(|name| {
+                let accel = self.presets.get(&name)?.accelerator.clone()?;
+                Some((accel, name))
+            })
+            .collect()
+    }
+
+    /// Bind a preset to a route being plugged in or unplugged, replacing
+    /// the preset bound to the same event
+    pub fn set_route_trigger(&mut self, trigger: RouteTrigger) {
+        self.remove_route_trigger(&trigger.device, &trigger.route, trigger.plugged);
+        self.route_triggers.push(trigger);
+    }
+
+    pub fn remove_route_trigger(&mut self, device: &str, route: &str, plugged: bool) {
+        self.route_triggers
+            .retain(|t| !(t.device == device && t.route == route && t.plugged == plugged));
+    }
+
+    /// The preset to activate when a route is plugged in or unplugged
+    pub fn route_trigger(&self, device: &str, route: &str, plugged: bool) -> Option<&RouteTrigger> {
+        self.route_triggers
+            .iter()
+            .find(|t| t.device == device && t.route == route && t.plugged == plugged)
     }
 
     /// Get a preset by name
@@ -90,6 +650,11 @@ impl PresetStore {
         self.presets.get(name)
     }
 
+    /// The statistics of a preset, to update them
+    pub fn stats_mut(&mut self, name: &str) -> Option<&mut PresetStats> {
+        self.presets.get_mut(name).map(|preset| &mut preset.stats)
+    }
+
     /// Get all preset names
     pub fn preset_names(&self) -> Vec<String> {
         let mut names: Vec<_> = self.presets.keys().cloned().collect();
@@ -121,3 +686,199 @@ impl PresetStore {
         self.active_preset.as_deref() == Some(name)
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::pipewire::messages::MediaType;
+
+    fn node(id: u32, name: &str, device_name: Option<&str>, serial: Option<u64>) -> PwNode {
+        PwNode {
+            id,
+            name: name.into(),
+            media_class: Some("Audio/Sink".into()),
+            description: None,
+            application_name: None,
+            serial,
+            nick: None,
+            device_name: device_name.map(String::from),
+            client_id: None,
+        }
+    }
+
+    fn add_node(state: &mut PwState, node: PwNode, ports: &[(u32, PortDirection)]) {
+        for &(id, direction) in ports {
+            state.ports.insert(
+                id,
+                PwPort {
+                    id,
+                    node_id: node.id,
+                    name: "playback_FL".into(),
+                    alias: None,
+                    direction,
+                    media_type: MediaType::Audio,
+                    channel: Some("FL".into()),
+                },
+            );
+        }
+        state.nodes.insert(node.id, node);
+    }
+
+    /// A connection from a player to `input_node`, saved with `input_identity`
+    fn connection(input_node: &str, input_identity: NodeIdentity) -> PresetConnection {
+        PresetConnection {
+            output_node: "player".into(),
+            output_port: "playback_FL".into(),
+            input_node: input_node.into(),
+            input_port: "playback_FL".into(),
+            output_identity: NodeIdentity::default(),
+            input_identity,
+        }
+    }
+
+    fn identity(device_name: Option<&str>, serial: Option<u64>) -> NodeIdentity {
+        NodeIdentity {
+            serial,
+            device_name: device_name.map(String::from),
+            nick: None,
+        }
+    }
+
+    #[test]
+    fn match_score_needs_name_or_serial_and_device() {
+        let saved = identity(Some("alsa_card.pci"), Some(7));
+        let analog = node(1, "analog", Some("alsa_card.pci"), Some(8));
+
+        assert_eq!(match_score("hdmi", &saved, &analog), None);
+        assert!(match_score("analog", &saved, &analog).is_some());
+        let renamed = node(2, "x", Some("alsa_card.pci"), Some(7));
+        assert!(match_score("renamed", &saved, &renamed).is_some());
+    }
+
+    #[test]
+    fn match_score_ignores_a_stale_serial() {
+        // After PipeWire restarts, the saved serial can belong to any node
+        let saved = identity(Some("usb_mic"), Some(7));
+        let player = node(1, "player", None, Some(7));
+        let speakers = node(2, "speakers", Some("alsa_card.pci"), Some(7));
+        assert_eq!(match_score("mic", &saved, &player), None);
+        assert_eq!(match_score("mic", &saved, &speakers), None);
+    }
+
+    #[test]
+    fn match_score_prefers_serial_then_device_name() {
+        let saved = identity(Some("alsa_card.pci"), Some(7));
+        let renamed = node(1, "other", Some("alsa_card.pci"), Some(7));
+        let by_serial = match_score("sink", &saved, &renamed);
+        let by_name_and_device = match_score(
+            "sink",
+            &saved,
+            &node(2, "sink", Some("alsa_card.pci"), None),
+        );
+        let by_name = match_score("sink", &saved, &node(3, "sink", Some("usb_card"), None));
+
+        assert!(by_serial > by_name_and_device);
+        assert!(by_name_and_device > by_name);
+        assert!(by_name.is_some());
+    }
+
+    #[test]
+    fn match_score_ignores_missing_identifiers() {
+        // Presets saved by older versions have no identity, and nodes without
+        // a device name must not match them through `None == None`
+        let saved = NodeIdentity::default();
+        assert!(match_score("sink", &saved, &node(1, "other", None, None)).is_none());
+        assert!(match_score("sink", &saved, &node(1, "sink", None, None)).is_some());
+    }
+
+    #[test]
+    fn find_ports_matches_by_name_and_direction() {
+        let mut state = PwState::new();
+        add_node(
+            &mut state,
+            node(1, "player", None, None),
+            &[(10, PortDirection::Output)],
+        );
+        add_node(
+            &mut state,
+            node(2, "speakers", None, None),
+            &[(20, PortDirection::Input)],
+        );
+
+        let (output, input) = connection("speakers", NodeIdentity::default()).find_ports(&state);
+        assert_eq!(output.map(|p| p.id), Some(10));
+        assert_eq!(input.map(|p| p.id), Some(20));
+    }
+
+    #[test]
+    fn find_ports_never_moves_to_another_output_of_the_card() {
+        let mut state = PwState::new();
+        add_node(
+            &mut state,
+            node(1, "player", None, None),
+            &[(10, PortDirection::Output)],
+        );
+        add_node(
+            &mut state,
+            node(3, "hdmi", Some("alsa_card.pci"), None),
+            &[(30, PortDirection::Input)],
+        );
+
+        let saved = identity(Some("alsa_card.pci"), None);
+        let (output, input) = connection("analog", saved).find_ports(&state);
+        assert!(output.is_some());
+        assert!(input.is_none());
+    }
+
+    #[test]
+    fn find_ports_tells_apart_nodes_sharing_a_name() {
+        let mut state = PwState::new();
+        add_node(
+            &mut state,
+            node(1, "player", None, None),
+            &[(10, PortDirection::Output)],
+        );
+        add_node(
+            &mut state,
+            node(2, "usb-headset", Some("usb_a"), None),
+            &[(20, PortDirection::Input)],
+        );
+        add_node(
+            &mut state,
+            node(3, "usb-headset", Some("usb_b"), None),
+            &[(30, PortDirection::Input)],
+        );
+
+        let (_, input) =
+            connection("usb-headset", identity(Some("usb_b"), None)).find_ports(&state);
+        assert_eq!(input.map(|p| p.id), Some(30));
+
+        // Without a device name to go by, the lowest id wins so the choice
+        // is stable
+        let (_, input) = connection("usb-headset", NodeIdentity::default()).find_ports(&state);
+        assert_eq!(input.map(|p| p.id), Some(20));
+    }
+
+    #[test]
+    fn find_ports_prefers_the_saved_serial() {
+        let mut state = PwState::new();
+        add_node(
+            &mut state,
+            node(1, "player", None, None),
+            &[(10, PortDirection::Output)],
+        );
+        add_node(
+            &mut state,
+            node(2, "sink", None, Some(5)),
+            &[(20, PortDirection::Input)],
+        );
+        add_node(
+            &mut state,
+            node(3, "sink", None, Some(6)),
+            &[(30, PortDirection::Input)],
+        );
+
+        let (_, input) = connection("sink", identity(None, Some(6))).find_ports(&state);
+        assert_eq!(input.map(|p| p.id), Some(30));
+    }
+}
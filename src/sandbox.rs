@@ -0,0 +1,154 @@
+use std::cell::RefCell;
+use std::collections::HashMap;
+use std::path::{Path, PathBuf};
+use std::process::Command;
+use std::rc::Rc;
+
+use gtk::gio;
+use gtk::glib::{self, ToVariant};
+
+const PORTAL_BUS_NAME: &str = "org.freedesktop.portal.Desktop";
+const PORTAL_OBJECT_PATH: &str = "/org/freedesktop/portal/desktop";
+
+/// Whether the app runs inside a Flatpak sandbox
+pub fn is_flatpak() -> bool {
+    Path::new("/.flatpak-info").exists()
+}
+
+/// A command for a PipeWire or desktop tool. Inside Flatpak the tools of the
+/// host are used through `flatpak-spawn --host`, which needs the
+/// `--talk-name=org.freedesktop.Flatpak` permission.
+pub fn host_command(program: &str) -> Command {
+    if is_flatpak() {
+        let mut command = Command::new("flatpak-spawn");
+        command.args(["--host", program]);
+        command
+    } else {
+        Command::new(program)
+    }
+}
+
+/// Directory for files read by host tools, such as the config of a module
+/// helper. Inside Flatpak only the app's `$XDG_RUNTIME_DIR/app/<id>` is
+/// shared with the host, at the same path.
+pub fn host_runtime_dir() -> PathBuf {
+    let runtime = dirs::runtime_dir().unwrap_or_else(std::env::temp_dir);
+    match std::env::var("FLATPAK_ID") {
+        Ok(id) if is_flatpak() => runtime.join("app").join(id),
+        _ => runtime.join(crate::config::APP_ID),
+    }
+}
+
+/// How to give a sandboxed app access to something it was denied, to append
+/// to error messages
+pub fn permission_hint(permission: &str) -> String {
+    if is_flatpak() {
+        format!(
+            " Grant access with: flatpak override --user {} {}",
+            permission,
+            crate::config::DBUS_NAME
+        )
+    } else {
+        String::new()
+    }
+}
+
+/// Ask the Background portal to let the app run in the background and,
+/// with `autostart`, to start it at login with `commandline`. `on_done`
+/// gets whether autostart was granted, or why the request failed.
+pub fn request_background<F>(reason: &str, autostart: bool, commandline: &[&str], on_done: F)
+where
+    F: FnOnce(Result<bool, String>) + 'static,
+{
+    let connection = match gio::bus_get_sync(gio::BusType::Session, gio::Cancellable::NONE) {
+        Ok(connection) => connection,
+        Err(e) => {
+            on_done(Err(format!("Failed to reach the session bus: {}", e)));
+            return;
+        }
+    };
+
+    // The reply arrives as a Response signal on a request object whose path
+    // is derived from our bus name and a token of our choosing
+    let token = format!("pw_audioshare_{}", glib::random_int());
+    let sender = connection
+        .unique_name()
+        .map(|name| name.trim_start_matches(':').replace('.', "_"))
+        .unwrap_or_default();
+    let request_path = format!("{}/request/{}/{}", PORTAL_OBJECT_PATH, sender, token);
+
+    let on_done = Rc::new(RefCell::new(Some(on_done)));
+    let subscription = Rc::new(RefCell::new(None));
+    let id = connection.signal_subscribe(
+        Some(PORTAL_BUS_NAME),
+        Some("org.freedesktop.portal.Request"),
+        Some("Response"),
+        Some(&request_path),
+        None,
+        gio::DBusSignalFlags::NO_MATCH_RULE,
+        glib::clone!(
+            #[strong]
+            on_done,
+            #[strong]
+            subscription,
+            move |connection, _, _, _, _, params| {
+                if let Some(id) = subscription.borrow_mut().take() {
+                    connection.signal_unsubscribe(id);
+                }
+                let Some(on_done) = on_done.borrow_mut().take() else {
+                    return;
+                };
+                let Some((response, results)) =
+                    params.get::<(u32, HashMap<String, glib::Variant>)>()
+                else {
+                    on_done(Err("The background portal sent an invalid reply".to_string()));
+                    return;
+                };
+                if response != 0 {
+                    on_done(Err("Running in the background was not allowed".to_string()));
+                    return;
+                }
+                let granted = results
+                    .get("autostart")
+                    .and_then(|v| v.get::<bool>())
+                    .unwrap_or(false);
+                on_done(Ok(granted));
+            }
+        ),
+    );
+    subscription.replace(Some(id));
+
+    let mut options: HashMap<&str, glib::Variant> = HashMap::new();
+    options.insert("handle_token", token.to_variant());
+    options.insert("reason", reason.to_variant());
+    options.insert("autostart", autostart.to_variant());
+    let commandline: Vec<String> = commandline.iter().map(|s| s.to_string()).collect();
+    options.insert("commandline", commandline.to_variant());
+
+    connection.call(
+        Some(PORTAL_BUS_NAME),
+        PORTAL_OBJECT_PATH,
+        "org.freedesktop.portal.Background",
+        "RequestBackground",
+        Some(&("", options).to_variant()),
+        None,
+        gio::DBusCallFlags::NONE,
+        -1,
+        gio::Cancellable::NONE,
+        glib::clone!(
+            #[strong]
+            connection,
+            move |result| {
+                let Err(e) = result else {
+                    return;
+                };
+                if let Some(id) = subscription.borrow_mut().take() {
+                    connection.signal_unsubscribe(id);
+                }
+                if let Some(on_done) = on_done.borrow_mut().take() {
+                    on_done(Err(format!("The background portal is unavailable: {}", e)));
+                }
+            }
+        ),
+    );
+}
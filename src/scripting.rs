@@ -0,0 +1,246 @@
+use std::cell::RefCell;
+use std::fs;
+use std::path::{Path, PathBuf};
+use std::rc::Rc;
+
+use mlua::{Function, IntoLuaMulti, Lua, RegistryKey, Table, Value};
+
+use crate::config;
+use crate::pipewire::state::{PwClient, PwNode, PwPort};
+
+/// A port named by a script, by id or by its "node.name:port.name" path
+#[derive(Debug, Clone)]
+pub enum PortRef {
+    Id(u32),
+    Path(String),
+}
+
+/// Something a script asked for, carried out by the window once the hook
+/// returns
+#[derive(Debug, Clone)]
+pub enum ScriptCommand {
+    Connect(PortRef, PortRef),
+    Disconnect(PortRef, PortRef),
+    ActivatePreset(String),
+}
+
+/// One loaded script and the environment holding its hooks
+struct Script {
+    name: String,
+    env: RegistryKey,
+}
+
+/// The Lua state running every user script
+pub struct ScriptHost {
+    lua: Lua,
+    scripts: Vec<Script>,
+    commands: Rc<RefCell<Vec<ScriptCommand>>>,
+}
+
+/// Directory user scripts are loaded from
+pub fn scripts_dir() -> Option<PathBuf> {
+    Some(config::config_dir()?.join("scripts"))
+}
+
+fn port_ref(value: Value) -> mlua::Result<PortRef> {
+    match value {
+        Value::Integer(id) => u32::try_from(id)
+            .map(PortRef::Id)
+            .map_err(|_| mlua::Error::runtime(format!("invalid port id {}", id))),
+        Value::String(path) => Ok(PortRef::Path(path.to_str()?.to_string())),
+        other => Err(mlua::Error::runtime(format!(
+            "a port must be an id or \"node:port\" path, not {}",
+            other.type_name()
+        ))),
+    }
+}
+
+impl ScriptHost {
+    /// Load every `*.lua` file in the scripts directory, in name order.
+    /// A script that fails to load is skipped.
+    pub fn load() -> Self {
+        let host = Self {
+            lua: Lua::new(),
+            scripts: Vec::new(),
+            commands: Rc::new(RefCell::new(Vec::new())),
+        };
+        let mut host = match host.install_api() {
+            Ok(()) => host,
+            Err(e) => {
+                log::error!("Failed to set up scripting: {}", e);
+                return host;
+            }
+        };
+
+        let Some(dir) = scripts_dir() else {
+            return host;
+        };
+        let mut paths: Vec<PathBuf> = match fs::read_dir(&dir) {
+            Ok(entries) => entries
+                .filter_map(|entry| entry.ok().map(|e| e.path()))
+                .filter(|path| path.extension().is_some_and(|ext| ext == "lua"))
+                .collect(),
+            Err(_) => return host,
+        };
+        paths.sort();
+
+        for path in paths {
+            let name = path
+                .file_name()
+                .map(|n| n.to_string_lossy().into_owned())
+                .unwrap_or_default();
+            match host.load_script(&name, &path) {
+                Ok(script) => {
+                    log::info!("Loaded script {}", name);
+                    host.scripts.push(script);
+                }
+                Err(e) => log::warn!("Failed to load script {}: {}", name, e),
+            }
+        }
+        host
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.scripts.is_empty()
+    }
+
+    /// The `pwa` table scripts use to change routing
+    fn install_api(&self) -> mlua::Result<()> {
+        let api = self.lua.create_table()?;
+
+        let commands = self.commands.clone();
+        api.set(
+            "connect",
+            self.lua.create_function(move |_, (output, input): (Value, Value)| {
+                let command = ScriptCommand::Connect(port_ref(output)?, port_ref(input)?);
+                commands.borrow_mut().push(command);
+                Ok(())
+            })?,
+        )?;
+
+        let commands = self.commands.clone();
+        api.set(
+            "disconnect",
+            self.lua.create_function(move |_, (output, input): (Value, Value)| {
+                let command = ScriptCommand::Disconnect(port_ref(output)?, port_ref(input)?);
+                commands.borrow_mut().push(command);
+                Ok(())
+            })?,
+        )?;
+
+        let commands = self.commands.clone();
+        api.set(
+            "activate_preset",
+            self.lua.create_function(move |_, name: String| {
+                commands.borrow_mut().push(ScriptCommand::ActivatePreset(name));
+                Ok(())
+            })?,
+        )?;
+
+        api.set(
+            "log",
+            self.lua.create_function(|_, message: String| {
+                log::info!("[script] {}", message);
+                Ok(())
+            })?,
+        )?;
+
+        self.lua.globals().set("pwa", api)
+    }
+
+    /// Run a script in its own environment, so hooks of different scripts
+    /// don't replace each other
+    fn load_script(&self, name: &str, path: &Path) -> anyhow::Result<Script> {
+        let code = fs::read_to_string(path)?;
+
+        let env = self.lua.create_table()?;
+        let meta = self.lua.create_table()?;
+        meta.set("__index", self.lua.globals())?;
+        env.set_metatable(Some(meta));
+
+        self.lua
+            .load(&code)
+            .set_name(name)
+            .set_environment(env.clone())
+            .exec()?;
+
+        Ok(Script {
+            name: name.to_string(),
+            env: self.lua.create_registry_value(env)?,
+        })
+    }
+
+    /// Call a hook in every script defining it
+    fn call<'lua, A>(&'lua self, hook: &str, args: A)
+    where
+        A: IntoLuaMulti<'lua> + Clone,
+    {
+        for script in &self.scripts {
+            let result = self
+                .lua
+                .registry_value::<Table>(&script.env)
+                .and_then(|env| env.get::<_, Option<Function>>(hook))
+                .and_then(|function| match function {
+                    Some(function) => function.call::<_, ()>(args.clone()),
+                    None => Ok(()),
+                });
+            if let Err(e) = result {
+                log::warn!("Script {} failed in {}: {}", script.name, hook, e);
+            }
+        }
+    }
+
+    /// Take the commands scripts issued since the last call
+    pub fn take_commands(&self) -> Vec<ScriptCommand> {
+        std::mem::take(&mut *self.commands.borrow_mut())
+    }
+
+    /// `client` is the client that created the node, when known, so rules
+    /// can match streams by their process
+    pub fn node_added(&self, node: &PwNode, client: Option<&PwClient>) {
+        let table = self.lua.create_table().and_then(|t| {
+            t.set("id", node.id)?;
+            t.set("name", node.name.as_str())?;
+            t.set("description", node.description.as_deref())?;
+            t.set("application_name", node.application_name.as_deref())?;
+            t.set("media_class", node.media_class.as_deref())?;
+            if let Some(client) = client {
+                t.set("process_binary", client.process_binary.as_deref())?;
+                t.set("pid", client.pid)?;
+            }
+            Ok(t)
+        });
+        match table {
+            Ok(table) => self.call("on_node_added", table),
+            Err(e) => log::warn!("Failed to pass node to scripts: {}", e),
+        }
+    }
+
+    /// Ports arrive after their node, so this is where scripts can connect
+    /// a new node
+    pub fn port_added(&self, port: &PwPort, path: Option<&str>) {
+        let table = self.lua.create_table().and_then(|t| {
+            t.set("id", port.id)?;
+            t.set("node_id", port.node_id)?;
+            t.set("name", port.name.as_str())?;
+            t.set("path", path)?;
+            t.set("direction", port.direction.as_str())?;
+            t.set("media_type", port.media_type.as_str())?;
+            t.set("channel", port.channel.as_deref())?;
+            Ok(t)
+        });
+        match table {
+            Ok(table) => self.call("on_port_added", table),
+            Err(e) => log::warn!("Failed to pass port to scripts: {}", e),
+        }
+    }
+
+    pub fn preset_activated(&self, name: &str) {
+        self.call("on_preset_activated", name.to_string());
+    }
+
+    /// A link failed; the ports are given as paths when still known
+    pub fn link_error(&self, output: Option<String>, input: Option<String>, message: &str) {
+        self.call("on_link_error", (output, input, message.to_string()));
+    }
+}
@@ -0,0 +1,155 @@
+use std::collections::HashMap;
+
+use gtk::gio;
+use gtk::glib::{self, ToVariant};
+
+use crate::presets::PresetStore;
+
+/// Object path of the GNOME Shell search provider, as named in
+/// `data/pw-audioshare-search-provider.ini`
+pub const OBJECT_PATH: &str = "/io/github/destructatron/PwAudioshare/SearchProvider";
+
+const INTERFACE_XML: &str = r#"
+<node>
+  <interface name="org.gnome.Shell.SearchProvider2">
+    <method name="GetInitialResultSet">
+      <arg type="as" name="terms" direction="in"/>
+      <arg type="as" name="results" direction="out"/>
+    </method>
+    <method name="GetSubsearchResultSet">
+      <arg type="as" name="previous_results" direction="in"/>
+      <arg type="as" name="terms" direction="in"/>
+      <arg type="as" name="results" direction="out"/>
+    </method>
+    <method name="GetResultMetas">
+      <arg type="as" name="identifiers" direction="in"/>
+      <arg type="aa{sv}" name="metas" direction="out"/>
+    </method>
+    <method name="ActivateResult">
+      <arg type="s" name="identifier" direction="in"/>
+      <arg type="as" name="terms" direction="in"/>
+      <arg type="u" name="timestamp" direction="in"/>
+    </method>
+    <method name="LaunchSearch">
+      <arg type="as" name="terms" direction="in"/>
+      <arg type="u" name="timestamp" direction="in"/>
+    </method>
+  </interface>
+</node>
+"#;
+
+/// Prefix of result identifiers, followed by the preset name
+const PRESET_RESULT_PREFIX: &str = "preset:";
+
+/// What the user chose in the Shell overview
+#[derive(Debug, Clone)]
+pub enum SearchRequest {
+    /// Activate the named preset
+    ActivatePreset(String),
+    /// Show the application, as for "more results"
+    ShowWindow,
+}
+
+/// Identifiers of the presets whose names contain every search term
+fn matching_presets(store: &PresetStore, terms: &[String]) -> Vec<String> {
+    let terms: Vec<String> = terms.iter().map(|t| t.to_lowercase()).collect();
+    store
+        .preset_names()
+        .into_iter()
+        .filter(|name| {
+            let name = name.to_lowercase();
+            terms.iter().all(|term| name.contains(term.as_str()))
+        })
+        .map(|name| format!("{}{}", PRESET_RESULT_PREFIX, name))
+        .collect()
+}
+
+/// Name and description shown for each result
+fn result_metas(
+    store: &PresetStore,
+    identifiers: &[String],
+) -> Vec<HashMap<String, glib::Variant>> {
+    identifiers
+        .iter()
+        .filter_map(|id| {
+            let name = id.strip_prefix(PRESET_RESULT_PREFIX)?;
+            let preset = store.get_preset(name)?;
+            let description = if store.is_active(name) {
+                format!("Active, {} connections", preset.connections.len())
+            } else {
+                format!("{} connections", preset.connections.len())
+            };
+            let mut meta = HashMap::new();
+            meta.insert("id".to_string(), id.to_variant());
+            meta.insert(
+                "name".to_string(),
+                format!("Activate preset \"{}\"", name).to_variant(),
+            );
+            meta.insert("description".to_string(), description.to_variant());
+            meta.insert("gicon".to_string(), "audio-card".to_variant());
+            Some(meta)
+        })
+        .collect()
+}
+
+/// Export the search provider on the application's D-Bus connection.
+/// `on_request` runs on the main thread when a result is chosen.
+pub fn register<F>(
+    connection: &gio::DBusConnection,
+    on_request: F,
+) -> Result<gio::RegistrationId, glib::Error>
+where
+    F: Fn(SearchRequest) + 'static,
+{
+    let node = gio::DBusNodeInfo::for_xml(INTERFACE_XML)?;
+    let interface = node
+        .lookup_interface("org.gnome.Shell.SearchProvider2")
+        .expect("search provider interface is defined above");
+
+    connection
+        .register_object(OBJECT_PATH, &interface)
+        .method_call(
+            move |_connection, _sender, _path, _interface, method, params, invocation| {
+                // Presets are saved on every change, so the file is current
+                let store = PresetStore::load();
+                match method {
+                    "GetInitialResultSet" => {
+                        let (terms,) = params.get::<(Vec<String>,)>().unwrap_or_default();
+                        let results = matching_presets(&store, &terms);
+                        invocation.return_value(Some(&(results,).to_variant()));
+                    }
+                    "GetSubsearchResultSet" => {
+                        let (_previous, terms) = params
+                            .get::<(Vec<String>, Vec<String>)>()
+                            .unwrap_or_default();
+                        let results = matching_presets(&store, &terms);
+                        invocation.return_value(Some(&(results,).to_variant()));
+                    }
+                    "GetResultMetas" => {
+                        let (identifiers,) = params.get::<(Vec<String>,)>().unwrap_or_default();
+                        let metas = result_metas(&store, &identifiers);
+                        invocation.return_value(Some(&(metas,).to_variant()));
+                    }
+                    "ActivateResult" => {
+                        let identifier = params
+                            .get::<(String, Vec<String>, u32)>()
+                            .map(|(id, _, _)| id)
+                            .unwrap_or_default();
+                        invocation.return_value(None);
+                        if let Some(name) = identifier.strip_prefix(PRESET_RESULT_PREFIX) {
+                            on_request(SearchRequest::ActivatePreset(name.to_string()));
+                        }
+                    }
+                    "LaunchSearch" => {
+                        invocation.return_value(None);
+                        on_request(SearchRequest::ShowWindow);
+                    }
+                    _ => invocation.return_dbus_error(
+                        "org.freedesktop.DBus.Error.UnknownMethod",
+                        &format!("Unknown method {}", method),
+                    ),
+                }
+            },
+        )
+        .build()
+}
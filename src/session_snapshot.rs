@@ -0,0 +1,96 @@
+use serde::{Deserialize, Serialize};
+use std::fs;
+use std::path::PathBuf;
+use std::time::{SystemTime, UNIX_EPOCH};
+
+use crate::config;
+use crate::presets::PresetConnection;
+
+/// The connections of the graph, saved every so often while the app runs so
+/// routing that was never saved as a preset can be restored after a crash
+/// or a PipeWire restart
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct SessionSnapshot {
+    /// Seconds since the Unix epoch when the snapshot was taken
+    pub saved_at: u64,
+    /// Set when the app quits normally; still false at the next start
+    /// means the app crashed or was killed
+    #[serde(default)]
+    pub clean_exit: bool,
+    pub connections: Vec<PresetConnection>,
+}
+
+impl SessionSnapshot {
+    /// A snapshot of `connections`, taken now. Serials are left out since
+    /// they don't survive a PipeWire restart and could match an unrelated
+    /// node afterwards.
+    pub fn new(connections: Vec<PresetConnection>) -> Self {
+        let connections = connections
+            .into_iter()
+            .map(|mut conn| {
+                conn.output_identity.serial = None;
+                conn.input_identity.serial = None;
+                conn
+            })
+            .collect();
+        Self {
+            saved_at: SystemTime::now()
+                .duration_since(UNIX_EPOCH)
+                .map(|d| d.as_secs())
+                .unwrap_or_default(),
+            clean_exit: false,
+            connections,
+        }
+    }
+
+    /// Get the path to the snapshot file
+    pub fn snapshot_path() -> Option<PathBuf> {
+        Some(config::state_dir()?.join("session.json"))
+    }
+
+    /// Load the last snapshot, if any was saved
+    pub fn load() -> Option<Self> {
+        let path = Self::snapshot_path()?;
+        if !path.exists() {
+            return None;
+        }
+        match fs::read_to_string(&path) {
+            Ok(content) => serde_json::from_str(&content).ok(),
+            Err(e) => {
+                log::warn!("Failed to load the session snapshot: {}", e);
+                None
+            }
+        }
+    }
+
+    /// Save the snapshot to disk
+    pub fn save(&self) -> Result<(), String> {
+        let path = Self::snapshot_path().ok_or("Could not determine state directory")?;
+
+        if let Some(parent) = path.parent() {
+            fs::create_dir_all(parent).map_err(|e| format!("Failed to create state dir: {}", e))?;
+        }
+
+        let content = serde_json::to_string_pretty(self)
+            .map_err(|e| format!("Failed to serialize: {}", e))?;
+
+        // Write a temporary file and rename it, so a crash while writing
+        // never leaves a truncated snapshot behind
+        let temp = path.with_extension("json.tmp");
+        fs::write(&temp, content)
+            .map_err(|e| format!("Failed to write the session snapshot: {}", e))?;
+        fs::rename(&temp, &path).map_err(|e| format!("Failed to write the session snapshot: {}", e))
+    }
+
+    /// Mark the last snapshot as ending in a normal quit, so the next start
+    /// doesn't offer to restore it
+    pub fn mark_clean_exit() {
+        let Some(mut snapshot) = Self::load() else {
+            return;
+        };
+        snapshot.clean_exit = true;
+        if let Err(e) = snapshot.save() {
+            log::warn!("{}", e);
+        }
+    }
+}
@@ -2,7 +2,248 @@ use serde::{Deserialize, Serialize};
 use std::fs;
 use std::path::PathBuf;
 
-use crate::config::APP_ID;
+use crate::config;
+use crate::gsettings;
+use crate::osc;
+use crate::pipewire::LinkOptions;
+use crate::presets::{self, PresetConnection};
+
+/// A tunnel to a remote PipeWire/PulseAudio server, recreated on startup
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct TunnelConfig {
+    /// Description shown for the local device
+    pub name: String,
+    pub host: String,
+    pub port: u16,
+    /// true for a sink sending to the remote host, false for a source receiving from it
+    pub send: bool,
+    /// Remote sink or source name; the remote default if unset
+    #[serde(default)]
+    pub remote_device: Option<String>,
+}
+
+/// How much the app announces to screen readers on its own, least first
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, PartialOrd, Ord, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum Verbosity {
+    /// Only errors
+    Silent,
+    /// Results of the user's actions and short summaries of background activity
+    #[default]
+    Important,
+    /// Also every automatic connection and device change
+    Verbose,
+}
+
+impl Verbosity {
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            Verbosity::Silent => "silent",
+            Verbosity::Important => "important",
+            Verbosity::Verbose => "verbose",
+        }
+    }
+
+    pub fn from_name(s: &str) -> Option<Self> {
+        match s {
+            "silent" => Some(Verbosity::Silent),
+            "important" => Some(Verbosity::Important),
+            "verbose" => Some(Verbosity::Verbose),
+            _ => None,
+        }
+    }
+}
+
+/// What the port lists show: search text and which kinds of port are visible
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct PortFilter {
+    #[serde(default)]
+    pub search: String,
+    #[serde(default = "default_true")]
+    pub show_audio: bool,
+    #[serde(default = "default_true")]
+    pub show_midi: bool,
+    #[serde(default = "default_true")]
+    pub show_video: bool,
+    /// Show the monitor ports of sinks
+    #[serde(default = "default_true")]
+    pub show_monitors: bool,
+}
+
+impl Default for PortFilter {
+    fn default() -> Self {
+        Self {
+            search: String::new(),
+            show_audio: true,
+            show_midi: true,
+            show_video: true,
+            show_monitors: true,
+        }
+    }
+}
+
+/// Which port types the filter bar shows when the app starts
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum StartupPortTypes {
+    /// The Audio, MIDI, Video and Monitors toggles as they were left
+    #[default]
+    Last,
+    /// Every port type
+    All,
+    /// Audio ports, including monitors, only
+    Audio,
+}
+
+impl StartupPortTypes {
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            StartupPortTypes::Last => "last",
+            StartupPortTypes::All => "all",
+            StartupPortTypes::Audio => "audio",
+        }
+    }
+
+    pub fn from_name(s: &str) -> Option<Self> {
+        match s {
+            "last" => Some(StartupPortTypes::Last),
+            "all" => Some(StartupPortTypes::All),
+            "audio" => Some(StartupPortTypes::Audio),
+            _ => None,
+        }
+    }
+}
+
+/// What closing the main window does
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum CloseAction {
+    /// Ask, with an option to remember the answer
+    #[default]
+    Ask,
+    /// Hide the window and keep running in the system tray
+    Tray,
+    /// Quit the application
+    Quit,
+}
+
+impl CloseAction {
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            CloseAction::Ask => "ask",
+            CloseAction::Tray => "tray",
+            CloseAction::Quit => "quit",
+        }
+    }
+
+    pub fn from_name(s: &str) -> Option<Self> {
+        match s {
+            "ask" => Some(CloseAction::Ask),
+            "tray" => Some(CloseAction::Tray),
+            "quit" => Some(CloseAction::Quit),
+            _ => None,
+        }
+    }
+}
+
+/// How the port lists are ordered
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum PortSort {
+    /// By node, then by channel position
+    #[default]
+    Name,
+    /// Grouped by the application owning each node
+    App,
+    /// Newest ports first
+    Recent,
+}
+
+impl PortSort {
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            PortSort::Name => "name",
+            PortSort::App => "app",
+            PortSort::Recent => "recent",
+        }
+    }
+
+    pub fn from_name(s: &str) -> Option<Self> {
+        match s {
+            "name" => Some(PortSort::Name),
+            "app" => Some(PortSort::App),
+            "recent" => Some(PortSort::Recent),
+            _ => None,
+        }
+    }
+}
+
+/// Size of the text and rows of the port and connection lists, apart from
+/// the system's scale
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum ListTextSize {
+    #[default]
+    Normal,
+    Large,
+    Largest,
+}
+
+impl ListTextSize {
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            ListTextSize::Normal => "normal",
+            ListTextSize::Large => "large",
+            ListTextSize::Largest => "largest",
+        }
+    }
+
+    pub fn from_name(s: &str) -> Option<Self> {
+        match s {
+            "normal" => Some(ListTextSize::Normal),
+            "large" => Some(ListTextSize::Large),
+            "largest" => Some(ListTextSize::Largest),
+            _ => None,
+        }
+    }
+}
+
+/// Light or dark style of the window
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum Appearance {
+    /// Follow the desktop's preference
+    #[default]
+    System,
+    Light,
+    Dark,
+}
+
+impl Appearance {
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            Appearance::System => "system",
+            Appearance::Light => "light",
+            Appearance::Dark => "dark",
+        }
+    }
+
+    pub fn from_name(s: &str) -> Option<Self> {
+        match s {
+            "system" => Some(Appearance::System),
+            "light" => Some(Appearance::Light),
+            "dark" => Some(Appearance::Dark),
+            _ => None,
+        }
+    }
+}
+
+/// A port filter saved under a name
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct SavedFilter {
+    pub name: String,
+    pub filter: PortFilter,
+}
 
 /// Application settings that persist across restarts
 #[derive(Debug, Clone, Serialize, Deserialize)]
@@ -10,26 +251,243 @@ pub struct Settings {
     /// Whether to start minimized to the system tray
     #[serde(default)]
     pub start_minimized: bool,
+
+    /// Whether to show the system tray icon; without one, closing the
+    /// window quits
+    #[serde(default = "default_true")]
+    pub show_tray: bool,
+
+    /// Scrolling over the tray icon switches the active preset
+    #[serde(default = "default_true")]
+    pub tray_scroll_presets: bool,
+
+    /// Middle-clicking the tray icon shows or hides the window
+    #[serde(default = "default_true")]
+    pub tray_middle_click_toggle: bool,
+
+    /// What the window's close button does
+    #[serde(default)]
+    pub close_action: CloseAction,
+
+    /// Create new links with `link.passive` so they don't keep devices running
+    #[serde(default)]
+    pub link_passive: bool,
+
+    /// Create new links with `object.linger` so they survive the app exiting
+    #[serde(default = "default_true")]
+    pub link_linger: bool,
+
+    /// Mirror log output to a rotating file in the state directory
+    #[serde(default)]
+    pub log_to_file: bool,
+
+    /// Record every PipeWire event for the event debugger
+    #[serde(default)]
+    pub record_events: bool,
+
+    /// Tunnels to remote machines
+    #[serde(default)]
+    pub tunnels: Vec<TunnelConfig>,
+
+    /// Which screen reader announcements are made
+    #[serde(default)]
+    pub announcement_verbosity: Verbosity,
+
+    /// Filter in use when the app last ran, restored on startup
+    #[serde(default)]
+    pub last_filter: PortFilter,
+
+    /// Port types shown at startup
+    #[serde(default)]
+    pub startup_port_types: StartupPortTypes,
+
+    /// Order of the port lists
+    #[serde(default)]
+    pub port_sort: PortSort,
+
+    /// Filters saved by the user, in the order they were saved
+    #[serde(default)]
+    pub saved_filters: Vec<SavedFilter>,
+
+    /// Connections shown with a toggle in the compact window
+    #[serde(default)]
+    pub pinned_connections: Vec<PresetConnection>,
+
+    /// Accept JSON-RPC requests on a Unix socket in the runtime directory
+    #[serde(default)]
+    pub control_socket: bool,
+
+    /// Accept OSC messages from touch surfaces on `osc_port`
+    #[serde(default)]
+    pub osc_server: bool,
+
+    /// UDP port of the OSC server
+    #[serde(default = "default_osc_port")]
+    pub osc_port: u16,
+
+    /// Accept OSC messages from other computers, not only this one
+    #[serde(default)]
+    pub osc_listen_all: bool,
+
+    /// Start at login through the Background portal; used inside Flatpak,
+    /// where the systemd user service can't be written
+    #[serde(default)]
+    pub background_autostart: bool,
+
+    /// PipeWire socket to connect to, by name in the runtime directory or
+    /// as a path; `pipewire-0` if unset
+    #[serde(default)]
+    pub pipewire_remote: Option<String>,
+
+    /// Switching presets removes the previous preset's connections once the
+    /// new ones are made, instead of keeping them
+    #[serde(default)]
+    pub replace_on_preset_switch: bool,
+
+    /// The setup assistant has been shown. Settings saved before it existed
+    /// count as done, so only new users see it.
+    #[serde(default = "default_true")]
+    pub setup_assistant_done: bool,
+
+    /// Removing more than one connection at once lists them and asks first
+    #[serde(default = "default_true")]
+    pub confirm_bulk_disconnect: bool,
+
+    /// Days deleted presets stay in the trash; 0 keeps them until the trash
+    /// is emptied
+    #[serde(default = "default_trash_days")]
+    pub preset_trash_days: u32,
+
+    /// Size of the text and rows of the lists
+    #[serde(default)]
+    pub list_text_size: ListTextSize,
+
+    /// Turn off animations, even if the desktop has them on
+    #[serde(default)]
+    pub reduce_motion: bool,
+
+    /// Show the input ports before the output ports
+    #[serde(default)]
+    pub swap_port_panes: bool,
+
+    /// Show the port lists above each other instead of side by side
+    #[serde(default)]
+    pub stack_port_panes: bool,
+
+    /// Share of the space taken by the first port list, in percent
+    #[serde(default = "default_port_pane_split")]
+    pub port_pane_split: u32,
+
+    /// Light or dark style, or the desktop's
+    #[serde(default)]
+    pub appearance: Appearance,
+
+    /// Mark port and connection rows with a colour for their media type
+    #[serde(default)]
+    pub media_colors: bool,
+
+    /// Keep the computer from suspending while audio is
+    /// shared over the network
+    #[serde(default = "default_true")]
+    pub inhibit_suspend: bool,
 }
 
 impl Default for Settings {
     fn default() -> Self {
         Self {
             start_minimized: false,
+            show_tray: true,
+            tray_scroll_presets: true,
+            tray_middle_click_toggle: true,
+            close_action: CloseAction::default(),
+            link_passive: false,
+            link_linger: true,
+            log_to_file: false,
+            record_events: false,
+            tunnels: Vec::new(),
+            announcement_verbosity: Verbosity::default(),
+            last_filter: PortFilter::default(),
+            startup_port_types: StartupPortTypes::default(),
+            port_sort: PortSort::default(),
+            saved_filters: Vec::new(),
+            pinned_connections: Vec::new(),
+            control_socket: false,
+            osc_server: false,
+            osc_port: osc::DEFAULT_PORT,
+            osc_listen_all: false,
+            background_autostart: false,
+            pipewire_remote: None,
+            replace_on_preset_switch: false,
+            setup_assistant_done: false,
+            confirm_bulk_disconnect: true,
+            preset_trash_days: presets::DEFAULT_TRASH_DAYS,
+            list_text_size: ListTextSize::default(),
+            reduce_motion: false,
+            swap_port_panes: false,
+            stack_port_panes: false,
+            port_pane_split: default_port_pane_split(),
+            appearance: Appearance::default(),
+            media_colors: false,
+            inhibit_suspend: true,
         }
     }
 }
 
+fn default_true() -> bool {
+    true
+}
+
+fn default_osc_port() -> u16 {
+    osc::DEFAULT_PORT
+}
+
+fn default_trash_days() -> u32 {
+    presets::DEFAULT_TRASH_DAYS
+}
+
+fn default_port_pane_split() -> u32 {
+    50
+}
+
 impl Settings {
+    /// The filter to start with: the last one, with the port types
+    /// replaced unless they should be kept
+    pub fn startup_filter(&self) -> PortFilter {
+        let mut filter = self.last_filter.clone();
+        let (audio, midi, video) = match self.startup_port_types {
+            StartupPortTypes::Last => return filter,
+            StartupPortTypes::All => (true, true, true),
+            StartupPortTypes::Audio => (true, false, false),
+        };
+        filter.show_audio = audio;
+        filter.show_midi = midi;
+        filter.show_video = video;
+        filter.show_monitors = true;
+        filter
+    }
+
+    /// Options applied to links created without asking
+    pub fn default_link_options(&self) -> LinkOptions {
+        LinkOptions {
+            passive: self.link_passive,
+            linger: self.link_linger,
+        }
+    }
+
     /// Get the path to the settings file
-    fn settings_path() -> Option<PathBuf> {
-        let config_dir = dirs::config_dir()?;
-        let app_dir = config_dir.join(APP_ID);
-        Some(app_dir.join("settings.json"))
+    pub fn settings_path() -> Option<PathBuf> {
+        Some(config::config_dir()?.join("settings.json"))
     }
 
-    /// Load settings from disk
+    /// Load settings from GSettings if chosen, else from disk
     pub fn load() -> Self {
+        if gsettings::enabled() {
+            match gsettings::load() {
+                Ok(settings) => return settings,
+                Err(e) => log::warn!("Failed to load settings from GSettings: {}", e),
+            }
+        }
+
         let path = match Self::settings_path() {
             Some(p) => p,
             None => return Self::default(),
@@ -48,8 +506,24 @@ impl Settings {
         }
     }
 
-    /// Save settings to disk
+    /// Read settings from disk, failing instead of falling back to the
+    /// defaults so a half-written file is never taken for the real one
+    pub fn read() -> Result<Self, String> {
+        if gsettings::enabled() {
+            return gsettings::load();
+        }
+        let path = Self::settings_path().ok_or("Could not determine config directory")?;
+        let content =
+            fs::read_to_string(&path).map_err(|e| format!("Failed to read settings: {}", e))?;
+        serde_json::from_str(&content).map_err(|e| format!("Invalid settings: {}", e))
+    }
+
+    /// Save settings to GSettings if chosen, else to disk
     pub fn save(&self) -> Result<(), String> {
+        if gsettings::enabled() {
+            return gsettings::save(self);
+        }
+
         let path = Self::settings_path().ok_or("Could not determine config directory")?;
 
         // Ensure directory exists
@@ -1,6 +1,7 @@
 use ksni::TrayMethods;
 use std::sync::mpsc;
 use std::thread;
+use tokio::sync::mpsc as tokio_mpsc;
 
 /// Commands sent from the system tray to the main application
 #[derive(Debug, Clone)]
@@ -9,16 +10,81 @@ pub enum TrayCommand {
     Show,
     /// Quit the application
     Quit,
+    /// The icon was scrolled: +1 for the next preset, -1 for the previous
+    CyclePreset(i32),
+    /// The icon was middle-clicked
+    ToggleWindow,
+    /// Create or remove one of the recent connections
+    ToggleConnection(TrayConnectionKey),
+    /// The tray icon could not be shown, e.g. because no StatusNotifier
+    /// host is running
+    Unavailable(String),
+}
+
+/// Node and port names of a connection, identifying it in the menu while
+/// the list changes between the menu being shown and an item being chosen
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct TrayConnectionKey {
+    pub output_node: String,
+    pub output_port: String,
+    pub input_node: String,
+    pub input_port: String,
+}
+
+/// A recently created connection listed in the tray menu
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct TrayConnection {
+    pub label: String,
+    pub connected: bool,
+    pub key: TrayConnectionKey,
+}
+
+/// Changes from the application shown by the tray
+#[derive(Debug, Clone)]
+enum TrayUpdate {
+    ActivePreset(Option<String>),
+    RecentConnections(Vec<TrayConnection>),
 }
 
 /// Handle to communicate with the tray
 pub struct TrayHandle {
     _thread: thread::JoinHandle<()>,
+    update_tx: tokio_mpsc::UnboundedSender<TrayUpdate>,
+}
+
+impl TrayHandle {
+    /// A sender the window can use to keep the tray up to date
+    pub fn updater(&self) -> TrayUpdater {
+        TrayUpdater {
+            update_tx: self.update_tx.clone(),
+        }
+    }
+}
+
+/// Sends changes to the tray from the GTK thread
+#[derive(Debug, Clone)]
+pub struct TrayUpdater {
+    update_tx: tokio_mpsc::UnboundedSender<TrayUpdate>,
+}
+
+impl TrayUpdater {
+    /// Show the active preset in the tray's title and menu
+    pub fn set_active_preset(&self, name: Option<String>) {
+        let _ = self.update_tx.send(TrayUpdate::ActivePreset(name));
+    }
+
+    /// List recently created connections in the tray menu, newest first
+    pub fn set_recent_connections(&self, connections: Vec<TrayConnection>) {
+        let _ = self
+            .update_tx
+            .send(TrayUpdate::RecentConnections(connections));
+    }
 }
 
 struct PwAudioshareTray {
     command_tx: mpsc::Sender<TrayCommand>,
     active_preset: Option<String>,
+    recent_connections: Vec<TrayConnection>,
 }
 
 impl ksni::Tray for PwAudioshareTray {
@@ -31,6 +97,19 @@ impl ksni::Tray for PwAudioshareTray {
         "audio-card".into()
     }
 
+    fn secondary_activate(&mut self, _x: i32, _y: i32) {
+        let _ = self.command_tx.send(TrayCommand::ToggleWindow);
+    }
+
+    fn scroll(&mut self, delta: i32, _orientation: ksni::Orientation) {
+        // Hosts report scrolling down as a positive delta
+        if delta != 0 {
+            let _ = self
+                .command_tx
+                .send(TrayCommand::CyclePreset(delta.signum()));
+        }
+    }
+
     fn title(&self) -> String {
         match &self.active_preset {
             Some(name) => format!("PW Audioshare [{}]", name),
@@ -66,6 +145,37 @@ impl ksni::Tray for PwAudioshareTray {
             );
         }
 
+        if !self.recent_connections.is_empty() {
+            let submenu = self
+                .recent_connections
+                .iter()
+                .map(|connection| {
+                    let key = connection.key.clone();
+                    CheckmarkItem {
+                        // Menu labels treat "_" as a mnemonic marker
+                        label: connection.label.replace('_', "__"),
+                        checked: connection.connected,
+                        activate: Box::new(move |this: &mut Self| {
+                            let _ = this
+                                .command_tx
+                                .send(TrayCommand::ToggleConnection(key.clone()));
+                        }),
+                        ..Default::default()
+                    }
+                    .into()
+                })
+                .collect();
+            items.push(MenuItem::Separator);
+            items.push(
+                SubMenu {
+                    label: "Recent Connections".into(),
+                    submenu,
+                    ..Default::default()
+                }
+                .into(),
+            );
+        }
+
         items.push(MenuItem::Separator);
         items.push(
             StandardItem {
@@ -87,6 +197,7 @@ impl ksni::Tray for PwAudioshareTray {
 /// Returns a receiver for tray commands and a handle to keep the tray alive
 pub fn spawn_tray(active_preset: Option<String>) -> (mpsc::Receiver<TrayCommand>, TrayHandle) {
     let (command_tx, command_rx) = mpsc::channel();
+    let (update_tx, mut update_rx) = tokio_mpsc::unbounded_channel();
 
     let thread = thread::spawn(move || {
         // Create a new Tokio runtime for this thread
@@ -96,22 +207,47 @@ pub fn spawn_tray(active_preset: Option<String>) -> (mpsc::Receiver<TrayCommand>
             .expect("Failed to create Tokio runtime for tray");
 
         rt.block_on(async {
+            let status_tx = command_tx.clone();
             let tray = PwAudioshareTray {
                 command_tx,
                 active_preset,
+                recent_connections: Vec::new(),
             };
 
             match tray.spawn().await {
-                Ok(_handle) => {
+                Ok(handle) => {
+                    while let Some(update) = update_rx.recv().await {
+                        match update {
+                            TrayUpdate::ActivePreset(name) => {
+                                handle.update(|tray| tray.active_preset = name).await;
+                            }
+                            TrayUpdate::RecentConnections(connections) => {
+                                handle
+                                    .update(|tray| tray.recent_connections = connections)
+                                    .await;
+                            }
+                        }
+                    }
                     // Keep the tray alive forever
                     std::future::pending::<()>().await;
                 }
                 Err(e) => {
                     log::error!("Failed to spawn system tray: {}", e);
+                    // Sandboxed apps need permission to talk to the tray host
+                    let hint = crate::sandbox::permission_hint(
+                        "--talk-name=org.kde.StatusNotifierWatcher",
+                    );
+                    let _ = status_tx.send(TrayCommand::Unavailable(format!("{}.{}", e, hint)));
                 }
             }
         });
     });
 
-    (command_rx, TrayHandle { _thread: thread })
+    (
+        command_rx,
+        TrayHandle {
+            _thread: thread,
+            update_tx,
+        },
+    )
 }
@@ -1,3 +1,8 @@
+mod query;
+mod shortcuts;
+mod style;
 mod window;
 
+pub use shortcuts::register_accels;
+pub use style::load_css;
 pub use window::Window;
@@ -0,0 +1,207 @@
+use crate::model::PortObject;
+
+/// What a single query term is compared against
+#[derive(Debug, Clone, PartialEq)]
+enum Field {
+    /// Port label or node name, for plain words
+    Any,
+    Node,
+    Media,
+    Channel,
+    Direction,
+}
+
+/// One word of a query, such as `node:firefox` or `-monitor`
+#[derive(Debug, Clone)]
+struct Term {
+    field: Field,
+    /// Lowercased text to look for
+    value: String,
+    negated: bool,
+}
+
+/// A parsed search box query.
+///
+/// Words are separated by spaces and must all match. A word can be limited to
+/// one field with `node:`, `media:`, `channel:` or `dir:`, and a leading `-`
+/// excludes ports that match it. Double quotes keep spaces inside a value, as
+/// in `node:"Built-in Audio"`.
+#[derive(Debug, Clone, Default)]
+pub struct PortQuery {
+    terms: Vec<Term>,
+}
+
+impl PortQuery {
+    pub fn parse(text: &str) -> Self {
+        let terms = split_words(text)
+            .into_iter()
+            .filter_map(|word| parse_term(&word))
+            .collect();
+        Self { terms }
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.terms.is_empty()
+    }
+
+    /// Whether every port this query matches is also matched by `other`, as
+    /// when a word is typed further or another word is added
+    pub fn is_narrower_than(&self, other: &PortQuery) -> bool {
+        other
+            .terms
+            .iter()
+            .all(|wider| self.terms.iter().any(|term| term.implies(wider)))
+    }
+
+    /// Whether a port satisfies every term of the query
+    pub fn matches(&self, port: &PortObject) -> bool {
+        self.terms
+            .iter()
+            .all(|term| term.matches(port) != term.negated)
+    }
+}
+
+impl Term {
+    /// Whether a port matching this term always matches `other`
+    fn implies(&self, other: &Term) -> bool {
+        if self.field != other.field || self.negated != other.negated {
+            return false;
+        }
+        // A negated term excludes more ports the shorter its text is
+        let (longer, shorter) = if self.negated {
+            (other.value.as_str(), self.value.as_str())
+        } else {
+            (self.value.as_str(), other.value.as_str())
+        };
+        match self.field {
+            Field::Any | Field::Node => longer.contains(shorter),
+            Field::Media => longer.starts_with(shorter),
+            Field::Channel | Field::Direction => longer == shorter,
+        }
+    }
+
+    fn matches(&self, port: &PortObject) -> bool {
+        let value = self.value.as_str();
+        match self.field {
+            Field::Any => port.label_contains(value) || port.node_name_contains(value),
+            Field::Node => port.node_name_contains(value),
+            Field::Media => port.media_type().starts_with(value),
+            Field::Channel => port.channel_is(value),
+            Field::Direction => match value {
+                "out" | "output" => port.is_output(),
+                "in" | "input" => port.is_input(),
+                _ => false,
+            },
+        }
+    }
+}
+
+/// Split a query on whitespace, keeping quoted text together
+fn split_words(text: &str) -> Vec<String> {
+    let mut words = Vec::new();
+    let mut current = String::new();
+    let mut quoted = false;
+
+    for ch in text.chars() {
+        match ch {
+            '"' => quoted = !quoted,
+            c if c.is_whitespace() && !quoted => {
+                if !current.is_empty() {
+                    words.push(std::mem::take(&mut current));
+                }
+            }
+            c => current.push(c),
+        }
+    }
+    if !current.is_empty() {
+        words.push(current);
+    }
+    words
+}
+
+fn parse_term(word: &str) -> Option<Term> {
+    let (negated, word) = match word.strip_prefix('-') {
+        Some(rest) => (true, rest),
+        None => (false, word),
+    };
+
+    let (field, value) = match word.split_once(':') {
+        Some((key, value)) => match key.to_lowercase().as_str() {
+            "node" => (Field::Node, value),
+            "media" => (Field::Media, value),
+            "channel" => (Field::Channel, value),
+            "dir" => (Field::Direction, value),
+            // Not a field name, so search for the whole word
+            _ => (Field::Any, word),
+        },
+        None => (Field::Any, word),
+    };
+
+    // A lone "-" or "node:" still being typed filters nothing
+    if value.is_empty() {
+        return None;
+    }
+
+    Some(Term {
+        field,
+        value: value.to_lowercase(),
+        negated,
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn narrower(new: &str, old: &str) -> bool {
+        PortQuery::parse(new).is_narrower_than(&PortQuery::parse(old))
+    }
+
+    #[test]
+    fn typing_further_narrows() {
+        assert!(narrower("fire", "fir"));
+        assert!(narrower("firefox", ""));
+        assert!(narrower("node:firefox", "node:fire"));
+        assert!(narrower("media:audio", "media:au"));
+    }
+
+    #[test]
+    fn adding_a_word_narrows() {
+        assert!(narrower("firefox left", "firefox"));
+        assert!(!narrower("firefox", "firefox left"));
+    }
+
+    #[test]
+    fn deleting_text_widens() {
+        assert!(!narrower("fir", "fire"));
+        assert!(narrower("fire", "fire"));
+    }
+
+    #[test]
+    fn exclusions_narrow_as_they_get_shorter() {
+        assert!(narrower("-mon", "-monitor"));
+        assert!(!narrower("-monitor", "-mon"));
+    }
+
+    #[test]
+    fn different_fields_are_unrelated() {
+        assert!(!narrower("node:fire", "fire"));
+        assert!(!narrower("-fire", "fire"));
+        assert!(!narrower("channel:FL", "channel:F"));
+        assert!(narrower("dir:in", "dir:in"));
+    }
+
+    #[test]
+    fn quotes_keep_spaces_in_a_value() {
+        assert_eq!(
+            split_words(r#"node:"Built-in Audio" left"#),
+            vec!["node:Built-in Audio", "left"]
+        );
+    }
+
+    #[test]
+    fn unfinished_terms_are_ignored() {
+        assert!(PortQuery::parse("- node:").is_empty());
+        assert!(narrower("firefox node:", "firefox"));
+    }
+}
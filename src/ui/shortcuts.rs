@@ -0,0 +1,301 @@
+use gtk::gio;
+use gtk::prelude::*;
+
+/// A keyboard shortcut shown in the shortcuts window
+pub struct Shortcut {
+    /// Accelerators in `gtk::accelerator_parse` syntax, separated by spaces
+    pub accels: &'static str,
+    pub title: &'static str,
+    /// Action the accelerators are bound to. `None` for keys handled by the
+    /// lists themselves.
+    pub action: Option<&'static str>,
+}
+
+/// Shortcuts shown together under a heading
+pub struct ShortcutGroup {
+    pub title: &'static str,
+    pub shortcuts: &'static [Shortcut],
+}
+
+/// Every keyboard shortcut of the application. Action accelerators are
+/// registered from this table, so the shortcuts window always matches them.
+pub const SHORTCUT_GROUPS: &[ShortcutGroup] = &[
+    ShortcutGroup {
+        title: "General",
+        shortcuts: &[
+            Shortcut {
+                accels: "<Ctrl>question",
+                title: "Keyboard shortcuts",
+                action: Some("win.show-help-overlay"),
+            },
+            Shortcut {
+                accels: "<Ctrl>m",
+                title: "Open the connection matrix",
+                action: Some("win.connection-matrix"),
+            },
+            Shortcut {
+                accels: "<Ctrl><Alt>m",
+                title: "Open the MIDI ports",
+                action: Some("win.midi-ports"),
+            },
+            Shortcut {
+                accels: "<Ctrl><Shift>m",
+                title: "Open the compact window",
+                action: Some("win.compact-mode"),
+            },
+            Shortcut {
+                accels: "<Ctrl><Shift>t",
+                title: "Keep the window on top",
+                action: Some("win.keep-above"),
+            },
+            Shortcut {
+                accels: "<Ctrl>r",
+                title: "Reconnect the last removed connection",
+                action: Some("win.reconnect-last"),
+            },
+            Shortcut {
+                accels: "F9",
+                title: "Show or hide the activity history",
+                action: Some("win.activity-history"),
+            },
+            Shortcut {
+                accels: "<Ctrl>i",
+                title: "Show or hide the properties of the selected port or connection",
+                action: Some("win.inspector"),
+            },
+            Shortcut {
+                accels: "<Ctrl>q",
+                title: "Quit",
+                action: Some("app.quit"),
+            },
+        ],
+    },
+    ShortcutGroup {
+        title: "Port Lists",
+        shortcuts: &[
+            Shortcut {
+                accels: "Up Down",
+                title: "Move between ports",
+                action: None,
+            },
+            Shortcut {
+                accels: "space",
+                title: "Toggle selection",
+                action: None,
+            },
+            Shortcut {
+                accels: "<Ctrl>a",
+                title: "Select all",
+                action: None,
+            },
+            Shortcut {
+                accels: "Left Right",
+                title: "Move between the output and input lists",
+                action: None,
+            },
+            Shortcut {
+                accels: "<Ctrl>Return",
+                title: "Connect selected ports",
+                action: Some("win.connect-selected"),
+            },
+            Shortcut {
+                accels: "<Ctrl><Shift>Return",
+                title: "Connect selected ports with options",
+                action: Some("win.connect-advanced"),
+            },
+            Shortcut {
+                accels: "Return",
+                title: "Arm the port, or connect it to the armed port",
+                action: None,
+            },
+            Shortcut {
+                accels: "Escape",
+                title: "Cancel the armed port",
+                action: None,
+            },
+            Shortcut {
+                accels: "<Shift>Delete",
+                title: "Remove every connection of the selected ports",
+                action: Some("win.disconnect-ports"),
+            },
+            Shortcut {
+                accels: "<Ctrl><Shift>Delete",
+                title: "Remove every connection of the selected ports' applications",
+                action: Some("win.disconnect-nodes"),
+            },
+            Shortcut {
+                accels: "<Ctrl>j",
+                title: "Show the selected port's connections",
+                action: None,
+            },
+            Shortcut {
+                accels: "<Ctrl>c",
+                title: "Copy the selected port names",
+                action: None,
+            },
+            Shortcut {
+                accels: "<Ctrl><Shift>c",
+                title: "Copy pw-link commands connecting the selected ports",
+                action: None,
+            },
+            Shortcut {
+                accels: "<Ctrl>Down F6",
+                title: "Move to the connections list",
+                action: None,
+            },
+        ],
+    },
+    ShortcutGroup {
+        title: "Connections List",
+        shortcuts: &[
+            Shortcut {
+                accels: "Up Down",
+                title: "Move between connections",
+                action: None,
+            },
+            Shortcut {
+                accels: "Delete BackSpace",
+                title: "Delete the selected connection",
+                action: None,
+            },
+            Shortcut {
+                accels: "<Ctrl>Left",
+                title: "Jump to the connection's output port",
+                action: None,
+            },
+            Shortcut {
+                accels: "<Ctrl>Right",
+                title: "Jump to the connection's input port",
+                action: None,
+            },
+            Shortcut {
+                accels: "<Ctrl>c",
+                title: "Copy the connection's name",
+                action: None,
+            },
+            Shortcut {
+                accels: "<Ctrl><Shift>c",
+                title: "Copy a pw-link command for the connection",
+                action: None,
+            },
+            Shortcut {
+                accels: "<Ctrl>p",
+                title: "Pin or unpin the connection in the compact window",
+                action: None,
+            },
+            Shortcut {
+                accels: "Escape",
+                title: "Show all connections again",
+                action: None,
+            },
+            Shortcut {
+                accels: "<Ctrl>Up F6",
+                title: "Return to the previous port list",
+                action: None,
+            },
+        ],
+    },
+    ShortcutGroup {
+        title: "Layout",
+        shortcuts: &[
+            Shortcut {
+                accels: "<Ctrl><Alt>s",
+                title: "Swap the output and input port lists",
+                action: Some("win.swap-port-panes"),
+            },
+            Shortcut {
+                accels: "<Ctrl><Alt>v",
+                title: "Show the port lists above each other or side by side",
+                action: Some("win.stack-port-panes"),
+            },
+            Shortcut {
+                accels: "<Ctrl>bracketright",
+                title: "Give the first port list more space",
+                action: Some("win.resize-port-panes(10)"),
+            },
+            Shortcut {
+                accels: "<Ctrl>bracketleft",
+                title: "Give the first port list less space",
+                action: Some("win.resize-port-panes(-10)"),
+            },
+            Shortcut {
+                accels: "F8",
+                title: "Focus the handle between the port lists, then move it with arrow keys",
+                action: None,
+            },
+        ],
+    },
+];
+
+/// Keys that can be given to presets
+pub const PRESET_ACCELS: &[&str] = &[
+    "<Alt>1", "<Alt>2", "<Alt>3", "<Alt>4", "<Alt>5", "<Alt>6", "<Alt>7", "<Alt>8", "<Alt>9",
+    "<Alt>0",
+];
+
+/// How an accelerator is written for people, e.g. "Alt+1"
+pub fn accel_label(accel: &str) -> Option<String> {
+    let (key, modifiers) = gtk::accelerator_parse(accel)?;
+    Some(gtk::accelerator_get_label(key, modifiers).to_string())
+}
+
+/// Bind each preset's key to activating it, replacing the keys bound before.
+/// `accels` pairs a key with a preset name.
+pub fn register_preset_accels(app: &impl IsA<gtk::Application>, accels: &[(String, String)]) {
+    for action in app.list_action_descriptions() {
+        if action.starts_with("app.activate-preset(") {
+            app.set_accels_for_action(&action, &[]);
+        }
+    }
+    for (accel, name) in accels {
+        let action =
+            gio::Action::print_detailed_name("app.activate-preset", Some(&name.to_variant()));
+        app.set_accels_for_action(&action, &[accel.as_str()]);
+    }
+}
+
+/// Bind the accelerators of every shortcut that has an action
+pub fn register_accels(app: &impl IsA<gtk::Application>) {
+    for shortcut in SHORTCUT_GROUPS.iter().flat_map(|group| group.shortcuts) {
+        if let Some(action) = shortcut.action {
+            let accels: Vec<&str> = shortcut.accels.split(' ').collect();
+            app.set_accels_for_action(action, &accels);
+        }
+    }
+}
+
+/// Build the shortcuts window from `SHORTCUT_GROUPS`, with the keys given
+/// to presets as pairs of key and preset name
+pub fn build_shortcuts_window(preset_accels: &[(String, String)]) -> gtk::ShortcutsWindow {
+    let section = gtk::ShortcutsSection::builder()
+        .section_name("shortcuts")
+        .build();
+
+    for group in SHORTCUT_GROUPS {
+        let shortcuts_group = gtk::ShortcutsGroup::builder().title(group.title).build();
+        for shortcut in group.shortcuts {
+            let item = gtk::ShortcutsShortcut::builder()
+                .accelerator(shortcut.accels)
+                .title(shortcut.title)
+                .build();
+            shortcuts_group.add_shortcut(&item);
+        }
+        section.add_group(&shortcuts_group);
+    }
+
+    if !preset_accels.is_empty() {
+        let shortcuts_group = gtk::ShortcutsGroup::builder().title("Presets").build();
+        for (accel, name) in preset_accels {
+            let item = gtk::ShortcutsShortcut::builder()
+                .accelerator(accel.as_str())
+                .title(format!("Activate {}", name))
+                .build();
+            shortcuts_group.add_shortcut(&item);
+        }
+        section.add_group(&shortcuts_group);
+    }
+
+    let window = gtk::ShortcutsWindow::builder().modal(true).build();
+    window.add_section(&section);
+    window
+}
@@ -0,0 +1,84 @@
+use gtk::gdk;
+use gtk::prelude::*;
+
+/// Styles of the application. Colours are libadwaita's named colours, which
+/// high-contrast and dark styles adjust, and every state also has its own
+/// icon so it never rests on colour alone.
+const CSS: &str = "
+.link-active {
+    color: @success_color;
+}
+.link-paused {
+    color: @warning_color;
+}
+.link-error {
+    color: @error_color;
+}
+
+/* Larger list text for low vision, apart from the system scale */
+window.list-text-large listview > row {
+    font-size: 125%;
+    padding-top: 4px;
+    padding-bottom: 4px;
+}
+window.list-text-largest listview > row {
+    font-size: 160%;
+    padding-top: 8px;
+    padding-bottom: 8px;
+}
+
+/* Media type marks, shown when the window has .media-colors */
+window.media-colors .media-audio {
+    box-shadow: inset 4px 0 @blue_3;
+    padding-left: 8px;
+}
+window.media-colors .media-midi {
+    box-shadow: inset 4px 0 @purple_3;
+    padding-left: 8px;
+}
+window.media-colors .media-video {
+    box-shadow: inset 4px 0 @orange_3;
+    padding-left: 8px;
+}
+
+/* MIDI activity lights, dim until events pass through the port */
+.midi-activity {
+    opacity: 0.2;
+}
+.midi-activity.lit {
+    opacity: 1;
+    color: @success_color;
+}
+";
+
+const MEDIA_CLASSES: &[(&str, &str)] = &[
+    ("audio", "media-audio"),
+    ("midi", "media-midi"),
+    ("video", "media-video"),
+];
+
+/// Give a row's widget the class of its media type, e.g. `media-audio`, in
+/// place of the class of the item it showed before
+pub fn set_media_class(widget: &impl IsA<gtk::Widget>, media_type: &str) {
+    for (media, class) in MEDIA_CLASSES {
+        if *media == media_type {
+            widget.add_css_class(class);
+        } else {
+            widget.remove_css_class(class);
+        }
+    }
+}
+
+/// Load the application's styles for the default display
+pub fn load_css() {
+    let Some(display) = gdk::Display::default() else {
+        return;
+    };
+    let provider = gtk::CssProvider::new();
+    provider.load_from_string(CSS);
+    gtk::style_context_add_provider_for_display(
+        &display,
+        &provider,
+        gtk::STYLE_PROVIDER_PRIORITY_APPLICATION,
+    );
+}
@@ -1,5 +1,6 @@
-use std::cell::RefCell;
-use std::collections::HashSet;
+use std::cell::{Cell, RefCell};
+use std::collections::{HashMap, HashSet, VecDeque};
+use std::time::{Duration, Instant};
 
 use adw::prelude::*;
 use adw::subclass::prelude::*;
@@ -8,15 +9,127 @@ use gtk::gdk::Key;
 use gtk::glib::Propagation;
 use gtk::{gio, glib};
 
+use crate::control_socket::{ControlClient, ControlRequest, ControlServer};
+use crate::gsettings;
 use crate::model::{LinkObject, PortObject};
-use crate::pipewire::{PortDirection, PwEvent, PwState, UiCommand};
+use crate::osc::OscServer;
+use crate::pipewire::clock::ClockSettings;
+use crate::pipewire::health::HealthReport;
+use crate::pipewire::modules::LoadedModule;
+use crate::pipewire::recording::RecordedEvent;
+use crate::pipewire::state::{LinkOrigin, PwClient};
+use crate::pipewire::{
+    LinkOptions, PipeWireThread, PortDirection, PwEvent, PwState, TestSignal, UiCommand,
+};
 use crate::presets::{Preset, PresetConnection, PresetStore};
-use crate::settings::Settings;
+use crate::session_snapshot::SessionSnapshot;
+#[cfg(feature = "scripting")]
+use crate::scripting::ScriptHost;
+use crate::settings::{
+    Appearance, CloseAction, ListTextSize, PortFilter, PortSort, Settings, StartupPortTypes,
+    Verbosity,
+};
+use crate::tray::{TrayConnection, TrayUpdater};
+use crate::ui::query::PortQuery;
+use crate::ui::{shortcuts, style};
+
+mod activity;
+mod appearance;
+mod background_service;
+mod bulk_disconnect;
+mod bulk_progress;
+mod clients;
+mod clipboard;
+mod clock;
+mod close;
+mod command_queue;
+mod compact;
+mod config_transfer;
+mod config_watch;
+mod connect_mode;
+mod connection_volume;
+mod control;
+mod device_profiles;
+mod device_routes;
+mod duplicate_links;
+mod duplicate_output;
+mod event_log;
+mod filter_counts;
+mod filters;
+mod focus;
+mod graph_load;
+mod health;
+mod hotplug;
+mod inspector;
+mod keep_above;
+mod link_origin;
+mod link_state;
+mod list_summary;
+mod list_text_size;
+mod logs;
+mod matrix;
+mod midi_view;
+mod motion;
+mod network;
+mod offline;
+mod osc;
+mod pane_layout;
+mod pipewire_remote;
+mod plug_presets;
+mod port_batch;
+mod port_connections;
+mod port_filter;
+mod preset_accels;
+mod preset_edit;
+mod preset_export;
+mod preset_stats;
+mod preset_switch;
+mod preset_trash;
+mod recently_removed;
+mod saved_filters;
+#[cfg(feature = "scripting")]
+mod scripting;
+mod sections;
+mod servers;
+mod session_manager;
+mod session_restore;
+mod settings_storage;
+mod setup_assistant;
+mod sorting;
+mod streaming;
+mod suspend_inhibit;
+mod toasts;
+mod tray_controls;
+mod tunnels;
+mod type_ahead;
+mod video_formats;
+mod virtual_devices;
+
+use activity::ActivityEntry;
+use bulk_progress::BulkOperation;
+use compact::CompactView;
+use filters::InsertedFilter;
+use graph_load::GraphLoadView;
+use matrix::ConnectionMatrix;
+use midi_view::MidiView;
+use network::PendingRoute;
+use recently_removed::RememberedLink;
+use saved_filters::FilterWidgets;
+use servers::PRESETS_MAIN_SERVER_ONLY;
+use virtual_devices::HostedModule;
+
+/// How long a requested link may take to appear before it is no longer pending
+const LINK_REQUEST_TIMEOUT: Duration = Duration::from_secs(5);
+
+/// How long the search box waits after the last key before filtering, so a
+/// word typed quickly filters the lists once
+const SEARCH_DELAY_MS: u32 = 150;
 
 mod imp {
     use super::*;
 
-    #[derive(gtk::CompositeTemplate)]
+    #[derive(gtk::CompositeTemplate, glib::Properties)]
+    #[properties(wrapper_type = super::Window)]
     #[template(string = r#"
         <interface>
             <template class="PwAudioshareWindow" parent="AdwApplicationWindow">
@@ -24,25 +137,55 @@ mod imp {
                 <property name="default-width">900</property>
                 <property name="default-height">700</property>
                 <child>
-                    <object class="GtkBox" id="main_box">
-                        <property name="orientation">vertical</property>
-                        <child>
-                            <object class="AdwHeaderBar">
-                                <property name="title-widget">
-                                    <object class="AdwWindowTitle">
-                                        <property name="title">PW Audioshare</property>
-                                        <property name="subtitle">PipeWire Patchbay</property>
+                    <object class="AdwToastOverlay" id="toast_overlay">
+                        <property name="child">
+                            <object class="GtkBox" id="main_box">
+                                <property name="orientation">vertical</property>
+                                <child>
+                                    <object class="AdwHeaderBar" id="header_bar">
+                                        <property name="title-widget">
+                                            <object class="AdwWindowTitle">
+                                                <property name="title">PW Audioshare</property>
+                                                <property name="subtitle">PipeWire Patchbay</property>
+                                            </object>
+                                        </property>
+                                        <child type="start">
+                                            <object class="GtkToggleButton">
+                                                <property name="icon-name">view-pin-symbolic</property>
+                                                <property name="tooltip-text">Keep on Top</property>
+                                                <property name="action-name">win.keep-above</property>
+                                            </object>
+                                        </child>
+                                        <child type="end">
+                                            <object class="GtkMenuButton" id="tools_menu_button">
+                                                <property name="icon-name">applications-utilities-symbolic</property>
+                                                <property name="tooltip-text">Tools</property>
+                                                <property name="menu-model">tools_menu</property>
+                                            </object>
+                                        </child>
+                                        <child type="end">
+                                            <object class="GtkMenuButton" id="preset_menu_button">
+                                                <property name="icon-name">document-save-symbolic</property>
+                                                <property name="tooltip-text">Presets</property>
+                                                <property name="menu-model">preset_menu</property>
+                                            </object>
+                                        </child>
+                                        <child type="end">
+                                            <object class="GtkSpinner" id="bulk_spinner">
+                                                <property name="visible">false</property>
+                                                <property name="tooltip-text">Making connections</property>
+                                            </object>
+                                        </child>
                                     </object>
-                                </property>
-                                <child type="end">
-                                    <object class="GtkMenuButton" id="preset_menu_button">
-                                        <property name="icon-name">document-save-symbolic</property>
-                                        <property name="tooltip-text">Presets</property>
-                                        <property name="menu-model">preset_menu</property>
+                                </child>
+                                <child>
+                                    <object class="AdwBanner" id="tray_banner">
+                                        <property name="title">The system tray is unavailable, so closing the window quits PW Audioshare</property>
+                                        <property name="button-label">Dismiss</property>
                                     </object>
                                 </child>
                             </object>
-                        </child>
+                        </property>
                     </object>
                 </child>
             </template>
@@ -56,42 +199,466 @@ mod imp {
                         <attribute name="label">Manage Presets...</attribute>
                         <attribute name="action">win.load-preset</attribute>
                     </item>
+                    <item>
+                        <attribute name="label">Deleted Presets...</attribute>
+                        <attribute name="action">win.preset-trash</attribute>
+                    </item>
+                    <item>
+                        <attribute name="label">Export Active Preset for WirePlumber...</attribute>
+                        <attribute name="action">win.export-preset-wireplumber</attribute>
+                    </item>
+                    <item>
+                        <attribute name="label">Preset on Plug...</attribute>
+                        <attribute name="action">win.plug-presets</attribute>
+                    </item>
+                </section>
+                <section>
+                    <item>
+                        <attribute name="label">Export Settings and Presets...</attribute>
+                        <attribute name="action">win.export-config</attribute>
+                    </item>
+                    <item>
+                        <attribute name="label">Import Settings and Presets...</attribute>
+                        <attribute name="action">win.import-config</attribute>
+                    </item>
+                    <item>
+                        <attribute name="label">Store Settings in GSettings</attribute>
+                        <attribute name="action">win.settings-gsettings</attribute>
+                    </item>
                 </section>
                 <section>
                     <item>
                         <attribute name="label">Deactivate Auto-connect</attribute>
                         <attribute name="action">win.deactivate-preset</attribute>
                     </item>
+                    <item>
+                        <attribute name="label">Replace Connections When Switching Presets</attribute>
+                        <attribute name="action">win.replace-on-preset-switch</attribute>
+                    </item>
                 </section>
                 <section>
+                    <item>
+                        <attribute name="label">Show Tray Icon</attribute>
+                        <attribute name="action">win.show-tray</attribute>
+                    </item>
                     <item>
                         <attribute name="label">Start Minimized to Tray</attribute>
                         <attribute name="action">win.start-minimized</attribute>
                     </item>
+                    <item>
+                        <attribute name="label">Scroll on Tray Icon Switches Presets</attribute>
+                        <attribute name="action">win.tray-scroll-presets</attribute>
+                    </item>
+                    <item>
+                        <attribute name="label">Middle-Click on Tray Icon Shows Window</attribute>
+                        <attribute name="action">win.tray-middle-click</attribute>
+                    </item>
+                </section>
+                <section>
+                    <item>
+                        <attribute name="label">Keep Presets Connected After Login</attribute>
+                        <attribute name="action">win.background-service</attribute>
+                    </item>
+                </section>
+                <section>
+                    <attribute name="label">When Closing the Window</attribute>
+                    <item>
+                        <attribute name="label">Ask</attribute>
+                        <attribute name="action">win.close-action</attribute>
+                        <attribute name="target">ask</attribute>
+                    </item>
+                    <item>
+                        <attribute name="label">Minimize to Tray</attribute>
+                        <attribute name="action">win.close-action</attribute>
+                        <attribute name="target">tray</attribute>
+                    </item>
+                    <item>
+                        <attribute name="label">Quit</attribute>
+                        <attribute name="action">win.close-action</attribute>
+                        <attribute name="target">quit</attribute>
+                    </item>
+                </section>
+                <section>
+                    <attribute name="label">New Connections</attribute>
+                    <item>
+                        <attribute name="label">Temporary Mode</attribute>
+                        <attribute name="action">win.temporary-mode</attribute>
+                    </item>
+                    <item>
+                        <attribute name="label">Connect with Options...</attribute>
+                        <attribute name="action">win.connect-advanced</attribute>
+                    </item>
+                    <item>
+                        <attribute name="label">Passive by Default</attribute>
+                        <attribute name="action">win.link-passive</attribute>
+                    </item>
+                    <item>
+                        <attribute name="label">Keep After Exit by Default</attribute>
+                        <attribute name="action">win.link-linger</attribute>
+                    </item>
+                </section>
+            </menu>
+            <menu id="tools_menu">
+                <section>
+                    <item>
+                        <attribute name="label">Connection Matrix...</attribute>
+                        <attribute name="action">win.connection-matrix</attribute>
+                    </item>
+                    <item>
+                        <attribute name="label">MIDI Ports</attribute>
+                        <attribute name="action">win.midi-ports</attribute>
+                    </item>
+                    <item>
+                        <attribute name="label">Compact Mode</attribute>
+                        <attribute name="action">win.compact-mode</attribute>
+                    </item>
+                </section>
+                <section>
+                    <item>
+                        <attribute name="label">Reconnect Last Removed</attribute>
+                        <attribute name="action">win.reconnect-last</attribute>
+                    </item>
+                    <submenu id="recently_removed_menu">
+                        <attribute name="label">Recently Removed</attribute>
+                    </submenu>
+                    <item>
+                        <attribute name="label">Restore Previous Session Routing</attribute>
+                        <attribute name="action">win.restore-session</attribute>
+                    </item>
+                </section>
+                <section>
+                    <item>
+                        <attribute name="label">Copy Connections as JSON</attribute>
+                        <attribute name="action">win.copy-connections</attribute>
+                    </item>
+                    <item>
+                        <attribute name="label">Paste Connections</attribute>
+                        <attribute name="action">win.paste-connections</attribute>
+                    </item>
+                </section>
+                <section>
+                    <item>
+                        <attribute name="label">Streaming Setup...</attribute>
+                        <attribute name="action">win.streaming-setup</attribute>
+                    </item>
+                    <item>
+                        <attribute name="label">Also Send To...</attribute>
+                        <attribute name="action">win.also-send-to</attribute>
+                    </item>
+                    <item>
+                        <attribute name="label">Stop Sending To...</attribute>
+                        <attribute name="action">win.stop-sending-to</attribute>
+                    </item>
+                </section>
+                <section>
+                    <item>
+                        <attribute name="label">Disconnect Selected Ports</attribute>
+                        <attribute name="action">win.disconnect-ports</attribute>
+                    </item>
+                    <item>
+                        <attribute name="label">Disconnect Applications of Selected Ports</attribute>
+                        <attribute name="action">win.disconnect-nodes</attribute>
+                    </item>
+                    <item>
+                        <attribute name="label">Remove Duplicate Connections</attribute>
+                        <attribute name="action">win.remove-duplicate-links</attribute>
+                    </item>
+                    <item>
+                        <attribute name="label">Ask Before Removing Several Connections</attribute>
+                        <attribute name="action">win.confirm-bulk-disconnect</attribute>
+                    </item>
+                </section>
+                <section>
+                    <item>
+                        <attribute name="label">New Combined Output...</attribute>
+                        <attribute name="action">win.new-combine</attribute>
+                    </item>
+                    <item>
+                        <attribute name="label">New Echo-Cancelled Microphone...</attribute>
+                        <attribute name="action">win.new-echo-cancel</attribute>
+                    </item>
+                    <item>
+                        <attribute name="label">Receive Network Audio...</attribute>
+                        <attribute name="action">win.receive-network-audio</attribute>
+                    </item>
+                    <item>
+                        <attribute name="label">New Network Tunnel...</attribute>
+                        <attribute name="action">win.new-tunnel</attribute>
+                    </item>
+                    <item>
+                        <attribute name="label">VBAN Streaming...</attribute>
+                        <attribute name="action">win.vban</attribute>
+                    </item>
+                    <item>
+                        <attribute name="label">AirPlay Speakers...</attribute>
+                        <attribute name="action">win.airplay</attribute>
+                    </item>
+                    <item>
+                        <attribute name="label">Manage Virtual Devices...</attribute>
+                        <attribute name="action">win.manage-virtual-devices</attribute>
+                    </item>
+                </section>
+                <section>
+                    <item>
+                        <attribute name="label">Insert Filter on Selected Connection...</attribute>
+                        <attribute name="action">win.insert-filter</attribute>
+                    </item>
+                    <item>
+                        <attribute name="label">Volume of Selected Connection...</attribute>
+                        <attribute name="action">win.connection-volume</attribute>
+                    </item>
+                    <item>
+                        <attribute name="label">Manage Filters...</attribute>
+                        <attribute name="action">win.manage-filters</attribute>
+                    </item>
+                </section>
+                <section>
+                    <attribute name="label">Test Signal</attribute>
+                    <item>
+                        <attribute name="label">Off</attribute>
+                        <attribute name="action">win.test-tone</attribute>
+                        <attribute name="target">off</attribute>
+                    </item>
+                    <item>
+                        <attribute name="label">440 Hz Sine Tone</attribute>
+                        <attribute name="action">win.test-tone</attribute>
+                        <attribute name="target">sine</attribute>
+                    </item>
+                    <item>
+                        <attribute name="label">Pink Noise</attribute>
+                        <attribute name="action">win.test-tone</attribute>
+                        <attribute name="target">pink</attribute>
+                    </item>
+                </section>
+                <section>
+                    <attribute name="label">Remote Control</attribute>
+                    <item>
+                        <attribute name="label">Control Socket</attribute>
+                        <attribute name="action">win.control-socket</attribute>
+                    </item>
+                    <item>
+                        <attribute name="label">OSC Server</attribute>
+                        <attribute name="action">win.osc-server</attribute>
+                    </item>
+                    <item>
+                        <attribute name="label">OSC Port...</attribute>
+                        <attribute name="action">win.osc-port</attribute>
+                    </item>
+                </section>
+                <section>
+                    <item>
+                        <attribute name="label">Keyboard Shortcuts</attribute>
+                        <attribute name="action">win.show-help-overlay</attribute>
+                    </item>
+                    <item>
+                        <attribute name="label">Setup Assistant...</attribute>
+                        <attribute name="action">win.setup-assistant</attribute>
+                    </item>
+                    <item>
+                        <attribute name="label">Activity History</attribute>
+                        <attribute name="action">win.activity-history</attribute>
+                    </item>
+                    <item>
+                        <attribute name="label">Properties</attribute>
+                        <attribute name="action">win.inspector</attribute>
+                    </item>
+                    <item>
+                        <attribute name="label">Device Profile...</attribute>
+                        <attribute name="action">win.device-profile</attribute>
+                    </item>
+                    <item>
+                        <attribute name="label">Device Route...</attribute>
+                        <attribute name="action">win.device-route</attribute>
+                    </item>
+                    <item>
+                        <attribute name="label">Graph Load...</attribute>
+                        <attribute name="action">win.graph-load</attribute>
+                    </item>
+                    <item>
+                        <attribute name="label">Sample Rate and Quantum...</attribute>
+                        <attribute name="action">win.clock-settings</attribute>
+                    </item>
+                    <item>
+                        <attribute name="label">PipeWire Server...</attribute>
+                        <attribute name="action">win.pipewire-server</attribute>
+                    </item>
+                    <item>
+                        <attribute name="label">Troubleshooting...</attribute>
+                        <attribute name="action">win.troubleshooting</attribute>
+                    </item>
+                    <item>
+                        <attribute name="label">View Logs...</attribute>
+                        <attribute name="action">win.view-logs</attribute>
+                    </item>
+                    <item>
+                        <attribute name="label">Save Logs to File</attribute>
+                        <attribute name="action">win.log-to-file</attribute>
+                    </item>
+                    <item>
+                        <attribute name="label">PipeWire Events...</attribute>
+                        <attribute name="action">win.event-debugger</attribute>
+                    </item>
+                    <item>
+                        <attribute name="label">Record PipeWire Events</attribute>
+                        <attribute name="action">win.record-events</attribute>
+                    </item>
+                </section>
+                <section>
+                    <submenu>
+                        <attribute name="label">Preferences</attribute>
+                        <section>
+                            <attribute name="label">Announcements</attribute>
+                            <item>
+                                <attribute name="label">Silent</attribute>
+                                <attribute name="action">win.announcement-verbosity</attribute>
+                                <attribute name="target">silent</attribute>
+                            </item>
+                            <item>
+                                <attribute name="label">Important Only</attribute>
+                                <attribute name="action">win.announcement-verbosity</attribute>
+                                <attribute name="target">important</attribute>
+                            </item>
+                            <item>
+                                <attribute name="label">Verbose</attribute>
+                                <attribute name="action">win.announcement-verbosity</attribute>
+                                <attribute name="target">verbose</attribute>
+                            </item>
+                        </section>
+                        <section>
+                            <attribute name="label">List Text Size</attribute>
+                            <item>
+                                <attribute name="label">Normal</attribute>
+                                <attribute name="action">win.list-text-size</attribute>
+                                <attribute name="target">normal</attribute>
+                            </item>
+                            <item>
+                                <attribute name="label">Large</attribute>
+                                <attribute name="action">win.list-text-size</attribute>
+                                <attribute name="target">large</attribute>
+                            </item>
+                            <item>
+                                <attribute name="label">Largest</attribute>
+                                <attribute name="action">win.list-text-size</attribute>
+                                <attribute name="target">largest</attribute>
+                            </item>
+                        </section>
+                        <section>
+                            <item>
+                                <attribute name="label">Reduce Motion</attribute>
+                                <attribute name="action">win.reduce-motion</attribute>
+                            </item>
+                        </section>
+                        <section>
+                            <attribute name="label">Appearance</attribute>
+                            <item>
+                                <attribute name="label">Follow System</attribute>
+                                <attribute name="action">win.appearance</attribute>
+                                <attribute name="target">system</attribute>
+                            </item>
+                            <item>
+                                <attribute name="label">Light</attribute>
+                                <attribute name="action">win.appearance</attribute>
+                                <attribute name="target">light</attribute>
+                            </item>
+                            <item>
+                                <attribute name="label">Dark</attribute>
+                                <attribute name="action">win.appearance</attribute>
+                                <attribute name="target">dark</attribute>
+                            </item>
+                            <item>
+                                <attribute name="label">Colour Rows by Media Type</attribute>
+                                <attribute name="action">win.media-colors</attribute>
+                            </item>
+                        </section>
+                        <section>
+                            <attribute name="label">Port Lists</attribute>
+                            <item>
+                                <attribute name="label">Inputs First</attribute>
+                                <attribute name="action">win.swap-port-panes</attribute>
+                            </item>
+                            <item>
+                                <attribute name="label">Above Each Other</attribute>
+                                <attribute name="action">win.stack-port-panes</attribute>
+                            </item>
+                        </section>
+                        <section>
+                            <attribute name="label">Port Types at Startup</attribute>
+                            <item>
+                                <attribute name="label">As Last Used</attribute>
+                                <attribute name="action">win.startup-port-types</attribute>
+                                <attribute name="target">last</attribute>
+                            </item>
+                            <item>
+                                <attribute name="label">All Types</attribute>
+                                <attribute name="action">win.startup-port-types</attribute>
+                                <attribute name="target">all</attribute>
+                            </item>
+                            <item>
+                                <attribute name="label">Audio Only</attribute>
+                                <attribute name="action">win.startup-port-types</attribute>
+                                <attribute name="target">audio</attribute>
+                            </item>
+                        </section>
+                        <section>
+                            <item>
+                                <attribute name="label">Prevent Suspend While Sharing Audio</attribute>
+                                <attribute name="action">win.inhibit-suspend</attribute>
+                            </item>
+                        </section>
+                    </submenu>
+                </section>
+                <section>
+                    <item>
+                        <attribute name="label">Quit</attribute>
+                        <attribute name="action">app.quit</attribute>
+                    </item>
                 </section>
             </menu>
         </interface>
     "#)]
     pub struct Window {
+        #[template_child]
+        pub toast_overlay: TemplateChild<adw::ToastOverlay>,
         #[template_child]
         pub main_box: TemplateChild<gtk::Box>,
+        #[template_child]
+        pub header_bar: TemplateChild<adw::HeaderBar>,
+        #[template_child]
+        pub tray_banner: TemplateChild<adw::Banner>,
+        #[template_child]
+        pub bulk_spinner: TemplateChild<gtk::Spinner>,
+        #[template_child]
+        pub recently_removed_menu: TemplateChild<gio::Menu>,
 
         // Data models
         pub output_ports: gio::ListStore,
         pub input_ports: gio::ListStore,
+        // Ports waiting to be added to the lists in one batch
+        pub pending_ports: RefCell<Vec<PortObject>>,
+        pub port_flush_scheduled: Cell<bool>,
         pub links: gio::ListStore,
+        // The same links by id, to update one without scanning the list
+        pub link_objects: RefCell<HashMap<u32, LinkObject>>,
 
         // PipeWire state tracking
         pub pw_state: RefCell<PwState>,
+        pub clock_settings: RefCell<ClockSettings>,
 
         // Command sender for PipeWire thread
         pub command_tx: RefCell<Option<Sender<UiCommand>>>,
+        // Commands waiting for room in the channel, whether they are sent
+        // again soon, and whether the user was told they back up
+        pub queued_commands: RefCell<VecDeque<UiCommand>>,
+        pub command_retry_scheduled: Cell<bool>,
+        pub command_backlog_warned: Cell<bool>,
 
         // Filter state
         pub search_text: RefCell<String>,
         pub show_audio: RefCell<bool>,
         pub show_midi: RefCell<bool>,
         pub show_video: RefCell<bool>,
+        pub show_monitors: RefCell<bool>,
+        pub filter_widgets: RefCell<Option<FilterWidgets>>,
 
         // Widget references (MultiSelection for bulk connect)
         pub output_selection: RefCell<Option<gtk::MultiSelection>>,
@@ -100,15 +667,46 @@ mod imp {
         pub input_list_view: RefCell<Option<gtk::ListView>>,
         pub connections_list_view: RefCell<Option<gtk::ListView>>,
         pub connections_selection: RefCell<Option<gtk::SingleSelection>>,
+        pub connections_filter: RefCell<Option<gtk::CustomFilter>>,
+        pub connections_filter_bar: RefCell<Option<gtk::Box>>,
+        pub connections_filter_label: RefCell<Option<gtk::Label>>,
+        // Whose connections are listed, None for everyone's
+        pub connections_origin: Cell<Option<LinkOrigin>>,
+        pub connections_origin_filter: RefCell<Option<gtk::CustomFilter>>,
         pub status_label: RefCell<Option<gtk::Label>>,
 
+        // Port lists and connections, or the status page shown in their
+        // place while PipeWire can't be reached
+        pub graph_stack: RefCell<Option<gtk::Stack>>,
+        pub offline_page: RefCell<Option<adw::StatusPage>>,
+        pub offline_retry_button: RefCell<Option<gtk::Button>>,
+
         // Filter references
         pub output_filter: RefCell<Option<gtk::CustomFilter>>,
         pub input_filter: RefCell<Option<gtk::CustomFilter>>,
+        // Query parsed from the search text, and the filter the port lists
+        // were last filtered with
+        pub port_query: RefCell<PortQuery>,
+        pub applied_port_filter: RefCell<Option<PortFilter>>,
+        // When the port lists were last refiltered, to log how long it took
+        pub filter_started: Cell<Option<Instant>>,
+
+        // Sort model references
+        pub output_sort_model: RefCell<Option<gtk::SortListModel>>,
+        pub input_sort_model: RefCell<Option<gtk::SortListModel>>,
+
+        // Type-ahead find: text typed so far, the list it applies to and
+        // when the last key was typed
+        pub type_ahead_text: RefCell<String>,
+        pub type_ahead_list: Cell<bool>,
+        pub type_ahead_time: Cell<i64>,
 
         // Track which port list was last focused (true = output, false = input)
         pub last_port_list_was_output: RefCell<bool>,
 
+        // Port whose connections alone are shown in the connections panel
+        pub connections_port_filter: Cell<Option<u32>>,
+
         // Track pending delete position for selection preservation
         pub pending_delete_position: RefCell<Option<u32>>,
 
@@ -116,40 +714,289 @@ mod imp {
         pub preset_store: RefCell<PresetStore>,
 
         // Track in-flight link creation requests to prevent duplicates
-        // Key is (output_port_id, input_port_id)
-        pub pending_links: RefCell<HashSet<(u32, u32)>>,
+        // Key is (output_port_id, input_port_id), value is the request id
+        pub pending_links: RefCell<HashMap<(u32, u32), u64>>,
+
+        // Correlation id for the next link request
+        pub next_link_request_id: Cell<u64>,
+
+        // Delete requests waiting for their result, with the link to delete
+        pub delete_link_requests: RefCell<HashMap<u64, u32>>,
+
+        // Link requests made by the user whose outcome is shown as a toast,
+        // with the requested (output_port_id, input_port_id)
+        pub toast_link_requests: RefCell<HashMap<u64, (u32, u32)>>,
+
+        // Link requests made by auto-connecting, with the preset whose
+        // statistics they count towards
+        pub preset_link_requests: RefCell<HashMap<u64, String>>,
+
+        // Bulk link requests, such as loading a preset, whose result is
+        // announced once all have finished
+        pub bulk_operations: RefCell<Vec<BulkOperation>>,
 
         // Application settings
         pub settings: RefCell<Settings>,
+
+        // Modules (virtual devices) created by the application
+        pub loaded_modules: RefCell<Vec<LoadedModule>>,
+        pub hosted_modules: RefCell<Vec<HostedModule>>,
+
+        // Sources waiting to be routed into nodes that have not appeared yet
+        pub pending_routes: RefCell<Vec<PendingRoute>>,
+
+        // Effects inserted into connections
+        pub inserted_filters: RefCell<Vec<InsertedFilter>>,
+
+        // Connections the user removed, most recent first
+        pub recently_removed: RefCell<VecDeque<RememberedLink>>,
+
+        // Connections the user created, most recent first, listed in the tray
+        pub recently_created: RefCell<VecDeque<RememberedLink>>,
+
+        // Port waiting for the other end of a connection in connect mode
+        pub armed_port: RefCell<Option<PortObject>>,
+
+        // Connection matrix window, while it is open
+        pub connection_matrix: RefCell<Option<ConnectionMatrix>>,
+
+        // Compact window, while it is open
+        pub compact_view: RefCell<Option<CompactView>>,
+
+        // Whether the compact window is kept on top, also across reopening it
+        pub compact_above: Cell<bool>,
+
+        // Instance number for the next helper process, keeps node names unique
+        pub next_helper_instance: Cell<u32>,
+
+        // Session-only mode creating every new link without object.linger
+        pub temporary_mode: Cell<bool>,
+
+        // Whether the tray icon is shown, so the hidden window can be restored
+        pub tray_available: Cell<bool>,
+        pub tray_updater: RefCell<Option<TrayUpdater>>,
+        pub tray_connections: RefCell<Vec<TrayConnection>>,
+        pub last_tray_scroll: Cell<i64>,
+
+        // Started with --daemon: closing hides the window instead of quitting
+        pub daemon_mode: Cell<bool>,
+
+        // Socket of the PipeWire server shown when this is not the main
+        // window, and the connection to it, closed with its tab
+        #[property(get, construct_only)]
+        pub server: RefCell<Option<String>>,
+        pub server_backend: RefCell<Option<PipeWireThread>>,
+
+        // Tabs of the main window once another server is opened, and the
+        // hidden window behind each server's tab
+        pub server_tabs: RefCell<Option<adw::TabView>>,
+        pub server_pages: RefCell<Vec<(adw::TabPage, super::Window)>>,
+
+        // Control socket while enabled, clients subscribed to events and
+        // connect requests waiting for PipeWire, by link request id
+        pub control_server: RefCell<Option<ControlServer>>,
+        pub control_subscribers: RefCell<Vec<ControlClient>>,
+        pub control_link_requests: RefCell<HashMap<u64, ControlRequest>>,
+
+        // OSC server while enabled
+        pub osc_server: RefCell<Option<OscServer>>,
+
+        // Links we created recently and links we are removing, to notice the
+        // session manager undoing our routing, and streams already warned about
+        pub created_links: RefCell<HashMap<u32, Instant>>,
+        pub deleting_links: RefCell<HashSet<u32>>,
+        pub conflict_warned_nodes: RefCell<HashSet<u32>>,
+        // Every link we created that still exists, to show who made it
+        pub own_links: RefCell<HashSet<u32>>,
+        // Connections of a session that ended in a crash or PipeWire
+        // restart, offered back once the graph loads
+        pub restorable_session: RefCell<Option<SessionSnapshot>>,
+        // Whether the links changed since the last session snapshot
+        pub session_snapshot_dirty: Cell<bool>,
+
+        // Cookie of the inhibitor keeping the computer awake while audio is
+        // shared over the network
+        pub suspend_inhibit_cookie: Cell<Option<u32>>,
+
+        // Lua scripts, if any were found
+        #[cfg(feature = "scripting")]
+        pub script_host: RefCell<Option<ScriptHost>>,
+        // Set while a hook runs, so what the scripts do doesn't run hooks
+        // again, such as activating a preset from on_preset_activated
+        #[cfg(feature = "scripting")]
+        pub scripts_running: Cell<bool>,
+
+        // Result of the startup health check, None while disconnected
+        pub health_report: RefCell<Option<HealthReport>>,
+
+        // Devices plugged in whose announcement waits for their ports
+        pub hotplug_pending: RefCell<Vec<u32>>,
+
+        // Widget in the window content focused last, and whether to focus
+        // it again when the window is next active because a dialog opened
+        pub last_content_focus: glib::WeakRef<gtk::Widget>,
+        pub restore_focus_on_activate: Cell<bool>,
+
+        // Pane holding the port lists, the output and input lists, and
+        // whether the split is being set or waits to be saved
+        pub port_paned: RefCell<Option<gtk::Paned>>,
+        pub port_panels: RefCell<Option<(gtk::Frame, gtk::Frame)>>,
+        pub applying_pane_split: Cell<bool>,
+        pub pane_split_save_pending: Cell<bool>,
+
+        // Activity history of notable changes, oldest first, and its panel
+        pub activity: RefCell<VecDeque<ActivityEntry>>,
+        pub activity_list: RefCell<Option<gtk::ListBox>>,
+        pub activity_revealer: RefCell<Option<gtk::Revealer>>,
+
+        // Property inspector panel and the port or link it shows
+        pub inspector_revealer: RefCell<Option<gtk::Revealer>>,
+        pub inspector_list: RefCell<Option<gtk::ListBox>>,
+        pub inspector_search: RefCell<Option<gtk::SearchEntry>>,
+        pub inspector_title: RefCell<String>,
+        pub inspected_object: Cell<Option<u32>>,
+
+        // Graph Load window while open, with the pw-top process feeding it
+        pub graph_load: RefCell<Option<GraphLoadView>>,
+        // The MIDI Ports tab while open
+        pub midi_view: RefCell<Option<MidiView>>,
+
+        // Event debugger: recent PipeWire events while recording is on
+        pub recording_events: Cell<bool>,
+        pub event_log: RefCell<VecDeque<RecordedEvent>>,
+        pub event_log_started: Cell<i64>,
+
+        // Monitors on settings.json and presets.json, and whether a reload
+        // is already waiting for a burst of changes to end
+        pub config_monitors: RefCell<Vec<gio::FileMonitor>>,
+        pub config_reload_pending: Cell<bool>,
+        // Kept to receive changes to GSettings while settings are stored there
+        pub settings_watch: RefCell<Option<gio::Settings>>,
     }
 
     impl Default for Window {
         fn default() -> Self {
             Self {
+                toast_overlay: TemplateChild::default(),
                 main_box: TemplateChild::default(),
+                header_bar: TemplateChild::default(),
+                tray_banner: TemplateChild::default(),
+                bulk_spinner: TemplateChild::default(),
+                recently_removed_menu: TemplateChild::default(),
                 output_ports: gio::ListStore::new::<PortObject>(),
                 input_ports: gio::ListStore::new::<PortObject>(),
+                pending_ports: RefCell::new(Vec::new()),
+                port_flush_scheduled: Cell::new(false),
                 links: gio::ListStore::new::<LinkObject>(),
+                link_objects: RefCell::new(HashMap::new()),
                 pw_state: RefCell::new(PwState::new()),
+                clock_settings: RefCell::new(ClockSettings::default()),
                 command_tx: RefCell::new(None),
+                queued_commands: RefCell::new(VecDeque::new()),
+                command_retry_scheduled: Cell::new(false),
+                command_backlog_warned: Cell::new(false),
                 search_text: RefCell::new(String::new()),
                 show_audio: RefCell::new(true),
                 show_midi: RefCell::new(true),
                 show_video: RefCell::new(true),
+                show_monitors: RefCell::new(true),
+                filter_widgets: RefCell::new(None),
                 output_selection: RefCell::new(None),
                 input_selection: RefCell::new(None),
                 output_list_view: RefCell::new(None),
                 input_list_view: RefCell::new(None),
                 connections_list_view: RefCell::new(None),
                 connections_selection: RefCell::new(None),
+                connections_filter: RefCell::new(None),
+                connections_filter_bar: RefCell::new(None),
+                connections_filter_label: RefCell::new(None),
+                connections_origin: Cell::new(None),
+                connections_origin_filter: RefCell::new(None),
                 status_label: RefCell::new(None),
+                graph_stack: RefCell::new(None),
+                offline_page: RefCell::new(None),
+                offline_retry_button: RefCell::new(None),
                 output_filter: RefCell::new(None),
                 input_filter: RefCell::new(None),
+                port_query: RefCell::new(PortQuery::default()),
+                applied_port_filter: RefCell::new(None),
+                filter_started: Cell::new(None),
+                output_sort_model: RefCell::new(None),
+                input_sort_model: RefCell::new(None),
+                type_ahead_text: RefCell::new(String::new()),
+                type_ahead_list: Cell::new(true),
+                type_ahead_time: Cell::new(0),
                 last_port_list_was_output: RefCell::new(true),
+                connections_port_filter: Cell::new(None),
                 pending_delete_position: RefCell::new(None),
                 preset_store: RefCell::new(PresetStore::load()),
-                pending_links: RefCell::new(HashSet::new()),
+                pending_links: RefCell::new(HashMap::new()),
+                next_link_request_id: Cell::new(1),
+                delete_link_requests: RefCell::new(HashMap::new()),
+                toast_link_requests: RefCell::new(HashMap::new()),
+                preset_link_requests: RefCell::new(HashMap::new()),
+                bulk_operations: RefCell::new(Vec::new()),
                 settings: RefCell::new(Settings::load()),
+                loaded_modules: RefCell::new(Vec::new()),
+                hosted_modules: RefCell::new(Vec::new()),
+                pending_routes: RefCell::new(Vec::new()),
+                inserted_filters: RefCell::new(Vec::new()),
+                recently_removed: RefCell::new(VecDeque::new()),
+                recently_created: RefCell::new(VecDeque::new()),
+                armed_port: RefCell::new(None),
+                connection_matrix: RefCell::new(None),
+                compact_view: RefCell::new(None),
+                compact_above: Cell::new(false),
+                next_helper_instance: Cell::new(1),
+                temporary_mode: Cell::new(false),
+                tray_available: Cell::new(false),
+                tray_updater: RefCell::new(None),
+                tray_connections: RefCell::new(Vec::new()),
+                last_tray_scroll: Cell::new(0),
+                daemon_mode: Cell::new(false),
+                server: RefCell::new(None),
+                server_backend: RefCell::new(None),
+                server_tabs: RefCell::new(None),
+                server_pages: RefCell::new(Vec::new()),
+                control_server: RefCell::new(None),
+                control_subscribers: RefCell::new(Vec::new()),
+                control_link_requests: RefCell::new(HashMap::new()),
+                osc_server: RefCell::new(None),
+                created_links: RefCell::new(HashMap::new()),
+                deleting_links: RefCell::new(HashSet::new()),
+                conflict_warned_nodes: RefCell::new(HashSet::new()),
+                own_links: RefCell::new(HashSet::new()),
+                restorable_session: RefCell::new(None),
+                session_snapshot_dirty: Cell::new(false),
+                suspend_inhibit_cookie: Cell::new(None),
+                #[cfg(feature = "scripting")]
+                script_host: RefCell::new(None),
+                #[cfg(feature = "scripting")]
+                scripts_running: Cell::new(false),
+                health_report: RefCell::new(None),
+                hotplug_pending: RefCell::new(Vec::new()),
+                last_content_focus: glib::WeakRef::new(),
+                restore_focus_on_activate: Cell::new(false),
+                port_paned: RefCell::new(None),
+                port_panels: RefCell::new(None),
+                applying_pane_split: Cell::new(false),
+                pane_split_save_pending: Cell::new(false),
+                activity: RefCell::new(VecDeque::new()),
+                activity_list: RefCell::new(None),
+                activity_revealer: RefCell::new(None),
+                inspector_revealer: RefCell::new(None),
+                inspector_list: RefCell::new(None),
+                inspector_search: RefCell::new(None),
+                inspector_title: RefCell::new(String::new()),
+                inspected_object: Cell::new(None),
+                graph_load: RefCell::new(None),
+                midi_view: RefCell::new(None),
+                recording_events: Cell::new(false),
+                event_log: RefCell::new(VecDeque::new()),
+                event_log_started: Cell::new(0),
+                config_monitors: RefCell::new(Vec::new()),
+                config_reload_pending: Cell::new(false),
+                settings_watch: RefCell::new(None),
             }
         }
     }
@@ -169,10 +1016,21 @@ mod imp {
         }
     }
 
+    #[glib::derived_properties]
     impl ObjectImpl for Window {
         fn constructed(&self) {
             self.parent_constructed();
             self.obj().setup_ui();
+            #[cfg(feature = "scripting")]
+            if !self.obj().is_server_window() {
+                self.obj().load_scripts();
+            }
+            self.obj().refresh_preset_accels();
+            if self.obj().is_server_window() {
+                self.obj().disable_preset_actions();
+            } else {
+                self.obj().start_session_snapshots();
+            }
         }
     }
 
@@ -193,6 +1051,15 @@ impl Window {
         glib::Object::builder().property("application", app).build()
     }
 
+    /// Create a window for another PipeWire server, such as `pipewire-1`
+    pub fn new_for_server(app: &adw::Application, server: &str) -> Self {
+        glib::Object::builder()
+            .property("application", app)
+            .property("server", server)
+            .property("title", format!("PW Audioshare ({})", server))
+            .build()
+    }
+
     /// Set the command sender for PipeWire communication
     pub fn set_command_sender(&self, tx: Sender<UiCommand>) {
         self.imp().command_tx.replace(Some(tx));
@@ -200,12 +1067,29 @@ impl Window {
 
     /// Handle a PipeWire event
     pub fn handle_pw_event(&self, event: PwEvent) {
+        if self.imp().recording_events.get() {
+            self.record_event(&event);
+        }
+        self.notify_control_clients(&event);
+
         match event {
             PwEvent::Connected => {
-                self.update_status("Connected to PipeWire", false);
+                self.update_status(
+                    &format!("Connected to PipeWire ({})", self.server_name()),
+                    false,
+                );
+                self.hide_offline_page();
             }
             PwEvent::Disconnected { reason } => {
                 self.update_status(&format!("Disconnected: {}", reason), false);
+                if !self.is_server_window() {
+                    self.session_disconnected();
+                }
+                self.connection_failed(&reason);
+            }
+            PwEvent::HealthChecked { report } => {
+                self.health_checked(report);
+                self.offer_session_restore();
             }
             PwEvent::NodeAdded {
                 id,
@@ -213,9 +1097,12 @@ impl Window {
                 media_class,
                 description,
                 application_name,
+                serial,
+                nick,
+                device_name,
+                client_id,
             } => {
-                let mut state = self.imp().pw_state.borrow_mut();
-                state.nodes.insert(
+                self.imp().pw_state.borrow_mut().nodes.insert(
                     id,
                     crate::pipewire::state::PwNode {
                         id,
@@ -223,11 +1110,62 @@ impl Window {
                         media_class,
                         description,
                         application_name,
+                        serial,
+                        nick,
+                        device_name,
+                        client_id,
                     },
                 );
+
+                if let Some(label) = self.device_label(id) {
+                    self.record_graph_activity(&format!("Device added: {}", label));
+                    self.device_added(id);
+                }
+
+                self.resolve_orphan_ports(id);
+                #[cfg(feature = "scripting")]
+                self.scripts_node_added(id);
+            }
+            PwEvent::NodeChanged {
+                id,
+                name,
+                media_class,
+                description,
+                application_name,
+                serial,
+                nick,
+                device_name,
+                client_id,
+            } => {
+                if let Some(node) = self.imp().pw_state.borrow_mut().nodes.get_mut(&id) {
+                    node.name = name;
+                    node.media_class = media_class;
+                    node.description = description;
+                    node.application_name = application_name;
+                    node.serial = serial;
+                    node.nick = nick;
+                    node.device_name = device_name;
+                    node.client_id = client_id;
+                }
+                self.refresh_node_labels(id);
+
+                // A renamed node may now match a preset
+                self.check_auto_connect();
             }
             PwEvent::NodeRemoved { id } => {
-                self.imp().pw_state.borrow_mut().nodes.remove(&id);
+                if let Some(label) = self.device_label(id) {
+                    self.record_graph_activity(&format!("Device removed: {}", label));
+                    self.device_removed(id, &label);
+                }
+
+                let mut state = self.imp().pw_state.borrow_mut();
+                state.nodes.remove(&id);
+                state.orphan_ports.remove(&id);
+                // Sent for every removed object, whatever its type
+                state.properties.remove(&id);
+                state.devices.remove(&id);
+                state.clients.remove(&id);
+                state.video_formats.remove(&id);
             }
             PwEvent::PortAdded {
                 id,
@@ -239,30 +1177,14 @@ impl Window {
                 channel,
             } => {
                 // Determine actual media type - if Unknown, check the node's media.class
-                let actual_media_type = {
-                    let state = self.imp().pw_state.borrow();
-                    if media_type == crate::pipewire::messages::MediaType::Unknown {
-                        // Try to infer from node's media.class
-                        state.nodes.get(&node_id).map(|n| {
-                            if let Some(ref mc) = n.media_class {
-                                let mc_lower = mc.to_lowercase();
-                                if mc_lower.contains("video") {
-                                    crate::pipewire::messages::MediaType::Video
-                                } else if mc_lower.contains("midi") {
-                                    crate::pipewire::messages::MediaType::Midi
-                                } else if mc_lower.contains("audio") || mc_lower.contains("stream") {
-                                    crate::pipewire::messages::MediaType::Audio
-                                } else {
-                                    media_type
-                                }
-                            } else {
-                                media_type
-                            }
-                        }).unwrap_or(media_type)
-                    } else {
-                        media_type
-                    }
-                };
+                let actual_media_type = self
+                    .imp()
+                    .pw_state
+                    .borrow()
+                    .nodes
+                    .get(&node_id)
+                    .map(|n| n.infer_media_type(media_type))
+                    .unwrap_or(media_type);
 
                 // Store in PW state
                 {
@@ -281,17 +1203,27 @@ impl Window {
                     );
                 }
 
-                // Get node name
-                let node_name = {
-                    let state = self.imp().pw_state.borrow();
-                    state
-                        .nodes
-                        .get(&node_id)
-                        .map(|n| n.display_name().to_string())
-                        .unwrap_or_else(|| format!("Node {}", node_id))
+                // Get node name; if the node hasn't arrived yet, keep the port
+                // aside so it can be relabeled once it does
+                let (node_name, app_name) = {
+                    let mut state = self.imp().pw_state.borrow_mut();
+                    match state.nodes.get(&node_id) {
+                        Some(n) => (state.node_label(n), n.app_name().to_string()),
+                        None => {
+                            state.add_orphan_port(node_id, id);
+                            let placeholder = format!("Node {}", node_id);
+                            (placeholder.clone(), placeholder)
+                        }
+                    }
                 };
 
-                // Create GObject and add to appropriate list
+                // Our MIDI monitors are linked up but not listed
+                if self.is_midi_monitor_port(id) {
+                    self.midi_monitor_port_added(id, node_id);
+                    return;
+                }
+
+                // Create GObject, added to its list with the rest of the burst
                 let port_obj = PortObject::new(
                     id,
                     node_id,
@@ -302,25 +1234,35 @@ impl Window {
                     actual_media_type.as_str(),
                     channel.as_deref(),
                 );
-
-                match direction {
-                    PortDirection::Output => {
-                        self.imp().output_ports.append(&port_obj);
-                    }
-                    PortDirection::Input => {
-                        self.imp().input_ports.append(&port_obj);
-                    }
-                }
-
-                self.update_status_counts();
+                port_obj.set_app_name(app_name);
+                self.queue_port(port_obj);
 
                 // Check if this new port completes any auto-connect preset connections
                 self.check_auto_connect();
+
+                // Route connections through filters whose ports just appeared
+                self.wire_pending_filters();
+                self.check_pending_routes(node_id);
+                #[cfg(feature = "scripting")]
+                self.scripts_port_added(id);
             }
             PwEvent::PortRemoved { id } => {
+                let armed_removed = self
+                    .imp()
+                    .armed_port
+                    .borrow()
+                    .as_ref()
+                    .is_some_and(|port| port.id() == id);
+                if armed_removed {
+                    self.cancel_armed_port();
+                }
+                if self.imp().connections_port_filter.get() == Some(id) {
+                    self.clear_port_connections_filter();
+                }
                 self.imp().pw_state.borrow_mut().ports.remove(&id);
                 self.remove_port_from_lists(id);
                 self.update_status_counts();
+                self.sync_midi_view();
             }
             PwEvent::LinkAdded {
                 id,
@@ -330,6 +1272,18 @@ impl Window {
                 input_port_id,
                 state,
             } => {
+                // Remove from pending links (link creation confirmed)
+                self.imp()
+                    .pending_links
+                    .borrow_mut()
+                    .remove(&(output_port_id, input_port_id));
+
+                // Links to our MIDI monitors are neither kept nor listed, so
+                // presets and snapshots never see them
+                if self.is_midi_monitor_port(input_port_id) {
+                    return;
+                }
+
                 // Store in PW state
                 {
                     let mut pw_state = self.imp().pw_state.borrow_mut();
@@ -346,31 +1300,15 @@ impl Window {
                     );
                 }
 
-                // Remove from pending links (link creation confirmed)
-                self.imp()
-                    .pending_links
-                    .borrow_mut()
-                    .remove(&(output_port_id, input_port_id));
-
                 // Get labels for the link
                 let (output_label, input_label, media_type) = {
                     let pw_state = self.imp().pw_state.borrow();
                     let out_label = pw_state
-                        .ports
-                        .get(&output_port_id)
-                        .and_then(|p| {
-                            let node = pw_state.nodes.get(&p.node_id)?;
-                            Some(format!("{} - {}", node.display_name(), p.display_name()))
-                        })
+                        .port_label(output_port_id)
                         .unwrap_or_else(|| format!("Port {}", output_port_id));
 
                     let in_label = pw_state
-                        .ports
-                        .get(&input_port_id)
-                        .and_then(|p| {
-                            let node = pw_state.nodes.get(&p.node_id)?;
-                            Some(format!("{} - {}", node.display_name(), p.display_name()))
-                        })
+                        .port_label(input_port_id)
                         .unwrap_or_else(|| format!("Port {}", input_port_id));
 
                     let media = pw_state
@@ -382,18 +1320,32 @@ impl Window {
                     (out_label, in_label, media.to_string())
                 };
 
-                let link_obj = LinkObject::new(
-                    id,
-                    output_port_id,
-                    input_port_id,
-                    &output_label,
-                    &input_label,
-                    state.as_str(),
-                    &media_type,
-                );
+                // A duplicate of a link already listed joins its row
+                if !self.merge_duplicate_link(id, output_port_id, input_port_id) {
+                    let link_obj = LinkObject::new(
+                        id,
+                        output_port_id,
+                        input_port_id,
+                        &output_label,
+                        &input_label,
+                        state.as_str(),
+                        &media_type,
+                    );
 
-                self.imp().links.append(&link_obj);
+                    self.imp().links.append(&link_obj);
+                    self.imp().link_objects.borrow_mut().insert(id, link_obj);
+                    self.refresh_link_origin(id);
+                }
+                self.session_links_changed();
+                self.update_suspend_inhibit();
                 self.update_status_counts();
+                self.sync_connection_matrix();
+                self.sync_compact_window();
+                self.update_tray_connections();
+                self.record_graph_activity(&format!(
+                    "Connected {} → {}",
+                    output_label, input_label
+                ));
             }
             PwEvent::LinkRemoved { id } => {
                 // Get port IDs before removing from state (to clean up pending_links)
@@ -410,25 +1362,158 @@ impl Window {
                     self.imp().pending_links.borrow_mut().remove(&key);
                 }
 
+                self.check_session_manager_conflict(id);
+                if let Some(label) = self.link_label(id) {
+                    self.record_graph_activity(&format!("Disconnected {}", label));
+                }
+
                 self.imp().pw_state.borrow_mut().links.remove(&id);
                 self.remove_link_from_list(id);
+                self.imp().own_links.borrow_mut().remove(&id);
+                self.session_links_changed();
+                self.update_suspend_inhibit();
                 self.update_status_counts();
+                self.sync_connection_matrix();
+                self.sync_compact_window();
+                self.update_tray_connections();
             }
-            PwEvent::LinkStateChanged { id, state } => {
-                // Update link state in model
-                for i in 0..self.imp().links.n_items() {
-                    if let Some(link) = self.imp().links.item(i).and_downcast::<LinkObject>() {
-                        if link.id() == id {
-                            link.set_state(state.as_str());
-                            break;
-                        }
-                    }
+            PwEvent::LinkCreated {
+                request_id,
+                link_id,
+            } => {
+                log::debug!("Link request {} created link {}", request_id, link_id);
+                self.finish_link_request(request_id);
+                self.bulk_request_finished(request_id, true);
+                self.preset_request_finished(request_id, Ok(()));
+                self.track_created_link(link_id);
+                self.track_own_link(link_id);
+                self.toast_link_created(request_id, link_id);
+                self.control_link_finished(request_id, Ok(link_id));
+            }
+            PwEvent::LinkFailed {
+                request_id,
+                message,
+            } => {
+                log::error!("Link request {} failed: {}", request_id, message);
+                #[cfg(feature = "scripting")]
+                self.scripts_link_failed(request_id, &message);
+                // Auto-connect may retry the pair on the next port change
+                self.finish_link_request(request_id);
+                // A failure within a bulk operation is counted in its summary
+                let in_bulk = self.bulk_request_finished(request_id, false);
+                self.preset_request_finished(request_id, Err(&message));
+                if !in_bulk {
+                    self.update_status(&format!("Error: {}", message), false);
+                }
+                self.toast_link_failed(request_id, &message);
+                self.control_link_finished(request_id, Err(message.clone()));
+                if !in_bulk {
+                    self.announce_error(&message);
                 }
+                self.sync_connection_matrix();
+            }
+            PwEvent::LinkStateChanged { id, state } => {
+                // Update link state in model. Many links can renegotiate at
+                // once, so find it by id rather than scanning the list.
+                // A row merging duplicates shows the state of the link it
+                // keeps.
+                let link = self.imp().link_objects.borrow().get(&id).cloned();
+                if let Some(link) = link.filter(|link| link.id() == id) {
+                    link.set_state(state.as_str());
+                }
+            }
+            PwEvent::TestToneChanged { signal } => {
+                let state = signal.map(|s| s.as_str()).unwrap_or("off");
+                if let Some(action) = self
+                    .lookup_action("test-tone")
+                    .and_downcast::<gio::SimpleAction>()
+                {
+                    action.set_state(&state.to_variant());
+                }
+
+                match signal {
+                    Some(signal) => self.announce(&format!(
+                        "{} playing on \"{}\" output ports",
+                        signal.label(),
+                        crate::pipewire::test_tone::TEST_TONE_DESCRIPTION
+                    )),
+                    None => self.announce("Test signal stopped"),
+                }
+            }
+            PwEvent::ModuleLoaded { id, name, args } => {
+                self.module_loaded(LoadedModule { id, name, args });
+            }
+            PwEvent::ModuleUnloaded { id } => {
+                self.module_unloaded(id);
+            }
+            PwEvent::ModuleScanComplete => {
+                self.restore_tunnels();
+            }
+            PwEvent::ModuleHostExited { key } => {
+                if !self.hosted_module_exited(&key) {
+                    self.filter_host_exited(&key);
+                }
+            }
+            PwEvent::DeviceAdded {
+                id,
+                name,
+                description,
+                api,
+            } => {
+                self.device_added(id, name, description, api);
+            }
+            PwEvent::DeviceProfilesChanged {
+                id,
+                profiles,
+                active_profile,
+            } => {
+                self.device_profiles_changed(id, profiles, active_profile);
+            }
+            PwEvent::DeviceRoutesChanged { id, routes } => {
+                self.device_routes_changed(id, routes);
+            }
+            PwEvent::VideoFormatsChanged {
+                id,
+                offered,
+                current,
+            } => {
+                self.video_formats_changed(id, offered, current);
+            }
+            PwEvent::ClientAdded {
+                id,
+                application_name,
+                pid,
+                process_binary,
+            }
+            | PwEvent::ClientChanged {
+                id,
+                application_name,
+                pid,
+                process_binary,
+            } => {
+                self.client_changed(PwClient {
+                    id,
+                    application_name,
+                    pid,
+                    process_binary,
+                });
+            }
+            PwEvent::PropertiesChanged { id, properties } => {
+                self.properties_changed(id, properties);
+                // A link's client is known from its properties
+                self.refresh_link_origin(id);
+            }
+            PwEvent::ClockSettingChanged { key, value } => {
+                self.clock_setting_changed(key, value);
+            }
+            PwEvent::CommandResult { request_id, error } => {
+                self.command_finished(request_id, error);
             }
             PwEvent::Error { message } => {
                 log::error!("PipeWire error: {}", message);
                 self.update_status(&format!("Error: {}", message), false);
-                self.announce(&message);
+                self.show_toast(&message);
+                self.announce_error(&message);
             }
         }
     }
@@ -438,17 +1523,43 @@ impl Window {
         let imp = self.imp();
         let main_box = &*imp.main_box;
 
+        imp.tray_banner
+            .connect_button_clicked(|banner| banner.set_revealed(false));
+
         // Create filter bar
         let filter_bar = self.build_filter_bar();
         main_box.append(&filter_bar);
 
+        // Port lists and connections, with the activity panel beside them
+        let body = gtk::Box::builder()
+            .orientation(gtk::Orientation::Horizontal)
+            .vexpand(true)
+            .build();
+        let center = gtk::Box::builder()
+            .orientation(gtk::Orientation::Vertical)
+            .hexpand(true)
+            .build();
+        body.append(&center);
+        body.append(&self.build_activity_panel());
+        body.append(&self.build_inspector_panel());
+
+        // The graph, or a status page while PipeWire can't be reached
+        let graph_stack = gtk::Stack::builder().vexpand(true).build();
+        graph_stack.add_named(&body, Some("graph"));
+        graph_stack.add_named(&self.build_offline_page(), Some("offline"));
+        main_box.append(&graph_stack);
+        imp.graph_stack.replace(Some(graph_stack));
+
         // Create main content area with port lists
         let content = self.build_content_area();
-        main_box.append(&content);
+        center.append(&content);
+        self.apply_port_sort();
+        self.apply_filters();
 
         // Create connections panel
         let connections = self.build_connections_panel();
-        main_box.append(&connections);
+        center.append(&connections);
+        self.connect_inspector_selection();
 
         // Create status bar
         let status_bar = self.build_status_bar();
@@ -456,13 +1567,32 @@ impl Window {
 
         // Setup actions
         self.setup_actions();
+        self.update_recently_removed_menu();
 
         // Show active preset if one was saved from previous session
         self.update_active_preset_display();
+        self.purge_preset_trash();
+
+        // Pick up settings and presets changed by another program
+        self.watch_config_files();
+
+        // Put focus back where it was when dialogs close
+        self.track_focus();
+        self.apply_list_text_size();
+        self.apply_reduce_motion();
+        self.apply_appearance();
     }
 
-    /// Build the filter bar with search and media type toggles
+    /// Build the filter bar with search and media type toggles, starting from
+    /// the filter used last time
     fn build_filter_bar(&self) -> gtk::Box {
+        let last_filter = self.imp().settings.borrow().startup_filter();
+        self.imp().search_text.replace(last_filter.search.clone());
+        self.imp().show_audio.replace(last_filter.show_audio);
+        self.imp().show_midi.replace(last_filter.show_midi);
+        self.imp().show_video.replace(last_filter.show_video);
+        self.imp().show_monitors.replace(last_filter.show_monitors);
+
         let bar = gtk::Box::builder()
             .orientation(gtk::Orientation::Horizontal)
             .spacing(12)
@@ -476,8 +1606,13 @@ impl Window {
         // Search entry
         let search = gtk::SearchEntry::builder()
             .placeholder_text("Search ports...")
+            .text(last_filter.search.as_str())
+            .search_delay(SEARCH_DELAY_MS)
             .hexpand(true)
-            .tooltip_text("Filter ports by name")
+            .tooltip_text(
+                "Filter ports by name. Narrow with node:, media:, channel: or dir:in/out, \
+                 and exclude with a leading -, as in node:firefox -monitor",
+            )
             .build();
 
         // Connect search
@@ -487,7 +1622,7 @@ impl Window {
             move |entry| {
                 let text = entry.text().to_string();
                 window.imp().search_text.replace(text);
-                window.apply_filters();
+                window.port_filter_changed();
             }
         ));
 
@@ -496,29 +1631,35 @@ impl Window {
         // Media type toggles
         let audio_btn = gtk::ToggleButton::builder()
             .label("Audio")
-            .active(true)
+            .active(last_filter.show_audio)
             .tooltip_text("Show audio ports")
             .build();
 
         let midi_btn = gtk::ToggleButton::builder()
             .label("MIDI")
-            .active(true)
+            .active(last_filter.show_midi)
             .tooltip_text("Show MIDI ports")
             .build();
 
         let video_btn = gtk::ToggleButton::builder()
             .label("Video")
-            .active(true)
+            .active(last_filter.show_video)
             .tooltip_text("Show video ports")
             .build();
 
+        let monitors_btn = gtk::ToggleButton::builder()
+            .label("Monitors")
+            .active(last_filter.show_monitors)
+            .tooltip_text("Show the monitor ports of outputs")
+            .build();
+
         // Connect toggles
         audio_btn.connect_toggled(glib::clone!(
             #[weak(rename_to = window)]
             self,
             move |btn| {
                 window.imp().show_audio.replace(btn.is_active());
-                window.apply_filters();
+                window.port_filter_changed();
             }
         ));
 
@@ -527,7 +1668,7 @@ impl Window {
             self,
             move |btn| {
                 window.imp().show_midi.replace(btn.is_active());
-                window.apply_filters();
+                window.port_filter_changed();
             }
         ));
 
@@ -536,39 +1677,68 @@ impl Window {
             self,
             move |btn| {
                 window.imp().show_video.replace(btn.is_active());
-                window.apply_filters();
+                window.port_filter_changed();
+            }
+        ));
+
+        monitors_btn.connect_toggled(glib::clone!(
+            #[weak(rename_to = window)]
+            self,
+            move |btn| {
+                window.imp().show_monitors.replace(btn.is_active());
+                window.port_filter_changed();
             }
         ));
 
         bar.append(&audio_btn);
         bar.append(&midi_btn);
         bar.append(&video_btn);
+        bar.append(&monitors_btn);
+
+        // Saved filters and sort order
+        let filters_menu = gio::Menu::new();
+        let saved_section = gio::Menu::new();
+        saved_section.append(Some("Save Filter..."), Some("win.save-filter"));
+        saved_section.append(Some("Saved Filters..."), Some("win.saved-filters"));
+        filters_menu.append_section(None, &saved_section);
+        let sort_section = gio::Menu::new();
+        for (label, target) in [
+            ("Name", "name"),
+            ("Application", "app"),
+            ("Most Recent", "recent"),
+        ] {
+            let item = gio::MenuItem::new(Some(label), None);
+            item.set_action_and_target_value(Some("win.port-sort"), Some(&target.to_variant()));
+            sort_section.append_item(&item);
+        }
+        filters_menu.append_section(Some("Sort Ports By"), &sort_section);
+        let filters_btn = gtk::MenuButton::builder()
+            .icon_name("view-more-symbolic")
+            .tooltip_text("Saved filters and sorting")
+            .menu_model(&filters_menu)
+            .build();
+        bar.append(&filters_btn);
+
+        self.imp().filter_widgets.replace(Some(FilterWidgets {
+            search,
+            audio: audio_btn,
+            midi: midi_btn,
+            video: video_btn,
+            monitors: monitors_btn,
+        }));
 
         bar
     }
 
     /// Build the main content area with output and input port lists
-    fn build_content_area(&self) -> gtk::Box {
-        let content = gtk::Box::builder()
-            .orientation(gtk::Orientation::Horizontal)
-            .spacing(12)
-            .margin_start(12)
-            .margin_end(12)
-            .margin_top(6)
-            .margin_bottom(6)
-            .homogeneous(true)
-            .vexpand(true)
-            .build();
-
+    fn build_content_area(&self) -> gtk::Paned {
         // Output ports panel
         let output_panel = self.build_port_panel("Output Ports (Sources)", true);
-        content.append(&output_panel);
 
         // Input ports panel
         let input_panel = self.build_port_panel("Input Ports (Sinks)", false);
-        content.append(&input_panel);
 
-        content
+        self.build_port_panes(output_panel, input_panel)
     }
 
     /// Build a port list panel (either outputs or inputs)
@@ -592,8 +1762,8 @@ impl Window {
         };
 
         // Create filter model
-        let filter = gtk::CustomFilter::new(|_| true);
-        let filter_model = gtk::FilterListModel::new(Some(model), Some(filter.clone()));
+        let filter = self.new_port_filter();
+        let filter_model = self.new_port_filter_model(model, &filter);
 
         // Store filter reference for later updates
         if is_output {
@@ -602,13 +1772,17 @@ impl Window {
             self.imp().input_filter.replace(Some(filter));
         }
 
-        // Create sort model (sort by display label)
-        let sorter = gtk::CustomSorter::new(|a, b| {
-            let port_a = a.downcast_ref::<PortObject>().unwrap();
-            let port_b = b.downcast_ref::<PortObject>().unwrap();
-            port_a.display_label().cmp(&port_b.display_label()).into()
-        });
-        let sort_model = gtk::SortListModel::new(Some(filter_model), Some(sorter));
+        // Create sort model; the sorter and node sections are set by apply_port_sort
+        let sort_model = gtk::SortListModel::new(Some(filter_model), None::<gtk::Sorter>);
+        if is_output {
+            self.imp()
+                .output_sort_model
+                .replace(Some(sort_model.clone()));
+        } else {
+            self.imp()
+                .input_sort_model
+                .replace(Some(sort_model.clone()));
+        }
 
         // Selection model (MultiSelection for bulk connect)
         let selection = gtk::MultiSelection::new(Some(sort_model));
@@ -641,7 +1815,8 @@ impl Window {
             let port = list_item.item().and_downcast::<PortObject>().unwrap();
             let label = list_item.child().and_downcast::<gtk::Label>().unwrap();
 
-            label.set_text(&port.display_label());
+            label.set_text(&port.row_label());
+            style::set_media_class(&label, &port.media_type());
             // Use tooltip for additional accessible description
             label.set_tooltip_text(Some(&port.accessible_description()));
         });
@@ -660,7 +1835,8 @@ impl Window {
             self.imp().input_list_view.replace(Some(list_view.clone()));
         }
 
-        // Keyboard navigation: Enter to connect, Left/Right to switch lists, F6 to connections
+        // Keyboard navigation: Enter to connect, Left/Right to switch lists, F6 to connections.
+        // Keep ui/shortcuts.rs in step when changing these keys.
         let key_controller = gtk::EventControllerKey::new();
         key_controller.connect_key_pressed(glib::clone!(
             #[weak(rename_to = window)]
@@ -669,14 +1845,29 @@ impl Window {
             Propagation::Proceed,
             move |_, key, _, modifiers| {
                 let ctrl = modifiers.contains(gtk::gdk::ModifierType::CONTROL_MASK);
+                let alt = modifiers.contains(gtk::gdk::ModifierType::ALT_MASK);
                 match key {
                     // Ctrl+Enter to connect selected ports (works from either list)
                     Key::Return | Key::KP_Enter if ctrl => {
                         window.connect_selected();
                         Propagation::Stop
                     }
-                    // F6: jump to connections list, remember which list we came from
-                    Key::F6 => {
+                    // Ctrl+J: show the connections of the selected port
+                    Key::j | Key::J if ctrl => {
+                        window.show_port_connections(is_output);
+                        Propagation::Stop
+                    }
+                    // Ctrl+C: copy port names, Ctrl+Shift+C: copy pw-link commands
+                    Key::c | Key::C if ctrl => {
+                        if modifiers.contains(gtk::gdk::ModifierType::SHIFT_MASK) {
+                            window.copy_port_commands();
+                        } else {
+                            window.copy_port_names(is_output);
+                        }
+                        Propagation::Stop
+                    }
+                    // F6 or Ctrl+Down: jump to connections list, remember which list we came from
+                    Key::F6 | Key::Down | Key::KP_Down if key == Key::F6 || ctrl => {
                         window.imp().last_port_list_was_output.replace(is_output);
                         window.focus_connections_list();
                         Propagation::Stop
@@ -691,12 +1882,39 @@ impl Window {
                         window.focus_output_list();
                         Propagation::Stop
                     }
-                    _ => Propagation::Proceed,
+                    // Escape: cancel a connection started in connect mode
+                    Key::Escape if window.cancel_armed_port() => Propagation::Stop,
+                    // Letters: type-ahead find within this list
+                    _ => match key.to_unicode() {
+                        Some(ch) if !ctrl && !alt && !ch.is_control() => {
+                            if window.type_ahead(is_output, ch) {
+                                Propagation::Stop
+                            } else {
+                                Propagation::Proceed
+                            }
+                        }
+                        _ => Propagation::Proceed,
+                    },
                 }
             }
         ));
         list_view.add_controller(key_controller);
 
+        // Connect mode: Enter on a port arms it, Enter on a port in the other list connects
+        list_view.connect_activate(glib::clone!(
+            #[weak(rename_to = window)]
+            self,
+            move |list_view, position| {
+                if let Some(port) = list_view
+                    .model()
+                    .and_then(|model| model.item(position))
+                    .and_downcast::<PortObject>()
+                {
+                    window.activate_port(&port);
+                }
+            }
+        ));
+
         // Scrolled window
         let scrolled = gtk::ScrolledWindow::builder()
             .hscrollbar_policy(gtk::PolicyType::Never)
@@ -718,6 +1936,13 @@ impl Window {
             panel_box.append(&connect_btn);
         }
 
+        let all_ports = if is_output {
+            self.imp().output_ports.clone()
+        } else {
+            self.imp().input_ports.clone()
+        };
+        self.summarize_list(&frame, &list_view, &selection, &all_ports, true);
+
         frame.set_child(Some(&panel_box));
         frame
     }
@@ -731,8 +1956,23 @@ impl Window {
             .margin_bottom(6)
             .build();
 
+        // Filter used to show the connections of one port only, and one
+        // keeping those made by the chosen origin
+        let filter = gtk::CustomFilter::new(|_| true);
+        let origin_filter = self.new_origin_filter();
+        let filters = gtk::EveryFilter::new();
+        filters.append(filter.clone());
+        filters.append(origin_filter.clone());
+        let filter_model = gtk::FilterListModel::new(Some(self.imp().links.clone()), Some(filters));
+        self.imp().connections_filter.replace(Some(filter));
+        self.imp().connections_origin_filter.replace(Some(origin_filter));
+
+        // Group connections by media type
+        let sort_model = gtk::SortListModel::new(Some(filter_model), None::<gtk::Sorter>);
+        sort_model.set_section_sorter(Some(&sections::link_section_sorter()));
+
         // Use SingleSelection so we can select and delete with keyboard
-        let selection = gtk::SingleSelection::new(Some(self.imp().links.clone()));
+        let selection = gtk::SingleSelection::new(Some(sort_model));
         self.imp().connections_selection.replace(Some(selection.clone()));
 
         let factory = gtk::SignalListItemFactory::new();
@@ -760,6 +2000,7 @@ impl Window {
                 .css_classes(["destructive-action"])
                 .build();
 
+            row.append(&link_state::attach_state_icon(list_item));
             row.append(&label);
             row.append(&delete_btn);
 
@@ -775,9 +2016,14 @@ impl Window {
                 let row = list_item.child().and_downcast::<gtk::Box>().unwrap();
 
                 // Update label
-                let label = row.first_child().and_downcast::<gtk::Label>().unwrap();
+                let label = row
+                    .first_child()
+                    .and_then(|icon| icon.next_sibling())
+                    .and_downcast::<gtk::Label>()
+                    .unwrap();
                 label.set_text(&link.display_label());
                 label.set_tooltip_text(Some(&link.accessible_description()));
+                style::set_media_class(&row, &link.media_type());
 
                 // Update delete button
                 let delete_btn = row.last_child().and_downcast::<gtk::Button>().unwrap();
@@ -792,7 +2038,7 @@ impl Window {
                     #[weak]
                     window,
                     move |_| {
-                        window.delete_link(link_id);
+                        window.delete_link_remembered(link_id);
                     }
                 ));
             }
@@ -801,6 +2047,7 @@ impl Window {
         let list_view = gtk::ListView::builder()
             .model(&selection)
             .factory(&factory)
+            .header_factory(&sections::link_header_factory())
             .build();
 
         // Store reference to connections list view
@@ -813,15 +2060,38 @@ impl Window {
             self,
             #[upgrade_or]
             Propagation::Proceed,
-            move |_, key, _, _modifiers| {
+            move |_, key, _, modifiers| {
+                let ctrl = modifiers.contains(gtk::gdk::ModifierType::CONTROL_MASK);
                 match key {
                     // Delete selected connection
                     Key::Delete | Key::KP_Delete | Key::BackSpace => {
                         window.delete_selected_connection();
                         Propagation::Stop
                     }
-                    // F6: jump back to the port list we came from
-                    Key::F6 => {
+                    // Ctrl+Left/Right: jump to the source or destination port
+                    Key::Left | Key::KP_Left if ctrl => {
+                        window.jump_to_connection_port(true);
+                        Propagation::Stop
+                    }
+                    Key::Right | Key::KP_Right if ctrl => {
+                        window.jump_to_connection_port(false);
+                        Propagation::Stop
+                    }
+                    // Ctrl+C: copy the connection's name, Ctrl+Shift+C: copy a pw-link command
+                    Key::c | Key::C if ctrl => {
+                        let shift = modifiers.contains(gtk::gdk::ModifierType::SHIFT_MASK);
+                        window.copy_connection(shift);
+                        Propagation::Stop
+                    }
+                    // Ctrl+P: pin to the compact window, or unpin
+                    Key::p | Key::P if ctrl => {
+                        window.toggle_pin_selected_connection();
+                        Propagation::Stop
+                    }
+                    // Escape: show all connections again after Ctrl+J
+                    Key::Escape if window.clear_port_connections_filter() => Propagation::Stop,
+                    // F6 or Ctrl+Up: jump back to the port list we came from
+                    Key::F6 | Key::Up | Key::KP_Up if key == Key::F6 || ctrl => {
                         if *window.imp().last_port_list_was_output.borrow() {
                             window.focus_output_list();
                         } else {
@@ -843,7 +2113,51 @@ impl Window {
             .child(&list_view)
             .build();
 
-        frame.set_child(Some(&scrolled));
+        // Shown while the list is limited to one port's connections
+        let filter_bar = gtk::Box::builder()
+            .orientation(gtk::Orientation::Horizontal)
+            .spacing(12)
+            .margin_start(6)
+            .margin_end(6)
+            .margin_top(4)
+            .visible(false)
+            .build();
+        let filter_label = gtk::Label::builder()
+            .halign(gtk::Align::Start)
+            .hexpand(true)
+            .xalign(0.0)
+            .build();
+        let show_all_btn = gtk::Button::builder()
+            .label("Show All")
+            .tooltip_text("Show all connections (Escape)")
+            .build();
+        show_all_btn.connect_clicked(glib::clone!(
+            #[weak(rename_to = window)]
+            self,
+            move |_| {
+                window.clear_port_connections_filter();
+            }
+        ));
+        filter_bar.append(&filter_label);
+        filter_bar.append(&show_all_btn);
+        self.imp()
+            .connections_filter_bar
+            .replace(Some(filter_bar.clone()));
+        self.imp()
+            .connections_filter_label
+            .replace(Some(filter_label));
+
+        let panel_box = gtk::Box::builder()
+            .orientation(gtk::Orientation::Vertical)
+            .build();
+        panel_box.append(&self.build_origin_filter_bar());
+        panel_box.append(&filter_bar);
+        panel_box.append(&scrolled);
+        panel_box.append(&link_state::build_state_legend());
+
+        self.summarize_list(&frame, &list_view, &selection, &self.imp().links, false);
+
+        frame.set_child(Some(&panel_box));
         frame
     }
 
@@ -883,6 +2197,17 @@ impl Window {
         ));
         self.add_action(&action_connect);
 
+        // Action: connect-advanced
+        let action_connect_advanced = gio::SimpleAction::new("connect-advanced", None);
+        action_connect_advanced.connect_activate(glib::clone!(
+            #[weak(rename_to = window)]
+            self,
+            move |_, _| {
+                window.show_connect_options_dialog();
+            }
+        ));
+        self.add_action(&action_connect_advanced);
+
         // Action: save-preset
         let action_save = gio::SimpleAction::new("save-preset", None);
         action_save.connect_activate(glib::clone!(
@@ -905,6 +2230,17 @@ impl Window {
         ));
         self.add_action(&action_load);
 
+        // Action: preset-trash
+        let action_trash = gio::SimpleAction::new("preset-trash", None);
+        action_trash.connect_activate(glib::clone!(
+            #[weak(rename_to = window)]
+            self,
+            move |_, _| {
+                window.show_preset_trash_dialog();
+            }
+        ));
+        self.add_action(&action_trash);
+
         // Action: deactivate-preset
         let action_deactivate = gio::SimpleAction::new("deactivate-preset", None);
         action_deactivate.connect_activate(glib::clone!(
@@ -916,6 +2252,114 @@ impl Window {
         ));
         self.add_action(&action_deactivate);
 
+        // Action: background-service (stateful toggle)
+        let service_enabled = self.background_service_enabled();
+        let action_background_service = gio::SimpleAction::new_stateful(
+            "background-service",
+            None,
+            &service_enabled.to_variant(),
+        );
+        action_background_service.connect_activate(glib::clone!(
+            #[weak(rename_to = window)]
+            self,
+            move |action, _| {
+                let current = action
+                    .state()
+                    .and_then(|v| v.get::<bool>())
+                    .unwrap_or(false);
+                let new_state = !current;
+                action.set_state(&new_state.to_variant());
+                window.set_background_service(new_state);
+            }
+        ));
+        self.add_action(&action_background_service);
+
+        // Action: export-preset-wireplumber
+        let action_export_wireplumber = gio::SimpleAction::new("export-preset-wireplumber", None);
+        action_export_wireplumber.connect_activate(glib::clone!(
+            #[weak(rename_to = window)]
+            self,
+            move |_, _| {
+                window.show_export_wireplumber_dialog();
+            }
+        ));
+        self.add_action(&action_export_wireplumber);
+
+        // Action: export-config
+        let action_export_config = gio::SimpleAction::new("export-config", None);
+        action_export_config.connect_activate(glib::clone!(
+            #[weak(rename_to = window)]
+            self,
+            move |_, _| {
+                window.show_export_config_dialog();
+            }
+        ));
+        self.add_action(&action_export_config);
+
+        // Action: import-config
+        let action_import_config = gio::SimpleAction::new("import-config", None);
+        action_import_config.connect_activate(glib::clone!(
+            #[weak(rename_to = window)]
+            self,
+            move |_, _| {
+                window.show_import_config_dialog();
+            }
+        ));
+        self.add_action(&action_import_config);
+
+        // Action: settings-gsettings (stateful toggle, only with the schema installed)
+        let action_settings_gsettings = gio::SimpleAction::new_stateful(
+            "settings-gsettings",
+            None,
+            &gsettings::enabled().to_variant(),
+        );
+        action_settings_gsettings.set_enabled(gsettings::available());
+        action_settings_gsettings.connect_activate(glib::clone!(
+            #[weak(rename_to = window)]
+            self,
+            move |action, _| {
+                let current = action
+                    .state()
+                    .and_then(|v| v.get::<bool>())
+                    .unwrap_or(false);
+                let new_state = !current;
+                if window.set_settings_in_gsettings(new_state) {
+                    action.set_state(&new_state.to_variant());
+                }
+            }
+        ));
+        self.add_action(&action_settings_gsettings);
+
+        // Action: plug-presets
+        let action_plug_presets = gio::SimpleAction::new("plug-presets", None);
+        action_plug_presets.connect_activate(glib::clone!(
+            #[weak(rename_to = window)]
+            self,
+            move |_, _| {
+                window.show_plug_presets_dialog();
+            }
+        ));
+        self.add_action(&action_plug_presets);
+
+        // Action: show-tray (stateful toggle)
+        let show_tray = self.imp().settings.borrow().show_tray;
+        let action_show_tray =
+            gio::SimpleAction::new_stateful("show-tray", None, &show_tray.to_variant());
+        action_show_tray.connect_activate(glib::clone!(
+            #[weak(rename_to = window)]
+            self,
+            move |action, _| {
+                let current = action
+                    .state()
+                    .and_then(|v| v.get::<bool>())
+                    .unwrap_or(false);
+                let new_state = !current;
+                action.set_state(&new_state.to_variant());
+                window.set_show_tray(new_state);
+            }
+        ));
+        self.add_action(&action_show_tray);
+
         // Action: start-minimized (stateful toggle)
         let start_minimized = self.imp().settings.borrow().start_minimized;
         let action_start_minimized =
@@ -934,115 +2378,1205 @@ impl Window {
             }
         ));
         self.add_action(&action_start_minimized);
-    }
 
-    /// Connect the selected output port to the selected input port
-    fn connect_selected(&self) {
-        // Get all selected output ports
-        let output_ports: Vec<PortObject> = {
-            let selection = self.imp().output_selection.borrow();
-            match selection.as_ref() {
-                Some(s) => {
-                    let bitset = s.selection();
-                    let mut ports = Vec::new();
-                    let size = bitset.size();
-                    for i in 0..size {
-                        let idx = bitset.nth(i as u32);
-                        if let Some(port) = s.item(idx).and_downcast::<PortObject>() {
-                            ports.push(port);
-                        }
-                    }
-                    ports
-                }
-                None => Vec::new(),
+        // Action: replace-on-preset-switch (stateful toggle)
+        let replace_on_switch = self.imp().settings.borrow().replace_on_preset_switch;
+        let action_replace_on_switch = gio::SimpleAction::new_stateful(
+            "replace-on-preset-switch",
+            None,
+            &replace_on_switch.to_variant(),
+        );
+        action_replace_on_switch.connect_activate(glib::clone!(
+            #[weak(rename_to = window)]
+            self,
+            move |action, _| {
+                let current = action
+                    .state()
+                    .and_then(|v| v.get::<bool>())
+                    .unwrap_or(false);
+                let new_state = !current;
+                action.set_state(&new_state.to_variant());
+                window.set_replace_on_preset_switch(new_state);
             }
-        };
-
-        if output_ports.is_empty() {
-            self.announce("No output ports selected");
-            return;
-        }
+        ));
+        self.add_action(&action_replace_on_switch);
 
-        // Get all selected input ports
-        let input_ports: Vec<PortObject> = {
-            let selection = self.imp().input_selection.borrow();
-            match selection.as_ref() {
-                Some(s) => {
-                    let bitset = s.selection();
-                    let mut ports = Vec::new();
-                    let size = bitset.size();
-                    for i in 0..size {
-                        let idx = bitset.nth(i as u32);
-                        if let Some(port) = s.item(idx).and_downcast::<PortObject>() {
-                            ports.push(port);
-                        }
-                    }
-                    ports
-                }
-                None => Vec::new(),
+        // Action: tray-scroll-presets (stateful toggle)
+        let tray_scroll = self.imp().settings.borrow().tray_scroll_presets;
+        let action_tray_scroll =
+            gio::SimpleAction::new_stateful("tray-scroll-presets", None, &tray_scroll.to_variant());
+        action_tray_scroll.connect_activate(glib::clone!(
+            #[weak(rename_to = window)]
+            self,
+            move |action, _| {
+                let current = action
+                    .state()
+                    .and_then(|v| v.get::<bool>())
+                    .unwrap_or(false);
+                let new_state = !current;
+                action.set_state(&new_state.to_variant());
+                window.set_tray_scroll_presets(new_state);
             }
-        };
-
-        if input_ports.is_empty() {
-            self.announce("No input ports selected");
-            return;
-        }
+        ));
+        self.add_action(&action_tray_scroll);
 
-        // Connection modes:
-        // - 1 output to N inputs: connect that output to ALL inputs (e.g., mono to stereo)
+        // Action: tray-middle-click (stateful toggle)
+        let tray_middle_click = self.imp().settings.borrow().tray_middle_click_toggle;
+        let action_tray_middle_click = gio::SimpleAction::new_stateful(
+            "tray-middle-click",
+            None,
+            &tray_middle_click.to_variant(),
+        );
+        action_tray_middle_click.connect_activate(glib::clone!(
+            #[weak(rename_to = window)]
+            self,
+            move |action, _| {
+                let current = action
+                    .state()
+                    .and_then(|v| v.get::<bool>())
+                    .unwrap_or(false);
+                let new_state = !current;
+                action.set_state(&new_state.to_variant());
+                window.set_tray_middle_click_toggle(new_state);
+            }
+        ));
+        self.add_action(&action_tray_middle_click);
+
+        // Action: close-action (stateful radio, "ask", "tray" or "quit")
+        let close_action = self.imp().settings.borrow().close_action;
+        let action_close = gio::SimpleAction::new_stateful(
+            "close-action",
+            Some(glib::VariantTy::STRING),
+            &close_action.as_str().to_variant(),
+        );
+        action_close.connect_activate(glib::clone!(
+            #[weak(rename_to = window)]
+            self,
+            move |action, param| {
+                let value = param.and_then(|v| v.get::<String>()).unwrap_or_default();
+                if let Some(close_action) = CloseAction::from_name(&value) {
+                    action.set_state(&close_action.as_str().to_variant());
+                    window.set_close_action(close_action);
+                }
+            }
+        ));
+        self.add_action(&action_close);
+
+        // Action: link-passive (stateful toggle)
+        let link_passive = self.imp().settings.borrow().link_passive;
+        let action_link_passive =
+            gio::SimpleAction::new_stateful("link-passive", None, &link_passive.to_variant());
+        action_link_passive.connect_activate(glib::clone!(
+            #[weak(rename_to = window)]
+            self,
+            move |action, _| {
+                let current = action
+                    .state()
+                    .and_then(|v| v.get::<bool>())
+                    .unwrap_or(false);
+                let new_state = !current;
+                action.set_state(&new_state.to_variant());
+                window.set_link_passive(new_state);
+            }
+        ));
+        self.add_action(&action_link_passive);
+
+        // Action: link-linger (stateful toggle)
+        let link_linger = self.imp().settings.borrow().link_linger;
+        let action_link_linger =
+            gio::SimpleAction::new_stateful("link-linger", None, &link_linger.to_variant());
+        action_link_linger.connect_activate(glib::clone!(
+            #[weak(rename_to = window)]
+            self,
+            move |action, _| {
+                let current = action
+                    .state()
+                    .and_then(|v| v.get::<bool>())
+                    .unwrap_or(true);
+                let new_state = !current;
+                action.set_state(&new_state.to_variant());
+                window.set_link_linger(new_state);
+            }
+        ));
+        self.add_action(&action_link_linger);
+
+        // Action: temporary-mode (stateful toggle, not saved)
+        let action_temporary_mode =
+            gio::SimpleAction::new_stateful("temporary-mode", None, &false.to_variant());
+        action_temporary_mode.connect_activate(glib::clone!(
+            #[weak(rename_to = window)]
+            self,
+            move |action, _| {
+                let current = action
+                    .state()
+                    .and_then(|v| v.get::<bool>())
+                    .unwrap_or(false);
+                let new_state = !current;
+                action.set_state(&new_state.to_variant());
+                window.set_temporary_mode(new_state);
+            }
+        ));
+        self.add_action(&action_temporary_mode);
+
+        // Action: announcement-verbosity (stateful radio, "silent", "important" or "verbose")
+        let verbosity = self.imp().settings.borrow().announcement_verbosity;
+        let action_verbosity = gio::SimpleAction::new_stateful(
+            "announcement-verbosity",
+            Some(glib::VariantTy::STRING),
+            &verbosity.as_str().to_variant(),
+        );
+        action_verbosity.connect_activate(glib::clone!(
+            #[weak(rename_to = window)]
+            self,
+            move |action, param| {
+                let value = param.and_then(|v| v.get::<String>()).unwrap_or_default();
+                if let Some(verbosity) = Verbosity::from_name(&value) {
+                    action.set_state(&verbosity.as_str().to_variant());
+                    window.set_announcement_verbosity(verbosity);
+                }
+            }
+        ));
+        self.add_action(&action_verbosity);
+
+        // Action: startup-port-types (stateful radio, "last", "all" or "audio")
+        let startup_port_types = self.imp().settings.borrow().startup_port_types;
+        let action_startup_types = gio::SimpleAction::new_stateful(
+            "startup-port-types",
+            Some(glib::VariantTy::STRING),
+            &startup_port_types.as_str().to_variant(),
+        );
+        action_startup_types.connect_activate(glib::clone!(
+            #[weak(rename_to = window)]
+            self,
+            move |action, param| {
+                let value = param.and_then(|v| v.get::<String>()).unwrap_or_default();
+                if let Some(types) = StartupPortTypes::from_name(&value) {
+                    action.set_state(&types.as_str().to_variant());
+                    window.set_startup_port_types(types);
+                }
+            }
+        ));
+        self.add_action(&action_startup_types);
+
+        // Action: port-sort (stateful radio, "name", "app" or "recent")
+        let port_sort = self.imp().settings.borrow().port_sort;
+        let action_port_sort = gio::SimpleAction::new_stateful(
+            "port-sort",
+            Some(glib::VariantTy::STRING),
+            &port_sort.as_str().to_variant(),
+        );
+        action_port_sort.connect_activate(glib::clone!(
+            #[weak(rename_to = window)]
+            self,
+            move |action, param| {
+                let value = param.and_then(|v| v.get::<String>()).unwrap_or_default();
+                if let Some(sort) = PortSort::from_name(&value) {
+                    action.set_state(&sort.as_str().to_variant());
+                    window.set_port_sort(sort);
+                }
+            }
+        ));
+        self.add_action(&action_port_sort);
+
+        // Action: list-text-size (stateful radio, "normal", "large" or "largest")
+        let list_text_size = self.imp().settings.borrow().list_text_size;
+        let action_text_size = gio::SimpleAction::new_stateful(
+            "list-text-size",
+            Some(glib::VariantTy::STRING),
+            &list_text_size.as_str().to_variant(),
+        );
+        action_text_size.connect_activate(glib::clone!(
+            #[weak(rename_to = window)]
+            self,
+            move |action, param| {
+                let value = param.and_then(|v| v.get::<String>()).unwrap_or_default();
+                if let Some(size) = ListTextSize::from_name(&value) {
+                    action.set_state(&size.as_str().to_variant());
+                    window.set_list_text_size(size);
+                }
+            }
+        ));
+        self.add_action(&action_text_size);
+
+        // Action: reduce-motion (stateful toggle)
+        let reduce_motion = self.imp().settings.borrow().reduce_motion;
+        let action_reduce_motion =
+            gio::SimpleAction::new_stateful("reduce-motion", None, &reduce_motion.to_variant());
+        action_reduce_motion.connect_activate(glib::clone!(
+            #[weak(rename_to = window)]
+            self,
+            move |action, _| {
+                let current = action
+                    .state()
+                    .and_then(|v| v.get::<bool>())
+                    .unwrap_or(false);
+                window.set_reduce_motion(!current);
+            }
+        ));
+        self.add_action(&action_reduce_motion);
+
+        // Action: appearance (stateful radio, "system", "light" or "dark")
+        let appearance = self.imp().settings.borrow().appearance;
+        let action_appearance = gio::SimpleAction::new_stateful(
+            "appearance",
+            Some(glib::VariantTy::STRING),
+            &appearance.as_str().to_variant(),
+        );
+        action_appearance.connect_activate(glib::clone!(
+            #[weak(rename_to = window)]
+            self,
+            move |action, param| {
+                let value = param.and_then(|v| v.get::<String>()).unwrap_or_default();
+                if let Some(appearance) = Appearance::from_name(&value) {
+                    action.set_state(&appearance.as_str().to_variant());
+                    window.set_appearance(appearance);
+                }
+            }
+        ));
+        self.add_action(&action_appearance);
+
+        // Action: media-colors (stateful toggle)
+        let media_colors = self.imp().settings.borrow().media_colors;
+        let action_media_colors =
+            gio::SimpleAction::new_stateful("media-colors", None, &media_colors.to_variant());
+        action_media_colors.connect_activate(glib::clone!(
+            #[weak(rename_to = window)]
+            self,
+            move |action, _| {
+                let current = action
+                    .state()
+                    .and_then(|v| v.get::<bool>())
+                    .unwrap_or(false);
+                window.set_media_colors(!current);
+            }
+        ));
+        self.add_action(&action_media_colors);
+
+        // Action: swap-port-panes (stateful toggle)
+        let swap_panes = self.imp().settings.borrow().swap_port_panes;
+        let action_swap_panes =
+            gio::SimpleAction::new_stateful("swap-port-panes", None, &swap_panes.to_variant());
+        action_swap_panes.connect_activate(glib::clone!(
+            #[weak(rename_to = window)]
+            self,
+            move |action, _| {
+                let current = action
+                    .state()
+                    .and_then(|v| v.get::<bool>())
+                    .unwrap_or(false);
+                window.set_swap_port_panes(!current);
+            }
+        ));
+        self.add_action(&action_swap_panes);
+
+        // Action: stack-port-panes (stateful toggle)
+        let stack_panes = self.imp().settings.borrow().stack_port_panes;
+        let action_stack_panes =
+            gio::SimpleAction::new_stateful("stack-port-panes", None, &stack_panes.to_variant());
+        action_stack_panes.connect_activate(glib::clone!(
+            #[weak(rename_to = window)]
+            self,
+            move |action, _| {
+                let current = action
+                    .state()
+                    .and_then(|v| v.get::<bool>())
+                    .unwrap_or(false);
+                window.set_stack_port_panes(!current);
+            }
+        ));
+        self.add_action(&action_stack_panes);
+
+        // Action: resize-port-panes (percent the first port list grows by)
+        let action_resize_panes =
+            gio::SimpleAction::new("resize-port-panes", Some(glib::VariantTy::INT32));
+        action_resize_panes.connect_activate(glib::clone!(
+            #[weak(rename_to = window)]
+            self,
+            move |_, param| {
+                if let Some(delta) = param.and_then(|v| v.get::<i32>()) {
+                    window.resize_port_panes(delta);
+                }
+            }
+        ));
+        self.add_action(&action_resize_panes);
+
+        // Action: test-tone (stateful radio, "off", "sine" or "pink")
+        let action_test_tone = gio::SimpleAction::new_stateful(
+            "test-tone",
+            Some(glib::VariantTy::STRING),
+            &"off".to_variant(),
+        );
+        action_test_tone.connect_activate(glib::clone!(
+            #[weak(rename_to = window)]
+            self,
+            move |_, param| {
+                let value = param.and_then(|v| v.get::<String>()).unwrap_or_default();
+                // The state is updated once the PipeWire thread reports back
+                match TestSignal::from_name(&value) {
+                    Some(signal) => window.send_command(UiCommand::StartTestTone { signal }),
+                    None => window.send_command(UiCommand::StopTestTone),
+                }
+            }
+        ));
+        self.add_action(&action_test_tone);
+
+        // Action: connection-matrix
+        let action_matrix = gio::SimpleAction::new("connection-matrix", None);
+        action_matrix.connect_activate(glib::clone!(
+            #[weak(rename_to = window)]
+            self,
+            move |_, _| {
+                window.show_connection_matrix();
+            }
+        ));
+        self.add_action(&action_matrix);
+
+        // Action: compact-mode
+        let action_compact = gio::SimpleAction::new("compact-mode", None);
+        action_compact.connect_activate(glib::clone!(
+            #[weak(rename_to = window)]
+            self,
+            move |_, _| {
+                window.show_compact_window();
+            }
+        ));
+        self.add_action(&action_compact);
+
+        // Action: keep-above (stateful toggle, not saved)
+        let action_keep_above =
+            gio::SimpleAction::new_stateful("keep-above", None, &false.to_variant());
+        action_keep_above.connect_activate(glib::clone!(
+            #[weak(rename_to = window)]
+            self,
+            move |action, _| {
+                let current = action
+                    .state()
+                    .and_then(|v| v.get::<bool>())
+                    .unwrap_or(false);
+                let new_state = !current;
+                action.set_state(&new_state.to_variant());
+                window.set_keep_above(new_state);
+            }
+        ));
+        self.add_action(&action_keep_above);
+        self.restore_keep_above_on_map();
+
+        // Action: reconnect-last
+        let action_reconnect_last = gio::SimpleAction::new("reconnect-last", None);
+        action_reconnect_last.connect_activate(glib::clone!(
+            #[weak(rename_to = window)]
+            self,
+            move |_, _| {
+                window.reconnect_removed(0);
+            }
+        ));
+        self.add_action(&action_reconnect_last);
+
+        // Action: restore-session
+        let action_restore_session = gio::SimpleAction::new("restore-session", None);
+        action_restore_session.connect_activate(glib::clone!(
+            #[weak(rename_to = window)]
+            self,
+            move |_, _| {
+                window.restore_previous_session();
+            }
+        ));
+        action_restore_session.set_enabled(self.imp().restorable_session.borrow().is_some());
+        self.add_action(&action_restore_session);
+
+        // Action: reconnect-removed (index into the recently removed connections)
+        let action_reconnect_removed =
+            gio::SimpleAction::new("reconnect-removed", Some(glib::VariantTy::UINT32));
+        action_reconnect_removed.connect_activate(glib::clone!(
+            #[weak(rename_to = window)]
+            self,
+            move |_, param| {
+                if let Some(index) = param.and_then(|v| v.get::<u32>()) {
+                    window.reconnect_removed(index as usize);
+                }
+            }
+        ));
+        self.add_action(&action_reconnect_removed);
+
+        // Action: copy-connections
+        let action_copy_connections = gio::SimpleAction::new("copy-connections", None);
+        action_copy_connections.connect_activate(glib::clone!(
+            #[weak(rename_to = window)]
+            self,
+            move |_, _| {
+                window.copy_connections_json();
+            }
+        ));
+        self.add_action(&action_copy_connections);
+
+        // Action: paste-connections
+        let action_paste_connections = gio::SimpleAction::new("paste-connections", None);
+        action_paste_connections.connect_activate(glib::clone!(
+            #[weak(rename_to = window)]
+            self,
+            move |_, _| {
+                window.paste_connections_json();
+            }
+        ));
+        self.add_action(&action_paste_connections);
+
+        // Action: save-filter
+        let action_save_filter = gio::SimpleAction::new("save-filter", None);
+        action_save_filter.connect_activate(glib::clone!(
+            #[weak(rename_to = window)]
+            self,
+            move |_, _| {
+                window.show_save_filter_dialog();
+            }
+        ));
+        self.add_action(&action_save_filter);
+
+        // Action: saved-filters
+        let action_saved_filters = gio::SimpleAction::new("saved-filters", None);
+        action_saved_filters.connect_activate(glib::clone!(
+            #[weak(rename_to = window)]
+            self,
+            move |_, _| {
+                window.show_saved_filters_dialog();
+            }
+        ));
+        self.add_action(&action_saved_filters);
+
+        // Action: setup-assistant
+        let action_setup_assistant = gio::SimpleAction::new("setup-assistant", None);
+        action_setup_assistant.connect_activate(glib::clone!(
+            #[weak(rename_to = window)]
+            self,
+            move |_, _| {
+                window.show_setup_assistant();
+            }
+        ));
+        self.add_action(&action_setup_assistant);
+
+        // Action: device-profile
+        let action_device_profile = gio::SimpleAction::new("device-profile", None);
+        action_device_profile.connect_activate(glib::clone!(
+            #[weak(rename_to = window)]
+            self,
+            move |_, _| {
+                window.show_device_profile_dialog();
+            }
+        ));
+        self.add_action(&action_device_profile);
+
+        // Action: device-route
+        let action_device_route = gio::SimpleAction::new("device-route", None);
+        action_device_route.connect_activate(glib::clone!(
+            #[weak(rename_to = window)]
+            self,
+            move |_, _| {
+                window.show_device_route_dialog();
+            }
+        ));
+        self.add_action(&action_device_route);
+
+        // Action: midi-ports
+        let action_midi_ports = gio::SimpleAction::new("midi-ports", None);
+        action_midi_ports.connect_activate(glib::clone!(
+            #[weak(rename_to = window)]
+            self,
+            move |_, _| {
+                window.show_midi_view();
+            }
+        ));
+        self.add_action(&action_midi_ports);
+
+        // Action: graph-load
+        let action_graph_load = gio::SimpleAction::new("graph-load", None);
+        action_graph_load.connect_activate(glib::clone!(
+            #[weak(rename_to = window)]
+            self,
+            move |_, _| {
+                window.show_graph_load();
+            }
+        ));
+        self.add_action(&action_graph_load);
+
+        // Action: clock-settings
+        let action_clock_settings = gio::SimpleAction::new("clock-settings", None);
+        action_clock_settings.connect_activate(glib::clone!(
+            #[weak(rename_to = window)]
+            self,
+            move |_, _| {
+                window.show_clock_dialog();
+            }
+        ));
+        self.add_action(&action_clock_settings);
+
+        // Action: pipewire-server
+        let action_pipewire_server = gio::SimpleAction::new("pipewire-server", None);
+        action_pipewire_server.connect_activate(glib::clone!(
+            #[weak(rename_to = window)]
+            self,
+            move |_, _| {
+                window.show_pipewire_server_dialog();
+            }
+        ));
+        self.add_action(&action_pipewire_server);
+
+        // Action: troubleshooting
+        let action_troubleshooting = gio::SimpleAction::new("troubleshooting", None);
+        action_troubleshooting.connect_activate(glib::clone!(
+            #[weak(rename_to = window)]
+            self,
+            move |_, _| {
+                window.show_troubleshooting_dialog();
+            }
+        ));
+        self.add_action(&action_troubleshooting);
+
+        // Action: view-logs
+        let action_view_logs = gio::SimpleAction::new("view-logs", None);
+        action_view_logs.connect_activate(glib::clone!(
+            #[weak(rename_to = window)]
+            self,
+            move |_, _| {
+                window.show_log_viewer();
+            }
+        ));
+        self.add_action(&action_view_logs);
+
+        // Action: log-to-file (stateful toggle)
+        let log_to_file = self.imp().settings.borrow().log_to_file;
+        let action_log_to_file =
+            gio::SimpleAction::new_stateful("log-to-file", None, &log_to_file.to_variant());
+        action_log_to_file.connect_activate(glib::clone!(
+            #[weak(rename_to = window)]
+            self,
+            move |action, _| {
+                let current = action
+                    .state()
+                    .and_then(|v| v.get::<bool>())
+                    .unwrap_or(false);
+                let new_state = !current;
+                action.set_state(&new_state.to_variant());
+                window.set_log_to_file(new_state);
+            }
+        ));
+        self.add_action(&action_log_to_file);
+
+        // Action: event-debugger
+        let action_event_debugger = gio::SimpleAction::new("event-debugger", None);
+        action_event_debugger.connect_activate(glib::clone!(
+            #[weak(rename_to = window)]
+            self,
+            move |_, _| {
+                window.show_event_debugger();
+            }
+        ));
+        self.add_action(&action_event_debugger);
+
+        // Action: activity-history (stateful toggle showing the side panel)
+        let action_activity =
+            gio::SimpleAction::new_stateful("activity-history", None, &false.to_variant());
+        action_activity.connect_activate(glib::clone!(
+            #[weak(rename_to = window)]
+            self,
+            move |action, _| {
+                let visible = !action
+                    .state()
+                    .and_then(|v| v.get::<bool>())
+                    .unwrap_or(false);
+                action.set_state(&visible.to_variant());
+                window.set_activity_panel_visible(visible);
+            }
+        ));
+        self.add_action(&action_activity);
+
+        // Action: inspector (stateful toggle showing the property panel)
+        let action_inspector =
+            gio::SimpleAction::new_stateful("inspector", None, &false.to_variant());
+        action_inspector.connect_activate(glib::clone!(
+            #[weak(rename_to = window)]
+            self,
+            move |action, _| {
+                let visible = !action
+                    .state()
+                    .and_then(|v| v.get::<bool>())
+                    .unwrap_or(false);
+                action.set_state(&visible.to_variant());
+                window.set_inspector_visible(visible);
+            }
+        ));
+        self.add_action(&action_inspector);
+
+        // Action: record-events (stateful toggle)
+        let action_record_events =
+            gio::SimpleAction::new_stateful("record-events", None, &false.to_variant());
+        action_record_events.connect_activate(glib::clone!(
+            #[weak(rename_to = window)]
+            self,
+            move |action, _| {
+                let current = action
+                    .state()
+                    .and_then(|v| v.get::<bool>())
+                    .unwrap_or(false);
+                window.set_record_events(!current);
+            }
+        ));
+        self.add_action(&action_record_events);
+        if self.imp().settings.borrow().record_events {
+            self.set_event_recording(true);
+        }
+
+        // Action: control-socket (stateful toggle)
+        let control_socket = self.imp().settings.borrow().control_socket;
+        let action_control_socket =
+            gio::SimpleAction::new_stateful("control-socket", None, &control_socket.to_variant());
+        action_control_socket.connect_activate(glib::clone!(
+            #[weak(rename_to = window)]
+            self,
+            move |action, _| {
+                let current = action
+                    .state()
+                    .and_then(|v| v.get::<bool>())
+                    .unwrap_or(false);
+                let new_state = !current;
+                action.set_state(&new_state.to_variant());
+                window.set_control_socket(new_state);
+            }
+        ));
+        // Remote control reaches the main window only
+        action_control_socket.set_enabled(!self.is_server_window());
+        self.add_action(&action_control_socket);
+        if control_socket && !self.is_server_window() {
+            if let Err(e) = self.start_control_socket() {
+                log::error!("Failed to open the control socket: {}", e);
+            }
+        }
+
+        // Action: osc-server (stateful toggle)
+        let osc_server = self.imp().settings.borrow().osc_server;
+        let action_osc_server =
+            gio::SimpleAction::new_stateful("osc-server", None, &osc_server.to_variant());
+        action_osc_server.connect_activate(glib::clone!(
+            #[weak(rename_to = window)]
+            self,
+            move |action, _| {
+                let current = action
+                    .state()
+                    .and_then(|v| v.get::<bool>())
+                    .unwrap_or(false);
+                let new_state = !current;
+                action.set_state(&new_state.to_variant());
+                window.set_osc_server(new_state);
+            }
+        ));
+        action_osc_server.set_enabled(!self.is_server_window());
+        self.add_action(&action_osc_server);
+        if osc_server && !self.is_server_window() {
+            if let Err(e) = self.start_osc_server() {
+                log::error!("Failed to start the OSC server: {}", e);
+            }
+        }
+
+        // Action: osc-port
+        let action_osc_port = gio::SimpleAction::new("osc-port", None);
+        action_osc_port.connect_activate(glib::clone!(
+            #[weak(rename_to = window)]
+            self,
+            move |_, _| {
+                window.show_osc_port_dialog();
+            }
+        ));
+        self.add_action(&action_osc_port);
+
+        // Action: streaming-setup
+        let action_streaming_setup = gio::SimpleAction::new("streaming-setup", None);
+        action_streaming_setup.connect_activate(glib::clone!(
+            #[weak(rename_to = window)]
+            self,
+            move |_, _| {
+                window.show_streaming_setup_dialog();
+            }
+        ));
+        self.add_action(&action_streaming_setup);
+
+        // Action: also-send-to
+        let action_also_send = gio::SimpleAction::new("also-send-to", None);
+        action_also_send.connect_activate(glib::clone!(
+            #[weak(rename_to = window)]
+            self,
+            move |_, _| {
+                window.show_also_send_dialog();
+            }
+        ));
+        self.add_action(&action_also_send);
+
+        // Action: stop-sending-to
+        let action_stop_sending = gio::SimpleAction::new("stop-sending-to", None);
+        action_stop_sending.connect_activate(glib::clone!(
+            #[weak(rename_to = window)]
+            self,
+            move |_, _| {
+                window.show_stop_sending_dialog();
+            }
+        ));
+        self.add_action(&action_stop_sending);
+
+        // Action: disconnect-ports
+        let action_disconnect_ports = gio::SimpleAction::new("disconnect-ports", None);
+        action_disconnect_ports.connect_activate(glib::clone!(
+            #[weak(rename_to = window)]
+            self,
+            move |_, _| {
+                window.disconnect_selected_ports();
+            }
+        ));
+        self.add_action(&action_disconnect_ports);
+
+        // Action: remove-duplicate-links
+        let action_remove_duplicates = gio::SimpleAction::new("remove-duplicate-links", None);
+        action_remove_duplicates.connect_activate(glib::clone!(
+            #[weak(rename_to = window)]
+            self,
+            move |_, _| {
+                window.remove_duplicate_links();
+            }
+        ));
+        self.add_action(&action_remove_duplicates);
+
+        // Action: disconnect-nodes
+        let action_disconnect_nodes = gio::SimpleAction::new("disconnect-nodes", None);
+        action_disconnect_nodes.connect_activate(glib::clone!(
+            #[weak(rename_to = window)]
+            self,
+            move |_, _| {
+                window.disconnect_selected_nodes();
+            }
+        ));
+        self.add_action(&action_disconnect_nodes);
+
+        // Action: confirm-bulk-disconnect (stateful toggle)
+        let confirm_bulk = self.imp().settings.borrow().confirm_bulk_disconnect;
+        let action_confirm_bulk = gio::SimpleAction::new_stateful(
+            "confirm-bulk-disconnect",
+            None,
+            &confirm_bulk.to_variant(),
+        );
+        action_confirm_bulk.connect_activate(glib::clone!(
+            #[weak(rename_to = window)]
+            self,
+            move |action, _| {
+                let current = action
+                    .state()
+                    .and_then(|v| v.get::<bool>())
+                    .unwrap_or(true);
+                window.set_confirm_bulk_disconnect(!current);
+            }
+        ));
+        self.add_action(&action_confirm_bulk);
+
+        // Action: inhibit-suspend (stateful toggle)
+        let inhibit_suspend = self.imp().settings.borrow().inhibit_suspend;
+        let action_inhibit_suspend =
+            gio::SimpleAction::new_stateful("inhibit-suspend", None, &inhibit_suspend.to_variant());
+        action_inhibit_suspend.connect_activate(glib::clone!(
+            #[weak(rename_to = window)]
+            self,
+            move |action, _| {
+                let current = action
+                    .state()
+                    .and_then(|v| v.get::<bool>())
+                    .unwrap_or(true);
+                window.set_inhibit_suspend(!current);
+            }
+        ));
+        self.add_action(&action_inhibit_suspend);
+
+        // Action: new-combine
+        let action_new_combine = gio::SimpleAction::new("new-combine", None);
+        action_new_combine.connect_activate(glib::clone!(
+            #[weak(rename_to = window)]
+            self,
+            move |_, _| {
+                window.show_new_combine_dialog();
+            }
+        ));
+        self.add_action(&action_new_combine);
+
+        // Action: new-echo-cancel
+        let action_new_echo_cancel = gio::SimpleAction::new("new-echo-cancel", None);
+        action_new_echo_cancel.connect_activate(glib::clone!(
+            #[weak(rename_to = window)]
+            self,
+            move |_, _| {
+                window.show_new_echo_cancel_dialog();
+            }
+        ));
+        self.add_action(&action_new_echo_cancel);
+
+        // Action: insert-filter
+        let action_insert_filter = gio::SimpleAction::new("insert-filter", None);
+        action_insert_filter.connect_activate(glib::clone!(
+            #[weak(rename_to = window)]
+            self,
+            move |_, _| {
+                window.show_insert_filter_dialog();
+            }
+        ));
+        self.add_action(&action_insert_filter);
+
+        // Action: connection-volume
+        let action_connection_volume = gio::SimpleAction::new("connection-volume", None);
+        action_connection_volume.connect_activate(glib::clone!(
+            #[weak(rename_to = window)]
+            self,
+            move |_, _| {
+                window.show_connection_volume_dialog();
+            }
+        ));
+        self.add_action(&action_connection_volume);
+
+        // Action: manage-filters
+        let action_manage_filters = gio::SimpleAction::new("manage-filters", None);
+        action_manage_filters.connect_activate(glib::clone!(
+            #[weak(rename_to = window)]
+            self,
+            move |_, _| {
+                window.show_manage_filters_dialog();
+            }
+        ));
+        self.add_action(&action_manage_filters);
+
+        // Action: receive-network-audio
+        let action_receive_network = gio::SimpleAction::new("receive-network-audio", None);
+        action_receive_network.connect_activate(glib::clone!(
+            #[weak(rename_to = window)]
+            self,
+            move |_, _| {
+                window.show_receive_network_audio_dialog();
+            }
+        ));
+        self.add_action(&action_receive_network);
+
+        // Action: new-tunnel
+        let action_new_tunnel = gio::SimpleAction::new("new-tunnel", None);
+        action_new_tunnel.connect_activate(glib::clone!(
+            #[weak(rename_to = window)]
+            self,
+            move |_, _| {
+                window.show_new_tunnel_dialog();
+            }
+        ));
+        self.add_action(&action_new_tunnel);
+
+        // Action: vban
+        let action_vban = gio::SimpleAction::new("vban", None);
+        action_vban.connect_activate(glib::clone!(
+            #[weak(rename_to = window)]
+            self,
+            move |_, _| {
+                window.show_vban_dialog();
+            }
+        ));
+        self.add_action(&action_vban);
+
+        // Action: airplay
+        let action_airplay = gio::SimpleAction::new("airplay", None);
+        action_airplay.connect_activate(glib::clone!(
+            #[weak(rename_to = window)]
+            self,
+            move |_, _| {
+                window.show_airplay_dialog();
+            }
+        ));
+        self.add_action(&action_airplay);
+
+        // Action: manage-virtual-devices
+        let action_manage_devices = gio::SimpleAction::new("manage-virtual-devices", None);
+        action_manage_devices.connect_activate(glib::clone!(
+            #[weak(rename_to = window)]
+            self,
+            move |_, _| {
+                window.show_manage_virtual_devices_dialog();
+            }
+        ));
+        self.add_action(&action_manage_devices);
+    }
+
+    /// Get the selected ports of the output or input list
+    fn selected_ports(&self, is_output: bool) -> Vec<PortObject> {
+        let selection = if is_output {
+            self.imp().output_selection.borrow()
+        } else {
+            self.imp().input_selection.borrow()
+        };
+
+        match selection.as_ref() {
+            Some(s) => {
+                let bitset = s.selection();
+                let mut ports = Vec::new();
+                let size = bitset.size();
+                for i in 0..size {
+                    let idx = bitset.nth(i as u32);
+                    if let Some(port) = s.item(idx).and_downcast::<PortObject>() {
+                        ports.push(port);
+                    }
+                }
+                ports
+            }
+            None => Vec::new(),
+        }
+    }
+
+    /// Connect the selected output port to the selected input port
+    fn connect_selected(&self) {
+        let options = self.default_link_options();
+        self.connect_selected_with_options(options);
+    }
+
+    /// The (output, input) port pairs that connecting the selected ports
+    /// would link. Announces and returns None when either side is empty.
+    fn selected_pairs(&self) -> Option<Vec<(u32, u32)>> {
+        // Get all selected output ports
+        let output_ports = self.selected_ports(true);
+
+        if output_ports.is_empty() {
+            self.announce("No output ports selected");
+            return None;
+        }
+
+        // Get all selected input ports
+        let input_ports = self.selected_ports(false);
+
+        if input_ports.is_empty() {
+            self.announce("No input ports selected");
+            return None;
+        }
+
+        // Connection modes:
+        // - 1 output to N inputs: connect that output to ALL inputs (e.g., mono to stereo)
         // - N outputs to 1 input: connect ALL outputs to that input (e.g., mix down)
         // - N outputs to N inputs: connect pairwise by position (e.g., stereo to stereo)
-        let mut count = 0;
+        let mut pairs = Vec::new();
+
+        if output_ports.len() == 1 {
+            // One output to multiple inputs
+            let output = &output_ports[0];
+            for input in &input_ports {
+                pairs.push((output.id(), input.id()));
+            }
+        } else if input_ports.len() == 1 {
+            // Multiple outputs to one input
+            let input = &input_ports[0];
+            for output in &output_ports {
+                pairs.push((output.id(), input.id()));
+            }
+        } else {
+            // Pairwise connection
+            for (output, input) in output_ports.iter().zip(&input_ports) {
+                pairs.push((output.id(), input.id()));
+            }
+        }
+
+        Some(pairs)
+    }
+
+    /// Connect the selected ports using the given link options
+    fn connect_selected_with_options(&self, options: LinkOptions) {
+        let Some(pairs) = self.selected_pairs() else {
+            return;
+        };
+        self.warn_video_incompatibility(&pairs);
+
+        let count = pairs.len();
+        let mut request_ids = Vec::new();
+        for (output_id, input_id) in pairs {
+            let request_id = self.create_link_with_options(output_id, input_id, options);
+            self.remember_created_link(output_id, input_id);
+            if count == 1 {
+                // A single connection gets a toast with Undo once it exists
+                self.imp()
+                    .toast_link_requests
+                    .borrow_mut()
+                    .insert(request_id, (output_id, input_id));
+            }
+            request_ids.push(request_id);
+        }
+
+        if count > 1 {
+            self.begin_bulk_operation(
+                BulkOperation::new(
+                    "Connecting".to_string(),
+                    "Created connections".to_string(),
+                    request_ids,
+                    0,
+                )
+                .with_toast(),
+            );
+        }
+    }
+
+    /// Create a link between two ports using the default link options.
+    /// Returns the id of the request.
+    fn create_link(&self, output_port_id: u32, input_port_id: u32) -> u64 {
+        let options = self.default_link_options();
+        self.create_link_with_options(output_port_id, input_port_id, options)
+    }
+
+    /// Options for links created without asking. Temporary mode overrides
+    /// the saved linger setting so nothing outlives the session.
+    fn default_link_options(&self) -> LinkOptions {
+        let mut options = self.imp().settings.borrow().default_link_options();
+        if self.imp().temporary_mode.get() {
+            options.linger = false;
+        }
+        options
+    }
+
+    /// Create a link between two ports. The pair stays pending until the
+    /// PipeWire thread reports the outcome or `LINK_REQUEST_TIMEOUT` passes.
+    /// Returns the id of the request.
+    fn create_link_with_options(
+        &self,
+        output_port_id: u32,
+        input_port_id: u32,
+        options: LinkOptions,
+    ) -> u64 {
+        let request_id = self.imp().next_link_request_id.get();
+        self.imp().next_link_request_id.set(request_id + 1);
+        self.imp()
+            .pending_links
+            .borrow_mut()
+            .insert((output_port_id, input_port_id), request_id);
+
+        glib::timeout_add_local_once(
+            LINK_REQUEST_TIMEOUT,
+            glib::clone!(
+                #[weak(rename_to = window)]
+                self,
+                move || {
+                    window
+                        .imp()
+                        .toast_link_requests
+                        .borrow_mut()
+                        .remove(&request_id);
+                    window.control_link_finished(
+                        request_id,
+                        Err("The link did not appear in time".to_string()),
+                    );
+                    window.bulk_request_finished(request_id, false);
+                    window.preset_request_finished(
+                        request_id,
+                        Err("The link did not appear in time"),
+                    );
+                    if window.finish_link_request(request_id) {
+                        log::warn!(
+                            "Link {} -> {} did not appear, no longer pending",
+                            output_port_id,
+                            input_port_id
+                        );
+                    }
+                }
+            ),
+        );
+
+        self.send_command(UiCommand::CreateLink {
+            request_id,
+            output_port_id,
+            input_port_id,
+            options,
+        });
+        request_id
+    }
 
-        if output_ports.len() == 1 {
-            // One output to multiple inputs
-            let output = &output_ports[0];
-            for input in &input_ports {
-                self.create_link(output.id(), input.id());
-                count += 1;
-            }
-        } else if input_ports.len() == 1 {
-            // Multiple outputs to one input
-            let input = &input_ports[0];
-            for output in &output_ports {
-                self.create_link(output.id(), input.id());
-                count += 1;
+    /// Forget a link request. Returns whether it was still pending.
+    fn finish_link_request(&self, request_id: u64) -> bool {
+        let mut pending = self.imp().pending_links.borrow_mut();
+        let before = pending.len();
+        pending.retain(|_, id| *id != request_id);
+        before != pending.len()
+    }
+
+    /// Connect every output port of one node to the matching input ports of another.
+    /// Ports are paired by channel name (FL to FL), falling back to port order.
+    /// Returns the number of links requested.
+    fn connect_nodes(&self, output_node_id: u32, input_node_id: u32) -> usize {
+        let pairs: Vec<(u32, u32)> = {
+            let pw_state = self.imp().pw_state.borrow();
+            let mut outputs: Vec<_> = pw_state
+                .get_node_ports(output_node_id)
+                .filter(|p| p.direction == PortDirection::Output)
+                .collect();
+            let mut inputs: Vec<_> = pw_state
+                .get_node_ports(input_node_id)
+                .filter(|p| p.direction == PortDirection::Input)
+                .collect();
+            outputs.sort_by_key(|p| p.id);
+            inputs.sort_by_key(|p| p.id);
+
+            if outputs.is_empty() || inputs.is_empty() {
+                return 0;
             }
-        } else {
-            // Pairwise connection
-            let pairs = output_ports.len().min(input_ports.len());
-            for i in 0..pairs {
-                self.create_link(output_ports[i].id(), input_ports[i].id());
-                count += 1;
+
+            let mut pairs = Vec::new();
+            if outputs.len() == 1 {
+                // Mono sources feed every input
+                for inp in &inputs {
+                    pairs.push((outputs[0].id, inp.id));
+                }
+            } else {
+                for (i, out) in outputs.iter().enumerate() {
+                    let inp = out
+                        .channel
+                        .as_ref()
+                        .and_then(|channel| {
+                            inputs.iter().find(|inp| inp.channel.as_ref() == Some(channel))
+                        })
+                        .unwrap_or(&inputs[i.min(inputs.len() - 1)]);
+                    pairs.push((out.id, inp.id));
+                }
             }
-        }
 
-        if count > 1 {
-            self.announce(&format!("Created {} connections", count));
-        }
-    }
+            pairs.retain(|&(out, inp)| !pw_state.link_exists(out, inp));
+            pairs
+        };
 
-    /// Create a link between two ports
-    fn create_link(&self, output_port_id: u32, input_port_id: u32) {
-        if let Some(tx) = self.imp().command_tx.borrow().as_ref() {
-            let cmd = UiCommand::CreateLink {
-                output_port_id,
-                input_port_id,
-            };
-            if let Err(e) = tx.send_blocking(cmd) {
-                log::error!("Failed to send create link command: {}", e);
-            }
+        for &(output_id, input_id) in &pairs {
+            self.create_link(output_id, input_id);
         }
+        pairs.len()
     }
 
     /// Delete a link
     fn delete_link(&self, link_id: u32) {
-        if let Some(tx) = self.imp().command_tx.borrow().as_ref() {
-            let cmd = UiCommand::DeleteLink { link_id };
-            if let Err(e) = tx.send_blocking(cmd) {
-                log::error!("Failed to send delete link command: {}", e);
-            }
+        self.track_deleted_link(link_id);
+        let request_id = self.imp().next_link_request_id.get();
+        self.imp().next_link_request_id.set(request_id + 1);
+        self.imp()
+            .delete_link_requests
+            .borrow_mut()
+            .insert(request_id, link_id);
+        self.send_command(UiCommand::DeleteLink {
+            request_id,
+            link_id,
+        });
+    }
+
+    /// Handle the result of a command sent with a request id. A failed
+    /// delete leaves its link in place, so it is no longer expected to go.
+    fn command_finished(&self, request_id: u64, error: Option<String>) {
+        let link_id = self
+            .imp()
+            .delete_link_requests
+            .borrow_mut()
+            .remove(&request_id);
+        let Some(message) = error else {
+            return;
+        };
+        log::error!("Request {} failed: {}", request_id, message);
+        if let Some(link_id) = link_id {
+            self.imp().deleting_links.borrow_mut().remove(&link_id);
+            self.imp().pending_delete_position.take();
         }
+        self.show_toast(&message);
+        self.announce_error(&message);
     }
 
     /// Delete the currently selected connection
@@ -1063,104 +3597,202 @@ impl Window {
             self.imp().pending_delete_position.replace(Some(selected_pos));
 
             // Delete the link (async - will trigger LinkRemoved event)
-            self.delete_link(link.id());
+            self.delete_link_remembered(link.id());
+
+            // The row stands for any duplicates too
+            let duplicates = self
+                .imp()
+                .pw_state
+                .borrow()
+                .links_between(link.output_port_id(), link.input_port_id());
+            for duplicate in duplicates.into_iter().filter(|&id| id != link.id()) {
+                self.delete_link(duplicate);
+            }
+
+            let (output_port_id, input_port_id) = (link.output_port_id(), link.input_port_id());
+            self.show_undo_toast(
+                &format!("Disconnected {}", link.display_label()),
+                move |window| {
+                    window.create_link(output_port_id, input_port_id);
+                },
+            );
         }
     }
 
-    /// Apply current filters to the port lists
-    fn apply_filters(&self) {
-        let search_text = self.imp().search_text.borrow().to_lowercase();
-        let show_audio = *self.imp().show_audio.borrow();
-        let show_midi = *self.imp().show_midi.borrow();
-        let show_video = *self.imp().show_video.borrow();
-
-        // Create a filter function that captures the current filter state
-        let filter_fn = move |obj: &glib::Object| -> bool {
-            let port = match obj.downcast_ref::<PortObject>() {
-                Some(p) => p,
-                None => return false,
-            };
-
-            // Check media type filter
-            let media_type = port.media_type();
-            let media_ok = match media_type.as_str() {
-                "audio" => show_audio,
-                "midi" => show_midi,
-                "video" => show_video,
-                _ => true, // Show unknown types
-            };
+    /// Relabel ports that arrived before their node, now that it exists
+    fn resolve_orphan_ports(&self, node_id: u32) {
+        let adopted = self.imp().pw_state.borrow_mut().adopt_orphan_ports(node_id);
+        if adopted.is_empty() {
+            return;
+        }
 
-            if !media_ok {
-                return false;
+        for port in &adopted {
+            if let Some(port_obj) = self.find_port_object(port.id) {
+                port_obj.set_media_type(port.media_type.as_str());
             }
+        }
+        self.refresh_node_labels(node_id);
+        self.update_filter_counts();
 
-            // Check search text filter
-            if !search_text.is_empty() {
-                let label = port.display_label().to_lowercase();
-                let node_name = port.node_name().to_lowercase();
-                if !label.contains(&search_text) && !node_name.contains(&search_text) {
-                    return false;
-                }
-            }
+        // The real node name may complete preset connections
+        self.check_auto_connect();
+        self.check_pending_routes(node_id);
+    }
+
+    /// Find a port in either list by ID
+    fn find_port_object(&self, id: u32) -> Option<PortObject> {
+        if let Some(port) = self.pending_port(id) {
+            return Some(port);
+        }
+        [&self.imp().output_ports, &self.imp().input_ports]
+            .into_iter()
+            .flat_map(|store| (0..store.n_items()).filter_map(move |i| store.item(i)))
+            .filter_map(|item| item.downcast::<PortObject>().ok())
+            .find(|port| port.id() == id)
+    }
 
-            true
+    /// Rebuild the labels of a node's ports and of links touching them
+    fn refresh_node_labels(&self, node_id: u32) {
+        if self.unlist_midi_monitor_ports(node_id) {
+            return;
+        }
+        let pw_state = self.imp().pw_state.borrow();
+        let Some(node) = pw_state.nodes.get(&node_id) else {
+            return;
         };
+        let node_name = pw_state.node_label(node);
+        let app_name = node.app_name().to_string();
+
+        // Ports not yet in the lists are sorted and filtered once added
+        for port in self.imp().pending_ports.borrow().iter() {
+            if port.node_id() == node_id {
+                port.update_node_name(&node_name);
+                port.set_app_name(app_name.as_str());
+            }
+        }
 
-        // Update output filter
-        if let Some(filter) = self.imp().output_filter.borrow().as_ref() {
-            filter.set_filter_func(filter_fn.clone());
+        for store in [&self.imp().output_ports, &self.imp().input_ports] {
+            for i in 0..store.n_items() {
+                let Some(port) = store.item(i).and_downcast::<PortObject>() else {
+                    continue;
+                };
+                if port.node_id() == node_id {
+                    port.update_node_name(&node_name);
+                    port.set_app_name(app_name.as_str());
+                    // Let the filter, sorter and list rows pick up the change
+                    store.items_changed(i, 1, 1);
+                }
+            }
         }
 
-        // Update input filter
-        if let Some(filter) = self.imp().input_filter.borrow().as_ref() {
-            filter.set_filter_func(filter_fn);
+        let links = &self.imp().links;
+        for i in 0..links.n_items() {
+            let Some(link) = links.item(i).and_downcast::<LinkObject>() else {
+                continue;
+            };
+            let on_node = |port_id| {
+                pw_state
+                    .ports
+                    .get(&port_id)
+                    .is_some_and(|p| p.node_id == node_id)
+            };
+            if !on_node(link.output_port_id()) && !on_node(link.input_port_id()) {
+                continue;
+            }
+            let output_label = pw_state
+                .port_label(link.output_port_id())
+                .unwrap_or_else(|| link.output_label());
+            let input_label = pw_state
+                .port_label(link.input_port_id())
+                .unwrap_or_else(|| link.input_label());
+            link.update_labels(&output_label, &input_label);
+            links.items_changed(i, 1, 1);
         }
     }
 
     /// Remove a port from the lists by ID
     fn remove_port_from_lists(&self, id: u32) {
-        // Remove from output ports
-        for i in 0..self.imp().output_ports.n_items() {
-            if let Some(port) = self.imp().output_ports.item(i).and_downcast::<PortObject>() {
-                if port.id() == id {
-                    self.imp().output_ports.remove(i);
-                    return;
-                }
-            }
+        if self.forget_pending_port(id) {
+            return;
         }
+        let imp = self.imp();
+        let lists = [
+            (
+                &imp.output_ports,
+                &imp.output_selection,
+                &imp.output_list_view,
+            ),
+            (&imp.input_ports, &imp.input_selection, &imp.input_list_view),
+        ];
+        for (store, selection, list_view) in lists {
+            for i in 0..store.n_items() {
+                let Some(port) = store.item(i) else {
+                    continue;
+                };
+                if port.downcast_ref::<PortObject>().map(|p| p.id()) != Some(id) {
+                    continue;
+                }
 
-        // Remove from input ports
-        for i in 0..self.imp().input_ports.n_items() {
-            if let Some(port) = self.imp().input_ports.item(i).and_downcast::<PortObject>() {
-                if port.id() == id {
-                    self.imp().input_ports.remove(i);
-                    return;
+                // Keep focus in the list if it was on the removed row
+                let list_view = list_view.borrow().clone();
+                let refocus = list_view
+                    .filter(|list_view| self.focus_in_list(list_view))
+                    .zip(
+                        selection
+                            .borrow()
+                            .as_ref()
+                            .and_then(|selection| focus::position_in(selection, &port)),
+                    );
+                store.remove(i);
+                if let Some((list_view, position)) = refocus {
+                    self.refocus_list_after_removal(&list_view, position);
                 }
+                return;
             }
         }
     }
 
     /// Remove a link from the list by ID
     fn remove_link_from_list(&self, id: u32) {
+        if self.unmerge_duplicate_link(id) {
+            return;
+        }
+        self.imp().link_objects.borrow_mut().remove(&id);
         let n_items = self.imp().links.n_items();
         for i in 0..n_items {
             if let Some(link) = self.imp().links.item(i).and_downcast::<LinkObject>() {
                 if link.id() == id {
-                    // Check if this was a user-initiated delete (pending position set)
-                    let was_user_delete = self.imp().pending_delete_position.take().is_some();
+                    // Position in the connections list if this was a user-initiated delete
+                    let deleted_pos = self.imp().pending_delete_position.take();
+                    let list_view = self.imp().connections_list_view.borrow().clone();
+                    let refocus = list_view
+                        .filter(|list_view| self.focus_in_list(list_view))
+                        .zip(self.imp().connections_selection.borrow().as_ref().and_then(
+                            |selection| focus::position_in(selection, link.upcast_ref()),
+                        ));
 
                     // Remove the item
                     self.imp().links.remove(i);
 
-                    // Restore selection and focus if this was user-initiated delete
-                    if was_user_delete && n_items > 1 {
-                        let new_pos = if i >= n_items - 1 {
-                            // Was last item, select new last
-                            i.saturating_sub(1)
-                        } else {
-                            // Select same position (next item slid into place)
-                            i
-                        };
+                    // Restore selection and focus if this was user-initiated delete.
+                    // The list is filtered and grouped, so work in its positions
+                    // rather than the store's.
+                    let remaining = self
+                        .imp()
+                        .connections_selection
+                        .borrow()
+                        .as_ref()
+                        .map(|s| s.n_items())
+                        .unwrap_or(0);
+                    if deleted_pos.is_none() {
+                        // Removed by something else; focus may have been on it
+                        if let Some((list_view, position)) = refocus {
+                            self.refocus_list_after_removal(&list_view, position);
+                        }
+                    }
+                    if let Some(deleted_pos) = deleted_pos.filter(|_| remaining > 0) {
+                        // Select same position (next item slid into place), or the new last
+                        let new_pos = deleted_pos.min(remaining - 1);
 
                         // Set selection immediately
                         if let Some(selection) = self.imp().connections_selection.borrow().as_ref() {
@@ -1187,8 +3819,13 @@ impl Window {
         }
     }
 
-    /// Update status with counts
+    /// Update status with counts, or the progress of a bulk operation
     fn update_status_counts(&self) {
+        self.update_filter_counts();
+        if let Some(progress) = self.bulk_progress() {
+            self.update_status(&progress, true);
+            return;
+        }
         let state = self.imp().pw_state.borrow();
         let msg = format!(
             "Connected | {} nodes | {} ports | {} links",
@@ -1220,33 +3857,67 @@ impl Window {
         }
     }
 
-    /// Announce a message to screen readers
+    /// Announce a message to screen readers, unless announcements are silenced
     fn announce(&self, message: &str) {
+        self.announce_at(Verbosity::Important, message);
+    }
+
+    /// Announce background activity, only at the verbose level
+    fn announce_verbose(&self, message: &str) {
+        self.announce_at(Verbosity::Verbose, message);
+    }
+
+    /// Announce a message if the verbosity setting is at least `level`
+    fn announce_at(&self, level: Verbosity, message: &str) {
         use gtk::AccessibleAnnouncementPriority;
-        self.announce_with_priority(message, AccessibleAnnouncementPriority::Medium);
+        if self.imp().settings.borrow().announcement_verbosity >= level {
+            self.announce_with_priority(message, AccessibleAnnouncementPriority::Medium);
+        }
+    }
+
+    /// Announce an error, whatever the verbosity setting
+    fn announce_error(&self, message: &str) {
+        self.announce_with_priority(message, gtk::AccessibleAnnouncementPriority::High);
     }
 
-    /// Announce a message to screen readers with a specific priority
+    /// Announce a message to screen readers with a specific priority. Made
+    /// from the content, which a server's window shows in a tab of the main
+    /// window while staying hidden itself.
     fn announce_with_priority(&self, message: &str, priority: gtk::AccessibleAnnouncementPriority) {
         use gtk::prelude::AccessibleExt;
-        self.upcast_ref::<gtk::Widget>().announce(message, priority);
+        self.imp()
+            .toast_overlay
+            .upcast_ref::<gtk::Widget>()
+            .announce(message, priority);
     }
 
     /// Show dialog to save current connections as a preset
     fn show_save_preset_dialog(&self) {
         let dialog = adw::MessageDialog::builder()
-            .transient_for(self)
+            .transient_for(&self.dialog_parent())
             .modal(true)
             .heading("Save Preset")
             .body("Enter a name for this connection preset:")
             .build();
 
+        let content = gtk::Box::builder()
+            .orientation(gtk::Orientation::Vertical)
+            .spacing(12)
+            .build();
+
         // Add entry for preset name
         let entry = gtk::Entry::builder()
             .placeholder_text("Preset name")
             .activates_default(true)
             .build();
-        dialog.set_extra_child(Some(&entry));
+        entry.update_property(&[gtk::accessible::Property::Label("Preset name")]);
+        content.append(&entry);
+
+        let include_routes = gtk::CheckButton::builder()
+            .label("Include device routes (e.g. headphones or speakers)")
+            .build();
+        content.append(&include_routes);
+        dialog.set_extra_child(Some(&content));
 
         dialog.add_response("cancel", "Cancel");
         dialog.add_response("save", "Save");
@@ -1261,6 +3932,8 @@ impl Window {
                 self,
                 #[weak]
                 entry,
+                #[weak]
+                include_routes,
                 move |dialog, response| {
                     dialog.close();
                     if response == "save" {
@@ -1269,7 +3942,7 @@ impl Window {
                             window.announce("Preset name cannot be empty");
                             return;
                         }
-                        window.save_preset(&name);
+                        window.save_preset(&name, include_routes.is_active());
                     }
                 }
             ),
@@ -1279,26 +3952,20 @@ impl Window {
         entry.grab_focus();
     }
 
-    /// Save current connections as a preset
-    fn save_preset(&self, name: &str) {
+    /// Save current connections as a preset, optionally with the routes of
+    /// the devices they use
+    fn save_preset(&self, name: &str, include_routes: bool) {
+        if self.is_server_window() {
+            self.announce(PRESETS_MAIN_SERVER_ONLY);
+            return;
+        }
+
         let connections: Vec<PresetConnection> = {
             let pw_state = self.imp().pw_state.borrow();
             pw_state
                 .links
                 .values()
-                .filter_map(|link| {
-                    let output_port = pw_state.ports.get(&link.output_port_id)?;
-                    let input_port = pw_state.ports.get(&link.input_port_id)?;
-                    let output_node = pw_state.nodes.get(&output_port.node_id)?;
-                    let input_node = pw_state.nodes.get(&input_port.node_id)?;
-
-                    Some(PresetConnection {
-                        output_node: output_node.name.clone(),
-                        output_port: output_port.name.clone(),
-                        input_node: input_node.name.clone(),
-                        input_port: input_port.name.clone(),
-                    })
-                })
+                .filter_map(|link| PresetConnection::from_link(&pw_state, link))
                 .collect()
         };
 
@@ -1307,9 +3974,25 @@ impl Window {
             return;
         }
 
+        let routes = if include_routes {
+            self.current_preset_routes()
+        } else {
+            Vec::new()
+        };
+        // Saving over a preset keeps its key
+        let accelerator = self
+            .imp()
+            .preset_store
+            .borrow()
+            .get_preset(name)
+            .and_then(|p| p.accelerator.clone());
         let preset = Preset {
             name: name.to_string(),
             connections,
+            routes,
+            accelerator,
+            // New connections start new statistics
+            stats: Default::default(),
         };
 
         let count = preset.connections.len();
@@ -1320,6 +4003,7 @@ impl Window {
         } else {
             self.announce(&format!("Saved preset \"{}\" with {} connections", name, count));
         }
+        self.sync_compact_window();
     }
 
     /// Show dialog to load a preset
@@ -1333,7 +4017,7 @@ impl Window {
         }
 
         let dialog = adw::MessageDialog::builder()
-            .transient_for(self)
+            .transient_for(&self.dialog_parent())
             .modal(true)
             .heading("Manage Presets")
             .body("Select a preset. Use 'Activate' for auto-connect or 'Load' for one-time.")
@@ -1347,9 +4031,20 @@ impl Window {
 
         for name in &preset_names {
             let is_active = active_preset.as_deref() == Some(name.as_str());
+            let mut subtitle = Vec::new();
+            if is_active {
+                subtitle.push("Active (auto-connecting)".to_string());
+            }
+            if let Some(preset) = self.imp().preset_store.borrow().get_preset(name) {
+                if let Some(label) = preset.accelerator.as_deref().and_then(shortcuts::accel_label)
+                {
+                    subtitle.push(format!("Shortcut {}", label));
+                }
+                subtitle.push(preset.stats.summary());
+            }
             let row = adw::ActionRow::builder()
                 .title(name)
-                .subtitle(if is_active { "Active (auto-connecting)" } else { "" })
+                .subtitle(subtitle.join("\n"))
                 .activatable(true)
                 .build();
 
@@ -1381,6 +4076,9 @@ impl Window {
 
         dialog.add_response("cancel", "Cancel");
         dialog.add_response("delete", "Delete");
+        dialog.add_response("rename", "Rename...");
+        dialog.add_response("duplicate", "Duplicate...");
+        dialog.add_response("shortcut", "Shortcut...");
         dialog.add_response("load", "Load Once");
         dialog.add_response("activate", "Activate");
         dialog.set_response_appearance("delete", adw::ResponseAppearance::Destructive);
@@ -1423,6 +4121,24 @@ impl Window {
                                 window.load_preset(&name);
                             }
                         }
+                        "rename" => {
+                            dialog.close();
+                            if let Some(name) = selected_name {
+                                window.show_rename_preset_dialog(&name);
+                            }
+                        }
+                        "shortcut" => {
+                            dialog.close();
+                            if let Some(name) = selected_name {
+                                window.show_preset_accel_dialog(&name);
+                            }
+                        }
+                        "duplicate" => {
+                            dialog.close();
+                            if let Some(name) = selected_name {
+                                window.show_duplicate_preset_dialog(&name);
+                            }
+                        }
                         "delete" => {
                             if let Some(name) = selected_name.clone() {
                                 window.delete_preset(&name);
@@ -1470,6 +4186,29 @@ impl Window {
             }
         };
 
+        self.apply_preset_routes(&preset.routes);
+        let (request_ids, skipped) = self.create_missing_links(&preset.connections);
+
+        // The result is announced once PipeWire has answered every request
+        if !request_ids.is_empty() {
+            self.begin_bulk_operation(BulkOperation::new(
+                format!("Loading preset \"{}\"", name),
+                format!("Loaded preset \"{}\"", name),
+                request_ids,
+                skipped,
+            ));
+        } else if skipped > 0 {
+            self.announce(&format!(
+                "Preset \"{}\": all {} connections already exist or unavailable",
+                name, skipped
+            ));
+        }
+    }
+
+    /// Create the links of saved connections that are not connected yet.
+    /// Returns the ids of the link requests and how many were skipped
+    /// because they exist already or their ports are missing.
+    fn create_missing_links(&self, connections: &[PresetConnection]) -> (Vec<u64>, usize) {
         // Collect links to create (to avoid borrow issues)
         let links_to_create: Vec<(u32, u32)>;
         let mut skipped = 0;
@@ -1478,30 +4217,8 @@ impl Window {
             let pw_state = self.imp().pw_state.borrow();
             let mut to_create = Vec::new();
 
-            for conn in &preset.connections {
-                // Find output port by node name and port name
-                let output_port = pw_state.ports.values().find(|p| {
-                    p.direction == PortDirection::Output
-                        && p.name == conn.output_port
-                        && pw_state
-                            .nodes
-                            .get(&p.node_id)
-                            .map(|n| n.name == conn.output_node)
-                            .unwrap_or(false)
-                });
-
-                // Find input port by node name and port name
-                let input_port = pw_state.ports.values().find(|p| {
-                    p.direction == PortDirection::Input
-                        && p.name == conn.input_port
-                        && pw_state
-                            .nodes
-                            .get(&p.node_id)
-                            .map(|n| n.name == conn.input_node)
-                            .unwrap_or(false)
-                });
-
-                match (output_port, input_port) {
+            for conn in connections {
+                match conn.find_ports(&pw_state) {
                     (Some(out), Some(inp)) => {
                         // Check if link already exists
                         let exists = pw_state.links.values().any(|l| {
@@ -1529,56 +4246,52 @@ impl Window {
         }
 
         // Now create the links (pw_state borrow is released)
-        let created = links_to_create.len();
-        for (output_id, input_id) in links_to_create {
-            self.create_link(output_id, input_id);
-        }
-
-        if created > 0 && skipped == 0 {
-            self.announce(&format!("Loaded preset \"{}\": {} connections", name, created));
-        } else if created > 0 {
-            self.announce(&format!(
-                "Loaded preset \"{}\": {} created, {} skipped",
-                name, created, skipped
-            ));
-        } else if skipped > 0 {
-            self.announce(&format!(
-                "Preset \"{}\": all {} connections already exist or unavailable",
-                name, skipped
-            ));
-        }
+        let request_ids = links_to_create
+            .into_iter()
+            .map(|(output_id, input_id)| self.create_link(output_id, input_id))
+            .collect();
+        (request_ids, skipped)
     }
 
-    /// Delete a preset by name
+    /// Move a preset to the trash, offering to undo
     fn delete_preset(&self, name: &str) {
-        // If deleting the active preset, deactivate it first
+        // Deleting the active preset deactivates it
         let was_active = self.imp().preset_store.borrow().is_active(name);
-        if was_active {
-            self.imp().preset_store.borrow_mut().deactivate_preset();
-        }
-
-        self.imp().preset_store.borrow_mut().remove_preset(name);
+        self.imp().preset_store.borrow_mut().trash_preset(name);
 
         if let Err(e) = self.imp().preset_store.borrow().save() {
             self.announce(&format!("Failed to save after delete: {}", e));
         } else {
-            self.announce(&format!("Deleted preset \"{}\"", name));
+            let message = format!("Moved preset \"{}\" to the trash", name);
+            let restored = name.to_string();
+            self.show_undo_toast(&message, move |window| {
+                window.restore_preset(&restored, was_active);
+            });
+            self.announce(&message);
         }
+        self.refresh_preset_accels();
 
         // Update display if we deactivated the preset
         if was_active {
             self.update_active_preset_display();
+        } else {
+            self.sync_compact_window();
         }
     }
 
     /// Check and create auto-connections for the active preset
     /// Called when a new port is added to see if it completes any preset connections
     fn check_auto_connect(&self) {
+        // The active preset is for the main server
+        if self.is_server_window() {
+            return;
+        }
+
         // Get the active preset's connections
-        let preset_connections: Vec<PresetConnection> = {
+        let (preset_name, preset_connections): (String, Vec<PresetConnection>) = {
             let store = self.imp().preset_store.borrow();
             match store.get_active_preset() {
-                Some(preset) => preset.connections.clone(),
+                Some(preset) => (preset.name.clone(), preset.connections.clone()),
                 None => return, // No active preset
             }
         };
@@ -1588,30 +4301,8 @@ impl Window {
         let mut links_to_create = Vec::new();
 
         for conn in &preset_connections {
-            // Find output port by node name and port name
-            let output_port = pw_state.ports.values().find(|p| {
-                p.direction == PortDirection::Output
-                    && p.name == conn.output_port
-                    && pw_state
-                        .nodes
-                        .get(&p.node_id)
-                        .map(|n| n.name == conn.output_node)
-                        .unwrap_or(false)
-            });
-
-            // Find input port by node name and port name
-            let input_port = pw_state.ports.values().find(|p| {
-                p.direction == PortDirection::Input
-                    && p.name == conn.input_port
-                    && pw_state
-                        .nodes
-                        .get(&p.node_id)
-                        .map(|n| n.name == conn.input_node)
-                        .unwrap_or(false)
-            });
-
             // If both ports exist and link doesn't already exist, queue it
-            if let (Some(out), Some(inp)) = (output_port, input_port) {
+            if let (Some(out), Some(inp)) = conn.find_ports(&pw_state) {
                 let link_key = (out.id, inp.id);
 
                 // Check if link already exists
@@ -1621,7 +4312,7 @@ impl Window {
                     .any(|l| l.output_port_id == out.id && l.input_port_id == inp.id);
 
                 // Check if link creation is already in-flight
-                let pending = self.imp().pending_links.borrow().contains(&link_key);
+                let pending = self.imp().pending_links.borrow().contains_key(&link_key);
 
                 if !exists && !pending {
                     links_to_create.push(link_key);
@@ -1632,23 +4323,41 @@ impl Window {
         // Release borrow before creating links
         drop(pw_state);
 
-        // Mark links as pending and create them
-        {
-            let mut pending = self.imp().pending_links.borrow_mut();
-            for &link_key in &links_to_create {
-                pending.insert(link_key);
-            }
-        }
-
-        // Create the links
+        // Create the links, which marks them as pending
         let count = links_to_create.len();
+        let verbose = self.imp().settings.borrow().announcement_verbosity == Verbosity::Verbose;
         for (output_id, input_id) in links_to_create {
             log::debug!("Auto-connecting ports {} -> {}", output_id, input_id);
-            self.create_link(output_id, input_id);
+            let request_id = self.create_link(output_id, input_id);
+            self.imp()
+                .preset_link_requests
+                .borrow_mut()
+                .insert(request_id, preset_name.clone());
+
+            let label = {
+                let pw_state = self.imp().pw_state.borrow();
+                format!(
+                    "{} to {}",
+                    pw_state
+                        .port_label(output_id)
+                        .unwrap_or_else(|| format!("Port {}", output_id)),
+                    pw_state
+                        .port_label(input_id)
+                        .unwrap_or_else(|| format!("Port {}", input_id))
+                )
+            };
+            self.record_activity(&format!(
+                "Preset \"{}\" auto-connected {}",
+                preset_name, label
+            ));
+            if verbose {
+                self.announce_verbose(&format!("Auto-connected {}", label));
+            }
         }
 
-        // Notify user of auto-connections (for accessibility)
-        if count > 0 {
+        // Notify user of auto-connections (for accessibility); verbose mode
+        // has already named each one
+        if count > 0 && !verbose {
             if count == 1 {
                 self.announce("Auto-connected 1 port");
             } else {
@@ -1659,10 +4368,39 @@ impl Window {
 
     /// Activate a preset for auto-connecting
     pub fn activate_preset(&self, name: &str) {
+        // Presets name the main server's ports
+        if self.is_server_window() {
+            self.announce(PRESETS_MAIN_SERVER_ONLY);
+            return;
+        }
+
+        // The name may come from the command line or the Shell search
+        if self.imp().preset_store.borrow().get_preset(name).is_none() {
+            log::warn!("No preset named \"{}\"", name);
+            self.announce(&format!("Preset \"{}\" not found", name));
+            return;
+        }
+
+        // Connections of the preset being replaced, removed once the new
+        // ones are made
+        let previous = self.imp().preset_store.borrow().active_preset.clone();
+        let replaced_links = match previous {
+            Some(previous)
+                if previous != name && self.imp().settings.borrow().replace_on_preset_switch =>
+            {
+                self.links_left_by_switch(&previous, name)
+            }
+            _ => Vec::new(),
+        };
+
         {
             let mut store = self.imp().preset_store.borrow_mut();
             store.activate_preset(name);
+            if let Some(stats) = store.stats_mut(name) {
+                stats.activations += 1;
+            }
         }
+        self.count_unmatched_connections(name);
 
         // Save the activation state
         if let Err(e) = self.imp().preset_store.borrow().save() {
@@ -1670,11 +4408,24 @@ impl Window {
             return;
         }
 
+        let routes = self
+            .imp()
+            .preset_store
+            .borrow()
+            .get_preset(name)
+            .map(|preset| preset.routes.clone())
+            .unwrap_or_default();
+        self.apply_preset_routes(&routes);
+
         // Immediately try to establish any connections
         self.check_auto_connect();
+        self.remove_links_after_switch(replaced_links);
 
         self.announce(&format!("Activated preset \"{}\"", name));
+        self.record_activity(&format!("Activated preset \"{}\"", name));
         self.update_active_preset_display();
+        #[cfg(feature = "scripting")]
+        self.scripts_preset_activated(name);
     }
 
     /// Deactivate the current preset
@@ -1701,6 +4452,7 @@ impl Window {
 
         if let Some(name) = name {
             self.announce(&format!("Deactivated preset \"{}\"", name));
+            self.record_activity(&format!("Deactivated preset \"{}\"", name));
         }
         self.update_active_preset_display();
     }
@@ -1718,6 +4470,140 @@ impl Window {
         } else {
             self.set_title(Some("PW Audioshare"));
         }
+
+        self.update_tray();
+        self.sync_compact_window();
+    }
+
+    /// Set which screen reader announcements are made and save the setting
+    fn set_announcement_verbosity(&self, verbosity: Verbosity) {
+        self.imp().settings.borrow_mut().announcement_verbosity = verbosity;
+
+        if let Err(e) = self.imp().settings.borrow().save() {
+            self.announce_error(&format!("Failed to save settings: {}", e));
+            return;
+        }
+
+        // Confirm even when switching to silent, so the change is heard
+        let message = match verbosity {
+            Verbosity::Silent => "Announcements silenced except errors",
+            Verbosity::Important => "Announcing important events only",
+            Verbosity::Verbose => "Announcing all events",
+        };
+        self.announce_with_priority(message, gtk::AccessibleAnnouncementPriority::Medium);
+    }
+
+    /// Set whether new connections are passive by default
+    fn set_link_passive(&self, passive: bool) {
+        self.imp().settings.borrow_mut().link_passive = passive;
+
+        if let Err(e) = self.imp().settings.borrow().save() {
+            self.announce(&format!("Failed to save settings: {}", e));
+            return;
+        }
+
+        if passive {
+            self.announce("New connections will not keep devices running");
+        } else {
+            self.announce("New connections will keep devices running");
+        }
+    }
+
+    /// Set whether new connections outlive the application by default
+    fn set_link_linger(&self, linger: bool) {
+        self.imp().settings.borrow_mut().link_linger = linger;
+
+        if let Err(e) = self.imp().settings.borrow().save() {
+            self.announce(&format!("Failed to save settings: {}", e));
+            return;
+        }
+
+        if linger {
+            self.announce("New connections will remain after exit");
+        } else {
+            self.announce("New connections will be removed on exit");
+        }
+    }
+
+    /// Turn temporary mode on or off for the rest of the session
+    fn set_temporary_mode(&self, enabled: bool) {
+        self.imp().temporary_mode.set(enabled);
+
+        if enabled {
+            self.announce("Temporary mode on, new connections will be removed on exit");
+        } else {
+            self.announce("Temporary mode off");
+        }
+    }
+
+    /// Show a dialog to connect the selected ports with specific link options
+    fn show_connect_options_dialog(&self) {
+        if self.selected_ports(true).is_empty() {
+            self.announce("No output ports selected");
+            return;
+        }
+        if self.selected_ports(false).is_empty() {
+            self.announce("No input ports selected");
+            return;
+        }
+
+        let defaults = self.default_link_options();
+
+        let dialog = adw::MessageDialog::builder()
+            .transient_for(&self.dialog_parent())
+            .modal(true)
+            .heading("Connect with Options")
+            .body("Connect the selected ports with these options:")
+            .build();
+
+        let content = gtk::Box::builder()
+            .orientation(gtk::Orientation::Vertical)
+            .spacing(6)
+            .build();
+
+        let passive_check = gtk::CheckButton::builder()
+            .label("Passive (don't keep devices running)")
+            .active(defaults.passive)
+            .build();
+        content.append(&passive_check);
+
+        let linger_check = gtk::CheckButton::builder()
+            .label("Keep after PW Audioshare exits")
+            .active(defaults.linger)
+            .build();
+        content.append(&linger_check);
+
+        dialog.set_extra_child(Some(&content));
+
+        dialog.add_response("cancel", "Cancel");
+        dialog.add_response("connect", "Connect");
+        dialog.set_response_appearance("connect", adw::ResponseAppearance::Suggested);
+        dialog.set_default_response(Some("connect"));
+        dialog.set_close_response("cancel");
+
+        dialog.connect_response(
+            None,
+            glib::clone!(
+                #[weak(rename_to = window)]
+                self,
+                #[weak]
+                passive_check,
+                #[weak]
+                linger_check,
+                move |dialog, response| {
+                    dialog.close();
+                    if response == "connect" {
+                        window.connect_selected_with_options(LinkOptions {
+                            passive: passive_check.is_active(),
+                            linger: linger_check.is_active(),
+                        });
+                    }
+                }
+            ),
+        );
+
+        dialog.present();
+        passive_check.grab_focus();
     }
 
     /// Set the start minimized setting and save it
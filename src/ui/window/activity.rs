@@ -0,0 +1,236 @@
+use std::path::Path;
+
+use adw::prelude::*;
+use adw::subclass::prelude::*;
+use gtk::{gio, glib};
+
+use super::Window;
+
+/// Number of entries kept in the activity history
+const ACTIVITY_CAPACITY: usize = 1000;
+
+/// Something that happened to the graph, with the local time it happened
+#[derive(Debug, Clone)]
+pub struct ActivityEntry {
+    /// "YYYY-MM-DD HH:MM:SS"
+    pub time: String,
+    pub message: String,
+}
+
+impl ActivityEntry {
+    /// The entry as one line of the exported history
+    pub fn line(&self) -> String {
+        format!("{} {}", self.time, self.message)
+    }
+}
+
+impl Window {
+    /// Add an entry to the activity history
+    pub(super) fn record_activity(&self, message: &str) {
+        let entry = ActivityEntry {
+            time: glib::DateTime::now_local()
+                .and_then(|now| now.format("%F %T"))
+                .map(|s| s.to_string())
+                .unwrap_or_default(),
+            message: message.to_string(),
+        };
+
+        if let Some(list_box) = self.imp().activity_list.borrow().as_ref() {
+            list_box.prepend(&activity_row(&entry));
+            if let Some(oldest) = list_box.row_at_index(ACTIVITY_CAPACITY as i32) {
+                list_box.remove(&oldest);
+            }
+        }
+
+        let mut activity = self.imp().activity.borrow_mut();
+        if activity.len() >= ACTIVITY_CAPACITY {
+            activity.pop_front();
+        }
+        activity.push_back(entry);
+    }
+
+    /// Add an entry for a change to the graph. Objects reported while the
+    /// existing graph is first loaded are not news, so they are skipped.
+    pub(super) fn record_graph_activity(&self, message: &str) {
+        if self.imp().health_report.borrow().is_some() {
+            self.record_activity(message);
+        }
+    }
+
+    /// "Output → Input" for a link still in the graph
+    pub(super) fn link_label(&self, link_id: u32) -> Option<String> {
+        let pw_state = self.imp().pw_state.borrow();
+        let link = pw_state.links.get(&link_id)?;
+        Some(format!(
+            "{} → {}",
+            pw_state.port_label(link.output_port_id)?,
+            pw_state.port_label(link.input_port_id)?
+        ))
+    }
+
+    /// Name of a node if it is a device rather than an application stream
+    pub(super) fn device_label(&self, node_id: u32) -> Option<String> {
+        let pw_state = self.imp().pw_state.borrow();
+        let node = pw_state.nodes.get(&node_id)?;
+        let media_class = node.media_class.as_deref()?;
+        let is_device = ["Audio/", "Video/", "Midi/"]
+            .iter()
+            .any(|prefix| media_class.starts_with(prefix));
+        is_device.then(|| node.display_name().to_string())
+    }
+
+    /// Build the side panel listing the activity history, newest first
+    pub(super) fn build_activity_panel(&self) -> gtk::Revealer {
+        let frame = gtk::Frame::builder()
+            .label("Activity")
+            .margin_end(12)
+            .margin_top(6)
+            .margin_bottom(6)
+            .width_request(320)
+            .build();
+
+        let panel_box = gtk::Box::builder()
+            .orientation(gtk::Orientation::Vertical)
+            .spacing(6)
+            .margin_start(6)
+            .margin_end(6)
+            .margin_top(6)
+            .margin_bottom(6)
+            .build();
+
+        let list_box = gtk::ListBox::builder()
+            .selection_mode(gtk::SelectionMode::None)
+            .build();
+        list_box.update_property(&[gtk::accessible::Property::Label("Activity history")]);
+        self.imp().activity_list.replace(Some(list_box.clone()));
+
+        let scrolled = gtk::ScrolledWindow::builder()
+            .hscrollbar_policy(gtk::PolicyType::Never)
+            .vscrollbar_policy(gtk::PolicyType::Automatic)
+            .vexpand(true)
+            .child(&list_box)
+            .build();
+        panel_box.append(&scrolled);
+
+        let controls = gtk::Box::builder()
+            .orientation(gtk::Orientation::Horizontal)
+            .spacing(6)
+            .build();
+
+        let clear_btn = gtk::Button::builder()
+            .label("Clear")
+            .tooltip_text("Forget the activity history")
+            .build();
+        clear_btn.connect_clicked(glib::clone!(
+            #[weak(rename_to = window)]
+            self,
+            move |_| {
+                window.imp().activity.borrow_mut().clear();
+                if let Some(list_box) = window.imp().activity_list.borrow().as_ref() {
+                    list_box.remove_all();
+                }
+                window.announce("Activity history cleared");
+            }
+        ));
+        controls.append(&clear_btn);
+
+        let export_btn = gtk::Button::builder()
+            .label("Export...")
+            .tooltip_text("Save the activity history to a text file")
+            .build();
+        export_btn.connect_clicked(glib::clone!(
+            #[weak(rename_to = window)]
+            self,
+            move |_| window.show_export_activity_dialog()
+        ));
+        controls.append(&export_btn);
+
+        panel_box.append(&controls);
+        frame.set_child(Some(&panel_box));
+
+        let revealer = gtk::Revealer::builder()
+            .transition_type(gtk::RevealerTransitionType::SlideLeft)
+            .reveal_child(false)
+            .child(&frame)
+            .build();
+        self.imp().activity_revealer.replace(Some(revealer.clone()));
+        revealer
+    }
+
+    /// Show or hide the activity panel
+    pub(super) fn set_activity_panel_visible(&self, visible: bool) {
+        if let Some(revealer) = self.imp().activity_revealer.borrow().as_ref() {
+            revealer.set_reveal_child(visible);
+        }
+        if visible {
+            if let Some(list_box) = self.imp().activity_list.borrow().as_ref() {
+                list_box.grab_focus();
+            }
+            let count = self.imp().activity.borrow().len();
+            self.announce(&format!("Activity history, {} entries", count));
+        } else if self
+            .imp()
+            .activity_revealer
+            .borrow()
+            .as_ref()
+            .is_some_and(|revealer| revealer.focus_child().is_some())
+        {
+            // Don't leave focus inside the hidden panel
+            self.focus_output_list();
+        }
+    }
+
+    fn show_export_activity_dialog(&self) {
+        let dialog = gtk::FileDialog::builder()
+            .title("Export Activity")
+            .initial_name("pw-audioshare-activity.txt")
+            .build();
+        dialog.save(
+            Some(&self.dialog_parent()),
+            None::<&gio::Cancellable>,
+            glib::clone!(
+                #[weak(rename_to = window)]
+                self,
+                move |result| {
+                    let Some(path) = result.ok().and_then(|file| file.path()) else {
+                        return;
+                    };
+                    match window.export_activity(&path) {
+                        Ok(count) => window.announce(&format!(
+                            "Exported {} entries to {}",
+                            count,
+                            path.display()
+                        )),
+                        Err(e) => window.announce(&e),
+                    }
+                }
+            ),
+        );
+    }
+
+    /// Write the history to a text file, oldest entry first
+    fn export_activity(&self, path: &Path) -> Result<usize, String> {
+        let activity = self.imp().activity.borrow();
+        let mut text: String = activity.iter().map(|e| e.line() + "\n").collect();
+        if text.is_empty() {
+            text.push_str("No activity recorded.\n");
+        }
+        std::fs::write(path, text).map_err(|e| format!("Failed to export activity: {}", e))?;
+        Ok(activity.len())
+    }
+}
+
+fn activity_row(entry: &ActivityEntry) -> gtk::Label {
+    // Only the time of day is shown; the date is in the export
+    let time_of_day = entry.time.split(' ').nth(1).unwrap_or(&entry.time);
+    gtk::Label::builder()
+        .label(format!("{} {}", time_of_day, entry.message))
+        .xalign(0.0)
+        .wrap(true)
+        .wrap_mode(gtk::pango::WrapMode::WordChar)
+        .margin_start(6)
+        .margin_end(6)
+        .margin_top(2)
+        .margin_bottom(2)
+        .build()
+}
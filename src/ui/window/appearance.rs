@@ -0,0 +1,67 @@
+use adw::prelude::*;
+use adw::subclass::prelude::*;
+use gtk::gio;
+
+use super::Window;
+use crate::settings::Appearance;
+
+impl Window {
+    /// Use the configured light or dark style, and mark rows with the colour
+    /// of their media type if wanted
+    pub(super) fn apply_appearance(&self) {
+        let (appearance, media_colors) = {
+            let settings = self.imp().settings.borrow();
+            (settings.appearance, settings.media_colors)
+        };
+        adw::StyleManager::default().set_color_scheme(match appearance {
+            Appearance::System => adw::ColorScheme::Default,
+            Appearance::Light => adw::ColorScheme::ForceLight,
+            Appearance::Dark => adw::ColorScheme::ForceDark,
+        });
+        if media_colors {
+            self.add_css_class("media-colors");
+        } else {
+            self.remove_css_class("media-colors");
+        }
+    }
+
+    /// Change the light or dark style
+    pub(super) fn set_appearance(&self, appearance: Appearance) {
+        self.imp().settings.borrow_mut().appearance = appearance;
+        self.apply_appearance();
+
+        if let Err(e) = self.imp().settings.borrow().save() {
+            self.announce(&format!("Failed to save settings: {}", e));
+            return;
+        }
+
+        self.announce(match appearance {
+            Appearance::System => "Appearance follows the system",
+            Appearance::Light => "Light appearance",
+            Appearance::Dark => "Dark appearance",
+        });
+    }
+
+    /// Mark rows with the colour of their media type, or stop
+    pub(super) fn set_media_colors(&self, enabled: bool) {
+        self.imp().settings.borrow_mut().media_colors = enabled;
+        if let Some(action) = self
+            .lookup_action("media-colors")
+            .and_downcast::<gio::SimpleAction>()
+        {
+            action.set_state(&enabled.to_variant());
+        }
+        self.apply_appearance();
+
+        if let Err(e) = self.imp().settings.borrow().save() {
+            self.announce(&format!("Failed to save settings: {}", e));
+            return;
+        }
+
+        if enabled {
+            self.announce("Rows coloured by media type");
+        } else {
+            self.announce("Rows no longer coloured by media type");
+        }
+    }
+}
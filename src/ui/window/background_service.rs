@@ -0,0 +1,162 @@
+use std::io::ErrorKind;
+use std::path::PathBuf;
+use std::process::Command;
+
+use adw::prelude::*;
+use adw::subclass::prelude::*;
+use gtk::{gio, glib};
+
+use super::Window;
+use crate::sandbox;
+
+/// Name of the systemd user unit running `pw-audioshare --daemon`
+const SERVICE_NAME: &str = "pw-audioshare.service";
+
+fn service_path() -> Option<PathBuf> {
+    Some(dirs::config_dir()?.join("systemd/user").join(SERVICE_NAME))
+}
+
+/// Whether the user unit has been written
+fn is_installed() -> bool {
+    service_path().is_some_and(|path| path.exists())
+}
+
+/// The unit starts with the graphical session, since the daemon keeps a
+/// hidden window, and stops with it
+fn service_unit(executable: &str) -> String {
+    format!(
+        "[Unit]\n\
+         Description=PW Audioshare preset auto-connect\n\
+         PartOf=graphical-session.target\n\
+         After=graphical-session.target pipewire.service wireplumber.service\n\
+         \n\
+         [Service]\n\
+         ExecStart=\"{}\" --daemon\n\
+         Restart=on-failure\n\
+         \n\
+         [Install]\n\
+         WantedBy=graphical-session.target\n",
+        executable
+    )
+}
+
+fn systemctl(args: &[&str]) -> Result<(), String> {
+    let output = Command::new("systemctl")
+        .arg("--user")
+        .args(args)
+        .output()
+        .map_err(|e| match e.kind() {
+            ErrorKind::NotFound => "systemd is not available".to_string(),
+            _ => format!("Failed to run systemctl: {}", e),
+        })?;
+
+    if !output.status.success() {
+        let stderr = String::from_utf8_lossy(&output.stderr);
+        return Err(format!("systemctl {} failed: {}", args.join(" "), stderr.trim()));
+    }
+    Ok(())
+}
+
+/// Write and enable the unit. It is not started now: this instance already
+/// holds the application name, so the daemon would only exit.
+fn install() -> Result<(), String> {
+    let path = service_path().ok_or("Could not determine config directory")?;
+    let executable = std::env::current_exe()
+        .map_err(|e| format!("Failed to find the program: {}", e))?;
+
+    if let Some(parent) = path.parent() {
+        std::fs::create_dir_all(parent)
+            .map_err(|e| format!("Failed to create {}: {}", parent.display(), e))?;
+    }
+    std::fs::write(&path, service_unit(&executable.to_string_lossy()))
+        .map_err(|e| format!("Failed to write {}: {}", path.display(), e))?;
+
+    systemctl(&["daemon-reload"])?;
+    systemctl(&["enable", SERVICE_NAME])
+}
+
+fn uninstall() -> Result<(), String> {
+    let path = service_path().ok_or("Could not determine config directory")?;
+    systemctl(&["disable", SERVICE_NAME])?;
+    std::fs::remove_file(&path)
+        .map_err(|e| format!("Failed to remove {}: {}", path.display(), e))?;
+    systemctl(&["daemon-reload"])
+}
+
+impl Window {
+    /// Whether the app is set up to run in the background at login
+    pub(super) fn background_service_enabled(&self) -> bool {
+        if sandbox::is_flatpak() {
+            self.imp().settings.borrow().background_autostart
+        } else {
+            is_installed()
+        }
+    }
+
+    /// Install or remove the systemd user service that runs the app in the
+    /// background at login. Inside Flatpak the Background portal is asked
+    /// instead.
+    pub(super) fn set_background_service(&self, enabled: bool) {
+        if sandbox::is_flatpak() {
+            self.request_background_autostart(enabled);
+            return;
+        }
+
+        let result = if enabled { install() } else { uninstall() };
+        self.background_service_changed(enabled, result);
+    }
+
+    fn request_background_autostart(&self, enabled: bool) {
+        sandbox::request_background(
+            "Keep the active preset connected after login",
+            enabled,
+            &["pw-audioshare", "--daemon"],
+            glib::clone!(
+                #[weak(rename_to = window)]
+                self,
+                move |result| {
+                    let result = match result {
+                        Ok(granted) if granted != enabled => {
+                            Err("Starting at login was not allowed".to_string())
+                        }
+                        Ok(_) => {
+                            window.imp().settings.borrow_mut().background_autostart = enabled;
+                            window
+                                .imp()
+                                .settings
+                                .borrow()
+                                .save()
+                                .map_err(|e| format!("Failed to save settings: {}", e))
+                        }
+                        Err(e) => Err(e),
+                    };
+                    window.background_service_changed(enabled, result);
+                }
+            ),
+        );
+    }
+
+    fn background_service_changed(&self, enabled: bool, result: Result<(), String>) {
+        if let Err(message) = result {
+            self.set_background_service_state(self.background_service_enabled());
+            self.show_toast(&message);
+            self.announce_error(&message);
+            return;
+        }
+
+        if enabled {
+            self.announce("PW Audioshare will run in the background from the next login");
+        } else {
+            self.announce("PW Audioshare will no longer run in the background at login");
+        }
+    }
+
+    fn set_background_service_state(&self, enabled: bool) {
+        if let Some(action) = self
+            .lookup_action("background-service")
+            .and_downcast::<gio::SimpleAction>()
+        {
+            action.set_state(&enabled.to_variant());
+        }
+    }
+}
@@ -0,0 +1,237 @@
+use adw::prelude::*;
+use adw::subclass::prelude::*;
+use gtk::{gio, glib};
+
+use super::Window;
+
+/// Most connections listed by name in the confirmation
+const MAX_LISTED: usize = 50;
+
+/// A connection about to be removed
+#[derive(Clone)]
+struct DoomedLink {
+    id: u32,
+    output_port_id: u32,
+    input_port_id: u32,
+    label: String,
+}
+
+impl Window {
+    /// Ports selected in the port list used last
+    fn bulk_selected_ports(&self) -> Vec<u32> {
+        let is_output = *self.imp().last_port_list_was_output.borrow();
+        self.selected_ports(is_output)
+            .iter()
+            .map(|port| port.id())
+            .collect()
+    }
+
+    /// The connections of ports matching `matches`, with their labels
+    fn links_where(&self, matches: impl Fn(u32) -> bool) -> Vec<DoomedLink> {
+        let pw_state = self.imp().pw_state.borrow();
+        let mut links: Vec<DoomedLink> = pw_state
+            .links
+            .values()
+            .filter(|link| matches(link.output_port_id) || matches(link.input_port_id))
+            .map(|link| DoomedLink {
+                id: link.id,
+                output_port_id: link.output_port_id,
+                input_port_id: link.input_port_id,
+                label: format!(
+                    "{} to {}",
+                    pw_state
+                        .port_label(link.output_port_id)
+                        .unwrap_or_else(|| format!("Port {}", link.output_port_id)),
+                    pw_state
+                        .port_label(link.input_port_id)
+                        .unwrap_or_else(|| format!("Port {}", link.input_port_id))
+                ),
+            })
+            .collect();
+        links.sort_by(|a, b| a.label.cmp(&b.label));
+        links
+    }
+
+    /// Remove every connection of the selected ports
+    pub(super) fn disconnect_selected_ports(&self) {
+        let ports = self.bulk_selected_ports();
+        if ports.is_empty() {
+            self.announce("No ports selected");
+            return;
+        }
+        let links = self.links_where(|port_id| ports.contains(&port_id));
+        let what = if ports.len() == 1 {
+            "the selected port".to_string()
+        } else {
+            format!("the {} selected ports", ports.len())
+        };
+        self.confirm_disconnect(links, &what);
+    }
+
+    /// Remove every connection of the applications or devices owning the
+    /// selected ports
+    pub(super) fn disconnect_selected_nodes(&self) {
+        let ports = self.bulk_selected_ports();
+        if ports.is_empty() {
+            self.announce("No ports selected");
+            return;
+        }
+        let (node_ports, names) = {
+            let pw_state = self.imp().pw_state.borrow();
+            let mut node_ids = Vec::new();
+            let mut names = Vec::new();
+            for node in ports.iter().filter_map(|id| pw_state.get_port_node(*id)) {
+                if !node_ids.contains(&node.id) {
+                    node_ids.push(node.id);
+                    names.push(node.display_name().to_string());
+                }
+            }
+            let node_ports: Vec<u32> = pw_state
+                .ports
+                .values()
+                .filter(|port| node_ids.contains(&port.node_id))
+                .map(|port| port.id)
+                .collect();
+            (node_ports, names)
+        };
+        let links = self.links_where(|port_id| node_ports.contains(&port_id));
+        self.confirm_disconnect(links, &names.join(", "));
+    }
+
+    /// Remove `links`, first listing them for confirmation when there is
+    /// more than one and confirming hasn't been turned off
+    fn confirm_disconnect(&self, links: Vec<DoomedLink>, what: &str) {
+        if links.is_empty() {
+            self.announce(&format!("No connections to remove from {}", what));
+            return;
+        }
+        if links.len() == 1 || !self.imp().settings.borrow().confirm_bulk_disconnect {
+            self.disconnect_links(&links);
+            return;
+        }
+
+        let dialog = adw::MessageDialog::builder()
+            .transient_for(&self.dialog_parent())
+            .modal(true)
+            .heading(format!("Remove {} Connections?", links.len()))
+            .body(format!(
+                "Every connection of {} will be removed. They can be made again \
+                 with Undo.",
+                what
+            ))
+            .build();
+
+        let list_box = gtk::ListBox::builder()
+            .selection_mode(gtk::SelectionMode::None)
+            .css_classes(["boxed-list"])
+            .build();
+        list_box.update_property(&[gtk::accessible::Property::Label("Connections to remove")]);
+        for link in links.iter().take(MAX_LISTED) {
+            let row = adw::ActionRow::builder()
+                .title(&link.label)
+                .focusable(true)
+                .build();
+            list_box.append(&row);
+        }
+        if links.len() > MAX_LISTED {
+            let row = adw::ActionRow::builder()
+                .title(format!("and {} more", links.len() - MAX_LISTED))
+                .focusable(true)
+                .build();
+            list_box.append(&row);
+        }
+
+        let scrolled = gtk::ScrolledWindow::builder()
+            .hscrollbar_policy(gtk::PolicyType::Never)
+            .vscrollbar_policy(gtk::PolicyType::Automatic)
+            .min_content_height(100)
+            .max_content_height(300)
+            .child(&list_box)
+            .build();
+
+        let dont_ask = gtk::CheckButton::with_label("Don't ask again");
+        let content = gtk::Box::builder()
+            .orientation(gtk::Orientation::Vertical)
+            .spacing(12)
+            .build();
+        content.append(&scrolled);
+        content.append(&dont_ask);
+        dialog.set_extra_child(Some(&content));
+
+        dialog.add_response("cancel", "Cancel");
+        dialog.add_response("remove", "Remove");
+        dialog.set_response_appearance("remove", adw::ResponseAppearance::Destructive);
+        dialog.set_default_response(Some("cancel"));
+        dialog.set_close_response("cancel");
+
+        dialog.connect_response(
+            None,
+            glib::clone!(
+                #[weak(rename_to = window)]
+                self,
+                #[weak]
+                dont_ask,
+                move |dialog, response| {
+                    dialog.close();
+                    if response != "remove" {
+                        return;
+                    }
+                    if dont_ask.is_active() {
+                        window.set_confirm_bulk_disconnect(false);
+                    }
+                    window.disconnect_links(&links);
+                }
+            ),
+        );
+
+        dialog.present();
+        if let Some(first_row) = list_box.row_at_index(0) {
+            first_row.grab_focus();
+        }
+    }
+
+    /// Remove connections, offering to make them again
+    fn disconnect_links(&self, links: &[DoomedLink]) {
+        for link in links {
+            self.delete_link_remembered(link.id);
+        }
+
+        let message = match links {
+            [link] => format!("Disconnected {}", link.label),
+            _ => format!("Removed {} connections", links.len()),
+        };
+        self.record_activity(&message);
+        let pairs: Vec<(u32, u32)> = links
+            .iter()
+            .map(|link| (link.output_port_id, link.input_port_id))
+            .collect();
+        self.show_undo_toast(&message, move |window| {
+            for (output_port_id, input_port_id) in &pairs {
+                window.create_link(*output_port_id, *input_port_id);
+            }
+        });
+        self.announce(&message);
+    }
+
+    /// Set whether removing several connections at once asks first
+    pub(super) fn set_confirm_bulk_disconnect(&self, confirm: bool) {
+        self.imp().settings.borrow_mut().confirm_bulk_disconnect = confirm;
+        if let Some(action) = self
+            .lookup_action("confirm-bulk-disconnect")
+            .and_downcast::<gio::SimpleAction>()
+        {
+            action.set_state(&confirm.to_variant());
+        }
+
+        if let Err(e) = self.imp().settings.borrow().save() {
+            self.announce(&format!("Failed to save settings: {}", e));
+            return;
+        }
+
+        if confirm {
+            self.announce("Removing several connections asks first");
+        } else {
+            self.announce("Removing several connections no longer asks");
+        }
+    }
+}
@@ -0,0 +1,134 @@
+use std::collections::HashSet;
+
+use adw::prelude::*;
+use adw::subclass::prelude::*;
+
+use super::Window;
+
+/// Link requests made together, such as loading a preset, whose outcome is
+/// announced once every one has succeeded or failed
+pub struct BulkOperation {
+    /// Shown while it runs, e.g. `Loading preset "Studio"`
+    label: String,
+    /// Starts the result, e.g. `Loaded preset "Studio"`
+    done_label: String,
+    pending: HashSet<u64>,
+    total: usize,
+    failed: usize,
+    /// Connections not requested because they exist or their ports are missing
+    skipped: usize,
+    /// Also show the result as a toast
+    toast: bool,
+}
+
+impl BulkOperation {
+    pub fn new(label: String, done_label: String, request_ids: Vec<u64>, skipped: usize) -> Self {
+        Self {
+            label,
+            done_label,
+            total: request_ids.len(),
+            pending: request_ids.into_iter().collect(),
+            failed: 0,
+            skipped,
+            toast: false,
+        }
+    }
+
+    /// Show the result as a toast as well as announcing it
+    pub fn with_toast(mut self) -> Self {
+        self.toast = true;
+        self
+    }
+
+    fn progress(&self) -> String {
+        format!(
+            "{}: {} of {} connections",
+            self.label,
+            self.total - self.pending.len(),
+            self.total
+        )
+    }
+
+    fn summary(&self) -> String {
+        let mut summary = format!(
+            "{}: {} connected",
+            self.done_label,
+            self.total - self.failed
+        );
+        if self.failed > 0 {
+            summary.push_str(&format!(", {} failed", self.failed));
+        }
+        if self.skipped > 0 {
+            summary.push_str(&format!(", {} skipped", self.skipped));
+        }
+        summary
+    }
+}
+
+impl Window {
+    /// Track the link requests of a bulk operation, showing a spinner and
+    /// a running count until all have finished
+    pub(super) fn begin_bulk_operation(&self, operation: BulkOperation) {
+        if operation.pending.is_empty() {
+            return;
+        }
+        self.imp().bulk_operations.borrow_mut().push(operation);
+        self.imp().bulk_spinner.set_visible(true);
+        self.imp().bulk_spinner.start();
+        self.update_status_counts();
+    }
+
+    /// The running count of the oldest bulk operation, shown in the status
+    /// bar instead of the graph counts while it runs
+    pub(super) fn bulk_progress(&self) -> Option<String> {
+        self.imp()
+            .bulk_operations
+            .borrow()
+            .first()
+            .map(BulkOperation::progress)
+    }
+
+    /// Count a finished link request towards its bulk operation, announcing
+    /// the result when it was the last. Returns whether the request was
+    /// part of one, in which case its failure is left for the summary.
+    pub(super) fn bulk_request_finished(&self, request_id: u64, succeeded: bool) -> bool {
+        let finished = {
+            let mut operations = self.imp().bulk_operations.borrow_mut();
+            let Some(index) = operations
+                .iter()
+                .position(|operation| operation.pending.contains(&request_id))
+            else {
+                return false;
+            };
+            let operation = &mut operations[index];
+            operation.pending.remove(&request_id);
+            if !succeeded {
+                operation.failed += 1;
+            }
+            if !operation.pending.is_empty() {
+                drop(operations);
+                self.update_status_counts();
+                return true;
+            }
+            operations.remove(index)
+        };
+
+        if self.imp().bulk_operations.borrow().is_empty() {
+            self.imp().bulk_spinner.stop();
+            self.imp().bulk_spinner.set_visible(false);
+        }
+        self.update_status_counts();
+
+        let summary = finished.summary();
+        self.record_activity(&summary);
+        if finished.toast {
+            self.show_toast(&summary);
+        }
+        if finished.failed > 0 {
+            self.announce_error(&summary);
+        } else {
+            self.announce(&summary);
+        }
+        true
+    }
+}
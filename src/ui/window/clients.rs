@@ -0,0 +1,31 @@
+use adw::subclass::prelude::*;
+
+use super::Window;
+use crate::pipewire::state::PwClient;
+
+impl Window {
+    /// Store a client that connected or whose properties changed, and
+    /// rename the streams it owns, which are named after it, and mark the
+    /// links it made
+    pub(super) fn client_changed(&self, client: PwClient) {
+        let id = client.id;
+        let (owned, links): (Vec<u32>, Vec<u32>) = {
+            let mut pw_state = self.imp().pw_state.borrow_mut();
+            pw_state.clients.insert(id, client);
+            let owned = pw_state
+                .nodes
+                .values()
+                .filter(|node| node.client_id == Some(id))
+                .map(|node| node.id)
+                .collect();
+            (owned, pw_state.links_of_client(id))
+        };
+        for node_id in owned {
+            self.refresh_node_labels(node_id);
+        }
+        // Links can arrive before the client that made them is known
+        for link_id in links {
+            self.refresh_link_origin(link_id);
+        }
+    }
+}
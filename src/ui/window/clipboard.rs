@@ -0,0 +1,176 @@
+use adw::prelude::*;
+use adw::subclass::prelude::*;
+use gtk::{gio, glib};
+
+use super::bulk_progress::BulkOperation;
+use super::Window;
+use crate::model::LinkObject;
+use crate::presets::{ConnectionSnippet, PresetConnection};
+
+impl Window {
+    /// Copy the labels of the selected ports in one list, one per line
+    pub(super) fn copy_port_names(&self, is_output: bool) {
+        let ports = self.selected_ports(is_output);
+        if ports.is_empty() {
+            self.announce("No port selected");
+            return;
+        }
+
+        let text = ports
+            .iter()
+            .map(|port| port.display_label())
+            .collect::<Vec<_>>()
+            .join("\n");
+        self.copy_text(&text, ports.len(), "name");
+    }
+
+    /// Copy `pw-link` commands creating the connections Connect would make
+    /// between the selected ports
+    pub(super) fn copy_port_commands(&self) {
+        let Some(pairs) = self.selected_pairs() else {
+            return;
+        };
+
+        let commands: Vec<String> = pairs
+            .iter()
+            .filter_map(|&(output_id, input_id)| self.pw_link_command(output_id, input_id))
+            .collect();
+        self.copy_text(&commands.join("\n"), commands.len(), "command");
+    }
+
+    /// Copy the selected connection's label, or a `pw-link` command recreating it
+    pub(super) fn copy_connection(&self, as_command: bool) {
+        let link = self
+            .imp()
+            .connections_selection
+            .borrow()
+            .as_ref()
+            .and_then(|s| s.selected_item())
+            .and_downcast::<LinkObject>();
+        let Some(link) = link else {
+            self.announce("No connection selected");
+            return;
+        };
+
+        if as_command {
+            match self.pw_link_command(link.output_port_id(), link.input_port_id()) {
+                Some(command) => self.copy_text(&command, 1, "command"),
+                None => self.announce("The ports of this connection are no longer known"),
+            }
+        } else {
+            self.copy_text(&link.display_label(), 1, "name");
+        }
+    }
+
+    /// Copy the connections shown in the connections list as JSON, which
+    /// Paste Connections can recreate here or on another machine
+    pub(super) fn copy_connections_json(&self) {
+        let connections: Vec<PresetConnection> = {
+            let Some(selection) = self.imp().connections_selection.borrow().clone() else {
+                return;
+            };
+            let pw_state = self.imp().pw_state.borrow();
+            (0..selection.n_items())
+                .filter_map(|i| selection.item(i).and_downcast::<LinkObject>())
+                .filter_map(|link| pw_state.links.get(&link.id()))
+                .filter_map(|link| PresetConnection::from_link(&pw_state, link))
+                .collect()
+        };
+
+        if connections.is_empty() {
+            self.announce("No connections to copy");
+            return;
+        }
+
+        let count = connections.len();
+        match ConnectionSnippet::new(connections).to_json() {
+            Ok(json) => self.copy_text(&json, count, "connection"),
+            Err(e) => self.announce(&e),
+        }
+    }
+
+    /// Recreate connections copied with Copy Connections as JSON, matching
+    /// their ports by name
+    pub(super) fn paste_connections_json(&self) {
+        self.clipboard().read_text_async(
+            None::<&gio::Cancellable>,
+            glib::clone!(
+                #[weak(rename_to = window)]
+                self,
+                move |result| {
+                    let text = match result {
+                        Ok(Some(text)) => text,
+                        Ok(None) => {
+                            window.announce("The clipboard is empty");
+                            return;
+                        }
+                        Err(e) => {
+                            window.announce(&format!("Failed to read the clipboard: {}", e));
+                            return;
+                        }
+                    };
+                    match ConnectionSnippet::from_json(&text) {
+                        Ok(snippet) => window.paste_snippet(&snippet),
+                        Err(e) => window.announce(&e),
+                    }
+                }
+            ),
+        );
+    }
+
+    fn paste_snippet(&self, snippet: &ConnectionSnippet) {
+        let (request_ids, skipped) = self.create_missing_links(&snippet.connections);
+        match (request_ids.len(), skipped) {
+            (0, 0) => self.announce("No connections to paste"),
+            (0, _) => self.announce(&format!(
+                "All {} pasted connections already exist or their ports are missing",
+                skipped
+            )),
+            _ => self.begin_bulk_operation(BulkOperation::new(
+                "Pasting connections".to_string(),
+                "Pasted connections".to_string(),
+                request_ids,
+                skipped,
+            )),
+        }
+    }
+
+    /// `pw-link "node:port" "node:port"` for a pair of ports
+    fn pw_link_command(&self, output_port_id: u32, input_port_id: u32) -> Option<String> {
+        let pw_state = self.imp().pw_state.borrow();
+        let output = pw_state.port_path(output_port_id)?;
+        let input = pw_state.port_path(input_port_id)?;
+        Some(format!(
+            "pw-link {} {}",
+            shell_quote(&output),
+            shell_quote(&input)
+        ))
+    }
+
+    fn copy_text(&self, text: &str, count: usize, what: &str) {
+        if count == 0 {
+            self.announce("Nothing to copy");
+            return;
+        }
+        self.clipboard().set_text(text);
+        if count == 1 {
+            self.announce(&format!("Copied {}", what));
+        } else {
+            self.announce(&format!("Copied {} {}s", count, what));
+        }
+    }
+}
+
+/// Double-quote a string for a POSIX shell
+fn shell_quote(s: &str) -> String {
+    let mut quoted = String::with_capacity(s.len() + 2);
+    quoted.push('"');
+    for c in s.chars() {
+        if matches!(c, '"' | '\\' | '$' | '`') {
+            quoted.push('\\');
+        }
+        quoted.push(c);
+    }
+    quoted.push('"');
+    quoted
+}
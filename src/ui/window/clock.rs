@@ -0,0 +1,202 @@
+use adw::prelude::*;
+use adw::subclass::prelude::*;
+use gtk::glib;
+
+use super::Window;
+use crate::pipewire::clock::{self, ClockSettings};
+use crate::pipewire::UiCommand;
+
+/// Labels for a dropdown choosing a value from `values`, led by `none`
+fn choice_labels(none: &str, values: &[u32], unit: &str) -> Vec<String> {
+    std::iter::once(none.to_string())
+        .chain(values.iter().map(|v| format!("{} {}", v, unit)))
+        .collect()
+}
+
+/// Dropdown position of `value`, 0 for unset or a value not offered
+fn choice_position(values: &[u32], value: Option<u32>) -> u32 {
+    value
+        .and_then(|value| values.iter().position(|v| *v == value))
+        .map_or(0, |i| i as u32 + 1)
+}
+
+/// Value at a dropdown position, 0 (unset) for the first entry
+fn choice_value(values: &[u32], position: u32) -> u32 {
+    position
+        .checked_sub(1)
+        .and_then(|i| values.get(i as usize).copied())
+        .unwrap_or(0)
+}
+
+/// Rate and quantum the graph is running at, for the dialog and announcements
+fn describe_clock(settings: &ClockSettings) -> String {
+    match (settings.get(clock::RATE), settings.get(clock::QUANTUM)) {
+        (Some(rate), Some(quantum)) => format!(
+            "Running at {} Hz with a quantum of {} samples ({:.1} ms).",
+            rate,
+            quantum,
+            quantum as f64 * 1000.0 / rate as f64
+        ),
+        (Some(rate), None) => format!("Running at {} Hz.", rate),
+        _ => "The current rate is unknown.".to_string(),
+    }
+}
+
+impl Window {
+    /// Track a clock value of the server's settings
+    pub(super) fn clock_setting_changed(&self, key: String, value: Option<String>) {
+        self.imp().clock_settings.borrow_mut().update(key, value);
+    }
+
+    /// Show a dialog to force the graph's sample rate and quantum, as
+    /// `pw-metadata -n settings` does. The values last until PipeWire restarts.
+    pub(super) fn show_clock_dialog(&self) {
+        let settings = self.imp().clock_settings.borrow().clone();
+        if settings.is_empty() {
+            self.announce("PipeWire has not reported its clock settings");
+            return;
+        }
+
+        let dialog = adw::MessageDialog::builder()
+            .transient_for(&self.dialog_parent())
+            .modal(true)
+            .heading("Sample Rate and Quantum")
+            .body(format!(
+                "{} A smaller quantum lowers latency but needs more CPU. \
+                 Changes apply to the whole PipeWire graph until it restarts.",
+                describe_clock(&settings)
+            ))
+            .build();
+
+        let content = gtk::Box::builder()
+            .orientation(gtk::Orientation::Vertical)
+            .spacing(12)
+            .build();
+
+        let rates = settings.allowed_rates();
+        let quantums = clock::COMMON_QUANTUMS;
+
+        let add_choice = |label: &str, none: &str, values: &[u32], unit: &str, key: &str| {
+            let labels = choice_labels(none, values, unit);
+            let labels: Vec<&str> = labels.iter().map(String::as_str).collect();
+            let dropdown = gtk::DropDown::from_strings(&labels);
+            dropdown.set_selected(choice_position(values, settings.get(key)));
+            dropdown.update_property(&[gtk::accessible::Property::Label(label)]);
+            content.append(&dropdown);
+            dropdown
+        };
+        let force_rate = add_choice(
+            "Force sample rate",
+            "Automatic sample rate",
+            &rates,
+            "Hz",
+            clock::FORCE_RATE,
+        );
+        let force_quantum = add_choice(
+            "Force quantum",
+            "Automatic quantum",
+            quantums,
+            "samples",
+            clock::FORCE_QUANTUM,
+        );
+        let min_quantum = add_choice(
+            "Minimum quantum",
+            "Default minimum quantum",
+            quantums,
+            "samples",
+            clock::MIN_QUANTUM,
+        );
+        let max_quantum = add_choice(
+            "Maximum quantum",
+            "Default maximum quantum",
+            quantums,
+            "samples",
+            clock::MAX_QUANTUM,
+        );
+
+        dialog.set_extra_child(Some(&content));
+
+        dialog.add_response("cancel", "Cancel");
+        dialog.add_response("reset", "Reset");
+        dialog.add_response("apply", "Apply");
+        dialog.set_response_appearance("apply", adw::ResponseAppearance::Suggested);
+        dialog.set_default_response(Some("apply"));
+        dialog.set_close_response("cancel");
+
+        dialog.connect_response(
+            None,
+            glib::clone!(
+                #[weak(rename_to = window)]
+                self,
+                #[weak]
+                force_rate,
+                #[weak]
+                force_quantum,
+                #[weak]
+                min_quantum,
+                #[weak]
+                max_quantum,
+                move |dialog, response| {
+                    dialog.close();
+                    let reset = match response {
+                        "apply" => false,
+                        "reset" => true,
+                        _ => return,
+                    };
+                    let value = |dropdown: &gtk::DropDown, values: &[u32]| {
+                        if reset {
+                            0
+                        } else {
+                            choice_value(values, dropdown.selected())
+                        }
+                    };
+                    window.apply_clock_settings(&[
+                        (clock::FORCE_RATE, value(&force_rate, &rates)),
+                        (clock::FORCE_QUANTUM, value(&force_quantum, quantums)),
+                        (clock::MIN_QUANTUM, value(&min_quantum, quantums)),
+                        (clock::MAX_QUANTUM, value(&max_quantum, quantums)),
+                    ]);
+                }
+            ),
+        );
+
+        dialog.present();
+        force_quantum.grab_focus();
+    }
+
+    /// Write the changed clock overrides, 0 removing one
+    fn apply_clock_settings(&self, values: &[(&str, u32)]) {
+        let changed: Vec<(&str, u32)> = {
+            let settings = self.imp().clock_settings.borrow();
+            values
+                .iter()
+                .filter(|(key, value)| settings.get(key).unwrap_or(0) != *value)
+                .copied()
+                .collect()
+        };
+        if changed.is_empty() {
+            return;
+        }
+        if self.imp().command_tx.borrow().is_none() {
+            self.announce("Not connected to PipeWire");
+            return;
+        }
+
+        let forced = values
+            .iter()
+            .find(|(key, _)| *key == clock::FORCE_QUANTUM)
+            .map_or(0, |(_, value)| *value);
+        for (key, value) in changed {
+            self.send_command(UiCommand::SetClockSetting {
+                key: key.to_string(),
+                value: value.to_string(),
+            });
+        }
+
+        if forced > 0 {
+            self.announce(&format!("Quantum forced to {} samples", forced));
+        } else {
+            self.announce("Clock settings changed");
+        }
+    }
+}
@@ -0,0 +1,173 @@
+use adw::prelude::*;
+use adw::subclass::prelude::*;
+use gtk::{gio, glib};
+
+use super::Window;
+use crate::settings::CloseAction;
+
+impl Window {
+    /// Handle the window's close button according to the close action setting.
+    /// The window itself is never destroyed; it is hidden or the app quits.
+    pub fn close_requested(&self) -> glib::Propagation {
+        // A daemon keeps running; launching the app again shows the window
+        if self.imp().daemon_mode.get() {
+            self.set_visible(false);
+            return glib::Propagation::Stop;
+        }
+
+        // A hidden window could not be brought back without the tray
+        if !self.imp().tray_available.get() {
+            self.quit_application();
+            return glib::Propagation::Stop;
+        }
+
+        let close_action = self.imp().settings.borrow().close_action;
+        match close_action {
+            CloseAction::Ask => self.show_close_dialog(),
+            CloseAction::Tray => self.set_visible(false),
+            CloseAction::Quit => self.quit_application(),
+        }
+        glib::Propagation::Stop
+    }
+
+    fn quit_application(&self) {
+        if let Some(app) = self.application() {
+            app.quit();
+        }
+    }
+
+    /// Ask whether closing should minimize to the tray or quit
+    fn show_close_dialog(&self) {
+        let dialog = adw::MessageDialog::builder()
+            .transient_for(&self.dialog_parent())
+            .modal(true)
+            .heading("Close PW Audioshare?")
+            .body(
+                "In the system tray, PW Audioshare keeps auto-connecting the active preset. \
+                 Quitting stops it.",
+            )
+            .build();
+
+        let remember_check = gtk::CheckButton::builder()
+            .label("Remember my choice")
+            .build();
+        dialog.set_extra_child(Some(&remember_check));
+
+        dialog.add_response("cancel", "Cancel");
+        dialog.add_response("quit", "Quit");
+        dialog.add_response("tray", "Minimize to Tray");
+        dialog.set_response_appearance("tray", adw::ResponseAppearance::Suggested);
+        dialog.set_default_response(Some("tray"));
+        dialog.set_close_response("cancel");
+
+        dialog.connect_response(
+            None,
+            glib::clone!(
+                #[weak(rename_to = window)]
+                self,
+                #[weak]
+                remember_check,
+                move |dialog, response| {
+                    dialog.close();
+                    let Some(close_action) = CloseAction::from_name(response) else {
+                        return;
+                    };
+                    if remember_check.is_active() {
+                        window.remember_close_action(close_action);
+                    }
+                    match close_action {
+                        CloseAction::Quit => window.quit_application(),
+                        _ => window.set_visible(false),
+                    }
+                }
+            ),
+        );
+
+        dialog.present();
+    }
+
+    /// Save a close action chosen in the close dialog and reflect it in the menu
+    fn remember_close_action(&self, close_action: CloseAction) {
+        if let Some(action) = self
+            .lookup_action("close-action")
+            .and_downcast::<gio::SimpleAction>()
+        {
+            action.set_state(&close_action.as_str().to_variant());
+        }
+        self.imp().settings.borrow_mut().close_action = close_action;
+        if let Err(e) = self.imp().settings.borrow().save() {
+            log::warn!("Failed to save settings: {}", e);
+        }
+    }
+
+    /// Change what the close button does and save the choice
+    pub(super) fn set_close_action(&self, close_action: CloseAction) {
+        self.imp().settings.borrow_mut().close_action = close_action;
+
+        if let Err(e) = self.imp().settings.borrow().save() {
+            self.announce(&format!("Failed to save settings: {}", e));
+            return;
+        }
+
+        self.announce(match close_action {
+            CloseAction::Ask => "Closing the window will ask what to do",
+            CloseAction::Tray => "Closing the window will minimize to tray",
+            CloseAction::Quit => "Closing the window will quit",
+        });
+    }
+
+    /// Record whether the tray icon is shown. Without it, closing quits and
+    /// the options about the tray are disabled.
+    pub fn set_tray_available(&self, available: bool) {
+        self.imp().tray_available.set(available);
+        let tray_actions = [
+            "close-action",
+            "start-minimized",
+            "tray-scroll-presets",
+            "tray-middle-click",
+        ];
+        for name in tray_actions {
+            if let Some(action) = self.lookup_action(name).and_downcast::<gio::SimpleAction>() {
+                action.set_enabled(available);
+            }
+        }
+    }
+
+    /// Run as a background service, where closing the window only hides it
+    pub fn set_daemon_mode(&self, daemon: bool) {
+        self.imp().daemon_mode.set(daemon);
+    }
+
+    /// The tray icon failed to appear: make sure the window can't be lost
+    pub fn show_tray_unavailable(&self, reason: &str) {
+        log::warn!("Running without a tray icon: {}", reason);
+        self.set_tray_available(false);
+        if crate::sandbox::is_flatpak() {
+            self.imp().tray_banner.set_title(&format!(
+                "The system tray is unavailable, so closing the window quits PW Audioshare.{}",
+                crate::sandbox::permission_hint("--talk-name=org.kde.StatusNotifierWatcher")
+            ));
+        }
+        self.imp().tray_banner.set_revealed(true);
+        self.set_visible(true);
+        self.present();
+        self.announce("The system tray is unavailable, closing the window will quit");
+    }
+
+    /// Set whether the tray icon is shown and save it. Takes effect at the
+    /// next start.
+    pub(super) fn set_show_tray(&self, show: bool) {
+        self.imp().settings.borrow_mut().show_tray = show;
+
+        if let Err(e) = self.imp().settings.borrow().save() {
+            self.announce(&format!("Failed to save settings: {}", e));
+            return;
+        }
+
+        if show {
+            self.announce("The tray icon will be shown after restarting");
+        } else {
+            self.announce("The tray icon will be hidden after restarting");
+        }
+    }
+}
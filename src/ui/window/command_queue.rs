@@ -0,0 +1,101 @@
+use adw::subclass::prelude::*;
+use async_channel::TrySendError;
+use gtk::glib;
+use std::time::Duration;
+
+use super::Window;
+use crate::pipewire::UiCommand;
+
+/// How often queued commands are offered to the PipeWire thread again
+const RETRY_INTERVAL: Duration = Duration::from_millis(20);
+
+/// Commands waiting beyond the channel before the user is told that
+/// PipeWire is not keeping up
+const BACKLOG_WARNING: usize = 64;
+
+impl Window {
+    /// Send a command to the PipeWire thread without blocking the window.
+    /// When the thread's channel is full, as while a large preset connects
+    /// and the thread is stalled, the command waits in a queue and is sent
+    /// in order once there is room.
+    pub(super) fn send_command(&self, cmd: UiCommand) {
+        if !self.imp().queued_commands.borrow().is_empty() {
+            self.queue_command(cmd);
+            return;
+        }
+        let Some(tx) = self.imp().command_tx.borrow().clone() else {
+            return;
+        };
+        match tx.try_send(cmd) {
+            Ok(()) => {}
+            Err(TrySendError::Full(cmd)) => self.queue_command(cmd),
+            Err(TrySendError::Closed(cmd)) => {
+                log::error!("Failed to send command, PipeWire thread is gone: {:?}", cmd);
+            }
+        }
+    }
+
+    fn queue_command(&self, cmd: UiCommand) {
+        let queued = {
+            let mut queue = self.imp().queued_commands.borrow_mut();
+            queue.push_back(cmd);
+            queue.len()
+        };
+        if queued >= BACKLOG_WARNING && !self.imp().command_backlog_warned.replace(true) {
+            let message = "PipeWire is slow to respond, changes are waiting to be made";
+            log::warn!("{} commands waiting for the PipeWire thread", queued);
+            self.show_toast(message);
+            self.announce(message);
+        }
+        if self.imp().command_retry_scheduled.replace(true) {
+            return;
+        }
+        glib::timeout_add_local(
+            RETRY_INTERVAL,
+            glib::clone!(
+                #[weak(rename_to = window)]
+                self,
+                #[upgrade_or]
+                glib::ControlFlow::Break,
+                move || {
+                    if window.send_queued_commands() {
+                        glib::ControlFlow::Continue
+                    } else {
+                        window.imp().command_retry_scheduled.set(false);
+                        glib::ControlFlow::Break
+                    }
+                }
+            ),
+        );
+    }
+
+    /// Send queued commands until the channel is full again. Returns
+    /// whether some are still waiting.
+    fn send_queued_commands(&self) -> bool {
+        let Some(tx) = self.imp().command_tx.borrow().clone() else {
+            self.imp().queued_commands.borrow_mut().clear();
+            return false;
+        };
+        loop {
+            let Some(cmd) = self.imp().queued_commands.borrow_mut().pop_front() else {
+                break;
+            };
+            match tx.try_send(cmd) {
+                Ok(()) => {}
+                Err(TrySendError::Full(cmd)) => {
+                    self.imp().queued_commands.borrow_mut().push_front(cmd);
+                    return true;
+                }
+                Err(TrySendError::Closed(_)) => {
+                    log::error!("PipeWire thread is gone, dropping queued commands");
+                    self.imp().queued_commands.borrow_mut().clear();
+                    break;
+                }
+            }
+        }
+        if self.imp().command_backlog_warned.replace(false) {
+            log::info!("Commands for the PipeWire thread caught up");
+        }
+        false
+    }
+}
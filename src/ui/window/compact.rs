@@ -0,0 +1,422 @@
+use std::cell::Cell;
+use std::rc::Rc;
+
+use adw::prelude::*;
+use adw::subclass::prelude::*;
+use gtk::glib;
+
+use super::{keep_above, Window};
+use crate::model::LinkObject;
+use crate::pipewire::PwState;
+use crate::presets::PresetConnection;
+
+/// The open compact window, kept in step with presets and the graph
+pub struct CompactView {
+    window: glib::WeakRef<adw::Window>,
+    preset_label: gtk::Label,
+    preset_names: gtk::StringList,
+    preset_dropdown: gtk::DropDown,
+    pinned_box: gtk::Box,
+    /// Check button for each pinned connection, in settings order
+    pinned_checks: Vec<gtk::CheckButton>,
+    /// Set while widgets are updated from the app, so nothing is changed back
+    syncing: Rc<Cell<bool>>,
+}
+
+/// Whether two connections join the same ports by name
+fn same_ports(a: &PresetConnection, b: &PresetConnection) -> bool {
+    a.output_node == b.output_node
+        && a.output_port == b.output_port
+        && a.input_node == b.input_node
+        && a.input_port == b.input_port
+}
+
+/// Readable name of a pinned connection, also while its ports are gone
+fn pinned_label(state: &PwState, pinned: &PresetConnection) -> String {
+    if let (Some(output), Some(input)) = pinned.find_ports(state) {
+        if let (Some(out_label), Some(in_label)) =
+            (state.port_label(output.id), state.port_label(input.id))
+        {
+            return format!("{} → {}", out_label, in_label);
+        }
+    }
+    format!(
+        "{}:{} → {}:{}",
+        pinned.output_node, pinned.output_port, pinned.input_node, pinned.input_port
+    )
+}
+
+impl Window {
+    /// Open a small window with the active preset, a preset switcher and the
+    /// pinned connections, or bring it to the front if it is open
+    pub(super) fn show_compact_window(&self) {
+        if let Some(window) = self
+            .imp()
+            .compact_view
+            .borrow()
+            .as_ref()
+            .and_then(|view| view.window.upgrade())
+        {
+            window.present();
+            return;
+        }
+
+        // Not transient, so it can sit on another monitor on its own
+        let compact = adw::Window::builder()
+            .title("PW Audioshare Compact")
+            .default_width(340)
+            .default_height(360)
+            .build();
+
+        let content = gtk::Box::builder()
+            .orientation(gtk::Orientation::Vertical)
+            .build();
+        let header = adw::HeaderBar::new();
+        let above = self.imp().compact_above.get();
+        let pin_button = gtk::ToggleButton::builder()
+            .icon_name("view-pin-symbolic")
+            .tooltip_text("Keep on Top")
+            .active(above)
+            .build();
+        pin_button.connect_toggled(glib::clone!(
+            #[weak(rename_to = window)]
+            self,
+            #[weak]
+            compact,
+            move |button| {
+                let above = button.is_active();
+                if above == window.imp().compact_above.get() {
+                    return;
+                }
+                // Not from inside the handler, the window may be rebuilt
+                glib::idle_add_local_once(glib::clone!(
+                    #[weak]
+                    window,
+                    #[weak]
+                    compact,
+                    #[weak]
+                    button,
+                    move || {
+                        window.set_compact_keep_above(&compact, above);
+                        if window.imp().compact_above.get() != above {
+                            button.set_active(!above);
+                        }
+                    }
+                ));
+            }
+        ));
+        header.pack_start(&pin_button);
+        content.append(&header);
+
+        let body = gtk::Box::builder()
+            .orientation(gtk::Orientation::Vertical)
+            .spacing(6)
+            .margin_start(12)
+            .margin_end(12)
+            .margin_top(6)
+            .margin_bottom(12)
+            .build();
+
+        let preset_label = gtk::Label::builder()
+            .xalign(0.0)
+            .wrap(true)
+            .css_classes(["heading"])
+            .build();
+        body.append(&preset_label);
+
+        let preset_names = gtk::StringList::new(&[]);
+        let preset_dropdown = gtk::DropDown::builder().model(&preset_names).build();
+        preset_dropdown.update_property(&[gtk::accessible::Property::Label("Active preset")]);
+        body.append(&preset_dropdown);
+
+        let pinned_heading = gtk::Label::builder()
+            .label("Pinned Connections")
+            .xalign(0.0)
+            .margin_top(6)
+            .css_classes(["heading"])
+            .accessible_role(gtk::AccessibleRole::Heading)
+            .build();
+        body.append(&pinned_heading);
+
+        let pinned_box = gtk::Box::builder()
+            .orientation(gtk::Orientation::Vertical)
+            .spacing(2)
+            .build();
+        let scrolled = gtk::ScrolledWindow::builder()
+            .hscrollbar_policy(gtk::PolicyType::Never)
+            .vscrollbar_policy(gtk::PolicyType::Automatic)
+            .vexpand(true)
+            .child(&pinned_box)
+            .build();
+        body.append(&scrolled);
+
+        content.append(&body);
+        compact.set_content(Some(&content));
+
+        let syncing = Rc::new(Cell::new(false));
+        preset_dropdown.connect_selected_notify(glib::clone!(
+            #[weak(rename_to = window)]
+            self,
+            #[strong]
+            syncing,
+            move |dropdown| {
+                if !syncing.get() {
+                    window.compact_preset_selected(dropdown.selected());
+                }
+            }
+        ));
+
+        self.imp().compact_view.replace(Some(CompactView {
+            window: compact.downgrade(),
+            preset_label,
+            preset_names,
+            preset_dropdown: preset_dropdown.clone(),
+            pinned_box,
+            pinned_checks: Vec::new(),
+            syncing,
+        }));
+        self.rebuild_compact_pins();
+        self.sync_compact_window();
+
+        compact.connect_close_request(glib::clone!(
+            #[weak(rename_to = window)]
+            self,
+            #[upgrade_or]
+            glib::Propagation::Proceed,
+            move |_| {
+                window.imp().compact_view.replace(None);
+                glib::Propagation::Proceed
+            }
+        ));
+
+        if above && keep_above::layer_shell_supported() {
+            keep_above::init_above_layer(&compact);
+        } else if above {
+            compact.connect_map(|compact| {
+                glib::idle_add_local_once(glib::clone!(
+                    #[weak]
+                    compact,
+                    move || {
+                        if let Err(message) = keep_above::request_keep_above(&compact, true) {
+                            log::warn!("{}", message);
+                        }
+                    }
+                ));
+            });
+        }
+
+        compact.present();
+        preset_dropdown.grab_focus();
+    }
+
+    /// Activate the preset chosen in the compact window; the first entry is "None"
+    fn compact_preset_selected(&self, position: u32) {
+        if position == 0 {
+            if self.imp().preset_store.borrow().active_preset.is_some() {
+                self.deactivate_preset();
+            }
+            return;
+        }
+
+        let name = self
+            .imp()
+            .preset_store
+            .borrow()
+            .preset_names()
+            .get(position as usize - 1)
+            .cloned();
+        if let Some(name) = name {
+            self.activate_preset(&name);
+        }
+    }
+
+    /// Recreate the check buttons after the pinned connections changed
+    fn rebuild_compact_pins(&self) {
+        let mut view = self.imp().compact_view.borrow_mut();
+        let Some(view) = view.as_mut() else {
+            return;
+        };
+
+        while let Some(child) = view.pinned_box.first_child() {
+            view.pinned_box.remove(&child);
+        }
+        view.pinned_checks.clear();
+
+        let pinned = self.imp().settings.borrow().pinned_connections.clone();
+        if pinned.is_empty() {
+            let empty = gtk::Label::builder()
+                .label("Pin connections with Ctrl+P in the connections list to show them here.")
+                .wrap(true)
+                .xalign(0.0)
+                .css_classes(["dim-label"])
+                .build();
+            view.pinned_box.append(&empty);
+            return;
+        }
+
+        let pw_state = self.imp().pw_state.borrow();
+        for (index, connection) in pinned.iter().enumerate() {
+            let check = gtk::CheckButton::builder()
+                .label(pinned_label(&pw_state, connection))
+                .build();
+            let syncing = view.syncing.clone();
+            check.connect_toggled(glib::clone!(
+                #[weak(rename_to = window)]
+                self,
+                move |check| {
+                    if !syncing.get() {
+                        window.toggle_pinned_connection(index, check.is_active());
+                    }
+                }
+            ));
+            view.pinned_box.append(&check);
+            view.pinned_checks.push(check);
+        }
+    }
+
+    /// Update the compact window from the active preset and the graph
+    pub(super) fn sync_compact_window(&self) {
+        let view = self.imp().compact_view.borrow();
+        let Some(view) = view.as_ref() else {
+            return;
+        };
+        view.syncing.set(true);
+
+        let store = self.imp().preset_store.borrow();
+        let mut entries = vec!["None".to_string()];
+        entries.extend(store.preset_names());
+        let current: Vec<String> = (0..view.preset_names.n_items())
+            .filter_map(|i| view.preset_names.string(i).map(|s| s.to_string()))
+            .collect();
+        if current != entries {
+            let entries: Vec<&str> = entries.iter().map(String::as_str).collect();
+            view.preset_names
+                .splice(0, view.preset_names.n_items(), &entries);
+        }
+
+        let position = store
+            .active_preset
+            .as_ref()
+            .and_then(|active| store.preset_names().iter().position(|name| name == active))
+            .map_or(0, |index| index as u32 + 1);
+        view.preset_dropdown.set_selected(position);
+        view.preset_label.set_text(&match &store.active_preset {
+            Some(name) => format!("Active preset: {}", name),
+            None => "No preset active".to_string(),
+        });
+
+        let pw_state = self.imp().pw_state.borrow();
+        let settings = self.imp().settings.borrow();
+        for (check, connection) in view.pinned_checks.iter().zip(&settings.pinned_connections) {
+            let connected = match connection.find_ports(&pw_state) {
+                (Some(output), Some(input)) => pw_state.link_exists(output.id, input.id),
+                _ => false,
+            };
+            check.set_active(connected);
+            check.set_label(Some(&pinned_label(&pw_state, connection)));
+        }
+
+        view.syncing.set(false);
+    }
+
+    /// Connect or disconnect a pinned connection from its check button
+    fn toggle_pinned_connection(&self, index: usize, connect: bool) {
+        let Some(connection) = self
+            .imp()
+            .settings
+            .borrow()
+            .pinned_connections
+            .get(index)
+            .cloned()
+        else {
+            return;
+        };
+
+        let ports = match connection.find_ports(&self.imp().pw_state.borrow()) {
+            (Some(output), Some(input)) => Some((output.id, input.id)),
+            _ => None,
+        };
+        let Some((output_port_id, input_port_id)) = ports else {
+            self.announce("Cannot connect: the ports of this connection are gone");
+            self.sync_compact_window();
+            return;
+        };
+
+        let existing = self
+            .imp()
+            .pw_state
+            .borrow()
+            .find_link(output_port_id, input_port_id)
+            .map(|link| link.id);
+        match (connect, existing) {
+            (true, None) => {
+                if !self
+                    .imp()
+                    .pending_links
+                    .borrow()
+                    .contains_key(&(output_port_id, input_port_id))
+                {
+                    self.warn_video_incompatibility(&[(output_port_id, input_port_id)]);
+                    self.create_link(output_port_id, input_port_id);
+                }
+            }
+            (false, Some(link_id)) => self.delete_link_remembered(link_id),
+            _ => {}
+        }
+    }
+
+    /// Pin the selected connection to the compact window, or unpin it
+    pub(super) fn toggle_pin_selected_connection(&self) {
+        let link = self
+            .imp()
+            .connections_selection
+            .borrow()
+            .as_ref()
+            .and_then(|s| s.selected_item())
+            .and_downcast::<LinkObject>();
+        let Some(link) = link else {
+            self.announce("No connection selected");
+            return;
+        };
+
+        let connection = {
+            let pw_state = self.imp().pw_state.borrow();
+            pw_state
+                .links
+                .get(&link.id())
+                .and_then(|l| PresetConnection::from_link(&pw_state, l))
+        };
+        let Some(connection) = connection else {
+            self.announce("The ports of this connection are no longer known");
+            return;
+        };
+
+        let pinned = {
+            let mut settings = self.imp().settings.borrow_mut();
+            let pins = &mut settings.pinned_connections;
+            match pins.iter().position(|p| same_ports(p, &connection)) {
+                Some(index) => {
+                    pins.remove(index);
+                    false
+                }
+                None => {
+                    pins.push(connection);
+                    true
+                }
+            }
+        };
+
+        if let Err(e) = self.imp().settings.borrow().save() {
+            self.announce(&format!("Failed to save settings: {}", e));
+            return;
+        }
+
+        self.rebuild_compact_pins();
+        self.sync_compact_window();
+        if pinned {
+            self.announce(&format!("Pinned {}", link.display_label()));
+        } else {
+            self.announce(&format!("Unpinned {}", link.display_label()));
+        }
+    }
+}
@@ -0,0 +1,178 @@
+use std::path::{Path, PathBuf};
+
+use adw::prelude::*;
+use adw::subclass::prelude::*;
+use gtk::{gio, glib};
+
+use super::Window;
+use crate::config_bundle::{ConfigBundle, ImportMode};
+
+impl Window {
+    /// Ask where to save the settings and presets as one file
+    pub(super) fn show_export_config_dialog(&self) {
+        let dialog = gtk::FileDialog::builder()
+            .title("Export Settings and Presets")
+            .initial_name("pw-audioshare-config.json")
+            .build();
+        dialog.save(
+            Some(&self.dialog_parent()),
+            None::<&gio::Cancellable>,
+            glib::clone!(
+                #[weak(rename_to = window)]
+                self,
+                move |result| {
+                    let Some(path) = result.ok().and_then(|file| file.path()) else {
+                        return;
+                    };
+                    window.export_config(&path);
+                }
+            ),
+        );
+    }
+
+    fn export_config(&self, path: &Path) {
+        let bundle = ConfigBundle::collect(
+            &self.imp().settings.borrow(),
+            &self.imp().preset_store.borrow(),
+        );
+        match bundle.write(path) {
+            Ok(()) => {
+                let message = format!(
+                    "Exported settings, {} presets and {} scripts to {}",
+                    bundle.presets.presets.len(),
+                    bundle.scripts.len(),
+                    path.display()
+                );
+                self.record_activity(&message);
+                self.announce(&message);
+            }
+            Err(e) => {
+                self.show_toast(&e);
+                self.announce_error(&e);
+            }
+        }
+    }
+
+    /// Pick an exported configuration and ask how to import it
+    pub(super) fn show_import_config_dialog(&self) {
+        let dialog = gtk::FileDialog::builder()
+            .title("Import Settings and Presets")
+            .build();
+        dialog.open(
+            Some(&self.dialog_parent()),
+            None::<&gio::Cancellable>,
+            glib::clone!(
+                #[weak(rename_to = window)]
+                self,
+                move |result| {
+                    let Some(path) = result.ok().and_then(|file| file.path()) else {
+                        return;
+                    };
+                    window.confirm_import_config(path);
+                }
+            ),
+        );
+    }
+
+    fn confirm_import_config(&self, path: PathBuf) {
+        let bundle = match ConfigBundle::read(&path) {
+            Ok(bundle) => bundle,
+            Err(e) => {
+                self.show_toast(&e);
+                self.announce_error(&e);
+                return;
+            }
+        };
+
+        let dialog = adw::MessageDialog::builder()
+            .transient_for(&self.dialog_parent())
+            .modal(true)
+            .heading("Import Settings and Presets")
+            .body(format!(
+                "The file has {} presets. Merge adds presets, saved filters, pinned \
+                 connections and tunnels you don't have yet and keeps everything else. \
+                 Replace makes your settings and presets exactly those of the file.",
+                bundle.presets.presets.len()
+            ))
+            .build();
+
+        // Scripts run with the user's permissions, so a shared file only
+        // installs them when asked to, knowing their names
+        let with_scripts = (!bundle.scripts.is_empty()).then(|| {
+            let names: Vec<&str> = bundle.scripts.keys().map(String::as_str).collect();
+            let label = gtk::Label::builder()
+                .label(format!(
+                    "Also install its Lua scripts, which run with your permissions: {}",
+                    names.join(", ")
+                ))
+                .wrap(true)
+                .xalign(0.0)
+                .build();
+            let check = gtk::CheckButton::builder().child(&label).build();
+            dialog.set_extra_child(Some(&check));
+            check
+        });
+
+        dialog.add_response("cancel", "Cancel");
+        dialog.add_response("overwrite", "Replace");
+        dialog.add_response("merge", "Merge");
+        dialog.set_response_appearance("overwrite", adw::ResponseAppearance::Destructive);
+        dialog.set_response_appearance("merge", adw::ResponseAppearance::Suggested);
+        dialog.set_default_response(Some("merge"));
+        dialog.set_close_response("cancel");
+
+        dialog.connect_response(
+            None,
+            glib::clone!(
+                #[weak(rename_to = window)]
+                self,
+                move |dialog, response| {
+                    dialog.close();
+                    let mode = match response {
+                        "merge" => ImportMode::Merge,
+                        "overwrite" => ImportMode::Overwrite,
+                        _ => return,
+                    };
+                    let scripts = with_scripts.as_ref().is_some_and(|check| check.is_active());
+                    window.import_config(bundle.clone(), mode, scripts);
+                }
+            ),
+        );
+
+        dialog.present();
+    }
+
+    fn import_config(&self, bundle: ConfigBundle, mode: ImportMode, with_scripts: bool) {
+        let result = {
+            let mut settings = self.imp().settings.borrow_mut();
+            let mut presets = self.imp().preset_store.borrow_mut();
+            bundle
+                .apply(mode, with_scripts, &mut settings, &mut presets)
+                .and_then(|summary| {
+                    settings.save()?;
+                    presets.save()?;
+                    Ok(summary)
+                })
+        };
+
+        match result {
+            Ok(summary) => {
+                self.update_active_preset_display();
+                self.refresh_preset_accels();
+                let mut message = format!(
+                    "Imported {} presets and {} scripts",
+                    summary.presets, summary.scripts
+                );
+                if mode == ImportMode::Overwrite {
+                    message.push_str(". Restart PW Audioshare to apply all imported settings");
+                }
+                self.record_activity(&message);
+                self.announce(&message);
+            }
+            Err(e) => {
+                self.show_toast(&e);
+                self.announce_error(&e);
+            }
+        }
+    }
+}
@@ -0,0 +1,197 @@
+use std::time::Duration;
+
+use adw::prelude::*;
+use adw::subclass::prelude::*;
+use gtk::{gio, glib};
+
+use super::Window;
+use crate::gsettings;
+use crate::presets::PresetStore;
+use crate::settings::Settings;
+
+/// Wait after the last change before reading, so an editor or Syncthing
+/// has finished writing
+const RELOAD_DELAY: Duration = Duration::from_millis(500);
+
+/// Whether two values would be saved identically, to tell external edits
+/// from the app's own saves
+fn same_json<T: serde::Serialize>(a: &T, b: &T) -> bool {
+    serde_json::to_value(a).ok() == serde_json::to_value(b).ok()
+}
+
+impl Window {
+    /// Watch settings.json and presets.json for edits made outside the app
+    pub(super) fn watch_config_files(&self) {
+        if self.is_server_window() {
+            return;
+        }
+        let paths = [Settings::settings_path(), PresetStore::presets_path()];
+        for path in paths.into_iter().flatten() {
+            let file = gio::File::for_path(&path);
+            let monitor = match file.monitor_file(
+                gio::FileMonitorFlags::WATCH_MOVES,
+                None::<&gio::Cancellable>,
+            ) {
+                Ok(monitor) => monitor,
+                Err(e) => {
+                    log::warn!("Cannot watch {}: {}", path.display(), e);
+                    continue;
+                }
+            };
+            monitor.connect_changed(glib::clone!(
+                #[weak(rename_to = window)]
+                self,
+                move |_, _, _, event| {
+                    if matches!(
+                        event,
+                        gio::FileMonitorEvent::ChangesDoneHint
+                            | gio::FileMonitorEvent::Created
+                            | gio::FileMonitorEvent::MovedIn
+                            | gio::FileMonitorEvent::Renamed
+                    ) {
+                        window.schedule_config_reload();
+                    }
+                }
+            ));
+            self.imp().config_monitors.borrow_mut().push(monitor);
+        }
+
+        // Settings stored in GSettings announce their own changes, also
+        // those made with dconf or by another instance
+        let watch = gsettings::watch(glib::clone!(
+            #[weak(rename_to = window)]
+            self,
+            move || window.schedule_config_reload()
+        ));
+        self.imp().settings_watch.replace(watch);
+    }
+
+    /// Reload once the burst of change events for a write is over
+    fn schedule_config_reload(&self) {
+        if self.imp().config_reload_pending.replace(true) {
+            return;
+        }
+        glib::timeout_add_local_once(
+            RELOAD_DELAY,
+            glib::clone!(
+                #[weak(rename_to = window)]
+                self,
+                move || {
+                    window.imp().config_reload_pending.set(false);
+                    window.reload_settings();
+                    window.reload_presets();
+                }
+            ),
+        );
+    }
+
+    /// Take settings changed outside the app. Menu toggles follow them; the tray,
+    /// log file, control socket and OSC server change at the next start.
+    fn reload_settings(&self) {
+        if let Some(action) = self
+            .lookup_action("settings-gsettings")
+            .and_downcast::<gio::SimpleAction>()
+        {
+            action.set_state(&gsettings::enabled().to_variant());
+        }
+
+        let settings = match Settings::read() {
+            Ok(settings) => settings,
+            Err(e) => {
+                log::debug!("Not reloading settings: {}", e);
+                return;
+            }
+        };
+        if same_json(&settings, &*self.imp().settings.borrow()) {
+            return;
+        }
+        log::info!("Settings changed outside the app, reloading");
+
+        let states = [
+            ("start-minimized", settings.start_minimized.to_variant()),
+            (
+                "tray-scroll-presets",
+                settings.tray_scroll_presets.to_variant(),
+            ),
+            (
+                "tray-middle-click",
+                settings.tray_middle_click_toggle.to_variant(),
+            ),
+            ("link-passive", settings.link_passive.to_variant()),
+            ("link-linger", settings.link_linger.to_variant()),
+            (
+                "confirm-bulk-disconnect",
+                settings.confirm_bulk_disconnect.to_variant(),
+            ),
+            (
+                "replace-on-preset-switch",
+                settings.replace_on_preset_switch.to_variant(),
+            ),
+            ("close-action", settings.close_action.as_str().to_variant()),
+            (
+                "announcement-verbosity",
+                settings.announcement_verbosity.as_str().to_variant(),
+            ),
+            (
+                "startup-port-types",
+                settings.startup_port_types.as_str().to_variant(),
+            ),
+            ("list-text-size", settings.list_text_size.as_str().to_variant()),
+            ("reduce-motion", settings.reduce_motion.to_variant()),
+            ("swap-port-panes", settings.swap_port_panes.to_variant()),
+            ("stack-port-panes", settings.stack_port_panes.to_variant()),
+            ("appearance", settings.appearance.as_str().to_variant()),
+            ("media-colors", settings.media_colors.to_variant()),
+            ("inhibit-suspend", settings.inhibit_suspend.to_variant()),
+        ];
+        let sort_changed = settings.port_sort != self.imp().settings.borrow().port_sort;
+        let port_sort = settings.port_sort;
+        self.imp().settings.replace(settings);
+
+        for (name, state) in states {
+            if let Some(action) = self.lookup_action(name).and_downcast::<gio::SimpleAction>() {
+                action.set_state(&state);
+            }
+        }
+        self.apply_list_text_size();
+        self.apply_reduce_motion();
+        self.apply_pane_layout();
+        self.apply_appearance();
+        self.update_suspend_inhibit();
+        if sort_changed {
+            if let Some(action) = self
+                .lookup_action("port-sort")
+                .and_downcast::<gio::SimpleAction>()
+            {
+                action.set_state(&port_sort.as_str().to_variant());
+            }
+            self.apply_port_sort();
+        }
+        self.record_activity("Settings reloaded after an outside change");
+    }
+
+    /// Take presets edited on disk, connecting a newly active preset
+    fn reload_presets(&self) {
+        let store = match PresetStore::read() {
+            Ok(store) => store,
+            Err(e) => {
+                log::debug!("Not reloading presets: {}", e);
+                return;
+            }
+        };
+        if same_json(&store, &*self.imp().preset_store.borrow()) {
+            return;
+        }
+        log::info!("Presets changed on disk, reloading");
+
+        let activated = store.active_preset.is_some()
+            && store.active_preset != self.imp().preset_store.borrow().active_preset;
+        self.imp().preset_store.replace(store);
+        self.update_active_preset_display();
+        self.refresh_preset_accels();
+        if activated {
+            self.check_auto_connect();
+        }
+        self.record_activity("Presets reloaded after an outside change");
+    }
+}
@@ -0,0 +1,96 @@
+use adw::prelude::*;
+use adw::subclass::prelude::*;
+
+use super::Window;
+use crate::model::PortObject;
+
+impl Window {
+    /// Two-step connecting: activating a port (Enter or double-click) arms
+    /// it, activating a port in the other list connects the two
+    pub(super) fn activate_port(&self, port: &PortObject) {
+        let armed = self.imp().armed_port.borrow().clone();
+
+        let Some(armed) = armed else {
+            self.arm_port(port);
+            return;
+        };
+
+        if armed.id() == port.id() {
+            self.cancel_armed_port();
+            return;
+        }
+        if armed.direction() == port.direction() {
+            self.arm_port(port);
+            return;
+        }
+
+        let (output, input) = if armed.is_output() {
+            (armed, port.clone())
+        } else {
+            (port.clone(), armed)
+        };
+        self.imp().armed_port.replace(None);
+        self.update_status_counts();
+
+        let (output_port_id, input_port_id) = (output.id(), input.id());
+        if self
+            .imp()
+            .pw_state
+            .borrow()
+            .link_exists(output_port_id, input_port_id)
+        {
+            self.announce(&format!(
+                "{} is already connected to {}",
+                output.display_label(),
+                input.display_label()
+            ));
+            return;
+        }
+
+        self.warn_video_incompatibility(&[(output_port_id, input_port_id)]);
+        let request_id = self.create_link(output_port_id, input_port_id);
+        self.remember_created_link(output_port_id, input_port_id);
+        self.imp()
+            .toast_link_requests
+            .borrow_mut()
+            .insert(request_id, (output_port_id, input_port_id));
+        self.announce(&format!(
+            "Connecting {} to {}",
+            output.display_label(),
+            input.display_label()
+        ));
+    }
+
+    /// Remember a port as the first end of a connection
+    fn arm_port(&self, port: &PortObject) {
+        self.imp().armed_port.replace(Some(port.clone()));
+
+        let other = if port.is_output() {
+            "an input"
+        } else {
+            "an output"
+        };
+        self.update_status(
+            &format!(
+                "Connecting from {} (Escape to cancel)",
+                port.display_label()
+            ),
+            false,
+        );
+        self.announce(&format!(
+            "Armed {}. Choose {} port and press Enter to connect, or Escape to cancel",
+            port.display_label(),
+            other
+        ));
+    }
+
+    /// Forget the armed port. Returns whether one was armed.
+    pub(super) fn cancel_armed_port(&self) -> bool {
+        if self.imp().armed_port.replace(None).is_none() {
+            return false;
+        }
+        self.update_status_counts();
+        self.announce("Connection cancelled");
+        true
+    }
+}
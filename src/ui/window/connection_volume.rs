@@ -0,0 +1,149 @@
+use adw::prelude::*;
+use adw::subclass::prelude::*;
+use gtk::glib;
+
+use super::Window;
+use crate::model::LinkObject;
+use crate::pipewire::filters::{self, FilterPreset};
+use crate::pipewire::UiCommand;
+
+/// Highest volume offered, in percent
+const MAX_VOLUME: f64 = 150.0;
+
+impl Window {
+    /// The Volume filter a connection was routed through, found from the
+    /// original connection or either of the links to and from the filter
+    fn volume_filter_for_link(&self, link: &LinkObject) -> Option<String> {
+        let candidates: Vec<(String, u32, u32)> = self
+            .imp()
+            .inserted_filters
+            .borrow()
+            .iter()
+            .filter(|f| f.preset == FilterPreset::Volume)
+            .map(|f| (f.key.clone(), f.output_port_id, f.input_port_id))
+            .collect();
+
+        candidates
+            .into_iter()
+            .find(|(key, output_port_id, input_port_id)| {
+                let original =
+                    link.output_port_id() == *output_port_id && link.input_port_id() == *input_port_id;
+                original
+                    || self.filter_ports(key).is_some_and(|(filter_in, filter_out)| {
+                        link.input_port_id() == filter_in || link.output_port_id() == filter_out
+                    })
+            })
+            .map(|(key, _, _)| key)
+    }
+
+    /// Show a volume slider for the selected connection, routing it through
+    /// a Volume filter first if it has none, so one connection can be made
+    /// quieter without changing the application's own volume
+    pub(super) fn show_connection_volume_dialog(&self) {
+        let link = self
+            .imp()
+            .connections_selection
+            .borrow()
+            .as_ref()
+            .and_then(|s| s.selected_item())
+            .and_downcast::<LinkObject>();
+        let Some(link) = link else {
+            self.announce("Select a connection to change its volume");
+            return;
+        };
+        if link.media_type() != "audio" {
+            self.announce("Only audio connections have a volume");
+            return;
+        }
+
+        let key = match self.volume_filter_for_link(&link) {
+            Some(key) => key,
+            None => self.insert_filter(&link, FilterPreset::Volume),
+        };
+        let (volume, label) = self
+            .imp()
+            .inserted_filters
+            .borrow()
+            .iter()
+            .find(|f| f.key == key)
+            .map(|f| (f.volume, f.connection_label.clone()))
+            .unwrap_or((100.0, link.display_label()));
+
+        let dialog = adw::MessageDialog::builder()
+            .transient_for(&self.dialog_parent())
+            .modal(true)
+            .heading("Connection Volume")
+            .body(format!(
+                "Volume of {}. The application's own volume is unchanged. \
+                 Remove the filter in Manage Filters to connect it directly again.",
+                label
+            ))
+            .build();
+
+        let scale = gtk::Scale::with_range(gtk::Orientation::Horizontal, 0.0, MAX_VOLUME, 5.0);
+        scale.set_value(volume);
+        scale.set_draw_value(true);
+        scale.set_format_value_func(|_, value| format!("{:.0}%", value));
+        scale.add_mark(100.0, gtk::PositionType::Bottom, None);
+        scale.update_property(&[gtk::accessible::Property::Label("Volume")]);
+        scale.connect_value_changed(glib::clone!(
+            #[weak(rename_to = window)]
+            self,
+            #[strong]
+            key,
+            move |scale| window.set_filter_volume(&key, scale.value())
+        ));
+        dialog.set_extra_child(Some(&scale));
+
+        dialog.add_response("close", "Close");
+        dialog.set_default_response(Some("close"));
+        dialog.set_close_response("close");
+
+        dialog.present();
+        scale.grab_focus();
+    }
+
+    /// Store a Volume filter's volume and apply it if the filter is running
+    fn set_filter_volume(&self, key: &str, percent: f64) {
+        if let Some(filter) = self
+            .imp()
+            .inserted_filters
+            .borrow_mut()
+            .iter_mut()
+            .find(|f| f.key == key)
+        {
+            filter.volume = percent;
+        }
+        self.apply_filter_volume(key);
+    }
+
+    /// Set the volume of a Volume filter's output node. Called again once
+    /// the filter is wired in, for a volume chosen while it was starting.
+    pub(super) fn apply_filter_volume(&self, key: &str) {
+        let Some(percent) = self
+            .imp()
+            .inserted_filters
+            .borrow()
+            .iter()
+            .find(|f| f.key == key && f.wired)
+            .map(|f| f.volume)
+        else {
+            return;
+        };
+        let output_node = filters::filter_output_node(key);
+        let node_id = self
+            .imp()
+            .pw_state
+            .borrow()
+            .nodes
+            .values()
+            .find(|n| n.name == output_node)
+            .map(|n| n.id);
+        if let Some(node_id) = node_id {
+            self.send_command(UiCommand::SetNodeVolume {
+                node_id,
+                volume: filters::volume_gain(percent),
+            });
+        }
+    }
+}
@@ -0,0 +1,293 @@
+use adw::prelude::*;
+use adw::subclass::prelude::*;
+use gtk::{gio, glib};
+use serde::Deserialize;
+use serde_json::{json, Value};
+
+use super::Window;
+use crate::control_socket::{self, ControlRequest, RpcError};
+use crate::pipewire::{PortDirection, PwEvent};
+
+#[derive(Deserialize)]
+struct ListPortsParams {
+    /// "input" or "output"; both if unset
+    #[serde(default)]
+    direction: Option<String>,
+}
+
+#[derive(Deserialize)]
+struct ConnectParams {
+    output_port_id: u32,
+    input_port_id: u32,
+}
+
+#[derive(Deserialize)]
+struct DisconnectParams {
+    link_id: u32,
+}
+
+#[derive(Deserialize)]
+struct PresetParams {
+    name: String,
+}
+
+/// Parse the params of a request, treating missing params as an empty object
+fn params<T: for<'de> Deserialize<'de>>(value: &Value) -> Result<T, RpcError> {
+    let value = if value.is_null() { json!({}) } else { value.clone() };
+    serde_json::from_value(value).map_err(|e| RpcError::invalid_params(e.to_string()))
+}
+
+impl Window {
+    /// Start or stop the control socket and save the setting
+    pub(super) fn set_control_socket(&self, enabled: bool) {
+        if enabled {
+            if let Err(e) = self.start_control_socket() {
+                let message = format!("Failed to open the control socket: {}", e);
+                self.set_control_socket_state(false);
+                self.show_toast(&message);
+                self.announce_error(&message);
+                return;
+            }
+        } else {
+            self.stop_control_socket();
+        }
+
+        self.imp().settings.borrow_mut().control_socket = enabled;
+        if let Err(e) = self.imp().settings.borrow().save() {
+            self.announce(&format!("Failed to save settings: {}", e));
+            return;
+        }
+
+        match self.imp().control_server.borrow().as_ref() {
+            Some(server) => self.announce(&format!(
+                "Control socket listening on {}",
+                server.path().display()
+            )),
+            None => self.announce("Control socket closed"),
+        }
+    }
+
+    fn set_control_socket_state(&self, enabled: bool) {
+        if let Some(action) = self
+            .lookup_action("control-socket")
+            .and_downcast::<gio::SimpleAction>()
+        {
+            action.set_state(&enabled.to_variant());
+        }
+    }
+
+    /// Listen on the control socket, answering requests on the main loop
+    pub(super) fn start_control_socket(&self) -> Result<(), anyhow::Error> {
+        if self.imp().control_server.borrow().is_some() {
+            return Ok(());
+        }
+
+        let (request_tx, request_rx) = async_channel::unbounded::<ControlRequest>();
+        let server = control_socket::spawn(request_tx)?;
+        self.imp().control_server.replace(Some(server));
+
+        glib::spawn_future_local(glib::clone!(
+            #[weak(rename_to = window)]
+            self,
+            async move {
+                while let Ok(request) = request_rx.recv().await {
+                    window.handle_control_request(request);
+                }
+            }
+        ));
+        Ok(())
+    }
+
+    fn stop_control_socket(&self) {
+        self.imp().control_server.replace(None);
+        self.imp().control_subscribers.borrow_mut().clear();
+    }
+
+    /// Answer one JSON-RPC request. Connecting is answered once PipeWire has
+    /// created or refused the link.
+    fn handle_control_request(&self, request: ControlRequest) {
+        if request.method == "connect" {
+            match params::<ConnectParams>(&request.params)
+                .and_then(|p| self.control_connect(p.output_port_id, p.input_port_id))
+            {
+                Ok(request_id) => {
+                    self.imp()
+                        .control_link_requests
+                        .borrow_mut()
+                        .insert(request_id, request);
+                }
+                Err(e) => request.reply(Err(e)),
+            }
+            return;
+        }
+
+        let result = match request.method.as_str() {
+            "list_ports" => params::<ListPortsParams>(&request.params)
+                .and_then(|p| self.control_list_ports(p.direction.as_deref())),
+            "list_links" => Ok(self.control_list_links()),
+            "list_presets" => {
+                let store = self.imp().preset_store.borrow();
+                Ok(json!({
+                    "presets": store.preset_names(),
+                    "active": store.active_preset,
+                }))
+            }
+            "disconnect" => params::<DisconnectParams>(&request.params).and_then(|p| {
+                if !self.imp().pw_state.borrow().links.contains_key(&p.link_id) {
+                    return Err(RpcError::failed(format!("No link {}", p.link_id)));
+                }
+                self.delete_link_remembered(p.link_id);
+                Ok(json!({}))
+            }),
+            "activate_preset" => params::<PresetParams>(&request.params).and_then(|p| {
+                if self.imp().preset_store.borrow().get_preset(&p.name).is_none() {
+                    return Err(RpcError::failed(format!("No preset named \"{}\"", p.name)));
+                }
+                self.activate_preset(&p.name);
+                Ok(json!({}))
+            }),
+            "deactivate_preset" => {
+                if self.imp().preset_store.borrow().active_preset.is_some() {
+                    self.deactivate_preset();
+                }
+                Ok(json!({}))
+            }
+            "subscribe" => {
+                let mut subscribers = self.imp().control_subscribers.borrow_mut();
+                if !subscribers.iter().any(|c| c.id() == request.client.id()) {
+                    subscribers.push(request.client.clone());
+                }
+                Ok(json!({}))
+            }
+            "unsubscribe" => {
+                self.imp()
+                    .control_subscribers
+                    .borrow_mut()
+                    .retain(|c| c.id() != request.client.id());
+                Ok(json!({}))
+            }
+            method => Err(RpcError::method_not_found(method)),
+        };
+        request.reply(result);
+    }
+
+    fn control_list_ports(&self, direction: Option<&str>) -> Result<Value, RpcError> {
+        let direction = match direction {
+            None => None,
+            Some("input") => Some(PortDirection::Input),
+            Some("output") => Some(PortDirection::Output),
+            Some(other) => {
+                return Err(RpcError::invalid_params(format!(
+                    "direction must be \"input\" or \"output\", not \"{}\"",
+                    other
+                )))
+            }
+        };
+
+        let pw_state = self.imp().pw_state.borrow();
+        let mut ports: Vec<_> = pw_state
+            .ports
+            .values()
+            .filter(|p| direction.is_none() || direction == Some(p.direction))
+            .collect();
+        ports.sort_by_key(|p| p.id);
+
+        Ok(ports
+            .into_iter()
+            .map(|port| {
+                json!({
+                    "id": port.id,
+                    "node_id": port.node_id,
+                    "node": pw_state.nodes.get(&port.node_id).map(|n| n.name.clone()),
+                    "name": port.name,
+                    "label": pw_state.port_label(port.id),
+                    "direction": port.direction.as_str(),
+                    "media_type": port.media_type.as_str(),
+                    "channel": port.channel,
+                })
+            })
+            .collect())
+    }
+
+    fn control_list_links(&self) -> Value {
+        let pw_state = self.imp().pw_state.borrow();
+        let mut links: Vec<_> = pw_state.links.values().collect();
+        links.sort_by_key(|l| l.id);
+
+        links
+            .into_iter()
+            .map(|link| {
+                json!({
+                    "id": link.id,
+                    "output_port_id": link.output_port_id,
+                    "input_port_id": link.input_port_id,
+                    "output": pw_state.port_label(link.output_port_id),
+                    "input": pw_state.port_label(link.input_port_id),
+                    "state": link.state.as_str(),
+                })
+            })
+            .collect()
+    }
+
+    /// Request a link for a client. Returns the id of the link request.
+    pub(super) fn control_connect(
+        &self,
+        output_port_id: u32,
+        input_port_id: u32,
+    ) -> Result<u64, RpcError> {
+        {
+            let pw_state = self.imp().pw_state.borrow();
+            let direction = |id| pw_state.ports.get(&id).map(|p| p.direction);
+            if direction(output_port_id) != Some(PortDirection::Output) {
+                return Err(RpcError::failed(format!("No output port {}", output_port_id)));
+            }
+            if direction(input_port_id) != Some(PortDirection::Input) {
+                return Err(RpcError::failed(format!("No input port {}", input_port_id)));
+            }
+            if pw_state.link_exists(output_port_id, input_port_id) {
+                return Err(RpcError::failed("The ports are already connected"));
+            }
+        }
+        if self
+            .imp()
+            .pending_links
+            .borrow()
+            .contains_key(&(output_port_id, input_port_id))
+        {
+            return Err(RpcError::failed("The ports are already being connected"));
+        }
+
+        // Not added to the tray's Recent Connections, which are the ones
+        // made by hand; a script could otherwise fill them in a moment
+        Ok(self.create_link(output_port_id, input_port_id))
+    }
+
+    /// Answer a client waiting for a link request, if one is
+    pub(super) fn control_link_finished(&self, request_id: u64, result: Result<u32, String>) {
+        let Some(request) = self
+            .imp()
+            .control_link_requests
+            .borrow_mut()
+            .remove(&request_id)
+        else {
+            return;
+        };
+        request.reply(
+            result
+                .map(|link_id| json!({ "link_id": link_id }))
+                .map_err(RpcError::failed),
+        );
+    }
+
+    /// Pass a PipeWire event to subscribed clients, forgetting those that left
+    pub(super) fn notify_control_clients(&self, event: &PwEvent) {
+        let mut subscribers = self.imp().control_subscribers.borrow_mut();
+        if subscribers.is_empty() {
+            return;
+        }
+        let Ok(params) = serde_json::to_value(event) else {
+            return;
+        };
+        subscribers.retain(|client| client.notify("event", params.clone()));
+    }
+}
@@ -0,0 +1,194 @@
+use adw::prelude::*;
+use adw::subclass::prelude::*;
+use gtk::glib;
+
+use super::Window;
+use crate::pipewire::messages::DeviceProfile;
+use crate::pipewire::state::PwDevice;
+use crate::pipewire::UiCommand;
+
+/// Dropdown label for a profile
+fn profile_label(profile: &DeviceProfile) -> String {
+    if profile.available {
+        profile.description.clone()
+    } else {
+        format!("{} (unavailable)", profile.description)
+    }
+}
+
+impl Window {
+    pub(super) fn device_added(
+        &self,
+        id: u32,
+        name: String,
+        description: Option<String>,
+        api: Option<String>,
+    ) {
+        self.imp().pw_state.borrow_mut().devices.insert(
+            id,
+            PwDevice {
+                id,
+                name,
+                description,
+                api,
+                profiles: Vec::new(),
+                active_profile: None,
+                routes: Vec::new(),
+            },
+        );
+    }
+
+    /// Track a device's profiles, announcing when a Bluetooth device
+    /// switches profile or codec
+    pub(super) fn device_profiles_changed(
+        &self,
+        id: u32,
+        profiles: Vec<DeviceProfile>,
+        active_profile: Option<u32>,
+    ) {
+        let switched = {
+            let mut pw_state = self.imp().pw_state.borrow_mut();
+            let Some(device) = pw_state.devices.get_mut(&id) else {
+                return;
+            };
+            let previous = device.active_profile;
+            device.profiles = profiles;
+            device.active_profile = active_profile;
+
+            // The first report is the profile the device already had
+            let changed = previous.is_some() && previous != active_profile;
+            if changed && device.is_bluetooth() {
+                device
+                    .active_profile()
+                    .map(|profile| format!("{} now uses {}", device.display_name(), profile.description))
+            } else {
+                None
+            }
+        };
+
+        if let Some(message) = switched {
+            self.record_graph_activity(&message);
+            self.announce_verbose(&message);
+        }
+    }
+
+    /// The device of the first selected port, output list first
+    fn selected_port_device(&self) -> Result<(u32, PwDevice), String> {
+        let port = self
+            .selected_ports(true)
+            .into_iter()
+            .chain(self.selected_ports(false))
+            .next()
+            .ok_or("Select a port of the device first")?;
+
+        let pw_state = self.imp().pw_state.borrow();
+        let node = pw_state
+            .get_port_node(port.id())
+            .ok_or("The selected port's node is gone")?;
+        let device = pw_state.get_node_device(node.id).ok_or_else(|| {
+            format!(
+                "{} is not part of a device; profiles belong to sound cards and headsets",
+                node.display_name()
+            )
+        })?;
+        Ok((node.id, device.clone()))
+    }
+
+    /// Show a dialog to switch the profile of the selected port's device,
+    /// which for a Bluetooth headset picks between A2DP with each codec and
+    /// HFP with its microphone
+    pub(super) fn show_device_profile_dialog(&self) {
+        let (node_id, device) = match self.selected_port_device() {
+            Ok(found) => found,
+            Err(message) => {
+                self.announce(&message);
+                return;
+            }
+        };
+        if device.profiles.is_empty() {
+            self.announce(&format!("{} has no profiles to choose from", device.display_name()));
+            return;
+        }
+
+        let mut body = match device.active_profile() {
+            Some(profile) => format!("{} uses {}.", device.display_name(), profile.description),
+            None => format!("{} has no active profile.", device.display_name()),
+        };
+        if device.is_bluetooth() {
+            let codec = self
+                .imp()
+                .pw_state
+                .borrow()
+                .properties
+                .get(&node_id)
+                .and_then(|props| props.get("api.bluez5.codec").cloned());
+            if let Some(codec) = codec {
+                body.push_str(&format!(" Codec: {}.", codec.to_uppercase()));
+            }
+            body.push_str(
+                " A2DP profiles play in high quality without the microphone; \
+                 headset (HFP) profiles enable the microphone at lower quality.",
+            );
+        }
+
+        let dialog = adw::MessageDialog::builder()
+            .transient_for(&self.dialog_parent())
+            .modal(true)
+            .heading("Device Profile")
+            .body(body)
+            .build();
+
+        let labels: Vec<String> = device.profiles.iter().map(profile_label).collect();
+        let labels: Vec<&str> = labels.iter().map(String::as_str).collect();
+        let dropdown = gtk::DropDown::from_strings(&labels);
+        if let Some(position) = device
+            .profiles
+            .iter()
+            .position(|p| Some(p.index) == device.active_profile)
+        {
+            dropdown.set_selected(position as u32);
+        }
+        dropdown.update_property(&[gtk::accessible::Property::Label("Profile")]);
+        dialog.set_extra_child(Some(&dropdown));
+
+        dialog.add_response("cancel", "Cancel");
+        dialog.add_response("switch", "Switch");
+        dialog.set_response_appearance("switch", adw::ResponseAppearance::Suggested);
+        dialog.set_default_response(Some("switch"));
+        dialog.set_close_response("cancel");
+
+        dialog.connect_response(
+            None,
+            glib::clone!(
+                #[weak(rename_to = window)]
+                self,
+                #[weak]
+                dropdown,
+                move |dialog, response| {
+                    dialog.close();
+                    if response != "switch" {
+                        return;
+                    }
+                    let Some(profile) = device.profiles.get(dropdown.selected() as usize) else {
+                        return;
+                    };
+                    if Some(profile.index) == device.active_profile {
+                        return;
+                    }
+                    window.send_command(UiCommand::SetDeviceProfile {
+                        device_id: device.id,
+                        index: profile.index,
+                    });
+                    window.announce(&format!(
+                        "Switching {} to {}",
+                        device.display_name(),
+                        profile.description
+                    ));
+                }
+            ),
+        );
+
+        dialog.present();
+        dropdown.grab_focus();
+    }
+}
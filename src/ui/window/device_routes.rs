@@ -0,0 +1,233 @@
+use adw::prelude::*;
+use adw::subclass::prelude::*;
+use gtk::glib;
+
+use super::Window;
+use crate::pipewire::messages::DeviceRoute;
+use crate::pipewire::state::PwDevice;
+use crate::pipewire::UiCommand;
+use crate::presets::PresetRoute;
+
+/// Dropdown label for a route
+fn route_label(route: &DeviceRoute) -> String {
+    if route.available {
+        route.description.clone()
+    } else {
+        format!("{} (unplugged)", route.description)
+    }
+}
+
+impl Window {
+    /// Track a device's routes, announcing when the route a device uses
+    /// changes and when jack detection sees a route plugged in or
+    /// unplugged, which may activate a preset bound to it
+    pub(super) fn device_routes_changed(&self, id: u32, routes: Vec<DeviceRoute>) {
+        let (switched, plug_events) = {
+            let mut pw_state = self.imp().pw_state.borrow_mut();
+            let Some(device) = pw_state.devices.get_mut(&id) else {
+                return;
+            };
+            let previous = std::mem::replace(&mut device.routes, routes);
+
+            let mut switched = Vec::new();
+            let mut plug_events = Vec::new();
+            for route in &device.routes {
+                // A route not reported before is how the device started
+                let Some(before) = previous.iter().find(|r| r.index == route.index) else {
+                    continue;
+                };
+                if !route.active_on.is_empty() && before.active_on.is_empty() {
+                    switched.push(format!(
+                        "{} now uses {}",
+                        device.display_name(),
+                        route.description
+                    ));
+                }
+                if route.available != before.available {
+                    plug_events.push((
+                        device.name.clone(),
+                        route.name.clone(),
+                        route.available,
+                        format!(
+                            "{} {} on {}",
+                            route.description,
+                            if route.available { "plugged in" } else { "unplugged" },
+                            device.display_name()
+                        ),
+                    ));
+                }
+            }
+            (switched, plug_events)
+        };
+
+        for message in switched {
+            self.record_graph_activity(&message);
+            self.announce_verbose(&message);
+        }
+        for (device, route, plugged, message) in plug_events {
+            self.record_graph_activity(&message);
+            self.announce_verbose(&message);
+            self.route_plugged(&device, &route, plugged);
+        }
+    }
+
+    /// The device and card device of the first selected port's node
+    pub(super) fn selected_port_card_device(&self) -> Result<(PwDevice, u32), String> {
+        let (node_id, device) = self.selected_port_device()?;
+        let card_device = self
+            .imp()
+            .pw_state
+            .borrow()
+            .get_node_card_device(node_id)
+            .ok_or_else(|| format!("{} has no routes to choose from", device.display_name()))?;
+        Ok((device, card_device))
+    }
+
+    /// Show a dialog to pick the route of the selected port's node, such as
+    /// speakers or headphones on the same sound card
+    pub(super) fn show_device_route_dialog(&self) {
+        let (device, card_device) = match self.selected_port_card_device() {
+            Ok(found) => found,
+            Err(message) => {
+                self.announce(&message);
+                return;
+            }
+        };
+        let routes: Vec<DeviceRoute> = device.routes_for(card_device).into_iter().cloned().collect();
+        if routes.is_empty() {
+            self.announce(&format!(
+                "{} has no routes to choose from with its current profile",
+                device.display_name()
+            ));
+            return;
+        }
+
+        let active = device.active_route(card_device).map(|r| r.index);
+        let body = match device.active_route(card_device) {
+            Some(route) => format!("{} uses {}.", device.display_name(), route.description),
+            None => format!("{} has no route selected.", device.display_name()),
+        };
+
+        let dialog = adw::MessageDialog::builder()
+            .transient_for(&self.dialog_parent())
+            .modal(true)
+            .heading("Device Route")
+            .body(body)
+            .build();
+
+        let labels: Vec<String> = routes.iter().map(route_label).collect();
+        let labels: Vec<&str> = labels.iter().map(String::as_str).collect();
+        let dropdown = gtk::DropDown::from_strings(&labels);
+        if let Some(position) = routes.iter().position(|r| Some(r.index) == active) {
+            dropdown.set_selected(position as u32);
+        }
+        dropdown.update_property(&[gtk::accessible::Property::Label("Route")]);
+        dialog.set_extra_child(Some(&dropdown));
+
+        dialog.add_response("cancel", "Cancel");
+        dialog.add_response("switch", "Switch");
+        dialog.set_response_appearance("switch", adw::ResponseAppearance::Suggested);
+        dialog.set_default_response(Some("switch"));
+        dialog.set_close_response("cancel");
+
+        dialog.connect_response(
+            None,
+            glib::clone!(
+                #[weak(rename_to = window)]
+                self,
+                #[weak]
+                dropdown,
+                move |dialog, response| {
+                    dialog.close();
+                    if response != "switch" {
+                        return;
+                    }
+                    let Some(route) = routes.get(dropdown.selected() as usize) else {
+                        return;
+                    };
+                    if Some(route.index) == active {
+                        return;
+                    }
+                    window.send_command(UiCommand::SetDeviceRoute {
+                        device_id: device.id,
+                        index: route.index,
+                        card_device,
+                    });
+                    window.announce(&format!(
+                        "Switching {} to {}",
+                        device.display_name(),
+                        route.description
+                    ));
+                }
+            ),
+        );
+
+        dialog.present();
+        dropdown.grab_focus();
+    }
+
+    /// The selected routes of the devices behind the current connections,
+    /// to save with a preset
+    pub(super) fn current_preset_routes(&self) -> Vec<PresetRoute> {
+        let pw_state = self.imp().pw_state.borrow();
+        let mut routes: Vec<PresetRoute> = Vec::new();
+        for link in pw_state.links.values() {
+            for port_id in [link.output_port_id, link.input_port_id] {
+                let Some(node) = pw_state.get_port_node(port_id) else {
+                    continue;
+                };
+                let (Some(device), Some(card_device)) = (
+                    pw_state.get_node_device(node.id),
+                    pw_state.get_node_card_device(node.id),
+                ) else {
+                    continue;
+                };
+                let Some(route) = device.active_route(card_device) else {
+                    continue;
+                };
+                let saved = PresetRoute {
+                    device: device.name.clone(),
+                    card_device,
+                    route: route.name.clone(),
+                };
+                if !routes.contains(&saved) {
+                    routes.push(saved);
+                }
+            }
+        }
+        routes
+    }
+
+    /// Select the routes saved with a preset that aren't selected already
+    pub(super) fn apply_preset_routes(&self, routes: &[PresetRoute]) {
+        let commands: Vec<(UiCommand, String)> = {
+            let pw_state = self.imp().pw_state.borrow();
+            routes
+                .iter()
+                .filter_map(|saved| {
+                    let device = pw_state.devices.values().find(|d| d.name == saved.device)?;
+                    let route = device
+                        .routes_for(saved.card_device)
+                        .into_iter()
+                        .find(|r| r.name == saved.route)?;
+                    if route.active_on.contains(&saved.card_device) {
+                        return None;
+                    }
+                    Some((
+                        UiCommand::SetDeviceRoute {
+                            device_id: device.id,
+                            index: route.index,
+                            card_device: saved.card_device,
+                        },
+                        format!("Switched {} to {}", device.display_name(), route.description),
+                    ))
+                })
+                .collect()
+        };
+
+        for (command, message) in commands {
+            self.send_command(command);
+            self.record_activity(&message);
+        }
+    }
+}
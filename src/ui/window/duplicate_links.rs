@@ -0,0 +1,93 @@
+use adw::subclass::prelude::*;
+
+use super::Window;
+
+impl Window {
+    /// Show a new link on the row of an earlier link between the same
+    /// ports, if there is one. Returns whether it was merged.
+    pub(super) fn merge_duplicate_link(
+        &self,
+        id: u32,
+        output_port_id: u32,
+        input_port_id: u32,
+    ) -> bool {
+        let ids = self
+            .imp()
+            .pw_state
+            .borrow()
+            .links_between(output_port_id, input_port_id);
+        let row = {
+            let link_objects = self.imp().link_objects.borrow();
+            ids.iter()
+                .filter(|&&other| other != id)
+                .find_map(|other| link_objects.get(other).cloned())
+        };
+        let Some(row) = row else {
+            return false;
+        };
+
+        self.imp().link_objects.borrow_mut().insert(id, row.clone());
+        row.update_link_count(ids.len() as u32);
+        log::info!(
+            "Link {} duplicates link {} from port {} to port {}",
+            id,
+            row.id(),
+            output_port_id,
+            input_port_id
+        );
+        true
+    }
+
+    /// Take a removed link off its row while other links between the same
+    /// ports remain. Returns whether the row stays.
+    pub(super) fn unmerge_duplicate_link(&self, id: u32) -> bool {
+        let Some(row) = self.imp().link_objects.borrow().get(&id).cloned() else {
+            return false;
+        };
+        let remaining = self
+            .imp()
+            .pw_state
+            .borrow()
+            .links_between(row.output_port_id(), row.input_port_id());
+        let Some(&kept) = remaining.first() else {
+            return false;
+        };
+
+        self.imp().link_objects.borrow_mut().remove(&id);
+        if row.id() == id {
+            row.set_id(kept);
+            let state = self
+                .imp()
+                .pw_state
+                .borrow()
+                .links
+                .get(&kept)
+                .map(|l| l.state);
+            if let Some(state) = state {
+                row.set_state(state.as_str());
+            }
+            self.refresh_link_origin(kept);
+        }
+        row.update_link_count(remaining.len() as u32);
+        true
+    }
+
+    /// Remove links repeating another between the same ports, keeping one
+    pub(super) fn remove_duplicate_links(&self) {
+        let duplicates = self.imp().pw_state.borrow().duplicate_links();
+        if duplicates.is_empty() {
+            self.announce("No duplicate connections");
+            return;
+        }
+
+        for &id in &duplicates {
+            self.delete_link(id);
+        }
+        let message = match duplicates.len() {
+            1 => "Removing 1 duplicate connection".to_string(),
+            n => format!("Removing {} duplicate connections", n),
+        };
+        self.record_activity(&message);
+        self.announce(&message);
+    }
+}
@@ -0,0 +1,217 @@
+use adw::prelude::*;
+use adw::subclass::prelude::*;
+use gtk::glib;
+
+use super::Window;
+use crate::pipewire::state::PwState;
+
+/// Nodes the outputs of `node_id` are linked to
+fn destinations(pw_state: &PwState, node_id: u32) -> Vec<u32> {
+    let mut destinations = Vec::new();
+    for link in pw_state.links.values() {
+        if link.output_node_id == node_id && !destinations.contains(&link.input_node_id) {
+            destinations.push(link.input_node_id);
+        }
+    }
+    destinations
+}
+
+/// (node id, display name) sorted by name
+fn sorted_names(pw_state: &PwState, ids: impl Iterator<Item = u32>) -> Vec<(u32, String)> {
+    let mut names: Vec<(u32, String)> = ids
+        .filter_map(|id| {
+            let node = pw_state.nodes.get(&id)?;
+            Some((id, node.display_name().to_string()))
+        })
+        .collect();
+    names.sort_by(|a, b| a.1.cmp(&b.1));
+    names
+}
+
+impl Window {
+    /// The node of the selected output port, whose audio is duplicated
+    fn selected_output_node(&self) -> Result<(u32, String), String> {
+        let port = self
+            .selected_ports(true)
+            .into_iter()
+            .next()
+            .ok_or("Select a port of the application in the output list first")?;
+        let pw_state = self.imp().pw_state.borrow();
+        let node = pw_state
+            .get_port_node(port.id())
+            .ok_or("The selected port's node is gone")?;
+        Ok((node.id, node.display_name().to_string()))
+    }
+
+    /// Show a dialog to send the selected application to one more output,
+    /// keeping where it plays now, e.g. to hear a stream while it also goes
+    /// to a recording or streaming sink
+    pub(super) fn show_also_send_dialog(&self) {
+        let (node_id, node_name) = match self.selected_output_node() {
+            Ok(found) => found,
+            Err(message) => {
+                self.announce(&message);
+                return;
+            }
+        };
+
+        let sinks = {
+            let pw_state = self.imp().pw_state.borrow();
+            let current = destinations(&pw_state, node_id);
+            let candidates = pw_state
+                .nodes
+                .values()
+                .filter(|n| n.id != node_id && !current.contains(&n.id))
+                .filter(|n| {
+                    n.media_class
+                        .as_deref()
+                        .is_some_and(|class| class.ends_with("/Sink") || class == "Audio/Duplex")
+                })
+                .map(|n| n.id)
+                .collect::<Vec<_>>();
+            sorted_names(&pw_state, candidates.into_iter())
+        };
+        if sinks.is_empty() {
+            self.announce(&format!("{} already plays to every output", node_name));
+            return;
+        }
+
+        let dialog = adw::MessageDialog::builder()
+            .transient_for(&self.dialog_parent())
+            .modal(true)
+            .heading("Also Send To")
+            .body(format!(
+                "Send {} to another output as well. Its current connections are kept.",
+                node_name
+            ))
+            .build();
+
+        let labels: Vec<&str> = sinks.iter().map(|(_, name)| name.as_str()).collect();
+        let dropdown = gtk::DropDown::from_strings(&labels);
+        dropdown.update_property(&[gtk::accessible::Property::Label("Output")]);
+        dialog.set_extra_child(Some(&dropdown));
+
+        dialog.add_response("cancel", "Cancel");
+        dialog.add_response("send", "Send");
+        dialog.set_response_appearance("send", adw::ResponseAppearance::Suggested);
+        dialog.set_default_response(Some("send"));
+        dialog.set_close_response("cancel");
+
+        dialog.connect_response(
+            None,
+            glib::clone!(
+                #[weak(rename_to = window)]
+                self,
+                #[weak]
+                dropdown,
+                move |dialog, response| {
+                    dialog.close();
+                    if response != "send" {
+                        return;
+                    }
+                    let Some((sink_id, sink_name)) = sinks.get(dropdown.selected() as usize) else {
+                        return;
+                    };
+                    let count = window.connect_nodes(node_id, *sink_id);
+                    if count == 0 {
+                        window.announce(&format!("Could not connect {} to {}", node_name, sink_name));
+                        return;
+                    }
+                    let message = format!("Also sending {} to {}", node_name, sink_name);
+                    window.record_activity(&message);
+                    window.announce(&message);
+                }
+            ),
+        );
+
+        dialog.present();
+        dropdown.grab_focus();
+    }
+
+    /// Show a dialog to stop sending the selected application to one of
+    /// the places it plays to, leaving the others connected
+    pub(super) fn show_stop_sending_dialog(&self) {
+        let (node_id, node_name) = match self.selected_output_node() {
+            Ok(found) => found,
+            Err(message) => {
+                self.announce(&message);
+                return;
+            }
+        };
+
+        let targets = {
+            let pw_state = self.imp().pw_state.borrow();
+            sorted_names(&pw_state, destinations(&pw_state, node_id).into_iter())
+        };
+        if targets.is_empty() {
+            self.announce(&format!("{} is not connected anywhere", node_name));
+            return;
+        }
+
+        let body = if targets.len() == 1 {
+            format!(
+                "{} only plays to {}; stopping leaves it disconnected.",
+                node_name, targets[0].1
+            )
+        } else {
+            format!("Stop sending {} to one of its {} destinations.", node_name, targets.len())
+        };
+        let dialog = adw::MessageDialog::builder()
+            .transient_for(&self.dialog_parent())
+            .modal(true)
+            .heading("Stop Sending To")
+            .body(body)
+            .build();
+
+        let labels: Vec<&str> = targets.iter().map(|(_, name)| name.as_str()).collect();
+        let dropdown = gtk::DropDown::from_strings(&labels);
+        dropdown.update_property(&[gtk::accessible::Property::Label("Destination")]);
+        dialog.set_extra_child(Some(&dropdown));
+
+        dialog.add_response("cancel", "Cancel");
+        dialog.add_response("stop", "Stop Sending");
+        dialog.set_response_appearance("stop", adw::ResponseAppearance::Destructive);
+        dialog.set_default_response(Some("stop"));
+        dialog.set_close_response("cancel");
+
+        dialog.connect_response(
+            None,
+            glib::clone!(
+                #[weak(rename_to = window)]
+                self,
+                #[weak]
+                dropdown,
+                move |dialog, response| {
+                    dialog.close();
+                    if response != "stop" {
+                        return;
+                    }
+                    let Some((target_id, target_name)) = targets.get(dropdown.selected() as usize)
+                    else {
+                        return;
+                    };
+                    let links: Vec<u32> = {
+                        let pw_state = window.imp().pw_state.borrow();
+                        pw_state
+                            .links
+                            .values()
+                            .filter(|link| {
+                                link.output_node_id == node_id && link.input_node_id == *target_id
+                            })
+                            .map(|link| link.id)
+                            .collect()
+                    };
+                    for link_id in &links {
+                        window.delete_link(*link_id);
+                    }
+                    let message = format!("Stopped sending {} to {}", node_name, target_name);
+                    window.record_activity(&message);
+                    window.announce(&message);
+                }
+            ),
+        );
+
+        dialog.present();
+        dropdown.grab_focus();
+    }
+}
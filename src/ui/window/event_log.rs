@@ -0,0 +1,219 @@
+use std::path::Path;
+
+use adw::prelude::*;
+use adw::subclass::prelude::*;
+use gtk::{gio, glib};
+
+use super::Window;
+use crate::pipewire::recording::{self, RecordedEvent};
+use crate::pipewire::PwEvent;
+
+/// Number of events kept by the event debugger
+const EVENT_LOG_CAPACITY: usize = 5000;
+
+impl Window {
+    /// Start or stop recording events for this session only
+    pub fn set_event_recording(&self, enabled: bool) {
+        let imp = self.imp();
+        if enabled && !imp.recording_events.get() {
+            imp.event_log_started.set(glib::monotonic_time());
+        }
+        imp.recording_events.set(enabled);
+
+        if let Some(action) = self
+            .lookup_action("record-events")
+            .and_downcast::<gio::SimpleAction>()
+        {
+            action.set_state(&enabled.to_variant());
+        }
+    }
+
+    /// Turn event recording on or off and remember it for future runs
+    pub(super) fn set_record_events(&self, enabled: bool) {
+        self.set_event_recording(enabled);
+
+        self.imp().settings.borrow_mut().record_events = enabled;
+        if let Err(e) = self.imp().settings.borrow().save() {
+            self.announce(&format!("Failed to save settings: {}", e));
+            return;
+        }
+
+        if enabled {
+            self.announce("Recording PipeWire events");
+        } else {
+            self.announce("Stopped recording PipeWire events");
+        }
+    }
+
+    /// Add an event to the ring buffer
+    pub(super) fn record_event(&self, event: &PwEvent) {
+        let imp = self.imp();
+        let recorded = RecordedEvent {
+            time: glib::DateTime::now_local()
+                .and_then(|now| now.format("%T.%f"))
+                .map(|s| s.to_string())
+                .unwrap_or_default(),
+            elapsed_us: glib::monotonic_time() - imp.event_log_started.get(),
+            event: event.clone(),
+        };
+
+        let mut log = imp.event_log.borrow_mut();
+        if log.len() >= EVENT_LOG_CAPACITY {
+            log.pop_front();
+        }
+        log.push_back(recorded);
+    }
+
+    /// Write the recorded events to a JSON file
+    fn export_events(&self, path: &Path) -> Result<usize, String> {
+        let events: Vec<RecordedEvent> = self.imp().event_log.borrow().iter().cloned().collect();
+        recording::save_recording(path, &events)
+            .map_err(|e| format!("Failed to export events: {}", e))?;
+        Ok(events.len())
+    }
+
+    /// Show the recorded events with export and clear controls
+    pub(super) fn show_event_debugger(&self) {
+        let viewer = adw::Window::builder()
+            .transient_for(&self.dialog_parent())
+            .title("PipeWire Events")
+            .default_width(900)
+            .default_height(500)
+            .build();
+
+        let content = gtk::Box::builder()
+            .orientation(gtk::Orientation::Vertical)
+            .build();
+        content.append(&adw::HeaderBar::new());
+
+        let controls = gtk::Box::builder()
+            .orientation(gtk::Orientation::Horizontal)
+            .spacing(6)
+            .margin_start(12)
+            .margin_end(12)
+            .margin_top(6)
+            .margin_bottom(6)
+            .build();
+
+        let refresh_btn = gtk::Button::builder()
+            .label("Refresh")
+            .tooltip_text("Show the latest events")
+            .build();
+        controls.append(&refresh_btn);
+
+        let clear_btn = gtk::Button::builder()
+            .label("Clear")
+            .tooltip_text("Forget all recorded events")
+            .build();
+        controls.append(&clear_btn);
+
+        let export_btn = gtk::Button::builder()
+            .label("Export JSON...")
+            .tooltip_text("Save the recorded events to a JSON file")
+            .build();
+        controls.append(&export_btn);
+
+        content.append(&controls);
+
+        let text_view = gtk::TextView::builder()
+            .editable(false)
+            .monospace(true)
+            .wrap_mode(gtk::WrapMode::WordChar)
+            .left_margin(12)
+            .right_margin(12)
+            .build();
+        text_view.update_property(&[gtk::accessible::Property::Label("Recorded events")]);
+
+        let scrolled = gtk::ScrolledWindow::builder()
+            .hscrollbar_policy(gtk::PolicyType::Never)
+            .vscrollbar_policy(gtk::PolicyType::Automatic)
+            .vexpand(true)
+            .child(&text_view)
+            .build();
+        content.append(&scrolled);
+
+        viewer.set_content(Some(&content));
+
+        let fill = glib::clone!(
+            #[weak(rename_to = window)]
+            self,
+            #[weak]
+            text_view,
+            move || {
+                let imp = window.imp();
+                let text = if imp.event_log.borrow().is_empty() {
+                    if imp.recording_events.get() {
+                        "No events recorded yet.".to_string()
+                    } else {
+                        "Recording is off. Turn on \"Record PipeWire Events\" in the \
+                         Tools menu, or start with --debug-events."
+                            .to_string()
+                    }
+                } else {
+                    imp.event_log
+                        .borrow()
+                        .iter()
+                        .map(|e| e.line())
+                        .collect::<Vec<_>>()
+                        .join("\n")
+                };
+                text_view.buffer().set_text(&text);
+            }
+        );
+        fill();
+
+        refresh_btn.connect_clicked(glib::clone!(
+            #[strong]
+            fill,
+            move |_| fill()
+        ));
+        clear_btn.connect_clicked(glib::clone!(
+            #[weak(rename_to = window)]
+            self,
+            #[strong]
+            fill,
+            move |_| {
+                window.imp().event_log.borrow_mut().clear();
+                window.imp().event_log_started.set(glib::monotonic_time());
+                fill();
+                window.announce("Recorded events cleared");
+            }
+        ));
+        export_btn.connect_clicked(glib::clone!(
+            #[weak(rename_to = window)]
+            self,
+            #[weak]
+            viewer,
+            move |_| {
+                let dialog = gtk::FileDialog::builder()
+                    .title("Export Events")
+                    .initial_name("pw-audioshare-events.json")
+                    .build();
+                dialog.save(
+                    Some(&viewer),
+                    None::<&gio::Cancellable>,
+                    glib::clone!(
+                        #[weak]
+                        window,
+                        move |result| {
+                            let Some(path) = result.ok().and_then(|file| file.path()) else {
+                                return;
+                            };
+                            match window.export_events(&path) {
+                                Ok(count) => window.announce(&format!(
+                                    "Exported {} events to {}",
+                                    count,
+                                    path.display()
+                                )),
+                                Err(e) => window.announce(&e),
+                            }
+                        }
+                    ),
+                );
+            }
+        ));
+
+        viewer.present();
+        refresh_btn.grab_focus();
+    }
+}
@@ -0,0 +1,52 @@
+use adw::prelude::*;
+use adw::subclass::prelude::*;
+
+use super::Window;
+use crate::pipewire::messages::MediaType;
+
+impl Window {
+    /// Show on each filter toggle how many ports of its kind exist, whether
+    /// or not they are filtered out, so an empty list can be told apart from
+    /// a graph with nothing of that kind
+    pub(super) fn update_filter_counts(&self) {
+        let (audio, midi, video, monitors) = {
+            let state = self.imp().pw_state.borrow();
+            let mut counts = (0, 0, 0, 0);
+            for port in state.ports.values() {
+                match port.media_type {
+                    MediaType::Audio => counts.0 += 1,
+                    MediaType::Midi => counts.1 += 1,
+                    MediaType::Video => counts.2 += 1,
+                    MediaType::Unknown => {}
+                }
+                if port.name.starts_with("monitor_") {
+                    counts.3 += 1;
+                }
+            }
+            counts
+        };
+
+        let widgets = self.imp().filter_widgets.borrow();
+        let Some(widgets) = widgets.as_ref() else {
+            return;
+        };
+        for (button, name, kind, count) in [
+            (&widgets.audio, "Audio", "audio ports", audio),
+            (&widgets.midi, "MIDI", "MIDI ports", midi),
+            (&widgets.video, "Video", "video ports", video),
+            (
+                &widgets.monitors,
+                "Monitors",
+                "monitor ports of outputs",
+                monitors,
+            ),
+        ] {
+            let label = format!("{} {}", name, count);
+            if button.label().as_deref() == Some(label.as_str()) {
+                continue;
+            }
+            button.set_label(&label);
+            button.set_tooltip_text(Some(&format!("Show {}, {} in all", kind, count)));
+        }
+    }
+}
@@ -0,0 +1,392 @@
+use adw::prelude::*;
+use adw::subclass::prelude::*;
+use gtk::glib;
+
+use super::Window;
+use crate::model::LinkObject;
+use crate::pipewire::filters::{self, FilterPreset};
+use crate::pipewire::{PortDirection, UiCommand};
+
+/// An effect inserted into a connection
+#[derive(Debug, Clone)]
+pub struct InsertedFilter {
+    /// Helper key, also the prefix of the filter's node names
+    pub key: String,
+    pub preset: FilterPreset,
+    /// Original source port of the connection
+    pub output_port_id: u32,
+    /// Original destination port of the connection
+    pub input_port_id: u32,
+    /// Label of the original connection for lists and announcements
+    pub connection_label: String,
+    /// Whether the connection currently bypasses the filter
+    pub bypassed: bool,
+    /// Whether the connection has been routed through the filter yet
+    pub wired: bool,
+    /// Volume in percent, used by the Volume preset
+    pub volume: f64,
+}
+
+impl Window {
+    /// Show a dialog to pick an effect for the selected connection
+    pub(super) fn show_insert_filter_dialog(&self) {
+        let link = self
+            .imp()
+            .connections_selection
+            .borrow()
+            .as_ref()
+            .and_then(|s| s.selected_item())
+            .and_downcast::<LinkObject>();
+
+        let link = match link {
+            Some(link) => link,
+            None => {
+                self.announce("Select a connection to insert a filter into");
+                return;
+            }
+        };
+
+        if link.media_type() != "audio" {
+            self.announce("Filters can only be inserted into audio connections");
+            return;
+        }
+
+        let dialog = adw::MessageDialog::builder()
+            .transient_for(&self.dialog_parent())
+            .modal(true)
+            .heading("Insert Filter")
+            .body(format!(
+                "Route {} through an effect:",
+                link.display_label()
+            ))
+            .build();
+
+        let labels: Vec<&str> = FilterPreset::ALL.iter().map(|p| p.label()).collect();
+        let dropdown = gtk::DropDown::from_strings(&labels);
+        dropdown.update_property(&[gtk::accessible::Property::Label("Effect")]);
+        dialog.set_extra_child(Some(&dropdown));
+
+        dialog.add_response("cancel", "Cancel");
+        dialog.add_response("insert", "Insert");
+        dialog.set_response_appearance("insert", adw::ResponseAppearance::Suggested);
+        dialog.set_default_response(Some("insert"));
+        dialog.set_close_response("cancel");
+
+        dialog.connect_response(
+            None,
+            glib::clone!(
+                #[weak(rename_to = window)]
+                self,
+                #[weak]
+                dropdown,
+                move |dialog, response| {
+                    dialog.close();
+                    if response != "insert" {
+                        return;
+                    }
+                    if let Some(preset) = FilterPreset::ALL.get(dropdown.selected() as usize) {
+                        window.insert_filter(&link, *preset);
+                    }
+                }
+            ),
+        );
+
+        dialog.present();
+        dropdown.grab_focus();
+    }
+
+    /// Start a filter for a connection; it is wired in once its ports appear.
+    /// Returns the filter's key.
+    pub(super) fn insert_filter(&self, link: &LinkObject, preset: FilterPreset) -> String {
+        let imp = self.imp();
+        let key = filters::filter_key(self.next_helper_instance());
+        let args = filters::filter_chain_args(&key, preset.label(), preset);
+
+        imp.inserted_filters.borrow_mut().push(InsertedFilter {
+            key: key.clone(),
+            preset,
+            output_port_id: link.output_port_id(),
+            input_port_id: link.input_port_id(),
+            connection_label: link.display_label(),
+            bypassed: false,
+            wired: false,
+            volume: 100.0,
+        });
+
+        self.send_command(UiCommand::StartModuleHost {
+            key: key.clone(),
+            module: filters::FILTER_CHAIN_MODULE.into(),
+            args,
+        });
+        self.announce(&format!("Inserting {}", preset.label()));
+        key
+    }
+
+    /// Find the filter's (input port, output port) once both nodes exist
+    pub(super) fn filter_ports(&self, key: &str) -> Option<(u32, u32)> {
+        let pw_state = self.imp().pw_state.borrow();
+        let port_on = |node_name: &str, direction: PortDirection| -> Option<u32> {
+            pw_state
+                .ports
+                .values()
+                .find(|p| {
+                    p.direction == direction
+                        && pw_state
+                            .nodes
+                            .get(&p.node_id)
+                            .map(|n| n.name == node_name)
+                            .unwrap_or(false)
+                })
+                .map(|p| p.id)
+        };
+
+        let input = port_on(filters::filter_input_node(key).as_str(), PortDirection::Input)?;
+        let output = port_on(filters::filter_output_node(key).as_str(), PortDirection::Output)?;
+        Some((input, output))
+    }
+
+    /// Route connections through filters whose ports have just appeared.
+    /// Called whenever a port is added.
+    pub(super) fn wire_pending_filters(&self) {
+        let pending: Vec<InsertedFilter> = self
+            .imp()
+            .inserted_filters
+            .borrow()
+            .iter()
+            .filter(|f| !f.wired && !f.bypassed)
+            .cloned()
+            .collect();
+
+        for filter in pending {
+            let Some((filter_in, filter_out)) = self.filter_ports(&filter.key) else {
+                continue;
+            };
+
+            self.remove_direct_link(filter.output_port_id, filter.input_port_id);
+            self.create_link(filter.output_port_id, filter_in);
+            self.create_link(filter_out, filter.input_port_id);
+
+            if let Some(f) = self
+                .imp()
+                .inserted_filters
+                .borrow_mut()
+                .iter_mut()
+                .find(|f| f.key == filter.key)
+            {
+                f.wired = true;
+            }
+            if filter.preset == FilterPreset::Volume {
+                self.apply_filter_volume(&filter.key);
+            }
+
+            self.announce(&format!(
+                "{} inserted into {}",
+                filter.preset.label(),
+                filter.connection_label
+            ));
+        }
+    }
+
+    /// Delete the link between two ports if it exists
+    fn remove_direct_link(&self, output_port_id: u32, input_port_id: u32) {
+        let link_id = self
+            .imp()
+            .pw_state
+            .borrow()
+            .find_link(output_port_id, input_port_id)
+            .map(|l| l.id);
+        if let Some(link_id) = link_id {
+            self.delete_link(link_id);
+        }
+    }
+
+    /// Remove the links that route a connection through its filter
+    fn unwire_filter(&self, filter: &InsertedFilter) {
+        if let Some((filter_in, filter_out)) = self.filter_ports(&filter.key) {
+            self.remove_direct_link(filter.output_port_id, filter_in);
+            self.remove_direct_link(filter_out, filter.input_port_id);
+        }
+    }
+
+    /// Toggle whether a connection bypasses its filter
+    fn toggle_filter_bypass(&self, key: &str) {
+        let filter = self
+            .imp()
+            .inserted_filters
+            .borrow()
+            .iter()
+            .find(|f| f.key == key)
+            .cloned();
+        let Some(filter) = filter else {
+            return;
+        };
+
+        if filter.bypassed {
+            // Re-enable: go back through the filter
+            if let Some(f) = self
+                .imp()
+                .inserted_filters
+                .borrow_mut()
+                .iter_mut()
+                .find(|f| f.key == key)
+            {
+                f.bypassed = false;
+                f.wired = false;
+            }
+            self.wire_pending_filters();
+            self.announce(&format!("{} enabled", filter.preset.label()));
+        } else {
+            if filter.wired {
+                self.unwire_filter(&filter);
+            }
+            self.create_link(filter.output_port_id, filter.input_port_id);
+            if let Some(f) = self
+                .imp()
+                .inserted_filters
+                .borrow_mut()
+                .iter_mut()
+                .find(|f| f.key == key)
+            {
+                f.bypassed = true;
+                f.wired = false;
+            }
+            self.announce(&format!("{} bypassed", filter.preset.label()));
+        }
+    }
+
+    /// Remove a filter and restore the direct connection
+    fn remove_filter(&self, key: &str) {
+        let filter = {
+            let mut inserted = self.imp().inserted_filters.borrow_mut();
+            let index = inserted.iter().position(|f| f.key == key);
+            index.map(|i| inserted.remove(i))
+        };
+        let Some(filter) = filter else {
+            return;
+        };
+
+        if filter.wired {
+            self.unwire_filter(&filter);
+        }
+        if !filter.bypassed {
+            self.create_link(filter.output_port_id, filter.input_port_id);
+        }
+
+        self.send_command(UiCommand::StopModuleHost {
+            key: filter.key.clone(),
+        });
+        self.announce(&format!(
+            "Removed {} from {}",
+            filter.preset.label(),
+            filter.connection_label
+        ));
+    }
+
+    /// A filter helper died; restore the original connection
+    pub(super) fn filter_host_exited(&self, key: &str) {
+        let filter = {
+            let mut inserted = self.imp().inserted_filters.borrow_mut();
+            let index = inserted.iter().position(|f| f.key == key);
+            index.map(|i| inserted.remove(i))
+        };
+
+        if let Some(filter) = filter {
+            if !filter.bypassed {
+                self.create_link(filter.output_port_id, filter.input_port_id);
+            }
+            self.announce(&format!(
+                "{} stopped, direct connection restored",
+                filter.preset.label()
+            ));
+        }
+    }
+
+    /// Show the inserted filters with bypass and remove controls
+    pub(super) fn show_manage_filters_dialog(&self) {
+        let inserted: Vec<InsertedFilter> = self.imp().inserted_filters.borrow().clone();
+
+        if inserted.is_empty() {
+            self.announce("No filters are inserted");
+            return;
+        }
+
+        let dialog = adw::MessageDialog::builder()
+            .transient_for(&self.dialog_parent())
+            .modal(true)
+            .heading("Inserted Filters")
+            .body("Filters stay in place while PW Audioshare is running.")
+            .build();
+
+        let list_box = gtk::ListBox::builder()
+            .selection_mode(gtk::SelectionMode::Single)
+            .css_classes(["boxed-list"])
+            .build();
+
+        for filter in &inserted {
+            let state = if filter.bypassed {
+                "bypassed"
+            } else if filter.wired {
+                "active"
+            } else {
+                "starting"
+            };
+            let title = match filter.preset {
+                FilterPreset::Volume => format!("Volume {:.0}%", filter.volume),
+                preset => preset.label().to_string(),
+            };
+            let row = adw::ActionRow::builder()
+                .title(title)
+                .subtitle(format!("{}, {}", filter.connection_label, state))
+                .activatable(true)
+                .build();
+            list_box.append(&row);
+        }
+
+        if let Some(first_row) = list_box.row_at_index(0) {
+            list_box.select_row(Some(&first_row));
+        }
+
+        let scrolled = gtk::ScrolledWindow::builder()
+            .hscrollbar_policy(gtk::PolicyType::Never)
+            .vscrollbar_policy(gtk::PolicyType::Automatic)
+            .min_content_height(100)
+            .max_content_height(300)
+            .child(&list_box)
+            .build();
+
+        dialog.set_extra_child(Some(&scrolled));
+
+        dialog.add_response("cancel", "Close");
+        dialog.add_response("remove", "Remove");
+        dialog.add_response("bypass", "Toggle Bypass");
+        dialog.set_response_appearance("remove", adw::ResponseAppearance::Destructive);
+        dialog.set_default_response(Some("bypass"));
+        dialog.set_close_response("cancel");
+
+        dialog.connect_response(
+            None,
+            glib::clone!(
+                #[weak(rename_to = window)]
+                self,
+                #[weak]
+                list_box,
+                move |dialog, response| {
+                    dialog.close();
+                    let selected = list_box
+                        .selected_row()
+                        .and_then(|row| inserted.get(row.index() as usize))
+                        .map(|f| f.key.clone());
+
+                    match (response, selected) {
+                        ("remove", Some(key)) => window.remove_filter(&key),
+                        ("bypass", Some(key)) => window.toggle_filter_bypass(&key),
+                        _ => {}
+                    }
+                }
+            ),
+        );
+
+        dialog.present();
+        list_box.grab_focus();
+    }
+}
@@ -0,0 +1,119 @@
+use adw::prelude::*;
+use adw::subclass::prelude::*;
+use gtk::{gio, glib};
+
+use super::Window;
+
+impl Window {
+    /// Remember where focus was in the window, and put it back there when
+    /// the window becomes active again after a dialog closes. Otherwise
+    /// focus lands on whatever opened the dialog, often the menu button.
+    pub(super) fn track_focus(&self) {
+        self.connect_focus_widget_notify(|window| {
+            let Some(widget) = gtk::prelude::RootExt::focus(window) else {
+                return;
+            };
+            let in_content = window
+                .imp()
+                .graph_stack
+                .borrow()
+                .as_ref()
+                .is_some_and(|stack| widget.is_ancestor(stack));
+            if !in_content {
+                return;
+            }
+            // A list puts focus back on its own focused row, which survives
+            // the row widget being recycled for another item
+            let target = widget
+                .ancestor(gtk::ListView::static_type())
+                .unwrap_or(widget);
+            window.imp().last_content_focus.set(Some(&target));
+        });
+
+        self.connect_is_active_notify(|window| {
+            if !window.is_active() {
+                window
+                    .imp()
+                    .restore_focus_on_activate
+                    .set(window.has_open_dialog());
+                return;
+            }
+            if window.imp().restore_focus_on_activate.replace(false) {
+                window.restore_content_focus();
+            }
+        });
+    }
+
+    /// Whether a dialog of this window is showing
+    fn has_open_dialog(&self) -> bool {
+        let toplevels = gtk::Window::toplevels();
+        (0..toplevels.n_items())
+            .filter_map(|i| toplevels.item(i).and_downcast::<gtk::Window>())
+            .any(|toplevel| {
+                toplevel.is_visible()
+                    && toplevel
+                        .transient_for()
+                        .is_some_and(|parent| parent == *self.upcast_ref::<gtk::Window>())
+            })
+    }
+
+    /// Focus the widget focused before the dialog, or the port list used
+    /// last if it is gone
+    fn restore_content_focus(&self) {
+        let target = self.imp().last_content_focus.upgrade();
+        // The dialog's own focus handling runs first
+        glib::idle_add_local_once(glib::clone!(
+            #[weak(rename_to = window)]
+            self,
+            move || {
+                if let Some(widget) = target.filter(|w| w.is_mapped() && w.is_focusable()) {
+                    if widget.grab_focus() {
+                        return;
+                    }
+                }
+                if *window.imp().last_port_list_was_output.borrow() {
+                    window.focus_output_list();
+                } else {
+                    window.focus_input_list();
+                }
+            }
+        ));
+    }
+
+    /// Whether focus is on a row of `list_view`
+    pub(super) fn focus_in_list(&self, list_view: &gtk::ListView) -> bool {
+        gtk::prelude::RootExt::focus(self).is_some_and(|widget| widget.is_ancestor(list_view))
+    }
+
+    /// After the focused row of `list_view` was removed, focus the row that
+    /// took its place at `position`, or the new last row
+    pub(super) fn refocus_list_after_removal(&self, list_view: &gtk::ListView, position: u32) {
+        glib::idle_add_local_once(glib::clone!(
+            #[weak(rename_to = window)]
+            self,
+            #[weak]
+            list_view,
+            move || {
+                // Focus moved on already, e.g. to a dialog
+                if window.focus_in_list(&list_view) {
+                    return;
+                }
+                if gtk::prelude::RootExt::focus(&window).is_some_and(|w| w != list_view) {
+                    return;
+                }
+                let n_items = list_view.model().map_or(0, |model| model.n_items());
+                if n_items == 0 {
+                    list_view.grab_focus();
+                    return;
+                }
+                list_view.scroll_to(position.min(n_items - 1), gtk::ListScrollFlags::FOCUS, None);
+            }
+        ));
+    }
+}
+
+/// Position of `item` in a list model, such as the filtered and sorted
+/// model a list shows
+pub(super) fn position_in(model: &impl IsA<gio::ListModel>, item: &glib::Object) -> Option<u32> {
+    (0..model.n_items()).find(|&i| model.item(i).as_ref() == Some(item))
+}
@@ -0,0 +1,251 @@
+use std::collections::HashMap;
+
+use adw::prelude::*;
+use adw::subclass::prelude::*;
+use gtk::glib;
+
+use super::Window;
+use crate::pipewire::profiler::{NodeLoad, ProfilerMonitor};
+
+/// The open Graph Load window: one label per node, updated in place so the
+/// screen reader's position survives each refresh
+pub struct GraphLoadView {
+    pub list_box: gtk::ListBox,
+    pub summary: gtk::Label,
+    pub rows: HashMap<u32, gtk::Label>,
+    /// (node, driver) in display order, to notice when the graph changes
+    pub order: Vec<(u32, Option<u32>)>,
+    /// Error counts at the last refresh, to announce new xruns
+    pub errors: HashMap<u32, u64>,
+    pub _monitor: ProfilerMonitor,
+}
+
+/// Drivers first, each followed by its followers, busiest first
+fn display_order(loads: &[NodeLoad]) -> Vec<&NodeLoad> {
+    let busy = |load: &&NodeLoad| std::cmp::Reverse((load.busy_us.unwrap_or(0.0) * 1000.0) as u64);
+    let mut drivers: Vec<&NodeLoad> = loads.iter().filter(|l| l.is_driver()).collect();
+    drivers.sort_by_key(busy);
+
+    let mut ordered = Vec::new();
+    for driver in drivers {
+        ordered.push(driver);
+        let mut followers: Vec<&NodeLoad> = loads
+            .iter()
+            .filter(|l| l.driver_id == Some(driver.id))
+            .collect();
+        followers.sort_by_key(busy);
+        ordered.extend(followers);
+    }
+    ordered
+}
+
+fn state_label(state: char) -> &'static str {
+    match state {
+        'R' => "running",
+        'I' => "idle",
+        'S' => "suspended",
+        'C' => "starting",
+        'E' => "error",
+        _ => "unknown state",
+    }
+}
+
+impl Window {
+    /// Show per-node processing time, errors and drivers from the PipeWire
+    /// profiler, like `pw-top`, to find the client causing crackles
+    pub(super) fn show_graph_load(&self) {
+        if let Some(view) = self.imp().graph_load.borrow().as_ref() {
+            if let Some(viewer) = view.list_box.root().and_downcast::<gtk::Window>() {
+                viewer.present();
+                return;
+            }
+        }
+
+        let (load_tx, load_rx) = async_channel::unbounded::<Vec<NodeLoad>>();
+        let monitor = match ProfilerMonitor::spawn(self.server().as_deref(), load_tx) {
+            Ok(monitor) => monitor,
+            Err(e) => {
+                self.announce_error(&e.to_string());
+                return;
+            }
+        };
+
+        let viewer = adw::Window::builder()
+            .transient_for(&self.dialog_parent())
+            .title("Graph Load")
+            .default_width(700)
+            .default_height(500)
+            .build();
+
+        let content = gtk::Box::builder()
+            .orientation(gtk::Orientation::Vertical)
+            .build();
+        content.append(&adw::HeaderBar::new());
+
+        let summary = gtk::Label::builder()
+            .label("Waiting for the profiler...")
+            .xalign(0.0)
+            .wrap(true)
+            .margin_start(12)
+            .margin_end(12)
+            .margin_top(6)
+            .margin_bottom(6)
+            .build();
+        content.append(&summary);
+
+        let list_box = gtk::ListBox::builder()
+            .selection_mode(gtk::SelectionMode::None)
+            .build();
+        list_box.update_property(&[gtk::accessible::Property::Label("Node load")]);
+        let scrolled = gtk::ScrolledWindow::builder()
+            .hscrollbar_policy(gtk::PolicyType::Never)
+            .vexpand(true)
+            .child(&list_box)
+            .build();
+        content.append(&scrolled);
+        viewer.set_content(Some(&content));
+
+        self.imp().graph_load.replace(Some(GraphLoadView {
+            list_box: list_box.clone(),
+            summary,
+            rows: HashMap::new(),
+            order: Vec::new(),
+            errors: HashMap::new(),
+            _monitor: monitor,
+        }));
+
+        // Stop pw-top when the window closes
+        viewer.connect_close_request(glib::clone!(
+            #[weak(rename_to = window)]
+            self,
+            #[upgrade_or]
+            glib::Propagation::Proceed,
+            move |_| {
+                window.imp().graph_load.replace(None);
+                glib::Propagation::Proceed
+            }
+        ));
+
+        glib::spawn_future_local(glib::clone!(
+            #[weak(rename_to = window)]
+            self,
+            async move {
+                while let Ok(loads) = load_rx.recv().await {
+                    window.graph_load_updated(&loads);
+                }
+            }
+        ));
+
+        viewer.present();
+        list_box.grab_focus();
+    }
+
+    /// Describe one node for its row, naming the driver of a follower
+    fn node_load_text(&self, load: &NodeLoad) -> String {
+        let pw_state = self.imp().pw_state.borrow();
+        let name_of = |id: u32, fallback: &str| {
+            pw_state
+                .nodes
+                .get(&id)
+                .map(|node| node.display_name().to_string())
+                .unwrap_or_else(|| fallback.to_string())
+        };
+
+        let mut text = if load.is_driver() {
+            format!("Driver {}, {}", name_of(load.id, &load.name), state_label(load.state))
+        } else {
+            format!("{}, {}", name_of(load.id, &load.name), state_label(load.state))
+        };
+        if let (Some(quantum), Some(rate)) = (load.quantum, load.rate) {
+            text.push_str(&format!(", quantum {} at {} Hz", quantum, rate));
+        }
+        if let Some(busy) = load.busy_us {
+            text.push_str(&format!(", busy {:.1} µs", busy));
+            if let Some(fraction) = load.busy_quantum {
+                text.push_str(&format!(" ({:.0}% of the cycle)", fraction * 100.0));
+            }
+        }
+        if let Some(wait) = load.wait_us {
+            text.push_str(&format!(", waits {:.1} µs", wait));
+        }
+        if load.errors > 0 {
+            text.push_str(&format!(", {} errors", load.errors));
+        }
+        text
+    }
+
+    /// Show a refresh from the profiler, announcing nodes with new errors
+    fn graph_load_updated(&self, loads: &[NodeLoad]) {
+        let ordered = display_order(loads);
+        let texts: Vec<(u32, Option<u32>, String)> = ordered
+            .iter()
+            .map(|load| (load.id, load.driver_id, self.node_load_text(load)))
+            .collect();
+
+        let mut new_errors = Vec::new();
+        {
+            let mut view = self.imp().graph_load.borrow_mut();
+            let Some(view) = view.as_mut() else {
+                return;
+            };
+
+            let order: Vec<(u32, Option<u32>)> =
+                texts.iter().map(|(id, driver, _)| (*id, *driver)).collect();
+            if order != view.order {
+                // Nodes came, went or moved to another driver
+                view.list_box.remove_all();
+                view.rows.clear();
+                for (id, driver, _) in &texts {
+                    let label = gtk::Label::builder()
+                        .xalign(0.0)
+                        .wrap(true)
+                        .margin_start(if driver.is_some() { 30 } else { 6 })
+                        .margin_end(6)
+                        .margin_top(2)
+                        .margin_bottom(2)
+                        .build();
+                    view.list_box.append(&label);
+                    view.rows.insert(*id, label);
+                }
+                view.order = order;
+            }
+            for (id, _, text) in &texts {
+                if let Some(label) = view.rows.get(id) {
+                    if label.text() != text.as_str() {
+                        label.set_text(text);
+                    }
+                }
+            }
+
+            for load in loads {
+                let before = view.errors.insert(load.id, load.errors);
+                if before.is_some_and(|before| load.errors > before) {
+                    new_errors.push(load.id);
+                }
+            }
+
+            let drivers = loads.iter().filter(|l| l.is_driver()).count();
+            let with_errors = loads.iter().filter(|l| l.errors > 0).count();
+            view.summary.set_text(&format!(
+                "{} drivers, {} nodes, {} with errors. Busy time close to 100% of the \
+                 cycle causes crackles; a larger quantum gives nodes more time.",
+                drivers,
+                loads.len(),
+                with_errors
+            ));
+        }
+
+        for id in new_errors {
+            let name = self
+                .imp()
+                .pw_state
+                .borrow()
+                .nodes
+                .get(&id)
+                .map(|node| node.display_name().to_string());
+            if let Some(name) = name {
+                self.announce(&format!("Xrun in {}", name));
+            }
+        }
+    }
+}
@@ -0,0 +1,102 @@
+use adw::prelude::*;
+use adw::subclass::prelude::*;
+
+use super::Window;
+use crate::pipewire::health::{self, HealthProblem, HealthReport};
+
+impl Window {
+    /// Store the startup health report and explain any problems found
+    pub(super) fn health_checked(&self, report: HealthReport) {
+        let problems = report.problems();
+        for problem in &problems {
+            log::warn!("Health check: {}", problem.summary);
+        }
+        self.imp().health_report.replace(Some(report));
+
+        if !problems.is_empty() {
+            self.show_health_dialog("PipeWire Problems Found", &problems);
+        } else {
+            self.maybe_show_setup_assistant();
+        }
+    }
+
+    /// Explain why connecting to PipeWire failed, on the status page
+    /// shown in place of the port lists
+    pub(super) fn connection_failed(&self, reason: &str) {
+        self.imp().health_report.replace(None);
+        self.show_offline_page(reason);
+    }
+
+    /// Show the result of the last health check on request
+    pub(super) fn show_troubleshooting_dialog(&self) {
+        let report = self.imp().health_report.borrow().clone();
+        let problems = match &report {
+            Some(report) => report.problems(),
+            None => health::diagnose_connection_failure(),
+        };
+
+        if problems.is_empty() {
+            let version = report
+                .and_then(|r| r.server_version)
+                .unwrap_or_else(|| "unknown".into());
+            self.announce(&format!("No problems found, PipeWire version {}", version));
+            return;
+        }
+
+        self.show_health_dialog("Troubleshooting", &problems);
+    }
+
+    /// List problems with a hint for fixing each
+    fn show_health_dialog(&self, heading: &str, problems: &[HealthProblem]) {
+        let body = if problems.len() == 1 {
+            "1 problem was found. Some features may not work until it is fixed.".to_string()
+        } else {
+            format!(
+                "{} problems were found. Some features may not work until they are fixed.",
+                problems.len()
+            )
+        };
+
+        let dialog = adw::MessageDialog::builder()
+            .transient_for(&self.dialog_parent())
+            .modal(true)
+            .heading(heading)
+            .body(body)
+            .build();
+
+        let list_box = gtk::ListBox::builder()
+            .selection_mode(gtk::SelectionMode::None)
+            .css_classes(["boxed-list"])
+            .build();
+        list_box.update_property(&[gtk::accessible::Property::Label("Problems")]);
+
+        for problem in problems {
+            let row = adw::ActionRow::builder()
+                .title(&problem.summary)
+                .subtitle(&problem.hint)
+                .subtitle_selectable(true)
+                .focusable(true)
+                .build();
+            list_box.append(&row);
+        }
+
+        let scrolled = gtk::ScrolledWindow::builder()
+            .hscrollbar_policy(gtk::PolicyType::Never)
+            .vscrollbar_policy(gtk::PolicyType::Automatic)
+            .min_content_height(100)
+            .max_content_height(300)
+            .child(&list_box)
+            .build();
+
+        dialog.set_extra_child(Some(&scrolled));
+
+        dialog.add_response("close", "Close");
+        dialog.set_default_response(Some("close"));
+        dialog.set_close_response("close");
+
+        dialog.present();
+        if let Some(first_row) = list_box.row_at_index(0) {
+            first_row.grab_focus();
+        }
+    }
+}
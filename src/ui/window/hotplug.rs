@@ -0,0 +1,89 @@
+use std::time::Duration;
+
+use adw::prelude::*;
+use adw::subclass::prelude::*;
+use gtk::glib;
+
+use super::Window;
+
+/// How long to wait for a new device's ports before announcing it
+const PORTS_SETTLE_TIME: Duration = Duration::from_millis(500);
+
+impl Window {
+    /// Announce a device plugged in after the graph was loaded, once its
+    /// ports have had time to arrive
+    pub(super) fn device_added(&self, node_id: u32) {
+        if self.imp().health_report.borrow().is_none() {
+            return;
+        }
+        let mut pending = self.imp().hotplug_pending.borrow_mut();
+        pending.push(node_id);
+        if pending.len() > 1 {
+            return;
+        }
+        glib::timeout_add_local_once(
+            PORTS_SETTLE_TIME,
+            glib::clone!(
+                #[weak(rename_to = window)]
+                self,
+                move || window.announce_added_devices()
+            ),
+        );
+    }
+
+    /// Announce a device that was unplugged, unless it was too short-lived
+    /// to be announced at all
+    pub(super) fn device_removed(&self, node_id: u32, label: &str) {
+        if self.imp().health_report.borrow().is_none() {
+            return;
+        }
+        let mut pending = self.imp().hotplug_pending.borrow_mut();
+        if let Some(index) = pending.iter().position(|id| *id == node_id) {
+            pending.remove(index);
+            return;
+        }
+        drop(pending);
+        if self.is_active() {
+            self.announce(&format!("Device removed: {}", label));
+        }
+    }
+
+    /// "New output device: USB Audio CODEC, 2 ports" for each device added
+    /// since the last announcement. Only made while the window is focused,
+    /// where the change is to the lists the user is reading.
+    fn announce_added_devices(&self) {
+        let added = self.imp().hotplug_pending.take();
+        if !self.is_active() {
+            return;
+        }
+        let messages: Vec<String> = {
+            let pw_state = self.imp().pw_state.borrow();
+            added
+                .iter()
+                .filter_map(|id| pw_state.nodes.get(id))
+                .map(|node| {
+                    let ports = pw_state
+                        .ports
+                        .values()
+                        .filter(|port| port.node_id == node.id)
+                        .count();
+                    let kind = match node.media_class.as_deref().unwrap_or_default() {
+                        class if class.starts_with("Video/") => "camera",
+                        class if class.starts_with("Midi/") => "MIDI device",
+                        class if class.ends_with("/Sink") => "output device",
+                        class if class.ends_with("/Source") => "input device",
+                        _ => "device",
+                    };
+                    let count = match ports {
+                        1 => "1 port".to_string(),
+                        n => format!("{} ports", n),
+                    };
+                    format!("New {}: {}, {}", kind, node.display_name(), count)
+                })
+                .collect()
+        };
+        for message in messages {
+            self.announce(&message);
+        }
+    }
+}
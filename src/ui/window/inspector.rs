@@ -0,0 +1,314 @@
+use std::collections::BTreeMap;
+
+use adw::prelude::*;
+use adw::subclass::prelude::*;
+use gtk::glib;
+
+use super::Window;
+use crate::model::{LinkObject, PortObject};
+
+/// A row of the property list; activating it copies `key = value`
+fn property_row(key: &str, value: &str) -> gtk::ListBoxRow {
+    let label = gtk::Label::builder()
+        .label(format!("{} = {}", key, value))
+        .xalign(0.0)
+        .wrap(true)
+        .wrap_mode(gtk::pango::WrapMode::WordChar)
+        .margin_start(6)
+        .margin_end(6)
+        .margin_top(2)
+        .margin_bottom(2)
+        .build();
+    gtk::ListBoxRow::builder().child(&label).build()
+}
+
+/// A heading naming the object whose properties follow
+fn heading_row(text: &str) -> gtk::ListBoxRow {
+    let label = gtk::Label::builder()
+        .label(text)
+        .xalign(0.0)
+        .margin_start(6)
+        .margin_top(6)
+        .css_classes(["heading"])
+        .build();
+    gtk::ListBoxRow::builder()
+        .child(&label)
+        .activatable(false)
+        .build()
+}
+
+/// Whether a property matches the inspector's search, by key or value
+fn matches_search(key: &str, value: &str, search: &str) -> bool {
+    search.is_empty()
+        || key.to_lowercase().contains(search)
+        || value.to_lowercase().contains(search)
+}
+
+impl Window {
+    /// Build the side panel listing every property of the selected port
+    /// (and its node) or connection
+    pub(super) fn build_inspector_panel(&self) -> gtk::Revealer {
+        let frame = gtk::Frame::builder()
+            .label("Properties")
+            .margin_end(12)
+            .margin_top(6)
+            .margin_bottom(6)
+            .width_request(360)
+            .build();
+
+        let panel_box = gtk::Box::builder()
+            .orientation(gtk::Orientation::Vertical)
+            .spacing(6)
+            .margin_start(6)
+            .margin_end(6)
+            .margin_top(6)
+            .margin_bottom(6)
+            .build();
+
+        let search = gtk::SearchEntry::builder()
+            .placeholder_text("Search properties...")
+            .build();
+        search.update_property(&[gtk::accessible::Property::Label("Search properties")]);
+        search.connect_search_changed(glib::clone!(
+            #[weak(rename_to = window)]
+            self,
+            move |_| window.refresh_inspector()
+        ));
+        panel_box.append(&search);
+        self.imp().inspector_search.replace(Some(search));
+
+        let list_box = gtk::ListBox::builder()
+            .selection_mode(gtk::SelectionMode::None)
+            .build();
+        list_box.update_property(&[gtk::accessible::Property::Label("Properties")]);
+        list_box.connect_row_activated(glib::clone!(
+            #[weak(rename_to = window)]
+            self,
+            move |_, row| {
+                if let Some(label) = row.child().and_downcast::<gtk::Label>() {
+                    window.clipboard().set_text(&label.text());
+                    window.announce(&format!("Copied {}", label.text()));
+                }
+            }
+        ));
+        self.imp().inspector_list.replace(Some(list_box.clone()));
+
+        let scrolled = gtk::ScrolledWindow::builder()
+            .hscrollbar_policy(gtk::PolicyType::Never)
+            .vscrollbar_policy(gtk::PolicyType::Automatic)
+            .vexpand(true)
+            .child(&list_box)
+            .build();
+        panel_box.append(&scrolled);
+
+        frame.set_child(Some(&panel_box));
+
+        let revealer = gtk::Revealer::builder()
+            .transition_type(gtk::RevealerTransitionType::SlideLeft)
+            .reveal_child(false)
+            .child(&frame)
+            .build();
+        self.imp().inspector_revealer.replace(Some(revealer.clone()));
+        revealer
+    }
+
+    /// Follow the selection of the port and connection lists
+    pub(super) fn connect_inspector_selection(&self) {
+        let selections: Vec<gtk::SelectionModel> = [
+            self.imp().output_selection.borrow().clone().map(|s| s.upcast()),
+            self.imp().input_selection.borrow().clone().map(|s| s.upcast()),
+            self.imp().connections_selection.borrow().clone().map(|s| s.upcast()),
+        ]
+        .into_iter()
+        .flatten()
+        .collect();
+
+        for selection in selections {
+            selection.connect_selection_changed(glib::clone!(
+                #[weak(rename_to = window)]
+                self,
+                move |selection, position, n_items| {
+                    if !window.inspector_visible() {
+                        return;
+                    }
+                    // The item just selected, else any still selected
+                    let changed = (position..position + n_items)
+                        .find(|&i| selection.is_selected(i))
+                        .and_then(|i| selection.item(i));
+                    let item = changed.or_else(|| {
+                        let bitset = selection.selection();
+                        if bitset.is_empty() {
+                            None
+                        } else {
+                            selection.item(bitset.nth(0))
+                        }
+                    });
+                    window.inspect_item(item.as_ref());
+                }
+            ));
+        }
+    }
+
+    fn inspector_visible(&self) -> bool {
+        self.imp()
+            .inspector_revealer
+            .borrow()
+            .as_ref()
+            .is_some_and(|revealer| revealer.reveals_child())
+    }
+
+    /// Show or hide the property inspector, inspecting what is selected in
+    /// the focused list
+    pub(super) fn set_inspector_visible(&self, visible: bool) {
+        if let Some(revealer) = self.imp().inspector_revealer.borrow().as_ref() {
+            revealer.set_reveal_child(visible);
+        }
+        if !visible {
+            let had_focus = self
+                .imp()
+                .inspector_revealer
+                .borrow()
+                .as_ref()
+                .is_some_and(|revealer| revealer.focus_child().is_some());
+            if had_focus {
+                // Don't leave focus inside the hidden panel
+                self.focus_output_list();
+            }
+            return;
+        }
+
+        let in_connections = self
+            .imp()
+            .connections_list_view
+            .borrow()
+            .as_ref()
+            .is_some_and(|list| list.focus_child().is_some());
+        let in_inputs = self
+            .imp()
+            .input_list_view
+            .borrow()
+            .as_ref()
+            .is_some_and(|list| list.focus_child().is_some());
+        let item: Option<glib::Object> = if in_connections {
+            self.imp()
+                .connections_selection
+                .borrow()
+                .as_ref()
+                .and_then(|s| s.selected_item())
+        } else {
+            self.selected_ports(!in_inputs)
+                .into_iter()
+                .next()
+                .map(|port| port.upcast())
+        };
+        self.inspect_item(item.as_ref());
+
+        let title = self.imp().inspector_title.borrow().clone();
+        if title.is_empty() {
+            self.announce("Properties. Select a port or connection to see its properties");
+        } else {
+            self.announce(&format!("Properties of {}", title));
+        }
+    }
+
+    /// Inspect a port or connection from one of the lists
+    fn inspect_item(&self, item: Option<&glib::Object>) {
+        let id = item.and_then(|item| {
+            if let Some(port) = item.downcast_ref::<PortObject>() {
+                Some(port.id())
+            } else {
+                item.downcast_ref::<LinkObject>().map(|link| link.id())
+            }
+        });
+        self.imp().inspected_object.set(id);
+        self.refresh_inspector();
+    }
+
+    /// Store the properties of an object, updating the inspector if it
+    /// shows them
+    pub(super) fn properties_changed(&self, id: u32, properties: BTreeMap<String, String>) {
+        self.imp()
+            .pw_state
+            .borrow_mut()
+            .properties
+            .insert(id, properties);
+
+        let Some(inspected) = self.imp().inspected_object.get() else {
+            return;
+        };
+        let node_of_inspected = self
+            .imp()
+            .pw_state
+            .borrow()
+            .ports
+            .get(&inspected)
+            .map(|port| port.node_id);
+        if self.inspector_visible() && (inspected == id || node_of_inspected == Some(id)) {
+            self.refresh_inspector();
+        }
+    }
+
+    /// Fill the property list for the inspected object
+    fn refresh_inspector(&self) {
+        let Some(list_box) = self.imp().inspector_list.borrow().clone() else {
+            return;
+        };
+        list_box.remove_all();
+
+        let search = self
+            .imp()
+            .inspector_search
+            .borrow()
+            .as_ref()
+            .map(|entry| entry.text().trim().to_lowercase())
+            .unwrap_or_default();
+
+        // The object itself, then the node of a port
+        let sections: Vec<(String, u32)> = {
+            let pw_state = self.imp().pw_state.borrow();
+            match self.imp().inspected_object.get() {
+                Some(id) if pw_state.ports.contains_key(&id) => {
+                    let port = &pw_state.ports[&id];
+                    let mut sections = vec![(
+                        format!("Port {}", pw_state.port_label(id).unwrap_or_default()),
+                        id,
+                    )];
+                    if let Some(node) = pw_state.nodes.get(&port.node_id) {
+                        sections.push((format!("Node {}", node.display_name()), node.id));
+                    }
+                    sections
+                }
+                Some(id) if pw_state.links.contains_key(&id) => {
+                    vec![(
+                        format!("Connection {}", self.link_label(id).unwrap_or_default()),
+                        id,
+                    )]
+                }
+                _ => Vec::new(),
+            }
+        };
+
+        let title = sections
+            .first()
+            .map(|(heading, _)| heading.clone())
+            .unwrap_or_default();
+        self.imp().inspector_title.replace(title);
+        if sections.is_empty() {
+            list_box.append(&heading_row("Select a port or connection"));
+            return;
+        }
+
+        let pw_state = self.imp().pw_state.borrow();
+        for (heading, id) in sections {
+            list_box.append(&heading_row(&heading));
+            let Some(properties) = pw_state.properties.get(&id) else {
+                continue;
+            };
+            for (key, value) in properties {
+                if matches_search(key, value, &search) {
+                    list_box.append(&property_row(key, value));
+                }
+            }
+        }
+    }
+}
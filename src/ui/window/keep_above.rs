@@ -0,0 +1,196 @@
+use adw::prelude::*;
+use adw::subclass::prelude::*;
+use gtk::{gio, glib};
+
+use super::Window;
+
+/// Ask the window manager to keep a window above others, or stop.
+///
+/// On X11 this sends the EWMH `_NET_WM_STATE` client message that window
+/// managers act on for "Always on Top". Wayland has no such request; there
+/// only the compact window can be kept on top, as a layer surface (see
+/// [`layer_shell_supported`]).
+pub(super) fn request_keep_above(
+    window: &impl IsA<gtk::Window>,
+    above: bool,
+) -> Result<(), String> {
+    #[cfg(feature = "x11")]
+    if let Some(display) = window.display().downcast_ref::<gdk4_x11::X11Display>() {
+        return x11_keep_above(display, window, above);
+    }
+
+    let _ = (window, above);
+    Err(if layer_shell_supported() {
+        "This desktop only lets the compact window stay on top. \
+         Use Always on Top from the window menu (Alt+Space) instead"
+            .to_string()
+    } else {
+        "This desktop doesn't let applications stay on top. \
+         Use Always on Top from the window menu (Alt+Space) instead"
+            .to_string()
+    })
+}
+
+/// Add or remove `_NET_WM_STATE_ABOVE` on a mapped X11 window
+#[cfg(feature = "x11")]
+fn x11_keep_above(
+    display: &gdk4_x11::X11Display,
+    window: &impl IsA<gtk::Window>,
+    above: bool,
+) -> Result<(), String> {
+    use std::os::raw::c_long;
+    use x11::xlib;
+
+    if !display.screen().supports_net_wm_hint("_NET_WM_STATE_ABOVE") {
+        return Err("The window manager can't keep windows on top".to_string());
+    }
+    let Some(surface) = window
+        .surface()
+        .and_then(|surface| surface.downcast::<gdk4_x11::X11Surface>().ok())
+    else {
+        return Err("The window isn't shown yet".to_string());
+    };
+
+    let wm_state = gdk4_x11::x11_get_xatom_by_name_for_display(display, "_NET_WM_STATE");
+    let state_above = gdk4_x11::x11_get_xatom_by_name_for_display(display, "_NET_WM_STATE_ABOVE");
+
+    // SAFETY: the Xlib display belongs to the open GDK display and the event
+    // is a fully initialised client message for the root window
+    unsafe {
+        let xdisplay = display.xdisplay();
+        let mut event: xlib::XEvent = std::mem::zeroed();
+        event.client_message.type_ = xlib::ClientMessage;
+        event.client_message.window = surface.xid();
+        event.client_message.message_type = wm_state;
+        event.client_message.format = 32;
+        // _NET_WM_STATE_ADD is 1, _NET_WM_STATE_REMOVE is 0
+        let data = &mut event.client_message.data;
+        data.set_long(0, above as c_long);
+        data.set_long(1, state_above as c_long);
+        // Source indication: a normal application
+        data.set_long(3, 1);
+        xlib::XSendEvent(
+            xdisplay,
+            display.xrootwindow(),
+            xlib::False,
+            xlib::SubstructureRedirectMask | xlib::SubstructureNotifyMask,
+            &mut event,
+        );
+        xlib::XFlush(xdisplay);
+    }
+    Ok(())
+}
+
+/// Whether windows can be kept on top as wlr layer surfaces, which
+/// compositors such as Sway, Hyprland and KDE Plasma offer.
+///
+/// A window has to become a layer surface before it is first shown, so only
+/// the compact window, which is rebuilt when pinned, is kept on top this way.
+#[cfg(feature = "layer-shell")]
+pub(super) fn layer_shell_supported() -> bool {
+    gtk4_layer_shell::is_supported()
+}
+
+#[cfg(not(feature = "layer-shell"))]
+pub(super) fn layer_shell_supported() -> bool {
+    false
+}
+
+/// Turn a window that has not been shown yet into a layer surface above
+/// normal windows, in the top right corner of the screen
+#[cfg(feature = "layer-shell")]
+pub(super) fn init_above_layer(window: &impl IsA<gtk::Window>) {
+    use gtk4_layer_shell::{Edge, KeyboardMode, Layer, LayerShell};
+
+    let window = window.as_ref();
+    window.init_layer_shell();
+    window.set_namespace(Some("pw-audioshare"));
+    window.set_layer(Layer::Top);
+    window.set_keyboard_mode(KeyboardMode::OnDemand);
+    window.set_anchor(Edge::Top, true);
+    window.set_anchor(Edge::Right, true);
+    window.set_margin(Edge::Top, 12);
+    window.set_margin(Edge::Right, 12);
+}
+
+#[cfg(not(feature = "layer-shell"))]
+pub(super) fn init_above_layer(_window: &impl IsA<gtk::Window>) {}
+
+impl Window {
+    /// Keep the main window above other windows, such as a DAW, or stop
+    pub(super) fn set_keep_above(&self, above: bool) {
+        if let Err(message) = request_keep_above(self, above) {
+            self.set_keep_above_state(false);
+            self.show_toast(&message);
+            self.announce_error(&message);
+            return;
+        }
+
+        if above {
+            self.announce("Window kept on top");
+        } else {
+            self.announce("Window no longer kept on top");
+        }
+    }
+
+    /// Whether the keep-above pin is on
+    fn keep_above(&self) -> bool {
+        self.lookup_action("keep-above")
+            .and_then(|action| action.state())
+            .and_then(|state| state.get::<bool>())
+            .unwrap_or(false)
+    }
+
+    fn set_keep_above_state(&self, above: bool) {
+        if let Some(action) = self
+            .lookup_action("keep-above")
+            .and_downcast::<gio::SimpleAction>()
+        {
+            action.set_state(&above.to_variant());
+        }
+    }
+
+    /// Window managers drop the keep-above state when a window is hidden, so
+    /// ask again whenever the window comes back from the tray
+    pub(super) fn restore_keep_above_on_map(&self) {
+        self.connect_map(|window| {
+            if !window.keep_above() {
+                return;
+            }
+            glib::idle_add_local_once(glib::clone!(
+                #[weak]
+                window,
+                move || {
+                    if let Err(message) = request_keep_above(&window, true) {
+                        log::warn!("{}", message);
+                    }
+                }
+            ));
+        });
+    }
+
+    /// Keep the compact window on top, or stop. With layer-shell the window
+    /// is closed and opened again, since only a new window can change role.
+    pub(super) fn set_compact_keep_above(&self, compact: &adw::Window, above: bool) {
+        self.imp().compact_above.set(above);
+
+        if layer_shell_supported() {
+            compact.close();
+            self.show_compact_window();
+        } else if let Err(message) = request_keep_above(compact, above) {
+            self.imp().compact_above.set(false);
+            if above {
+                self.announce_error(&message);
+            } else {
+                log::warn!("{}", message);
+            }
+            return;
+        }
+
+        if above {
+            self.announce("Compact window kept on top");
+        } else {
+            self.announce("Compact window no longer kept on top");
+        }
+    }
+}
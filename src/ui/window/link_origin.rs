@@ -0,0 +1,132 @@
+use adw::prelude::*;
+use adw::subclass::prelude::*;
+use gtk::glib;
+
+use super::Window;
+use crate::model::LinkObject;
+use crate::pipewire::state::LinkOrigin;
+
+impl Window {
+    /// Who made a link. Links we requested are known from the request, as
+    /// inside a sandbox our process id isn't the one PipeWire sees.
+    fn link_origin(&self, link_id: u32) -> LinkOrigin {
+        if self.imp().own_links.borrow().contains(&link_id) {
+            return LinkOrigin::App;
+        }
+        self.imp().pw_state.borrow().link_origin(link_id)
+    }
+
+    /// Update who a link is shown as made by, once its properties or the
+    /// request that made it are known
+    pub(super) fn refresh_link_origin(&self, link_id: u32) {
+        let Some(link) = self.imp().link_objects.borrow().get(&link_id).cloned() else {
+            return;
+        };
+        // A row merging duplicates shows the link it keeps
+        if link.id() != link_id {
+            return;
+        }
+        let origin = self.link_origin(link_id).as_str();
+        if link.origin() == origin {
+            return;
+        }
+        link.set_origin(origin);
+
+        if self.imp().connections_origin.get().is_some() {
+            if let Some(filter) = self.imp().connections_origin_filter.borrow().as_ref() {
+                filter.changed(gtk::FilterChange::Different);
+            }
+        }
+    }
+
+    /// Note a link made on our request
+    pub(super) fn track_own_link(&self, link_id: u32) {
+        self.imp().own_links.borrow_mut().insert(link_id);
+        self.refresh_link_origin(link_id);
+    }
+
+    /// Filter for the connections list keeping the links made by the chosen
+    /// origin, or all of them
+    pub(super) fn new_origin_filter(&self) -> gtk::CustomFilter {
+        gtk::CustomFilter::new(glib::clone!(
+            #[weak(rename_to = window)]
+            self,
+            #[upgrade_or]
+            true,
+            move |obj| {
+                let Some(origin) = window.imp().connections_origin.get() else {
+                    return true;
+                };
+                obj.downcast_ref::<LinkObject>()
+                    .is_some_and(|link| link.origin() == origin.as_str())
+            }
+        ))
+    }
+
+    /// Drop-down choosing whose connections are listed, to tell the links
+    /// of auto-connect and presets from those of the session manager
+    pub(super) fn build_origin_filter_bar(&self) -> gtk::Box {
+        let bar = gtk::Box::builder()
+            .orientation(gtk::Orientation::Horizontal)
+            .spacing(12)
+            .margin_start(6)
+            .margin_end(6)
+            .margin_top(4)
+            .build();
+
+        let label = gtk::Label::builder()
+            .label("_Made by")
+            .use_underline(true)
+            .halign(gtk::Align::Start)
+            .build();
+
+        let mut labels = vec!["All"];
+        labels.extend(LinkOrigin::ALL.iter().map(|origin| origin.label()));
+        let dropdown = gtk::DropDown::from_strings(&labels);
+        dropdown.set_tooltip_text(Some("Show the connections made by"));
+        label.set_mnemonic_widget(Some(&dropdown));
+        dropdown.connect_selected_notify(glib::clone!(
+            #[weak(rename_to = window)]
+            self,
+            move |dropdown| {
+                let origin = (dropdown.selected() as usize)
+                    .checked_sub(1)
+                    .and_then(|i| LinkOrigin::ALL.get(i).copied());
+                window.set_connections_origin(origin);
+            }
+        ));
+
+        bar.append(&label);
+        bar.append(&dropdown);
+        bar
+    }
+
+    /// List only the connections made by `origin`, or all
+    fn set_connections_origin(&self, origin: Option<LinkOrigin>) {
+        self.imp().connections_origin.set(origin);
+        if let Some(filter) = self.imp().connections_origin_filter.borrow().as_ref() {
+            filter.changed(gtk::FilterChange::Different);
+        }
+
+        let count = self
+            .imp()
+            .connections_selection
+            .borrow()
+            .as_ref()
+            .map(|s| s.n_items())
+            .unwrap_or(0);
+        let made_by = match origin {
+            None => {
+                self.announce(&format!("Showing all {} connections", count));
+                return;
+            }
+            Some(LinkOrigin::App) => "this app",
+            Some(LinkOrigin::SessionManager) => "the session manager",
+            Some(LinkOrigin::External) => "other applications",
+        };
+        match count {
+            1 => self.announce(&format!("1 connection made by {}", made_by)),
+            n => self.announce(&format!("{} connections made by {}", n, made_by)),
+        }
+    }
+}
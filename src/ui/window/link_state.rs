@@ -0,0 +1,78 @@
+use std::cell::RefCell;
+use std::rc::Rc;
+
+use adw::prelude::*;
+use gtk::glib;
+
+use crate::model::{LinkObject, LinkStateStyle};
+
+/// Add an icon showing the state of the row's link, kept up to date as the
+/// link's state changes. The state is also the row's accessible description.
+pub(super) fn attach_state_icon(list_item: &gtk::ListItem) -> gtk::Image {
+    let icon = gtk::Image::new();
+    let bound: Rc<RefCell<Option<(LinkObject, glib::SignalHandlerId)>>> = Rc::default();
+
+    list_item.connect_item_notify(glib::clone!(
+        #[weak]
+        icon,
+        move |list_item| {
+            if let Some((link, handler)) = bound.take() {
+                link.disconnect(handler);
+            }
+            let Some(link) = list_item.item().and_downcast::<LinkObject>() else {
+                return;
+            };
+            show_state(&icon, list_item, &link);
+            let handler = link.connect_state_notify(glib::clone!(
+                #[weak]
+                icon,
+                #[weak]
+                list_item,
+                move |link| show_state(&icon, &list_item, link)
+            ));
+            bound.replace(Some((link, handler)));
+        }
+    ));
+    icon
+}
+
+fn show_state(icon: &gtk::Image, list_item: &gtk::ListItem, link: &LinkObject) {
+    let style = link.state_style();
+    icon.set_icon_name(Some(style.icon_name));
+    icon.set_css_classes(&[style.css_class]);
+    icon.set_tooltip_text(Some(style.name));
+    icon.update_property(&[gtk::accessible::Property::Label(style.name)]);
+    list_item.set_accessible_description(&link.accessible_description());
+}
+
+/// A line explaining the state icons of the connections list
+pub(super) fn build_state_legend() -> gtk::Box {
+    let legend = gtk::Box::builder()
+        .orientation(gtk::Orientation::Horizontal)
+        .spacing(12)
+        .margin_start(6)
+        .margin_end(6)
+        .margin_top(4)
+        .margin_bottom(4)
+        .build();
+    for state in LinkStateStyle::STATES {
+        let style = LinkStateStyle::of(state);
+        let item = gtk::Box::builder()
+            .orientation(gtk::Orientation::Horizontal)
+            .spacing(4)
+            .build();
+        let icon = gtk::Image::builder()
+            .icon_name(style.icon_name)
+            .css_classes([style.css_class])
+            .accessible_role(gtk::AccessibleRole::Presentation)
+            .build();
+        let label = gtk::Label::builder()
+            .label(style.name)
+            .css_classes(["dim-label"])
+            .build();
+        item.append(&icon);
+        item.append(&label);
+        legend.append(&item);
+    }
+    legend
+}
@@ -0,0 +1,75 @@
+use adw::prelude::*;
+use gtk::{gio, glib};
+
+use super::Window;
+
+impl Window {
+    /// Keep a list's frame title summing it up, e.g. "Output Ports
+    /// (Sources), 42 items, filtered from 97, 3 selected", and name the list
+    /// by that title, so screen readers say which list it is and how big
+    pub(super) fn summarize_list(
+        &self,
+        frame: &gtk::Frame,
+        list_view: &gtk::ListView,
+        selection: &impl IsA<gtk::SelectionModel>,
+        all: &impl IsA<gio::ListModel>,
+        count_selected: bool,
+    ) {
+        let title = frame.label().map(|t| t.to_string()).unwrap_or_default();
+        let selection = selection.upcast_ref::<gtk::SelectionModel>().clone();
+        let all = all.upcast_ref::<gio::ListModel>().clone();
+
+        let update = glib::clone!(
+            #[weak]
+            frame,
+            #[weak]
+            selection,
+            #[weak]
+            all,
+            move || {
+                // Setting the text rather than the frame's label keeps the
+                // label widget the list is labelled by
+                if let Some(label) = frame.label_widget().and_downcast::<gtk::Label>() {
+                    label.set_text(&list_summary(&title, &selection, &all, count_selected));
+                }
+            }
+        );
+        if let Some(label) = frame.label_widget() {
+            list_view
+                .update_relation(&[gtk::accessible::Relation::LabelledBy(&[label.upcast_ref()])]);
+        }
+
+        update();
+
+        let on_items_changed = update.clone();
+        selection.connect_items_changed(move |_, _, _, _| on_items_changed());
+        if count_selected {
+            selection.connect_selection_changed(move |_, _, _| update());
+        }
+    }
+}
+
+/// "Title, 42 items, filtered from 97, 3 selected"
+fn list_summary(
+    title: &str,
+    selection: &gtk::SelectionModel,
+    all: &gio::ListModel,
+    count_selected: bool,
+) -> String {
+    let shown = selection.n_items();
+    let total = all.n_items();
+    let mut summary = match shown {
+        1 => format!("{}, 1 item", title),
+        n => format!("{}, {} items", title, n),
+    };
+    if shown != total {
+        summary.push_str(&format!(", filtered from {}", total));
+    }
+    if count_selected {
+        let selected = selection.selection().size();
+        if selected > 0 {
+            summary.push_str(&format!(", {} selected", selected));
+        }
+    }
+    summary
+}
@@ -0,0 +1,37 @@
+use adw::prelude::*;
+use adw::subclass::prelude::*;
+
+use super::Window;
+use crate::settings::ListTextSize;
+
+impl Window {
+    /// Style the lists for the configured text size. The CSS for each size
+    /// is in `ui::style`.
+    pub(super) fn apply_list_text_size(&self) {
+        let size = self.imp().settings.borrow().list_text_size;
+        self.remove_css_class("list-text-large");
+        self.remove_css_class("list-text-largest");
+        match size {
+            ListTextSize::Normal => {}
+            ListTextSize::Large => self.add_css_class("list-text-large"),
+            ListTextSize::Largest => self.add_css_class("list-text-largest"),
+        }
+    }
+
+    /// Change the text size of the lists
+    pub(super) fn set_list_text_size(&self, size: ListTextSize) {
+        self.imp().settings.borrow_mut().list_text_size = size;
+        self.apply_list_text_size();
+
+        if let Err(e) = self.imp().settings.borrow().save() {
+            self.announce(&format!("Failed to save settings: {}", e));
+            return;
+        }
+
+        self.announce(match size {
+            ListTextSize::Normal => "List text size: Normal",
+            ListTextSize::Large => "List text size: Large",
+            ListTextSize::Largest => "List text size: Largest",
+        });
+    }
+}
@@ -0,0 +1,169 @@
+use adw::prelude::*;
+use adw::subclass::prelude::*;
+use gtk::glib;
+use log::LevelFilter;
+
+use super::Window;
+use crate::logging;
+
+/// Levels offered by the log viewer, most verbose last
+const VIEWER_LEVELS: [(LevelFilter, &str); 4] = [
+    (LevelFilter::Error, "Errors"),
+    (LevelFilter::Warn, "Warnings and errors"),
+    (LevelFilter::Info, "Information"),
+    (LevelFilter::Debug, "Debug"),
+];
+
+impl Window {
+    /// Turn mirroring the log to a file on or off and save the setting
+    pub(super) fn set_log_to_file(&self, enabled: bool) {
+        if let Err(e) = logging::set_file_logging(enabled) {
+            self.announce(&e);
+            return;
+        }
+
+        self.imp().settings.borrow_mut().log_to_file = enabled;
+        if let Err(e) = self.imp().settings.borrow().save() {
+            self.announce(&format!("Failed to save settings: {}", e));
+            return;
+        }
+
+        if enabled {
+            let path = logging::log_file_path()
+                .map(|p| p.display().to_string())
+                .unwrap_or_default();
+            self.announce(&format!("Saving logs to {}", path));
+        } else {
+            self.announce("No longer saving logs to a file");
+        }
+    }
+
+    /// Show recent log messages, filtered by level
+    pub(super) fn show_log_viewer(&self) {
+        let viewer = adw::Window::builder()
+            .transient_for(&self.dialog_parent())
+            .title("Logs")
+            .default_width(800)
+            .default_height(500)
+            .build();
+
+        let content = gtk::Box::builder()
+            .orientation(gtk::Orientation::Vertical)
+            .build();
+        content.append(&adw::HeaderBar::new());
+
+        let controls = gtk::Box::builder()
+            .orientation(gtk::Orientation::Horizontal)
+            .spacing(6)
+            .margin_start(12)
+            .margin_end(12)
+            .margin_top(6)
+            .margin_bottom(6)
+            .build();
+
+        let labels: Vec<&str> = VIEWER_LEVELS.iter().map(|(_, label)| *label).collect();
+        let level_dropdown = gtk::DropDown::from_strings(&labels);
+        level_dropdown.set_selected(2);
+        level_dropdown.update_property(&[gtk::accessible::Property::Label("Show level")]);
+        controls.append(&level_dropdown);
+
+        let refresh_btn = gtk::Button::builder()
+            .label("Refresh")
+            .tooltip_text("Show the latest messages")
+            .build();
+        controls.append(&refresh_btn);
+
+        let copy_btn = gtk::Button::builder()
+            .label("Copy All")
+            .tooltip_text("Copy the shown messages to the clipboard")
+            .build();
+        controls.append(&copy_btn);
+
+        content.append(&controls);
+
+        let text_view = gtk::TextView::builder()
+            .editable(false)
+            .monospace(true)
+            .wrap_mode(gtk::WrapMode::WordChar)
+            .left_margin(12)
+            .right_margin(12)
+            .build();
+        text_view.update_property(&[gtk::accessible::Property::Label("Log messages")]);
+
+        let scrolled = gtk::ScrolledWindow::builder()
+            .hscrollbar_policy(gtk::PolicyType::Never)
+            .vscrollbar_policy(gtk::PolicyType::Automatic)
+            .vexpand(true)
+            .child(&text_view)
+            .build();
+        content.append(&scrolled);
+
+        if let Some(path) = logging::log_file_path() {
+            let file_note = if self.imp().settings.borrow().log_to_file {
+                format!("Also saved to {}", path.display())
+            } else {
+                "Turn on \"Save Logs to File\" in the Tools menu to keep logs between runs."
+                    .to_string()
+            };
+            let file_label = gtk::Label::builder()
+                .label(file_note)
+                .selectable(true)
+                .wrap(true)
+                .xalign(0.0)
+                .margin_start(12)
+                .margin_end(12)
+                .margin_top(6)
+                .margin_bottom(6)
+                .build();
+            content.append(&file_label);
+        }
+
+        viewer.set_content(Some(&content));
+
+        let fill = glib::clone!(
+            #[weak]
+            text_view,
+            #[weak]
+            level_dropdown,
+            move || {
+                let level = VIEWER_LEVELS
+                    .get(level_dropdown.selected() as usize)
+                    .map(|(level, _)| *level)
+                    .unwrap_or(LevelFilter::Info);
+                let text = logging::recent_entries(level)
+                    .iter()
+                    .map(|entry| entry.line())
+                    .collect::<Vec<_>>()
+                    .join("\n");
+                text_view.buffer().set_text(&text);
+            }
+        );
+        fill();
+
+        level_dropdown.connect_selected_notify(glib::clone!(
+            #[strong]
+            fill,
+            move |_| fill()
+        ));
+        refresh_btn.connect_clicked(glib::clone!(
+            #[strong]
+            fill,
+            move |_| fill()
+        ));
+        copy_btn.connect_clicked(glib::clone!(
+            #[weak(rename_to = window)]
+            self,
+            #[weak]
+            text_view,
+            move |_| {
+                let buffer = text_view.buffer();
+                let text = buffer.text(&buffer.start_iter(), &buffer.end_iter(), false);
+                window.clipboard().set_text(&text);
+                window.announce("Log messages copied");
+            }
+        ));
+
+        viewer.present();
+        level_dropdown.grab_focus();
+    }
+}
@@ -0,0 +1,225 @@
+use std::cell::Cell;
+use std::collections::HashMap;
+use std::rc::Rc;
+
+use adw::prelude::*;
+use adw::subclass::prelude::*;
+use gtk::glib;
+
+use super::Window;
+use crate::model::PortObject;
+
+/// More cells than this make the grid slow and hard to navigate
+const MAX_MATRIX_CELLS: usize = 4000;
+
+/// The open connection matrix, kept in step with the graph
+pub struct ConnectionMatrix {
+    window: glib::WeakRef<adw::Window>,
+    /// Check button for each (output_port_id, input_port_id) cell
+    cells: HashMap<(u32, u32), gtk::CheckButton>,
+    /// Set while cells are updated from the graph, so no links are requested
+    syncing: Rc<Cell<bool>>,
+}
+
+impl Window {
+    /// Ports currently shown in the output or input list, in list order
+    fn visible_ports(&self, is_output: bool) -> Vec<PortObject> {
+        let selection = if is_output {
+            self.imp().output_selection.borrow()
+        } else {
+            self.imp().input_selection.borrow()
+        };
+        let Some(selection) = selection.as_ref() else {
+            return Vec::new();
+        };
+        (0..selection.n_items())
+            .filter_map(|i| selection.item(i).and_downcast::<PortObject>())
+            .collect()
+    }
+
+    /// Show outputs as rows and inputs as columns, with a check box in each
+    /// cell to connect or disconnect that pair
+    pub(super) fn show_connection_matrix(&self) {
+        let outputs = self.visible_ports(true);
+        let inputs = self.visible_ports(false);
+
+        if outputs.is_empty() || inputs.is_empty() {
+            self.announce("No ports to show in the matrix");
+            return;
+        }
+        if outputs.len() * inputs.len() > MAX_MATRIX_CELLS {
+            self.announce(&format!(
+                "Too many ports for the matrix: {} outputs and {} inputs. \
+                 Use the search box or media filters to show fewer.",
+                outputs.len(),
+                inputs.len()
+            ));
+            return;
+        }
+
+        // Only one matrix at a time, reflecting the current filter
+        if let Some(old) = self.imp().connection_matrix.take() {
+            if let Some(window) = old.window.upgrade() {
+                window.close();
+            }
+        }
+
+        let viewer = adw::Window::builder()
+            .transient_for(&self.dialog_parent())
+            .title("Connection Matrix")
+            .default_width(900)
+            .default_height(600)
+            .build();
+
+        let content = gtk::Box::builder()
+            .orientation(gtk::Orientation::Vertical)
+            .build();
+        content.append(&adw::HeaderBar::new());
+
+        let note = gtk::Label::builder()
+            .label(
+                "Rows are outputs and columns are inputs. Use the arrow keys to move \
+                 and Space to connect or disconnect. Only ports matching the current \
+                 filter are shown.",
+            )
+            .wrap(true)
+            .xalign(0.0)
+            .margin_start(12)
+            .margin_end(12)
+            .margin_top(6)
+            .margin_bottom(6)
+            .build();
+        content.append(&note);
+
+        let grid = gtk::Grid::builder()
+            .row_spacing(2)
+            .column_spacing(2)
+            .margin_start(12)
+            .margin_end(12)
+            .margin_top(6)
+            .margin_bottom(12)
+            .build();
+        grid.update_property(&[gtk::accessible::Property::Label("Connection matrix")]);
+
+        for (col, input) in inputs.iter().enumerate() {
+            let header = gtk::Label::builder()
+                .label(input.display_label())
+                .wrap(true)
+                .max_width_chars(12)
+                .build();
+            grid.attach(&header, col as i32 + 1, 0, 1, 1);
+        }
+
+        let syncing = Rc::new(Cell::new(false));
+        let mut cells = HashMap::new();
+
+        for (row, output) in outputs.iter().enumerate() {
+            let header = gtk::Label::builder()
+                .label(output.display_label())
+                .xalign(0.0)
+                .build();
+            grid.attach(&header, 0, row as i32 + 1, 1, 1);
+
+            for (col, input) in inputs.iter().enumerate() {
+                let (output_port_id, input_port_id) = (output.id(), input.id());
+                let label = format!("{} to {}", output.display_label(), input.display_label());
+
+                let cell = gtk::CheckButton::builder()
+                    .halign(gtk::Align::Center)
+                    .tooltip_text(&label)
+                    .build();
+                cell.update_property(&[gtk::accessible::Property::Label(&label)]);
+                cell.connect_toggled(glib::clone!(
+                    #[weak(rename_to = window)]
+                    self,
+                    #[strong]
+                    syncing,
+                    move |cell| {
+                        if syncing.get() {
+                            return;
+                        }
+                        window.toggle_matrix_cell(output_port_id, input_port_id, cell.is_active());
+                    }
+                ));
+                grid.attach(&cell, col as i32 + 1, row as i32 + 1, 1, 1);
+                cells.insert((output_port_id, input_port_id), cell);
+            }
+        }
+
+        let scrolled = gtk::ScrolledWindow::builder()
+            .hscrollbar_policy(gtk::PolicyType::Automatic)
+            .vscrollbar_policy(gtk::PolicyType::Automatic)
+            .vexpand(true)
+            .child(&grid)
+            .build();
+        content.append(&scrolled);
+
+        viewer.set_content(Some(&content));
+
+        let first_cell = cells.get(&(outputs[0].id(), inputs[0].id())).cloned();
+        self.imp().connection_matrix.replace(Some(ConnectionMatrix {
+            window: viewer.downgrade(),
+            cells,
+            syncing,
+        }));
+        self.sync_connection_matrix();
+
+        viewer.connect_close_request(glib::clone!(
+            #[weak(rename_to = window)]
+            self,
+            #[upgrade_or]
+            glib::Propagation::Proceed,
+            move |_| {
+                window.imp().connection_matrix.replace(None);
+                glib::Propagation::Proceed
+            }
+        ));
+
+        viewer.present();
+        if let Some(cell) = first_cell {
+            cell.grab_focus();
+        }
+    }
+
+    /// Connect or disconnect a pair from the matrix
+    fn toggle_matrix_cell(&self, output_port_id: u32, input_port_id: u32, connect: bool) {
+        let existing = self
+            .imp()
+            .pw_state
+            .borrow()
+            .find_link(output_port_id, input_port_id)
+            .map(|link| link.id);
+
+        match (connect, existing) {
+            (true, None) => {
+                if !self
+                    .imp()
+                    .pending_links
+                    .borrow()
+                    .contains_key(&(output_port_id, input_port_id))
+                {
+                    self.warn_video_incompatibility(&[(output_port_id, input_port_id)]);
+                    self.create_link(output_port_id, input_port_id);
+                    self.remember_created_link(output_port_id, input_port_id);
+                }
+            }
+            (false, Some(link_id)) => self.delete_link_remembered(link_id),
+            _ => {}
+        }
+    }
+
+    /// Update the matrix check boxes from the current links
+    pub(super) fn sync_connection_matrix(&self) {
+        let matrix = self.imp().connection_matrix.borrow();
+        let Some(matrix) = matrix.as_ref() else {
+            return;
+        };
+
+        let pw_state = self.imp().pw_state.borrow();
+        matrix.syncing.set(true);
+        for (&(output_port_id, input_port_id), cell) in &matrix.cells {
+            cell.set_active(pw_state.link_exists(output_port_id, input_port_id));
+        }
+        matrix.syncing.set(false);
+    }
+}
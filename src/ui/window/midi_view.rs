@@ -0,0 +1,521 @@
+use std::collections::HashMap;
+use std::time::{Duration, Instant};
+
+use adw::prelude::*;
+use adw::subclass::prelude::*;
+use async_channel::Sender;
+use gtk::glib;
+
+use super::Window;
+use crate::pipewire::messages::MediaType;
+use crate::pipewire::midi::{self, MidiMonitor};
+use crate::pipewire::{LinkOptions, PortDirection};
+
+/// Watch at most this many MIDI outputs, each needing its own helper
+const MAX_MONITORED_PORTS: usize = 32;
+
+/// How long a port's light stays on after its last event
+const LIGHT_DURATION: Duration = Duration::from_millis(250);
+
+/// The open MIDI Ports tab, listing MIDI ports with a light showing their
+/// activity
+pub struct MidiView {
+    /// The tab, in the main window's tabs
+    pub page: adw::TabPage,
+    pub tab_view: glib::WeakRef<adw::TabView>,
+    /// Forgets the view once its tab is closed
+    pub detached_handler: Option<glib::SignalHandlerId>,
+    pub outputs: gtk::ListBox,
+    pub inputs: gtk::ListBox,
+    /// Ports of each list in row order
+    pub output_ids: Vec<u32>,
+    pub input_ids: Vec<u32>,
+    /// Activity light of each listed port
+    pub lights: HashMap<u32, gtk::Image>,
+    /// When each port last had events, to turn its light off
+    pub last_event: HashMap<u32, Instant>,
+    /// A helper watching each output, dropped to stop it
+    pub monitors: HashMap<u32, MidiMonitor>,
+    pub activity_tx: Sender<u32>,
+}
+
+impl Window {
+    /// Show the MIDI ports with ALSA sequencer style names and a light
+    /// for each that flashes when events pass through it, in a tab of the
+    /// main window
+    pub(super) fn show_midi_view(&self) {
+        if let Some(view) = self.imp().midi_view.borrow().as_ref() {
+            if let Some(tab_view) = view.tab_view.upgrade() {
+                tab_view.set_selected_page(&view.page);
+                return;
+            }
+        }
+
+        let content = gtk::Box::builder()
+            .orientation(gtk::Orientation::Vertical)
+            .spacing(6)
+            .margin_top(6)
+            .build();
+
+        let note = gtk::Label::builder()
+            .label(
+                "Each MIDI connection carries all 16 channels. A port's light flashes \
+                 when events pass through it.",
+            )
+            .xalign(0.0)
+            .wrap(true)
+            .margin_start(12)
+            .margin_end(12)
+            .build();
+        content.append(&note);
+
+        let panes = gtk::Box::builder()
+            .orientation(gtk::Orientation::Horizontal)
+            .spacing(12)
+            .homogeneous(true)
+            .margin_start(12)
+            .margin_end(12)
+            .vexpand(true)
+            .build();
+        let (output_frame, outputs) = midi_port_list("MIDI Outputs");
+        let (input_frame, inputs) = midi_port_list("MIDI Inputs");
+        panes.append(&output_frame);
+        panes.append(&input_frame);
+        content.append(&panes);
+
+        let buttons = gtk::Box::builder()
+            .orientation(gtk::Orientation::Horizontal)
+            .spacing(12)
+            .halign(gtk::Align::End)
+            .margin_start(12)
+            .margin_end(12)
+            .margin_bottom(12)
+            .build();
+        let to_inputs = gtk::Button::builder()
+            .label("Send to _Every Input")
+            .use_underline(true)
+            .tooltip_text("Connect the selected output to every MIDI input")
+            .build();
+        to_inputs.connect_clicked(glib::clone!(
+            #[weak(rename_to = window)]
+            self,
+            move |_| window.connect_midi_to_all(true)
+        ));
+        let from_outputs = gtk::Button::builder()
+            .label("Receive from Every _Output")
+            .use_underline(true)
+            .tooltip_text("Connect every MIDI output to the selected input")
+            .build();
+        from_outputs.connect_clicked(glib::clone!(
+            #[weak(rename_to = window)]
+            self,
+            move |_| window.connect_midi_to_all(false)
+        ));
+        buttons.append(&to_inputs);
+        buttons.append(&from_outputs);
+        content.append(&buttons);
+
+        // A server's window shows its tabs in the main window
+        let host = self
+            .dialog_parent()
+            .downcast::<Window>()
+            .unwrap_or_else(|_| self.clone());
+        let tab_view = host.server_tab_view();
+        // Menus and shortcuts in the tab act on this window's server
+        content.insert_action_group("win", Some(self));
+        let page = tab_view.append(&content);
+        page.set_title(&if self.is_server_window() {
+            format!("MIDI Ports on {}", self.server_name())
+        } else {
+            "MIDI Ports".to_string()
+        });
+
+        let detached_handler = tab_view.connect_page_detached(glib::clone!(
+            #[weak(rename_to = window)]
+            self,
+            move |_, detached, _| {
+                let closed = window
+                    .imp()
+                    .midi_view
+                    .borrow()
+                    .as_ref()
+                    .is_some_and(|view| view.page == *detached);
+                if closed {
+                    window.forget_midi_view();
+                }
+            }
+        ));
+
+        let (activity_tx, activity_rx) = async_channel::unbounded::<u32>();
+        self.imp().midi_view.replace(Some(MidiView {
+            page: page.clone(),
+            tab_view: tab_view.downgrade(),
+            detached_handler: Some(detached_handler),
+            outputs: outputs.clone(),
+            inputs,
+            output_ids: Vec::new(),
+            input_ids: Vec::new(),
+            lights: HashMap::new(),
+            last_event: HashMap::new(),
+            monitors: HashMap::new(),
+            activity_tx,
+        }));
+        self.fill_midi_view();
+
+        glib::spawn_future_local(glib::clone!(
+            #[weak(rename_to = window)]
+            self,
+            async move {
+                while let Ok(port_id) = activity_rx.recv().await {
+                    window.midi_activity(port_id);
+                }
+            }
+        ));
+
+        tab_view.set_selected_page(&page);
+        outputs.grab_focus();
+    }
+
+    /// Drop the MIDI view once its tab is closed, stopping its helpers
+    fn forget_midi_view(&self) {
+        let Some(mut view) = self.imp().midi_view.take() else {
+            return;
+        };
+        if let (Some(tab_view), Some(handler)) =
+            (view.tab_view.upgrade(), view.detached_handler.take())
+        {
+            tab_view.disconnect(handler);
+        }
+    }
+
+    /// Close the MIDI tab, as this window's server tab is closed
+    pub(super) fn close_midi_view(&self) {
+        let Some((tab_view, page)) = self
+            .imp()
+            .midi_view
+            .borrow()
+            .as_ref()
+            .and_then(|view| Some((view.tab_view.upgrade()?, view.page.clone())))
+        else {
+            return;
+        };
+        // Detaching the page forgets the view
+        tab_view.close_page(&page);
+    }
+
+    /// MIDI ports of one direction, by name, leaving out our monitors
+    fn midi_ports(&self, direction: PortDirection) -> Vec<(u32, String)> {
+        let mut ports: Vec<(u32, String)> = {
+            let pw_state = self.imp().pw_state.borrow();
+            pw_state
+                .ports
+                .values()
+                .filter(|port| port.media_type == MediaType::Midi && port.direction == direction)
+                .filter(|port| !self.is_midi_monitor_port(port.id))
+                .map(|port| port.id)
+                .collect::<Vec<_>>()
+        }
+        .into_iter()
+        .map(|id| (id, self.midi_port_label(id)))
+        .collect();
+        ports.sort_by(|a, b| a.1.cmp(&b.1));
+        ports
+    }
+
+    /// A MIDI port named as the ALSA sequencer does, "client:port name".
+    /// Ports of the ALSA bridge keep their sequencer numbers, as in
+    /// "14:0 Midi Through Port-0"; others are named by node and port.
+    fn midi_port_label(&self, port_id: u32) -> String {
+        let pw_state = self.imp().pw_state.borrow();
+        let Some(port) = pw_state.ports.get(&port_id) else {
+            return format!("Port {}", port_id);
+        };
+        let address = pw_state
+            .properties
+            .get(&port_id)
+            .and_then(|props| props.get("object.path"))
+            .and_then(|path| midi::alsa_seq_address(path));
+        match address {
+            Some((client, number)) => format!("{}:{} {}", client, number, port.display_name()),
+            None => {
+                let node = pw_state
+                    .nodes
+                    .get(&port.node_id)
+                    .map(|node| pw_state.node_label(node))
+                    .unwrap_or_else(|| format!("Node {}", port.node_id));
+                format!("{}:{}", node, port.display_name())
+            }
+        }
+    }
+
+    /// Whether a port belongs to one of our MIDI monitors
+    pub(super) fn is_midi_monitor_port(&self, port_id: u32) -> bool {
+        let pw_state = self.imp().pw_state.borrow();
+        pw_state
+            .get_port_node(port_id)
+            .is_some_and(|node| midi::monitored_port(&node.name).is_some())
+    }
+
+    /// Link a monitor's input to the port it watches once it appears
+    pub(super) fn midi_monitor_port_added(&self, port_id: u32, node_id: u32) {
+        let watched = self
+            .imp()
+            .pw_state
+            .borrow()
+            .nodes
+            .get(&node_id)
+            .and_then(|node| midi::monitored_port(&node.name));
+        if let Some(watched) = watched {
+            let options = LinkOptions {
+                passive: false,
+                linger: false,
+            };
+            self.create_link_with_options(watched, port_id, options);
+        }
+    }
+
+    /// Take the ports of one of our MIDI monitors out of the lists and link
+    /// them up, for ports listed before the node naming them as a monitor
+    /// arrived. Returns whether the node is a monitor.
+    pub(super) fn unlist_midi_monitor_ports(&self, node_id: u32) -> bool {
+        let ports: Vec<u32> = {
+            let pw_state = self.imp().pw_state.borrow();
+            let is_monitor = pw_state
+                .nodes
+                .get(&node_id)
+                .is_some_and(|node| midi::monitored_port(&node.name).is_some());
+            if !is_monitor {
+                return false;
+            }
+            pw_state
+                .ports
+                .values()
+                .filter(|port| port.node_id == node_id)
+                .map(|port| port.id)
+                .collect()
+        };
+
+        for port_id in ports {
+            if self.find_port_object(port_id).is_some() {
+                self.remove_port_from_lists(port_id);
+                self.midi_monitor_port_added(port_id, node_id);
+            }
+        }
+        true
+    }
+
+    /// Refill the MIDI Ports tab if MIDI ports came or went
+    pub(super) fn sync_midi_view(&self) {
+        let changed = match self.imp().midi_view.borrow().as_ref() {
+            Some(view) => {
+                let ids = |ports: Vec<(u32, String)>| -> Vec<u32> {
+                    ports.into_iter().map(|(id, _)| id).collect()
+                };
+                ids(self.midi_ports(PortDirection::Output)) != view.output_ids
+                    || ids(self.midi_ports(PortDirection::Input)) != view.input_ids
+            }
+            None => false,
+        };
+        if changed {
+            self.fill_midi_view();
+        }
+    }
+
+    /// List the MIDI ports and watch every output, keeping the helpers of
+    /// outputs already watched
+    fn fill_midi_view(&self) {
+        let outputs = self.midi_ports(PortDirection::Output);
+        let inputs = self.midi_ports(PortDirection::Input);
+        let remote = self.server();
+
+        let mut view = self.imp().midi_view.borrow_mut();
+        let Some(view) = view.as_mut() else {
+            return;
+        };
+
+        view.lights.clear();
+        for (list_box, ports) in [(&view.outputs, &outputs), (&view.inputs, &inputs)] {
+            list_box.remove_all();
+            for (id, label) in ports {
+                let (row, light) = midi_port_row(label);
+                list_box.append(&row);
+                view.lights.insert(*id, light);
+            }
+        }
+        view.output_ids = outputs.iter().map(|(id, _)| *id).collect();
+        view.input_ids = inputs.iter().map(|(id, _)| *id).collect();
+
+        view.monitors.retain(|id, _| view.output_ids.contains(id));
+        for &id in view.output_ids.iter().take(MAX_MONITORED_PORTS) {
+            if view.monitors.contains_key(&id) {
+                continue;
+            }
+            match MidiMonitor::spawn(remote.as_deref(), id, view.activity_tx.clone()) {
+                Ok(monitor) => {
+                    view.monitors.insert(id, monitor);
+                }
+                Err(e) => {
+                    log::warn!("Could not watch MIDI port {}: {}", id, e);
+                    break;
+                }
+            }
+        }
+        if view.output_ids.len() > MAX_MONITORED_PORTS {
+            log::info!(
+                "Watching the first {} of {} MIDI outputs",
+                MAX_MONITORED_PORTS,
+                view.output_ids.len()
+            );
+        }
+    }
+
+    /// Light an output that had events, and the inputs it is connected to
+    fn midi_activity(&self, port_id: u32) {
+        let mut lit = vec![port_id];
+        lit.extend(
+            self.imp()
+                .pw_state
+                .borrow()
+                .links
+                .values()
+                .filter(|link| link.output_port_id == port_id)
+                .map(|link| link.input_port_id),
+        );
+
+        let now = Instant::now();
+        {
+            let mut view = self.imp().midi_view.borrow_mut();
+            let Some(view) = view.as_mut() else {
+                return;
+            };
+            for id in &lit {
+                if let Some(light) = view.lights.get(id) {
+                    light.add_css_class("lit");
+                    view.last_event.insert(*id, now);
+                }
+            }
+        }
+
+        glib::timeout_add_local_once(
+            LIGHT_DURATION,
+            glib::clone!(
+                #[weak(rename_to = window)]
+                self,
+                move || {
+                    let view = window.imp().midi_view.borrow();
+                    let Some(view) = view.as_ref() else {
+                        return;
+                    };
+                    for id in lit {
+                        let quiet = view
+                            .last_event
+                            .get(&id)
+                            .is_some_and(|at| at.elapsed() >= LIGHT_DURATION);
+                        if let (true, Some(light)) = (quiet, view.lights.get(&id)) {
+                            light.remove_css_class("lit");
+                        }
+                    }
+                }
+            ),
+        );
+    }
+
+    /// Connect the selected MIDI output to every input, or every output to
+    /// the selected input
+    fn connect_midi_to_all(&self, from_output: bool) {
+        let (selected, others) = {
+            let view = self.imp().midi_view.borrow();
+            let Some(view) = view.as_ref() else {
+                return;
+            };
+            let (list_box, ids, others) = if from_output {
+                (&view.outputs, &view.output_ids, &view.input_ids)
+            } else {
+                (&view.inputs, &view.input_ids, &view.output_ids)
+            };
+            let selected = list_box
+                .selected_row()
+                .and_then(|row| ids.get(row.index() as usize).copied());
+            (selected, others.clone())
+        };
+        let Some(selected) = selected else {
+            self.announce(if from_output {
+                "Select a MIDI output first"
+            } else {
+                "Select a MIDI input first"
+            });
+            return;
+        };
+
+        let pairs: Vec<(u32, u32)> = {
+            let pw_state = self.imp().pw_state.borrow();
+            others
+                .into_iter()
+                .map(|other| {
+                    if from_output {
+                        (selected, other)
+                    } else {
+                        (other, selected)
+                    }
+                })
+                .filter(|&(output, input)| !pw_state.link_exists(output, input))
+                .collect()
+        };
+        for &(output, input) in &pairs {
+            self.create_link(output, input);
+        }
+
+        let label = self.midi_port_label(selected);
+        let message = match (pairs.len(), from_output) {
+            (0, _) => format!("{} is already connected to every MIDI port", label),
+            (1, true) => format!("Connecting {} to 1 MIDI input", label),
+            (n, true) => format!("Connecting {} to {} MIDI inputs", label, n),
+            (1, false) => format!("Connecting 1 MIDI output to {}", label),
+            (n, false) => format!("Connecting {} MIDI outputs to {}", n, label),
+        };
+        self.record_activity(&message);
+        self.announce(&message);
+    }
+}
+
+/// A titled list of MIDI ports
+fn midi_port_list(title: &str) -> (gtk::Frame, gtk::ListBox) {
+    let list_box = gtk::ListBox::builder()
+        .selection_mode(gtk::SelectionMode::Single)
+        .build();
+    list_box.update_property(&[gtk::accessible::Property::Label(title)]);
+    let scrolled = gtk::ScrolledWindow::builder()
+        .hscrollbar_policy(gtk::PolicyType::Never)
+        .vexpand(true)
+        .child(&list_box)
+        .build();
+    let frame = gtk::Frame::builder().label(title).child(&scrolled).build();
+    (frame, list_box)
+}
+
+/// A row naming a MIDI port, with its activity light
+fn midi_port_row(label: &str) -> (gtk::ListBoxRow, gtk::Image) {
+    let light = gtk::Image::builder()
+        .icon_name("media-record-symbolic")
+        .css_classes(["midi-activity"])
+        .accessible_role(gtk::AccessibleRole::Presentation)
+        .build();
+    let text = gtk::Label::builder()
+        .label(label)
+        .xalign(0.0)
+        .hexpand(true)
+        .build();
+    let row_box = gtk::Box::builder()
+        .orientation(gtk::Orientation::Horizontal)
+        .spacing(6)
+        .margin_start(6)
+        .margin_end(6)
+        .margin_top(4)
+        .margin_bottom(4)
+        .build();
+    row_box.append(&light);
+    row_box.append(&text);
+    let row = gtk::ListBoxRow::builder().child(&row_box).build();
+    row.update_property(&[gtk::accessible::Property::Label(label)]);
+    (row, light)
+}
@@ -0,0 +1,43 @@
+use adw::prelude::*;
+use adw::subclass::prelude::*;
+use gtk::gio;
+
+use super::Window;
+
+impl Window {
+    /// Turn animations off for the display when motion is reduced. GTK and
+    /// libadwaita skip every transition then: toasts, revealed panes and
+    /// stack pages appear at once. Otherwise the desktop's own preference
+    /// applies, which GTK reads itself.
+    pub(super) fn apply_reduce_motion(&self) {
+        let gtk_settings = gtk::Settings::for_display(&self.display());
+        if self.imp().settings.borrow().reduce_motion {
+            gtk_settings.set_gtk_enable_animations(false);
+        } else {
+            gtk_settings.reset_property("gtk-enable-animations");
+        }
+    }
+
+    /// Turn animations off or back to the desktop's preference
+    pub(super) fn set_reduce_motion(&self, reduce: bool) {
+        self.imp().settings.borrow_mut().reduce_motion = reduce;
+        if let Some(action) = self
+            .lookup_action("reduce-motion")
+            .and_downcast::<gio::SimpleAction>()
+        {
+            action.set_state(&reduce.to_variant());
+        }
+        self.apply_reduce_motion();
+
+        if let Err(e) = self.imp().settings.borrow().save() {
+            self.announce(&format!("Failed to save settings: {}", e));
+            return;
+        }
+
+        if reduce {
+            self.announce("Animations off");
+        } else {
+            self.announce("Animations follow the desktop setting");
+        }
+    }
+}
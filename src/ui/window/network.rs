@@ -0,0 +1,512 @@
+use std::time::Duration;
+
+use adw::prelude::*;
+use adw::subclass::prelude::*;
+use gtk::glib;
+
+use super::virtual_devices::HostedModule;
+use super::Window;
+use crate::pipewire::modules;
+use crate::pipewire::network;
+
+/// Delay between the first port of a new node appearing and routing to it,
+/// so all of its channels exist when ports are paired
+const ROUTE_SETTLE_DELAY: Duration = Duration::from_millis(300);
+
+/// Sources waiting for a node created by a helper to appear
+#[derive(Debug, Clone)]
+pub struct PendingRoute {
+    /// Name of the node to route into
+    pub node_name: String,
+    /// Nodes whose outputs get connected to it
+    pub source_node_ids: Vec<u32>,
+}
+
+impl Window {
+    /// Connect `source_node_ids` to a node as soon as it appears
+    pub(super) fn queue_route(&self, node_name: String, source_node_ids: Vec<u32>) {
+        self.imp().pending_routes.borrow_mut().push(PendingRoute {
+            node_name,
+            source_node_ids,
+        });
+    }
+
+    /// Route queued sources into `node_id` if it is awaited.
+    /// Called whenever a port is added.
+    pub(super) fn check_pending_routes(&self, node_id: u32) {
+        let node_name = match self.imp().pw_state.borrow().nodes.get(&node_id) {
+            Some(node) => node.name.clone(),
+            None => return,
+        };
+
+        let route = {
+            let mut pending = self.imp().pending_routes.borrow_mut();
+            let index = pending.iter().position(|r| r.node_name == node_name);
+            index.map(|i| pending.remove(i))
+        };
+
+        if let Some(route) = route {
+            glib::timeout_add_local_once(
+                ROUTE_SETTLE_DELAY,
+                glib::clone!(
+                    #[weak(rename_to = window)]
+                    self,
+                    move || {
+                        let count: usize = route
+                            .source_node_ids
+                            .iter()
+                            .map(|&source| window.connect_nodes(source, node_id))
+                            .sum();
+                        log::debug!("Routed {} links into {}", count, route.node_name);
+                    }
+                ),
+            );
+        }
+    }
+
+    /// Node ids of every node with a selected output port
+    pub(super) fn selected_output_nodes(&self) -> Vec<u32> {
+        let mut node_ids: Vec<u32> = self
+            .selected_ports(true)
+            .iter()
+            .map(|p| p.node_id())
+            .collect();
+        node_ids.sort_unstable();
+        node_ids.dedup();
+        node_ids
+    }
+
+    /// Show a dialog to receive RTP streams from the network
+    pub(super) fn show_receive_network_audio_dialog(&self) {
+        let dialog = adw::MessageDialog::builder()
+            .transient_for(&self.dialog_parent())
+            .modal(true)
+            .heading("Receive Network Audio")
+            .body(
+                "Received streams appear as sources in the output list. \
+                 Discovery picks up every stream announced with SAP; \
+                 otherwise enter the address the stream is sent to.",
+            )
+            .build();
+
+        let content = gtk::Box::builder()
+            .orientation(gtk::Orientation::Vertical)
+            .spacing(12)
+            .build();
+
+        let mode = gtk::DropDown::from_strings(&[
+            "Discover announced streams (SAP)",
+            "Receive from a specific address",
+        ]);
+        mode.update_property(&[gtk::accessible::Property::Label("Receive mode")]);
+        content.append(&mode);
+
+        let name_entry = gtk::Entry::builder()
+            .text("Network Audio")
+            .activates_default(true)
+            .sensitive(false)
+            .build();
+        name_entry.update_property(&[gtk::accessible::Property::Label("Source name")]);
+        content.append(&name_entry);
+
+        let address_entry = gtk::Entry::builder()
+            .text(network::SAP_DEFAULT_ADDRESS)
+            .activates_default(true)
+            .build();
+        address_entry.update_property(&[gtk::accessible::Property::Label(
+            "Multicast or listen address",
+        )]);
+        content.append(&address_entry);
+
+        let port_spin = gtk::SpinButton::with_range(1.0, 65535.0, 1.0);
+        port_spin.set_value(network::SAP_DEFAULT_PORT as f64);
+        port_spin.update_property(&[gtk::accessible::Property::Label("Port")]);
+        content.append(&port_spin);
+
+        // Switch defaults when the mode changes
+        mode.connect_selected_notify(glib::clone!(
+            #[weak]
+            name_entry,
+            #[weak]
+            address_entry,
+            #[weak]
+            port_spin,
+            move |mode| {
+                let manual = mode.selected() == 1;
+                name_entry.set_sensitive(manual);
+                if manual {
+                    address_entry.set_text("0.0.0.0");
+                    port_spin.set_value(network::RTP_DEFAULT_PORT as f64);
+                } else {
+                    address_entry.set_text(network::SAP_DEFAULT_ADDRESS);
+                    port_spin.set_value(network::SAP_DEFAULT_PORT as f64);
+                }
+            }
+        ));
+
+        dialog.set_extra_child(Some(&content));
+
+        dialog.add_response("cancel", "Cancel");
+        dialog.add_response("receive", "Receive");
+        dialog.set_response_appearance("receive", adw::ResponseAppearance::Suggested);
+        dialog.set_default_response(Some("receive"));
+        dialog.set_close_response("cancel");
+
+        dialog.connect_response(
+            None,
+            glib::clone!(
+                #[weak(rename_to = window)]
+                self,
+                #[weak]
+                mode,
+                #[weak]
+                name_entry,
+                #[weak]
+                address_entry,
+                #[weak]
+                port_spin,
+                move |dialog, response| {
+                    dialog.close();
+                    if response != "receive" {
+                        return;
+                    }
+
+                    let address = address_entry.text().trim().to_string();
+                    if address.is_empty() {
+                        window.announce("Address cannot be empty");
+                        return;
+                    }
+                    let port = port_spin.value() as u16;
+
+                    if mode.selected() == 1 {
+                        let description = modules::sanitize_description(&name_entry.text());
+                        if description.is_empty() {
+                            window.announce("Source name cannot be empty");
+                            return;
+                        }
+                        window.receive_rtp_stream(&description, &address, port);
+                    } else {
+                        window.start_sap_discovery(&address, port);
+                    }
+                }
+            ),
+        );
+
+        dialog.present();
+        mode.grab_focus();
+    }
+
+    /// Listen for SAP announcements and receive every announced stream
+    fn start_sap_discovery(&self, address: &str, port: u16) {
+        let key = network::network_key("sap", self.next_helper_instance());
+        self.start_hosted_module(
+            HostedModule {
+                key,
+                description: format!("Network stream discovery on {}", address),
+                kind: "Network audio receiver".into(),
+            },
+            network::RTP_SAP_MODULE,
+            network::rtp_sap_args(address, port),
+        );
+        self.announce("Listening for announced network streams");
+    }
+
+    /// Receive a single RTP stream as a new source
+    fn receive_rtp_stream(&self, description: &str, address: &str, port: u16) {
+        let key = network::network_key("rtp", self.next_helper_instance());
+        let args = network::rtp_source_args(&key, description, address, port);
+        self.start_hosted_module(
+            HostedModule {
+                key,
+                description: description.to_string(),
+                kind: format!("Network audio from {}:{}", address, port),
+            },
+            network::RTP_SOURCE_MODULE,
+            args,
+        );
+        self.announce(&format!("Receiving network audio as \"{}\"", description));
+    }
+
+    /// Whether AirPlay discovery is running in a helper
+    fn airplay_discovery_running(&self) -> bool {
+        let prefix = network::network_key_prefix("raop");
+        self.imp()
+            .hosted_modules
+            .borrow()
+            .iter()
+            .any(|m| m.key.starts_with(&prefix))
+    }
+
+    /// Start discovering AirPlay speakers
+    fn start_airplay_discovery(&self) {
+        let key = network::network_key("raop", self.next_helper_instance());
+        self.start_hosted_module(
+            HostedModule {
+                key,
+                description: "AirPlay speaker discovery".into(),
+                kind: "AirPlay speakers on the local network".into(),
+            },
+            network::RAOP_DISCOVER_MODULE,
+            network::raop_discover_args(),
+        );
+    }
+
+    /// Show discovered AirPlay speakers and route the selected outputs to one
+    pub(super) fn show_airplay_dialog(&self) {
+        if !self.airplay_discovery_running() {
+            self.start_airplay_discovery();
+        }
+
+        // (node id, display name) of every AirPlay sink seen so far
+        let mut speakers: Vec<(u32, String)> = {
+            let pw_state = self.imp().pw_state.borrow();
+            pw_state
+                .nodes
+                .values()
+                .filter(|n| network::is_raop_sink(&n.name))
+                .map(|n| (n.id, n.display_name().to_string()))
+                .collect()
+        };
+        speakers.sort_by(|a, b| a.1.cmp(&b.1));
+
+        if speakers.is_empty() {
+            self.announce(
+                "Searching for AirPlay speakers. Open this dialog again in a few seconds.",
+            );
+            return;
+        }
+
+        let dialog = adw::MessageDialog::builder()
+            .transient_for(&self.dialog_parent())
+            .modal(true)
+            .heading("AirPlay Speakers")
+            .body(
+                "Choose a speaker. \"Route Selected\" connects the applications \
+                 whose ports are selected in the output list.",
+            )
+            .build();
+
+        let list_box = gtk::ListBox::builder()
+            .selection_mode(gtk::SelectionMode::Single)
+            .css_classes(["boxed-list"])
+            .build();
+
+        for (_, name) in &speakers {
+            let row = adw::ActionRow::builder()
+                .title(name)
+                .activatable(true)
+                .build();
+            list_box.append(&row);
+        }
+
+        if let Some(first_row) = list_box.row_at_index(0) {
+            list_box.select_row(Some(&first_row));
+        }
+
+        dialog.set_extra_child(Some(&list_box));
+
+        dialog.add_response("cancel", "Close");
+        dialog.add_response("route", "Route Selected");
+        dialog.set_response_appearance("route", adw::ResponseAppearance::Suggested);
+        dialog.set_default_response(Some("route"));
+        dialog.set_close_response("cancel");
+
+        let dialog_weak = dialog.downgrade();
+        list_box.connect_row_activated(move |_, _| {
+            if let Some(dialog) = dialog_weak.upgrade() {
+                dialog.response("route");
+            }
+        });
+
+        dialog.connect_response(
+            None,
+            glib::clone!(
+                #[weak(rename_to = window)]
+                self,
+                #[weak]
+                list_box,
+                move |dialog, response| {
+                    dialog.close();
+                    if response != "route" {
+                        return;
+                    }
+
+                    let speaker = list_box
+                        .selected_row()
+                        .and_then(|row| speakers.get(row.index() as usize).cloned());
+                    if let Some((node_id, name)) = speaker {
+                        window.route_selected_outputs_to(node_id, &name);
+                    }
+                }
+            ),
+        );
+
+        dialog.present();
+        list_box.grab_focus();
+    }
+
+    /// Connect every node with a selected output port to `sink_node_id`
+    fn route_selected_outputs_to(&self, sink_node_id: u32, sink_name: &str) {
+        let node_ids = self.selected_output_nodes();
+
+        if node_ids.is_empty() {
+            self.announce("Select an application's ports in the output list first");
+            return;
+        }
+
+        let count: usize = node_ids
+            .iter()
+            .map(|&node_id| self.connect_nodes(node_id, sink_node_id))
+            .sum();
+
+        self.announce(&format!(
+            "Routed {} application{} to {} ({} connections)",
+            node_ids.len(),
+            if node_ids.len() == 1 { "" } else { "s" },
+            sink_name,
+            count
+        ));
+    }
+
+    /// Show a dialog to send or receive VBAN streams
+    pub(super) fn show_vban_dialog(&self) {
+        let dialog = adw::MessageDialog::builder()
+            .transient_for(&self.dialog_parent())
+            .modal(true)
+            .heading("VBAN Streaming")
+            .body(
+                "Send streams the applications selected in the output list to a VBAN \
+                 receiver such as Voicemeeter. Receive creates a source for incoming streams.",
+            )
+            .build();
+
+        let content = gtk::Box::builder()
+            .orientation(gtk::Orientation::Vertical)
+            .spacing(12)
+            .build();
+
+        let mode = gtk::DropDown::from_strings(&["Send to a VBAN receiver", "Receive VBAN streams"]);
+        mode.update_property(&[gtk::accessible::Property::Label("Direction")]);
+        content.append(&mode);
+
+        let address_entry = gtk::Entry::builder()
+            .placeholder_text("Receiver address, e.g. 192.168.1.20")
+            .activates_default(true)
+            .build();
+        address_entry.update_property(&[gtk::accessible::Property::Label("Receiver address")]);
+        content.append(&address_entry);
+
+        let stream_entry = gtk::Entry::builder()
+            .text("Stream1")
+            .max_length(16)
+            .activates_default(true)
+            .build();
+        stream_entry.update_property(&[gtk::accessible::Property::Label("VBAN stream name")]);
+        content.append(&stream_entry);
+
+        let port_spin = gtk::SpinButton::with_range(1.0, 65535.0, 1.0);
+        port_spin.set_value(network::VBAN_DEFAULT_PORT as f64);
+        port_spin.update_property(&[gtk::accessible::Property::Label("UDP port")]);
+        content.append(&port_spin);
+
+        mode.connect_selected_notify(glib::clone!(
+            #[weak]
+            address_entry,
+            #[weak]
+            stream_entry,
+            move |mode| {
+                let send = mode.selected() == 0;
+                address_entry.set_sensitive(send);
+                stream_entry.set_sensitive(send);
+            }
+        ));
+
+        dialog.set_extra_child(Some(&content));
+
+        dialog.add_response("cancel", "Cancel");
+        dialog.add_response("start", "Start");
+        dialog.set_response_appearance("start", adw::ResponseAppearance::Suggested);
+        dialog.set_default_response(Some("start"));
+        dialog.set_close_response("cancel");
+
+        dialog.connect_response(
+            None,
+            glib::clone!(
+                #[weak(rename_to = window)]
+                self,
+                #[weak]
+                mode,
+                #[weak]
+                address_entry,
+                #[weak]
+                stream_entry,
+                #[weak]
+                port_spin,
+                move |dialog, response| {
+                    dialog.close();
+                    if response != "start" {
+                        return;
+                    }
+
+                    let port = port_spin.value() as u16;
+                    if mode.selected() == 1 {
+                        window.start_vban_receive(port);
+                        return;
+                    }
+
+                    let address = address_entry.text().trim().to_string();
+                    let stream_name = modules::sanitize_description(&stream_entry.text());
+                    if address.is_empty() || stream_name.is_empty() {
+                        window.announce("Receiver address and stream name are required");
+                        return;
+                    }
+                    window.start_vban_send(&address, &stream_name, port);
+                }
+            ),
+        );
+
+        dialog.present();
+        mode.grab_focus();
+    }
+
+    /// Create a VBAN sender and route the selected applications into it
+    fn start_vban_send(&self, address: &str, stream_name: &str, port: u16) {
+        let key = network::network_key("vban_send", self.next_helper_instance());
+        let description = format!("VBAN {} to {}", stream_name, address);
+        let args = network::vban_send_args(&key, &description, stream_name, address, port);
+
+        let sources = self.selected_output_nodes();
+        if !sources.is_empty() {
+            self.queue_route(key.clone(), sources);
+        }
+
+        self.start_hosted_module(
+            HostedModule {
+                key,
+                description: description.clone(),
+                kind: format!("VBAN sender to {}:{}", address, port),
+            },
+            network::VBAN_SEND_MODULE,
+            args,
+        );
+        self.announce(&format!("Starting {}", description));
+    }
+
+    /// Create a source for incoming VBAN streams
+    fn start_vban_receive(&self, port: u16) {
+        let key = network::network_key("vban_recv", self.next_helper_instance());
+        let description = format!("VBAN on port {}", port);
+        let args = network::vban_recv_args(&key, &description, port);
+
+        self.start_hosted_module(
+            HostedModule {
+                key,
+                description: description.clone(),
+                kind: "VBAN receiver".into(),
+            },
+            network::VBAN_RECV_MODULE,
+            args,
+        );
+        self.announce(&format!("Receiving {}", description));
+    }
+}
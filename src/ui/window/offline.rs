@@ -0,0 +1,128 @@
+use adw::prelude::*;
+use adw::subclass::prelude::*;
+use gtk::glib;
+
+use super::Window;
+use crate::pipewire::health;
+use crate::pipewire::state::PwState;
+
+impl Window {
+    /// Build the page shown instead of the port lists while PipeWire
+    /// can't be reached
+    pub(super) fn build_offline_page(&self) -> adw::StatusPage {
+        let retry = gtk::Button::builder()
+            .label("Retry")
+            .css_classes(["pill", "suggested-action"])
+            .build();
+        retry.connect_clicked(glib::clone!(
+            #[weak(rename_to = window)]
+            self,
+            move |_| window.retry_connection()
+        ));
+
+        let troubleshoot = gtk::Button::builder()
+            .label("Troubleshooting")
+            .css_classes(["pill"])
+            .build();
+        troubleshoot.connect_clicked(glib::clone!(
+            #[weak(rename_to = window)]
+            self,
+            move |_| window.show_troubleshooting_dialog()
+        ));
+
+        let buttons = gtk::Box::builder()
+            .orientation(gtk::Orientation::Horizontal)
+            .spacing(12)
+            .halign(gtk::Align::Center)
+            .build();
+        buttons.append(&retry);
+        buttons.append(&troubleshoot);
+
+        let page = adw::StatusPage::builder()
+            .icon_name("audio-volume-muted-symbolic")
+            .title("PipeWire Is Not Running")
+            .child(&buttons)
+            .vexpand(true)
+            .build();
+
+        self.imp().offline_page.replace(Some(page.clone()));
+        self.imp().offline_retry_button.replace(Some(retry));
+        page
+    }
+
+    /// Replace the port lists with the status page, saying why PipeWire
+    /// can't be reached and what may fix it
+    pub(super) fn show_offline_page(&self, reason: &str) {
+        self.clear_graph();
+
+        let mut description = format!("Could not connect to PipeWire: {}.", reason);
+        match health::diagnose_connection_failure().first() {
+            Some(problem) => {
+                description.push_str(&format!("\n\n{} {}", problem.summary, problem.hint));
+            }
+            None => description.push_str(
+                "\n\nStart it with \"systemctl --user start pipewire wireplumber\", then choose Retry.",
+            ),
+        }
+        if let Some(page) = self.imp().offline_page.borrow().as_ref() {
+            page.set_description(Some(&glib::markup_escape_text(&description)));
+        }
+        if let Some(stack) = self.imp().graph_stack.borrow().as_ref() {
+            stack.set_visible_child_name("offline");
+        }
+
+        self.announce_error(&format!("Disconnected from PipeWire: {}", reason));
+        if let Some(retry) = self.imp().offline_retry_button.borrow().as_ref() {
+            retry.set_sensitive(true);
+            retry.grab_focus();
+        }
+    }
+
+    /// Show the port lists again once connected
+    pub(super) fn hide_offline_page(&self) {
+        let Some(stack) = self.imp().graph_stack.borrow().clone() else {
+            return;
+        };
+        if stack.visible_child_name().as_deref() != Some("offline") {
+            return;
+        }
+        stack.set_visible_child_name("graph");
+        self.announce("Connected to PipeWire");
+        self.focus_output_list();
+    }
+
+    /// Start a new connection in place of the one that failed
+    fn retry_connection(&self) {
+        self.update_status("Connecting to PipeWire...", true);
+        if let Some(retry) = self.imp().offline_retry_button.borrow().as_ref() {
+            retry.set_sensitive(false);
+        }
+        self.announce("Connecting to PipeWire");
+
+        let server = self.server().unwrap_or_default();
+        if let Err(e) = self.activate_action("app.reconnect", Some(&server.to_variant())) {
+            log::warn!("Failed to reconnect: {}", e);
+        }
+    }
+
+    /// Forget the nodes, ports and links of a connection that is gone, so
+    /// a new connection starts from an empty graph
+    fn clear_graph(&self) {
+        let imp = self.imp();
+        imp.pw_state.replace(PwState::default());
+        imp.output_ports.remove_all();
+        imp.input_ports.remove_all();
+        imp.pending_ports.borrow_mut().clear();
+        imp.links.remove_all();
+        imp.link_objects.borrow_mut().clear();
+        imp.pending_links.borrow_mut().clear();
+        imp.toast_link_requests.borrow_mut().clear();
+        imp.created_links.borrow_mut().clear();
+        imp.deleting_links.borrow_mut().clear();
+        imp.delete_link_requests.borrow_mut().clear();
+        imp.queued_commands.borrow_mut().clear();
+        imp.command_backlog_warned.set(false);
+        imp.conflict_warned_nodes.borrow_mut().clear();
+        imp.own_links.borrow_mut().clear();
+    }
+}
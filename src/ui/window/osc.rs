@@ -0,0 +1,276 @@
+use std::net::SocketAddr;
+
+use adw::prelude::*;
+use adw::subclass::prelude::*;
+use gtk::{gio, glib};
+
+use super::Window;
+use crate::osc::{self, OscArg, OscMessage};
+
+impl Window {
+    /// Start or stop the OSC server and save the setting
+    pub(super) fn set_osc_server(&self, enabled: bool) {
+        if enabled {
+            if let Err(e) = self.start_osc_server() {
+                let message = format!("Failed to start the OSC server: {}", e);
+                self.set_osc_server_state(false);
+                self.show_toast(&message);
+                self.announce_error(&message);
+                return;
+            }
+        } else {
+            self.imp().osc_server.replace(None);
+        }
+
+        self.imp().settings.borrow_mut().osc_server = enabled;
+        if let Err(e) = self.imp().settings.borrow().save() {
+            self.announce(&format!("Failed to save settings: {}", e));
+            return;
+        }
+
+        match self.imp().osc_server.borrow().as_ref() {
+            Some(server) => self.announce(&format!(
+                "OSC server listening on UDP port {}",
+                server.port()
+            )),
+            None => self.announce("OSC server stopped"),
+        }
+    }
+
+    fn set_osc_server_state(&self, enabled: bool) {
+        if let Some(action) = self
+            .lookup_action("osc-server")
+            .and_downcast::<gio::SimpleAction>()
+        {
+            action.set_state(&enabled.to_variant());
+        }
+    }
+
+    /// Listen for OSC messages on the configured port, handling them on the
+    /// main loop
+    pub(super) fn start_osc_server(&self) -> Result<(), anyhow::Error> {
+        if self.imp().osc_server.borrow().is_some() {
+            return Ok(());
+        }
+
+        let (port, listen_all) = {
+            let settings = self.imp().settings.borrow();
+            (settings.osc_port, settings.osc_listen_all)
+        };
+        let (message_tx, message_rx) = async_channel::unbounded::<(OscMessage, SocketAddr)>();
+        let server = osc::spawn(port, listen_all, message_tx)?;
+        self.imp().osc_server.replace(Some(server));
+
+        glib::spawn_future_local(glib::clone!(
+            #[weak(rename_to = window)]
+            self,
+            async move {
+                while let Ok((message, sender)) = message_rx.recv().await {
+                    if let Err(e) = window.handle_osc_message(&message) {
+                        log::warn!("OSC {} from {}: {}", message.address, sender, e);
+                        window.announce_verbose(&format!("OSC: {}", e));
+                    }
+                }
+            }
+        ));
+        Ok(())
+    }
+
+    /// Show a dialog to change the port the OSC server listens on and
+    /// whether other computers may send to it
+    pub(super) fn show_osc_port_dialog(&self) {
+        let dialog = adw::MessageDialog::builder()
+            .transient_for(&self.dialog_parent())
+            .modal(true)
+            .heading("OSC Port")
+            .body(
+                "The UDP port touch surfaces send OSC messages to. Anyone who can \
+                 reach it can change your routing, so only accept messages from \
+                 other devices on networks you trust.",
+            )
+            .build();
+
+        let port_spin = gtk::SpinButton::with_range(1024.0, 65535.0, 1.0);
+        port_spin.set_value(self.imp().settings.borrow().osc_port as f64);
+        port_spin.set_activates_default(true);
+        port_spin.update_property(&[gtk::accessible::Property::Label("Port")]);
+        let listen_all_check = gtk::CheckButton::builder()
+            .label("Accept messages from other devices")
+            .active(self.imp().settings.borrow().osc_listen_all)
+            .build();
+        let content = gtk::Box::builder()
+            .orientation(gtk::Orientation::Vertical)
+            .spacing(12)
+            .build();
+        content.append(&port_spin);
+        content.append(&listen_all_check);
+        dialog.set_extra_child(Some(&content));
+
+        dialog.add_response("cancel", "Cancel");
+        dialog.add_response("save", "Save");
+        dialog.set_response_appearance("save", adw::ResponseAppearance::Suggested);
+        dialog.set_default_response(Some("save"));
+        dialog.set_close_response("cancel");
+
+        dialog.connect_response(
+            None,
+            glib::clone!(
+                #[weak(rename_to = window)]
+                self,
+                #[weak]
+                port_spin,
+                #[weak]
+                listen_all_check,
+                move |dialog, response| {
+                    dialog.close();
+                    if response == "save" {
+                        window.set_osc_port(port_spin.value() as u16, listen_all_check.is_active());
+                    }
+                }
+            ),
+        );
+
+        dialog.present();
+        port_spin.grab_focus();
+    }
+
+    /// Save a new OSC port and whether other computers may send to it,
+    /// restarting the server if it is running
+    fn set_osc_port(&self, port: u16, listen_all: bool) {
+        {
+            let mut settings = self.imp().settings.borrow_mut();
+            if settings.osc_port == port && settings.osc_listen_all == listen_all {
+                return;
+            }
+            settings.osc_port = port;
+            settings.osc_listen_all = listen_all;
+        }
+        if let Err(e) = self.imp().settings.borrow().save() {
+            self.announce(&format!("Failed to save settings: {}", e));
+            return;
+        }
+
+        let from = if listen_all {
+            "from any device"
+        } else {
+            "from this computer only"
+        };
+        if self.imp().osc_server.replace(None).is_none() {
+            self.announce(&format!("OSC port set to {}, {}", port, from));
+            return;
+        }
+        match self.start_osc_server() {
+            Ok(()) => self.announce(&format!(
+                "OSC server listening on UDP port {}, {}",
+                port, from
+            )),
+            Err(e) => {
+                let message = format!("Failed to start the OSC server: {}", e);
+                self.set_osc_server_state(false);
+                self.imp().settings.borrow_mut().osc_server = false;
+                let _ = self.imp().settings.borrow().save();
+                self.show_toast(&message);
+                self.announce_error(&message);
+            }
+        }
+    }
+
+    /// Carry out one OSC message. Buttons send 1 when pressed and 0 when
+    /// released, so messages whose only argument is 0 are ignored.
+    fn handle_osc_message(&self, message: &OscMessage) -> Result<(), String> {
+        let args = &message.args;
+        if args.len() == 1 && args[0].is_release() {
+            return Ok(());
+        }
+
+        // The preset name may be part of the address, for one button per preset
+        if let Some(name) = message.address.strip_prefix("/preset/activate/") {
+            return self.osc_activate_preset(name);
+        }
+
+        match message.address.as_str() {
+            "/preset/activate" => {
+                let name = args
+                    .first()
+                    .and_then(OscArg::as_str)
+                    .ok_or("/preset/activate needs a preset name")?;
+                self.osc_activate_preset(name)
+            }
+            "/preset/deactivate" => {
+                if self.imp().preset_store.borrow().active_preset.is_some() {
+                    self.deactivate_preset();
+                }
+                Ok(())
+            }
+            "/connect" => {
+                let (output, input) = self.osc_port_pair(message)?;
+                self.control_connect(output, input)
+                    .map(|_| ())
+                    .map_err(|e| e.message)
+            }
+            "/disconnect" => {
+                let (output, input) = self.osc_port_pair(message)?;
+                let link_id = self
+                    .imp()
+                    .pw_state
+                    .borrow()
+                    .find_link(output, input)
+                    .map(|link| link.id)
+                    .ok_or("The ports are not connected")?;
+                self.delete_link_remembered(link_id);
+                Ok(())
+            }
+            // Toggle buttons send 1 and 0 to the same address
+            "/link" => {
+                let (output, input) = self.osc_port_pair(message)?;
+                let on = !args.get(2).is_some_and(OscArg::is_release);
+                let link_id = self
+                    .imp()
+                    .pw_state
+                    .borrow()
+                    .find_link(output, input)
+                    .map(|link| link.id);
+                match (on, link_id) {
+                    (true, None) => self
+                        .control_connect(output, input)
+                        .map(|_| ())
+                        .map_err(|e| e.message),
+                    (false, Some(link_id)) => {
+                        self.delete_link_remembered(link_id);
+                        Ok(())
+                    }
+                    _ => Ok(()),
+                }
+            }
+            address => Err(format!("Unknown address {}", address)),
+        }
+    }
+
+    fn osc_activate_preset(&self, name: &str) -> Result<(), String> {
+        if self.imp().preset_store.borrow().get_preset(name).is_none() {
+            return Err(format!("No preset named \"{}\"", name));
+        }
+        self.activate_preset(name);
+        Ok(())
+    }
+
+    /// The output and input port named by the first two arguments, each a
+    /// port id or a "node.name:port.name" path as `pw-link` shows them
+    fn osc_port_pair(&self, message: &OscMessage) -> Result<(u32, u32), String> {
+        let pw_state = self.imp().pw_state.borrow();
+        let resolve = |arg: Option<&OscArg>| -> Option<u32> {
+            let arg = arg?;
+            if let Some(id) = arg.as_u32() {
+                return Some(id);
+            }
+            let path = arg.as_str()?;
+            path.parse().ok().or_else(|| pw_state.find_port_by_path(path))
+        };
+
+        let output = resolve(message.args.first())
+            .ok_or_else(|| format!("{} needs an output port", message.address))?;
+        let input = resolve(message.args.get(1))
+            .ok_or_else(|| format!("{} needs an input port", message.address))?;
+        Ok((output, input))
+    }
+}
@@ -0,0 +1,223 @@
+use adw::prelude::*;
+use adw::subclass::prelude::*;
+use gtk::{gio, glib};
+use std::time::Duration;
+
+use super::Window;
+
+/// Smallest share of the space a port list can be given, in percent
+const MIN_SPLIT: u32 = 10;
+const MAX_SPLIT: u32 = 90;
+
+/// Wait after the split stops moving before saving it, so dragging the
+/// handle doesn't write the settings at every step
+const SPLIT_SAVE_DELAY: Duration = Duration::from_millis(500);
+
+impl Window {
+    /// Put the port lists in a pane whose handle moves the split between
+    /// them. F8 focuses the handle, then arrow keys move it.
+    pub(super) fn build_port_panes(
+        &self,
+        output_panel: gtk::Frame,
+        input_panel: gtk::Frame,
+    ) -> gtk::Paned {
+        let paned = gtk::Paned::builder()
+            .wide_handle(true)
+            .resize_start_child(true)
+            .resize_end_child(true)
+            .shrink_start_child(false)
+            .shrink_end_child(false)
+            .margin_start(12)
+            .margin_end(12)
+            .margin_top(6)
+            .margin_bottom(6)
+            .vexpand(true)
+            .build();
+        paned.update_property(&[gtk::accessible::Property::Label("Port lists")]);
+
+        // Keep the share of each list as the window is resized
+        paned.connect_max_position_notify(glib::clone!(
+            #[weak(rename_to = window)]
+            self,
+            move |_| window.apply_pane_split()
+        ));
+        paned.connect_position_notify(glib::clone!(
+            #[weak(rename_to = window)]
+            self,
+            move |paned| window.pane_split_moved(paned)
+        ));
+
+        self.imp()
+            .port_panels
+            .replace(Some((output_panel, input_panel)));
+        self.imp().port_paned.replace(Some(paned.clone()));
+        self.apply_pane_layout();
+        paned
+    }
+
+    /// Order and stack the port lists as configured
+    pub(super) fn apply_pane_layout(&self) {
+        let Some(paned) = self.imp().port_paned.borrow().clone() else {
+            return;
+        };
+        let Some((output_panel, input_panel)) = self.imp().port_panels.borrow().clone() else {
+            return;
+        };
+        let (swap, stack) = {
+            let settings = self.imp().settings.borrow();
+            (settings.swap_port_panes, settings.stack_port_panes)
+        };
+
+        paned.set_orientation(if stack {
+            gtk::Orientation::Vertical
+        } else {
+            gtk::Orientation::Horizontal
+        });
+        let (first, second) = if swap {
+            (input_panel, output_panel)
+        } else {
+            (output_panel, input_panel)
+        };
+        if paned.start_child().as_ref() != Some(first.upcast_ref()) {
+            // Moving the lists drops focus from them, so take it along
+            let focus = gtk::prelude::RootExt::focus(self);
+            paned.set_start_child(None::<&gtk::Widget>);
+            paned.set_end_child(None::<&gtk::Widget>);
+            paned.set_start_child(Some(&first));
+            paned.set_end_child(Some(&second));
+            if let Some(widget) = focus {
+                widget.grab_focus();
+            }
+        }
+        self.apply_pane_split();
+    }
+
+    /// Move the handle to the configured share of the space
+    fn apply_pane_split(&self) {
+        let Some(paned) = self.imp().port_paned.borrow().clone() else {
+            return;
+        };
+        let max = paned.max_position();
+        if max <= 0 {
+            return;
+        }
+        let split = self
+            .imp()
+            .settings
+            .borrow()
+            .port_pane_split
+            .clamp(MIN_SPLIT, MAX_SPLIT);
+        self.imp().applying_pane_split.set(true);
+        paned.set_position(max * split as i32 / 100);
+        self.imp().applying_pane_split.set(false);
+    }
+
+    /// Remember where the handle was dragged or moved with the keyboard
+    fn pane_split_moved(&self, paned: &gtk::Paned) {
+        if self.imp().applying_pane_split.get() {
+            return;
+        }
+        let max = paned.max_position();
+        if max <= 0 {
+            return;
+        }
+        let split = ((paned.position() as f64 * 100.0 / max as f64).round() as u32)
+            .clamp(MIN_SPLIT, MAX_SPLIT);
+        if self.imp().settings.borrow().port_pane_split == split {
+            return;
+        }
+        self.imp().settings.borrow_mut().port_pane_split = split;
+        self.schedule_pane_split_save();
+    }
+
+    fn schedule_pane_split_save(&self) {
+        if self.imp().pane_split_save_pending.replace(true) {
+            return;
+        }
+        glib::timeout_add_local_once(
+            SPLIT_SAVE_DELAY,
+            glib::clone!(
+                #[weak(rename_to = window)]
+                self,
+                move || {
+                    window.imp().pane_split_save_pending.set(false);
+                    if let Err(e) = window.imp().settings.borrow().save() {
+                        log::warn!("Failed to save the port list split: {}", e);
+                    }
+                }
+            ),
+        );
+    }
+
+    /// Give the first port list `delta` percent more of the space, or less
+    /// if negative
+    pub(super) fn resize_port_panes(&self, delta: i32) {
+        let split = {
+            let mut settings = self.imp().settings.borrow_mut();
+            let split = (settings.port_pane_split as i32 + delta)
+                .clamp(MIN_SPLIT as i32, MAX_SPLIT as i32) as u32;
+            settings.port_pane_split = split;
+            split
+        };
+        self.apply_pane_split();
+
+        if let Err(e) = self.imp().settings.borrow().save() {
+            self.announce(&format!("Failed to save settings: {}", e));
+            return;
+        }
+
+        let (output_share, input_share) = if self.imp().settings.borrow().swap_port_panes {
+            (100 - split, split)
+        } else {
+            (split, 100 - split)
+        };
+        self.announce(&format!(
+            "Output ports {}%, input ports {}%",
+            output_share, input_share
+        ));
+    }
+
+    /// Show the input ports first, or the output ports
+    pub(super) fn set_swap_port_panes(&self, swap: bool) {
+        self.imp().settings.borrow_mut().swap_port_panes = swap;
+        self.sync_layout_action("swap-port-panes", swap);
+        self.apply_pane_layout();
+
+        if let Err(e) = self.imp().settings.borrow().save() {
+            self.announce(&format!("Failed to save settings: {}", e));
+            return;
+        }
+
+        let stack = self.imp().settings.borrow().stack_port_panes;
+        self.announce(match (swap, stack) {
+            (false, false) => "Output ports on the left, input ports on the right",
+            (true, false) => "Input ports on the left, output ports on the right",
+            (false, true) => "Output ports above input ports",
+            (true, true) => "Input ports above output ports",
+        });
+    }
+
+    /// Show the port lists above each other, or side by side
+    pub(super) fn set_stack_port_panes(&self, stack: bool) {
+        self.imp().settings.borrow_mut().stack_port_panes = stack;
+        self.sync_layout_action("stack-port-panes", stack);
+        self.apply_pane_layout();
+
+        if let Err(e) = self.imp().settings.borrow().save() {
+            self.announce(&format!("Failed to save settings: {}", e));
+            return;
+        }
+
+        if stack {
+            self.announce("Port lists above each other");
+        } else {
+            self.announce("Port lists side by side");
+        }
+    }
+
+    fn sync_layout_action(&self, name: &str, state: bool) {
+        if let Some(action) = self.lookup_action(name).and_downcast::<gio::SimpleAction>() {
+            action.set_state(&state.to_variant());
+        }
+    }
+}
@@ -0,0 +1,136 @@
+use adw::prelude::*;
+use adw::subclass::prelude::*;
+use gtk::glib;
+
+use super::Window;
+use crate::pipewire::remote;
+
+impl Window {
+    /// Show a dialog to choose the PipeWire socket to connect to at the
+    /// next start, for a second instance such as `pipewire-1`
+    pub(super) fn show_pipewire_server_dialog(&self) {
+        let mut body = format!(
+            "Connected to {}. Choose a socket in the runtime directory or enter \
+             a socket path. Open it in a new tab alongside this server, or save it to \
+             connect to it instead after restarting. Leave empty for the default.",
+            self.server_name()
+        );
+        if std::env::var_os("PIPEWIRE_REMOTE").is_some() {
+            body.push_str(" PIPEWIRE_REMOTE is set in the environment and takes precedence.");
+        }
+
+        let dialog = adw::MessageDialog::builder()
+            .transient_for(&self.dialog_parent())
+            .modal(true)
+            .heading("PipeWire Server")
+            .body(body)
+            .build();
+
+        let content = gtk::Box::builder()
+            .orientation(gtk::Orientation::Vertical)
+            .spacing(12)
+            .build();
+
+        let remotes = remote::available_remotes();
+        let remote_names: Vec<&str> = remotes.iter().map(String::as_str).collect();
+        let found = gtk::DropDown::from_strings(&remote_names);
+        found.update_property(&[gtk::accessible::Property::Label("Sockets found")]);
+        found.set_sensitive(!remotes.is_empty());
+        content.append(&found);
+
+        let entry = gtk::Entry::builder()
+            .text(
+                self.imp()
+                    .settings
+                    .borrow()
+                    .pipewire_remote
+                    .as_deref()
+                    .unwrap_or_default(),
+            )
+            .placeholder_text(remote::DEFAULT_REMOTE)
+            .activates_default(true)
+            .build();
+        entry.update_property(&[gtk::accessible::Property::Label("Socket name or path")]);
+        content.append(&entry);
+
+        // Choosing a found socket fills in its name
+        found.connect_selected_notify(glib::clone!(
+            #[weak]
+            entry,
+            move |found| {
+                if let Some(name) = found
+                    .selected_item()
+                    .and_downcast::<gtk::StringObject>()
+                {
+                    entry.set_text(&name.string());
+                }
+            }
+        ));
+
+        dialog.set_extra_child(Some(&content));
+
+        dialog.add_response("cancel", "Cancel");
+        dialog.add_response("open", "Open in New Tab");
+        dialog.add_response("save", "Save");
+        dialog.set_response_appearance("save", adw::ResponseAppearance::Suggested);
+        dialog.set_default_response(Some("save"));
+        dialog.set_close_response("cancel");
+
+        dialog.connect_response(
+            None,
+            glib::clone!(
+                #[weak(rename_to = window)]
+                self,
+                #[weak]
+                entry,
+                move |dialog, response| {
+                    dialog.close();
+                    let server = entry.text().trim().to_string();
+                    match response {
+                        "open" => window.open_server_tab(&server),
+                        "save" => window.set_pipewire_remote(&server),
+                        _ => {}
+                    }
+                }
+            ),
+        );
+
+        dialog.present();
+        entry.grab_focus();
+    }
+
+    /// Open `server` in a tab of the main window, each tab keeping its own
+    /// connection
+    fn open_server_tab(&self, server: &str) {
+        let server = if server.is_empty() {
+            remote::DEFAULT_REMOTE
+        } else {
+            server
+        };
+        if server == self.server_name() {
+            self.announce(&format!("This tab is already connected to {}", server));
+            return;
+        }
+        if let Err(e) = self.activate_action("app.open-server", Some(&server.to_variant())) {
+            log::error!("Failed to open a tab for {}: {}", server, e);
+        }
+    }
+
+    /// Save the socket to connect to at the next start
+    fn set_pipewire_remote(&self, remote: &str) {
+        let remote = (!remote.is_empty()).then(|| remote.to_string());
+        self.imp().settings.borrow_mut().pipewire_remote = remote.clone();
+        if let Err(e) = self.imp().settings.borrow().save() {
+            let message = format!("Failed to save settings: {}", e);
+            self.show_toast(&message);
+            self.announce_error(&message);
+            return;
+        }
+
+        let name = remote.as_deref().unwrap_or(remote::DEFAULT_REMOTE);
+        self.announce(&format!(
+            "PW Audioshare will connect to {} after restarting",
+            name
+        ));
+    }
+}
@@ -0,0 +1,194 @@
+use adw::prelude::*;
+use adw::subclass::prelude::*;
+use gtk::glib;
+
+use super::Window;
+use crate::pipewire::messages::DeviceRoute;
+use crate::presets::RouteTrigger;
+
+/// Choices of the event dropdown, matching `RouteTrigger::plugged`
+const EVENTS: [(&str, bool); 2] = [("When plugged in", true), ("When unplugged", false)];
+
+impl Window {
+    /// Activate the preset bound to a route being plugged in or unplugged.
+    /// Events reach the window even while it is hidden in the tray, so this
+    /// works minimized.
+    pub(super) fn route_plugged(&self, device: &str, route: &str, plugged: bool) {
+        // Bindings are kept with the main server's presets
+        if self.is_server_window() {
+            return;
+        }
+
+        let preset = {
+            let store = self.imp().preset_store.borrow();
+            match store.route_trigger(device, route, plugged) {
+                Some(trigger) if !store.is_active(&trigger.preset) => Some(trigger.preset.clone()),
+                _ => None,
+            }
+        };
+        if let Some(preset) = preset {
+            log::info!("Activating preset \"{}\" for {} on {}", preset, route, device);
+            self.activate_preset(&preset);
+        }
+    }
+
+    /// Show a dialog to bind presets to a route of the selected port's
+    /// device being plugged in or unplugged, such as activating a
+    /// "Headphones" preset when headphones are connected
+    pub(super) fn show_plug_presets_dialog(&self) {
+        let (device, card_device) = match self.selected_port_card_device() {
+            Ok(found) => found,
+            Err(message) => {
+                self.announce(&message);
+                return;
+            }
+        };
+        let routes: Vec<DeviceRoute> = device.routes_for(card_device).into_iter().cloned().collect();
+        if routes.is_empty() {
+            self.announce(&format!(
+                "{} has no routes to choose from with its current profile",
+                device.display_name()
+            ));
+            return;
+        }
+        let preset_names = self.imp().preset_store.borrow().preset_names();
+        if preset_names.is_empty() {
+            self.announce("No presets saved yet");
+            return;
+        }
+
+        let dialog = adw::MessageDialog::builder()
+            .transient_for(&self.dialog_parent())
+            .modal(true)
+            .heading("Preset on Plug")
+            .body(format!(
+                "Activate a preset when a route of {} is plugged in or unplugged. \
+                 Only routes with jack detection report this.",
+                device.display_name()
+            ))
+            .build();
+
+        let content = gtk::Box::builder()
+            .orientation(gtk::Orientation::Vertical)
+            .spacing(12)
+            .build();
+
+        let route_labels: Vec<&str> = routes.iter().map(|r| r.description.as_str()).collect();
+        let route_dropdown = gtk::DropDown::from_strings(&route_labels);
+        route_dropdown.update_property(&[gtk::accessible::Property::Label("Route")]);
+        content.append(&route_dropdown);
+
+        let event_labels: Vec<&str> = EVENTS.iter().map(|(label, _)| *label).collect();
+        let event_dropdown = gtk::DropDown::from_strings(&event_labels);
+        event_dropdown.update_property(&[gtk::accessible::Property::Label("Event")]);
+        content.append(&event_dropdown);
+
+        let mut preset_labels = vec!["Nothing"];
+        preset_labels.extend(preset_names.iter().map(String::as_str));
+        let preset_dropdown = gtk::DropDown::from_strings(&preset_labels);
+        preset_dropdown.update_property(&[gtk::accessible::Property::Label("Preset to activate")]);
+        content.append(&preset_dropdown);
+        dialog.set_extra_child(Some(&content));
+
+        // Show the preset already bound to the chosen route and event
+        let device_name = device.name.clone();
+        let bound_routes = routes.clone();
+        let bound_names = preset_names.clone();
+        let show_binding = glib::clone!(
+            #[weak(rename_to = window)]
+            self,
+            #[weak]
+            route_dropdown,
+            #[weak]
+            event_dropdown,
+            #[weak]
+            preset_dropdown,
+            move || {
+                let (Some(route), Some((_, plugged))) = (
+                    bound_routes.get(route_dropdown.selected() as usize),
+                    EVENTS.get(event_dropdown.selected() as usize),
+                ) else {
+                    return;
+                };
+                let store = window.imp().preset_store.borrow();
+                let position = store
+                    .route_trigger(&device_name, &route.name, *plugged)
+                    .and_then(|t| bound_names.iter().position(|name| *name == t.preset))
+                    .map_or(0, |i| i as u32 + 1);
+                preset_dropdown.set_selected(position);
+            }
+        );
+        show_binding();
+        let on_change = show_binding.clone();
+        route_dropdown.connect_selected_notify(move |_| on_change());
+        event_dropdown.connect_selected_notify(move |_| show_binding());
+
+        dialog.add_response("cancel", "Cancel");
+        dialog.add_response("save", "Save");
+        dialog.set_response_appearance("save", adw::ResponseAppearance::Suggested);
+        dialog.set_default_response(Some("save"));
+        dialog.set_close_response("cancel");
+
+        dialog.connect_response(
+            None,
+            glib::clone!(
+                #[weak(rename_to = window)]
+                self,
+                #[weak]
+                route_dropdown,
+                #[weak]
+                event_dropdown,
+                #[weak]
+                preset_dropdown,
+                move |dialog, response| {
+                    dialog.close();
+                    if response != "save" {
+                        return;
+                    }
+                    let (Some(route), Some((event, plugged))) = (
+                        routes.get(route_dropdown.selected() as usize),
+                        EVENTS.get(event_dropdown.selected() as usize),
+                    ) else {
+                        return;
+                    };
+                    let preset = (preset_dropdown.selected() as usize)
+                        .checked_sub(1)
+                        .and_then(|i| preset_names.get(i));
+
+                    {
+                        let mut store = window.imp().preset_store.borrow_mut();
+                        match preset {
+                            Some(preset) => store.set_route_trigger(RouteTrigger {
+                                device: device.name.clone(),
+                                route: route.name.clone(),
+                                plugged: *plugged,
+                                preset: preset.clone(),
+                            }),
+                            None => store.remove_route_trigger(&device.name, &route.name, *plugged),
+                        }
+                    }
+                    if let Err(e) = window.imp().preset_store.borrow().save() {
+                        window.announce(&format!("Failed to save: {}", e));
+                        return;
+                    }
+                    match preset {
+                        Some(preset) => window.announce(&format!(
+                            "{} {}: activate preset \"{}\"",
+                            event,
+                            route.description,
+                            preset
+                        )),
+                        None => window.announce(&format!(
+                            "{} {}: no preset",
+                            event,
+                            route.description
+                        )),
+                    }
+                }
+            ),
+        );
+
+        dialog.present();
+        route_dropdown.grab_focus();
+    }
+}
@@ -0,0 +1,64 @@
+use adw::prelude::*;
+use adw::subclass::prelude::*;
+use gtk::glib;
+
+use super::Window;
+use crate::model::PortObject;
+
+impl Window {
+    /// Add a new port to its list once the burst of events it came in is
+    /// handled. Dozens of ports arrive at once when a session loads or a
+    /// device appears, and adding them one by one would re-sort and
+    /// refilter the lists for every port.
+    pub(super) fn queue_port(&self, port: PortObject) {
+        self.imp().pending_ports.borrow_mut().push(port);
+        if self.imp().port_flush_scheduled.replace(true) {
+            return;
+        }
+        // Idle callbacks run after the pending PipeWire events
+        glib::idle_add_local_once(glib::clone!(
+            #[weak(rename_to = window)]
+            self,
+            move || window.flush_pending_ports()
+        ));
+    }
+
+    /// A new port still waiting to be added to its list
+    pub(super) fn pending_port(&self, id: u32) -> Option<PortObject> {
+        self.imp()
+            .pending_ports
+            .borrow()
+            .iter()
+            .find(|port| port.id() == id)
+            .cloned()
+    }
+
+    /// Drop a port that was removed before its batch was added
+    pub(super) fn forget_pending_port(&self, id: u32) -> bool {
+        let mut pending = self.imp().pending_ports.borrow_mut();
+        let before = pending.len();
+        pending.retain(|port| port.id() != id);
+        pending.len() != before
+    }
+
+    /// Add the queued ports, each list in one change, so the sorted and
+    /// filtered views above them update once per batch
+    fn flush_pending_ports(&self) {
+        let imp = self.imp();
+        imp.port_flush_scheduled.set(false);
+        let ports = imp.pending_ports.take();
+        if ports.is_empty() {
+            return;
+        }
+
+        let (outputs, inputs): (Vec<_>, Vec<_>) =
+            ports.into_iter().partition(|port| port.is_output());
+        for (store, batch) in [(&imp.output_ports, outputs), (&imp.input_ports, inputs)] {
+            if !batch.is_empty() {
+                store.splice(store.n_items(), 0, &batch);
+            }
+        }
+        self.update_status_counts();
+        self.sync_midi_view();
+    }
+}
@@ -0,0 +1,141 @@
+use adw::prelude::*;
+use adw::subclass::prelude::*;
+
+use super::Window;
+use crate::model::{LinkObject, PortObject};
+
+impl Window {
+    /// The single selected port of a port list
+    fn single_selected_port(&self, is_output: bool) -> Option<PortObject> {
+        let ports = self.selected_ports(is_output);
+        match ports.as_slice() {
+            [port] => Some(port.clone()),
+            [] => {
+                self.announce("No port selected");
+                None
+            }
+            _ => {
+                self.announce("Select a single port to show its connections");
+                None
+            }
+        }
+    }
+
+    /// Show only the connections of the selected port and move focus to them
+    pub(super) fn show_port_connections(&self, is_output: bool) {
+        let Some(port) = self.single_selected_port(is_output) else {
+            return;
+        };
+
+        let port_id = port.id();
+        if let Some(filter) = self.imp().connections_filter.borrow().as_ref() {
+            filter.set_filter_func(move |obj| {
+                obj.downcast_ref::<LinkObject>().is_some_and(|link| {
+                    link.output_port_id() == port_id || link.input_port_id() == port_id
+                })
+            });
+        }
+        self.imp().connections_port_filter.set(Some(port_id));
+        self.imp().last_port_list_was_output.replace(is_output);
+
+        if let Some(label) = self.imp().connections_filter_label.borrow().as_ref() {
+            label.set_text(&format!("Connections of {}", port.display_label()));
+        }
+        if let Some(bar) = self.imp().connections_filter_bar.borrow().as_ref() {
+            bar.set_visible(true);
+        }
+
+        let count = self
+            .imp()
+            .connections_selection
+            .borrow()
+            .as_ref()
+            .map(|s| s.n_items())
+            .unwrap_or(0);
+        match count {
+            0 => self.announce(&format!("{} has no connections", port.display_label())),
+            1 => self.announce(&format!("1 connection of {}", port.display_label())),
+            n => self.announce(&format!("{} connections of {}", n, port.display_label())),
+        }
+
+        if let Some(selection) = self.imp().connections_selection.borrow().as_ref() {
+            if count > 0 {
+                selection.set_selected(0);
+            }
+        }
+        self.focus_connections_list();
+    }
+
+    /// Show every connection again. Returns whether a port filter was set.
+    pub(super) fn clear_port_connections_filter(&self) -> bool {
+        if self.imp().connections_port_filter.take().is_none() {
+            return false;
+        }
+        if let Some(filter) = self.imp().connections_filter.borrow().as_ref() {
+            filter.set_filter_func(|_| true);
+        }
+        if let Some(bar) = self.imp().connections_filter_bar.borrow().as_ref() {
+            bar.set_visible(false);
+        }
+        self.announce("Showing all connections");
+        true
+    }
+
+    /// Select one end of the selected connection in its port list and focus it
+    pub(super) fn jump_to_connection_port(&self, to_output: bool) {
+        let link = self
+            .imp()
+            .connections_selection
+            .borrow()
+            .as_ref()
+            .and_then(|s| s.selected_item())
+            .and_downcast::<LinkObject>();
+        let Some(link) = link else {
+            self.announce("No connection selected");
+            return;
+        };
+
+        let port_id = if to_output {
+            link.output_port_id()
+        } else {
+            link.input_port_id()
+        };
+
+        let (selection, list_view) = if to_output {
+            (
+                self.imp().output_selection.borrow().clone(),
+                self.imp().output_list_view.borrow().clone(),
+            )
+        } else {
+            (
+                self.imp().input_selection.borrow().clone(),
+                self.imp().input_list_view.borrow().clone(),
+            )
+        };
+        let (Some(selection), Some(list_view)) = (selection, list_view) else {
+            return;
+        };
+
+        let position = (0..selection.n_items()).find(|&i| {
+            selection
+                .item(i)
+                .and_downcast::<PortObject>()
+                .is_some_and(|port| port.id() == port_id)
+        });
+        let Some(position) = position else {
+            let label = if to_output {
+                link.output_label()
+            } else {
+                link.input_label()
+            };
+            self.announce(&format!("{} is hidden by the current filter", label));
+            return;
+        };
+
+        list_view.scroll_to(
+            position,
+            gtk::ListScrollFlags::FOCUS | gtk::ListScrollFlags::SELECT,
+            None,
+        );
+    }
+}
@@ -0,0 +1,213 @@
+use adw::prelude::*;
+use adw::subclass::prelude::*;
+use gtk::{gio, glib};
+use std::time::Instant;
+
+use super::Window;
+use crate::model::PortObject;
+use crate::settings::PortFilter;
+use crate::ui::query::PortQuery;
+
+impl Window {
+    /// Filter for a port list that reads the search and toggles of the
+    /// window when it runs, so changing them only needs `apply_filters`
+    pub(super) fn new_port_filter(&self) -> gtk::CustomFilter {
+        gtk::CustomFilter::new(glib::clone!(
+            #[weak(rename_to = window)]
+            self,
+            #[upgrade_or]
+            false,
+            move |obj| {
+                obj.downcast_ref::<PortObject>()
+                    .is_some_and(|port| window.port_passes_filter(port))
+            }
+        ))
+    }
+
+    /// Filter a port list in the background, a batch of ports at a time, so
+    /// refiltering thousands of ports doesn't freeze the window
+    pub(super) fn new_port_filter_model(
+        &self,
+        model: gio::ListStore,
+        filter: &gtk::CustomFilter,
+    ) -> gtk::FilterListModel {
+        let filter_model = gtk::FilterListModel::new(Some(model), Some(filter.clone()));
+        filter_model.set_incremental(true);
+
+        // Log how long a refilter took, to measure big graphs such as
+        // --synthetic-graph 5000
+        filter_model.connect_pending_notify(glib::clone!(
+            #[weak(rename_to = window)]
+            self,
+            move |filter_model| {
+                if filter_model.pending() > 0 {
+                    return;
+                }
+                if let Some(started) = window.imp().filter_started.get() {
+                    log::debug!(
+                        "Filtered {} ports in {:?}, {} shown",
+                        filter_model.model().map_or(0, |model| model.n_items()),
+                        started.elapsed(),
+                        filter_model.n_items()
+                    );
+                }
+            }
+        ));
+        filter_model
+    }
+
+    fn port_passes_filter(&self, port: &PortObject) -> bool {
+        let imp = self.imp();
+
+        // Check media type filter
+        let media_ok = match port.media_type().as_str() {
+            "audio" => *imp.show_audio.borrow(),
+            "midi" => *imp.show_midi.borrow(),
+            "video" => *imp.show_video.borrow(),
+            _ => true, // Show unknown types
+        };
+        if !media_ok {
+            return false;
+        }
+
+        if !*imp.show_monitors.borrow() && port.name().starts_with("monitor_") {
+            return false;
+        }
+
+        // Check search query
+        let query = imp.port_query.borrow();
+        query.is_empty() || query.matches(port)
+    }
+
+    /// Refilter the port lists after the search or toggles changed. When
+    /// the filter only narrowed, as while typing a word, GTK checks just the
+    /// ports still shown, and when it only widened, just the hidden ones.
+    pub(super) fn apply_filters(&self) {
+        let filter = self.current_port_filter();
+        let query = PortQuery::parse(&filter.search);
+
+        let change = match self.imp().applied_port_filter.borrow().as_ref() {
+            Some(applied) => {
+                filter_change(applied, &self.imp().port_query.borrow(), &filter, &query)
+            }
+            None => Some(gtk::FilterChange::Different),
+        };
+        self.imp().port_query.replace(query);
+        self.imp().applied_port_filter.replace(Some(filter));
+
+        let Some(change) = change else {
+            return;
+        };
+        self.imp().filter_started.set(Some(Instant::now()));
+        for filter in [&self.imp().output_filter, &self.imp().input_filter] {
+            if let Some(filter) = filter.borrow().as_ref() {
+                filter.changed(change);
+            }
+        }
+    }
+}
+
+/// How the set of ports shown changes from the `old` filter to the `new`
+/// one, or `None` if it stays the same
+fn filter_change(
+    old: &PortFilter,
+    old_query: &PortQuery,
+    new: &PortFilter,
+    new_query: &PortQuery,
+) -> Option<gtk::FilterChange> {
+    let mut narrower = new_query.is_narrower_than(old_query);
+    let mut wider = old_query.is_narrower_than(new_query);
+    for (was_shown, shown) in [
+        (old.show_audio, new.show_audio),
+        (old.show_midi, new.show_midi),
+        (old.show_video, new.show_video),
+        (old.show_monitors, new.show_monitors),
+    ] {
+        if was_shown && !shown {
+            wider = false;
+        }
+        if !was_shown && shown {
+            narrower = false;
+        }
+    }
+
+    match (narrower, wider) {
+        (true, true) => None,
+        (true, false) => Some(gtk::FilterChange::MoreStrict),
+        (false, true) => Some(gtk::FilterChange::LessStrict),
+        (false, false) => Some(gtk::FilterChange::Different),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn change(old: &PortFilter, new: &PortFilter) -> Option<gtk::FilterChange> {
+        filter_change(
+            old,
+            &PortQuery::parse(&old.search),
+            new,
+            &PortQuery::parse(&new.search),
+        )
+    }
+
+    fn search(text: &str) -> PortFilter {
+        PortFilter {
+            search: text.to_string(),
+            ..PortFilter::default()
+        }
+    }
+
+    #[test]
+    fn same_filter_changes_nothing() {
+        assert_eq!(change(&search("fire"), &search("fire")), None);
+    }
+
+    #[test]
+    fn typing_is_more_strict() {
+        assert_eq!(
+            change(&search("fir"), &search("fire")),
+            Some(gtk::FilterChange::MoreStrict)
+        );
+    }
+
+    #[test]
+    fn deleting_is_less_strict() {
+        assert_eq!(
+            change(&search("fire"), &search("fir")),
+            Some(gtk::FilterChange::LessStrict)
+        );
+    }
+
+    #[test]
+    fn replacing_the_search_is_different() {
+        assert_eq!(
+            change(&search("firefox"), &search("obs")),
+            Some(gtk::FilterChange::Different)
+        );
+    }
+
+    #[test]
+    fn hiding_a_port_type_is_more_strict() {
+        let old = search("fire");
+        let new = PortFilter {
+            show_midi: false,
+            ..old.clone()
+        };
+        assert_eq!(change(&old, &new), Some(gtk::FilterChange::MoreStrict));
+        assert_eq!(change(&new, &old), Some(gtk::FilterChange::LessStrict));
+    }
+
+    #[test]
+    fn showing_a_type_while_typing_is_different() {
+        let old = PortFilter {
+            show_monitors: false,
+            ..search("fir")
+        };
+        assert_eq!(
+            change(&old, &search("fire")),
+            Some(gtk::FilterChange::Different)
+        );
+    }
+}
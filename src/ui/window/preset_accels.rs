@@ -0,0 +1,106 @@
+use adw::prelude::*;
+use adw::subclass::prelude::*;
+use gtk::glib;
+
+use super::Window;
+use crate::ui::shortcuts::{self, PRESET_ACCELS};
+
+impl Window {
+    /// Bind the keys given to presets and list them in the shortcuts window.
+    /// Called whenever presets are added, renamed or deleted.
+    pub(super) fn refresh_preset_accels(&self) {
+        let accels = self.imp().preset_store.borrow().accelerators();
+        // The main window's presets are the ones keys activate
+        if !self.is_server_window() {
+            if let Some(app) = self.application() {
+                shortcuts::register_preset_accels(&app, &accels);
+            }
+        }
+        self.set_help_overlay(Some(&shortcuts::build_shortcuts_window(&accels)));
+    }
+
+    /// Choose the key that activates a preset
+    pub(super) fn show_preset_accel_dialog(&self, name: &str) {
+        let (current, labels) = {
+            let store = self.imp().preset_store.borrow();
+            let current = store
+                .get_preset(name)
+                .and_then(|p| p.accelerator.clone())
+                .and_then(|accel| PRESET_ACCELS.iter().position(|a| *a == accel))
+                .map_or(0, |i| i as u32 + 1);
+            let mut labels = vec!["None".to_string()];
+            for accel in PRESET_ACCELS {
+                let label = shortcuts::accel_label(accel).unwrap_or_else(|| accel.to_string());
+                labels.push(match store.accelerator_preset(accel) {
+                    Some(owner) if owner != name => format!("{} (used by {})", label, owner),
+                    _ => label,
+                });
+            }
+            (current, labels)
+        };
+
+        let dialog = adw::MessageDialog::builder()
+            .transient_for(&self.dialog_parent())
+            .modal(true)
+            .heading("Preset Shortcut")
+            .body(format!(
+                "Choose the key that activates \"{}\" while a window is focused. \
+                 A key used by another preset moves to this one.",
+                name
+            ))
+            .build();
+
+        let label_refs: Vec<&str> = labels.iter().map(String::as_str).collect();
+        let dropdown = gtk::DropDown::from_strings(&label_refs);
+        dropdown.set_selected(current);
+        dropdown.update_property(&[gtk::accessible::Property::Label("Shortcut")]);
+        dialog.set_extra_child(Some(&dropdown));
+
+        dialog.add_response("cancel", "Cancel");
+        dialog.add_response("save", "Save");
+        dialog.set_response_appearance("save", adw::ResponseAppearance::Suggested);
+        dialog.set_default_response(Some("save"));
+        dialog.set_close_response("cancel");
+
+        let name = name.to_string();
+        dialog.connect_response(
+            None,
+            glib::clone!(
+                #[weak(rename_to = window)]
+                self,
+                #[weak]
+                dropdown,
+                move |dialog, response| {
+                    dialog.close();
+                    if response == "save" {
+                        let accel = (dropdown.selected() as usize)
+                            .checked_sub(1)
+                            .and_then(|i| PRESET_ACCELS.get(i).copied());
+                        window.set_preset_accel(&name, accel);
+                    }
+                }
+            ),
+        );
+
+        dialog.present();
+        dropdown.grab_focus();
+    }
+
+    /// Give a preset a key, or none
+    fn set_preset_accel(&self, name: &str, accel: Option<&str>) {
+        self.imp()
+            .preset_store
+            .borrow_mut()
+            .set_accelerator(name, accel);
+        if let Err(e) = self.imp().preset_store.borrow().save() {
+            self.announce(&format!("Failed to save: {}", e));
+            return;
+        }
+        self.refresh_preset_accels();
+
+        match accel.and_then(shortcuts::accel_label) {
+            Some(label) => self.announce(&format!("{} activates \"{}\"", label, name)),
+            None => self.announce(&format!("\"{}\" has no shortcut", name)),
+        }
+    }
+}
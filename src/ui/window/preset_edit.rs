@@ -0,0 +1,134 @@
+use adw::prelude::*;
+use adw::subclass::prelude::*;
+use gtk::glib;
+
+use super::Window;
+
+impl Window {
+    /// Ask for a new name for a preset
+    pub(super) fn show_rename_preset_dialog(&self, name: &str) {
+        let old_name = name.to_string();
+        self.ask_preset_name(
+            "Rename Preset",
+            &format!("Enter a new name for \"{}\":", name),
+            name,
+            "Rename",
+            move |window, new_name| window.rename_preset(&old_name, new_name),
+        );
+    }
+
+    /// Ask for the name of a copy of a preset
+    pub(super) fn show_duplicate_preset_dialog(&self, name: &str) {
+        let source = name.to_string();
+        let copy_name = self.imp().preset_store.borrow().unused_copy_name(name);
+        self.ask_preset_name(
+            "Duplicate Preset",
+            &format!("Enter a name for the copy of \"{}\":", name),
+            &copy_name,
+            "Duplicate",
+            move |window, new_name| window.duplicate_preset(&source, new_name),
+        );
+    }
+
+    /// Show a dialog with a preset name entry, calling `confirmed` with the
+    /// trimmed name when `action` is chosen
+    fn ask_preset_name<F>(
+        &self,
+        heading: &str,
+        body: &str,
+        initial: &str,
+        action: &str,
+        confirmed: F,
+    ) where
+        F: Fn(&Window, &str) + 'static,
+    {
+        let dialog = adw::MessageDialog::builder()
+            .transient_for(&self.dialog_parent())
+            .modal(true)
+            .heading(heading)
+            .body(body)
+            .build();
+
+        let entry = gtk::Entry::builder()
+            .text(initial)
+            .placeholder_text("Preset name")
+            .activates_default(true)
+            .build();
+        entry.update_property(&[gtk::accessible::Property::Label("Preset name")]);
+        dialog.set_extra_child(Some(&entry));
+
+        dialog.add_response("cancel", "Cancel");
+        dialog.add_response("confirm", action);
+        dialog.set_response_appearance("confirm", adw::ResponseAppearance::Suggested);
+        dialog.set_default_response(Some("confirm"));
+        dialog.set_close_response("cancel");
+
+        dialog.connect_response(
+            None,
+            glib::clone!(
+                #[weak(rename_to = window)]
+                self,
+                #[weak]
+                entry,
+                move |dialog, response| {
+                    dialog.close();
+                    if response == "confirm" {
+                        confirmed(&window, entry.text().trim());
+                    }
+                }
+            ),
+        );
+
+        dialog.present();
+        entry.grab_focus();
+    }
+
+    /// Rename a preset, keeping it active and its triggers
+    fn rename_preset(&self, name: &str, new_name: &str) {
+        if name == new_name {
+            return;
+        }
+        let result = self
+            .imp()
+            .preset_store
+            .borrow_mut()
+            .rename_preset(name, new_name);
+        if let Err(e) = result {
+            self.announce_error(&e);
+            return;
+        }
+
+        if let Err(e) = self.imp().preset_store.borrow().save() {
+            self.announce(&format!("Failed to save: {}", e));
+            return;
+        }
+        self.update_active_preset_display();
+        self.refresh_preset_accels();
+        let message = format!("Renamed preset \"{}\" to \"{}\"", name, new_name);
+        self.record_activity(&message);
+        self.announce(&message);
+    }
+
+    /// Save a copy of a preset under a new name, to start a variant from
+    fn duplicate_preset(&self, name: &str, new_name: &str) {
+        let result = self
+            .imp()
+            .preset_store
+            .borrow_mut()
+            .duplicate_preset(name, new_name);
+        if let Err(e) = result {
+            self.announce_error(&e);
+            return;
+        }
+
+        if let Err(e) = self.imp().preset_store.borrow().save() {
+            self.announce(&format!("Failed to save: {}", e));
+            return;
+        }
+        self.sync_compact_window();
+        self.update_tray();
+        let message = format!("Copied preset \"{}\" to \"{}\"", name, new_name);
+        self.record_activity(&message);
+        self.announce(&message);
+    }
+}
@@ -0,0 +1,93 @@
+use std::path::Path;
+
+use adw::subclass::prelude::*;
+use gtk::{gio, glib};
+
+use super::Window;
+use crate::presets::WirePlumberExport;
+
+/// File name for a preset's rules; WirePlumber loads `.conf` fragments in
+/// name order, after its own defaults
+fn fragment_name(preset_name: &str) -> String {
+    let slug: String = preset_name
+        .chars()
+        .map(|c| {
+            if c.is_ascii_alphanumeric() {
+                c.to_ascii_lowercase()
+            } else {
+                '-'
+            }
+        })
+        .collect();
+    format!("51-pw-audioshare-{}.conf", slug.trim_matches('-'))
+}
+
+/// Write the rules, returning the message to announce
+fn write_export(path: &Path, export: &WirePlumberExport) -> Result<String, String> {
+    std::fs::write(path, &export.text).map_err(|e| format!("Failed to export the preset: {}", e))?;
+
+    let mut message = format!(
+        "Exported rules for {} streams to {}. Restart WirePlumber to apply them",
+        export.rules,
+        path.display()
+    );
+    if export.skipped > 0 {
+        message.push_str(&format!(
+            ". {} connections can't be expressed as rules and are listed as comments",
+            export.skipped
+        ));
+    }
+    Ok(message)
+}
+
+impl Window {
+    /// Save the active preset as a WirePlumber config fragment, so its
+    /// routing is kept without PW Audioshare running
+    pub(super) fn show_export_wireplumber_dialog(&self) {
+        let (name, export) = {
+            let store = self.imp().preset_store.borrow();
+            let Some(preset) = store.get_active_preset() else {
+                self.announce("No preset is active. Activate the preset to export first");
+                return;
+            };
+            let export = preset.to_wireplumber_rules(&self.imp().pw_state.borrow());
+            (preset.name.clone(), export)
+        };
+        if export.rules == 0 {
+            self.announce(&format!(
+                "Preset \"{}\" has no application streams WirePlumber can route",
+                name
+            ));
+            return;
+        }
+
+        let dialog = gtk::FileDialog::builder()
+            .title("Export Preset for WirePlumber")
+            .initial_name(fragment_name(&name))
+            .build();
+        // Inside Flatpak the config directory is the sandbox's own
+        if let Some(dir) = dirs::config_dir().map(|d| d.join("wireplumber/wireplumber.conf.d")) {
+            if dir.is_dir() && !crate::sandbox::is_flatpak() {
+                dialog.set_initial_folder(Some(&gio::File::for_path(dir)));
+            }
+        }
+
+        dialog.save(
+            Some(&self.dialog_parent()),
+            None::<&gio::Cancellable>,
+            glib::clone!(
+                #[weak(rename_to = window)]
+                self,
+                move |result| {
+                    let Some(path) = result.ok().and_then(|file| file.path()) else {
+                        return;
+                    };
+                    match write_export(&path, &export) {
+                        Ok(message) => window.announce(&message),
+                        Err(e) => window.announce_error(&e),
+                    }
+                }
+            ),
+        );
+    }
+}
@@ -0,0 +1,82 @@
+use adw::subclass::prelude::*;
+
+use super::Window;
+
+/// Failure reason of a preset connection whose ports aren't in the graph
+const PORTS_NOT_FOUND: &str = "ports not found";
+
+impl Window {
+    /// Count the outcome of an auto-connect request towards the statistics
+    /// of the preset that made it. They are saved once the preset has no
+    /// requests left, rather than for every link.
+    pub(super) fn preset_request_finished(&self, request_id: u64, result: Result<(), &str>) {
+        let Some(name) = self
+            .imp()
+            .preset_link_requests
+            .borrow_mut()
+            .remove(&request_id)
+        else {
+            return;
+        };
+
+        {
+            let mut store = self.imp().preset_store.borrow_mut();
+            let Some(stats) = store.stats_mut(&name) else {
+                return;
+            };
+            match result {
+                Ok(()) => stats.connections_made += 1,
+                Err(reason) => {
+                    stats.failures += 1;
+                    stats.last_failure = Some(reason.to_string());
+                }
+            }
+        }
+
+        let waiting = self
+            .imp()
+            .preset_link_requests
+            .borrow()
+            .values()
+            .any(|pending| *pending == name);
+        if waiting {
+            return;
+        }
+        if let Err(e) = self.imp().preset_store.borrow().save() {
+            log::warn!("Failed to save preset statistics: {}", e);
+        }
+    }
+
+    /// Count the connections of a preset being activated whose ports aren't
+    /// in the graph, as after a device was renamed, as failures. Saved with
+    /// the activation.
+    pub(super) fn count_unmatched_connections(&self, name: &str) {
+        // Before the graph is known every port would be missing
+        if self.imp().health_report.borrow().is_none() {
+            return;
+        }
+
+        let unmatched = {
+            let pw_state = self.imp().pw_state.borrow();
+            let store = self.imp().preset_store.borrow();
+            let Some(preset) = store.get_preset(name) else {
+                return;
+            };
+            preset
+                .connections
+                .iter()
+                .filter(|connection| {
+                    !matches!(connection.find_ports(&pw_state), (Some(_), Some(_)))
+                })
+                .count() as u32
+        };
+        if unmatched == 0 {
+            return;
+        }
+
+        if let Some(stats) = self.imp().preset_store.borrow_mut().stats_mut(name) {
+            stats.failures += unmatched;
+            stats.last_failure = Some(PORTS_NOT_FOUND.to_string());
+        }
+    }
+}
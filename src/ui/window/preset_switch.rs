@@ -0,0 +1,102 @@
+use std::time::{Duration, Instant};
+
+use adw::subclass::prelude::*;
+use gtk::glib;
+
+use super::Window;
+use crate::presets::PresetConnection;
+
+/// How often to check whether the new preset's links exist
+const POLL_INTERVAL: Duration = Duration::from_millis(50);
+
+/// Longest wait for the new links before removing the old ones anyway
+const MAX_WAIT: Duration = Duration::from_secs(2);
+
+impl Window {
+    /// Links made by a preset that the next preset doesn't have, to remove
+    /// when switching from one to the other
+    pub(super) fn links_left_by_switch(&self, previous: &str, next: &str) -> Vec<u32> {
+        let store = self.imp().preset_store.borrow();
+        let (Some(previous), Some(next)) = (store.get_preset(previous), store.get_preset(next))
+        else {
+            return Vec::new();
+        };
+
+        let pw_state = self.imp().pw_state.borrow();
+        let port_pairs = |connections: &[PresetConnection]| -> Vec<(u32, u32)> {
+            connections
+                .iter()
+                .filter_map(|conn| match conn.find_ports(&pw_state) {
+                    (Some(out), Some(inp)) => Some((out.id, inp.id)),
+                    _ => None,
+                })
+                .collect()
+        };
+        let kept = port_pairs(&next.connections);
+        port_pairs(&previous.connections)
+            .into_iter()
+            .filter(|pair| !kept.contains(pair))
+            .filter_map(|(out, inp)| pw_state.find_link(out, inp).map(|link| link.id))
+            .collect()
+    }
+
+    /// Remove the previous preset's links once the new preset's links have
+    /// been made, so the audio never has a gap or plays nowhere in between
+    pub(super) fn remove_links_after_switch(&self, links: Vec<u32>) {
+        if links.is_empty() {
+            return;
+        }
+        let started = Instant::now();
+        glib::timeout_add_local(
+            POLL_INTERVAL,
+            glib::clone!(
+                #[weak(rename_to = window)]
+                self,
+                #[upgrade_or]
+                glib::ControlFlow::Break,
+                move || {
+                    // Links still being created show up as pending
+                    let waiting = !window.imp().pending_links.borrow().is_empty();
+                    if waiting && started.elapsed() < MAX_WAIT {
+                        return glib::ControlFlow::Continue;
+                    }
+                    let existing: Vec<u32> = {
+                        let pw_state = window.imp().pw_state.borrow();
+                        links
+                            .iter()
+                            .copied()
+                            .filter(|id| pw_state.links.contains_key(id))
+                            .collect()
+                    };
+                    for link_id in &existing {
+                        window.delete_link(*link_id);
+                    }
+                    if !existing.is_empty() {
+                        window.record_activity(&format!(
+                            "Removed {} connections of the previous preset",
+                            existing.len()
+                        ));
+                    }
+                    glib::ControlFlow::Break
+                }
+            ),
+        );
+    }
+
+    /// Set whether switching presets replaces the previous preset's
+    /// connections and save it
+    pub(super) fn set_replace_on_preset_switch(&self, enabled: bool) {
+        self.imp().settings.borrow_mut().replace_on_preset_switch = enabled;
+
+        if let Err(e) = self.imp().settings.borrow().save() {
+            self.announce(&format!("Failed to save settings: {}", e));
+            return;
+        }
+
+        if enabled {
+            self.announce("Switching presets replaces the previous preset's connections");
+        } else {
+            self.announce("Switching presets keeps the previous preset's connections");
+        }
+    }
+}
@@ -0,0 +1,212 @@
+use adw::prelude::*;
+use adw::subclass::prelude::*;
+use gtk::glib;
+
+use super::Window;
+
+impl Window {
+    /// Permanently remove presets that have been in the trash longer than
+    /// the configured number of days
+    pub(super) fn purge_preset_trash(&self) {
+        let days = self.imp().settings.borrow().preset_trash_days;
+        if days == 0 {
+            return;
+        }
+        let purged = self.imp().preset_store.borrow_mut().purge_trash(Some(days));
+        if purged == 0 {
+            return;
+        }
+        log::info!("Removed {} presets deleted over {} days ago", purged, days);
+        if let Err(e) = self.imp().preset_store.borrow().save() {
+            log::warn!("Failed to save presets after emptying the trash: {}", e);
+        }
+    }
+
+    /// Put a deleted preset back, activating it again if it was active
+    pub(super) fn restore_preset(&self, name: &str, activate: bool) {
+        let result = {
+            let mut store = self.imp().preset_store.borrow_mut();
+            store.restore_preset(name).map(|()| {
+                if activate {
+                    store.activate_preset(name);
+                }
+            })
+        };
+        if let Err(e) = result {
+            self.show_toast(&e);
+            self.announce_error(&e);
+            return;
+        }
+
+        if let Err(e) = self.imp().preset_store.borrow().save() {
+            self.announce(&format!("Failed to save: {}", e));
+            return;
+        }
+        if activate {
+            self.update_active_preset_display();
+            self.check_auto_connect();
+        } else {
+            self.sync_compact_window();
+        }
+        self.refresh_preset_accels();
+        let message = format!("Restored preset \"{}\"", name);
+        self.record_activity(&message);
+        self.announce(&message);
+    }
+
+    /// Show the deleted presets, to restore one or empty the trash, and how
+    /// long they are kept
+    pub(super) fn show_preset_trash_dialog(&self) {
+        let trash = self.imp().preset_store.borrow().trash.clone();
+
+        let dialog = adw::MessageDialog::builder()
+            .transient_for(&self.dialog_parent())
+            .modal(true)
+            .heading("Deleted Presets")
+            .body(if trash.is_empty() {
+                "The trash is empty."
+            } else {
+                "Select a preset to restore."
+            })
+            .build();
+
+        let list_box = gtk::ListBox::builder()
+            .selection_mode(gtk::SelectionMode::Single)
+            .css_classes(["boxed-list"])
+            .build();
+        list_box.update_property(&[gtk::accessible::Property::Label("Deleted presets")]);
+        // Most recently deleted first
+        for deleted in trash.iter().rev() {
+            let when = match deleted.days_ago() {
+                0 => "Deleted today".to_string(),
+                1 => "Deleted yesterday".to_string(),
+                days => format!("Deleted {} days ago", days),
+            };
+            let row = adw::ActionRow::builder()
+                .title(&deleted.preset.name)
+                .subtitle(format!(
+                    "{}, {} connections",
+                    when,
+                    deleted.preset.connections.len()
+                ))
+                .activatable(true)
+                .build();
+            list_box.append(&row);
+        }
+        if let Some(first_row) = list_box.row_at_index(0) {
+            list_box.select_row(Some(&first_row));
+        }
+
+        let scrolled = gtk::ScrolledWindow::builder()
+            .hscrollbar_policy(gtk::PolicyType::Never)
+            .vscrollbar_policy(gtk::PolicyType::Automatic)
+            .min_content_height(100)
+            .max_content_height(300)
+            .child(&list_box)
+            .visible(!trash.is_empty())
+            .build();
+
+        let days_label = gtk::Label::new(Some("Keep deleted presets for (days, 0 for ever)"));
+        days_label.set_xalign(0.0);
+        days_label.set_hexpand(true);
+        let days_spin = gtk::SpinButton::with_range(0.0, 3650.0, 1.0);
+        days_spin.set_value(self.imp().settings.borrow().preset_trash_days as f64);
+        days_spin.update_property(&[gtk::accessible::Property::Label(
+            "Keep deleted presets for days, 0 for ever",
+        )]);
+        let days_box = gtk::Box::builder()
+            .orientation(gtk::Orientation::Horizontal)
+            .spacing(12)
+            .build();
+        days_box.append(&days_label);
+        days_box.append(&days_spin);
+
+        let content = gtk::Box::builder()
+            .orientation(gtk::Orientation::Vertical)
+            .spacing(12)
+            .build();
+        content.append(&scrolled);
+        content.append(&days_box);
+        dialog.set_extra_child(Some(&content));
+
+        dialog.add_response("close", "Close");
+        dialog.add_response("empty", "Empty Trash");
+        dialog.add_response("restore", "Restore");
+        dialog.set_response_appearance("empty", adw::ResponseAppearance::Destructive);
+        dialog.set_response_appearance("restore", adw::ResponseAppearance::Suggested);
+        dialog.set_response_enabled("empty", !trash.is_empty());
+        dialog.set_response_enabled("restore", !trash.is_empty());
+        dialog.set_default_response(Some(if trash.is_empty() { "close" } else { "restore" }));
+        dialog.set_close_response("close");
+
+        // Handle row activation (double-click or Enter)
+        let dialog_weak = dialog.downgrade();
+        list_box.connect_row_activated(move |_, _| {
+            if let Some(dialog) = dialog_weak.upgrade() {
+                dialog.response("restore");
+            }
+        });
+
+        dialog.connect_response(
+            None,
+            glib::clone!(
+                #[weak(rename_to = window)]
+                self,
+                #[weak]
+                list_box,
+                #[weak]
+                days_spin,
+                move |dialog, response| {
+                    dialog.close();
+                    window.set_preset_trash_days(days_spin.value() as u32);
+                    match response {
+                        "restore" => {
+                            let name = list_box.selected_row().and_then(|row| {
+                                row.downcast::<adw::ActionRow>()
+                                    .ok()
+                                    .map(|ar| ar.title().to_string())
+                            });
+                            if let Some(name) = name {
+                                window.restore_preset(&name, false);
+                            }
+                        }
+                        "empty" => window.empty_preset_trash(),
+                        _ => {}
+                    }
+                }
+            ),
+        );
+
+        dialog.present();
+        if trash.is_empty() {
+            days_spin.grab_focus();
+        } else {
+            list_box.grab_focus();
+        }
+    }
+
+    /// Permanently remove every deleted preset
+    fn empty_preset_trash(&self) {
+        let purged = self.imp().preset_store.borrow_mut().purge_trash(None);
+        if let Err(e) = self.imp().preset_store.borrow().save() {
+            self.announce(&format!("Failed to save: {}", e));
+            return;
+        }
+        let message = format!("Permanently deleted {} presets", purged);
+        self.record_activity(&message);
+        self.announce(&message);
+    }
+
+    /// Save how many days deleted presets are kept, purging older ones
+    fn set_preset_trash_days(&self, days: u32) {
+        if self.imp().settings.borrow().preset_trash_days == days {
+            return;
+        }
+        self.imp().settings.borrow_mut().preset_trash_days = days;
+        if let Err(e) = self.imp().settings.borrow().save() {
+            self.announce(&format!("Failed to save settings: {}", e));
+            return;
+        }
+        self.purge_preset_trash();
+    }
+}
@@ -0,0 +1,115 @@
+use adw::prelude::*;
+use adw::subclass::prelude::*;
+use gtk::gio;
+
+use super::Window;
+use crate::pipewire::PwState;
+use crate::presets::PresetConnection;
+
+/// How many removed connections are kept for reconnecting
+const MAX_RECENTLY_REMOVED: usize = 10;
+
+/// A connection kept by names so it can be recreated even after its ports
+/// come back with new ids
+#[derive(Debug, Clone)]
+pub struct RememberedLink {
+    pub label: String,
+    pub connection: PresetConnection,
+}
+
+impl RememberedLink {
+    /// Remember the connection between two ports in the graph
+    pub fn of_ports(state: &PwState, output_port_id: u32, input_port_id: u32) -> Option<Self> {
+        let connection = PresetConnection::from_ports(state, output_port_id, input_port_id)?;
+        let label = format!(
+            "{} → {}",
+            state.port_label(output_port_id)?,
+            state.port_label(input_port_id)?
+        );
+        Some(Self { label, connection })
+    }
+
+    /// The ports of the connection if both are in the graph, and whether
+    /// they are linked
+    pub fn find_ports(&self, state: &PwState) -> Option<(u32, u32, bool)> {
+        match self.connection.find_ports(state) {
+            (Some(output), Some(input)) => {
+                Some((output.id, input.id, state.link_exists(output.id, input.id)))
+            }
+            _ => None,
+        }
+    }
+}
+
+impl Window {
+    /// Delete a link on the user's behalf, remembering it for Reconnect
+    pub(super) fn delete_link_remembered(&self, link_id: u32) {
+        let removed = {
+            let pw_state = self.imp().pw_state.borrow();
+            pw_state.links.get(&link_id).and_then(|link| {
+                RememberedLink::of_ports(&pw_state, link.output_port_id, link.input_port_id)
+            })
+        };
+
+        if let Some(removed) = removed {
+            let mut recent = self.imp().recently_removed.borrow_mut();
+            recent.push_front(removed);
+            recent.truncate(MAX_RECENTLY_REMOVED);
+        }
+        self.update_recently_removed_menu();
+        self.delete_link(link_id);
+    }
+
+    /// Recreate a removed connection; 0 is the most recently removed
+    pub(super) fn reconnect_removed(&self, index: usize) {
+        let removed = self.imp().recently_removed.borrow_mut().remove(index);
+        let Some(removed) = removed else {
+            self.announce("No removed connections to reconnect");
+            return;
+        };
+        self.update_recently_removed_menu();
+
+        let ports = removed.find_ports(&self.imp().pw_state.borrow());
+
+        match ports {
+            Some((_, _, true)) => self.announce(&format!("{} is already connected", removed.label)),
+            Some((output_id, input_id, false)) => {
+                self.create_link(output_id, input_id);
+                self.announce(&format!("Reconnected {}", removed.label));
+            }
+            None => self.announce(&format!(
+                "Cannot reconnect {}: its ports are gone",
+                removed.label
+            )),
+        }
+    }
+
+    /// List the removed connections in the Recently Removed menu
+    pub(super) fn update_recently_removed_menu(&self) {
+        let menu = &self.imp().recently_removed_menu;
+        menu.remove_all();
+
+        let recent = self.imp().recently_removed.borrow();
+        if recent.is_empty() {
+            // No action, so the item is shown insensitive
+            menu.append(Some("No Removed Connections"), None);
+        }
+        for (index, removed) in recent.iter().enumerate() {
+            // Menu labels treat "_" as a mnemonic marker, and node names are full of them
+            let label = removed.label.replace('_', "__");
+            let item = gio::MenuItem::new(Some(&label), None);
+            item.set_action_and_target_value(
+                Some("win.reconnect-removed"),
+                Some(&(index as u32).to_variant()),
+            );
+            menu.append_item(&item);
+        }
+
+        if let Some(action) = self
+            .lookup_action("reconnect-last")
+            .and_downcast::<gio::SimpleAction>()
+        {
+            action.set_enabled(!recent.is_empty());
+        }
+    }
+}
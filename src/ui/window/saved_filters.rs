@@ -0,0 +1,274 @@
+use adw::prelude::*;
+use adw::subclass::prelude::*;
+use gtk::glib;
+
+use super::Window;
+use crate::settings::{PortFilter, SavedFilter, StartupPortTypes};
+
+/// Filter bar controls, kept so a saved filter can be put back into them
+pub struct FilterWidgets {
+    pub search: gtk::SearchEntry,
+    pub audio: gtk::ToggleButton,
+    pub midi: gtk::ToggleButton,
+    pub video: gtk::ToggleButton,
+    pub monitors: gtk::ToggleButton,
+}
+
+impl Window {
+    /// The filter currently applied to the port lists
+    pub(super) fn current_port_filter(&self) -> PortFilter {
+        let imp = self.imp();
+        PortFilter {
+            search: imp.search_text.borrow().clone(),
+            show_audio: *imp.show_audio.borrow(),
+            show_midi: *imp.show_midi.borrow(),
+            show_video: *imp.show_video.borrow(),
+            show_monitors: *imp.show_monitors.borrow(),
+        }
+    }
+
+    /// Refilter the port lists and remember the filter for the next start
+    pub(super) fn port_filter_changed(&self) {
+        self.apply_filters();
+
+        let filter = self.current_port_filter();
+        if self.imp().settings.borrow().last_filter == filter {
+            return;
+        }
+        self.imp().settings.borrow_mut().last_filter = filter;
+        if let Err(e) = self.imp().settings.borrow().save() {
+            log::warn!("Failed to save filter: {}", e);
+        }
+    }
+
+    /// Set which port types are shown at startup and save the setting
+    pub(super) fn set_startup_port_types(&self, types: StartupPortTypes) {
+        self.imp().settings.borrow_mut().startup_port_types = types;
+
+        if let Err(e) = self.imp().settings.borrow().save() {
+            self.announce(&format!("Failed to save settings: {}", e));
+            return;
+        }
+
+        self.announce(match types {
+            StartupPortTypes::Last => "Port types will start as last used",
+            StartupPortTypes::All => "All port types will be shown at startup",
+            StartupPortTypes::Audio => "Only audio ports will be shown at startup",
+        });
+    }
+
+    /// Put a filter into the filter bar, which applies it
+    fn set_port_filter(&self, filter: &PortFilter) {
+        let imp = self.imp();
+        imp.search_text.replace(filter.search.clone());
+        imp.show_audio.replace(filter.show_audio);
+        imp.show_midi.replace(filter.show_midi);
+        imp.show_video.replace(filter.show_video);
+        imp.show_monitors.replace(filter.show_monitors);
+
+        if let Some(widgets) = imp.filter_widgets.borrow().as_ref() {
+            widgets.search.set_text(&filter.search);
+            widgets.audio.set_active(filter.show_audio);
+            widgets.midi.set_active(filter.show_midi);
+            widgets.video.set_active(filter.show_video);
+            widgets.monitors.set_active(filter.show_monitors);
+        }
+        self.port_filter_changed();
+    }
+
+    /// Ask for a name and save the current filter under it
+    pub(super) fn show_save_filter_dialog(&self) {
+        let dialog = adw::MessageDialog::builder()
+            .transient_for(&self.dialog_parent())
+            .modal(true)
+            .heading("Save Filter")
+            .body("Enter a name for the current search and port type filter:")
+            .build();
+
+        let entry = gtk::Entry::builder()
+            .placeholder_text("Filter name")
+            .activates_default(true)
+            .build();
+        dialog.set_extra_child(Some(&entry));
+
+        dialog.add_response("cancel", "Cancel");
+        dialog.add_response("save", "Save");
+        dialog.set_response_appearance("save", adw::ResponseAppearance::Suggested);
+        dialog.set_default_response(Some("save"));
+        dialog.set_close_response("cancel");
+
+        dialog.connect_response(
+            None,
+            glib::clone!(
+                #[weak(rename_to = window)]
+                self,
+                #[weak]
+                entry,
+                move |dialog, response| {
+                    dialog.close();
+                    if response != "save" {
+                        return;
+                    }
+                    let name = entry.text().trim().to_string();
+                    if name.is_empty() {
+                        window.announce("Filter name cannot be empty");
+                        return;
+                    }
+                    window.save_port_filter(&name);
+                }
+            ),
+        );
+
+        dialog.present();
+        entry.grab_focus();
+    }
+
+    /// Save the current filter, replacing one with the same name
+    fn save_port_filter(&self, name: &str) {
+        let filter = self.current_port_filter();
+        {
+            let mut settings = self.imp().settings.borrow_mut();
+            match settings.saved_filters.iter_mut().find(|f| f.name == name) {
+                Some(saved) => saved.filter = filter,
+                None => settings.saved_filters.push(SavedFilter {
+                    name: name.to_string(),
+                    filter,
+                }),
+            }
+        }
+
+        if let Err(e) = self.imp().settings.borrow().save() {
+            self.announce(&format!("Failed to save filter: {}", e));
+        } else {
+            self.announce(&format!("Saved filter \"{}\"", name));
+        }
+    }
+
+    /// Show the saved filters to apply or delete one
+    pub(super) fn show_saved_filters_dialog(&self) {
+        let saved: Vec<SavedFilter> = self.imp().settings.borrow().saved_filters.clone();
+
+        if saved.is_empty() {
+            self.announce("No filters saved yet");
+            return;
+        }
+
+        let dialog = adw::MessageDialog::builder()
+            .transient_for(&self.dialog_parent())
+            .modal(true)
+            .heading("Saved Filters")
+            .body("Select a filter to apply to the port lists.")
+            .build();
+
+        let list_box = gtk::ListBox::builder()
+            .selection_mode(gtk::SelectionMode::Single)
+            .css_classes(["boxed-list"])
+            .build();
+
+        for saved_filter in &saved {
+            let row = adw::ActionRow::builder()
+                .title(saved_filter.name.as_str())
+                .subtitle(describe_filter(&saved_filter.filter))
+                .use_markup(false)
+                .activatable(true)
+                .build();
+            list_box.append(&row);
+        }
+
+        if let Some(first_row) = list_box.row_at_index(0) {
+            list_box.select_row(Some(&first_row));
+        }
+
+        let scrolled = gtk::ScrolledWindow::builder()
+            .hscrollbar_policy(gtk::PolicyType::Never)
+            .vscrollbar_policy(gtk::PolicyType::Automatic)
+            .min_content_height(100)
+            .max_content_height(300)
+            .child(&list_box)
+            .build();
+
+        dialog.set_extra_child(Some(&scrolled));
+
+        dialog.add_response("cancel", "Close");
+        dialog.add_response("delete", "Delete");
+        dialog.add_response("apply", "Apply");
+        dialog.set_response_appearance("delete", adw::ResponseAppearance::Destructive);
+        dialog.set_response_appearance("apply", adw::ResponseAppearance::Suggested);
+        dialog.set_default_response(Some("apply"));
+        dialog.set_close_response("cancel");
+
+        let dialog_weak = dialog.downgrade();
+        list_box.connect_row_activated(move |_, _| {
+            if let Some(dialog) = dialog_weak.upgrade() {
+                dialog.response("apply");
+            }
+        });
+
+        dialog.connect_response(
+            None,
+            glib::clone!(
+                #[weak(rename_to = window)]
+                self,
+                #[weak]
+                list_box,
+                move |dialog, response| {
+                    dialog.close();
+                    let selected = list_box
+                        .selected_row()
+                        .and_then(|row| saved.get(row.index() as usize));
+
+                    match (response, selected) {
+                        ("apply", Some(saved_filter)) => {
+                            window.set_port_filter(&saved_filter.filter);
+                            window.announce(&format!("Applied filter \"{}\"", saved_filter.name));
+                        }
+                        ("delete", Some(saved_filter)) => {
+                            window.delete_port_filter(&saved_filter.name);
+                        }
+                        _ => {}
+                    }
+                }
+            ),
+        );
+
+        dialog.present();
+        list_box.grab_focus();
+    }
+
+    fn delete_port_filter(&self, name: &str) {
+        self.imp()
+            .settings
+            .borrow_mut()
+            .saved_filters
+            .retain(|f| f.name != name);
+
+        if let Err(e) = self.imp().settings.borrow().save() {
+            self.announce(&format!("Failed to save settings: {}", e));
+        } else {
+            self.announce(&format!("Deleted filter \"{}\"", name));
+        }
+    }
+}
+
+/// Summarize a filter for the saved filters list, e.g. "node:firefox, audio, MIDI"
+fn describe_filter(filter: &PortFilter) -> String {
+    let mut parts = Vec::new();
+    if !filter.search.is_empty() {
+        parts.push(filter.search.clone());
+    }
+    let kinds: Vec<&str> = [
+        (filter.show_audio, "audio"),
+        (filter.show_midi, "MIDI"),
+        (filter.show_video, "video"),
+        (filter.show_monitors, "monitors"),
+    ]
+    .into_iter()
+    .filter_map(|(shown, kind)| shown.then_some(kind))
+    .collect();
+    if kinds.is_empty() {
+        parts.push("no port types".to_string());
+    } else {
+        parts.push(kinds.join(", "));
+    }
+    parts.join(", ")
+}
@@ -0,0 +1,129 @@
+use adw::subclass::prelude::*;
+
+use super::Window;
+use crate::scripting::{PortRef, ScriptCommand, ScriptHost};
+
+impl Window {
+    /// Load the user's Lua scripts
+    pub(super) fn load_scripts(&self) {
+        let host = ScriptHost::load();
+        if !host.is_empty() {
+            self.imp().script_host.replace(Some(host));
+        }
+    }
+
+    /// Run a hook in the loaded scripts, then carry out what they asked for.
+    /// Events caused by those commands run no hooks.
+    fn run_scripts(&self, hook: impl FnOnce(&ScriptHost)) {
+        if self.imp().scripts_running.get() {
+            return;
+        }
+        let commands = {
+            let host = self.imp().script_host.borrow();
+            let Some(host) = host.as_ref() else {
+                return;
+            };
+            hook(host);
+            host.take_commands()
+        };
+        self.imp().scripts_running.set(true);
+        for command in commands {
+            if let Err(e) = self.run_script_command(&command) {
+                log::warn!("Script command {:?} failed: {}", command, e);
+            }
+        }
+        self.imp().scripts_running.set(false);
+    }
+
+    pub(super) fn scripts_node_added(&self, node_id: u32) {
+        let (node, client) = {
+            let pw_state = self.imp().pw_state.borrow();
+            let Some(node) = pw_state.nodes.get(&node_id).cloned() else {
+                return;
+            };
+            (node, pw_state.node_client(node_id).cloned())
+        };
+        self.run_scripts(|host| host.node_added(&node, client.as_ref()));
+    }
+
+    pub(super) fn scripts_port_added(&self, port_id: u32) {
+        let (port, path) = {
+            let pw_state = self.imp().pw_state.borrow();
+            let Some(port) = pw_state.ports.get(&port_id).cloned() else {
+                return;
+            };
+            (port, pw_state.port_path(port_id))
+        };
+        self.run_scripts(|host| host.port_added(&port, path.as_deref()));
+    }
+
+    pub(super) fn scripts_preset_activated(&self, name: &str) {
+        self.run_scripts(|host| host.preset_activated(name));
+    }
+
+    /// Call before the request is finished, while its ports are still known
+    pub(super) fn scripts_link_failed(&self, request_id: u64, message: &str) {
+        if self.imp().script_host.borrow().is_none() {
+            return;
+        }
+        let ports = self
+            .imp()
+            .pending_links
+            .borrow()
+            .iter()
+            .find(|(_, id)| **id == request_id)
+            .map(|(ports, _)| *ports);
+        let (output, input) = {
+            let pw_state = self.imp().pw_state.borrow();
+            match ports {
+                Some((output, input)) => (pw_state.port_path(output), pw_state.port_path(input)),
+                None => (None, None),
+            }
+        };
+        self.run_scripts(|host| host.link_error(output, input, message));
+    }
+
+    fn resolve_script_port(&self, port: &PortRef) -> Result<u32, String> {
+        match port {
+            PortRef::Id(id) => Ok(*id),
+            PortRef::Path(path) => self
+                .imp()
+                .pw_state
+                .borrow()
+                .find_port_by_path(path)
+                .ok_or_else(|| format!("No port {}", path)),
+        }
+    }
+
+    fn run_script_command(&self, command: &ScriptCommand) -> Result<(), String> {
+        match command {
+            ScriptCommand::Connect(output, input) => {
+                let output = self.resolve_script_port(output)?;
+                let input = self.resolve_script_port(input)?;
+                self.control_connect(output, input)
+                    .map(|_| ())
+                    .map_err(|e| e.message)
+            }
+            ScriptCommand::Disconnect(output, input) => {
+                let output = self.resolve_script_port(output)?;
+                let input = self.resolve_script_port(input)?;
+                let link_id = self
+                    .imp()
+                    .pw_state
+                    .borrow()
+                    .find_link(output, input)
+                    .map(|link| link.id)
+                    .ok_or("The ports are not connected")?;
+                self.delete_link_remembered(link_id);
+                Ok(())
+            }
+            ScriptCommand::ActivatePreset(name) => {
+                if self.imp().preset_store.borrow().get_preset(name).is_none() {
+                    return Err(format!("No preset named \"{}\"", name));
+                }
+                self.activate_preset(name);
+                Ok(())
+            }
+        }
+    }
+}
@@ -0,0 +1,100 @@
+use gtk::glib;
+use gtk::prelude::*;
+
+use super::sorting::natural_cmp;
+use crate::model::{LinkObject, PortObject};
+use crate::settings::PortSort;
+
+/// Keeps the ports of each node (or application) together, so each gets one
+/// header. Ports sorted by most recent are not grouped.
+pub(super) fn port_section_sorter(sort: PortSort) -> Option<gtk::CustomSorter> {
+    let sorter = match sort {
+        PortSort::Name => gtk::CustomSorter::new(|a, b| {
+            let port_a = a.downcast_ref::<PortObject>().unwrap();
+            let port_b = b.downcast_ref::<PortObject>().unwrap();
+            natural_cmp(&port_a.node_name(), &port_b.node_name())
+                .then_with(|| port_a.node_id().cmp(&port_b.node_id()))
+                .into()
+        }),
+        PortSort::App => gtk::CustomSorter::new(|a, b| {
+            let port_a = a.downcast_ref::<PortObject>().unwrap();
+            let port_b = b.downcast_ref::<PortObject>().unwrap();
+            natural_cmp(&port_a.app_name(), &port_b.app_name()).into()
+        }),
+        PortSort::Recent => return None,
+    };
+    Some(sorter)
+}
+
+/// Keeps connections of the same media type together
+pub(super) fn link_section_sorter() -> gtk::CustomSorter {
+    gtk::CustomSorter::new(|a, b| {
+        let link_a = a.downcast_ref::<LinkObject>().unwrap();
+        let link_b = b.downcast_ref::<LinkObject>().unwrap();
+        link_a.media_type().cmp(&link_b.media_type()).into()
+    })
+}
+
+/// Headers naming the node or application of each group of ports
+pub(super) fn port_header_factory(sort: PortSort) -> Option<gtk::SignalListItemFactory> {
+    let title: fn(&PortObject) -> String = match sort {
+        PortSort::Name => PortObject::node_name,
+        PortSort::App => PortObject::app_name,
+        PortSort::Recent => return None,
+    };
+    Some(header_factory(move |item| {
+        item.downcast_ref::<PortObject>()
+            .map(title)
+            .unwrap_or_default()
+    }))
+}
+
+/// Headers naming the media type of each group of connections
+pub(super) fn link_header_factory() -> gtk::SignalListItemFactory {
+    header_factory(|item| {
+        item.downcast_ref::<LinkObject>()
+            .map(|link| media_heading(&link.media_type()))
+            .unwrap_or_default()
+    })
+}
+
+fn header_factory<F>(title: F) -> gtk::SignalListItemFactory
+where
+    F: Fn(&glib::Object) -> String + 'static,
+{
+    let factory = gtk::SignalListItemFactory::new();
+
+    factory.connect_setup(|_, header| {
+        let header = header.downcast_ref::<gtk::ListHeader>().unwrap();
+        // Read as a heading so screen reader users can jump between groups
+        let label = gtk::Label::builder()
+            .halign(gtk::Align::Start)
+            .xalign(0.0)
+            .margin_start(6)
+            .margin_end(6)
+            .margin_top(8)
+            .margin_bottom(2)
+            .css_classes(["heading"])
+            .accessible_role(gtk::AccessibleRole::Heading)
+            .build();
+        header.set_child(Some(&label));
+    });
+
+    factory.connect_bind(move |_, header| {
+        let header = header.downcast_ref::<gtk::ListHeader>().unwrap();
+        let label = header.child().and_downcast::<gtk::Label>().unwrap();
+        let text = header.item().map(|item| title(&item)).unwrap_or_default();
+        label.set_text(&text);
+    });
+
+    factory
+}
+
+fn media_heading(media_type: &str) -> String {
+    match media_type {
+        "audio" => "Audio".to_string(),
+        "midi" => "MIDI".to_string(),
+        "video" => "Video".to_string(),
+        other => other.to_string(),
+    }
+}
@@ -0,0 +1,176 @@
+use adw::prelude::*;
+use adw::subclass::prelude::*;
+use gtk::{gio, glib};
+
+use super::Window;
+use crate::pipewire::{remote, PipeWireThread};
+
+/// Said when a preset is used in a server's tab
+pub(super) const PRESETS_MAIN_SERVER_ONLY: &str =
+    "Presets are for the main server; switch to its tab to use them";
+
+/// Actions on presets, whose port names belong to the main server
+const PRESET_ACTIONS: [&str; 8] = [
+    "save-preset",
+    "load-preset",
+    "preset-trash",
+    "export-preset-wireplumber",
+    "plug-presets",
+    "import-config",
+    "deactivate-preset",
+    "compact-mode",
+];
+
+impl Window {
+    /// Whether this window shows another PipeWire server than the main
+    /// window. Presets, remote control and scripts act on the main one only.
+    pub fn is_server_window(&self) -> bool {
+        self.imp().server.borrow().is_some()
+    }
+
+    /// Socket of the server this window is connected to
+    pub(super) fn server_name(&self) -> String {
+        self.server().unwrap_or_else(remote::remote_name)
+    }
+
+    /// Keep the connection to this window's server until its tab is closed
+    pub fn set_server_backend(&self, backend: PipeWireThread) {
+        self.imp().server_backend.replace(Some(backend));
+    }
+
+    /// Disconnect from this window's server
+    pub fn close_server_connection(&self) {
+        // Dropping the thread shuts it down
+        self.imp().server_backend.take();
+    }
+
+    /// Grey out the preset menu items of a server's window, as presets saved
+    /// or activated there would be applied to the main server
+    pub(super) fn disable_preset_actions(&self) {
+        for name in PRESET_ACTIONS {
+            if let Some(action) = self.lookup_action(name).and_downcast::<gio::SimpleAction>() {
+                action.set_enabled(false);
+            }
+        }
+    }
+
+    /// Window that dialogs of this window belong to: the main window while
+    /// this window's content is shown in one of its tabs
+    pub(super) fn dialog_parent(&self) -> gtk::Window {
+        self.imp()
+            .toast_overlay
+            .root()
+            .and_downcast::<gtk::Window>()
+            .unwrap_or_else(|| self.clone().upcast())
+    }
+
+    /// Show the window of another server as a tab of this window. The
+    /// server's window stays hidden, keeping that server's state and
+    /// actions, while its content moves into the tab.
+    pub fn add_server_tab(&self, window: &Window) {
+        let tab_view = self.server_tab_view();
+
+        let content = window.imp().toast_overlay.get();
+        window.set_content(None::<&gtk::Widget>);
+        // Menus and shortcuts in the tab act on the server's window
+        content.insert_action_group("win", Some(window));
+        // This window's own title bar buttons close or minimize it
+        let header_bar = &window.imp().header_bar;
+        header_bar.set_show_start_title_buttons(false);
+        header_bar.set_show_end_title_buttons(false);
+
+        let server = window.server_name();
+        let page = tab_view.append(&content);
+        page.set_title(&server);
+        self.imp()
+            .server_pages
+            .borrow_mut()
+            .push((page.clone(), window.clone()));
+        tab_view.set_selected_page(&page);
+        self.announce(&format!("Opened {} in a new tab", server));
+    }
+
+    /// Switch to the tab of `server` if it is open, this window's own
+    /// server included
+    pub fn select_server_tab(&self, server: &str) -> bool {
+        let Some(tab_view) = self.imp().server_tabs.borrow().clone() else {
+            return server == self.server_name();
+        };
+        let page = if server == self.server_name() {
+            tab_view.nth_page(0)
+        } else {
+            let pages = self.imp().server_pages.borrow();
+            match pages
+                .iter()
+                .find(|(_, window)| window.server().as_deref() == Some(server))
+            {
+                Some((page, _)) => page.clone(),
+                None => return false,
+            }
+        };
+        tab_view.set_selected_page(&page);
+        true
+    }
+
+    /// The tabs holding this window's content, the other servers' and
+    /// tools such as MIDI Ports, put around the content when the first
+    /// other tab is opened
+    pub(super) fn server_tab_view(&self) -> adw::TabView {
+        if let Some(tab_view) = self.imp().server_tabs.borrow().as_ref() {
+            return tab_view.clone();
+        }
+
+        let content = self.imp().toast_overlay.get();
+        self.set_content(None::<&gtk::Widget>);
+
+        let tab_view = adw::TabView::builder().vexpand(true).build();
+        // Pinned, so the main server's tab can't be closed
+        let page = tab_view.append_pinned(&content);
+        page.set_title(&self.server_name());
+        tab_view.connect_close_page(glib::clone!(
+            #[weak(rename_to = window)]
+            self,
+            #[upgrade_or]
+            glib::Propagation::Proceed,
+            move |tab_view, page| window.server_tab_closing(tab_view, page)
+        ));
+
+        // Hidden again while only the main server's tab is left
+        let tab_bar = adw::TabBar::builder()
+            .view(&tab_view)
+            .autohide(true)
+            .build();
+        let layout = gtk::Box::builder()
+            .orientation(gtk::Orientation::Vertical)
+            .build();
+        layout.append(&tab_bar);
+        layout.append(&tab_view);
+        self.set_content(Some(&layout));
+
+        self.imp().server_tabs.replace(Some(tab_view.clone()));
+        tab_view
+    }
+
+    /// Disconnect from a server as its tab is closed
+    fn server_tab_closing(
+        &self,
+        tab_view: &adw::TabView,
+        page: &adw::TabPage,
+    ) -> glib::Propagation {
+        let window = {
+            let mut pages = self.imp().server_pages.borrow_mut();
+            let Some(index) = pages.iter().position(|(p, _)| p == page) else {
+                // The main server's tab, kept open by the default handler
+                // as it is pinned, or a tool's tab, closed by it
+                return glib::Propagation::Proceed;
+            };
+            pages.remove(index).1
+        };
+
+        tab_view.close_page_finish(page, true);
+        window.close_midi_view();
+        window.close_server_connection();
+        window.destroy();
+        glib::Propagation::Stop
+    }
+}
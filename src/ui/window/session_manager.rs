@@ -0,0 +1,247 @@
+use std::io::ErrorKind;
+use std::time::{Duration, Instant};
+
+use adw::prelude::*;
+use adw::subclass::prelude::*;
+use gtk::glib;
+
+use super::Window;
+use crate::pipewire::state::PwNode;
+use crate::sandbox::host_command;
+
+/// A link of ours removed this soon after it appeared was most likely
+/// removed by the session manager restoring its own routing
+const CONFLICT_WINDOW: Duration = Duration::from_secs(2);
+
+/// How long the conflict warning stays up, in seconds
+const CONFLICT_TOAST_TIMEOUT: u32 = 10;
+
+/// Ask the session manager to route a stream to a node by setting
+/// `target.object` in the default metadata, which WirePlumber honours when
+/// it decides where the stream belongs. `remote` is the socket of the
+/// server, or `None` for the default one.
+fn set_stream_target(node_id: u32, target_name: &str, remote: Option<&str>) -> Result<(), String> {
+    let mut command = host_command("pw-metadata");
+    if let Some(remote) = remote {
+        command.args(["--remote", remote]);
+    }
+    let output = command
+        .args([&node_id.to_string(), "target.object", target_name])
+        .output()
+        .map_err(|e| match e.kind() {
+            ErrorKind::NotFound => "pw-metadata was not found".to_string(),
+            _ => format!("Failed to run pw-metadata: {}", e),
+        })?;
+
+    if !output.status.success() {
+        let stderr = String::from_utf8_lossy(&output.stderr);
+        return Err(format!(
+            "Failed to set the stream target: {}",
+            stderr.trim()
+        ));
+    }
+    Ok(())
+}
+
+/// The stream end of a link and the node to pin it to, as node ids. A
+/// playback stream (Stream/Output) is pinned to the node it plays into, a
+/// recording stream (Stream/Input) to the node it records from; links
+/// between two devices have no stream to pin.
+fn pin_ends(output: &PwNode, input: &PwNode) -> Option<(u32, u32)> {
+    let has_class = |node: &PwNode, prefix: &str| {
+        node.media_class
+            .as_deref()
+            .is_some_and(|class| class.starts_with(prefix))
+    };
+    if has_class(output, "Stream/Output") {
+        Some((output.id, input.id))
+    } else if has_class(input, "Stream/Input") {
+        Some((input.id, output.id))
+    } else {
+        None
+    }
+}
+
+impl Window {
+    /// Note a link created on our request, to recognise it being undone
+    pub(super) fn track_created_link(&self, link_id: u32) {
+        let mut created = self.imp().created_links.borrow_mut();
+        created.retain(|_, at| at.elapsed() < CONFLICT_WINDOW);
+        created.insert(link_id, Instant::now());
+    }
+
+    /// Note a link we are removing ourselves, which is no conflict
+    pub(super) fn track_deleted_link(&self, link_id: u32) {
+        self.imp().deleting_links.borrow_mut().insert(link_id);
+    }
+
+    /// Call before a removed link leaves the state. Warns when a link we just
+    /// created was removed by someone else while both its ports remain.
+    pub(super) fn check_session_manager_conflict(&self, link_id: u32) {
+        let deleted_by_us = self.imp().deleting_links.borrow_mut().remove(&link_id);
+        let created_at = self.imp().created_links.borrow_mut().remove(&link_id);
+        if deleted_by_us || !created_at.is_some_and(|at| at.elapsed() < CONFLICT_WINDOW) {
+            return;
+        }
+
+        let (output_port_id, input_port_id, output_node_id, pin) = {
+            let pw_state = self.imp().pw_state.borrow();
+            let Some(link) = pw_state.links.get(&link_id) else {
+                return;
+            };
+            let (Some(output), Some(input)) = (
+                pw_state.ports.get(&link.output_port_id),
+                pw_state.ports.get(&link.input_port_id),
+            ) else {
+                return;
+            };
+            let pin = match (
+                pw_state.nodes.get(&output.node_id),
+                pw_state.nodes.get(&input.node_id),
+            ) {
+                (Some(output_node), Some(input_node)) => pin_ends(output_node, input_node),
+                _ => None,
+            };
+            (output.id, input.id, output.node_id, pin)
+        };
+
+        let label = self
+            .link_label(link_id)
+            .unwrap_or_else(|| format!("link {}", link_id));
+        log::warn!(
+            "Link {} was removed right after it was created, likely by the session manager",
+            label
+        );
+        self.record_activity(&format!("Session manager removed {}", label));
+
+        // Warn once per stream, as a preset fighting the session manager
+        // would otherwise repeat it on every retry
+        if !self
+            .imp()
+            .conflict_warned_nodes
+            .borrow_mut()
+            .insert(output_node_id)
+        {
+            return;
+        }
+
+        let message = format!(
+            "The session manager removed {} right after it was connected",
+            label
+        );
+        // Between two devices there is no stream for the session manager to
+        // keep on a target, so only warn
+        let Some((stream_node_id, target_node_id)) = pin else {
+            self.show_toast(&message);
+            self.announce(&message);
+            return;
+        };
+
+        let toast = adw::Toast::builder()
+            .title(&message)
+            .use_markup(false)
+            .button_label("Pin Target")
+            .timeout(CONFLICT_TOAST_TIMEOUT)
+            .build();
+        toast.connect_button_clicked(glib::clone!(
+            #[weak(rename_to = window)]
+            self,
+            move |_| {
+                window.pin_stream_target(
+                    output_port_id,
+                    input_port_id,
+                    stream_node_id,
+                    target_node_id,
+                )
+            }
+        ));
+        self.imp().toast_overlay.add_toast(toast);
+        self.announce(&format!(
+            "{}. Choose Pin Target in the notification to make it keep this routing",
+            message
+        ));
+    }
+
+    /// Tell the session manager the stream belongs on the node at the other
+    /// end of the link, then connect the ports again
+    fn pin_stream_target(
+        &self,
+        output_port_id: u32,
+        input_port_id: u32,
+        stream_node_id: u32,
+        target_node_id: u32,
+    ) {
+        let target = {
+            let pw_state = self.imp().pw_state.borrow();
+            pw_state
+                .nodes
+                .get(&target_node_id)
+                .map(|n| (n.name.clone(), n.display_name().to_string()))
+        };
+        let Some((target_name, target_label)) = target else {
+            self.announce("The device is no longer available");
+            return;
+        };
+
+        if let Err(message) =
+            set_stream_target(stream_node_id, &target_name, self.server().as_deref())
+        {
+            self.show_toast(&message);
+            self.announce_error(&message);
+            return;
+        }
+
+        let connected = self
+            .imp()
+            .pw_state
+            .borrow()
+            .link_exists(output_port_id, input_port_id);
+        if !connected {
+            self.create_link(output_port_id, input_port_id);
+        }
+        self.announce(&format!(
+            "Asked the session manager to keep the stream on {}",
+            target_label
+        ));
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn node(id: u32, media_class: &str) -> PwNode {
+        PwNode {
+            id,
+            name: format!("node{}", id),
+            media_class: Some(media_class.to_string()),
+            description: None,
+            application_name: None,
+            serial: None,
+            nick: None,
+            device_name: None,
+            client_id: None,
+        }
+    }
+
+    #[test]
+    fn playback_stream_is_pinned_to_its_sink() {
+        let stream = node(1, "Stream/Output/Audio");
+        let sink = node(2, "Audio/Sink");
+        assert_eq!(pin_ends(&stream, &sink), Some((1, 2)));
+    }
+
+    #[test]
+    fn recording_stream_is_pinned_to_its_source() {
+        let source = node(1, "Audio/Source");
+        let stream = node(2, "Stream/Input/Audio");
+        assert_eq!(pin_ends(&source, &stream), Some((2, 1)));
+    }
+
+    #[test]
+    fn device_links_have_nothing_to_pin() {
+        let source = node(1, "Audio/Source");
+        let sink = node(2, "Audio/Sink");
+        assert_eq!(pin_ends(&source, &sink), None);
+    }
+}
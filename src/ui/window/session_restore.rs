@@ -0,0 +1,201 @@
+use adw::prelude::*;
+use adw::subclass::prelude::*;
+use gtk::{gio, glib};
+use std::collections::HashSet;
+
+use super::bulk_progress::BulkOperation;
+use super::Window;
+use crate::presets::PresetConnection;
+use crate::session_snapshot::SessionSnapshot;
+
+/// Seconds between snapshots of the connections, taken only if they changed
+const SNAPSHOT_INTERVAL: u32 = 30;
+
+/// Seconds to wait after the graph loaded before counting the missing
+/// connections, as the session manager relinks streams in that time
+const RESTORE_OFFER_DELAY: u32 = 5;
+
+/// How long the offer to restore the previous session stays up, in seconds
+const RESTORE_TOAST_TIMEOUT: u32 = 20;
+
+impl Window {
+    /// Keep the snapshot of the last run if it didn't end in a normal quit,
+    /// and start taking snapshots of this one
+    pub(super) fn start_session_snapshots(&self) {
+        if let Some(snapshot) = SessionSnapshot::load() {
+            if !snapshot.clean_exit && !snapshot.connections.is_empty() {
+                log::info!(
+                    "The last session did not quit normally, {} connections can be restored",
+                    snapshot.connections.len()
+                );
+                self.set_restorable_session(Some(snapshot));
+            }
+        }
+
+        glib::timeout_add_seconds_local(
+            SNAPSHOT_INTERVAL,
+            glib::clone!(
+                #[weak(rename_to = window)]
+                self,
+                #[upgrade_or]
+                glib::ControlFlow::Break,
+                move || {
+                    if window.imp().session_snapshot_dirty.replace(false) {
+                        window.save_session_snapshot(false);
+                    }
+                    glib::ControlFlow::Continue
+                }
+            ),
+        );
+    }
+
+    /// Snapshot the connections at the next interval
+    pub(super) fn session_links_changed(&self) {
+        self.imp().session_snapshot_dirty.set(true);
+    }
+
+    /// The connections in the graph, without duplicates or the links of
+    /// our own MIDI monitors
+    fn session_snapshot(&self) -> SessionSnapshot {
+        let pw_state = self.imp().pw_state.borrow();
+        let mut links: Vec<_> = pw_state.links.values().collect();
+        links.sort_by_key(|link| link.id);
+        let mut seen = HashSet::new();
+        let connections = links
+            .into_iter()
+            .filter(|link| seen.insert((link.output_port_id, link.input_port_id)))
+            .filter_map(|link| PresetConnection::from_link(&pw_state, link))
+            .collect();
+        SessionSnapshot::new(connections)
+    }
+
+    fn save_session_snapshot(&self, clean_exit: bool) {
+        let mut snapshot = self.session_snapshot();
+        snapshot.clean_exit = clean_exit;
+        if let Err(e) = snapshot.save() {
+            log::warn!("{}", e);
+        }
+    }
+
+    /// Take a last snapshot as the app quits, marked so the next start
+    /// doesn't offer to restore it
+    pub fn save_session_on_quit(&self) {
+        self.save_session_snapshot(true);
+    }
+
+    /// Keep the connections of a server that went away, to offer them back
+    /// once it is running again. Call before the graph is cleared.
+    pub(super) fn session_disconnected(&self) {
+        let snapshot = self.session_snapshot();
+        if snapshot.connections.is_empty() {
+            return;
+        }
+        if let Err(e) = snapshot.save() {
+            log::warn!("{}", e);
+        }
+        self.imp().session_snapshot_dirty.set(false);
+        self.set_restorable_session(Some(snapshot));
+    }
+
+    fn set_restorable_session(&self, snapshot: Option<SessionSnapshot>) {
+        let available = snapshot.is_some();
+        self.imp().restorable_session.replace(snapshot);
+        if let Some(action) = self
+            .lookup_action("restore-session")
+            .and_downcast::<gio::SimpleAction>()
+        {
+            action.set_enabled(available);
+        }
+    }
+
+    /// Number of connections of the snapshot that aren't in the graph now
+    fn missing_session_links(&self, snapshot: &SessionSnapshot) -> usize {
+        let pw_state = self.imp().pw_state.borrow();
+        snapshot
+            .connections
+            .iter()
+            .filter(|conn| match conn.find_ports(&pw_state) {
+                (Some(output), Some(input)) => !pw_state.link_exists(output.id, input.id),
+                _ => true,
+            })
+            .count()
+    }
+
+    /// Offer to restore the previous session's connections shortly after
+    /// the graph has loaded, if some of them are still missing then
+    pub(super) fn offer_session_restore(&self) {
+        if self.imp().restorable_session.borrow().is_none() {
+            return;
+        }
+        glib::timeout_add_seconds_local_once(
+            RESTORE_OFFER_DELAY,
+            glib::clone!(
+                #[weak(rename_to = window)]
+                self,
+                move || window.show_session_restore_offer()
+            ),
+        );
+    }
+
+    fn show_session_restore_offer(&self) {
+        let missing = match self.imp().restorable_session.borrow().as_ref() {
+            Some(snapshot) => self.missing_session_links(snapshot),
+            None => return,
+        };
+        if missing == 0 {
+            // PipeWire kept every link, there is nothing to restore
+            self.set_restorable_session(None);
+            return;
+        }
+
+        let message = if missing == 1 {
+            "1 connection from the previous session is missing".to_string()
+        } else {
+            format!(
+                "{} connections from the previous session are missing",
+                missing
+            )
+        };
+        let toast = adw::Toast::builder()
+            .title(&message)
+            .use_markup(false)
+            .button_label("Restore")
+            .timeout(RESTORE_TOAST_TIMEOUT)
+            .build();
+        toast.connect_button_clicked(glib::clone!(
+            #[weak(rename_to = window)]
+            self,
+            move |_| window.restore_previous_session()
+        ));
+        self.imp().toast_overlay.add_toast(toast);
+        self.announce(&format!(
+            "{}. Choose Restore in the notification, or Restore Previous Session Routing \
+             in the Tools menu",
+            message
+        ));
+    }
+
+    /// Recreate the connections of the previous session whose ports exist
+    pub(super) fn restore_previous_session(&self) {
+        let Some(snapshot) = self.imp().restorable_session.borrow().clone() else {
+            self.announce("No previous session routing to restore");
+            return;
+        };
+
+        let (request_ids, skipped) = self.create_missing_links(&snapshot.connections);
+        if request_ids.is_empty() {
+            self.announce(&format!(
+                "All {} connections of the previous session already exist or their ports \
+                 are missing",
+                skipped
+            ));
+            return;
+        }
+        self.begin_bulk_operation(BulkOperation::new(
+            "Restoring session routing".to_string(),
+            "Restored connections".to_string(),
+            request_ids,
+            skipped,
+        ));
+    }
+}
@@ -0,0 +1,32 @@
+use adw::subclass::prelude::*;
+
+use super::Window;
+use crate::gsettings;
+
+impl Window {
+    /// Move settings between settings.json and GSettings, copying the
+    /// current values so nothing changes but where they are kept. Returns
+    /// whether the switch was made.
+    pub(super) fn set_settings_in_gsettings(&self, enabled: bool) -> bool {
+        let result = if enabled {
+            gsettings::save(&self.imp().settings.borrow())
+                .and_then(|()| gsettings::set_enabled(true))
+        } else {
+            gsettings::set_enabled(false).and_then(|()| self.imp().settings.borrow().save())
+        };
+
+        if let Err(e) = result {
+            self.announce(&format!("Failed to save settings: {}", e));
+            return false;
+        }
+
+        let message = if enabled {
+            "Settings are now stored in GSettings"
+        } else {
+            "Settings are now stored in settings.json"
+        };
+        self.record_activity(message);
+        self.announce(message);
+        true
+    }
+}
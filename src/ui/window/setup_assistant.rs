@@ -0,0 +1,303 @@
+use std::cell::Cell;
+use std::rc::Rc;
+
+use adw::prelude::*;
+use adw::subclass::prelude::*;
+use gtk::glib;
+
+use super::Window;
+
+/// Most names listed per kind on the first page
+const MAX_LISTED: usize = 6;
+
+/// A page of the assistant: a heading, its text and optional extra widgets
+struct Page {
+    heading: String,
+    text: String,
+    widget: gtk::Box,
+}
+
+impl Page {
+    fn new(heading: &str, text: String) -> Self {
+        let widget = gtk::Box::builder()
+            .orientation(gtk::Orientation::Vertical)
+            .spacing(12)
+            .margin_start(24)
+            .margin_end(24)
+            .margin_top(12)
+            .margin_bottom(12)
+            .build();
+        widget.append(
+            &gtk::Label::builder()
+                .label(heading)
+                .xalign(0.0)
+                .css_classes(["title-2"])
+                .build(),
+        );
+        widget.append(
+            &gtk::Label::builder()
+                .label(&text)
+                .xalign(0.0)
+                .wrap(true)
+                .build(),
+        );
+        Self {
+            heading: heading.to_string(),
+            text,
+            widget,
+        }
+    }
+}
+
+/// "A, B and 3 more", or `none` for an empty list
+fn name_list(mut names: Vec<String>, none: &str) -> String {
+    if names.is_empty() {
+        return none.to_string();
+    }
+    names.sort();
+    names.dedup();
+    let more = names.len().saturating_sub(MAX_LISTED);
+    names.truncate(MAX_LISTED);
+    if more > 0 {
+        format!("{} and {} more", names.join(", "), more)
+    } else {
+        names.join(", ")
+    }
+}
+
+impl Window {
+    /// Show the setup assistant once, after the first connection to
+    /// PipeWire of a new installation
+    pub(super) fn maybe_show_setup_assistant(&self) {
+        if self.imp().settings.borrow().setup_assistant_done
+            || self.is_server_window()
+            || !self.is_visible()
+        {
+            return;
+        }
+        self.show_setup_assistant();
+    }
+
+    /// What the assistant found, by kind
+    fn detected_summary(&self) -> String {
+        let pw_state = self.imp().pw_state.borrow();
+        let names_of = |class: &str| -> Vec<String> {
+            pw_state
+                .nodes
+                .values()
+                .filter(|n| n.media_class.as_deref() == Some(class))
+                .map(|n| n.app_name().to_string())
+                .collect()
+        };
+        format!(
+            "Applications playing audio: {}.\nApplications recording: {}.\n\
+             Speakers and other outputs: {}.\nMicrophones and other inputs: {}.",
+            name_list(names_of("Stream/Output/Audio"), "none yet"),
+            name_list(names_of("Stream/Input/Audio"), "none yet"),
+            name_list(names_of("Audio/Sink"), "none found"),
+            name_list(names_of("Audio/Source"), "none found"),
+        )
+    }
+
+    /// Walk through what the window shows, how to move around it with the
+    /// keyboard and a screen reader, and offer to save the current
+    /// connections as a first preset
+    pub(super) fn show_setup_assistant(&self) {
+        let connection_count = self.imp().pw_state.borrow().links.len();
+
+        let mut pages = vec![
+            Page::new(
+                "Welcome to PW Audioshare",
+                format!(
+                    "PW Audioshare connects the audio of applications and devices through \
+                     PipeWire. This is what is running now:\n\n{}",
+                    self.detected_summary()
+                ),
+            ),
+            Page::new(
+                "Two Lists of Ports",
+                "The window has two lists. Outputs, on the left, are where audio comes from: \
+                 applications playing and microphones. Inputs, on the right, are where it goes: \
+                 speakers, headphones and applications recording. Each channel is a separate \
+                 port, such as front left and front right.\n\n\
+                 To connect, select ports in both lists and press Control+Return. Or press \
+                 Return on an output to arm it, then Return on an input to connect them. \
+                 Existing connections are in the list below; press Delete there to remove one."
+                    .to_string(),
+            ),
+            Page::new(
+                "Moving Around with the Keyboard",
+                "Up and Down move between ports and Space selects them. Left and Right switch \
+                 between the output and input lists. F6 or Control+Down moves to the \
+                 connections list, and Control+Up returns. Type in the search box to filter \
+                 both lists. Control+Question mark lists every shortcut."
+                    .to_string(),
+            ),
+            Page::new(
+                "Using a Screen Reader",
+                "Each port is read with its application or device and channel. Connections, \
+                 disconnections and devices appearing are announced; choose how much is \
+                 spoken under Announcements in Tools → Preferences (Tools button), \
+                 from Silent to Verbose. F9 opens the activity history, a list of everything \
+                 announced, for reviewing what happened at your own pace. With Orca, leave \
+                 browse mode off so the arrow keys reach the lists."
+                    .to_string(),
+            ),
+        ];
+
+        let preset_name = gtk::Entry::builder().text("Starter").build();
+        preset_name.update_property(&[gtk::accessible::Property::Label("Preset name")]);
+        let create_preset = gtk::CheckButton::builder()
+            .label("Save the current connections as a preset")
+            .active(connection_count > 0)
+            .sensitive(connection_count > 0)
+            .build();
+        let preset_page = Page::new(
+            "A First Preset",
+            if connection_count > 0 {
+                format!(
+                    "A preset remembers connections so they can be restored with one action, \
+                     or kept connected automatically whenever the applications start. There \
+                     are {} connections now.",
+                    connection_count
+                )
+            } else {
+                "A preset remembers connections so they can be restored with one action. \
+                 There are no connections yet; once you have made some, choose Save Preset \
+                 from the preset menu."
+                    .to_string()
+            },
+        );
+        preset_page.widget.append(&create_preset);
+        preset_page.widget.append(&preset_name);
+        create_preset
+            .bind_property("active", &preset_name, "sensitive")
+            .sync_create()
+            .build();
+        pages.push(preset_page);
+
+        let assistant = adw::Window::builder()
+            .transient_for(&self.dialog_parent())
+            .modal(true)
+            .title("Setup Assistant")
+            .default_width(560)
+            .default_height(460)
+            .build();
+
+        let stack = gtk::Stack::builder()
+            .vexpand(true)
+            .transition_type(gtk::StackTransitionType::SlideLeftRight)
+            .build();
+        for (i, page) in pages.iter().enumerate() {
+            stack.add_named(&page.widget, Some(&i.to_string()));
+        }
+
+        let back = gtk::Button::with_label("Back");
+        let next = gtk::Button::builder()
+            .label("Next")
+            .css_classes(["suggested-action"])
+            .build();
+        let header = adw::HeaderBar::new();
+        header.pack_start(&back);
+        header.pack_end(&next);
+
+        let content = gtk::Box::builder()
+            .orientation(gtk::Orientation::Vertical)
+            .build();
+        content.append(&header);
+        content.append(&stack);
+        assistant.set_content(Some(&content));
+
+        let summaries: Vec<String> = pages
+            .iter()
+            .map(|page| format!("{}. {}", page.heading, page.text))
+            .collect();
+        let page_count = pages.len();
+        let current = Rc::new(Cell::new(0usize));
+
+        let show_page = glib::clone!(
+            #[weak(rename_to = window)]
+            self,
+            #[weak]
+            stack,
+            #[weak]
+            back,
+            #[weak]
+            next,
+            #[strong]
+            current,
+            move |index: usize| {
+                current.set(index);
+                stack.set_visible_child_name(&index.to_string());
+                back.set_sensitive(index > 0);
+                next.set_label(if index + 1 == page_count { "Finish" } else { "Next" });
+                next.grab_focus();
+                window.announce(&format!(
+                    "Step {} of {}. {}",
+                    index + 1,
+                    page_count,
+                    summaries[index]
+                ));
+            }
+        );
+        show_page(0);
+
+        let go_back = show_page.clone();
+        back.connect_clicked(glib::clone!(
+            #[strong]
+            current,
+            move |_| {
+                if current.get() > 0 {
+                    go_back(current.get() - 1);
+                }
+            }
+        ));
+        next.connect_clicked(glib::clone!(
+            #[weak(rename_to = window)]
+            self,
+            #[weak]
+            assistant,
+            #[weak]
+            create_preset,
+            #[weak]
+            preset_name,
+            move |_| {
+                if current.get() + 1 < page_count {
+                    show_page(current.get() + 1);
+                    return;
+                }
+                if create_preset.is_active() {
+                    let name = preset_name.text().trim().to_string();
+                    if name.is_empty() {
+                        window.announce("Preset name cannot be empty");
+                        return;
+                    }
+                    if window.imp().preset_store.borrow().get_preset(&name).is_some() {
+                        window.announce(&format!("A preset named \"{}\" already exists", name));
+                        return;
+                    }
+                    window.save_preset(&name, false);
+                }
+                assistant.close();
+            }
+        ));
+
+        // Closing at any step counts as done; the assistant stays in the menu
+        assistant.connect_close_request(glib::clone!(
+            #[weak(rename_to = window)]
+            self,
+            #[upgrade_or]
+            glib::Propagation::Proceed,
+            move |_| {
+                window.imp().settings.borrow_mut().setup_assistant_done = true;
+                if let Err(e) = window.imp().settings.borrow().save() {
+                    window.announce(&format!("Failed to save settings: {}", e));
+                }
+                glib::Propagation::Proceed
+            }
+        ));
+
+        assistant.present();
+        next.grab_focus();
+    }
+}
@@ -0,0 +1,205 @@
+use std::cmp::Ordering;
+
+use adw::prelude::*;
+use adw::subclass::prelude::*;
+
+use super::{sections, Window};
+use crate::model::PortObject;
+use crate::settings::PortSort;
+
+/// Channel positions in the order PipeWire lays them out, so FL comes
+/// before FR and surround channels follow the front ones
+const CHANNEL_ORDER: &[&str] = &[
+    "MONO", "FL", "FR", "FC", "LFE", "SL", "SR", "FLC", "FRC", "RC", "RL", "RR", "TC", "TFL",
+    "TFC", "TFR", "TRL", "TRC", "TRR", "RLC", "RRC", "FLW", "FRW", "LFE2", "FLH", "FCH", "FRH",
+    "TFLC", "TFRC", "TSL", "TSR", "LLFE", "RLFE", "BC", "BLC", "BRC",
+];
+
+/// Compare strings so that runs of digits compare by value: "Port 2"
+/// sorts before "Port 10". Letters compare without regard to case.
+pub(super) fn natural_cmp(a: &str, b: &str) -> Ordering {
+    let mut a_chars = a.chars().peekable();
+    let mut b_chars = b.chars().peekable();
+
+    loop {
+        match (a_chars.peek().copied(), b_chars.peek().copied()) {
+            (None, None) => return a.cmp(b),
+            (None, Some(_)) => return Ordering::Less,
+            (Some(_), None) => return Ordering::Greater,
+            (Some(ca), Some(cb)) if ca.is_ascii_digit() && cb.is_ascii_digit() => {
+                let num_a = take_digits(&mut a_chars);
+                let num_b = take_digits(&mut b_chars);
+                // Without leading zeros, a longer run is a larger number
+                let ordering = num_a
+                    .len()
+                    .cmp(&num_b.len())
+                    .then_with(|| num_a.cmp(&num_b));
+                if ordering != Ordering::Equal {
+                    return ordering;
+                }
+            }
+            (Some(ca), Some(cb)) => {
+                let ordering = ca.to_lowercase().cmp(cb.to_lowercase());
+                if ordering != Ordering::Equal {
+                    return ordering;
+                }
+                a_chars.next();
+                b_chars.next();
+            }
+        }
+    }
+}
+
+/// Consume a run of digits, dropping leading zeros
+fn take_digits(chars: &mut std::iter::Peekable<std::str::Chars>) -> String {
+    let mut digits = String::new();
+    while let Some(c) = chars.peek().copied().filter(|c| c.is_ascii_digit()) {
+        chars.next();
+        if !(digits.is_empty() && c == '0') {
+            digits.push(c);
+        }
+    }
+    digits
+}
+
+/// Position of a channel: known positions first in layout order, then
+/// AUX0, AUX1, ... by number, then anything else
+fn channel_rank(channel: &str) -> (u32, u32) {
+    if let Some(index) = CHANNEL_ORDER.iter().position(|&c| c == channel) {
+        return (0, index as u32);
+    }
+    if let Some(n) = channel
+        .strip_prefix("AUX")
+        .and_then(|n| n.parse::<u32>().ok())
+    {
+        return (1, n);
+    }
+    (2, 0)
+}
+
+/// Order two ports of the same node: playback before monitor ports, then by
+/// channel position, then by name
+fn compare_within_node(a: &PortObject, b: &PortObject) -> Ordering {
+    let monitor_a = a.name().starts_with("monitor_");
+    let monitor_b = b.name().starts_with("monitor_");
+    monitor_a
+        .cmp(&monitor_b)
+        .then_with(|| channel_rank(&a.channel()).cmp(&channel_rank(&b.channel())))
+        .then_with(|| natural_cmp(&a.name(), &b.name()))
+}
+
+/// Order ports by node, then within each node
+fn compare_by_node(a: &PortObject, b: &PortObject) -> Ordering {
+    natural_cmp(&a.node_name(), &b.node_name())
+        .then_with(|| a.node_id().cmp(&b.node_id()))
+        .then_with(|| compare_within_node(a, b))
+}
+
+fn port_sorter(sort: PortSort) -> gtk::CustomSorter {
+    gtk::CustomSorter::new(move |a, b| {
+        let port_a = a.downcast_ref::<PortObject>().unwrap();
+        let port_b = b.downcast_ref::<PortObject>().unwrap();
+        let ordering = match sort {
+            PortSort::Name => compare_by_node(port_a, port_b),
+            PortSort::App => natural_cmp(&port_a.app_name(), &port_b.app_name())
+                .then_with(|| compare_by_node(port_a, port_b)),
+            PortSort::Recent => port_b
+                .added_time()
+                .cmp(&port_a.added_time())
+                .then_with(|| compare_by_node(port_a, port_b)),
+        };
+        ordering.into()
+    })
+}
+
+impl Window {
+    /// Apply the sort order from settings to both port lists
+    pub(super) fn apply_port_sort(&self) {
+        let sort = self.imp().settings.borrow().port_sort;
+
+        for is_output in [true, false] {
+            let (sort_model, list_view) = if is_output {
+                (
+                    self.imp().output_sort_model.borrow().clone(),
+                    self.imp().output_list_view.borrow().clone(),
+                )
+            } else {
+                (
+                    self.imp().input_sort_model.borrow().clone(),
+                    self.imp().input_list_view.borrow().clone(),
+                )
+            };
+            let (Some(sort_model), Some(list_view)) = (sort_model, list_view) else {
+                continue;
+            };
+
+            sort_model.set_sorter(Some(&port_sorter(sort)));
+            sort_model.set_section_sorter(sections::port_section_sorter(sort).as_ref());
+            list_view.set_header_factory(sections::port_header_factory(sort).as_ref());
+        }
+    }
+
+    /// Change the order of the port lists and save the choice
+    pub(super) fn set_port_sort(&self, sort: PortSort) {
+        self.imp().settings.borrow_mut().port_sort = sort;
+        self.apply_port_sort();
+
+        if let Err(e) = self.imp().settings.borrow().save() {
+            self.announce(&format!("Failed to save settings: {}", e));
+            return;
+        }
+
+        self.announce(match sort {
+            PortSort::Name => "Sorted by name",
+            PortSort::App => "Sorted by application",
+            PortSort::Recent => "Sorted by most recent",
+        });
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn digit_runs_compare_by_value() {
+        assert_eq!(natural_cmp("Port 2", "Port 10"), Ordering::Less);
+        assert_eq!(natural_cmp("playback_10", "playback_9"), Ordering::Greater);
+        assert_eq!(natural_cmp("Port", "Port 1"), Ordering::Less);
+        assert_eq!(natural_cmp("Port 1", "Port 1"), Ordering::Equal);
+    }
+
+    #[test]
+    fn leading_zeros_are_ignored_for_the_value() {
+        assert_eq!(natural_cmp("Port 010", "Port 9"), Ordering::Greater);
+        assert_eq!(natural_cmp("Port 007", "Port 8"), Ordering::Less);
+        // Equal values still sort the same way every time
+        assert_eq!(natural_cmp("Port 007", "Port 7"), Ordering::Less);
+    }
+
+    #[test]
+    fn letters_compare_without_case() {
+        assert_eq!(natural_cmp("capture", "Playback"), Ordering::Less);
+        assert_eq!(natural_cmp("Zoom", "alsa"), Ordering::Greater);
+        // Names differing only in case fall back to a plain comparison
+        assert_eq!(natural_cmp("Mic", "mic"), Ordering::Less);
+        assert_eq!(natural_cmp("mic", "Mic"), Ordering::Greater);
+    }
+
+    #[test]
+    fn channels_follow_the_layout() {
+        assert!(channel_rank("MONO") < channel_rank("FL"));
+        assert!(channel_rank("FL") < channel_rank("FR"));
+        assert!(channel_rank("FR") < channel_rank("FC"));
+        assert!(channel_rank("FC") < channel_rank("LFE"));
+        assert!(channel_rank("LFE") < channel_rank("SL"));
+    }
+
+    #[test]
+    fn aux_channels_sort_by_number_after_known_ones() {
+        assert!(channel_rank("BRC") < channel_rank("AUX0"));
+        assert!(channel_rank("AUX2") < channel_rank("AUX10"));
+        assert!(channel_rank("AUX10") < channel_rank("UNKNOWN"));
+        assert_eq!(channel_rank("AUXX"), channel_rank(""));
+    }
+}
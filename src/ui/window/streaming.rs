@@ -0,0 +1,170 @@
+use adw::prelude::*;
+use adw::subclass::prelude::*;
+use gtk::glib;
+
+use super::Window;
+use crate::pipewire::modules::{self, OWNED_NAME_PREFIX};
+use crate::pipewire::UiCommand;
+
+/// Pulse module used for the stream mix sink
+pub(super) const NULL_SINK_MODULE: &str = "module-null-sink";
+
+impl Window {
+    /// Node name of the stream mix sink for a given description
+    fn stream_mix_node_name(description: &str) -> String {
+        format!("{}stream_{}", OWNED_NAME_PREFIX, modules::slugify(description))
+    }
+
+    /// Show the guided streaming setup
+    pub(super) fn show_streaming_setup_dialog(&self) {
+        // (node id, display name) of every application playing audio
+        let mut apps: Vec<(u32, String)> = {
+            let pw_state = self.imp().pw_state.borrow();
+            pw_state
+                .nodes
+                .values()
+                .filter(|n| n.media_class.as_deref() == Some("Stream/Output/Audio"))
+                .map(|n| (n.id, n.display_name().to_string()))
+                .collect()
+        };
+        apps.sort_by(|a, b| a.1.cmp(&b.1));
+
+        if apps.is_empty() {
+            self.announce("No applications are playing audio. Start the applications to stream first.");
+            return;
+        }
+
+        let dialog = adw::MessageDialog::builder()
+            .transient_for(&self.dialog_parent())
+            .modal(true)
+            .heading("Streaming Setup")
+            .body(
+                "Checked applications are sent to a virtual output while staying audible \
+                 on your current output. In OBS, add an Audio Output Capture and choose \
+                 \"Monitor of\" followed by the mix name.",
+            )
+            .build();
+
+        let content = gtk::Box::builder()
+            .orientation(gtk::Orientation::Vertical)
+            .spacing(12)
+            .build();
+
+        let entry = gtk::Entry::builder()
+            .text("Stream Mix")
+            .activates_default(true)
+            .build();
+        entry.update_property(&[gtk::accessible::Property::Label("Mix name")]);
+        content.append(&entry);
+
+        let list_box = gtk::ListBox::builder()
+            .selection_mode(gtk::SelectionMode::None)
+            .css_classes(["boxed-list"])
+            .build();
+        list_box.update_property(&[gtk::accessible::Property::Label("Applications to stream")]);
+
+        let mut checks = Vec::new();
+        for (id, label) in &apps {
+            let check = gtk::CheckButton::builder().label(label).build();
+            list_box.append(&check);
+            checks.push((*id, check));
+        }
+
+        let scrolled = gtk::ScrolledWindow::builder()
+            .hscrollbar_policy(gtk::PolicyType::Never)
+            .vscrollbar_policy(gtk::PolicyType::Automatic)
+            .min_content_height(100)
+            .max_content_height(300)
+            .child(&list_box)
+            .build();
+        content.append(&scrolled);
+
+        dialog.set_extra_child(Some(&content));
+
+        dialog.add_response("cancel", "Cancel");
+        dialog.add_response("setup", "Set Up");
+        dialog.set_response_appearance("setup", adw::ResponseAppearance::Suggested);
+        dialog.set_default_response(Some("setup"));
+        dialog.set_close_response("cancel");
+
+        dialog.connect_response(
+            None,
+            glib::clone!(
+                #[weak(rename_to = window)]
+                self,
+                #[weak]
+                entry,
+                move |dialog, response| {
+                    dialog.close();
+                    if response != "setup" {
+                        return;
+                    }
+
+                    let selected: Vec<u32> = checks
+                        .iter()
+                        .filter(|(_, check)| check.is_active())
+                        .map(|(id, _)| *id)
+                        .collect();
+                    if selected.is_empty() {
+                        window.announce("Select at least one application to stream");
+                        return;
+                    }
+
+                    let description = modules::sanitize_description(&entry.text());
+                    if description.is_empty() {
+                        window.announce("Mix name cannot be empty");
+                        return;
+                    }
+
+                    window.setup_stream_mix(&description, selected);
+                }
+            ),
+        );
+
+        dialog.present();
+        entry.grab_focus();
+    }
+
+    /// Create the stream mix sink if needed and route `apps` into it.
+    /// Existing links are kept, so the applications stay audible.
+    fn setup_stream_mix(&self, description: &str, apps: Vec<u32>) {
+        let node_name = Self::stream_mix_node_name(description);
+        let existing = self
+            .imp()
+            .pw_state
+            .borrow()
+            .nodes
+            .values()
+            .find(|n| n.name == node_name)
+            .map(|n| n.id);
+
+        match existing {
+            Some(sink_id) => {
+                let count: usize = apps
+                    .iter()
+                    .map(|&app| self.connect_nodes(app, sink_id))
+                    .sum();
+                self.announce(&format!(
+                    "Added {} connections to \"{}\"",
+                    count, description
+                ));
+            }
+            None => {
+                let args = format!(
+                    "sink_name={} sink_properties={} channel_map=front-left,front-right",
+                    node_name,
+                    modules::description_properties(description)
+                );
+                self.queue_route(node_name, apps);
+                self.send_command(UiCommand::LoadModule {
+                    name: NULL_SINK_MODULE.into(),
+                    args,
+                });
+                self.announce(&format!(
+                    "Creating \"{}\". Capture \"Monitor of {}\" in OBS.",
+                    description, description
+                ));
+            }
+        }
+    }
+}
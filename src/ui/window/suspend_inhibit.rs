@@ -0,0 +1,108 @@
+use adw::prelude::*;
+use adw::subclass::prelude::*;
+use gtk::gio;
+
+use super::Window;
+
+impl Window {
+    /// Names of the network audio in use: helpers receiving or sending
+    /// streams, and tunnels to other computers we made, while something is
+    /// connected to them
+    fn active_network_shares(&self) -> Vec<String> {
+        let imp = self.imp();
+        let hosted = imp
+            .hosted_modules
+            .borrow()
+            .iter()
+            .filter(|hosted| self.share_linked(|name| name.starts_with(&hosted.key)))
+            .map(|hosted| hosted.description.clone())
+            .collect::<Vec<_>>();
+        let tunnels = imp
+            .loaded_modules
+            .borrow()
+            .iter()
+            .filter(|module| Self::is_tunnel_module(module) && module.is_owned())
+            .filter(|module| {
+                let node_name = module.arg("sink_name").or_else(|| module.arg("source_name"));
+                node_name.is_some_and(|node_name| self.share_linked(|name| name == node_name))
+            })
+            .map(Self::module_display_name)
+            .collect::<Vec<_>>();
+        hosted.into_iter().chain(tunnels).collect()
+    }
+
+    /// Whether a link joins a port of a node whose name `is_share` accepts,
+    /// so audio flows through it
+    fn share_linked(&self, is_share: impl Fn(&str) -> bool) -> bool {
+        let pw_state = self.imp().pw_state.borrow();
+        pw_state.links.values().any(|link| {
+            [link.output_port_id, link.input_port_id].iter().any(|&port_id| {
+                pw_state
+                    .get_port_node(port_id)
+                    .is_some_and(|node| is_share(&node.name))
+            })
+        })
+    }
+
+    /// Keep the computer from suspending while network audio is streaming,
+    /// if enabled, so a long stream isn't cut off when the laptop idles. The
+    /// screen may still blank and lock.
+    pub(super) fn update_suspend_inhibit(&self) {
+        let shares = self.active_network_shares();
+        let wanted = self.imp().settings.borrow().inhibit_suspend && !shares.is_empty();
+        let cookie = self.imp().suspend_inhibit_cookie.get();
+        let Some(app) = self.application() else {
+            return;
+        };
+
+        match (wanted, cookie) {
+            (true, None) => {
+                let reason = match shares.as_slice() {
+                    [share] => format!("Sharing audio: {}", share),
+                    _ => format!("Sharing audio over the network ({} streams)", shares.len()),
+                };
+                let cookie = app.inhibit(
+                    Some(self),
+                    gtk::ApplicationInhibitFlags::SUSPEND,
+                    Some(&reason),
+                );
+                // 0 means the desktop doesn't support inhibiting
+                if cookie == 0 {
+                    log::warn!("Could not prevent suspend while sharing audio");
+                    return;
+                }
+                log::info!("Preventing suspend: {}", reason);
+                self.imp().suspend_inhibit_cookie.set(Some(cookie));
+            }
+            (false, Some(cookie)) => {
+                app.uninhibit(cookie);
+                self.imp().suspend_inhibit_cookie.set(None);
+                log::info!("Allowing suspend again");
+            }
+            _ => {}
+        }
+    }
+
+    /// Set whether network audio keeps the computer awake and save it
+    pub(super) fn set_inhibit_suspend(&self, inhibit: bool) {
+        self.imp().settings.borrow_mut().inhibit_suspend = inhibit;
+        if let Some(action) = self
+            .lookup_action("inhibit-suspend")
+            .and_downcast::<gio::SimpleAction>()
+        {
+            action.set_state(&inhibit.to_variant());
+        }
+        self.update_suspend_inhibit();
+
+        if let Err(e) = self.imp().settings.borrow().save() {
+            self.announce(&format!("Failed to save settings: {}", e));
+            return;
+        }
+
+        if inhibit {
+            self.announce("The computer stays awake while audio is shared over the network");
+        } else {
+            self.announce("The computer may suspend while audio is shared over the network");
+        }
+    }
+}
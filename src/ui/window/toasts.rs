@@ -0,0 +1,73 @@
+use adw::prelude::*;
+use adw::subclass::prelude::*;
+use gtk::glib;
+
+use super::Window;
+
+/// How long a toast with an Undo button stays up, in seconds
+const UNDO_TOAST_TIMEOUT: u32 = 8;
+
+impl Window {
+    /// Show a short message over the window
+    pub(super) fn show_toast(&self, title: &str) {
+        let toast = adw::Toast::builder().title(title).use_markup(false).build();
+        self.imp().toast_overlay.add_toast(toast);
+    }
+
+    /// Show a message with an Undo button that runs `undo` when pressed
+    pub(super) fn show_undo_toast<F>(&self, title: &str, undo: F)
+    where
+        F: Fn(&Window) + 'static,
+    {
+        let toast = adw::Toast::builder()
+            .title(title)
+            .use_markup(false)
+            .button_label("Undo")
+            .timeout(UNDO_TOAST_TIMEOUT)
+            .build();
+        toast.connect_button_clicked(glib::clone!(
+            #[weak(rename_to = window)]
+            self,
+            move |_| undo(&window)
+        ));
+        self.imp().toast_overlay.add_toast(toast);
+    }
+
+    /// Report the outcome of a link request made by the user
+    pub(super) fn toast_link_created(&self, request_id: u64, link_id: u32) {
+        let Some((output_port_id, input_port_id)) = self
+            .imp()
+            .toast_link_requests
+            .borrow_mut()
+            .remove(&request_id)
+        else {
+            return;
+        };
+
+        let (output_label, input_label) = {
+            let pw_state = self.imp().pw_state.borrow();
+            (
+                pw_state
+                    .port_label(output_port_id)
+                    .unwrap_or_else(|| format!("Port {}", output_port_id)),
+                pw_state
+                    .port_label(input_port_id)
+                    .unwrap_or_else(|| format!("Port {}", input_port_id)),
+            )
+        };
+
+        self.show_undo_toast(
+            &format!("Connected {} → {}", output_label, input_label),
+            move |window| window.delete_link(link_id),
+        );
+    }
+
+    /// Report a failed link request as a toast
+    pub(super) fn toast_link_failed(&self, request_id: u64, message: &str) {
+        self.imp()
+            .toast_link_requests
+            .borrow_mut()
+            .remove(&request_id);
+        self.show_toast(message);
+    }
+}
@@ -0,0 +1,207 @@
+use adw::prelude::*;
+use adw::subclass::prelude::*;
+use gtk::glib;
+
+use super::recently_removed::RememberedLink;
+use super::Window;
+use crate::tray::{TrayConnection, TrayConnectionKey, TrayUpdater};
+
+/// How many connections the tray's Recent Connections menu lists
+const MAX_TRAY_CONNECTIONS: usize = 5;
+
+/// Scrolls closer together than this are one gesture, so a single flick on
+/// a touchpad moves one preset rather than several (microseconds)
+const TRAY_SCROLL_INTERVAL: i64 = 300_000;
+
+/// The names identifying a recent connection in the tray menu
+fn tray_key(created: &RememberedLink) -> TrayConnectionKey {
+    let connection = &created.connection;
+    TrayConnectionKey {
+        output_node: connection.output_node.clone(),
+        output_port: connection.output_port.clone(),
+        input_node: connection.input_node.clone(),
+        input_port: connection.input_port.clone(),
+    }
+}
+
+impl Window {
+    /// Let the window keep the tray's title and menu up to date
+    pub fn set_tray_updater(&self, updater: TrayUpdater) {
+        self.imp().tray_updater.replace(Some(updater));
+        self.update_tray();
+    }
+
+    /// Send the active preset and recent connections to the tray
+    pub(super) fn update_tray(&self) {
+        if let Some(updater) = self.imp().tray_updater.borrow().as_ref() {
+            let active_preset = self.imp().preset_store.borrow().active_preset.clone();
+            updater.set_active_preset(active_preset);
+            updater.set_recent_connections(self.imp().tray_connections.borrow().clone());
+        }
+    }
+
+    /// Send the recent connections to the tray if they or their state changed
+    pub(super) fn update_tray_connections(&self) {
+        let connections: Vec<TrayConnection> = {
+            let pw_state = self.imp().pw_state.borrow();
+            self.imp()
+                .recently_created
+                .borrow()
+                .iter()
+                .map(|created| TrayConnection {
+                    label: created.label.clone(),
+                    connected: created
+                        .find_ports(&pw_state)
+                        .is_some_and(|(_, _, linked)| linked),
+                    key: tray_key(created),
+                })
+                .collect()
+        };
+
+        if *self.imp().tray_connections.borrow() == connections {
+            return;
+        }
+        if let Some(updater) = self.imp().tray_updater.borrow().as_ref() {
+            updater.set_recent_connections(connections.clone());
+        }
+        self.imp().tray_connections.replace(connections);
+    }
+
+    /// Remember a connection the user created, for the tray's Recent
+    /// Connections menu
+    pub(super) fn remember_created_link(&self, output_port_id: u32, input_port_id: u32) {
+        let created =
+            RememberedLink::of_ports(&self.imp().pw_state.borrow(), output_port_id, input_port_id);
+        let Some(created) = created else {
+            return;
+        };
+
+        {
+            let mut recent = self.imp().recently_created.borrow_mut();
+            recent.retain(|link| link.label != created.label);
+            recent.push_front(created);
+            recent.truncate(MAX_TRAY_CONNECTIONS);
+        }
+        self.update_tray_connections();
+    }
+
+    /// Remove a recent connection chosen in the tray if it exists, otherwise
+    /// create it again
+    pub fn toggle_recent_connection(&self, key: &TrayConnectionKey) {
+        let created = self
+            .imp()
+            .recently_created
+            .borrow()
+            .iter()
+            .find(|created| tray_key(created) == *key)
+            .cloned();
+        let Some(created) = created else {
+            return;
+        };
+
+        let ports = created.find_ports(&self.imp().pw_state.borrow());
+        match ports {
+            Some((output_id, input_id, true)) => {
+                let link_id = self
+                    .imp()
+                    .pw_state
+                    .borrow()
+                    .find_link(output_id, input_id)
+                    .map(|link| link.id);
+                if let Some(link_id) = link_id {
+                    self.delete_link_remembered(link_id);
+                    self.announce(&format!("Disconnected {}", created.label));
+                }
+            }
+            Some((output_id, input_id, false)) => {
+                self.create_link(output_id, input_id);
+                self.announce(&format!("Connected {}", created.label));
+            }
+            None => self.announce(&format!(
+                "Cannot connect {}: its ports are gone",
+                created.label
+            )),
+        }
+    }
+
+    /// The tray icon was scrolled: activate the next or previous preset
+    pub fn tray_scrolled(&self, step: i32) {
+        if !self.imp().settings.borrow().tray_scroll_presets {
+            return;
+        }
+
+        let now = glib::monotonic_time();
+        if now - self.imp().last_tray_scroll.get() < TRAY_SCROLL_INTERVAL {
+            return;
+        }
+        self.imp().last_tray_scroll.set(now);
+
+        let next = {
+            let store = self.imp().preset_store.borrow();
+            let names = store.preset_names();
+            if names.is_empty() {
+                return;
+            }
+            let count = names.len() as i32;
+            let current = store
+                .active_preset
+                .as_ref()
+                .and_then(|active| names.iter().position(|name| name == active));
+            let index = match current {
+                Some(index) => (index as i32 + step).rem_euclid(count),
+                // With none active, down starts at the first and up at the last
+                None if step > 0 => 0,
+                None => count - 1,
+            };
+            names[index as usize].clone()
+        };
+
+        self.activate_preset(&next);
+    }
+
+    /// The tray icon was middle-clicked: show or hide the window
+    pub fn tray_middle_clicked(&self) {
+        if !self.imp().settings.borrow().tray_middle_click_toggle {
+            return;
+        }
+
+        if self.is_visible() {
+            self.set_visible(false);
+        } else {
+            self.set_visible(true);
+            self.present();
+        }
+    }
+
+    /// Set whether scrolling over the tray icon switches presets and save it
+    pub(super) fn set_tray_scroll_presets(&self, enabled: bool) {
+        self.imp().settings.borrow_mut().tray_scroll_presets = enabled;
+
+        if let Err(e) = self.imp().settings.borrow().save() {
+            self.announce(&format!("Failed to save settings: {}", e));
+            return;
+        }
+
+        if enabled {
+            self.announce("Scrolling over the tray icon switches presets");
+        } else {
+            self.announce("Scrolling over the tray icon does nothing");
+        }
+    }
+
+    /// Set whether middle-clicking the tray icon shows or hides the window and save it
+    pub(super) fn set_tray_middle_click_toggle(&self, enabled: bool) {
+        self.imp().settings.borrow_mut().tray_middle_click_toggle = enabled;
+
+        if let Err(e) = self.imp().settings.borrow().save() {
+            self.announce(&format!("Failed to save settings: {}", e));
+            return;
+        }
+
+        if enabled {
+            self.announce("Middle-clicking the tray icon shows or hides the window");
+        } else {
+            self.announce("Middle-clicking the tray icon does nothing");
+        }
+    }
+}
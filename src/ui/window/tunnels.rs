@@ -0,0 +1,233 @@
+use adw::prelude::*;
+use adw::subclass::prelude::*;
+use gtk::glib;
+
+use super::Window;
+use crate::pipewire::modules::{self, LoadedModule, OWNED_NAME_PREFIX};
+use crate::pipewire::UiCommand;
+use crate::settings::TunnelConfig;
+
+/// Pulse modules used for tunnels
+pub(super) const TUNNEL_SINK_MODULE: &str = "module-tunnel-sink";
+pub(super) const TUNNEL_SOURCE_MODULE: &str = "module-tunnel-source";
+
+/// Default native protocol port of a Pulse server
+const PULSE_DEFAULT_PORT: u16 = 4713;
+
+impl TunnelConfig {
+    /// Local node name for the tunnel
+    fn node_name(&self) -> String {
+        format!("{}tunnel_{}", OWNED_NAME_PREFIX, modules::slugify(&self.name))
+    }
+
+    /// Module name and arguments that create this tunnel
+    fn module_args(&self) -> (&'static str, String) {
+        let (module, prefix) = if self.send {
+            (TUNNEL_SINK_MODULE, "sink")
+        } else {
+            (TUNNEL_SOURCE_MODULE, "source")
+        };
+
+        let mut args = format!(
+            "server=tcp:{}:{} {prefix}_name={} {prefix}_properties={}",
+            modules::sanitize_description(&self.host),
+            self.port,
+            self.node_name(),
+            modules::description_properties(&self.name),
+            prefix = prefix,
+        );
+        if let Some(remote) = &self.remote_device {
+            args.push_str(&format!(" {}={}", prefix, modules::sanitize_node_name(remote)));
+        }
+
+        (module, args)
+    }
+}
+
+impl Window {
+    /// Whether a loaded module is one of our tunnels
+    pub(super) fn is_tunnel_module(module: &LoadedModule) -> bool {
+        module.name == TUNNEL_SINK_MODULE || module.name == TUNNEL_SOURCE_MODULE
+    }
+
+    /// Whether a loaded module is the tunnel for a config
+    fn module_is_tunnel(module: &LoadedModule, tunnel: &TunnelConfig) -> bool {
+        let key = if tunnel.send { "sink_name" } else { "source_name" };
+        module.arg(key).as_deref() == Some(tunnel.node_name().as_str())
+    }
+
+    /// Recreate saved tunnels that are not loaded, e.g. after PipeWire restarted.
+    /// Called once the startup module scan is complete. Tunnels belong to
+    /// the main server, as modules can't be loaded into another one.
+    pub(super) fn restore_tunnels(&self) {
+        if self.is_server_window() {
+            return;
+        }
+        let tunnels = self.imp().settings.borrow().tunnels.clone();
+        let loaded = self.imp().loaded_modules.borrow().clone();
+
+        for tunnel in tunnels {
+            if loaded.iter().any(|m| Self::module_is_tunnel(m, &tunnel)) {
+                continue;
+            }
+            log::info!("Restoring tunnel \"{}\" to {}", tunnel.name, tunnel.host);
+            let (name, args) = tunnel.module_args();
+            self.send_command(UiCommand::LoadModule {
+                name: name.into(),
+                args,
+            });
+        }
+    }
+
+    /// Forget the saved tunnel created by `module`, if any
+    pub(super) fn forget_tunnel(&self, module: &LoadedModule) {
+        let removed = {
+            let mut settings = self.imp().settings.borrow_mut();
+            let before = settings.tunnels.len();
+            settings
+                .tunnels
+                .retain(|t| !Self::module_is_tunnel(module, t));
+            before != settings.tunnels.len()
+        };
+
+        if removed {
+            if let Err(e) = self.imp().settings.borrow().save() {
+                self.announce(&format!("Failed to save settings: {}", e));
+            }
+        }
+    }
+
+    /// Show a dialog to create a tunnel to another machine
+    pub(super) fn show_new_tunnel_dialog(&self) {
+        let dialog = adw::MessageDialog::builder()
+            .transient_for(&self.dialog_parent())
+            .modal(true)
+            .heading("New Network Tunnel")
+            .body(
+                "Connect to another computer running PipeWire or PulseAudio with \
+                 network access enabled. The tunnel is recreated whenever PW Audioshare starts.",
+            )
+            .build();
+
+        let content = gtk::Box::builder()
+            .orientation(gtk::Orientation::Vertical)
+            .spacing(12)
+            .build();
+
+        let direction = gtk::DropDown::from_strings(&[
+            "Send audio to the remote computer",
+            "Receive audio from the remote computer",
+        ]);
+        direction.update_property(&[gtk::accessible::Property::Label("Direction")]);
+        content.append(&direction);
+
+        let name_entry = gtk::Entry::builder()
+            .placeholder_text("Name, e.g. Living Room PC")
+            .activates_default(true)
+            .build();
+        name_entry.update_property(&[gtk::accessible::Property::Label("Tunnel name")]);
+        content.append(&name_entry);
+
+        let host_entry = gtk::Entry::builder()
+            .placeholder_text("Host name or address")
+            .activates_default(true)
+            .build();
+        host_entry.update_property(&[gtk::accessible::Property::Label("Remote host")]);
+        content.append(&host_entry);
+
+        let port_spin = gtk::SpinButton::with_range(1.0, 65535.0, 1.0);
+        port_spin.set_value(PULSE_DEFAULT_PORT as f64);
+        port_spin.update_property(&[gtk::accessible::Property::Label("Remote port")]);
+        content.append(&port_spin);
+
+        let device_entry = gtk::Entry::builder()
+            .placeholder_text("Remote device (optional, default device if empty)")
+            .activates_default(true)
+            .build();
+        device_entry.update_property(&[gtk::accessible::Property::Label("Remote device")]);
+        content.append(&device_entry);
+
+        dialog.set_extra_child(Some(&content));
+
+        dialog.add_response("cancel", "Cancel");
+        dialog.add_response("create", "Create");
+        dialog.set_response_appearance("create", adw::ResponseAppearance::Suggested);
+        dialog.set_default_response(Some("create"));
+        dialog.set_close_response("cancel");
+
+        dialog.connect_response(
+            None,
+            glib::clone!(
+                #[weak(rename_to = window)]
+                self,
+                #[weak]
+                direction,
+                #[weak]
+                name_entry,
+                #[weak]
+                host_entry,
+                #[weak]
+                port_spin,
+                #[weak]
+                device_entry,
+                move |dialog, response| {
+                    dialog.close();
+                    if response != "create" {
+                        return;
+                    }
+
+                    let name = modules::sanitize_description(&name_entry.text());
+                    let host = host_entry.text().trim().to_string();
+                    if name.is_empty() || host.is_empty() {
+                        window.announce("Tunnel name and host are required");
+                        return;
+                    }
+
+                    let remote_device = device_entry.text().trim().to_string();
+                    window.create_tunnel(TunnelConfig {
+                        name,
+                        host,
+                        port: port_spin.value() as u16,
+                        send: direction.selected() == 0,
+                        remote_device: (!remote_device.is_empty()).then_some(remote_device),
+                    });
+                }
+            ),
+        );
+
+        dialog.present();
+        direction.grab_focus();
+    }
+
+    /// Save a tunnel and load it
+    fn create_tunnel(&self, tunnel: TunnelConfig) {
+        let duplicate = self
+            .imp()
+            .settings
+            .borrow()
+            .tunnels
+            .iter()
+            .any(|t| t.node_name() == tunnel.node_name());
+        if duplicate {
+            self.announce(&format!("A tunnel named \"{}\" already exists", tunnel.name));
+            return;
+        }
+
+        let (name, args) = tunnel.module_args();
+        self.send_command(UiCommand::LoadModule {
+            name: name.into(),
+            args,
+        });
+        self.announce(&format!(
+            "Creating tunnel \"{}\" {} {}",
+            tunnel.name,
+            if tunnel.send { "to" } else { "from" },
+            tunnel.host
+        ));
+
+        self.imp().settings.borrow_mut().tunnels.push(tunnel);
+        if let Err(e) = self.imp().settings.borrow().save() {
+            self.announce(&format!("Failed to save settings: {}", e));
+        }
+    }
+}
@@ -0,0 +1,87 @@
+use adw::prelude::*;
+use adw::subclass::prelude::*;
+use gtk::glib;
+
+use super::Window;
+use crate::model::PortObject;
+
+/// Typing pauses longer than this start a new search, in microseconds
+const TYPE_AHEAD_TIMEOUT_US: i64 = 1_000_000;
+
+impl Window {
+    /// Jump to the next port in a list whose label starts with the typed text.
+    /// Returns whether the key was used.
+    pub(super) fn type_ahead(&self, is_output: bool, ch: char) -> bool {
+        let imp = self.imp();
+        let now = glib::monotonic_time();
+
+        let expired = now - imp.type_ahead_time.get() > TYPE_AHEAD_TIMEOUT_US
+            || imp.type_ahead_list.get() != is_output;
+        if expired {
+            imp.type_ahead_text.borrow_mut().clear();
+        }
+        // Space keeps toggling the selection unless a search is under way
+        if ch.is_whitespace() && imp.type_ahead_text.borrow().is_empty() {
+            return false;
+        }
+        imp.type_ahead_time.set(now);
+        imp.type_ahead_list.set(is_output);
+        imp.type_ahead_text.borrow_mut().extend(ch.to_lowercase());
+
+        let (selection, list_view) = if is_output {
+            (
+                imp.output_selection.borrow().clone(),
+                imp.output_list_view.borrow().clone(),
+            )
+        } else {
+            (
+                imp.input_selection.borrow().clone(),
+                imp.input_list_view.borrow().clone(),
+            )
+        };
+        let (Some(selection), Some(list_view)) = (selection, list_view) else {
+            return true;
+        };
+
+        let n_items = selection.n_items();
+        if n_items == 0 {
+            return true;
+        }
+
+        let text = imp.type_ahead_text.borrow().clone();
+        let current = {
+            let selected = selection.selection();
+            if selected.is_empty() {
+                0
+            } else {
+                selected.minimum()
+            }
+        };
+        // Typing the same letter again moves on to the next match
+        let mut chars = text.chars();
+        let first = chars.next();
+        let repeating = chars.all(|c| Some(c) == first);
+        let (search, start) = if repeating {
+            (first.map(String::from).unwrap_or_default(), current + 1)
+        } else {
+            (text, current)
+        };
+
+        let matches = |position: u32| {
+            selection
+                .item(position % n_items)
+                .and_downcast::<PortObject>()
+                .is_some_and(|port| port.starts_with(&search))
+        };
+
+        match (start..start + n_items).find(|&position| matches(position)) {
+            Some(position) => list_view.scroll_to(
+                position % n_items,
+                gtk::ListScrollFlags::FOCUS | gtk::ListScrollFlags::SELECT,
+                None,
+            ),
+            None => self.announce(&format!("No port starting with \"{}\"", search)),
+        }
+        true
+    }
+}
@@ -0,0 +1,76 @@
+use adw::prelude::*;
+use adw::subclass::prelude::*;
+
+use super::Window;
+use crate::model::PortObject;
+use crate::pipewire::messages::VideoFormat;
+use crate::pipewire::state::PortVideoFormats;
+
+impl Window {
+    /// Store the formats a video port reported and show them in its row
+    pub(super) fn video_formats_changed(
+        &self,
+        port_id: u32,
+        offered: Vec<VideoFormat>,
+        current: Option<VideoFormat>,
+    ) {
+        let formats = PortVideoFormats { offered, current };
+        let label = formats.label().unwrap_or_default();
+        self.imp()
+            .pw_state
+            .borrow_mut()
+            .video_formats
+            .insert(port_id, formats);
+
+        // The port may still wait in the batch of new ports, outside the lists
+        if let Some(port) = self.pending_port(port_id) {
+            port.set_video_format(label.as_str());
+            return;
+        }
+        for store in [&self.imp().output_ports, &self.imp().input_ports] {
+            for i in 0..store.n_items() {
+                let Some(port) = store.item(i).and_downcast::<PortObject>() else {
+                    continue;
+                };
+                if port.id() == port_id {
+                    if port.video_format() != label {
+                        port.set_video_format(label.as_str());
+                        // Let the list row pick up the change
+                        store.items_changed(i, 1, 1);
+                    }
+                    return;
+                }
+            }
+        }
+    }
+
+    /// Warn before connecting video ports whose formats don't match, as
+    /// the link would fail to negotiate and carry no picture. The links are
+    /// still made, since the formats offered may change once streaming.
+    pub(super) fn warn_video_incompatibility(&self, pairs: &[(u32, u32)]) {
+        let problems: Vec<String> = {
+            let pw_state = self.imp().pw_state.borrow();
+            pairs
+                .iter()
+                .filter_map(|&(output_id, input_id)| {
+                    pw_state.video_incompatibility(output_id, input_id)
+                })
+                .collect()
+        };
+        let Some(first) = problems.first() else {
+            return;
+        };
+
+        let message = match problems.len() {
+            1 => format!("{}. The connection may carry no video", first),
+            n => format!(
+                "{}, and {} more connections have mismatched video formats",
+                first,
+                n - 1
+            ),
+        };
+        log::warn!("{}", message);
+        self.show_toast(&message);
+        self.announce(&message);
+    }
+}
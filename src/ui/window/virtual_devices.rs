@@ -0,0 +1,611 @@
+use adw::prelude::*;
+use adw::subclass::prelude::*;
+use gtk::glib;
+
+use super::Window;
+use crate::pipewire::modules::{self, LoadedModule, OWNED_NAME_PREFIX};
+use crate::pipewire::UiCommand;
+
+/// Pulse module used for combined outputs
+const COMBINE_MODULE: &str = "module-combine-sink";
+
+/// Pulse module used for echo-cancelled microphones
+const ECHO_CANCEL_MODULE: &str = "module-echo-cancel";
+
+/// A native module running in a helper process (see `ModuleHost`)
+#[derive(Debug, Clone)]
+pub struct HostedModule {
+    /// Helper key, also the prefix of the nodes it creates
+    pub key: String,
+    pub description: String,
+    /// What the module provides, e.g. "Network audio receiver"
+    pub kind: String,
+}
+
+/// Anything listed in the virtual device manager
+#[derive(Debug, Clone)]
+enum VirtualDevice {
+    Loaded(LoadedModule),
+    Hosted(HostedModule),
+}
+
+impl VirtualDevice {
+    fn display_name(&self) -> String {
+        match self {
+            VirtualDevice::Loaded(module) => Window::module_display_name(module),
+            VirtualDevice::Hosted(hosted) => hosted.description.clone(),
+        }
+    }
+
+    fn kind_label(&self) -> String {
+        match self {
+            VirtualDevice::Loaded(module) => Window::module_kind_label(module),
+            VirtualDevice::Hosted(hosted) => {
+                format!("{}, removed when PW Audioshare quits", hosted.kind)
+            }
+        }
+    }
+}
+
+impl Window {
+    /// Allocate a unique instance number for a helper process
+    pub(super) fn next_helper_instance(&self) -> u32 {
+        let instance = self.imp().next_helper_instance.get();
+        self.imp().next_helper_instance.set(instance + 1);
+        instance
+    }
+
+    /// Start a native module in a helper process and list it as a virtual device
+    pub(super) fn start_hosted_module(&self, hosted: HostedModule, module: &str, args: String) {
+        self.send_command(UiCommand::StartModuleHost {
+            key: hosted.key.clone(),
+            module: module.into(),
+            args,
+        });
+
+        {
+            let mut modules = self.imp().hosted_modules.borrow_mut();
+            modules.retain(|m| m.key != hosted.key);
+            modules.push(hosted);
+        }
+        self.update_suspend_inhibit();
+    }
+
+    /// A helper process exited; forget its virtual device.
+    /// Returns true if the key belonged to a listed virtual device.
+    pub(super) fn hosted_module_exited(&self, key: &str) -> bool {
+        let removed = {
+            let mut modules = self.imp().hosted_modules.borrow_mut();
+            let index = modules.iter().position(|m| m.key == key);
+            index.map(|i| modules.remove(i))
+        };
+        self.update_suspend_inhibit();
+
+        match removed {
+            Some(hosted) => {
+                self.announce(&format!("\"{}\" stopped", hosted.description));
+                true
+            }
+            None => false,
+        }
+    }
+
+    /// Record a module reported by the PipeWire thread
+    pub(super) fn module_loaded(&self, module: LoadedModule) {
+        {
+            let mut loaded = self.imp().loaded_modules.borrow_mut();
+            loaded.retain(|m| m.id != module.id);
+            loaded.push(module);
+        }
+        self.update_suspend_inhibit();
+    }
+
+    /// Forget a module that was unloaded
+    pub(super) fn module_unloaded(&self, id: u32) {
+        self.imp().loaded_modules.borrow_mut().retain(|m| m.id != id);
+        self.update_suspend_inhibit();
+    }
+
+    /// Describe what kind of virtual device a module provides
+    fn module_kind_label(module: &LoadedModule) -> String {
+        match module.name.as_str() {
+            COMBINE_MODULE => {
+                let count = module
+                    .arg("slaves")
+                    .map(|s| s.split(',').filter(|s| !s.is_empty()).count())
+                    .unwrap_or(0);
+                format!("Combined output to {} devices", count)
+            }
+            ECHO_CANCEL_MODULE => format!(
+                "Echo-cancelled {}",
+                module.arg("source_master").unwrap_or_else(|| "microphone".into())
+            ),
+            super::streaming::NULL_SINK_MODULE => {
+                "Virtual output, capture its monitor in OBS".to_string()
+            }
+            super::tunnels::TUNNEL_SINK_MODULE => format!(
+                "Tunnel to {}",
+                module.arg("server").unwrap_or_default()
+            ),
+            super::tunnels::TUNNEL_SOURCE_MODULE => format!(
+                "Tunnel from {}",
+                module.arg("server").unwrap_or_default()
+            ),
+            other => other.to_string(),
+        }
+    }
+
+    /// Collect `(node name, display name)` pairs for nodes of a media class
+    fn nodes_of_class(&self, media_class: &str) -> Vec<(String, String)> {
+        let pw_state = self.imp().pw_state.borrow();
+        let mut nodes: Vec<(String, String)> = pw_state
+            .nodes
+            .values()
+            .filter(|n| n.media_class.as_deref() == Some(media_class))
+            .map(|n| (n.name.clone(), n.display_name().to_string()))
+            .collect();
+        nodes.sort_by(|a, b| a.1.cmp(&b.1));
+        nodes
+    }
+
+    /// Name shown for a virtual device in lists and announcements
+    pub(super) fn module_display_name(module: &LoadedModule) -> String {
+        module
+            .description()
+            .or_else(|| module.arg("sink_name"))
+            .or_else(|| module.arg("source_name"))
+            .unwrap_or_else(|| format!("Module {}", module.id))
+    }
+
+    /// Show a dialog to combine several sinks into one virtual output
+    pub(super) fn show_new_combine_dialog(&self) {
+        // Collect hardware and virtual sinks that can be combined
+        let sinks = self.nodes_of_class("Audio/Sink");
+
+        if sinks.len() < 2 {
+            self.announce("At least two audio outputs are needed to create a combined output");
+            return;
+        }
+
+        let dialog = adw::MessageDialog::builder()
+            .transient_for(&self.dialog_parent())
+            .modal(true)
+            .heading("New Combined Output")
+            .body("Audio sent to the combined output plays on every checked device.")
+            .build();
+
+        let content = gtk::Box::builder()
+            .orientation(gtk::Orientation::Vertical)
+            .spacing(12)
+            .build();
+
+        let entry = gtk::Entry::builder()
+            .placeholder_text("Combined output name")
+            .text("Combined Output")
+            .activates_default(true)
+            .build();
+        entry.update_property(&[gtk::accessible::Property::Label("Combined output name")]);
+        content.append(&entry);
+
+        let list_box = gtk::ListBox::builder()
+            .selection_mode(gtk::SelectionMode::None)
+            .css_classes(["boxed-list"])
+            .build();
+        list_box.update_property(&[gtk::accessible::Property::Label("Devices to combine")]);
+
+        let mut checks = Vec::new();
+        for (name, label) in &sinks {
+            let check = gtk::CheckButton::builder().label(label).build();
+            list_box.append(&check);
+            checks.push((name.clone(), check));
+        }
+
+        let scrolled = gtk::ScrolledWindow::builder()
+            .hscrollbar_policy(gtk::PolicyType::Never)
+            .vscrollbar_policy(gtk::PolicyType::Automatic)
+            .min_content_height(100)
+            .max_content_height(300)
+            .child(&list_box)
+            .build();
+        content.append(&scrolled);
+
+        dialog.set_extra_child(Some(&content));
+
+        dialog.add_response("cancel", "Cancel");
+        dialog.add_response("create", "Create");
+        dialog.set_response_appearance("create", adw::ResponseAppearance::Suggested);
+        dialog.set_default_response(Some("create"));
+        dialog.set_close_response("cancel");
+
+        dialog.connect_response(
+            None,
+            glib::clone!(
+                #[weak(rename_to = window)]
+                self,
+                #[weak]
+                entry,
+                move |dialog, response| {
+                    dialog.close();
+                    if response != "create" {
+                        return;
+                    }
+
+                    let slaves: Vec<String> = checks
+                        .iter()
+                        .filter(|(_, check)| check.is_active())
+                        .map(|(name, _)| name.clone())
+                        .collect();
+
+                    if slaves.len() < 2 {
+                        window.announce("Select at least two devices to combine");
+                        return;
+                    }
+
+                    let description = modules::sanitize_description(&entry.text());
+                    if description.is_empty() {
+                        window.announce("Combined output name cannot be empty");
+                        return;
+                    }
+
+                    window.create_combined_output(&description, &slaves);
+                }
+            ),
+        );
+
+        dialog.present();
+        entry.grab_focus();
+    }
+
+    /// Load a combine sink playing to all `slaves` (sink node names)
+    fn create_combined_output(&self, description: &str, slaves: &[String]) {
+        let args = format!(
+            "sink_name={}combine_{} sink_properties={} slaves={}",
+            OWNED_NAME_PREFIX,
+            modules::slugify(description),
+            modules::description_properties(description),
+            slaves.join(",")
+        );
+
+        self.send_command(UiCommand::LoadModule {
+            name: COMBINE_MODULE.into(),
+            args,
+        });
+        self.announce(&format!(
+            "Creating combined output \"{}\" for {} devices",
+            description,
+            slaves.len()
+        ));
+    }
+
+    /// Show a dialog to set up an echo-cancelled microphone
+    pub(super) fn show_new_echo_cancel_dialog(&self) {
+        let sources = self.nodes_of_class("Audio/Source");
+        let sinks = self.nodes_of_class("Audio/Sink");
+
+        if sources.is_empty() || sinks.is_empty() {
+            self.announce("Echo cancellation needs at least one microphone and one audio output");
+            return;
+        }
+
+        let dialog = adw::MessageDialog::builder()
+            .transient_for(&self.dialog_parent())
+            .modal(true)
+            .heading("New Echo-Cancelled Microphone")
+            .body(
+                "Sound played on the chosen output is removed from the microphone. \
+                 The cleaned microphone appears as a new source.",
+            )
+            .build();
+
+        let content = gtk::Box::builder()
+            .orientation(gtk::Orientation::Vertical)
+            .spacing(12)
+            .build();
+
+        let entry = gtk::Entry::builder()
+            .placeholder_text("Microphone name")
+            .text("Echo-Cancelled Microphone")
+            .activates_default(true)
+            .build();
+        entry.update_property(&[gtk::accessible::Property::Label("Microphone name")]);
+        content.append(&entry);
+
+        let source_labels: Vec<&str> = sources.iter().map(|(_, l)| l.as_str()).collect();
+        let source_dropdown = gtk::DropDown::from_strings(&source_labels);
+        source_dropdown.update_property(&[gtk::accessible::Property::Label("Microphone")]);
+        content.append(&gtk::Label::builder().label("Microphone").xalign(0.0).build());
+        content.append(&source_dropdown);
+
+        let sink_labels: Vec<&str> = sinks.iter().map(|(_, l)| l.as_str()).collect();
+        let sink_dropdown = gtk::DropDown::from_strings(&sink_labels);
+        sink_dropdown.update_property(&[gtk::accessible::Property::Label(
+            "Output to cancel",
+        )]);
+        content.append(
+            &gtk::Label::builder()
+                .label("Output to cancel")
+                .xalign(0.0)
+                .build(),
+        );
+        content.append(&sink_dropdown);
+
+        dialog.set_extra_child(Some(&content));
+
+        dialog.add_response("cancel", "Cancel");
+        dialog.add_response("create", "Create");
+        dialog.set_response_appearance("create", adw::ResponseAppearance::Suggested);
+        dialog.set_default_response(Some("create"));
+        dialog.set_close_response("cancel");
+
+        dialog.connect_response(
+            None,
+            glib::clone!(
+                #[weak(rename_to = window)]
+                self,
+                #[weak]
+                entry,
+                #[weak]
+                source_dropdown,
+                #[weak]
+                sink_dropdown,
+                move |dialog, response| {
+                    dialog.close();
+                    if response != "create" {
+                        return;
+                    }
+
+                    let description = modules::sanitize_description(&entry.text());
+                    if description.is_empty() {
+                        window.announce("Microphone name cannot be empty");
+                        return;
+                    }
+
+                    let source = sources.get(source_dropdown.selected() as usize);
+                    let sink = sinks.get(sink_dropdown.selected() as usize);
+                    if let (Some((source, _)), Some((sink, _))) = (source, sink) {
+                        window.create_echo_cancel(&description, source, sink);
+                    }
+                }
+            ),
+        );
+
+        dialog.present();
+        entry.grab_focus();
+    }
+
+    /// Load an echo canceller cleaning `source` of whatever plays on `sink`
+    fn create_echo_cancel(&self, description: &str, source: &str, sink: &str) {
+        let slug = modules::slugify(description);
+        let args = format!(
+            "source_master={} sink_master={} source_name={}ec_{} source_properties={} \
+             sink_name={}ec_{}_ref sink_properties={} aec_method=webrtc",
+            source,
+            sink,
+            OWNED_NAME_PREFIX,
+            slug,
+            modules::description_properties(description),
+            OWNED_NAME_PREFIX,
+            slug,
+            modules::description_properties(&format!("{} Reference", description)),
+        );
+
+        self.send_command(UiCommand::LoadModule {
+            name: ECHO_CANCEL_MODULE.into(),
+            args,
+        });
+        self.announce(&format!("Creating echo-cancelled microphone \"{}\"", description));
+    }
+
+    /// Show a dialog listing virtual devices created by the application
+    pub(super) fn show_manage_virtual_devices_dialog(&self) {
+        let mut devices: Vec<VirtualDevice> = self
+            .imp()
+            .loaded_modules
+            .borrow()
+            .iter()
+            .cloned()
+            .map(VirtualDevice::Loaded)
+            .chain(
+                self.imp()
+                    .hosted_modules
+                    .borrow()
+                    .iter()
+                    .cloned()
+                    .map(VirtualDevice::Hosted),
+            )
+            .collect();
+        devices.sort_by_key(VirtualDevice::display_name);
+
+        if devices.is_empty() {
+            self.announce("No virtual devices have been created");
+            return;
+        }
+
+        let dialog = adw::MessageDialog::builder()
+            .transient_for(&self.dialog_parent())
+            .modal(true)
+            .heading("Virtual Devices")
+            .body("Devices created by PW Audioshare. They stay available until removed.")
+            .build();
+
+        let list_box = gtk::ListBox::builder()
+            .selection_mode(gtk::SelectionMode::Single)
+            .css_classes(["boxed-list"])
+            .build();
+
+        for device in &devices {
+            let row = adw::ActionRow::builder()
+                .title(device.display_name())
+                .subtitle(device.kind_label())
+                .activatable(true)
+                .build();
+            list_box.append(&row);
+        }
+
+        if let Some(first_row) = list_box.row_at_index(0) {
+            list_box.select_row(Some(&first_row));
+        }
+
+        let scrolled = gtk::ScrolledWindow::builder()
+            .hscrollbar_policy(gtk::PolicyType::Never)
+            .vscrollbar_policy(gtk::PolicyType::Automatic)
+            .min_content_height(100)
+            .max_content_height(300)
+            .child(&list_box)
+            .build();
+
+        dialog.set_extra_child(Some(&scrolled));
+
+        dialog.add_response("cancel", "Close");
+        dialog.add_response("remove", "Remove");
+        dialog.add_response("rename", "Rename");
+        dialog.set_response_appearance("remove", adw::ResponseAppearance::Destructive);
+        dialog.set_default_response(Some("rename"));
+        dialog.set_close_response("cancel");
+
+        dialog.connect_response(
+            None,
+            glib::clone!(
+                #[weak(rename_to = window)]
+                self,
+                #[weak]
+                list_box,
+                move |dialog, response| {
+                    let selected = list_box
+                        .selected_row()
+                        .and_then(|row| devices.get(row.index() as usize).cloned());
+
+                    match (response, selected) {
+                        ("remove", Some(device)) => {
+                            dialog.close();
+                            window.remove_virtual_device(&device);
+                        }
+                        ("rename", Some(VirtualDevice::Loaded(module)))
+                            if Window::is_tunnel_module(&module) =>
+                        {
+                            window.announce(
+                                "Tunnels cannot be renamed. Remove it and create it again.",
+                            );
+                        }
+                        ("rename", Some(VirtualDevice::Loaded(module))) => {
+                            dialog.close();
+                            window.show_rename_virtual_device_dialog(module);
+                        }
+                        ("rename", Some(VirtualDevice::Hosted(_))) => {
+                            window.announce(
+                                "This device cannot be renamed. Remove it and create it again.",
+                            );
+                        }
+                        _ => dialog.close(),
+                    }
+                }
+            ),
+        );
+
+        dialog.present();
+        list_box.grab_focus();
+    }
+
+    /// Unload a virtual device
+    fn remove_virtual_device(&self, device: &VirtualDevice) {
+        match device {
+            VirtualDevice::Loaded(module) => {
+                self.send_command(UiCommand::UnloadModule { id: module.id });
+                if Self::is_tunnel_module(module) {
+                    self.forget_tunnel(module);
+                }
+            }
+            VirtualDevice::Hosted(hosted) => {
+                self.imp()
+                    .hosted_modules
+                    .borrow_mut()
+                    .retain(|m| m.key != hosted.key);
+                self.send_command(UiCommand::StopModuleHost {
+                    key: hosted.key.clone(),
+                });
+                self.update_suspend_inhibit();
+            }
+        }
+        self.announce(&format!("Removed \"{}\"", device.display_name()));
+    }
+
+    /// Ask for a new name and reload the module with the new description
+    fn show_rename_virtual_device_dialog(&self, module: LoadedModule) {
+        let dialog = adw::MessageDialog::builder()
+            .transient_for(&self.dialog_parent())
+            .modal(true)
+            .heading("Rename Virtual Device")
+            .body("Renaming recreates the device, so its connections must be made again.")
+            .build();
+
+        let entry = gtk::Entry::builder()
+            .text(Self::module_display_name(&module))
+            .activates_default(true)
+            .build();
+        entry.update_property(&[gtk::accessible::Property::Label("New name")]);
+        dialog.set_extra_child(Some(&entry));
+
+        dialog.add_response("cancel", "Cancel");
+        dialog.add_response("rename", "Rename");
+        dialog.set_response_appearance("rename", adw::ResponseAppearance::Suggested);
+        dialog.set_default_response(Some("rename"));
+        dialog.set_close_response("cancel");
+
+        dialog.connect_response(
+            None,
+            glib::clone!(
+                #[weak(rename_to = window)]
+                self,
+                #[weak]
+                entry,
+                move |dialog, response| {
+                    dialog.close();
+                    if response != "rename" {
+                        return;
+                    }
+
+                    let description = modules::sanitize_description(&entry.text());
+                    if description.is_empty() {
+                        window.announce("Name cannot be empty");
+                        return;
+                    }
+
+                    // Replace whichever property lists the module uses. The
+                    // sink of an echo canceller keeps its "Reference" suffix
+                    // so it stays apart from the microphone.
+                    let mut args = module.args.clone();
+                    let has_source = module.arg("source_properties").is_some();
+                    if has_source {
+                        args = modules::replace_module_arg(
+                            &args,
+                            "source_properties",
+                            &modules::description_properties(&description),
+                        );
+                    }
+                    if module.arg("sink_properties").is_some() {
+                        let sink_description = if has_source {
+                            format!("{} Reference", description)
+                        } else {
+                            description.clone()
+                        };
+                        args = modules::replace_module_arg(
+                            &args,
+                            "sink_properties",
+                            &modules::description_properties(&sink_description),
+                        );
+                    }
+
+                    window.send_command(UiCommand::UnloadModule { id: module.id });
+                    window.send_command(UiCommand::LoadModule {
+                        name: module.name.clone(),
+                        args,
+                    });
+                    window.announce(&format!("Renamed to \"{}\"", description));
+                }
+            ),
+        );
+
+        dialog.present();
+        entry.grab_focus();
+    }
+}